@@ -1,6 +1,7 @@
 use anyhow::Result;
 use clap::{Args, Parser, Subcommand};
 
+use goose::agents::SessionBudget;
 use goose::config::{Config, ExtensionConfig};
 
 use crate::commands::acp::run_acp_agent;
@@ -16,7 +17,10 @@ use crate::commands::schedule::{
     handle_schedule_run_now, handle_schedule_services_status, handle_schedule_services_stop,
     handle_schedule_sessions,
 };
-use crate::commands::session::{handle_session_list, handle_session_remove};
+use crate::commands::session::{
+    handle_session_fork, handle_session_list, handle_session_migrate, handle_session_remove,
+    handle_session_rewind, handle_session_search,
+};
 use crate::recipes::extract_from_cli::extract_recipe_info_from_cli;
 use crate::recipes::recipe::{explain_recipe, render_recipe_as_yaml};
 use crate::session;
@@ -59,6 +63,47 @@ struct Identifier {
     path: Option<PathBuf>,
 }
 
+#[derive(Args, Debug, Clone, Default)]
+struct BudgetArgs {
+    /// Maximum total tokens the session may use
+    #[arg(
+        long = "max-tokens",
+        value_name = "NUMBER",
+        help = "Stop the agent once the session has used this many tokens",
+        long_help = "Stop the agent once the session has used this many tokens in total, counting all models, subagents and summarization. Overrides GOOSE_MAX_TOKENS."
+    )]
+    max_tokens: Option<i64>,
+
+    /// Maximum estimated cost in USD the session may incur
+    #[arg(
+        long = "max-cost",
+        value_name = "USD",
+        help = "Stop the agent once the session's estimated cost reaches this many USD",
+        long_help = "Stop the agent once the session's estimated cost, based on cached model pricing, reaches this many USD. Calls to models without known pricing are not counted. Overrides GOOSE_MAX_COST_USD."
+    )]
+    max_cost: Option<f64>,
+
+    /// Maximum wall-clock time for a run
+    #[arg(
+        long = "max-duration",
+        value_name = "SECONDS",
+        help = "Stop the agent once a run has taken this many seconds",
+        long_help = "Stop the agent once a single run has taken this many seconds of wall-clock time. The limit is checked between turns, so a running tool call is allowed to finish. Overrides GOOSE_MAX_DURATION_SECS."
+    )]
+    max_duration: Option<u64>,
+}
+
+impl BudgetArgs {
+    fn into_budget(self) -> Option<SessionBudget> {
+        let budget = SessionBudget {
+            max_tokens: self.max_tokens,
+            max_cost_usd: self.max_cost,
+            max_duration_secs: self.max_duration,
+        };
+        (!budget.is_unlimited()).then_some(budget)
+    }
+}
+
 fn extract_identifier(identifier: Identifier) -> session::Identifier {
     if let Some(name) = identifier.name {
         session::Identifier::Name(name)
@@ -69,6 +114,30 @@ fn extract_identifier(identifier: Identifier) -> session::Identifier {
     }
 }
 
+fn load_output_schema(path: &std::path::Path) -> Result<goose::recipe::Response> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read output schema {}: {}", path.display(), e))?;
+    let schema: serde_json::Value = serde_json::from_str(&contents).map_err(|e| {
+        anyhow::anyhow!("Output schema {} is not valid JSON: {}", path.display(), e)
+    })?;
+    if schema.as_object().is_none_or(|obj| obj.is_empty()) {
+        anyhow::bail!(
+            "Output schema {} must be a non-empty JSON object",
+            path.display()
+        );
+    }
+    jsonschema::meta::validate(&schema).map_err(|e| {
+        anyhow::anyhow!(
+            "Output schema {} is not a valid JSON schema: {}",
+            path.display(),
+            e
+        )
+    })?;
+    Ok(goose::recipe::Response {
+        json_schema: Some(schema),
+    })
+}
+
 fn parse_key_val(s: &str) -> Result<(String, String), String> {
     match s.split_once('=') {
         Some((key, value)) => Ok((key.to_string(), value.to_string())),
@@ -97,6 +166,13 @@ enum SessionCommand {
             long_help = "Sort sessions by date in ascending order (oldest first). Default is descending order (newest first)."
         )]
         ascending: bool,
+
+        #[arg(
+            long,
+            help = "Show the estimated cost of each session",
+            long_help = "Show the estimated USD cost of each session, based on the recorded token usage per model and cached model pricing."
+        )]
+        cost: bool,
     },
     #[command(about = "Remove sessions. Runs interactively if no ID or regex is provided.")]
     Remove {
@@ -123,6 +199,71 @@ enum SessionCommand {
         )]
         output: Option<PathBuf>,
     },
+    #[command(about = "Fork a session into a new branch that ends before a given message")]
+    Fork {
+        #[command(flatten)]
+        identifier: Option<Identifier>,
+
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Index of the first message to leave out of the fork",
+            long_help = "The fork keeps messages 0 to N-1 of the session, so it continues from just before message N. Message indices are shown by `goose session search`."
+        )]
+        at: usize,
+
+        #[arg(
+            long = "new-name",
+            value_name = "NAME",
+            help = "Name for the forked session (default: a new generated id)"
+        )]
+        new_name: Option<String>,
+    },
+    #[command(about = "Restore the files and conversation of a session to the start of a turn")]
+    Rewind {
+        #[command(flatten)]
+        identifier: Option<Identifier>,
+
+        #[arg(
+            long = "to-turn",
+            value_name = "N",
+            help = "Turn to rewind to; lists the session's checkpoints if omitted",
+            long_help = "Restores the working directory to the checkpoint taken before turn N and drops turn N and everything after it from the conversation. Files ignored by .gitignore or .gooseignore are left alone."
+        )]
+        to_turn: Option<usize>,
+
+        #[arg(
+            short = 'y',
+            long = "yes",
+            help = "Rewind without asking for confirmation"
+        )]
+        yes: bool,
+    },
+    #[command(about = "Search messages and tool calls across all sessions")]
+    Search {
+        #[arg(help = "Text to search for; every word must match")]
+        query: String,
+
+        #[arg(short, long, help = "Maximum number of results", default_value = "20")]
+        limit: usize,
+
+        #[arg(
+            short,
+            long,
+            help = "Output format (text, json)",
+            default_value = "text"
+        )]
+        format: String,
+    },
+    #[command(about = "Migrate JSONL session files into the SQLite session store")]
+    Migrate {
+        #[arg(
+            long,
+            help = "Replace sessions that already exist in the database",
+            long_help = "Re-import sessions that were already migrated, replacing the stored copy. By default they are skipped."
+        )]
+        overwrite: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -142,6 +283,9 @@ enum SchedulerCommand {
             help = "Recipe source (path to file, or base64 encoded recipe string)"
         )]
         recipe_source: String,
+
+        #[command(flatten)]
+        budget: BudgetArgs,
     },
     #[command(about = "List all scheduled jobs")]
     List {},
@@ -351,6 +495,9 @@ enum Command {
         )]
         max_turns: Option<u32>,
 
+        #[command(flatten)]
+        budget: BudgetArgs,
+
         /// Add stdio extensions with environment variables and commands
         #[arg(
             long = "with-extension",
@@ -507,6 +654,9 @@ enum Command {
         )]
         max_turns: Option<u32>,
 
+        #[command(flatten)]
+        budget: BudgetArgs,
+
         /// Identifier for this run session
         #[command(flatten)]
         identifier: Option<Identifier>,
@@ -614,6 +764,15 @@ enum Command {
             long_help = "Override the GOOSE_MODEL environment variable for this run. The model must be supported by the specified provider."
         )]
         model: Option<String>,
+
+        /// JSON schema the final output must match
+        #[arg(
+            long = "output-schema",
+            value_name = "FILE",
+            help = "Path to a JSON schema file the final output must match",
+            long_help = "Require the final output to be JSON matching this schema, overriding the recipe's response schema. Uses the provider's native structured output when available."
+        )]
+        output_schema: Option<PathBuf>,
     },
 
     /// Recipe utilities for validation and deeplinking
@@ -757,6 +916,7 @@ pub async fn cli() -> Result<()> {
             debug,
             max_tool_repetitions,
             max_turns,
+            budget,
             extensions,
             remote_extensions,
             streamable_http_extensions,
@@ -767,8 +927,9 @@ pub async fn cli() -> Result<()> {
                     verbose,
                     format,
                     ascending,
+                    cost,
                 }) => {
-                    handle_session_list(verbose, format, ascending)?;
+                    handle_session_list(verbose, format, ascending, cost).await?;
                     Ok(())
                 }
                 Some(SessionCommand::Remove { id, regex }) => {
@@ -792,6 +953,58 @@ pub async fn cli() -> Result<()> {
                     crate::commands::session::handle_session_export(session_identifier, output)?;
                     Ok(())
                 }
+                Some(SessionCommand::Fork {
+                    identifier,
+                    at,
+                    new_name,
+                }) => {
+                    let session_identifier = if let Some(id) = identifier {
+                        extract_identifier(id)
+                    } else {
+                        match crate::commands::session::prompt_interactive_session_selection() {
+                            Ok(id) => id,
+                            Err(e) => {
+                                eprintln!("Error: {}", e);
+                                return Ok(());
+                            }
+                        }
+                    };
+
+                    handle_session_fork(session_identifier, at, new_name)?;
+                    Ok(())
+                }
+                Some(SessionCommand::Rewind {
+                    identifier,
+                    to_turn,
+                    yes,
+                }) => {
+                    let session_identifier = if let Some(id) = identifier {
+                        extract_identifier(id)
+                    } else {
+                        match crate::commands::session::prompt_interactive_session_selection() {
+                            Ok(id) => id,
+                            Err(e) => {
+                                eprintln!("Error: {}", e);
+                                return Ok(());
+                            }
+                        }
+                    };
+
+                    handle_session_rewind(session_identifier, to_turn, yes).await?;
+                    Ok(())
+                }
+                Some(SessionCommand::Search {
+                    query,
+                    limit,
+                    format,
+                }) => {
+                    handle_session_search(&query, limit, format)?;
+                    Ok(())
+                }
+                Some(SessionCommand::Migrate { overwrite }) => {
+                    handle_session_migrate(overwrite)?;
+                    Ok(())
+                }
                 None => {
                     let session_start = std::time::Instant::now();
                     let session_type = if resume { "resumed" } else { "new" };
@@ -820,6 +1033,7 @@ pub async fn cli() -> Result<()> {
                         debug,
                         max_tool_repetitions,
                         max_turns,
+                        budget: budget.into_budget(),
                         scheduled_job_id: None,
                         interactive: true,
                         quiet: false,
@@ -895,6 +1109,7 @@ pub async fn cli() -> Result<()> {
             debug,
             max_tool_repetitions,
             max_turns,
+            budget,
             extensions,
             remote_extensions,
             streamable_http_extensions,
@@ -907,7 +1122,16 @@ pub async fn cli() -> Result<()> {
             additional_sub_recipes,
             provider,
             model,
+            output_schema,
         }) => {
+            let output_schema = match output_schema.map(|path| load_output_schema(&path)) {
+                Some(Ok(response)) => Some(response),
+                Some(Err(err)) => {
+                    eprintln!("{}: {}", console::style("Error").red().bold(), err);
+                    std::process::exit(1);
+                }
+                None => None,
+            };
             let (input_config, recipe_info) = match (instructions, input_text, recipe) {
                 (Some(file), _, _) if file == "-" => {
                     let mut input = String::new();
@@ -1012,13 +1236,16 @@ pub async fn cli() -> Result<()> {
                 debug,
                 max_tool_repetitions,
                 max_turns,
+                budget: budget.into_budget(),
                 scheduled_job_id,
                 interactive, // Use the interactive flag from the Run command
                 quiet,
                 sub_recipes: recipe_info.as_ref().and_then(|r| r.sub_recipes.clone()),
-                final_output_response: recipe_info
-                    .as_ref()
-                    .and_then(|r| r.final_output_response.clone()),
+                final_output_response: output_schema.or_else(|| {
+                    recipe_info
+                        .as_ref()
+                        .and_then(|r| r.final_output_response.clone())
+                }),
                 retry_config: recipe_info.as_ref().and_then(|r| r.retry_config.clone()),
             })
             .await;
@@ -1089,8 +1316,9 @@ pub async fn cli() -> Result<()> {
                     id,
                     cron,
                     recipe_source,
+                    budget,
                 } => {
-                    handle_schedule_add(id, cron, recipe_source).await?;
+                    handle_schedule_add(id, cron, recipe_source, budget.into_budget()).await?;
                 }
                 SchedulerCommand::List {} => {
                     handle_schedule_list().await?;
@@ -1186,6 +1414,7 @@ pub async fn cli() -> Result<()> {
                     debug: false,
                     max_tool_repetitions: None,
                     max_turns: None,
+                    budget: None,
                     scheduled_job_id: None,
                     interactive: true, // Default case is always interactive
                     quiet: false,
@@ -69,6 +69,7 @@ impl GooseAcpAgent {
             toolshim: false,
             toolshim_model: None,
             fast_model: None,
+            reasoning: None,
         };
         let provider = create(&provider_name, model_config)?;
 
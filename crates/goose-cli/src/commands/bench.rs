@@ -49,6 +49,7 @@ pub async fn agent_generator(
         interactive: false, // Benchmarking is non-interactive
         scheduled_job_id: None,
         max_turns: None,
+        budget: None,
         quiet: false,
         sub_recipes: None,
         final_output_response: None,
@@ -16,7 +16,9 @@ use goose::config::{
     PermissionManager,
 };
 use goose::conversation::message::Message;
-use goose::model::ModelConfig;
+use goose::model::{ModelConfig, MIN_THINKING_BUDGET};
+use goose::providers::base::{Provider, SupportedModel};
+use goose::providers::ollama::OllamaProvider;
 use goose::providers::{create, providers};
 use rmcp::model::{Tool, ToolAnnotations};
 use rmcp::object;
@@ -383,6 +385,99 @@ fn interactive_model_search(models: &[String]) -> Result<String, Box<dyn Error>>
     }
 }
 
+/// Lists the models in the local Ollama with their context length and capabilities, and
+/// pulls the chosen model if it is not there yet. Returns None if listing or pulling fails.
+async fn select_ollama_model(
+    provider_meta: &goose::providers::base::ProviderMetadata,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let provider = OllamaProvider::from_env(ModelConfig::new(&provider_meta.default_model)?)?;
+
+    let spin = spinner();
+    spin.start("Fetching local Ollama models...");
+    let models = match provider.fetch_supported_model_details().await {
+        Ok(models) => models.unwrap_or_default(),
+        Err(e) => {
+            spin.stop(style("Could not reach Ollama").red());
+            cliclack::outro(style(e.to_string()).on_red().white())?;
+            return Ok(None);
+        }
+    };
+    spin.stop(style("Model fetch complete").green());
+
+    const PULL: &str = "pull_model";
+    let selection = if models.is_empty() {
+        let _ = cliclack::log::info("No models have been pulled into Ollama yet");
+        PULL.to_string()
+    } else {
+        let mut items: Vec<(String, String, String)> = models
+            .iter()
+            .map(|model| {
+                (
+                    model.name.clone(),
+                    model.name.clone(),
+                    ollama_model_hint(model),
+                )
+            })
+            .collect();
+        items.push((
+            PULL.to_string(),
+            "Pull another model...".to_string(),
+            "Download a model from the Ollama library".to_string(),
+        ));
+        cliclack::select("Select a model:")
+            .items(&items)
+            .interact()?
+    };
+    if selection != PULL {
+        return Ok(Some(selection));
+    }
+
+    let name: String = cliclack::input("Enter a model from the Ollama library:")
+        .default_input(&provider_meta.default_model)
+        .interact()?;
+
+    // Ollama reports progress per layer, so the bar tracks the sum over all layers
+    let progress = cliclack::progress_bar(0).with_download_template();
+    progress.start(format!("Pulling {}...", name));
+    let mut layers: HashMap<String, (u64, u64)> = HashMap::new();
+    let result = provider
+        .pull_model(&name, |update| {
+            if let (Some(digest), Some(total)) = (&update.digest, update.total) {
+                let layer = layers.entry(digest.clone()).or_default();
+                let completed = update.completed.unwrap_or(0);
+                progress.set_length(progress.length().unwrap_or(0) + total.saturating_sub(layer.0));
+                progress.inc(completed.saturating_sub(layer.1));
+                *layer = (total, completed.max(layer.1));
+            } else {
+                progress.set_message(&update.status);
+            }
+        })
+        .await;
+
+    match result {
+        Ok(()) => {
+            progress.stop(format!("Pulled {}", name));
+            Ok(Some(name))
+        }
+        Err(e) => {
+            progress.error(e.to_string());
+            cliclack::outro(style(format!("Failed to pull {}", name)).on_red().white())?;
+            Ok(None)
+        }
+    }
+}
+
+/// Context length and capabilities of an Ollama model, e.g. "32768 token context, tools"
+fn ollama_model_hint(model: &SupportedModel) -> String {
+    let mut hint: Vec<String> = model
+        .context_limit
+        .map(|limit| format!("{} token context", limit))
+        .into_iter()
+        .collect();
+    hint.extend(model.capabilities.iter().cloned());
+    hint.join(", ")
+}
+
 fn select_model_from_list(
     models: &[String],
     provider_meta: &goose::providers::base::ProviderMetadata,
@@ -573,30 +668,38 @@ pub async fn configure_provider_dialog() -> Result<bool, Box<dyn Error>> {
         }
     }
 
-    // Attempt to fetch supported models for this provider
-    let spin = spinner();
-    spin.start("Attempting to fetch supported models...");
-    let models_res = {
-        let temp_model_config = ModelConfig::new(&provider_meta.default_model)?;
-        let temp_provider = create(provider_name, temp_model_config)?;
-        temp_provider.fetch_supported_models().await
-    };
-    spin.stop(style("Model fetch complete").green());
-
-    // Select a model: on fetch error show styled error and abort; if Some(models), show list; if None, free-text input
-    let model: String = match models_res {
-        Err(e) => {
-            // Provider hook error
-            cliclack::outro(style(e.to_string()).on_red().white())?;
-            return Ok(false);
+    // Ollama models can be pulled on demand, so they get a picker of their own
+    let model: String = if provider_name == "ollama" {
+        match select_ollama_model(provider_meta).await? {
+            Some(model) => model,
+            None => return Ok(false),
         }
-        Ok(Some(models)) => select_model_from_list(&models, provider_meta)?,
-        Ok(None) => {
-            let default_model =
-                std::env::var("GOOSE_MODEL").unwrap_or(provider_meta.default_model.clone());
-            cliclack::input("Enter a model from that provider:")
-                .default_input(&default_model)
-                .interact()?
+    } else {
+        // Attempt to fetch supported models for this provider
+        let spin = spinner();
+        spin.start("Attempting to fetch supported models...");
+        let models_res = {
+            let temp_model_config = ModelConfig::new(&provider_meta.default_model)?;
+            let temp_provider = create(provider_name, temp_model_config)?;
+            temp_provider.fetch_supported_models().await
+        };
+        spin.stop(style("Model fetch complete").green());
+
+        // Select a model: on fetch error show styled error and abort; if Some(models), show list; if None, free-text input
+        match models_res {
+            Err(e) => {
+                // Provider hook error
+                cliclack::outro(style(e.to_string()).on_red().white())?;
+                return Ok(false);
+            }
+            Ok(Some(models)) => select_model_from_list(&models, provider_meta)?,
+            Ok(None) => {
+                let default_model =
+                    std::env::var("GOOSE_MODEL").unwrap_or(provider_meta.default_model.clone());
+                cliclack::input("Enter a model from that provider:")
+                    .default_input(&default_model)
+                    .interact()?
+            }
         }
     };
 
@@ -1224,6 +1327,11 @@ pub async fn configure_settings_dialog() -> Result<(), Box<dyn Error>> {
             "Max Turns",
             "Set maximum number of turns without user input",
         )
+        .item(
+            "reasoning",
+            "Reasoning",
+            "Set how much the model thinks before answering",
+        )
         .item(
             "experiment",
             "Toggle Experiment",
@@ -1257,6 +1365,9 @@ pub async fn configure_settings_dialog() -> Result<(), Box<dyn Error>> {
         "max_turns" => {
             configure_max_turns_dialog()?;
         }
+        "reasoning" => {
+            configure_reasoning_dialog()?;
+        }
         "experiment" => {
             toggle_experiments_dialog()?;
         }
@@ -1692,6 +1803,60 @@ pub fn configure_max_turns_dialog() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+pub fn configure_reasoning_dialog() -> Result<(), Box<dyn Error>> {
+    let config = Config::global();
+
+    for key in ["GOOSE_REASONING_EFFORT", "GOOSE_THINKING_BUDGET"] {
+        if std::env::var(key).is_ok() {
+            let _ = cliclack::log::info(format!(
+                "Notice: {} environment variable is set and will override the configuration here.",
+                key
+            ));
+        }
+    }
+
+    let choice = cliclack::select("How much should the model reason before answering?")
+        .item("off", "Model default", "Don't request extended thinking")
+        .item("low", "Low", "Short reasoning, faster and cheaper")
+        .item("medium", "Medium", "Balanced reasoning")
+        .item("high", "High", "Thorough reasoning for hard problems")
+        .item(
+            "budget",
+            "Custom budget",
+            "Set the thinking budget in tokens",
+        )
+        .interact()?;
+
+    match choice {
+        "off" => {
+            config.delete("GOOSE_REASONING_EFFORT")?;
+            config.delete("GOOSE_THINKING_BUDGET")?;
+            cliclack::outro("Reasoning set to the model default")?;
+        }
+        "budget" => {
+            let budget_input: String = cliclack::input("Thinking budget in tokens:")
+                .placeholder("16000")
+                .validate(|input: &String| match input.parse::<u32>() {
+                    Ok(value) if value >= MIN_THINKING_BUDGET => Ok(()),
+                    Ok(_) => Err(format!("Budget must be at least {}", MIN_THINKING_BUDGET)),
+                    Err(_) => Err("Please enter a valid number".to_string()),
+                })
+                .interact()?;
+            let budget: u32 = budget_input.parse()?;
+            config.delete("GOOSE_REASONING_EFFORT")?;
+            config.set_param("GOOSE_THINKING_BUDGET", Value::from(budget))?;
+            cliclack::outro(format!("Set thinking budget to {} tokens", budget))?;
+        }
+        effort => {
+            config.delete("GOOSE_THINKING_BUDGET")?;
+            config.set_param("GOOSE_REASONING_EFFORT", Value::from(effort))?;
+            cliclack::outro(format!("Set reasoning effort to {}", effort))?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Handle OpenRouter authentication
 pub async fn handle_openrouter_auth() -> Result<(), Box<dyn Error>> {
     use goose::config::{configure_openrouter, signup_openrouter::OpenRouterAuth};
@@ -1,7 +1,9 @@
 use crate::session::message_to_markdown;
 use anyhow::{Context, Result};
 use cliclack::{confirm, multiselect, select};
+use goose::providers::pricing::initialize_pricing_cache;
 use goose::session::info::{get_valid_sorted_sessions, SessionInfo, SortOrder};
+use goose::session::sqlite_store::SqliteSessionStore;
 use goose::session::{self, Identifier};
 use goose::utils::safe_truncate;
 use regex::Regex;
@@ -22,7 +24,7 @@ pub fn remove_sessions(sessions: Vec<SessionInfo>) -> Result<()> {
 
     if should_delete {
         for session in sessions {
-            fs::remove_file(session.path.clone())
+            session::delete_session(Path::new(&session.path))
                 .with_context(|| format!("Failed to remove session file '{}'", session.path))?;
             println!("Session `{}` removed.", session.id);
         }
@@ -115,7 +117,12 @@ pub fn handle_session_remove(id: Option<String>, regex_string: Option<String>) -
     remove_sessions(matched_sessions)
 }
 
-pub fn handle_session_list(verbose: bool, format: String, ascending: bool) -> Result<()> {
+pub async fn handle_session_list(
+    verbose: bool,
+    format: String,
+    ascending: bool,
+    show_cost: bool,
+) -> Result<()> {
     let sort_order = if ascending {
         SortOrder::Ascending
     } else {
@@ -130,9 +137,29 @@ pub fn handle_session_list(verbose: bool, format: String, ascending: bool) -> Re
         }
     };
 
+    let mut costs = Vec::new();
+    if show_cost {
+        if let Err(e) = initialize_pricing_cache().await {
+            tracing::warn!("Failed to initialize pricing cache: {e}. Costs may be unavailable.");
+        }
+        for session in &sessions {
+            costs.push(session::session_cost_usd(&session.metadata).await);
+        }
+    }
+
     match format.as_str() {
         "json" => {
-            println!("{}", serde_json::to_string(&sessions)?);
+            if show_cost {
+                let mut values = Vec::new();
+                for (session, cost) in sessions.iter().zip(&costs) {
+                    let mut value = serde_json::to_value(session)?;
+                    value["cost_usd"] = serde_json::json!(cost);
+                    values.push(value);
+                }
+                println!("{}", serde_json::to_string(&values)?);
+            } else {
+                println!("{}", serde_json::to_string(&sessions)?);
+            }
         }
         _ => {
             if sessions.is_empty() {
@@ -140,32 +167,196 @@ pub fn handle_session_list(verbose: bool, format: String, ascending: bool) -> Re
                 return Ok(());
             } else {
                 println!("Available sessions:");
-                for SessionInfo {
-                    id,
-                    path,
-                    metadata,
-                    modified,
-                } in sessions
+                for (
+                    index,
+                    SessionInfo {
+                        id,
+                        path,
+                        metadata,
+                        modified,
+                    },
+                ) in sessions.iter().enumerate()
                 {
                     let description = if metadata.description.is_empty() {
                         "(none)"
                     } else {
                         &metadata.description
                     };
-                    let output = format!("{} - {} - {}", id, description, modified);
+                    let mut output = format!("{} - {} - {}", id, description, modified);
+                    if show_cost {
+                        output.push_str(&format!(" - {}", format_cost(costs[index])));
+                    }
                     if verbose {
                         println!("  {}", output);
                         println!("    Path: {}", path);
+                        if let (Some(parent), Some(at)) =
+                            (&metadata.parent_session_id, metadata.fork_point)
+                        {
+                            println!("    Forked from: {} at message {}", parent, at);
+                        }
+                        if show_cost {
+                            for usage in &metadata.model_usage {
+                                println!(
+                                    "    {} ({}): {} - {} calls, {} tokens",
+                                    usage.model,
+                                    usage.role,
+                                    format_cost(usage.cost_or_estimate().await),
+                                    usage.calls,
+                                    usage.total_tokens()
+                                );
+                            }
+                        }
                     } else {
                         println!("{}", output);
                     }
                 }
+                if show_cost {
+                    let total: f64 = costs.iter().flatten().sum();
+                    println!("Total estimated cost: {}", format_cost(Some(total)));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn format_cost(cost: Option<f64>) -> String {
+    match cost {
+        Some(cost) => format!("${:.4}", cost),
+        None => "cost unknown".to_string(),
+    }
+}
+
+pub fn handle_session_fork(
+    identifier: Identifier,
+    at: usize,
+    new_name: Option<String>,
+) -> Result<()> {
+    let source_path = session::get_path(identifier)
+        .map_err(|e| anyhow::anyhow!("Invalid session identifier: {}", e))?;
+    let target = new_name.map(Identifier::Name);
+
+    let fork_path = session::fork_session(&source_path, at, target)?;
+    let fork_id = fork_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    println!(
+        "Forked session `{}` at message {} into `{}`.",
+        source_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default(),
+        at,
+        fork_id
+    );
+    println!(
+        "Continue the branch with: goose session --resume --id {}",
+        fork_id
+    );
+    Ok(())
+}
+
+pub async fn handle_session_rewind(
+    identifier: Identifier,
+    to_turn: Option<usize>,
+    skip_confirmation: bool,
+) -> Result<()> {
+    let session_file = session::get_path(identifier)
+        .map_err(|e| anyhow::anyhow!("Invalid session identifier: {}", e))?;
+    if !session::session_exists(&session_file) {
+        return Err(anyhow::anyhow!("Session not found"));
+    }
+
+    match to_turn {
+        None => {
+            let metadata = session::read_metadata(&session_file)?;
+            crate::session::render_checkpoints(&metadata.checkpoints);
+        }
+        Some(turn) => {
+            if !skip_confirmation {
+                let prompt = format!(
+                    "Restore the files and conversation to the start of turn {}? Later changes will be lost.",
+                    turn
+                );
+                let confirmed = match confirm(prompt).initial_value(false).interact() {
+                    Ok(choice) => choice,
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => false,
+                    Err(e) => return Err(e.into()),
+                };
+                if !confirmed {
+                    println!("{}", console::style("Rewind cancelled.").yellow());
+                    return Ok(());
+                }
             }
+            let outcome = session::rewind_to_turn(&session_file, turn).await?;
+            crate::session::render_rewind(&outcome);
         }
     }
     Ok(())
 }
 
+pub fn handle_session_search(query: &str, limit: usize, format: String) -> Result<()> {
+    let hits = session::search_sessions(query, limit)?;
+
+    match format.as_str() {
+        "json" => {
+            println!("{}", serde_json::to_string(&hits)?);
+        }
+        _ => {
+            if hits.is_empty() {
+                println!("No matching sessions found");
+                return Ok(());
+            }
+            for hit in hits {
+                let description = if hit.description.is_empty() {
+                    "(none)"
+                } else {
+                    &hit.description
+                };
+                println!(
+                    "{} [message {}] - {}",
+                    hit.session_id,
+                    hit.message_index,
+                    safe_truncate(description, TRUNCATED_DESC_LENGTH)
+                );
+                println!("    {}", hit.snippet);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Copy all JSONL session files into the SQLite session store
+pub fn handle_session_migrate(overwrite: bool) -> Result<()> {
+    let session_dir = session::ensure_session_dir()?;
+    let db_path = session_dir.join(session::store::SQLITE_DB_FILE_NAME);
+    let store = SqliteSessionStore::open(&db_path)?;
+
+    let report = session::migrate_jsonl_sessions(&session_dir, &store, overwrite)?;
+
+    println!(
+        "Migrated {} session(s) into {}",
+        report.migrated.len(),
+        db_path.display()
+    );
+    if !report.skipped.is_empty() {
+        println!(
+            "Skipped {} session(s) already in the database (use --overwrite to replace them)",
+            report.skipped.len()
+        );
+    }
+    for (id, error) in &report.failed {
+        eprintln!("Failed to migrate session `{}`: {}", id, error);
+    }
+    println!(
+        "Set {}: sqlite in your config to start using the database. The JSONL files were left in place.",
+        session::store::SESSION_STORE_CONFIG_KEY
+    );
+    Ok(())
+}
+
 /// Export a session to Markdown without creating a full Session object
 ///
 /// This function directly reads messages from the session file and converts them to Markdown
@@ -179,7 +370,7 @@ pub fn handle_session_export(identifier: Identifier, output_path: Option<PathBuf
         }
     };
 
-    if !session_file_path.exists() {
+    if !goose::session::session_exists(&session_file_path) {
         return Err(anyhow::anyhow!(
             "Session file not found (expected path: {})",
             session_file_path.display()
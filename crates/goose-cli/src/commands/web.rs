@@ -492,6 +492,7 @@ async fn process_message_streaming(
         execution_mode: None,
         max_turns: None,
         retry_config: None,
+        budget: None,
     };
 
     match agent
@@ -666,6 +667,28 @@ async fn process_message_streaming(
                         // Log model change
                         tracing::info!("Model changed to {} in {} mode", model, mode);
                     }
+                    Ok(AgentEvent::ModelFallback(fallback)) => {
+                        tracing::warn!(
+                            "Switched from {}/{} to {}/{}: {}",
+                            fallback.from_provider,
+                            fallback.from_model,
+                            fallback.to_provider,
+                            fallback.to_model,
+                            fallback.reason
+                        );
+                    }
+                    Ok(AgentEvent::BudgetExceeded(exceeded)) => {
+                        let mut sender = sender.lock().await;
+                        let _ = sender
+                            .send(Message::Text(
+                                serde_json::to_string(&WebSocketMessage::Error {
+                                    message: exceeded.to_string(),
+                                })
+                                .unwrap()
+                                .into(),
+                            ))
+                            .await;
+                    }
 
                     Err(e) => {
                         error!("Error in message stream: {}", e);
@@ -1,7 +1,8 @@
 use console::style;
 use goose::agents::types::RetryConfig;
-use goose::agents::Agent;
+use goose::agents::{Agent, SessionBudget};
 use goose::config::{Config, ExtensionConfig, ExtensionConfigManager};
+use goose::model::{ReasoningConfig, ReasoningEffort};
 use goose::providers::create;
 use goose::recipe::{Response, SubRecipe};
 use goose::session;
@@ -51,6 +52,8 @@ pub struct SessionBuilderConfig {
     pub max_tool_repetitions: Option<u32>,
     /// Maximum number of turns (iterations) allowed without user input
     pub max_turns: Option<u32>,
+    /// Token, cost and time limits for the session
+    pub budget: Option<SessionBudget>,
     /// ID of the scheduled job that triggered this session (if any)
     pub scheduled_job_id: Option<String>,
     /// Whether this session will be used interactively (affects debugging prompts)
@@ -172,6 +175,8 @@ pub struct SessionSettings {
     pub goose_model: Option<String>,
     pub goose_provider: Option<String>,
     pub temperature: Option<f32>,
+    pub reasoning_effort: Option<ReasoningEffort>,
+    pub thinking_budget: Option<u32>,
 }
 
 pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
@@ -209,6 +214,24 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         })
         .with_temperature(temperature);
 
+    // Reasoning from recipe settings replaces the configured reasoning
+    let reasoning_effort = session_config
+        .settings
+        .as_ref()
+        .and_then(|s| s.reasoning_effort);
+    let thinking_budget = session_config
+        .settings
+        .as_ref()
+        .and_then(|s| s.thinking_budget);
+    let model_config = if reasoning_effort.is_some() || thinking_budget.is_some() {
+        model_config.with_reasoning(Some(ReasoningConfig {
+            effort: reasoning_effort,
+            budget_tokens: thinking_budget,
+        }))
+    } else {
+        model_config
+    };
+
     // Create the agent
     let agent: Agent = Agent::new();
 
@@ -268,7 +291,7 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
                 }
                 Ok(path) => path,
             };
-            if !session_file.exists() {
+            if !session::session_exists(&session_file) {
                 output::render_error(&format!(
                     "Cannot resume session {} - no such session exists",
                     style(session_file.display()).cyan()
@@ -426,6 +449,10 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         edit_mode,
         session_config.retry_config.clone(),
     );
+    if let Some(budget) = session_config.budget.clone() {
+        session.set_budget(budget);
+    }
+    session.set_provider_name(provider_name.clone());
 
     // Add extensions if provided
     for extension_str in session_config.extensions {
@@ -615,6 +642,7 @@ mod tests {
             debug: true,
             max_tool_repetitions: Some(5),
             max_turns: None,
+            budget: None,
             scheduled_job_id: None,
             interactive: true,
             quiet: false,
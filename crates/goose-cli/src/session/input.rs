@@ -1,5 +1,6 @@
 use super::completion::GooseCompleter;
 use anyhow::Result;
+use goose::model::{ReasoningConfig, ReasoningEffort, MIN_THINKING_BUDGET};
 use rustyline::Editor;
 use shlex;
 use std::collections::HashMap;
@@ -21,6 +22,21 @@ pub enum InputResult {
     Clear,
     Recipe(Option<String>),
     Summarize,
+    Rewind(Option<usize>),
+    Think(ThinkCommand),
+    Context,
+    Pin(Option<usize>),
+    Unpin(usize),
+    Drop(usize),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ThinkCommand {
+    /// Show the current reasoning setting
+    Show,
+    /// Go back to the model's default reasoning
+    Off,
+    Set(ReasoningConfig),
 }
 
 #[derive(Debug)]
@@ -121,6 +137,12 @@ fn handle_slash_command(input: &str) -> Option<InputResult> {
     const CMD_CLEAR: &str = "/clear";
     const CMD_RECIPE: &str = "/recipe";
     const CMD_SUMMARIZE: &str = "/summarize";
+    const CMD_REWIND: &str = "/rewind";
+    const CMD_THINK: &str = "/think";
+    const CMD_CONTEXT: &str = "/context";
+    const CMD_PIN: &str = "/pin";
+    const CMD_UNPIN: &str = "/unpin";
+    const CMD_DROP: &str = "/drop";
 
     match input {
         "/exit" | "/quit" => Some(InputResult::Exit),
@@ -181,6 +203,23 @@ fn handle_slash_command(input: &str) -> Option<InputResult> {
         s if s == CMD_CLEAR => Some(InputResult::Clear),
         s if s.starts_with(CMD_RECIPE) => parse_recipe_command(s),
         s if s == CMD_SUMMARIZE => Some(InputResult::Summarize),
+        s if s == CMD_REWIND => Some(InputResult::Rewind(None)),
+        s if s.starts_with("/rewind ") => parse_rewind_command(s[CMD_REWIND.len()..].trim()),
+        s if s == CMD_THINK => Some(InputResult::Think(ThinkCommand::Show)),
+        s if s.starts_with("/think ") => parse_think_command(s[CMD_THINK.len()..].trim()),
+        s if s == CMD_CONTEXT => Some(InputResult::Context),
+        s if s == CMD_PIN => Some(InputResult::Pin(None)),
+        s if s.starts_with("/pin ") => {
+            parse_message_command(CMD_PIN, s[CMD_PIN.len()..].trim(), |index| {
+                InputResult::Pin(Some(index))
+            })
+        }
+        s if s == CMD_UNPIN || s.starts_with("/unpin ") => {
+            parse_message_command(CMD_UNPIN, s[CMD_UNPIN.len()..].trim(), InputResult::Unpin)
+        }
+        s if s == CMD_DROP || s.starts_with("/drop ") => {
+            parse_message_command(CMD_DROP, s[CMD_DROP.len()..].trim(), InputResult::Drop)
+        }
         _ => None,
     }
 }
@@ -210,6 +249,70 @@ fn parse_recipe_command(s: &str) -> Option<InputResult> {
     Some(InputResult::Recipe(Some(filepath.to_string())))
 }
 
+fn parse_rewind_command(turn: &str) -> Option<InputResult> {
+    match turn.parse::<usize>() {
+        Ok(turn) => Some(InputResult::Rewind(Some(turn))),
+        Err(_) => {
+            println!(
+                "{}",
+                console::style("Usage: /rewind [turn], where turn is a number").red()
+            );
+            Some(InputResult::Retry)
+        }
+    }
+}
+
+fn parse_message_command(
+    command: &str,
+    index: &str,
+    result: impl FnOnce(usize) -> InputResult,
+) -> Option<InputResult> {
+    match index.parse::<usize>() {
+        Ok(index) => Some(result(index)),
+        Err(_) => {
+            println!(
+                "{}",
+                console::style(format!(
+                    "Usage: {} <message>, where message is a number listed by /pin",
+                    command
+                ))
+                .red()
+            );
+            Some(InputResult::Retry)
+        }
+    }
+}
+
+fn parse_think_command(arg: &str) -> Option<InputResult> {
+    let arg = arg.to_lowercase();
+    let budget = arg.strip_suffix("tokens").unwrap_or(&arg).trim();
+    if arg == "off" {
+        Some(InputResult::Think(ThinkCommand::Off))
+    } else if let Ok(effort) = arg.parse::<ReasoningEffort>() {
+        Some(InputResult::Think(ThinkCommand::Set(
+            ReasoningConfig::from_effort(effort),
+        )))
+    } else if let Some(budget) = budget
+        .parse::<u32>()
+        .ok()
+        .filter(|b| *b >= MIN_THINKING_BUDGET)
+    {
+        Some(InputResult::Think(ThinkCommand::Set(
+            ReasoningConfig::from_budget_tokens(budget),
+        )))
+    } else {
+        println!(
+            "{}",
+            console::style(format!(
+                "Usage: /think [low|medium|high|off|<tokens>], where tokens is at least {}",
+                MIN_THINKING_BUDGET
+            ))
+            .red()
+        );
+        Some(InputResult::Retry)
+    }
+}
+
 fn parse_prompts_command(args: &str) -> Option<InputResult> {
     let parts: Vec<String> = shlex::split(args).unwrap_or_default();
 
@@ -291,6 +394,12 @@ fn print_help() {
 /recipe [filepath] - Generate a recipe from the current conversation and save it to the specified filepath (must end with .yaml).
                        If no filepath is provided, it will be saved to ./recipe.yaml.
 /summarize - Summarize the current conversation to reduce context length while preserving key information.
+/rewind [turn] - List the checkpoints of this session, or restore the files and conversation to the start of a turn.
+/think [setting] - Show the reasoning setting, or set it to low, medium, high, off or a thinking budget in tokens.
+/context - Show what is using the context window: the system prompt, extensions, hints, tools and messages.
+/pin [message] - List the messages of this session, or pin one so that compaction always keeps it in the context.
+/unpin <message> - Unpin a message, so that compaction can summarize or remove it again.
+/drop <message> - Remove a message from the context, keeping it in the session history.
 /? or /help - Display this help message
 /clear - Clears the current chat history
 
@@ -536,4 +645,90 @@ mod tests {
         let result = handle_slash_command("  /summarize  ");
         assert!(matches!(result, Some(InputResult::Summarize)));
     }
+
+    #[test]
+    fn test_rewind_command() {
+        assert!(matches!(
+            handle_slash_command("/rewind"),
+            Some(InputResult::Rewind(None))
+        ));
+        assert!(matches!(
+            handle_slash_command("/rewind 3"),
+            Some(InputResult::Rewind(Some(3)))
+        ));
+        assert!(matches!(
+            handle_slash_command("/rewind three"),
+            Some(InputResult::Retry)
+        ));
+    }
+
+    #[test]
+    fn test_context_command() {
+        assert!(matches!(
+            handle_slash_command("/context"),
+            Some(InputResult::Context)
+        ));
+        assert!(handle_slash_command("/contextual").is_none());
+    }
+
+    #[test]
+    fn test_pin_commands() {
+        assert!(matches!(
+            handle_slash_command("/pin"),
+            Some(InputResult::Pin(None))
+        ));
+        assert!(matches!(
+            handle_slash_command("/pin 4"),
+            Some(InputResult::Pin(Some(4)))
+        ));
+        assert!(matches!(
+            handle_slash_command("/unpin 4"),
+            Some(InputResult::Unpin(4))
+        ));
+        assert!(matches!(
+            handle_slash_command("/drop 7"),
+            Some(InputResult::Drop(7))
+        ));
+        assert!(matches!(
+            handle_slash_command("/drop"),
+            Some(InputResult::Retry)
+        ));
+        assert!(matches!(
+            handle_slash_command("/unpin last"),
+            Some(InputResult::Retry)
+        ));
+        assert!(handle_slash_command("/dropped").is_none());
+    }
+
+    #[test]
+    fn test_think_command() {
+        assert!(matches!(
+            handle_slash_command("/think"),
+            Some(InputResult::Think(ThinkCommand::Show))
+        ));
+        assert!(matches!(
+            handle_slash_command("/think off"),
+            Some(InputResult::Think(ThinkCommand::Off))
+        ));
+        match handle_slash_command("/think High") {
+            Some(InputResult::Think(ThinkCommand::Set(config))) => {
+                assert_eq!(config, ReasoningConfig::from_effort(ReasoningEffort::High))
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match handle_slash_command("/think 8000 tokens") {
+            Some(InputResult::Think(ThinkCommand::Set(config))) => {
+                assert_eq!(config, ReasoningConfig::from_budget_tokens(8000))
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(matches!(
+            handle_slash_command("/think 10"),
+            Some(InputResult::Retry)
+        ));
+        assert!(matches!(
+            handle_slash_command("/think hard"),
+            Some(InputResult::Retry)
+        ));
+    }
 }
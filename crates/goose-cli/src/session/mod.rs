@@ -14,6 +14,7 @@ use goose::conversation::Conversation;
 use std::io::Write;
 
 pub use self::export::message_to_markdown;
+pub use self::output::{render_checkpoints, render_rewind};
 pub use builder::{build_session, SessionBuilderConfig, SessionSettings};
 use console::Color;
 use goose::agents::AgentEvent;
@@ -29,7 +30,7 @@ use completion::GooseCompleter;
 use etcetera::{choose_app_strategy, AppStrategy};
 use goose::agents::extension::{Envs, ExtensionConfig};
 use goose::agents::types::RetryConfig;
-use goose::agents::{Agent, SessionConfig};
+use goose::agents::{Agent, SessionBudget, SessionConfig};
 use goose::config::Config;
 use goose::providers::pricing::initialize_pricing_cache;
 use goose::session;
@@ -66,6 +67,8 @@ pub struct Session {
     max_turns: Option<u32>,
     edit_mode: Option<EditMode>,
     retry_config: Option<RetryConfig>,
+    budget: Option<SessionBudget>,
+    provider_name: Option<String>,
 }
 
 // Cache structure for completion data
@@ -152,9 +155,21 @@ impl Session {
             max_turns,
             edit_mode,
             retry_config,
+            budget: None,
+            provider_name: None,
         }
     }
 
+    /// Limit the tokens, cost and time the agent may spend in this session
+    pub fn set_budget(&mut self, budget: SessionBudget) {
+        self.budget = Some(budget);
+    }
+
+    /// Name of the provider the agent was created with, needed to recreate it with new settings
+    pub fn set_provider_name(&mut self, provider_name: String) {
+        self.provider_name = Some(provider_name);
+    }
+
     /// Helper function to summarize context messages
     async fn summarize_context_messages(
         messages: &mut Conversation,
@@ -656,9 +671,12 @@ impl Session {
                         &Message::assistant().with_text("Chat context cleared."),
                         self.debug,
                     );
-                    if let Some(file) = self.session_file.as_ref().filter(|f| f.exists()) {
-                        std::fs::remove_file(file)?;
-                        std::fs::File::create(file)?;
+                    if let Some(file) = self
+                        .session_file
+                        .as_ref()
+                        .filter(|f| session::session_exists(f))
+                    {
+                        session::delete_session(file)?;
                     }
                     continue;
                 }
@@ -778,6 +796,12 @@ impl Session {
                                     metadata.accumulated_output_tokens,
                                     usage.usage.output_tokens,
                                 );
+                                session::usage::add_usage(
+                                    &mut metadata,
+                                    session::UsageRole::Summarization,
+                                    &usage,
+                                )
+                                .await;
 
                                 session::storage::update_metadata(&session_file_path, &metadata)
                                     .await?;
@@ -802,6 +826,46 @@ impl Session {
 
                     continue;
                 }
+                InputResult::Rewind(turn) => {
+                    save_history(&mut editor);
+                    self.handle_rewind(turn).await?;
+                    continue;
+                }
+                InputResult::Think(command) => {
+                    save_history(&mut editor);
+                    if let Err(e) = self.handle_think(command).await {
+                        output::render_error(&e.to_string());
+                    }
+                    continue;
+                }
+                InputResult::Context => {
+                    save_history(&mut editor);
+                    match self.agent.context_usage(self.messages.messages()).await {
+                        Ok(usage) => output::render_context_usage(&usage),
+                        Err(e) => output::render_error(&e.to_string()),
+                    }
+                    continue;
+                }
+                InputResult::Pin(None) => {
+                    save_history(&mut editor);
+                    output::render_pinnable_messages(self.messages.messages());
+                    continue;
+                }
+                InputResult::Pin(Some(index)) => {
+                    save_history(&mut editor);
+                    self.handle_pin(index, true).await?;
+                    continue;
+                }
+                InputResult::Unpin(index) => {
+                    save_history(&mut editor);
+                    self.handle_pin(index, false).await?;
+                    continue;
+                }
+                InputResult::Drop(index) => {
+                    save_history(&mut editor);
+                    self.handle_drop(index).await?;
+                    continue;
+                }
             }
         }
 
@@ -919,6 +983,7 @@ impl Session {
                 execution_mode: None,
                 max_turns: self.max_turns,
                 retry_config: self.retry_config.clone(),
+                budget: self.budget.clone(),
             }
         });
         let mut stream = self
@@ -1277,6 +1342,15 @@ impl Session {
                                 }
                             }
                         }
+                        Some(Ok(AgentEvent::BudgetExceeded(exceeded))) => {
+                            output::render_error(&format!(
+                                "{}.\nThe agent was stopped to stay within the session budget.",
+                                exceeded
+                            ));
+                        }
+                        Some(Ok(AgentEvent::ModelFallback(fallback))) => {
+                            output::render_model_fallback(&fallback);
+                        }
                         Some(Ok(AgentEvent::ModelChange { model, mode })) => {
                             // Log model change if in debug mode
                             if self.debug {
@@ -1529,7 +1603,11 @@ impl Session {
     }
 
     pub fn get_metadata(&self) -> Result<session::SessionMetadata> {
-        if !self.session_file.as_ref().is_some_and(|f| f.exists()) {
+        if !self
+            .session_file
+            .as_ref()
+            .is_some_and(|f| session::session_exists(f))
+        {
             return Err(anyhow::anyhow!("Session file does not exist"));
         }
 
@@ -1576,7 +1654,10 @@ impl Session {
 
                 output::display_context_usage(total_tokens, context_limit);
 
-                if show_cost {
+                if show_cost && !metadata.model_usage.is_empty() {
+                    output::display_session_cost(&metadata.model_usage).await;
+                } else if show_cost {
+                    // Sessions recorded before per-model usage tracking only know the last call
                     let input_tokens = metadata.input_tokens.unwrap_or(0) as usize;
                     let output_tokens = metadata.output_tokens.unwrap_or(0) as usize;
                     output::display_cost_usage(
@@ -1596,6 +1677,130 @@ impl Session {
         Ok(())
     }
 
+    /// List the checkpoints of the session, or rewind files and conversation to a turn
+    async fn handle_rewind(&mut self, turn: Option<usize>) -> Result<()> {
+        let Some(session_file) = self.session_file.clone() else {
+            output::render_error("This session is not recorded, so it has no checkpoints");
+            return Ok(());
+        };
+        let Some(turn) = turn else {
+            let metadata = session::read_metadata(&session_file)?;
+            output::render_checkpoints(&metadata.checkpoints);
+            return Ok(());
+        };
+
+        let prompt = format!(
+            "Restore the files and conversation to the start of turn {}? Later changes will be lost.",
+            turn
+        );
+        let confirmed = match cliclack::confirm(prompt).initial_value(false).interact() {
+            Ok(choice) => choice,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => false,
+            Err(e) => return Err(e.into()),
+        };
+        if !confirmed {
+            println!("{}", console::style("Rewind cancelled.").yellow());
+            return Ok(());
+        }
+
+        match session::rewind_to_turn(&session_file, turn).await {
+            Ok(outcome) => {
+                self.messages = outcome.conversation.clone();
+                output::render_rewind(&outcome);
+            }
+            Err(e) => output::render_error(&e.to_string()),
+        }
+        Ok(())
+    }
+
+    /// Pin or unpin a message, so that compaction keeps it in the context or may remove it again
+    async fn handle_pin(&mut self, index: usize, pinned: bool) -> Result<()> {
+        if !self.messages.set_pinned(index, pinned) {
+            output::render_error(&format!(
+                "There is no message {} in the context, use /pin to list the messages",
+                index
+            ));
+            return Ok(());
+        }
+        self.persist_edited_messages().await?;
+
+        let action = if pinned { "Pinned" } else { "Unpinned" };
+        println!(
+            "{}",
+            console::style(format!("{} message {}.", action, index)).green()
+        );
+        Ok(())
+    }
+
+    /// Remove a message and the other half of its tool calls from the context
+    async fn handle_drop(&mut self, index: usize) -> Result<()> {
+        let dropped = self.messages.drop_message(index);
+        if dropped.is_empty() {
+            output::render_error(&format!(
+                "There is no message {} in the context, use /pin to list the messages",
+                index
+            ));
+            return Ok(());
+        }
+        self.persist_edited_messages().await?;
+
+        let noun = if dropped.len() > 1 {
+            "messages"
+        } else {
+            "message"
+        };
+        let dropped: Vec<String> = dropped.iter().map(|i| i.to_string()).collect();
+        println!(
+            "{}",
+            console::style(format!(
+                "Dropped {} {} from the context.",
+                noun,
+                dropped.join(", ")
+            ))
+            .green()
+        );
+        Ok(())
+    }
+
+    async fn persist_edited_messages(&self) -> Result<()> {
+        if let Some(session_file) = &self.session_file {
+            session::persist_messages_with_schedule_id(
+                session_file,
+                &self.messages,
+                None,
+                self.scheduled_job_id.clone(),
+                std::env::current_dir().ok(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Show or change the reasoning setting of the current model
+    async fn handle_think(&mut self, command: input::ThinkCommand) -> Result<()> {
+        let provider = self.agent.provider().await?;
+        let model_config = provider.get_model_config();
+        let reasoning = match command {
+            input::ThinkCommand::Show => {
+                output::render_reasoning(&model_config.model_name, model_config.reasoning);
+                return Ok(());
+            }
+            input::ThinkCommand::Off => None,
+            input::ThinkCommand::Set(reasoning) => Some(reasoning),
+        };
+
+        let provider_name = match &self.provider_name {
+            Some(name) => name.clone(),
+            None => Config::global().get_param::<String>("GOOSE_PROVIDER")?,
+        };
+        let model_config = model_config.with_reasoning(reasoning);
+        let model_name = model_config.model_name.clone();
+        let new_provider = goose::providers::create(&provider_name, model_config)?;
+        self.agent.update_provider(new_provider).await?;
+        output::render_reasoning(&model_name, reasoning);
+        Ok(())
+    }
+
     /// Handle prompt command execution
     async fn handle_prompt_command(&mut self, opts: input::PromptCommandOptions) -> Result<()> {
         // name is required
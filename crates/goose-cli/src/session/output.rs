@@ -2,13 +2,15 @@ use anstream::println;
 use bat::WrappingMode;
 use console::{measure_text_width, style, Color, Term};
 use goose::config::Config;
+use goose::context_mgmt::usage::ContextUsage;
 use goose::conversation::message::{Message, MessageContent, ToolRequest, ToolResponse};
-use goose::providers::pricing::get_model_pricing;
-use goose::providers::pricing::parse_model_id;
+use goose::model::ReasoningConfig;
+use goose::providers::fallback::ModelFallback;
+use goose::providers::pricing::estimate_cost_usd;
+use goose::session::{Checkpoint, ModelUsage, RewindOutcome};
 use goose::utils::safe_truncate;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use mcp_core::tool::ToolCall;
-use regex::Regex;
 use rmcp::model::PromptArgument;
 use serde_json::Value;
 use std::cell::RefCell;
@@ -174,7 +176,8 @@ pub fn render_message(message: &Message, debug: bool) {
                 println!("Image: [data: {}, type: {}]", image.data, image.mime_type);
             }
             MessageContent::Thinking(thinking) => {
-                if std::env::var("GOOSE_CLI_SHOW_THINKING").is_ok()
+                if !thinking.thinking.is_empty()
+                    && std::env::var("GOOSE_CLI_SHOW_THINKING").is_ok()
                     && std::io::stdout().is_terminal()
                 {
                     println!("\n{}", style("Thinking:").dim().italic());
@@ -299,6 +302,72 @@ pub fn render_error(message: &str) {
     println!("\n  {} {}\n", style("error:").red().bold(), message);
 }
 
+pub fn render_checkpoints(checkpoints: &[Checkpoint]) {
+    if checkpoints.is_empty() {
+        println!("No checkpoints in this session yet");
+        return;
+    }
+    println!();
+    for checkpoint in checkpoints {
+        let created = chrono::DateTime::from_timestamp(checkpoint.created_at, 0)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        println!(
+            "  {} {} {}",
+            style(format!("turn {:>3}", checkpoint.turn)).cyan(),
+            style(created).dim(),
+            checkpoint.prompt
+        );
+    }
+    println!();
+}
+
+pub fn render_rewind(outcome: &RewindOutcome) {
+    println!();
+    println!(
+        "  {} to the start of turn {} ({} messages kept)",
+        style("rewound").green(),
+        style(outcome.checkpoint.turn).cyan(),
+        outcome.conversation.len()
+    );
+    for path in &outcome.restored {
+        println!("    {} {}", style("restored").dim(), path.display());
+    }
+    for path in &outcome.removed {
+        println!("    {} {}", style("removed").dim(), path.display());
+    }
+    println!();
+}
+
+pub fn render_model_fallback(fallback: &ModelFallback) {
+    println!(
+        "\n  {} {}/{} to {}/{}: {}",
+        style("switched from").yellow(),
+        fallback.from_provider,
+        fallback.from_model,
+        style(&fallback.to_provider).cyan(),
+        style(&fallback.to_model).cyan(),
+        style(&fallback.reason).dim()
+    );
+}
+
+pub fn render_reasoning(model: &str, reasoning: Option<ReasoningConfig>) {
+    let setting = match reasoning {
+        Some(reasoning) => reasoning.to_string(),
+        None => "model default".to_string(),
+    };
+    println!(
+        "  {} {} for {}",
+        style("reasoning:").dim(),
+        style(setting).cyan(),
+        model
+    );
+}
+
 pub fn render_prompts(prompts: &HashMap<String, Vec<String>>) {
     println!();
     for (extension, prompts) in prompts {
@@ -792,59 +861,90 @@ pub fn display_context_usage(total_tokens: usize, context_limit: usize) {
     );
 }
 
-fn normalize_model_name(model: &str) -> String {
-    let mut result = model.to_string();
-
-    // Remove "-latest" suffix
-    if result.ends_with("-latest") {
-        result = result.strip_suffix("-latest").unwrap().to_string();
-    }
-
-    // Remove date-like suffixes: -YYYYMMDD
-    let re_date = Regex::new(r"-\d{8}$").unwrap();
-    if re_date.is_match(&result) {
-        result = re_date.replace(&result, "").to_string();
-    }
-
-    // Convert version numbers like -3-7- to -3.7- (e.g., claude-3-7-sonnet -> claude-3.7-sonnet)
-    let re_version = Regex::new(r"-(\d+)-(\d+)-").unwrap();
-    if re_version.is_match(&result) {
-        result = re_version.replace(&result, "-$1.$2-").to_string();
+/// Display the token breakdown of the next request, largest parts first within each kind
+pub fn render_context_usage(usage: &ContextUsage) {
+    println!();
+    display_context_usage(usage.total_tokens, usage.context_limit);
+
+    let mut entries: Vec<_> = usage.entries.iter().collect();
+    entries.sort_by(|a, b| a.kind.cmp(&b.kind).then(b.tokens.cmp(&a.tokens)));
+
+    let mut current_kind = None;
+    for entry in entries {
+        if current_kind != Some(entry.kind) {
+            current_kind = Some(entry.kind);
+            let count: usize = usage
+                .entries
+                .iter()
+                .filter(|e| e.kind == entry.kind)
+                .map(|e| e.count)
+                .sum();
+            let count = if count > 1 {
+                format!(", {} items", count)
+            } else {
+                String::new()
+            };
+            println!(
+                "\n {} {}",
+                style(entry.kind).green(),
+                style(format!("{} tokens{}", usage.tokens_for(entry.kind), count)).dim()
+            );
+        }
+        if let Some(name) = &entry.name {
+            println!("  {:>8}  {}", style(entry.tokens).cyan(), name);
+        }
     }
-
-    result
+    println!("\n {} {}", style("model:").dim(), usage.model);
 }
 
-async fn estimate_cost_usd(
-    provider: &str,
-    model: &str,
-    input_tokens: usize,
-    output_tokens: usize,
-) -> Option<f64> {
-    // For OpenRouter, parse the model name to extract real provider/model
-    let openrouter_data = if provider == "openrouter" {
-        parse_model_id(model)
-    } else {
-        None
-    };
-
-    let (provider_to_use, model_to_use) = match &openrouter_data {
-        Some((real_provider, real_model)) => (real_provider.as_str(), real_model.as_str()),
-        None => (provider, model),
-    };
-
-    // Use the pricing module's get_model_pricing which handles model name mapping internally
-    let cleaned_model = normalize_model_name(model_to_use);
-    let pricing_info = get_model_pricing(provider_to_use, &cleaned_model).await;
-
-    match pricing_info {
-        Some(pricing) => {
-            let input_cost = pricing.input_cost * input_tokens as f64;
-            let output_cost = pricing.output_cost * output_tokens as f64;
-            Some(input_cost + output_cost)
+/// List the messages shown to the user with their index for /pin, /unpin and /drop,
+/// marking the pinned ones and those no longer in the context
+pub fn render_pinnable_messages(messages: &[Message]) {
+    println!();
+    for (index, message) in messages.iter().enumerate() {
+        if !message.is_user_visible() {
+            continue;
         }
-        None => None,
+
+        let text = message.as_concat_text();
+        let preview = if text.trim().is_empty() {
+            message
+                .content
+                .iter()
+                .find_map(|content| match content {
+                    MessageContent::ToolRequest(request) => request
+                        .tool_call
+                        .as_ref()
+                        .ok()
+                        .map(|call| format!("[tool call {}]", call.name)),
+                    MessageContent::ToolResponse(_) => Some("[tool output]".to_string()),
+                    MessageContent::Image(_) => Some("[image]".to_string()),
+                    _ => None,
+                })
+                .unwrap_or_default()
+        } else {
+            safe_truncate(&text.replace('\n', " "), 80)
+        };
+        let marker = if message.is_pinned() {
+            style("pinned").green()
+        } else if !message.is_agent_visible() {
+            style("out of context").dim()
+        } else {
+            style("")
+        };
+        let role = match message.role {
+            rmcp::model::Role::User => "user",
+            rmcp::model::Role::Assistant => "goose",
+        };
+        println!(
+            "  {} {:<6} {} {}",
+            style(format!("{:>4}", index)).cyan(),
+            style(role).dim(),
+            preview,
+            marker
+        );
     }
+    println!();
 }
 
 /// Display cost information, if price data is available.
@@ -866,6 +966,60 @@ pub async fn display_cost_usage(
     }
 }
 
+/// Display the estimated cost of the whole session, broken down per model and role
+/// when more than one was used. Usage that couldn't be priced is left out of the total.
+pub async fn display_session_cost(model_usage: &[ModelUsage]) {
+    use console::style;
+
+    let mut total = None;
+    let mut lines = Vec::new();
+    for usage in model_usage {
+        let cost = usage.cost_or_estimate().await;
+        if let Some(cost) = cost {
+            total = Some(total.unwrap_or(0.0) + cost);
+        }
+        lines.push(format!(
+            "  {} ({}): {} - {} calls, {} tokens",
+            usage.model,
+            usage.role,
+            cost.map(|c| format!("${:.4}", c))
+                .unwrap_or_else(|| "unknown".to_string()),
+            usage.calls,
+            usage.total_tokens()
+        ));
+    }
+
+    let Some(total) = total else {
+        return;
+    };
+    let input_tokens: i64 = model_usage.iter().map(|u| u.input_tokens).sum();
+    let output_tokens: i64 = model_usage.iter().map(|u| u.output_tokens).sum();
+    eprintln!(
+        "Session cost: {} USD ({} tokens: in {}, out {})",
+        style(format!("${:.4}", total)).cyan(),
+        input_tokens + output_tokens,
+        input_tokens,
+        output_tokens
+    );
+    let cache_read_tokens: i64 = model_usage.iter().map(|u| u.cache_read_tokens).sum();
+    if cache_read_tokens > 0 && input_tokens > 0 {
+        eprintln!(
+            "Prompt cache: {} of input tokens read from cache ({} tokens)",
+            style(format!(
+                "{:.0}%",
+                cache_read_tokens as f64 / input_tokens as f64 * 100.0
+            ))
+            .cyan(),
+            cache_read_tokens
+        );
+    }
+    if model_usage.len() > 1 {
+        for line in lines {
+            eprintln!("{}", style(line).dim());
+        }
+    }
+}
+
 pub struct McpSpinners {
     bars: HashMap<String, ProgressBar>,
     log_spinner: Option<ProgressBar>,
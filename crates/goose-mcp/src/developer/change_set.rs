@@ -0,0 +1,171 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use rmcp::model::{ErrorCode, ErrorData};
+
+use super::text_editor::save_file_history;
+
+type FileHistory = Arc<Mutex<HashMap<PathBuf, Vec<String>>>>;
+
+/// Environment variable with a default check command run before a change set is committed
+pub const GOOSE_EDIT_CHECK_COMMAND: &str = "GOOSE_EDIT_CHECK_COMMAND";
+
+#[derive(Debug)]
+struct TrackedFile {
+    /// Position in the file history of the content from before the change set
+    history_index: usize,
+    existed: bool,
+}
+
+/// A set of file edits that are committed or rolled back together.
+///
+/// The content of each file from before its first edit in the change set is saved to
+/// the regular file history, so rolling back restores it from there.
+#[derive(Debug, Default)]
+pub struct ChangeSet {
+    files: HashMap<PathBuf, TrackedFile>,
+    check_command: Option<String>,
+}
+
+impl ChangeSet {
+    /// Start a change set, validated with `check_command` (or the configured default) on commit
+    pub fn new(check_command: Option<String>) -> Self {
+        let check_command = check_command
+            .or_else(|| std::env::var(GOOSE_EDIT_CHECK_COMMAND).ok())
+            .filter(|c| !c.trim().is_empty());
+        Self {
+            files: HashMap::new(),
+            check_command,
+        }
+    }
+
+    pub fn check_command(&self) -> Option<&str> {
+        self.check_command.as_deref()
+    }
+
+    /// The files edited in this change set, sorted
+    pub fn files(&self) -> Vec<&PathBuf> {
+        let mut files: Vec<&PathBuf> = self.files.keys().collect();
+        files.sort();
+        files
+    }
+
+    /// Save the current content of `path` before it is first edited in this change set
+    pub fn track(&mut self, path: &PathBuf, file_history: &FileHistory) -> Result<(), ErrorData> {
+        if self.files.contains_key(path) {
+            return Ok(());
+        }
+        let existed = path.exists();
+        save_file_history(path, file_history)?;
+        let history_index = file_history.lock().unwrap()[path].len() - 1;
+        self.files.insert(
+            path.clone(),
+            TrackedFile {
+                history_index,
+                existed,
+            },
+        );
+        Ok(())
+    }
+
+    /// Keep all edits, dropping the history entries saved by [`ChangeSet::track`]
+    pub fn commit(self, file_history: &FileHistory) {
+        let mut history = file_history.lock().unwrap();
+        for (path, tracked) in self.files {
+            if let Some(contents) = history.get_mut(&path) {
+                if contents.len() > tracked.history_index {
+                    contents.remove(tracked.history_index);
+                }
+            }
+        }
+    }
+
+    /// Restore every edited file to its content from before the change set, removing
+    /// files the change set created
+    pub fn rollback(self, file_history: &FileHistory) -> Result<(), ErrorData> {
+        let mut history = file_history.lock().unwrap();
+        let mut failures = Vec::new();
+        for (path, tracked) in self.files {
+            let contents = history.entry(path.clone()).or_default();
+            // If the saved content was already popped by undo_edit, undo has written it back
+            let original = if contents.len() > tracked.history_index {
+                let original = contents[tracked.history_index].clone();
+                contents.truncate(tracked.history_index);
+                Some(original)
+            } else {
+                None
+            };
+
+            let result = match (tracked.existed, original) {
+                (true, Some(original)) => std::fs::write(&path, original),
+                (true, None) => Ok(()),
+                (false, _) if path.exists() => std::fs::remove_file(&path),
+                (false, _) => Ok(()),
+            };
+            if let Err(e) = result {
+                failures.push(format!("{}: {}", path.display(), e));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                format!("Failed to roll back some files:\n{}", failures.join("\n")),
+                None,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_rollback_restores_and_removes_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("existing.txt");
+        let created = dir.path().join("created.txt");
+        fs::write(&existing, "original\n").unwrap();
+        let history = FileHistory::default();
+
+        let mut change_set = ChangeSet::new(None);
+        change_set.track(&existing, &history).unwrap();
+        save_file_history(&existing, &history).unwrap();
+        fs::write(&existing, "first edit\n").unwrap();
+        save_file_history(&existing, &history).unwrap();
+        fs::write(&existing, "second edit\n").unwrap();
+        change_set.track(&created, &history).unwrap();
+        fs::write(&created, "new\n").unwrap();
+        assert_eq!(change_set.files(), vec![&created, &existing]);
+
+        change_set.rollback(&history).unwrap();
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "original\n");
+        assert!(!created.exists());
+        assert!(history.lock().unwrap()[&existing].is_empty());
+    }
+
+    #[test]
+    fn test_commit_keeps_edits_and_undo_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        fs::write(&path, "original\n").unwrap();
+        let history = FileHistory::default();
+
+        let mut change_set = ChangeSet::new(Some("true".to_string()));
+        assert_eq!(change_set.check_command(), Some("true"));
+        change_set.track(&path, &history).unwrap();
+        save_file_history(&path, &history).unwrap();
+        fs::write(&path, "edited\n").unwrap();
+
+        change_set.commit(&history);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "edited\n");
+        assert_eq!(history.lock().unwrap()[&path], vec!["original\n"]);
+    }
+}
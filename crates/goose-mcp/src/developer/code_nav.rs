@@ -0,0 +1,454 @@
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+use tree_sitter::{Language, Node, Parser};
+
+use super::lang::get_language_identifier;
+
+/// Files larger than this are skipped when scanning a directory
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+/// Maximum number of files parsed for a single directory scan
+pub const MAX_FILES: usize = 5000;
+
+/// A node kind that defines a symbol, and the field holding the symbol's name
+struct DefinitionKind {
+    node_kind: &'static str,
+    label: &'static str,
+    name_field: &'static str,
+}
+
+const fn def(node_kind: &'static str, label: &'static str) -> DefinitionKind {
+    DefinitionKind {
+        node_kind,
+        label,
+        name_field: "name",
+    }
+}
+
+const RUST_DEFINITIONS: &[DefinitionKind] = &[
+    def("function_item", "fn"),
+    def("function_signature_item", "fn"),
+    def("struct_item", "struct"),
+    def("enum_item", "enum"),
+    def("union_item", "union"),
+    def("trait_item", "trait"),
+    DefinitionKind {
+        node_kind: "impl_item",
+        label: "impl",
+        name_field: "type",
+    },
+    def("mod_item", "mod"),
+    def("const_item", "const"),
+    def("static_item", "static"),
+    def("type_item", "type"),
+    def("macro_definition", "macro"),
+];
+
+const PYTHON_DEFINITIONS: &[DefinitionKind] = &[
+    def("function_definition", "def"),
+    def("class_definition", "class"),
+];
+
+const TYPESCRIPT_DEFINITIONS: &[DefinitionKind] = &[
+    def("function_declaration", "function"),
+    def("generator_function_declaration", "function"),
+    def("function_signature", "function"),
+    def("class_declaration", "class"),
+    def("abstract_class_declaration", "class"),
+    def("interface_declaration", "interface"),
+    def("type_alias_declaration", "type"),
+    def("enum_declaration", "enum"),
+    def("method_definition", "method"),
+    def("method_signature", "method"),
+    def("abstract_method_signature", "method"),
+    def("internal_module", "namespace"),
+];
+
+const GO_DEFINITIONS: &[DefinitionKind] = &[
+    def("function_declaration", "func"),
+    def("method_declaration", "method"),
+    def("type_spec", "type"),
+    def("method_elem", "method"),
+];
+
+const JAVA_DEFINITIONS: &[DefinitionKind] = &[
+    def("class_declaration", "class"),
+    def("interface_declaration", "interface"),
+    def("enum_declaration", "enum"),
+    def("record_declaration", "record"),
+    def("annotation_type_declaration", "@interface"),
+    def("method_declaration", "method"),
+    def("constructor_declaration", "constructor"),
+];
+
+/// Languages supported by the code navigation tools
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeLanguage {
+    Rust,
+    Python,
+    TypeScript,
+    Tsx,
+    Go,
+    Java,
+}
+
+impl CodeLanguage {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match get_language_identifier(path) {
+            "rust" => Some(Self::Rust),
+            "python" => Some(Self::Python),
+            "typescript" => Some(Self::TypeScript),
+            "tsx" => Some(Self::Tsx),
+            "go" => Some(Self::Go),
+            "java" => Some(Self::Java),
+            _ => None,
+        }
+    }
+
+    fn grammar(self) -> Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Go => tree_sitter_go::LANGUAGE.into(),
+            Self::Java => tree_sitter_java::LANGUAGE.into(),
+        }
+    }
+
+    fn definitions(self) -> &'static [DefinitionKind] {
+        match self {
+            Self::Rust => RUST_DEFINITIONS,
+            Self::Python => PYTHON_DEFINITIONS,
+            Self::TypeScript | Self::Tsx => TYPESCRIPT_DEFINITIONS,
+            Self::Go => GO_DEFINITIONS,
+            Self::Java => JAVA_DEFINITIONS,
+        }
+    }
+}
+
+/// A definition found in a source file. Lines are 1-indexed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub kind: &'static str,
+    pub name: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Nesting level, 0 for top-level definitions
+    pub depth: usize,
+    /// Name of the enclosing definition, e.g. the class of a method
+    pub container: Option<String>,
+}
+
+impl Symbol {
+    pub fn describe(&self) -> String {
+        let lines = format!("lines {}-{}", self.start_line, self.end_line);
+        match &self.container {
+            Some(container) => format!("{} {} ({}, in {})", self.kind, self.name, lines, container),
+            None => format!("{} {} ({})", self.kind, self.name, lines),
+        }
+    }
+}
+
+/// An occurrence of an identifier that is not its definition. Lines and columns are 1-indexed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub line: usize,
+    pub column: usize,
+    /// The trimmed source line containing the reference
+    pub text: String,
+}
+
+struct Analyzer<'a> {
+    source: &'a [u8],
+    language: CodeLanguage,
+    symbols: Vec<Symbol>,
+    /// Ids of the nodes naming a definition, so they are not reported as references
+    definition_names: Vec<usize>,
+}
+
+impl Analyzer<'_> {
+    fn text(&self, node: Node) -> String {
+        node.utf8_text(self.source).unwrap_or_default().to_string()
+    }
+
+    /// The definition kind and name node of `node`, if it defines a symbol
+    fn definition<'t>(&self, node: Node<'t>) -> Option<(&'static DefinitionKind, Node<'t>)> {
+        if let Some(kind) = self
+            .language
+            .definitions()
+            .iter()
+            .find(|d| d.node_kind == node.kind())
+        {
+            return Some((kind, node.child_by_field_name(kind.name_field)?));
+        }
+
+        // `const handler = () => {}` defines a function in TypeScript
+        if matches!(self.language, CodeLanguage::TypeScript | CodeLanguage::Tsx)
+            && node.kind() == "variable_declarator"
+        {
+            static ARROW: DefinitionKind = def("variable_declarator", "function");
+            let value = node.child_by_field_name("value")?;
+            if matches!(value.kind(), "arrow_function" | "function_expression") {
+                return Some((&ARROW, node.child_by_field_name("name")?));
+            }
+        }
+        None
+    }
+
+    fn symbol_name(&self, node: Node, kind: &DefinitionKind, name_node: Node) -> String {
+        let name = self.text(name_node);
+        if kind.node_kind == "impl_item" {
+            if let Some(trait_node) = node.child_by_field_name("trait") {
+                return format!("{} for {}", self.text(trait_node), name);
+            }
+        }
+        name
+    }
+
+    fn visit(&mut self, node: Node, depth: usize, container: Option<&str>) {
+        let mut child_depth = depth;
+        let mut child_container = container.map(str::to_string);
+
+        if let Some((kind, name_node)) = self.definition(node) {
+            let name = self.symbol_name(node, kind, name_node);
+            // The type of an impl block refers to a definition elsewhere
+            if kind.node_kind != "impl_item" {
+                self.definition_names.push(name_node.id());
+            }
+            self.symbols.push(Symbol {
+                kind: kind.label,
+                name: name.clone(),
+                start_line: node.start_position().row + 1,
+                end_line: node.end_position().row + 1,
+                depth,
+                container: container.map(str::to_string),
+            });
+            child_depth = depth + 1;
+            child_container = Some(name);
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit(child, child_depth, child_container.as_deref());
+        }
+    }
+}
+
+fn analyze<'a>(
+    source: &'a str,
+    language: CodeLanguage,
+) -> Option<(Analyzer<'a>, tree_sitter::Tree)> {
+    let mut parser = Parser::new();
+    parser.set_language(&language.grammar()).ok()?;
+    let tree = parser.parse(source, None)?;
+    let mut analyzer = Analyzer {
+        source: source.as_bytes(),
+        language,
+        symbols: Vec::new(),
+        definition_names: Vec::new(),
+    };
+    analyzer.visit(tree.root_node(), 0, None);
+    Some((analyzer, tree))
+}
+
+/// All definitions in `source`, in source order
+pub fn outline(source: &str, language: CodeLanguage) -> Vec<Symbol> {
+    analyze(source, language)
+        .map(|(analyzer, _)| analyzer.symbols)
+        .unwrap_or_default()
+}
+
+/// All identifiers named `name` in `source` that are not the name of a definition
+pub fn references(source: &str, language: CodeLanguage, name: &str) -> Vec<Reference> {
+    let Some((analyzer, tree)) = analyze(source, language) else {
+        return Vec::new();
+    };
+    let lines: Vec<&str> = source.lines().collect();
+
+    let mut references = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if node.child_count() == 0 {
+            if node.kind().ends_with("identifier")
+                && node.utf8_text(source.as_bytes()) == Ok(name)
+                && !analyzer.definition_names.contains(&node.id())
+            {
+                let position = node.start_position();
+                references.push(Reference {
+                    line: position.row + 1,
+                    column: position.column + 1,
+                    text: lines
+                        .get(position.row)
+                        .map(|l| l.trim().to_string())
+                        .unwrap_or_default(),
+                });
+            }
+            continue;
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    references
+}
+
+/// Supported source files under `root` (or `root` itself if it is a file), skipping
+/// files that are gitignored, hidden, too large or rejected by `is_ignored`.
+///
+/// Returns the files and whether the scan stopped early at [`MAX_FILES`].
+pub fn source_files(root: &Path, is_ignored: impl Fn(&Path) -> bool) -> (Vec<PathBuf>, bool) {
+    if root.is_file() {
+        let supported = CodeLanguage::from_path(root).is_some() && !is_ignored(root);
+        return (
+            supported.then(|| root.to_path_buf()).into_iter().collect(),
+            false,
+        );
+    }
+
+    let mut files = Vec::new();
+    let mut truncated = false;
+    for entry in WalkBuilder::new(root).build().flatten() {
+        let path = entry.path();
+        if !entry.file_type().is_some_and(|t| t.is_file())
+            || CodeLanguage::from_path(path).is_none()
+            || is_ignored(path)
+            || entry
+                .metadata()
+                .map(|m| m.len() > MAX_FILE_SIZE)
+                .unwrap_or(true)
+        {
+            continue;
+        }
+        if files.len() == MAX_FILES {
+            truncated = true;
+            break;
+        }
+        files.push(path.to_path_buf());
+    }
+    files.sort();
+    (files, truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUST_SOURCE: &str = r#"
+struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    fn new() -> Self {
+        Counter { count: 0 }
+    }
+
+    fn increment(&mut self) {
+        self.count += 1;
+    }
+}
+
+impl Default for Counter {
+    fn default() -> Self {
+        Counter::new()
+    }
+}
+"#;
+
+    fn summary(symbols: &[Symbol]) -> Vec<String> {
+        symbols
+            .iter()
+            .map(|s| format!("{}{} {}", "  ".repeat(s.depth), s.kind, s.name))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_outline() {
+        let symbols = outline(RUST_SOURCE, CodeLanguage::Rust);
+        assert_eq!(
+            summary(&symbols),
+            vec![
+                "struct Counter",
+                "impl Counter",
+                "  fn new",
+                "  fn increment",
+                "impl Default for Counter",
+                "  fn default",
+            ]
+        );
+        assert_eq!(symbols[0].start_line, 2);
+        assert_eq!(symbols[0].end_line, 4);
+        assert_eq!(symbols[3].container.as_deref(), Some("Counter"));
+        assert_eq!(
+            symbols[3].describe(),
+            "fn increment (lines 11-13, in Counter)"
+        );
+    }
+
+    #[test]
+    fn test_rust_references_skip_definitions() {
+        let refs = references(RUST_SOURCE, CodeLanguage::Rust, "new");
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].line, 18);
+        assert_eq!(refs[0].text, "Counter::new()");
+
+        let refs = references(RUST_SOURCE, CodeLanguage::Rust, "Counter");
+        let lines: Vec<usize> = refs.iter().map(|r| r.line).collect();
+        assert_eq!(lines, vec![6, 8, 16, 18]);
+    }
+
+    #[test]
+    fn test_python_outline() {
+        let source = "class Greeter:\n    def greet(self):\n        return helper()\n\ndef helper():\n    pass\n";
+        assert_eq!(
+            summary(&outline(source, CodeLanguage::Python)),
+            vec!["class Greeter", "  def greet", "def helper"]
+        );
+        let refs = references(source, CodeLanguage::Python, "helper");
+        assert_eq!(refs.len(), 1);
+        assert_eq!((refs[0].line, refs[0].column), (3, 16));
+    }
+
+    #[test]
+    fn test_typescript_outline() {
+        let source = "interface Shape { area(): number; }\nexport class Square implements Shape {\n  area() { return 1; }\n}\nconst make = () => new Square();\n";
+        assert_eq!(
+            summary(&outline(source, CodeLanguage::TypeScript)),
+            vec![
+                "interface Shape",
+                "  method area",
+                "class Square",
+                "  method area",
+                "function make",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_go_and_java_outline() {
+        let go = "package main\n\ntype Server struct{}\n\nfunc (s *Server) Start() {}\n\nfunc main() {}\n";
+        assert_eq!(
+            summary(&outline(go, CodeLanguage::Go)),
+            vec!["type Server", "method Start", "func main"]
+        );
+
+        let java = "class App {\n  App() {}\n  void run() {}\n}\n";
+        assert_eq!(
+            summary(&outline(java, CodeLanguage::Java)),
+            vec!["class App", "  constructor App", "  method run"]
+        );
+    }
+
+    #[test]
+    fn test_source_files_filters_unsupported_and_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("notes.md"), "# notes").unwrap();
+        std::fs::write(dir.path().join("secret.py"), "x = 1").unwrap();
+
+        let (files, truncated) = source_files(dir.path(), |p| p.ends_with("secret.py"));
+        assert!(!truncated);
+        assert_eq!(files, vec![dir.path().join("main.rs")]);
+    }
+}
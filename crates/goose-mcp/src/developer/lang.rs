@@ -9,6 +9,7 @@ pub fn get_language_identifier(path: &Path) -> &'static str {
         Some("py") => "python",
         Some("js") => "javascript",
         Some("ts") => "typescript",
+        Some("tsx") => "tsx",
         Some("json") => "json",
         Some("toml") => "toml",
         Some("yaml") | Some("yml") => "yaml",
@@ -1,7 +1,11 @@
+mod change_set;
+mod code_nav;
 mod editor_models;
 mod goose_hints;
 mod lang;
 mod shell;
+mod shell_jobs;
+mod shell_session;
 mod text_editor;
 
 pub mod rmcp_developer;
@@ -6,9 +6,8 @@ use rmcp::{
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
     model::{
         CallToolResult, Content, ErrorCode, ErrorData, GetPromptRequestParam, GetPromptResult,
-        Implementation, ListPromptsResult, LoggingLevel, LoggingMessageNotificationParam,
-        PaginatedRequestParam, Prompt, PromptArgument, PromptMessage, PromptMessageRole, Role,
-        ServerCapabilities, ServerInfo,
+        Implementation, ListPromptsResult, PaginatedRequestParam, Prompt, PromptArgument,
+        PromptMessage, PromptMessageRole, Role, ServerCapabilities, ServerInfo,
     },
     schemars::JsonSchema,
     service::RequestContext,
@@ -22,20 +21,23 @@ use std::{
     path::{Path, PathBuf},
     process::Stdio,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 use xcap::{Monitor, Window};
 
-use tokio::{
-    io::{AsyncBufReadExt, BufReader},
-    process::Command,
-};
+use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio_stream::{wrappers::SplitStream, StreamExt as _};
 
+use super::change_set::ChangeSet;
+use super::code_nav::{self, CodeLanguage};
 use super::editor_models::{create_editor_model, EditorModel};
 use super::goose_hints::load_hints::{load_hint_files, GOOSE_HINTS_FILENAME};
-use super::shell::{expand_path, get_shell_config, is_absolute_path};
+use super::shell::{expand_path, is_absolute_path};
+use super::shell_jobs::{kill_process_tree, shell_command, ShellJobs};
+use super::shell_session::{notify_shell_output, persistent_shell_enabled, ShellSession};
 use super::text_editor::{
-    text_editor_insert, text_editor_replace, text_editor_undo, text_editor_view, text_editor_write,
+    diff_target_paths, text_editor_insert, text_editor_replace, text_editor_undo, text_editor_view,
+    text_editor_write,
 };
 
 /// Parameters for the screen_capture tool
@@ -56,7 +58,8 @@ pub struct TextEditorParams {
     /// Absolute path to file or directory, e.g. `/repo/file.py` or `/repo`.
     pub path: String,
 
-    /// The operation to perform. Allowed options are: `view`, `write`, `str_replace`, `insert`, `undo_edit`.
+    /// The operation to perform. Allowed options are: `view`, `write`, `str_replace`, `insert`, `undo_edit`,
+    /// and `begin`, `commit`, `rollback` to group edits across files into one change set.
     pub command: String,
 
     /// Unified diff to apply. Supports editing multiple files simultaneously. Cannot create or delete files
@@ -80,6 +83,10 @@ pub struct TextEditorParams {
 
     /// The line number after which to insert text (0 for beginning). Required for `insert` command.
     pub insert_line: Option<i64>,
+
+    /// Optional for `begin`: a shell command, e.g. `cargo check`, run in `path` when the change set is
+    /// committed. If it fails, all edits of the change set are rolled back.
+    pub check_command: Option<String>,
 }
 
 /// Parameters for the shell tool
@@ -87,6 +94,53 @@ pub struct TextEditorParams {
 pub struct ShellParams {
     /// The command string to execute in the shell
     pub command: String,
+
+    /// Optional: kill the command if it has not finished after this many seconds.
+    /// Ignored for background commands.
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+
+    /// Run the command in the background and return a job id right away, e.g. for dev servers
+    /// and watchers. Use the shell_job tool to read its output, send it input or kill it.
+    #[serde(default)]
+    pub background: bool,
+
+    /// Restart the shell session before running the command, discarding its working directory,
+    /// environment and shell functions. The command may be empty to only reset the session.
+    /// Only applies when the persistent shell session is enabled.
+    #[serde(default)]
+    pub reset: bool,
+}
+
+/// Parameters for the shell_job tool
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ShellJobParams {
+    /// The operation to perform. Allowed options are: `output`, `input`, `list`, `kill`.
+    pub action: String,
+
+    /// The id of the background job, as returned by the shell tool. Required for all actions except `list`.
+    pub job_id: Option<u32>,
+
+    /// Text to write to the stdin of the job. Required for `input`. Include a trailing newline
+    /// to submit a line.
+    pub input: Option<String>,
+}
+
+/// Parameters for the code_outline tool
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CodeOutlineParams {
+    /// Absolute path to a source file or a directory to outline
+    pub path: String,
+}
+
+/// Parameters for the find_symbol and find_references tools
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindSymbolParams {
+    /// The name of the function, method, type or other symbol, e.g. `parse_config`
+    pub name: String,
+
+    /// Absolute path to the file or directory to search, defaults to the current directory
+    pub path: Option<String>,
 }
 
 /// Parameters for the image_processor tool
@@ -115,6 +169,23 @@ pub struct PromptArgumentTemplate {
 // Embeds the prompts directory to the build
 static PROMPTS_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/src/developer/prompts");
 
+/// Note a failed command's exit status after its output, so a failure is not mistaken for
+/// a command that succeeded quietly
+fn append_exit_status(output: &mut String, code: Option<i32>) {
+    let note = match code {
+        Some(0) => return,
+        Some(code) => format!("Command exited with code {}", code),
+        None => "Command was terminated by a signal".to_string(),
+    };
+    if !output.is_empty() {
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push('\n');
+    }
+    output.push_str(&note);
+}
+
 /// Loads prompt files from the embedded PROMPTS_DIR and returns a HashMap of prompts.
 /// Ensures that each prompt name is unique.
 fn load_prompt_files() -> HashMap<String, Prompt> {
@@ -168,9 +239,13 @@ fn load_prompt_files() -> HashMap<String, Prompt> {
 pub struct DeveloperServer {
     tool_router: ToolRouter<Self>,
     file_history: Arc<Mutex<HashMap<PathBuf, Vec<String>>>>,
+    change_set: Arc<Mutex<Option<ChangeSet>>>,
     ignore_patterns: Gitignore,
     editor_model: Option<EditorModel>,
     prompts: HashMap<String, Prompt>,
+    shell_jobs: Arc<ShellJobs>,
+    persistent_shell: bool,
+    shell_session: Arc<tokio::sync::Mutex<Option<ShellSession>>>,
 }
 
 #[tool_handler(router = self.tool_router)]
@@ -260,6 +335,10 @@ impl ServerHandler for DeveloperServer {
                 Always batch file edits together by using a multi-file unified `diff` within a single str_replace tool call.
                 Not batching file edits using `diff` is an error and wastes context, time, and inference.
 
+                To make related edits across several files all-or-nothing, run `begin` first (optionally with a `check_command`
+                such as `cargo check`), make the edits, then `commit` to keep them or `rollback` to restore every file. If the
+                check command fails on commit, all edits of the change set are rolled back.
+
                 {}
 
             "#, editor.get_str_replace_description()}
@@ -289,10 +368,21 @@ impl ServerHandler for DeveloperServer {
                 To use the insert command, you must specify both `insert_line` (the line number after which to insert, 0 for beginning, -1 for end)
                 and `new_str` (the text to insert).
 
+                To make related edits across several files all-or-nothing, run `begin` first (optionally with a `check_command`
+                such as `cargo check`), make the edits, then `commit` to keep them or `rollback` to restore every file. If the
+                check command fails on commit, all edits of the change set are rolled back.
+
 
             "#}
         };
 
+        let code_navigation_instructions = indoc! {r#"
+            Code Navigation Tool Instructions:
+            For Rust, Python, TypeScript, Go and Java, prefer code_outline to get an overview of a file or
+            directory, find_symbol to jump to a definition and find_references to see where a symbol is used
+            over searching with the shell. They parse the code, so comments and strings are not matched.
+        "#};
+
         // Create comprehensive shell tool instructions
         let common_shell_instructions = indoc! {r#"
             Additional Shell Tool Instructions:
@@ -303,12 +393,22 @@ impl ServerHandler for DeveloperServer {
             of if the command succeeded or failed.
 
             Avoid commands that produce a large amount of output, and consider piping those outputs to files.
+        "#};
 
+        let shell_state_instructions = if self.persistent_shell {
+            indoc! {r#"
+            **Important**: Shell commands run in one persistent shell session. Directory changes, exported
+            variables, shell functions and sourced files (such as a virtualenv) carry over to later tool calls,
+            so there is no need to repeat them. Set `reset` to start over from a fresh shell.
+        "#}
+        } else {
+            indoc! {r#"
             **Important**: Each shell command runs in its own process. Things like directory changes or
             sourcing files do not persist between tool calls. So you may need to repeat them each time by
             stringing together commands.
               - Pathnames: Use absolute paths and avoid cd unless explicitly requested
-        "#};
+        "#}
+        };
 
         let windows_specific = indoc! {r#"
             **Important**: For searching files and code:
@@ -332,8 +432,9 @@ impl ServerHandler for DeveloperServer {
         "#};
 
         let unix_specific = indoc! {r#"
-            If you need to run a long lived command, background it - e.g. `uvicorn main:app &` so that
-            this tool does not run indefinitely.
+            If you need to run a long lived command such as a dev server, run it with `background` set to true
+            so that this tool does not run indefinitely, then use the shell_job tool to check its output or stop it.
+            Set `timeout_secs` for commands that might hang.
 
             **Important**: Use ripgrep - `rg` - exclusively when you need to locate a file or a code reference,
             other solutions may produce too large output because of hidden files! For example *do not* use `find` or `ls -r`
@@ -345,15 +446,21 @@ impl ServerHandler for DeveloperServer {
         "#};
 
         let shell_tool_desc = match os {
-            "windows" => format!("{}{}", common_shell_instructions, windows_specific),
-            _ => format!("{}{}", common_shell_instructions, unix_specific),
+            "windows" => format!(
+                "{}{}{}",
+                common_shell_instructions, shell_state_instructions, windows_specific
+            ),
+            _ => format!(
+                "{}{}{}",
+                common_shell_instructions, shell_state_instructions, unix_specific
+            ),
         };
 
         // Return base instructions directly when no hints are found
         let instructions = if hints.is_empty() {
-            format!("{base_instructions}{editor_description}\n{shell_tool_desc}")
+            format!("{base_instructions}{editor_description}\n{shell_tool_desc}\n{code_navigation_instructions}")
         } else {
-            format!("{base_instructions}\n{editor_description}\n{shell_tool_desc}\n{hints}")
+            format!("{base_instructions}\n{editor_description}\n{shell_tool_desc}\n{code_navigation_instructions}\n{hints}")
         };
 
         ServerInfo {
@@ -513,9 +620,13 @@ impl DeveloperServer {
         Self {
             tool_router: Self::tool_router(),
             file_history: Arc::new(Mutex::new(HashMap::new())),
+            change_set: Arc::new(Mutex::new(None)),
             ignore_patterns,
             editor_model,
             prompts: load_prompt_files(),
+            shell_jobs: Arc::new(ShellJobs::default()),
+            persistent_shell: persistent_shell_enabled(),
+            shell_session: Arc::new(tokio::sync::Mutex::new(None)),
         }
     }
 
@@ -668,9 +779,11 @@ impl DeveloperServer {
     /// - `str_replace`: Replace old_str with new_str in the file.
     /// - `insert`: Insert text at a specific line location in the file.
     /// - `undo_edit`: Undo the last edit made to a file.
+    /// - `begin`, `commit`, `rollback`: Group edits across files into a change set that is
+    ///   kept or reverted as a whole.
     #[tool(
         name = "text_editor",
-        description = "Perform text editing operations on files. Commands: view (show file content), write (create/overwrite file), str_replace (edit file), insert (insert at line), undo_edit (undo last change)."
+        description = "Perform text editing operations on files. Commands: view (show file content), write (create/overwrite file), str_replace (edit file), insert (insert at line), undo_edit (undo last change), begin/commit/rollback (apply edits across files as one change set)."
     )]
     pub async fn text_editor(
         &self,
@@ -691,7 +804,42 @@ impl DeveloperServer {
             ));
         }
 
+        if matches!(params.command.as_str(), "write" | "str_replace" | "insert") {
+            self.track_change_set_edit(&path, params.diff.as_deref())?;
+        }
+
         match params.command.as_str() {
+            "begin" => {
+                let mut change_set = self.change_set.lock().unwrap();
+                if change_set.is_some() {
+                    return Err(ErrorData::new(
+                        ErrorCode::INVALID_PARAMS,
+                        "A change set is already in progress, commit or roll it back first"
+                            .to_string(),
+                        None,
+                    ));
+                }
+                let new_change_set = ChangeSet::new(params.check_command);
+                let check = match new_change_set.check_command() {
+                    Some(command) => format!(" `{}` will be run before committing.", command),
+                    None => String::new(),
+                };
+                *change_set = Some(new_change_set);
+                Ok(CallToolResult::success(vec![Content::text(format!(
+                    "Started a change set. Edits are now grouped until `commit` or `rollback`.{}",
+                    check
+                ))]))
+            }
+            "commit" => self.commit_change_set(&path).await,
+            "rollback" => {
+                let change_set = self.take_change_set()?;
+                let files = Self::format_change_set_files(&change_set);
+                change_set.rollback(&self.file_history)?;
+                Ok(CallToolResult::success(vec![Content::text(format!(
+                    "Rolled back the change set:\n{}",
+                    files
+                ))]))
+            }
             "view" => {
                 let view_range = params.view_range.as_ref().and_then(|vr| {
                     if vr.len() == 2 {
@@ -788,6 +936,97 @@ impl DeveloperServer {
         }
     }
 
+    /// Save the files an edit is about to change to the active change set, if there is one
+    fn track_change_set_edit(&self, path: &Path, diff: Option<&str>) -> Result<(), ErrorData> {
+        let mut change_set = self.change_set.lock().unwrap();
+        let Some(change_set) = change_set.as_mut() else {
+            return Ok(());
+        };
+        let paths = match diff {
+            Some(diff) => diff_target_paths(path, diff)?,
+            None => vec![path.to_path_buf()],
+        };
+        for path in &paths {
+            change_set.track(path, &self.file_history)?;
+        }
+        Ok(())
+    }
+
+    fn take_change_set(&self) -> Result<ChangeSet, ErrorData> {
+        self.change_set.lock().unwrap().take().ok_or_else(|| {
+            ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                "No change set in progress, start one with `begin`".to_string(),
+                None,
+            )
+        })
+    }
+
+    fn format_change_set_files(change_set: &ChangeSet) -> String {
+        let files = change_set.files();
+        if files.is_empty() {
+            return "(no files were edited)".to_string();
+        }
+        files
+            .iter()
+            .map(|f| format!("- {}", f.display()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Run the check command of the active change set in `path`, then keep all of its
+    /// edits, or roll all of them back if the check fails.
+    async fn commit_change_set(&self, path: &Path) -> Result<CallToolResult, ErrorData> {
+        const CHECK_TIMEOUT_SECS: u64 = 600;
+
+        let change_set = self.take_change_set()?;
+        let files = Self::format_change_set_files(&change_set);
+
+        if let Some(check_command) = change_set.check_command() {
+            let mut command = shell_command(check_command);
+            if path.is_dir() {
+                command.current_dir(path);
+            }
+            let output = tokio::time::timeout(
+                Duration::from_secs(CHECK_TIMEOUT_SECS),
+                command.stdin(Stdio::null()).output(),
+            )
+            .await;
+
+            let failure = match output {
+                Ok(Ok(output)) if output.status.success() => None,
+                Ok(Ok(output)) => Some(format!(
+                    "exited with {}:\n{}{}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stdout),
+                    String::from_utf8_lossy(&output.stderr)
+                )),
+                Ok(Err(e)) => Some(format!("could not be run: {}", e)),
+                Err(_) => Some(format!("timed out after {} seconds", CHECK_TIMEOUT_SECS)),
+            };
+
+            if let Some(failure) = failure {
+                let check_command = check_command.to_string();
+                change_set.rollback(&self.file_history)?;
+                let (failure, _) = self.process_shell_output(&failure)?;
+                return Err(ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    format!(
+                        "The check `{}` {}\n\nAll edits of the change set were rolled back:\n{}",
+                        check_command, failure, files
+                    ),
+                    None,
+                ));
+            }
+        }
+
+        change_set.commit(&self.file_history);
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Committed the change set:\n{}",
+            files
+        ))]))
+    }
+
     /// Execute a command in the shell.
     ///
     /// This will return the output and error concatenated into a single string, as
@@ -795,11 +1034,11 @@ impl DeveloperServer {
     /// of if the command succeeded or failed.
     ///
     /// Avoid commands that produce a large amount of output, and consider piping those outputs to files.
-    /// If you need to run a long lived command, background it - e.g. `uvicorn main:app &` so that
-    /// this tool does not run indefinitely.
+    /// If you need to run a long lived command, run it with `background` set so that this tool does
+    /// not run indefinitely, and manage it with the shell_job tool.
     #[tool(
         name = "shell",
-        description = "Execute a command in the shell. Returns output and error concatenated. Avoid commands with large output, use background commands for long-running processes."
+        description = "Execute a command in the shell. Returns output and error concatenated. Avoid commands with large output. Set timeout_secs for commands that might hang, and background for long-running processes such as dev servers."
     )]
     pub async fn shell(
         &self,
@@ -810,11 +1049,36 @@ impl DeveloperServer {
         let command = &params.command;
         let peer = context.peer;
 
-        // Validate the shell command
-        self.validate_shell_command(command)?;
+        if params.reset && self.persistent_shell {
+            // Dropping the session kills the shell and everything it started
+            self.shell_session.lock().await.take();
+            if command.trim().is_empty() {
+                return Ok(CallToolResult::success(vec![Content::text(
+                    "The shell session was reset",
+                )]));
+            }
+        }
+
+        // Validate the shell command against the directory it will run in
+        let cwd = self.shell_cwd(params.background).await;
+        self.validate_shell_command(command, &cwd)?;
+
+        if params.background {
+            let job_id = self.shell_jobs.spawn(command).await?;
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "Started background job {}. Use the shell_job tool with job_id {} to read its output, send it input or kill it.",
+                job_id, job_id
+            ))]));
+        }
 
         // Execute the command and capture output
-        let output_str = self.execute_shell_command(command, &peer).await?;
+        let output_str = if self.persistent_shell {
+            self.execute_in_session(command, params.timeout_secs, &peer)
+                .await?
+        } else {
+            self.execute_shell_command(command, params.timeout_secs, &peer)
+                .await?
+        };
 
         // Validate output size
         self.validate_shell_output_size(command, &output_str)?;
@@ -830,11 +1094,99 @@ impl DeveloperServer {
         ]))
     }
 
+    /// Manage commands started in the background by the shell tool.
+    ///
+    /// `output` returns the job status and the output produced since the last read,
+    /// `input` writes to the stdin of the job, `list` shows all jobs and `kill` stops
+    /// a job together with its child processes.
+    #[tool(
+        name = "shell_job",
+        description = "Manage background commands started by the shell tool. Actions: `output` returns the job status and any new output since the last read, `input` writes text to the job's stdin, `list` shows all jobs, `kill` stops a job and its child processes."
+    )]
+    pub async fn shell_job(
+        &self,
+        params: Parameters<ShellJobParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+        if params.action == "list" {
+            let jobs = self.shell_jobs.list().await;
+            let text = if jobs.is_empty() {
+                "No background jobs".to_string()
+            } else {
+                jobs.join("\n")
+            };
+            return Ok(CallToolResult::success(vec![Content::text(text)]));
+        }
+
+        let job_id = params.job_id.ok_or_else(|| {
+            ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("Missing 'job_id' parameter for {} action", params.action),
+                None,
+            )
+        })?;
+
+        match params.action.as_str() {
+            "output" | "kill" => {
+                let (status, output) = if params.action == "kill" {
+                    self.shell_jobs.kill(job_id).await?
+                } else {
+                    self.shell_jobs.read_output(job_id).await?
+                };
+                let (final_output, user_output) = self.process_shell_output(&output)?;
+                Ok(CallToolResult::success(vec![
+                    Content::text(format!("Job {} {}\n{}", job_id, status, final_output))
+                        .with_audience(vec![Role::Assistant]),
+                    Content::text(user_output)
+                        .with_audience(vec![Role::User])
+                        .with_priority(0.0),
+                ]))
+            }
+            "input" => {
+                let input = params.input.ok_or_else(|| {
+                    ErrorData::new(
+                        ErrorCode::INVALID_PARAMS,
+                        "Missing 'input' parameter for input action".to_string(),
+                        None,
+                    )
+                })?;
+                self.shell_jobs.send_input(job_id, &input).await?;
+                Ok(CallToolResult::success(vec![Content::text(format!(
+                    "Sent input to job {}",
+                    job_id
+                ))]))
+            }
+            _ => Err(ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("Unknown action '{}'", params.action),
+                None,
+            )),
+        }
+    }
+
+    /// The directory a shell command runs in: the persistent shell's own working directory,
+    /// which `cd` moves, or else the working directory of this process
+    async fn shell_cwd(&self, background: bool) -> PathBuf {
+        let session_cwd = if self.persistent_shell && !background {
+            self.shell_session
+                .lock()
+                .await
+                .as_ref()
+                .map(|session| session.cwd().to_path_buf())
+        } else {
+            None
+        };
+        session_cwd
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default()
+    }
+
     /// Validate a shell command before execution.
     ///
     /// Checks for empty commands and ensures the command doesn't attempt to access
-    /// files that are restricted by ignore patterns.
-    fn validate_shell_command(&self, command: &str) -> Result<(), ErrorData> {
+    /// files that are restricted by ignore patterns. Relative paths are resolved against
+    /// `cwd`, following any `cd` earlier in the command.
+    fn validate_shell_command(&self, command: &str, cwd: &Path) -> Result<(), ErrorData> {
         // Check for empty commands
         if command.trim().is_empty() {
             return Err(ErrorData::new(
@@ -847,19 +1199,20 @@ impl DeveloperServer {
         let cmd_parts: Vec<&str> = command.split_whitespace().collect();
 
         // Check if command arguments reference ignored files
-        for arg in &cmd_parts[1..] {
+        let mut cwd = cwd.to_path_buf();
+        for (i, arg) in cmd_parts.iter().enumerate().skip(1) {
             // Skip command flags
             if arg.starts_with('-') {
                 continue;
             }
 
             // Skip invalid paths
-            let path = Path::new(arg);
+            let path = cwd.join(arg);
             if !path.exists() {
                 continue;
             }
 
-            if self.is_ignored(path) {
+            if self.is_ignored(&path) {
                 return Err(ErrorData::new(
                     ErrorCode::INTERNAL_ERROR,
                     format!(
@@ -869,6 +1222,11 @@ impl DeveloperServer {
                     None,
                 ));
             }
+
+            // Later arguments are relative to the directory a `cd` moves to
+            if cmd_parts[i - 1] == "cd" && path.is_dir() {
+                cwd = path;
+            }
         }
 
         Ok(())
@@ -876,60 +1234,148 @@ impl DeveloperServer {
 
     /// Execute a shell command and return the combined output.
     ///
-    /// Streams output in real-time to the client using logging notifications.
+    /// Streams output in real-time to the client using logging notifications. If
+    /// `timeout_secs` is given, the command and its children are killed once it expires.
     async fn execute_shell_command(
         &self,
         command: &str,
+        timeout_secs: Option<u64>,
         peer: &rmcp::service::Peer<RoleServer>,
     ) -> Result<String, ErrorData> {
-        // Get platform-specific shell configuration
-        let shell_config = get_shell_config();
-
         // Execute the command using platform-specific shell
-        let mut child = Command::new(&shell_config.executable)
+        let mut child = shell_command(command)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .stdin(Stdio::null())
-            .kill_on_drop(true)
-            .env("GOOSE_TERMINAL", "1")
-            .args(&shell_config.args)
-            .arg(command)
             .spawn()
             .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
 
         // Stream the output
-        let output_str = self
-            .stream_shell_output(
-                child.stdout.take().unwrap(),
-                child.stderr.take().unwrap(),
-                peer.clone(),
-            )
-            .await?;
+        let output = Arc::new(Mutex::new(String::new()));
+        let output_task = self.stream_shell_output(
+            child.stdout.take().unwrap(),
+            child.stderr.take().unwrap(),
+            peer.clone(),
+            output.clone(),
+        );
 
-        // Wait for the command to complete
-        child
-            .wait()
-            .await
-            .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        let run = async {
+            output_task.await?;
+            // Wait for the command to complete
+            child
+                .wait()
+                .await
+                .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))
+        };
+
+        let status = match timeout_secs {
+            None => run.await?,
+            Some(secs) => {
+                // Bound first, so the timed out future no longer borrows the child below
+                let result = tokio::time::timeout(Duration::from_secs(secs), run).await;
+                match result {
+                    Ok(status) => status?,
+                    Err(_) => {
+                        kill_process_tree(&mut child);
+                        let partial_output = output.lock().unwrap().clone();
+                        let (partial_output, _) = self.process_shell_output(&partial_output)?;
+                        return Err(ErrorData::new(
+                            ErrorCode::INTERNAL_ERROR,
+                            format!(
+                                "Command timed out after {} seconds and was killed. Run long-running commands in the background instead. Output before the timeout:\n{}",
+                                secs, partial_output
+                            ),
+                            None,
+                        ));
+                    }
+                }
+            }
+        };
 
+        let mut output_str = output.lock().unwrap().clone();
+        append_exit_status(&mut output_str, status.code());
         Ok(output_str)
     }
 
+    /// Execute a shell command in the persistent shell session, starting it if needed.
+    ///
+    /// The session is discarded when the command times out or the shell exits, so the
+    /// next command starts from a fresh shell.
+    async fn execute_in_session(
+        &self,
+        command: &str,
+        timeout_secs: Option<u64>,
+        peer: &rmcp::service::Peer<RoleServer>,
+    ) -> Result<String, ErrorData> {
+        let mut guard = self.shell_session.lock().await;
+        if guard.is_none() {
+            let session = ShellSession::start()
+                .await
+                .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+            *guard = Some(session);
+        }
+        let session = guard.as_mut().unwrap();
+
+        let mut output = String::new();
+        let run = session.run(command, &mut output, Some(peer));
+        let result = match timeout_secs {
+            None => Some(run.await),
+            Some(secs) => tokio::time::timeout(Duration::from_secs(secs), run)
+                .await
+                .ok(),
+        };
+
+        match result {
+            Some(Ok(Some(code))) => {
+                append_exit_status(&mut output, Some(code));
+                Ok(output)
+            }
+            Some(Ok(None)) => {
+                guard.take();
+                output.push_str(
+                    "\nThe shell session exited. The next command will start in a fresh shell.",
+                );
+                Ok(output)
+            }
+            Some(Err(e)) => {
+                guard.take();
+                Err(ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    format!("The shell session failed and was reset: {}", e),
+                    None,
+                ))
+            }
+            None => {
+                guard.take();
+                let (partial_output, _) = self.process_shell_output(&output)?;
+                Err(ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    format!(
+                        "Command timed out after {} seconds and was killed. The shell session was reset, so its working directory and environment are lost. Output before the timeout:\n{}",
+                        timeout_secs.unwrap_or_default(),
+                        partial_output
+                    ),
+                    None,
+                ))
+            }
+        }
+    }
+
     /// Stream shell output in real-time and return the combined output.
     ///
-    /// Merges stdout and stderr streams and sends each line as a logging notification.
+    /// Merges stdout and stderr streams into `combined_output` and sends each line as a
+    /// logging notification.
     async fn stream_shell_output(
         &self,
         stdout: tokio::process::ChildStdout,
         stderr: tokio::process::ChildStderr,
         peer: rmcp::service::Peer<RoleServer>,
-    ) -> Result<String, ErrorData> {
+        combined_output: Arc<Mutex<String>>,
+    ) -> Result<(), ErrorData> {
         let stdout = BufReader::new(stdout);
         let stderr = BufReader::new(stderr);
 
         let output_task = tokio::spawn(async move {
-            let mut combined_output = String::new();
-
             // Merge stdout and stderr streams
             // ref https://blog.yoshuawuyts.com/futures-concurrency-3
             let stdout = SplitStream::new(stdout.split(b'\n')).map(|v| ("stdout", v));
@@ -943,30 +1389,12 @@ impl DeveloperServer {
                 // Convert to UTF-8 to avoid corrupted output
                 let line_str = String::from_utf8_lossy(&line);
 
-                combined_output.push_str(&line_str);
+                combined_output.lock().unwrap().push_str(&line_str);
 
                 // Stream each line back to the client in real-time
-                let trimmed_line = line_str.trim();
-                if !trimmed_line.is_empty() {
-                    // Send the output line as a structured logging message
-                    if let Err(e) = peer
-                        .notify_logging_message(LoggingMessageNotificationParam {
-                            level: LoggingLevel::Info,
-                            data: serde_json::json!({
-                                "type": "shell_output",
-                                "stream": stream_type,
-                                "output": trimmed_line
-                            }),
-                            logger: Some("shell_tool".to_string()),
-                        })
-                        .await
-                    {
-                        // Don't break execution if streaming fails, just log it
-                        eprintln!("Failed to stream output line: {}", e);
-                    }
-                }
+                notify_shell_output(&peer, stream_type, &line_str).await;
             }
-            Ok::<_, std::io::Error>(combined_output)
+            Ok::<_, std::io::Error>(())
         });
 
         match output_task.await {
@@ -1122,6 +1550,181 @@ impl DeveloperServer {
         ]))
     }
 
+    /// Outline the definitions in a source file or directory.
+    ///
+    /// Lists functions, methods, classes, structs and other definitions with their
+    /// line ranges, nested under their enclosing definition.
+    #[tool(
+        name = "code_outline",
+        description = "Outline the functions, methods, classes, structs and other definitions of a source file, or of every source file in a directory, with their line ranges. Supports Rust, Python, TypeScript, Go and Java. Use it to get an overview of code before viewing or editing it."
+    )]
+    pub async fn code_outline(
+        &self,
+        params: Parameters<CodeOutlineParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let root = self.resolve_code_path(Some(&params.0.path))?;
+        self.run_code_search(root, |path, source, language, results| {
+            let symbols = code_nav::outline(source, language);
+            if symbols.is_empty() {
+                return;
+            }
+            results.push(path.display().to_string());
+            results.extend(symbols.iter().map(|s| {
+                format!(
+                    "{}{} {} (lines {}-{})",
+                    "  ".repeat(s.depth + 1),
+                    s.kind,
+                    s.name,
+                    s.start_line,
+                    s.end_line
+                )
+            }));
+        })
+        .await
+    }
+
+    /// Find where a symbol is defined.
+    #[tool(
+        name = "find_symbol",
+        description = "Find the definitions of a function, method, class, struct or other symbol by its exact name. Returns the file, line range and enclosing definition of each match. Supports Rust, Python, TypeScript, Go and Java."
+    )]
+    pub async fn find_symbol(
+        &self,
+        params: Parameters<FindSymbolParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+        let root = self.resolve_code_path(params.path.as_deref())?;
+        let name = params.name;
+        let impl_suffix = format!(" for {}", name);
+        self.run_code_search(root, move |path, source, language, results| {
+            results.extend(
+                code_nav::outline(source, language)
+                    .iter()
+                    .filter(|s| s.name == name || s.name.ends_with(&impl_suffix))
+                    .map(|s| {
+                        format!(
+                            "{}:{}-{} {}",
+                            path.display(),
+                            s.start_line,
+                            s.end_line,
+                            s.describe()
+                        )
+                    }),
+            );
+        })
+        .await
+    }
+
+    /// Find where a symbol is used.
+    #[tool(
+        name = "find_references",
+        description = "Find the references to a symbol by its exact name, excluding its definitions. Matches identifiers in the syntax tree, so comments and strings are skipped. Returns the file, line, column and source line of each reference. Supports Rust, Python, TypeScript, Go and Java."
+    )]
+    pub async fn find_references(
+        &self,
+        params: Parameters<FindSymbolParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let params = params.0;
+        let root = self.resolve_code_path(params.path.as_deref())?;
+        let name = params.name;
+        self.run_code_search(root, move |path, source, language, results| {
+            results.extend(
+                code_nav::references(source, language, &name)
+                    .iter()
+                    .map(|r| format!("{}:{}:{}: {}", path.display(), r.line, r.column, r.text)),
+            );
+        })
+        .await
+    }
+
+    /// Resolve the path of a code navigation tool, defaulting to the current directory
+    fn resolve_code_path(&self, path: Option<&str>) -> Result<PathBuf, ErrorData> {
+        let path = match path {
+            Some(path) => self.resolve_path(path)?,
+            None => std::env::current_dir()
+                .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?,
+        };
+        if !path.exists() {
+            return Err(ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("The path '{}' does not exist", path.display()),
+                None,
+            ));
+        }
+        if self.is_ignored(&path) {
+            return Err(ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                format!(
+                    "Access to '{}' is restricted by .gooseignore",
+                    path.display()
+                ),
+                None,
+            ));
+        }
+        Ok(path)
+    }
+
+    /// Parse every supported source file under `root` and collect the lines `search`
+    /// produces for each, skipping files restricted by .gooseignore.
+    async fn run_code_search<F>(
+        &self,
+        root: PathBuf,
+        search: F,
+    ) -> Result<CallToolResult, ErrorData>
+    where
+        F: Fn(&Path, &str, CodeLanguage, &mut Vec<String>) + Send + 'static,
+    {
+        const MAX_RESULTS: usize = 500;
+
+        let ignore_patterns = self.ignore_patterns.clone();
+        let (results, files_truncated) = tokio::task::spawn_blocking(move || {
+            let (files, truncated) = code_nav::source_files(&root, |path| {
+                ignore_patterns.matched(path, false).is_ignore()
+            });
+            let mut results = Vec::new();
+            for file in files {
+                let Some(language) = CodeLanguage::from_path(&file) else {
+                    continue;
+                };
+                let Ok(source) = std::fs::read_to_string(&file) else {
+                    continue;
+                };
+                search(&file, &source, language, &mut results);
+                if results.len() > MAX_RESULTS {
+                    break;
+                }
+            }
+            (results, truncated)
+        })
+        .await
+        .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+
+        let mut output = if results.is_empty() {
+            "No results found in supported source files (Rust, Python, TypeScript, Go, Java)"
+                .to_string()
+        } else {
+            results
+                .iter()
+                .take(MAX_RESULTS)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        if results.len() > MAX_RESULTS {
+            output.push_str(&format!(
+                "\n\nOnly the first {} results are shown, narrow the search with a more specific path.",
+                MAX_RESULTS
+            ));
+        } else if files_truncated {
+            output.push_str(&format!(
+                "\n\nOnly the first {} files were searched, narrow the search with a more specific path.",
+                code_nav::MAX_FILES
+            ));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
     // Helper method to resolve and validate file paths
     fn resolve_path(&self, path_str: &str) -> Result<PathBuf, ErrorData> {
         let cwd = std::env::current_dir().expect("should have a current working dir");
@@ -1370,6 +1973,9 @@ mod tests {
                 .shell(
                     Parameters(ShellParams {
                         command: "".to_string(),
+                        timeout_secs: None,
+                        background: false,
+                        reset: false,
                     }),
                     RequestContext {
                         ct: Default::default(),
@@ -1390,6 +1996,180 @@ mod tests {
         });
     }
 
+    #[test]
+    #[serial]
+    #[cfg(unix)]
+    fn test_shell_timeout_kills_command() {
+        run_shell_test(|| async {
+            let temp_dir = tempfile::tempdir().unwrap();
+            std::env::set_current_dir(&temp_dir).unwrap();
+
+            let server = create_test_server();
+            let running_service = serve_directly(server.clone(), create_test_transport(), None);
+            let peer = running_service.peer().clone();
+
+            let started = std::time::Instant::now();
+            let result = server
+                .shell(
+                    Parameters(ShellParams {
+                        command: "echo started; sleep 30".to_string(),
+                        timeout_secs: Some(1),
+                        background: false,
+                        reset: false,
+                    }),
+                    RequestContext {
+                        ct: Default::default(),
+                        id: NumberOrString::Number(1),
+                        meta: Default::default(),
+                        extensions: Default::default(),
+                        peer: peer.clone(),
+                    },
+                )
+                .await;
+
+            assert!(started.elapsed() < std::time::Duration::from_secs(10));
+            let err = result.err().unwrap();
+            assert!(err.message.contains("timed out after 1 seconds"));
+            assert!(err.message.contains("started"));
+
+            cleanup_test_service(running_service, peer);
+        });
+    }
+
+    #[test]
+    #[serial]
+    #[cfg(unix)]
+    fn test_shell_background_job() {
+        run_shell_test(|| async {
+            let temp_dir = tempfile::tempdir().unwrap();
+            std::env::set_current_dir(&temp_dir).unwrap();
+
+            let server = create_test_server();
+            let running_service = serve_directly(server.clone(), create_test_transport(), None);
+            let peer = running_service.peer().clone();
+
+            let result = server
+                .shell(
+                    Parameters(ShellParams {
+                        command: "sleep 30".to_string(),
+                        timeout_secs: None,
+                        background: true,
+                        reset: false,
+                    }),
+                    RequestContext {
+                        ct: Default::default(),
+                        id: NumberOrString::Number(1),
+                        meta: Default::default(),
+                        extensions: Default::default(),
+                        peer: peer.clone(),
+                    },
+                )
+                .await
+                .unwrap();
+            let text = result.content[0].as_text().unwrap().text.clone();
+            assert!(text.starts_with("Started background job 1."));
+
+            let list = server
+                .shell_job(Parameters(ShellJobParams {
+                    action: "list".to_string(),
+                    job_id: None,
+                    input: None,
+                }))
+                .await
+                .unwrap();
+            let text = list.content[0].as_text().unwrap().text.clone();
+            assert!(text.starts_with("1: running for"));
+            assert!(text.ends_with("sleep 30"));
+
+            let killed = server
+                .shell_job(Parameters(ShellJobParams {
+                    action: "kill".to_string(),
+                    job_id: Some(1),
+                    input: None,
+                }))
+                .await
+                .unwrap();
+            let text = killed.content[0].as_text().unwrap().text.clone();
+            assert!(text.starts_with("Job 1 terminated by signal"));
+
+            let missing = server
+                .shell_job(Parameters(ShellJobParams {
+                    action: "output".to_string(),
+                    job_id: Some(1),
+                    input: None,
+                }))
+                .await;
+            assert_eq!(missing.err().unwrap().code, ErrorCode::INVALID_PARAMS);
+
+            cleanup_test_service(running_service, peer);
+        });
+    }
+
+    #[test]
+    #[serial]
+    #[cfg(unix)]
+    fn test_persistent_shell_keeps_state_until_reset() {
+        run_shell_test(|| async {
+            let temp_dir = tempfile::tempdir().unwrap();
+            std::env::set_current_dir(&temp_dir).unwrap();
+            fs::create_dir(temp_dir.path().join("nested")).unwrap();
+            fs::write(".gooseignore", "secret.txt").unwrap();
+            fs::write(temp_dir.path().join("nested/secret.txt"), "hidden").unwrap();
+
+            let mut server = create_test_server();
+            server.persistent_shell = true;
+            let running_service = serve_directly(server.clone(), create_test_transport(), None);
+            let peer = running_service.peer().clone();
+
+            let run = |command: &str, reset: bool| {
+                server.shell(
+                    Parameters(ShellParams {
+                        command: command.to_string(),
+                        timeout_secs: None,
+                        background: false,
+                        reset,
+                    }),
+                    RequestContext {
+                        ct: Default::default(),
+                        id: NumberOrString::Number(1),
+                        meta: Default::default(),
+                        extensions: Default::default(),
+                        peer: peer.clone(),
+                    },
+                )
+            };
+            let output = |result: Result<CallToolResult, ErrorData>| {
+                result.unwrap().content[0].as_text().unwrap().text.clone()
+            };
+
+            // Ignored files are found through a `cd` in the same command
+            assert!(run("cd nested && cat secret.txt", false).await.is_err());
+
+            output(run("cd nested && export GOOSE_TEST_VAR=kept", false).await);
+            assert_eq!(
+                output(run("basename \"$PWD\"; echo $GOOSE_TEST_VAR", false).await),
+                "nested\nkept\n"
+            );
+
+            // and relative to the directory an earlier command moved the shell to
+            assert!(run("cat secret.txt", false).await.is_err());
+
+            // Failures are reported with their exit code
+            assert_eq!(
+                output(run("echo failing; (exit 2)", false).await),
+                "failing\n\nCommand exited with code 2"
+            );
+
+            assert_eq!(output(run("", true).await), "The shell session was reset");
+            assert_eq!(
+                output(run("echo \"[$GOOSE_TEST_VAR]\"", false).await),
+                "[]\n"
+            );
+
+            cleanup_test_service(running_service, peer);
+        });
+    }
+
     #[test]
     #[serial]
     #[cfg(windows)]
@@ -1405,6 +2185,9 @@ mod tests {
             // Test PowerShell command
             let shell_params = Parameters(ShellParams {
                 command: "Get-ChildItem".to_string(),
+                timeout_secs: None,
+                background: false,
+                reset: false,
             });
 
             let result = server
@@ -1458,6 +2241,7 @@ mod tests {
                 new_str: None,
                 insert_line: None,
                 diff: None,
+                check_command: None,
             });
 
             let result = server.text_editor(view_params).await;
@@ -1485,6 +2269,7 @@ mod tests {
                 new_str: None,
                 insert_line: None,
                 diff: None,
+                check_command: None,
             });
 
             let result = server.text_editor(view_params).await;
@@ -1516,6 +2301,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -1530,6 +2316,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         let view_result = server.text_editor(view_params).await.unwrap();
@@ -1568,6 +2355,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -1582,6 +2370,7 @@ mod tests {
             new_str: Some("Rust".to_string()),
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         let replace_result = server.text_editor(replace_params).await.unwrap();
@@ -1607,6 +2396,106 @@ mod tests {
         assert!(content.contains("Hello, Rust!"));
     }
 
+    fn change_set_params(path: &Path, command: &str) -> Parameters<TextEditorParams> {
+        Parameters(TextEditorParams {
+            path: path.to_str().unwrap().to_string(),
+            command: command.to_string(),
+            view_range: None,
+            file_text: None,
+            old_str: None,
+            new_str: None,
+            insert_line: None,
+            diff: None,
+            check_command: None,
+        })
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_text_editor_change_set_rollback() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::env::set_current_dir(&temp_dir).unwrap();
+        let existing = temp_dir.path().join("existing.txt");
+        let created = temp_dir.path().join("created.txt");
+        fs::write(&existing, "line one\nline two\n").unwrap();
+
+        let server = create_test_server();
+        server
+            .text_editor(change_set_params(temp_dir.path(), "begin"))
+            .await
+            .unwrap();
+        assert!(server
+            .text_editor(change_set_params(temp_dir.path(), "begin"))
+            .await
+            .is_err());
+
+        let mut diff = change_set_params(temp_dir.path(), "str_replace");
+        diff.0.diff = Some(
+            "--- a/existing.txt\n+++ b/existing.txt\n@@ -1,2 +1,2 @@\n-line one\n+line 1\n line two\n"
+                .to_string(),
+        );
+        server.text_editor(diff).await.unwrap();
+        let mut write = change_set_params(&created, "write");
+        write.0.file_text = Some("new file".to_string());
+        server.text_editor(write).await.unwrap();
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "line 1\nline two\n");
+
+        let result = server
+            .text_editor(change_set_params(temp_dir.path(), "rollback"))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("created.txt") && text.contains("existing.txt"));
+        assert_eq!(
+            fs::read_to_string(&existing).unwrap(),
+            "line one\nline two\n"
+        );
+        assert!(!created.exists());
+        assert!(server
+            .text_editor(change_set_params(temp_dir.path(), "commit"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    #[serial]
+    #[cfg(unix)]
+    async fn test_text_editor_change_set_commit_runs_check() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::env::set_current_dir(&temp_dir).unwrap();
+        let file = temp_dir.path().join("config.txt");
+        fs::write(&file, "valid\n").unwrap();
+
+        let server = create_test_server();
+        let mut begin = change_set_params(temp_dir.path(), "begin");
+        begin.0.check_command = Some("grep -q valid config.txt".to_string());
+
+        // A failing check rolls back every edit
+        server.text_editor(begin).await.unwrap();
+        let mut write = change_set_params(&file, "write");
+        write.0.file_text = Some("broken".to_string());
+        server.text_editor(write).await.unwrap();
+        let err = server
+            .text_editor(change_set_params(temp_dir.path(), "commit"))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("rolled back"));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "valid\n");
+
+        // A passing check keeps them
+        let mut begin = change_set_params(temp_dir.path(), "begin");
+        begin.0.check_command = Some("grep -q valid config.txt".to_string());
+        server.text_editor(begin).await.unwrap();
+        let mut write = change_set_params(&file, "write");
+        write.0.file_text = Some("still valid".to_string());
+        server.text_editor(write).await.unwrap();
+        server
+            .text_editor(change_set_params(temp_dir.path(), "commit"))
+            .await
+            .unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "still valid\n");
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_text_editor_undo_edit() {
@@ -1627,6 +2516,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -1641,6 +2531,7 @@ mod tests {
             new_str: Some("Modified".to_string()),
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(replace_params).await.unwrap();
@@ -1659,6 +2550,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         let undo_result = server.text_editor(undo_params).await.unwrap();
@@ -1677,6 +2569,75 @@ mod tests {
         assert!(undo_content.text.contains("Undid the last edit"));
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_code_navigation_respects_gooseignore() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::env::set_current_dir(&temp_dir).unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+
+        fs::write(".gooseignore", "secret.rs").unwrap();
+        fs::write(
+            root.join("lib.rs"),
+            "pub fn parse() {}\n\nfn main() {\n    parse();\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("secret.rs"), "fn parse() {}\n").unwrap();
+
+        let server = create_test_server();
+        let text = |result: Result<CallToolResult, ErrorData>| {
+            result.unwrap().content[0].as_text().unwrap().text.clone()
+        };
+
+        let outline = text(
+            server
+                .code_outline(Parameters(CodeOutlineParams {
+                    path: root.to_string_lossy().to_string(),
+                }))
+                .await,
+        );
+        assert_eq!(
+            outline,
+            format!(
+                "{}\n  fn parse (lines 1-1)\n  fn main (lines 3-5)",
+                root.join("lib.rs").display()
+            )
+        );
+
+        let symbols = text(
+            server
+                .find_symbol(Parameters(FindSymbolParams {
+                    name: "parse".to_string(),
+                    path: None,
+                }))
+                .await,
+        );
+        assert_eq!(
+            symbols,
+            format!("{}:1-1 fn parse (lines 1-1)", root.join("lib.rs").display())
+        );
+
+        let references = text(
+            server
+                .find_references(Parameters(FindSymbolParams {
+                    name: "parse".to_string(),
+                    path: Some(root.to_string_lossy().to_string()),
+                }))
+                .await,
+        );
+        assert_eq!(
+            references,
+            format!("{}:4:5: parse();", root.join("lib.rs").display())
+        );
+
+        let result = server
+            .code_outline(Parameters(CodeOutlineParams {
+                path: root.join("secret.rs").to_string_lossy().to_string(),
+            }))
+            .await;
+        assert!(result.is_err());
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_goose_ignore_basic_patterns() {
@@ -1739,6 +2700,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         let result = server.text_editor(write_params).await;
@@ -1759,6 +2721,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         let result = server.text_editor(write_params).await;
@@ -1788,6 +2751,9 @@ mod tests {
                 .shell(
                     Parameters(ShellParams {
                         command: format!("cat {}", secret_file_path.to_str().unwrap()),
+                        timeout_secs: None,
+                        background: false,
+                        reset: false,
                     }),
                     RequestContext {
                         ct: Default::default(),
@@ -1810,6 +2776,9 @@ mod tests {
                 .shell(
                     Parameters(ShellParams {
                         command: format!("cat {}", allowed_file_path.to_str().unwrap()),
+                        timeout_secs: None,
+                        background: false,
+                        reset: false,
                     }),
                     RequestContext {
                         ct: Default::default(),
@@ -1926,6 +2895,7 @@ mod tests {
                 view_range: None,
                 insert_line: None,
                 diff: None,
+                check_command: None,
             }))
             .await;
 
@@ -1950,6 +2920,7 @@ mod tests {
                 view_range: None,
                 insert_line: None,
                 diff: None,
+                check_command: None,
             }))
             .await;
 
@@ -1984,6 +2955,9 @@ mod tests {
                 .shell(
                     Parameters(ShellParams {
                         command: format!("cat {}", log_file_path.to_str().unwrap()),
+                        timeout_secs: None,
+                        background: false,
+                        reset: false,
                     }),
                     RequestContext {
                         ct: Default::default(),
@@ -2009,6 +2983,9 @@ mod tests {
                 .shell(
                     Parameters(ShellParams {
                         command: format!("cat {}", allowed_file_path.to_str().unwrap()),
+                        timeout_secs: None,
+                        background: false,
+                        reset: false,
                     }),
                     RequestContext {
                         ct: Default::default(),
@@ -2051,6 +3028,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -2065,6 +3043,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         let view_result = server.text_editor(view_params).await.unwrap();
@@ -2112,6 +3091,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -2126,6 +3106,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         let view_result = server.text_editor(view_params).await.unwrap();
@@ -2172,6 +3153,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -2186,6 +3168,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         let result = server.text_editor(view_params).await;
@@ -2216,6 +3199,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -2230,6 +3214,7 @@ mod tests {
             new_str: Some("Line 1".to_string()),
             insert_line: Some(0),
             diff: None,
+            check_command: None,
         });
 
         let insert_result = server.text_editor(insert_params).await.unwrap();
@@ -2273,6 +3258,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -2287,6 +3273,7 @@ mod tests {
             new_str: Some("Line 3".to_string()),
             insert_line: Some(2),
             diff: None,
+            check_command: None,
         });
 
         let insert_result = server.text_editor(insert_params).await.unwrap();
@@ -2335,6 +3322,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -2349,6 +3337,7 @@ mod tests {
             new_str: Some("Line 4".to_string()),
             insert_line: Some(3),
             diff: None,
+            check_command: None,
         });
 
         let insert_result = server.text_editor(insert_params).await.unwrap();
@@ -2392,6 +3381,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -2406,6 +3396,7 @@ mod tests {
             new_str: Some("Line 4".to_string()),
             insert_line: Some(-1),
             diff: None,
+            check_command: None,
         });
 
         let insert_result = server.text_editor(insert_params).await.unwrap();
@@ -2449,6 +3440,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -2463,6 +3455,7 @@ mod tests {
             new_str: Some("Line 11".to_string()),
             insert_line: Some(10),
             diff: None,
+            check_command: None,
         });
 
         let result = server.text_editor(insert_params).await;
@@ -2493,6 +3486,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -2507,6 +3501,7 @@ mod tests {
             new_str: None, // Missing required parameter
             insert_line: Some(1),
             diff: None,
+            check_command: None,
         });
 
         let result = server.text_editor(insert_params).await;
@@ -2525,6 +3520,7 @@ mod tests {
             new_str: Some("New text".to_string()),
             insert_line: None, // Missing required parameter
             diff: None,
+            check_command: None,
         });
 
         let result = server.text_editor(insert_params).await;
@@ -2555,6 +3551,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -2569,6 +3566,7 @@ mod tests {
             new_str: Some("Inserted Line".to_string()),
             insert_line: Some(1),
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(insert_params).await.unwrap();
@@ -2583,6 +3581,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         let undo_result = server.text_editor(undo_params).await.unwrap();
@@ -2622,6 +3621,7 @@ mod tests {
             new_str: Some("New line".to_string()),
             insert_line: Some(0),
             diff: None,
+            check_command: None,
         });
 
         let result = server.text_editor(insert_params).await;
@@ -2657,6 +3657,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -2671,6 +3672,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         let result = server.text_editor(view_params).await;
@@ -2696,6 +3698,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         let result = server.text_editor(view_params).await;
@@ -2728,6 +3731,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         let result = server.text_editor(view_params).await;
@@ -2759,6 +3763,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -2773,6 +3778,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         let result = server.text_editor(view_params).await;
@@ -2820,6 +3826,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         server.text_editor(write_params).await.unwrap();
@@ -2834,6 +3841,7 @@ mod tests {
             new_str: None,
             insert_line: None,
             diff: None,
+            check_command: None,
         });
 
         let result = server.text_editor(view_params).await;
@@ -2878,6 +3886,9 @@ mod tests {
                 .shell(
                     Parameters(ShellParams {
                         command: command.to_string(),
+                        timeout_secs: None,
+                        background: false,
+                        reset: false,
                     }),
                     RequestContext {
                         ct: Default::default(),
@@ -3024,6 +4035,9 @@ mod tests {
                 .shell(
                     Parameters(ShellParams {
                         command: command.to_string(),
+                        timeout_secs: None,
+                        background: false,
+                        reset: false,
                     }),
                     RequestContext {
                         ct: Default::default(),
@@ -0,0 +1,382 @@
+use std::{
+    collections::HashMap,
+    process::{ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex as StdMutex,
+    },
+    time::{Duration, Instant},
+};
+
+use rmcp::model::{ErrorCode, ErrorData};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    process::{Child, ChildStdin, Command},
+    sync::Mutex,
+};
+use tokio_stream::{wrappers::SplitStream, StreamExt as _};
+
+use super::shell::get_shell_config;
+
+/// Maximum output kept in memory per background job, older output is discarded first
+const MAX_JOB_OUTPUT: usize = 512 * 1024;
+
+/// Maximum finished jobs kept around for their status and output, the oldest go first
+const MAX_FINISHED_JOBS: usize = 20;
+
+/// Build a command running `command` through the platform shell.
+///
+/// On unix the command is started in its own process group so that the whole
+/// process tree can be killed, not just the shell.
+pub fn shell_command(command: &str) -> Command {
+    let shell_config = get_shell_config();
+
+    let mut cmd = Command::new(&shell_config.executable);
+    cmd.kill_on_drop(true)
+        .env("GOOSE_TERMINAL", "1")
+        .args(&shell_config.args)
+        .arg(command);
+    #[cfg(unix)]
+    cmd.process_group(0);
+    cmd
+}
+
+/// Kill a process started by [`shell_command`] together with all of its children
+pub fn kill_process_tree(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        use nix::sys::signal::{killpg, Signal};
+        use nix::unistd::Pid;
+
+        let _ = killpg(Pid::from_raw(pid as i32), Signal::SIGKILL);
+    }
+    let _ = child.start_kill();
+}
+
+/// Output of a background job, trimmed from the front once it grows too large
+#[derive(Debug, Default)]
+struct JobOutput {
+    text: String,
+    /// Number of bytes discarded from the front of `text` so far
+    discarded: usize,
+}
+
+impl JobOutput {
+    fn push(&mut self, line: &str) {
+        self.text.push_str(line);
+        if self.text.len() > MAX_JOB_OUTPUT {
+            let mut cut = self.text.len() - MAX_JOB_OUTPUT / 2;
+            while !self.text.is_char_boundary(cut) {
+                cut += 1;
+            }
+            self.text.drain(..cut);
+            self.discarded += cut;
+        }
+    }
+
+    /// Output after the absolute byte `offset`, and the offset to continue reading from
+    fn read_from(&self, offset: usize) -> (&str, usize, bool) {
+        let truncated = offset < self.discarded;
+        let start = offset.saturating_sub(self.discarded).min(self.text.len());
+        (
+            &self.text[start..],
+            self.discarded + self.text.len(),
+            truncated,
+        )
+    }
+}
+
+#[derive(Debug)]
+struct ShellJob {
+    command: String,
+    child: Child,
+    stdin: Option<ChildStdin>,
+    output: Arc<StdMutex<JobOutput>>,
+    read_offset: usize,
+    started: Instant,
+    exit_status: Option<ExitStatus>,
+}
+
+impl ShellJob {
+    fn is_finished(&mut self) -> bool {
+        if self.exit_status.is_none() {
+            self.exit_status = self.child.try_wait().ok().flatten();
+        }
+        self.exit_status.is_some()
+    }
+
+    fn status(&mut self) -> String {
+        self.is_finished();
+        match self.exit_status {
+            None => format!("running for {}s", self.started.elapsed().as_secs()),
+            Some(status) => match status.code() {
+                Some(code) => format!("exited with code {}", code),
+                None => "terminated by signal".to_string(),
+            },
+        }
+    }
+
+    /// Output produced since the last read
+    fn take_output(&mut self) -> String {
+        let output = self.output.lock().unwrap();
+        let (text, next_offset, truncated) = output.read_from(self.read_offset);
+        let text = if truncated {
+            format!(
+                "[earlier output was discarded, only the most recent output is kept]\n{}",
+                text
+            )
+        } else {
+            text.to_string()
+        };
+        self.read_offset = next_offset;
+        text
+    }
+}
+
+/// Registry of shell commands running in the background.
+///
+/// Finished jobs are kept until killed, up to [`MAX_FINISHED_JOBS`] of them. Every job
+/// still running when the registry is dropped is killed, so no processes outlive the
+/// developer extension.
+#[derive(Debug, Default)]
+pub struct ShellJobs {
+    jobs: Mutex<HashMap<u32, ShellJob>>,
+    next_id: AtomicU32,
+}
+
+/// Drop the oldest finished jobs beyond [`MAX_FINISHED_JOBS`]
+fn prune_finished(jobs: &mut HashMap<u32, ShellJob>) {
+    let mut finished: Vec<u32> = jobs
+        .iter_mut()
+        .filter_map(|(id, job)| job.is_finished().then_some(*id))
+        .collect();
+    if finished.len() <= MAX_FINISHED_JOBS {
+        return;
+    }
+    finished.sort_unstable();
+    for id in &finished[..finished.len() - MAX_FINISHED_JOBS] {
+        jobs.remove(id);
+    }
+}
+
+fn job_not_found(id: u32) -> ErrorData {
+    ErrorData::new(
+        ErrorCode::INVALID_PARAMS,
+        format!("No background job with id {}", id),
+        None,
+    )
+}
+
+impl ShellJobs {
+    /// Start `command` in the background and return its job id
+    pub async fn spawn(&self, command: &str) -> Result<u32, ErrorData> {
+        let mut child = shell_command(command)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+
+        let output = Arc::new(StdMutex::new(JobOutput::default()));
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        let stderr = BufReader::new(child.stderr.take().unwrap());
+        let job_output = output.clone();
+        tokio::spawn(async move {
+            let stdout = SplitStream::new(stdout.split(b'\n'));
+            let stderr = SplitStream::new(stderr.split(b'\n'));
+            let mut merged = stdout.merge(stderr);
+            while let Some(Ok(mut line)) = merged.next().await {
+                line.push(b'\n');
+                job_output
+                    .lock()
+                    .unwrap()
+                    .push(&String::from_utf8_lossy(&line));
+            }
+        });
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let stdin = child.stdin.take();
+        let mut jobs = self.jobs.lock().await;
+        prune_finished(&mut jobs);
+        jobs.insert(
+            id,
+            ShellJob {
+                command: command.to_string(),
+                child,
+                stdin,
+                output,
+                read_offset: 0,
+                started: Instant::now(),
+                exit_status: None,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Status of a job and the output it produced since the last read
+    pub async fn read_output(&self, id: u32) -> Result<(String, String), ErrorData> {
+        let mut jobs = self.jobs.lock().await;
+        let job = jobs.get_mut(&id).ok_or_else(|| job_not_found(id))?;
+        let status = job.status();
+        Ok((status, job.take_output()))
+    }
+
+    /// Write `input` to the stdin of a running job
+    pub async fn send_input(&self, id: u32, input: &str) -> Result<(), ErrorData> {
+        let mut jobs = self.jobs.lock().await;
+        let job = jobs.get_mut(&id).ok_or_else(|| job_not_found(id))?;
+        let stdin = job.stdin.as_mut().ok_or_else(|| {
+            ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("The stdin of job {} is closed", id),
+                None,
+            )
+        })?;
+        let result = async {
+            stdin.write_all(input.as_bytes()).await?;
+            stdin.flush().await
+        }
+        .await;
+        if let Err(e) = result {
+            job.stdin = None;
+            return Err(ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                format!("Failed to write to job {}: {}", id, e),
+                None,
+            ));
+        }
+        Ok(())
+    }
+
+    /// One line per job with its id, status and command
+    pub async fn list(&self) -> Vec<String> {
+        let mut jobs = self.jobs.lock().await;
+        let mut ids: Vec<u32> = jobs.keys().copied().collect();
+        ids.sort_unstable();
+        ids.into_iter()
+            .filter_map(|id| {
+                let job = jobs.get_mut(&id)?;
+                Some(format!("{}: {} - {}", id, job.status(), job.command))
+            })
+            .collect()
+    }
+
+    /// Kill a job with all its child processes and return its final status and unread output
+    pub async fn kill(&self, id: u32) -> Result<(String, String), ErrorData> {
+        let mut job = self
+            .jobs
+            .lock()
+            .await
+            .remove(&id)
+            .ok_or_else(|| job_not_found(id))?;
+        if job.child.try_wait().ok().flatten().is_none() {
+            kill_process_tree(&mut job.child);
+            let _ = tokio::time::timeout(Duration::from_secs(5), job.child.wait()).await;
+        }
+        let status = job.status();
+        Ok((status, job.take_output()))
+    }
+}
+
+impl Drop for ShellJobs {
+    fn drop(&mut self) {
+        for job in self.jobs.get_mut().values_mut() {
+            kill_process_tree(&mut job.child);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    fn test_job_output_discards_oldest_output() {
+        let mut output = JobOutput::default();
+        output.push("first\n");
+        let (text, offset, truncated) = output.read_from(0);
+        assert_eq!(text, "first\n");
+        assert!(!truncated);
+
+        output.push(&"x".repeat(MAX_JOB_OUTPUT));
+        let (text, next, truncated) = output.read_from(offset);
+        assert!(truncated);
+        assert!(text.len() <= MAX_JOB_OUTPUT);
+        assert_eq!(next, "first\n".len() + MAX_JOB_OUTPUT);
+        assert_eq!(output.read_from(next).0, "");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[serial]
+    async fn test_background_job_lifecycle() {
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_current_dir(dir.path()).unwrap();
+        let jobs = ShellJobs::default();
+        let id = jobs.spawn("read line; echo \"got $line\"").await.unwrap();
+        assert_eq!(jobs.list().await.len(), 1);
+
+        jobs.send_input(id, "hello\n").await.unwrap();
+        let mut output = String::new();
+        let mut status = String::new();
+        for _ in 0..50 {
+            let (job_status, job_output) = jobs.read_output(id).await.unwrap();
+            output.push_str(&job_output);
+            status = job_status;
+            if !status.starts_with("running") {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(status, "exited with code 0");
+
+        let (status, unread) = jobs.kill(id).await.unwrap();
+        output.push_str(&unread);
+        assert_eq!(output, "got hello\n");
+        assert_eq!(status, "exited with code 0");
+        assert!(jobs.list().await.is_empty());
+        assert!(jobs.read_output(id).await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[serial]
+    async fn test_finished_jobs_are_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_current_dir(dir.path()).unwrap();
+        let jobs = ShellJobs::default();
+        for _ in 0..MAX_FINISHED_JOBS + 2 {
+            jobs.spawn("true").await.unwrap();
+        }
+        for _ in 0..50 {
+            let list = jobs.list().await;
+            if !list.iter().any(|line| line.contains("running")) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        let id = jobs.spawn("sleep 30").await.unwrap();
+        assert_eq!(jobs.list().await.len(), MAX_FINISHED_JOBS + 1);
+        assert!(jobs.read_output(1).await.is_err());
+        assert!(jobs.read_output(2).await.is_err());
+        assert!(jobs.read_output(3).await.is_ok());
+        jobs.kill(id).await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[serial]
+    async fn test_kill_stops_child_processes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_current_dir(dir.path()).unwrap();
+        let jobs = ShellJobs::default();
+        let id = jobs.spawn("sleep 30 & sleep 30; echo done").await.unwrap();
+        let started = Instant::now();
+        let (status, output) = jobs.kill(id).await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(status, "terminated by signal");
+        assert_eq!(output, "");
+    }
+}
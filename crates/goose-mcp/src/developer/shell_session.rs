@@ -0,0 +1,243 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rmcp::{
+    model::{LoggingLevel, LoggingMessageNotificationParam},
+    service::Peer,
+    RoleServer,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    process::{Child, ChildStdin, ChildStdout, Command},
+};
+
+use super::shell::get_shell_config;
+use super::shell_jobs::kill_process_tree;
+
+/// Environment variable that enables the persistent shell session
+pub const GOOSE_SHELL_PERSISTENT: &str = "GOOSE_SHELL_PERSISTENT";
+
+/// Whether shell commands should share one long-lived shell process
+pub fn persistent_shell_enabled() -> bool {
+    cfg!(unix)
+        && std::env::var(GOOSE_SHELL_PERSISTENT)
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false)
+}
+
+/// Send a line of shell output to the client as a logging notification
+pub async fn notify_shell_output(peer: &Peer<RoleServer>, stream_type: &str, line: &str) {
+    let trimmed_line = line.trim();
+    if trimmed_line.is_empty() {
+        return;
+    }
+    if let Err(e) = peer
+        .notify_logging_message(LoggingMessageNotificationParam {
+            level: LoggingLevel::Info,
+            data: serde_json::json!({
+                "type": "shell_output",
+                "stream": stream_type,
+                "output": trimmed_line
+            }),
+            logger: Some("shell_tool".to_string()),
+        })
+        .await
+    {
+        // Don't break execution if streaming fails, just log it
+        eprintln!("Failed to stream output line: {}", e);
+    }
+}
+
+/// A random-enough marker that will not show up in regular command output
+fn new_nonce() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    format!(
+        "{:x}{:x}{:x}",
+        std::process::id(),
+        nanos,
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// A long-lived shell process that runs commands one after another, so the working
+/// directory, environment variables and shell functions carry over between commands.
+///
+/// Commands are written to the shell's stdin and followed by a sentinel line carrying
+/// the exit status and working directory; the output of a command is everything the
+/// shell prints before the sentinel.
+#[derive(Debug)]
+pub struct ShellSession {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    nonce: String,
+    cwd: PathBuf,
+}
+
+impl ShellSession {
+    pub async fn start() -> std::io::Result<Self> {
+        let shell_config = get_shell_config();
+        let mut command = Command::new(&shell_config.executable);
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .env("GOOSE_TERMINAL", "1");
+        #[cfg(unix)]
+        command.process_group(0);
+
+        let mut child = command.spawn()?;
+        let mut stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        // Merge stderr into stdout for everything the session runs
+        stdin.write_all(b"exec 2>&1\n").await?;
+
+        Ok(Self {
+            child,
+            stdin,
+            stdout,
+            nonce: new_nonce(),
+            cwd: std::env::current_dir()?,
+        })
+    }
+
+    /// The shell's working directory after the last command, which `cd` may have moved
+    /// away from the working directory of this process
+    pub fn cwd(&self) -> &Path {
+        &self.cwd
+    }
+
+    /// Run `command` in the session, appending its output to `output` as it arrives.
+    ///
+    /// Returns the exit code of the command, or None if the shell exited while running
+    /// it (for example on `exit`), in which case the session can not be used any more.
+    pub async fn run(
+        &mut self,
+        command: &str,
+        output: &mut String,
+        peer: Option<&Peer<RoleServer>>,
+    ) -> std::io::Result<Option<i32>> {
+        let delimiter = format!("__GOOSE_CMD_{}__", self.nonce);
+        let sentinel = format!("__GOOSE_DONE_{}__", self.nonce);
+        // eval keeps syntax errors in the command from breaking the framing, and stdin
+        // is redirected so commands can't read the rest of the session input
+        let script = format!(
+            "eval \"$(cat <<'{delimiter}'\n{command}\n{delimiter}\n)\" < /dev/null\nprintf '\\n{sentinel} %d %s\\n' \"$?\" \"$PWD\"\n"
+        );
+        self.stdin.write_all(script.as_bytes()).await?;
+        self.stdin.flush().await?;
+
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            if self.stdout.read_until(b'\n', &mut line).await? == 0 {
+                return Ok(None);
+            }
+            let line_str = String::from_utf8_lossy(&line);
+            if let Some((status, cwd)) = line_str
+                .trim_end_matches('\n')
+                .strip_prefix(&format!("{} ", sentinel))
+                .and_then(|rest| rest.split_once(' '))
+            {
+                // Drop the newline printed in front of the sentinel
+                if output.ends_with('\n') {
+                    output.pop();
+                }
+                self.cwd = PathBuf::from(cwd);
+                return Ok(Some(status.parse().unwrap_or(-1)));
+            }
+            output.push_str(&line_str);
+            if let Some(peer) = peer {
+                notify_shell_output(peer, "stdout", &line_str).await;
+            }
+        }
+    }
+}
+
+impl Drop for ShellSession {
+    fn drop(&mut self) {
+        kill_process_tree(&mut self.child);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    async fn run(session: &mut ShellSession, command: &str) -> String {
+        let mut output = String::new();
+        assert!(session
+            .run(command, &mut output, None)
+            .await
+            .unwrap()
+            .is_some());
+        output
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_state_persists_between_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_current_dir(dir.path()).unwrap();
+        let mut session = ShellSession::start().await.unwrap();
+
+        run(&mut session, "mkdir sub && cd sub").await;
+        run(
+            &mut session,
+            "export GOOSE_TEST_VAR=kept; greet() { echo \"hi $1\"; }",
+        )
+        .await;
+        let output = run(
+            &mut session,
+            "basename \"$PWD\"; echo $GOOSE_TEST_VAR; greet goose",
+        )
+        .await;
+        assert_eq!(output, "sub\nkept\nhi goose\n");
+        assert_eq!(
+            session.cwd().canonicalize().unwrap(),
+            dir.path().join("sub").canonicalize().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_output_framing() {
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_current_dir(dir.path()).unwrap();
+        let mut session = ShellSession::start().await.unwrap();
+
+        assert_eq!(run(&mut session, "printf 'no newline'").await, "no newline");
+        assert_eq!(run(&mut session, "echo oops >&2").await, "oops\n");
+        // A syntax error must not swallow the sentinel
+        assert!(run(&mut session, "echo 'unterminated")
+            .await
+            .contains("unexpected EOF"));
+        // Commands don't get to read the session's own input
+        assert_eq!(run(&mut session, "cat").await, "");
+        assert_eq!(run(&mut session, "echo still alive").await, "still alive\n");
+
+        let mut output = String::new();
+        assert_eq!(
+            session.run("true", &mut output, None).await.unwrap(),
+            Some(0)
+        );
+        assert_eq!(
+            session.run("(exit 7)", &mut output, None).await.unwrap(),
+            Some(7)
+        );
+        assert_eq!(
+            session.run("exit 3", &mut output, None).await.unwrap(),
+            None
+        );
+    }
+}
@@ -267,12 +267,8 @@ fn apply_single_patch(
     Ok(())
 }
 
-/// Applies any diff (single or multi-file) using mpatch for fuzzy matching
-pub async fn apply_diff(
-    base_path: &Path,
-    diff_content: &str,
-    file_history: &std::sync::Arc<std::sync::Mutex<HashMap<PathBuf, Vec<String>>>>,
-) -> Result<Vec<Content>, ErrorData> {
+/// Parses a diff (single or multi-file) into patches, validating its size and file count
+fn parse_diff(diff_content: &str) -> Result<Vec<mpatch::Patch>, ErrorData> {
     // Validate size
     validate_diff_size(diff_content)?;
 
@@ -322,12 +318,37 @@ pub async fn apply_diff(
         ));
     }
 
-    // Determine base directory
-    let base_dir = if base_path.is_file() {
+    Ok(patches)
+}
+
+/// The directory the file paths of a diff applied at `base_path` are relative to
+fn diff_base_dir(base_path: &Path) -> PathBuf {
+    if base_path.is_file() {
         base_path.parent().unwrap_or(Path::new(".")).to_path_buf()
     } else {
         base_path.to_path_buf()
-    };
+    }
+}
+
+/// The files a diff applied at `base_path` would modify or create
+pub fn diff_target_paths(base_path: &Path, diff_content: &str) -> Result<Vec<PathBuf>, ErrorData> {
+    let base_dir = diff_base_dir(base_path);
+    Ok(parse_diff(diff_content)?
+        .iter()
+        .map(|patch| base_dir.join(&patch.file_path))
+        .collect())
+}
+
+/// Applies any diff (single or multi-file) using mpatch for fuzzy matching
+pub async fn apply_diff(
+    base_path: &Path,
+    diff_content: &str,
+    file_history: &std::sync::Arc<std::sync::Mutex<HashMap<PathBuf, Vec<String>>>>,
+) -> Result<Vec<Content>, ErrorData> {
+    let patches = parse_diff(diff_content)?;
+
+    // Determine base directory
+    let base_dir = diff_base_dir(base_path);
 
     // Apply all patches with fuzzy matching
     let mut results = DiffResults::default();
@@ -4,7 +4,7 @@ use goose::agents::ExtensionConfig;
 use goose::config::permission::PermissionLevel;
 use goose::config::ExtensionEntry;
 use goose::permission::permission_confirmation::PrincipalType;
-use goose::providers::base::{ConfigKey, ModelInfo, ProviderMetadata};
+use goose::providers::base::{ConfigKey, ModelInfo, ProviderMetadata, SupportedModel};
 use goose::session::info::SessionInfo;
 use goose::session::SessionMetadata;
 use rmcp::model::{
@@ -383,8 +383,13 @@ impl<'__s> ToSchema<'__s> for AnnotatedSchema {
         super::routes::agent::update_session_config,
         super::routes::reply::confirm_permission,
         super::routes::context::manage_context,
+        super::routes::context::context_usage,
         super::routes::session::list_sessions,
+        super::routes::session::search_sessions,
         super::routes::session::get_session_history,
+        super::routes::session::fork_session,
+        super::routes::session::pin_message,
+        super::routes::session::drop_message,
         super::routes::schedule::create_schedule,
         super::routes::schedule::list_schedules,
         super::routes::schedule::delete_schedule,
@@ -418,8 +423,20 @@ impl<'__s> ToSchema<'__s> for AnnotatedSchema {
         super::routes::reply::PermissionConfirmationRequest,
         super::routes::context::ContextManageRequest,
         super::routes::context::ContextManageResponse,
+        super::routes::context::ContextUsageRequest,
+        goose::context_mgmt::usage::ContextUsage,
+        goose::context_mgmt::usage::ContextUsageEntry,
+        goose::context_mgmt::usage::ContextUsageKind,
         super::routes::session::SessionListResponse,
         super::routes::session::SessionHistoryResponse,
+        super::routes::session::SessionSearchResponse,
+        super::routes::session::ForkSessionRequest,
+        super::routes::session::ForkSessionResponse,
+        super::routes::session::PinMessageRequest,
+        super::routes::session::DropMessageRequest,
+        super::routes::session::DropMessageResponse,
+        goose::session::SessionSearchHit,
+        goose::session::SearchMatchKind,
         Message,
         MessageContent,
         MessageMetadata,
@@ -454,9 +471,18 @@ impl<'__s> ToSchema<'__s> for AnnotatedSchema {
         PermissionLevel,
         PrincipalType,
         ModelInfo,
+        SupportedModel,
         SessionInfo,
         SessionMetadata,
         goose::session::ExtensionData,
+        goose::session::ModelUsage,
+        goose::session::UsageRole,
+        goose::session::Checkpoint,
+        goose::context_mgmt::summarize::RollingSummary,
+        goose::agents::SessionBudget,
+        goose::agents::BudgetExceeded,
+        goose::agents::BudgetLimit,
+        goose::providers::fallback::ModelFallback,
         super::routes::schedule::CreateScheduleRequest,
         super::routes::schedule::UpdateScheduleRequest,
         super::routes::schedule::KillJobResponse,
@@ -481,6 +507,7 @@ impl<'__s> ToSchema<'__s> for AnnotatedSchema {
         goose::recipe::Recipe,
         goose::recipe::Author,
         goose::recipe::Settings,
+        goose::model::ReasoningEffort,
         goose::recipe::RecipeParameter,
         goose::recipe::RecipeParameterInputType,
         goose::recipe::RecipeParameterRequirement,
@@ -134,6 +134,12 @@ async fn start_agent(
         accumulated_output_tokens: Some(0),
         extension_data: Default::default(),
         recipe: payload.recipe,
+        parent_session_id: None,
+        fork_point: None,
+        model_usage: Vec::new(),
+        budget_exceeded: None,
+        checkpoints: Vec::new(),
+        rolling_summary: None,
     };
 
     let session_path = match session::get_path(session::Identifier::Name(session_id.clone())) {
@@ -10,7 +10,7 @@ use goose::config::APP_STRATEGY;
 use goose::config::{Config, ConfigError};
 use goose::config::{ExtensionConfigManager, ExtensionEntry};
 use goose::model::ModelConfig;
-use goose::providers::base::ProviderMetadata;
+use goose::providers::base::{ProviderMetadata, SupportedModel};
 use goose::providers::pricing::{
     get_all_pricing, get_model_pricing, parse_model_id, refresh_pricing,
 };
@@ -356,7 +356,7 @@ pub async fn providers() -> Result<Json<Vec<ProviderDetails>>, StatusCode> {
         ("name" = String, Path, description = "Provider name (e.g., openai)")
     ),
     responses(
-        (status = 200, description = "Models fetched successfully", body = [String]),
+        (status = 200, description = "Models fetched successfully, with context length and capabilities where the provider reports them", body = [SupportedModel]),
         (status = 400, description = "Unknown provider, provider not configured, or authentication error"),
         (status = 429, description = "Rate limit exceeded"),
         (status = 500, description = "Internal server error")
@@ -364,7 +364,7 @@ pub async fn providers() -> Result<Json<Vec<ProviderDetails>>, StatusCode> {
 )]
 pub async fn get_provider_models(
     Path(name): Path<String>,
-) -> Result<Json<Vec<String>>, StatusCode> {
+) -> Result<Json<Vec<SupportedModel>>, StatusCode> {
     let all = get_providers();
     let Some(metadata) = all.into_iter().find(|m| m.name == name) else {
         return Err(StatusCode::BAD_REQUEST);
@@ -378,7 +378,7 @@ pub async fn get_provider_models(
     let provider = goose::providers::create(&name, model_config)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    match provider.fetch_supported_models().await {
+    match provider.fetch_supported_model_details().await {
         Ok(Some(models)) => Ok(Json(models)),
         Ok(None) => Ok(Json(Vec::new())),
         Err(provider_error) => {
@@ -1,5 +1,6 @@
 use crate::state::AppState;
 use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use goose::context_mgmt::usage::ContextUsage;
 use goose::conversation::{message::Message, Conversation};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -72,9 +73,47 @@ async fn manage_context(
     }))
 }
 
+/// Request payload for a breakdown of the context usage
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextUsageRequest {
+    /// Messages of the conversation, as they would be sent with the next request
+    pub messages: Vec<Message>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/context/usage",
+    request_body = ContextUsageRequest,
+    responses(
+        (status = 200, description = "Token breakdown of the next request", body = ContextUsage),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 412, description = "Precondition failed - Agent not available"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Context Management"
+)]
+async fn context_usage(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ContextUsageRequest>,
+) -> Result<Json<ContextUsage>, StatusCode> {
+    let agent = state.get_agent().await;
+
+    let usage = agent
+        .context_usage(&request.messages)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(usage))
+}
+
 // Configure routes for this module
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/context/manage", post(manage_context))
+        .route("/context/usage", post(context_usage))
         .with_state(state)
 }
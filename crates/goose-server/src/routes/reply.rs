@@ -11,8 +11,9 @@ use futures::{stream::StreamExt, Stream};
 use goose::conversation::message::{Message, MessageContent};
 use goose::conversation::Conversation;
 use goose::{
-    agents::{AgentEvent, SessionConfig},
+    agents::{AgentEvent, BudgetExceeded, SessionConfig},
     permission::permission_confirmation::PrincipalType,
+    providers::fallback::ModelFallback,
 };
 use goose::{
     permission::{Permission, PermissionConfirmation},
@@ -141,6 +142,12 @@ enum MessageEvent {
         model: String,
         mode: String,
     },
+    BudgetExceeded {
+        exceeded: BudgetExceeded,
+    },
+    ModelFallback {
+        fallback: ModelFallback,
+    },
     Notification {
         request_id: String,
         message: ServerNotification,
@@ -241,6 +248,14 @@ async fn reply_handler(
             }
         };
 
+        // Messages pinned or dropped since the client loaded the session are only marked as
+        // such in the stored session
+        let mut messages = messages;
+        match session::read_messages(&session_path) {
+            Ok(stored) => messages.keep_context_flags_from(&stored),
+            Err(e) => tracing::warn!("Failed to read stored messages for {}: {}", session_id, e),
+        }
+
         let session_config = SessionConfig {
             id: session::Identifier::Name(session_id.clone()),
             working_dir: session_metadata.working_dir.clone(),
@@ -248,6 +263,7 @@ async fn reply_handler(
             execution_mode: None,
             max_turns: None,
             retry_config: None,
+            budget: None,
         };
 
         let mut stream = match agent
@@ -324,6 +340,12 @@ async fn reply_handler(
                         Ok(Some(Ok(AgentEvent::ModelChange { model, mode }))) => {
                             stream_event(MessageEvent::ModelChange { model, mode }, &tx, &cancel_token).await;
                         }
+                        Ok(Some(Ok(AgentEvent::BudgetExceeded(exceeded)))) => {
+                            stream_event(MessageEvent::BudgetExceeded { exceeded }, &tx, &cancel_token).await;
+                        }
+                        Ok(Some(Ok(AgentEvent::ModelFallback(fallback)))) => {
+                            stream_event(MessageEvent::ModelFallback { fallback }, &tx, &cancel_token).await;
+                        }
                         Ok(Some(Ok(AgentEvent::McpNotification((request_id, n))))) => {
                             stream_event(MessageEvent::Notification{
                                 request_id: request_id.clone(),
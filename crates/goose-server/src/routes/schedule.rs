@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use chrono::NaiveDateTime;
 
 use crate::state::AppState;
+use goose::agents::SessionBudget;
 use goose::scheduler::ScheduledJob;
 
 #[derive(Deserialize, Serialize, utoipa::ToSchema)]
@@ -20,6 +21,8 @@ pub struct CreateScheduleRequest {
     cron: String,
     #[serde(default)]
     execution_mode: Option<String>, // "foreground" or "background"
+    #[serde(default)]
+    budget: Option<SessionBudget>,
 }
 
 #[derive(Deserialize, Serialize, utoipa::ToSchema)]
@@ -124,6 +127,7 @@ async fn create_schedule(
         current_session_id: None,
         process_start_time: None,
         execution_mode: req.execution_mode.or(Some("background".to_string())), // Default to background
+        budget: req.budget,
     };
     scheduler
         .add_scheduled_job(job.clone())
@@ -1,18 +1,19 @@
 use chrono::DateTime;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use crate::state::AppState;
 use axum::{
-    extract::Path,
+    extract::{Path, Query},
     http::StatusCode,
-    routing::{delete, get, put},
+    routing::{delete, get, post, put},
     Json, Router,
 };
 use goose::conversation::message::Message;
 use goose::session;
 use goose::session::info::{get_valid_sorted_sessions, SessionInfo, SortOrder};
-use goose::session::SessionMetadata;
+use goose::session::{SessionMetadata, SessionSearchHit};
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 use utoipa::ToSchema;
@@ -44,6 +45,63 @@ pub struct UpdateSessionMetadataRequest {
 
 const MAX_DESCRIPTION_LENGTH: usize = 200;
 
+#[derive(Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct SessionSearchQuery {
+    /// Text to search for; every word must match
+    query: String,
+    /// Maximum number of results to return
+    #[serde(default = "default_search_limit")]
+    limit: usize,
+}
+
+fn default_search_limit() -> usize {
+    20
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkSessionRequest {
+    /// Index of the first message to leave out; the fork keeps messages before it
+    at: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkSessionResponse {
+    /// Identifier of the newly created session
+    session_id: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PinMessageRequest {
+    /// Index of the message in the session
+    index: usize,
+    /// Whether to pin or unpin the message
+    pinned: bool,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DropMessageRequest {
+    /// Index of the message in the session
+    index: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DropMessageResponse {
+    /// Indices of the dropped messages, including the other half of any tool calls
+    dropped: Vec<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSearchResponse {
+    /// Matching messages, best match first
+    results: Vec<SessionSearchHit>,
+}
+
 #[derive(Serialize, ToSchema, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionInsights {
@@ -57,6 +115,10 @@ pub struct SessionInsights {
     total_tokens: i64,
     /// Activity trend for the last 7 days
     recent_activity: Vec<(String, usize)>,
+    /// Estimated cost in USD across all sessions, from usage that could be priced
+    total_cost_usd: f64,
+    /// Estimated cost in USD per model across all sessions, most expensive first
+    cost_by_model: Vec<(String, f64)>,
 }
 
 #[derive(Serialize, ToSchema, Debug)]
@@ -88,6 +150,36 @@ async fn list_sessions() -> Result<Json<SessionListResponse>, StatusCode> {
     Ok(Json(SessionListResponse { sessions }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/sessions/search",
+    params(SessionSearchQuery),
+    responses(
+        (status = 200, description = "Search results retrieved successfully", body = SessionSearchResponse),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// Search messages and tool calls across all sessions
+async fn search_sessions(
+    Query(query): Query<SessionSearchQuery>,
+) -> Result<Json<SessionSearchResponse>, StatusCode> {
+    let results =
+        tokio::task::spawn_blocking(move || session::search_sessions(&query.query, query.limit))
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .map_err(|e| {
+                error!("Failed to search sessions: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+    Ok(Json(SessionSearchResponse { results }))
+}
+
 #[utoipa::path(
     get,
     path = "/sessions/{session_id}",
@@ -181,6 +273,8 @@ async fn get_session_insights() -> Result<Json<SessionInsights>, StatusCode> {
     let mut total_duration = 0.0;
     let mut total_tokens = 0;
     let mut activity_by_date: HashMap<String, usize> = HashMap::new();
+    let mut total_cost_usd = 0.0;
+    let mut model_costs: HashMap<String, f64> = HashMap::new();
 
     for session in &sessions {
         // Track directory usage
@@ -206,6 +300,14 @@ async fn get_session_insights() -> Result<Json<SessionInsights>, StatusCode> {
             }
         }
 
+        // Track cost per model
+        for usage in &session.metadata.model_usage {
+            if let Some(cost) = usage.cost_or_estimate().await {
+                total_cost_usd += cost;
+                *model_costs.entry(usage.model.clone()).or_insert(0.0) += cost;
+            }
+        }
+
         // Track activity by date
         if let Ok(date) = DateTime::parse_from_str(&session.modified, "%Y-%m-%d %H:%M:%S UTC") {
             let date_str = date.format("%Y-%m-%d").to_string();
@@ -241,12 +343,17 @@ async fn get_session_insights() -> Result<Json<SessionInsights>, StatusCode> {
     activity_vec.sort_by(|a, b| b.0.cmp(&a.0)); // Sort by date descending
     let recent_activity = activity_vec.into_iter().take(7).collect();
 
+    let mut cost_by_model: Vec<(String, f64)> = model_costs.into_iter().collect();
+    cost_by_model.sort_by(|a, b| b.1.total_cmp(&a.1));
+
     let insights = SessionInsights {
         total_sessions,
         most_active_dirs,
         avg_session_duration,
         total_tokens,
         recent_activity,
+        total_cost_usd,
+        cost_by_model,
     };
 
     info!("Returning insights: {:?}", insights);
@@ -324,23 +431,168 @@ async fn delete_session(Path(session_id): Path<String>) -> Result<StatusCode, St
         Err(_) => return Err(StatusCode::BAD_REQUEST),
     };
 
-    // Check if session file exists
-    if !session_path.exists() {
+    // Check if the session exists
+    if !session::session_exists(&session_path) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    session::delete_session(&session_path).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(StatusCode::OK)
+}
+
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/fork",
+    request_body = ForkSessionRequest,
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session to fork")
+    ),
+    responses(
+        (status = 200, description = "Session forked successfully", body = ForkSessionResponse),
+        (status = 400, description = "Bad request - Fork point beyond the end of the session"),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// Fork a session into a new session that ends before the given message
+async fn fork_session(
+    Path(session_id): Path<String>,
+    Json(request): Json<ForkSessionRequest>,
+) -> Result<Json<ForkSessionResponse>, StatusCode> {
+    let session_path = session::get_path(session::Identifier::Name(session_id.clone()))
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    if !session::session_exists(&session_path) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let message_count = session::read_messages(&session_path)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .len();
+    if request.at > message_count {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let fork_path = session::fork_session(&session_path, request.at, None).map_err(|e| {
+        error!("Failed to fork session {}: {}", session_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let fork_id = fork_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(ForkSessionResponse {
+        session_id: fork_id,
+    }))
+}
+
+/// Path of an existing session
+fn existing_session_path(session_id: &str) -> Result<PathBuf, StatusCode> {
+    let session_path = session::get_path(session::Identifier::Name(session_id.to_string()))
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    if !session::session_exists(&session_path) {
         return Err(StatusCode::NOT_FOUND);
     }
+    Ok(session_path)
+}
+
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/pin",
+    request_body = PinMessageRequest,
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    responses(
+        (status = 200, description = "Message pinned or unpinned successfully"),
+        (status = 400, description = "Bad request - No such message in the agent's context"),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// Pin a message so that compaction keeps it in the agent's context, or unpin it
+async fn pin_message(
+    Path(session_id): Path<String>,
+    Json(request): Json<PinMessageRequest>,
+) -> Result<StatusCode, StatusCode> {
+    let session_path = existing_session_path(&session_id)?;
 
-    // Delete the session file
-    std::fs::remove_file(&session_path).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let found = session::pin_message(&session_path, request.index, request.pinned)
+        .await
+        .map_err(|e| {
+            error!("Failed to pin message in session {}: {}", session_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !found {
+        return Err(StatusCode::BAD_REQUEST);
+    }
 
     Ok(StatusCode::OK)
 }
 
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/drop",
+    request_body = DropMessageRequest,
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    responses(
+        (status = 200, description = "Message dropped from the agent's context successfully", body = DropMessageResponse),
+        (status = 400, description = "Bad request - No such message in the agent's context"),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// Drop a message from the agent's context, keeping it in the session history
+async fn drop_message(
+    Path(session_id): Path<String>,
+    Json(request): Json<DropMessageRequest>,
+) -> Result<Json<DropMessageResponse>, StatusCode> {
+    let session_path = existing_session_path(&session_id)?;
+
+    let dropped = session::drop_message(&session_path, request.index)
+        .await
+        .map_err(|e| {
+            error!("Failed to drop message in session {}: {}", session_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if dropped.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    Ok(Json(DropMessageResponse { dropped }))
+}
+
 // Configure routes for this module
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/sessions", get(list_sessions))
+        .route("/sessions/search", get(search_sessions))
         .route("/sessions/{session_id}", get(get_session_history))
         .route("/sessions/{session_id}/delete", delete(delete_session))
+        .route("/sessions/{session_id}/fork", post(fork_session))
+        .route("/sessions/{session_id}/pin", post(pin_message))
+        .route("/sessions/{session_id}/drop", post(drop_message))
         .route("/sessions/insights", get(get_session_insights))
         .route(
             "/sessions/{session_id}/metadata",
@@ -8,6 +8,7 @@ use futures::stream::BoxStream;
 use futures::{stream, FutureExt, Stream, StreamExt, TryStreamExt};
 use uuid::Uuid;
 
+use crate::agents::budget::{BudgetExceeded, BudgetTracker, SessionBudget};
 use crate::agents::extension::{ExtensionConfig, ExtensionError, ExtensionResult, ToolInfo};
 use crate::agents::extension_manager::{get_parameter_names, ExtensionManager};
 use crate::agents::final_output_tool::{FINAL_OUTPUT_CONTINUATION_MESSAGE, FINAL_OUTPUT_TOOL_NAME};
@@ -20,6 +21,7 @@ use crate::agents::prompt_manager::PromptManager;
 use crate::agents::recipe_tools::dynamic_task_tools::{
     create_dynamic_task, create_dynamic_task_tool, DYNAMIC_TASK_TOOL_NAME_PREFIX,
 };
+use crate::agents::reply_parts::usage_role_for;
 use crate::agents::retry::{RetryManager, RetryResult};
 use crate::agents::router_tools::ROUTER_LLM_SEARCH_TOOL_NAME;
 use crate::agents::sub_recipe_manager::SubRecipeManager;
@@ -39,6 +41,7 @@ use crate::permission::permission_judge::PermissionCheckResult;
 use crate::permission::PermissionConfirmation;
 use crate::providers::base::Provider;
 use crate::providers::errors::ProviderError;
+use crate::providers::fallback::ModelFallback;
 use crate::recipe::{Author, Recipe, Response, Settings, SubRecipe};
 use crate::scheduler_trait::SchedulerTrait;
 use crate::security::security_inspector::SecurityInspector;
@@ -112,8 +115,15 @@ pub struct Agent {
 pub enum AgentEvent {
     Message(Message),
     McpNotification((String, ServerNotification)),
-    ModelChange { model: String, mode: String },
+    ModelChange {
+        model: String,
+        mode: String,
+    },
     HistoryReplaced(Vec<Message>),
+    /// The agent stopped because the session ran out of budget
+    BudgetExceeded(BudgetExceeded),
+    /// The provider failed over to another model in its fallback chain
+    ModelFallback(ModelFallback),
 }
 
 impl Default for Agent {
@@ -455,7 +465,8 @@ impl Agent {
         } else if tool_call.name == SUBAGENT_EXECUTE_TASK_TOOL_NAME {
             let provider = self.provider().await.ok();
 
-            let task_config = TaskConfig::new(provider);
+            let mut task_config = TaskConfig::new(provider);
+            task_config.parent_session = session.as_ref().map(|s| s.id.clone());
             subagent_execute_task_tool::run_tasks(
                 tool_call.arguments.clone(),
                 task_config,
@@ -910,26 +921,32 @@ impl Agent {
         )>,
     > {
         // Try to get session metadata for more accurate token counts
-        let session_metadata = if let Some(session_config) = session {
-            match session::storage::get_path(session_config.id.clone()) {
-                Ok(session_file_path) => session::storage::read_metadata(&session_file_path).ok(),
-                Err(_) => None,
-            }
-        } else {
-            None
-        };
+        let session_file = session
+            .as_ref()
+            .and_then(|session_config| session::storage::get_path(session_config.id.clone()).ok());
+        let session_metadata = session_file
+            .as_ref()
+            .and_then(|session_file_path| session::storage::read_metadata(session_file_path).ok());
 
         let compact_result = auto_compact::check_and_compact_messages(
             self,
             messages,
             None,
             session_metadata.as_ref(),
+            session_file.as_deref(),
         )
         .await?;
 
         if compact_result.compacted {
             let compacted_messages = compact_result.messages;
 
+            if let (Some(session_config), Some(summary)) = (session, compact_result.rolling_summary)
+            {
+                if let Err(e) = Self::save_rolling_summary(session_config, summary).await {
+                    error!("Failed to save rolling summary: {}", e);
+                }
+            }
+
             // Get threshold from config to include in message
             let config = crate::config::Config::global();
             let threshold = config
@@ -937,10 +954,17 @@ impl Agent {
                 .unwrap_or(0.8); // Default to 80%
             let threshold_percentage = (threshold * 100.0) as u32;
 
-            let compaction_msg = format!(
-                "Exceeded auto-compact threshold of {}%. Context has been summarized and reduced.\n\n",
-                threshold_percentage
-            );
+            let compaction_msg = if compact_result.summarized {
+                format!(
+                    "Exceeded auto-compact threshold of {}%. Context has been summarized and reduced.\n\n",
+                    threshold_percentage
+                )
+            } else {
+                format!(
+                    "Exceeded auto-compact threshold of {}%. Old tool outputs have been pruned to reduce context.\n\n",
+                    threshold_percentage
+                )
+            };
 
             return Ok(Some((
                 compacted_messages,
@@ -960,7 +984,7 @@ impl Agent {
         cancel_token: Option<CancellationToken>,
     ) -> Result<BoxStream<'_, Result<AgentEvent>>> {
         // Handle auto-compaction before processing
-        let (messages, compaction_msg, _summarization_usage) = match self
+        let (messages, compaction_msg, summarization_usage) = match self
             .handle_auto_compaction(unfixed_conversation.messages(), &session)
             .await?
         {
@@ -973,6 +997,38 @@ impl Agent {
             }
         };
 
+        if let (Some(session_config), Some(usage)) = (&session, &summarization_usage) {
+            if let Ok(session_file_path) = session::storage::get_path(session_config.id.clone()) {
+                if let Err(e) = session::record_usage(
+                    &session_file_path,
+                    session::UsageRole::Summarization,
+                    usage,
+                )
+                .await
+                {
+                    error!("Failed to record summarization usage: {}", e);
+                }
+            }
+        }
+
+        // Snapshot the working directory so the session can be rewound to this turn
+        if let Some(session_config) = &session {
+            if session::checkpoint::checkpoints_enabled() {
+                if let Ok(session_file_path) = session::storage::get_path(session_config.id.clone())
+                {
+                    if let Err(e) = session::create_checkpoint(
+                        &session_file_path,
+                        &session_config.working_dir,
+                        &messages,
+                    )
+                    .await
+                    {
+                        tracing::warn!("Failed to create workspace checkpoint: {}", e);
+                    }
+                }
+            }
+        }
+
         // If we compacted, yield the compaction message and history replacement event
         if let Some(compaction_msg) = compaction_msg {
             return Ok(Box::pin(async_stream::try_stream! {
@@ -1028,6 +1084,16 @@ impl Agent {
                 .unwrap_or_else(|| {
                     config.get_param("GOOSE_MAX_TURNS").unwrap_or(DEFAULT_MAX_TURNS)
                 });
+            let mut budget_tracker = BudgetTracker::new(
+                session
+                    .as_ref()
+                    .and_then(|s| s.budget.clone())
+                    .unwrap_or_default()
+                    .or(&SessionBudget::from_config()),
+            );
+            budget_tracker
+                .prepare_pricing(&self.provider().await?.get_model_config().model_name)
+                .await?;
 
             loop {
                 if is_token_cancelled(&cancel_token) {
@@ -1052,6 +1118,11 @@ impl Agent {
                     break;
                 }
 
+                if let Some(exceeded) = Self::check_budget(&budget_tracker, &session).await {
+                    yield AgentEvent::BudgetExceeded(exceeded);
+                    break;
+                }
+
                 {
                     let mut autopilot = self.autopilot.lock().await;
                     if let Some((new_provider, role, model)) = autopilot.check_for_switch(&messages, self.provider().await?).await? {
@@ -1074,6 +1145,12 @@ impl Agent {
                     &toolshim_tools,
                 ).await?;
 
+                if let Some(fallback) = self.provider().await?.as_fallback() {
+                    for event in fallback.take_events() {
+                        yield AgentEvent::ModelFallback(event);
+                    }
+                }
+
                 let mut added_message = false;
                 let mut messages_to_add = Vec::new();
                 let mut tools_updated = false;
@@ -1107,10 +1184,13 @@ impl Agent {
                             }
 
                             // Record usage for the session
-                            if let Some(ref session_config) = &session {
-                                if let Some(ref usage) = usage {
-                                    Self::update_session_metrics(session_config, usage, messages.len())
+                            if let Some(ref usage) = usage {
+                                if let Some(ref session_config) = &session {
+                                    let role = usage_role_for(provider.as_ref(), usage);
+                                    Self::update_session_metrics(session_config, usage, role, messages.len())
                                         .await?;
+                                } else {
+                                    budget_tracker.record(usage).await;
                                 }
                             }
 
@@ -1268,10 +1348,22 @@ impl Agent {
                         Err(ProviderError::ContextLengthExceeded(error_msg)) => {
                             info!("Context length exceeded, attempting compaction");
 
-                            match auto_compact::perform_compaction(self, messages.messages()).await {
+                            let session_file = session
+                                .as_ref()
+                                .and_then(|session_config| session::storage::get_path(session_config.id.clone()).ok());
+                            let session_metadata = session_file
+                                .as_ref()
+                                .and_then(|session_file_path| session::storage::read_metadata(session_file_path).ok());
+                            match auto_compact::perform_compaction(self, messages.messages(), session_metadata.as_ref(), session_file.as_deref()).await {
                                 Ok(compact_result) => {
                                     messages = compact_result.messages;
 
+                                    if let (Some(session_config), Some(summary)) = (&session, compact_result.rolling_summary) {
+                                        if let Err(e) = Self::save_rolling_summary(session_config, summary).await {
+                                            error!("Failed to save rolling summary: {}", e);
+                                        }
+                                    }
+
                                     yield AgentEvent::Message(
                                         Message::assistant().with_summarization_requested(
                                             "Context limit reached. Conversation has been automatically compacted to continue."
@@ -1302,7 +1394,36 @@ impl Agent {
                     (tools, toolshim_tools, system_prompt) = self.prepare_tools_and_prompt().await?;
                 }
                 if !added_message {
-                    if let Some(final_output_tool) = self.final_output_tool.lock().await.as_ref() {
+                    if let Some(final_output_tool) = self.final_output_tool.lock().await.as_mut() {
+                        let provider = self.provider().await?;
+                        if final_output_tool.final_output.is_none() && provider.supports_structured_output() {
+                            let conversation: Vec<Message> = messages
+                                .messages()
+                                .iter()
+                                .chain(messages_to_add.iter())
+                                .cloned()
+                                .collect();
+                            match final_output_tool
+                                .collect_structured_output(provider.as_ref(), &conversation)
+                                .await
+                            {
+                                Ok((_, usage)) => {
+                                    if let Some(ref session_config) = &session {
+                                        if let Ok(session_file_path) = session::storage::get_path(session_config.id.clone()) {
+                                            let role = usage_role_for(provider.as_ref(), &usage);
+                                            if let Err(e) = session::record_usage(&session_file_path, role, &usage).await {
+                                                error!("Failed to record structured output usage: {}", e);
+                                            }
+                                        }
+                                    } else {
+                                        budget_tracker.record(&usage).await;
+                                    }
+                                }
+                                Err(e) => {
+                                    tracing::warn!("Structured output failed, asking for the final output tool instead: {}", e);
+                                }
+                            }
+                        }
                         if final_output_tool.final_output.is_none() {
                             tracing::warn!("Final output tool has not been called yet. Continuing agent loop.");
                             let message = Message::user().with_text(FINAL_OUTPUT_CONTINUATION_MESSAGE);
@@ -1625,6 +1746,8 @@ impl Agent {
             goose_provider: Some(provider_name.clone()),
             goose_model: Some(model_name.clone()),
             temperature: Some(model_config.temperature.unwrap_or(0.0)),
+            reasoning_effort: model_config.reasoning.and_then(|r| r.effort),
+            thinking_budget: model_config.reasoning.and_then(|r| r.budget_tokens),
         };
 
         tracing::debug!(
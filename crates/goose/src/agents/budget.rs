@@ -0,0 +1,280 @@
+use crate::config::Config;
+use crate::providers::base::{ProviderUsage, Usage};
+use crate::providers::pricing::ensure_pricing_cache;
+use crate::session::storage::SessionMetadata;
+use crate::session::usage::{estimate_usage_cost, UsageRole};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// Config key for the maximum total tokens a session may use
+pub const GOOSE_MAX_TOKENS: &str = "GOOSE_MAX_TOKENS";
+/// Config key for the maximum estimated cost in USD a session may incur
+pub const GOOSE_MAX_COST_USD: &str = "GOOSE_MAX_COST_USD";
+/// Config key for the maximum wall-clock seconds of a single agent run
+pub const GOOSE_MAX_DURATION_SECS: &str = "GOOSE_MAX_DURATION_SECS";
+
+/// Spend limits for a session. Limits that are not set are not enforced.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SessionBudget {
+    /// Maximum total tokens across all provider calls of the session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<i64>,
+    /// Maximum estimated cost in USD across all provider calls of the session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cost_usd: Option<f64>,
+    /// Maximum wall-clock time in seconds for a single run of the agent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_secs: Option<u64>,
+}
+
+impl SessionBudget {
+    /// Read the budget from the global config, leaving unset keys unlimited
+    pub fn from_config() -> Self {
+        let config = Config::global();
+        Self {
+            max_tokens: config.get_param(GOOSE_MAX_TOKENS).ok(),
+            max_cost_usd: config.get_param(GOOSE_MAX_COST_USD).ok(),
+            max_duration_secs: config.get_param(GOOSE_MAX_DURATION_SECS).ok(),
+        }
+    }
+
+    /// Fill in any limit not set here from `fallback`
+    pub fn or(self, fallback: &SessionBudget) -> Self {
+        Self {
+            max_tokens: self.max_tokens.or(fallback.max_tokens),
+            max_cost_usd: self.max_cost_usd.or(fallback.max_cost_usd),
+            max_duration_secs: self.max_duration_secs.or(fallback.max_duration_secs),
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_tokens.is_none() && self.max_cost_usd.is_none() && self.max_duration_secs.is_none()
+    }
+}
+
+/// The kind of budget that was exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetLimit {
+    Tokens,
+    CostUsd,
+    Duration,
+}
+
+/// Why the agent was stopped: which limit was hit, the limit itself and what had been spent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct BudgetExceeded {
+    pub limit: BudgetLimit,
+    pub max: f64,
+    pub spent: f64,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.limit {
+            BudgetLimit::Tokens => write!(
+                f,
+                "Token budget exceeded: {} of {} tokens used",
+                self.spent, self.max
+            ),
+            BudgetLimit::CostUsd => write!(
+                f,
+                "Cost budget exceeded: ${:.4} of ${:.4} spent",
+                self.spent, self.max
+            ),
+            BudgetLimit::Duration => write!(
+                f,
+                "Time budget exceeded: {:.0}s of {:.0}s elapsed",
+                self.spent, self.max
+            ),
+        }
+    }
+}
+
+/// Tracks spend against a budget over one run of the agent.
+///
+/// Token and cost totals come from the session metadata when the run belongs to a
+/// session, so usage by subagents and summarization counts too. Runs without a
+/// session only count the usage reported to the tracker.
+pub struct BudgetTracker {
+    budget: SessionBudget,
+    started: Instant,
+    tokens: i64,
+    cost_usd: Option<f64>,
+}
+
+impl BudgetTracker {
+    pub fn new(budget: SessionBudget) -> Self {
+        Self {
+            budget,
+            started: Instant::now(),
+            tokens: 0,
+            cost_usd: None,
+        }
+    }
+
+    pub fn budget(&self) -> &SessionBudget {
+        &self.budget
+    }
+
+    /// Load model prices when a cost limit is set, so the calls of this run can be priced.
+    /// Fails when no price is known for `model`, as the cost limit could then never trip.
+    pub async fn prepare_pricing(&self, model: &str) -> anyhow::Result<()> {
+        let Some(max) = self.budget.max_cost_usd else {
+            return Ok(());
+        };
+        if let Err(e) = ensure_pricing_cache().await {
+            tracing::warn!("Failed to initialize pricing cache: {}", e);
+        }
+
+        let probe = ProviderUsage::new(model.to_string(), Usage::new(Some(0), Some(0), None));
+        if estimate_usage_cost(UsageRole::Main, &probe).await.is_none() {
+            anyhow::bail!(
+                "A cost budget of ${:.2} is set, but no price is known for model '{}'. \
+                 Use a model with known pricing or remove {}",
+                max,
+                model,
+                GOOSE_MAX_COST_USD
+            );
+        }
+        Ok(())
+    }
+
+    /// Count a provider call made without a session
+    pub async fn record(&mut self, usage: &ProviderUsage) {
+        let input = usage.usage.input_tokens.unwrap_or(0).max(0) as i64;
+        let output = usage.usage.output_tokens.unwrap_or(0).max(0) as i64;
+        self.tokens += input + output;
+        if self.budget.max_cost_usd.is_some() {
+            if let Some(cost) = estimate_usage_cost(UsageRole::Main, usage).await {
+                self.cost_usd = Some(self.cost_usd.unwrap_or(0.0) + cost);
+            }
+        }
+    }
+
+    /// Check the budget against the session totals, or the recorded usage if there is no session
+    pub fn check(&self, metadata: Option<&SessionMetadata>) -> Option<BudgetExceeded> {
+        let (tokens, cost_usd) = match metadata {
+            Some(metadata) => (metadata.total_model_tokens(), metadata.total_cost_usd()),
+            None => (self.tokens, self.cost_usd),
+        };
+        self.check_spent(tokens, cost_usd, self.started.elapsed())
+    }
+
+    fn check_spent(
+        &self,
+        tokens: i64,
+        cost_usd: Option<f64>,
+        elapsed: Duration,
+    ) -> Option<BudgetExceeded> {
+        if let Some(max) = self.budget.max_tokens {
+            if tokens >= max {
+                return Some(BudgetExceeded {
+                    limit: BudgetLimit::Tokens,
+                    max: max as f64,
+                    spent: tokens as f64,
+                });
+            }
+        }
+        if let (Some(max), Some(spent)) = (self.budget.max_cost_usd, cost_usd) {
+            if spent >= max {
+                return Some(BudgetExceeded {
+                    limit: BudgetLimit::CostUsd,
+                    max,
+                    spent,
+                });
+            }
+        }
+        if let Some(max) = self.budget.max_duration_secs {
+            if elapsed.as_secs() >= max {
+                return Some(BudgetExceeded {
+                    limit: BudgetLimit::Duration,
+                    max: max as f64,
+                    spent: elapsed.as_secs_f64(),
+                });
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_budget_never_trips() {
+        let tracker = BudgetTracker::new(SessionBudget::default());
+        assert!(tracker
+            .check_spent(i64::MAX, Some(1e9), Duration::from_secs(u64::MAX / 2))
+            .is_none());
+    }
+
+    #[test]
+    fn test_check_spent_reports_first_exceeded_limit() {
+        let tracker = BudgetTracker::new(SessionBudget {
+            max_tokens: Some(1000),
+            max_cost_usd: Some(0.5),
+            max_duration_secs: Some(60),
+        });
+
+        assert!(tracker
+            .check_spent(999, Some(0.49), Duration::from_secs(59))
+            .is_none());
+
+        let exceeded = tracker
+            .check_spent(1000, None, Duration::from_secs(0))
+            .unwrap();
+        assert_eq!(exceeded.limit, BudgetLimit::Tokens);
+        assert_eq!(exceeded.max, 1000.0);
+
+        let exceeded = tracker
+            .check_spent(10, Some(0.75), Duration::from_secs(0))
+            .unwrap();
+        assert_eq!(exceeded.limit, BudgetLimit::CostUsd);
+        assert_eq!(
+            exceeded.to_string(),
+            "Cost budget exceeded: $0.7500 of $0.5000 spent"
+        );
+
+        let exceeded = tracker
+            .check_spent(10, None, Duration::from_secs(61))
+            .unwrap();
+        assert_eq!(exceeded.limit, BudgetLimit::Duration);
+    }
+
+    #[tokio::test]
+    async fn test_check_uses_session_totals_when_available() {
+        let mut tracker = BudgetTracker::new(SessionBudget {
+            max_tokens: Some(100),
+            ..Default::default()
+        });
+        let usage = ProviderUsage::new("model".to_string(), Usage::new(Some(60), Some(50), None));
+
+        tracker.record(&usage).await;
+        assert_eq!(tracker.check(None).unwrap().spent, 110.0);
+
+        let metadata = SessionMetadata::default();
+        assert!(tracker.check(Some(&metadata)).is_none());
+    }
+
+    #[test]
+    fn test_budget_or_prefers_own_limits() {
+        let session = SessionBudget {
+            max_tokens: Some(10),
+            ..Default::default()
+        };
+        let config = SessionBudget {
+            max_tokens: Some(20),
+            max_duration_secs: Some(30),
+            ..Default::default()
+        };
+        let merged = session.or(&config);
+        assert_eq!(merged.max_tokens, Some(10));
+        assert_eq!(merged.max_duration_secs, Some(30));
+        assert!(merged.max_cost_usd.is_none());
+        assert!(SessionBudget::default().is_unlimited());
+    }
+}
@@ -1,15 +1,27 @@
 use anyhow::Ok;
+use rmcp::model::Role;
+use std::collections::HashMap;
+use std::path::Path;
 
 use crate::conversation::message::{Message, MessageMetadata};
-use crate::conversation::Conversation;
-use crate::token_counter::create_async_token_counter;
+use crate::conversation::{pinned_indices, Conversation};
+use crate::token_counter::create_async_token_counter_for_model;
 
-use crate::context_mgmt::summarize::summarize_messages;
-use crate::context_mgmt::truncate::{truncate_messages, OldestFirstTruncation};
+use crate::context_mgmt::summarize::{summarize_messages, update_rolling_summary, RollingSummary};
+use crate::context_mgmt::truncate::{
+    truncate_messages, OldestFirstTruncation, ToolOutputPruning, TruncationStrategy,
+};
+use crate::context_mgmt::usage::{context_usage_entries, ContextUsage};
 use crate::context_mgmt::{estimate_target_context_limit, get_messages_token_counts_async};
 
 use super::super::agents::Agent;
 
+/// Follows the rolling summary in the context, so the agent continues from it
+const ROLLING_SUMMARY_CONTINUATION: &str =
+    "The previous message contains a summary of the earlier part of this session. \
+Do not mention that you read a summary or that conversation summarization occurred. \
+Just continue the conversation naturally based on the summary and the messages that follow.";
+
 impl Agent {
     /// Public API to truncate oldest messages so that the conversation's token count is within the allowed context limit.
     pub async fn truncate_context(
@@ -17,7 +29,7 @@ impl Agent {
         messages: &[Message], // last message is a user msg that led to assistant message with_context_length_exceeded
     ) -> Result<(Conversation, Vec<usize>), anyhow::Error> {
         let provider = self.provider().await?;
-        let token_counter = create_async_token_counter()
+        let token_counter = create_async_token_counter_for_model(&provider.get_model_config())
             .await
             .map_err(|e| anyhow::anyhow!("Failed to create token counter: {}", e))?;
         let target_context_limit = estimate_target_context_limit(provider);
@@ -48,7 +60,228 @@ impl Agent {
         Ok((new_messages, new_token_counts))
     }
 
+    /// Public API to replace old tool outputs with short stubs so that the conversation's token count is within the allowed context limit.
+    /// Pruned messages stay visible to the user, while the agent sees their stubbed copy instead.
+    /// Returns the messages and token counts, which may still exceed the limit when pruning was not enough.
+    /// The full outputs are kept with the data of `session_file`, if there is a session.
+    pub async fn prune_context(
+        &self,
+        messages: &[Message],
+        session_file: Option<&Path>,
+    ) -> Result<(Conversation, Vec<usize>), anyhow::Error> {
+        let provider = self.provider().await?;
+        let token_counter = create_async_token_counter_for_model(&provider.get_model_config())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create token counter: {}", e))?;
+        let target_context_limit = estimate_target_context_limit(provider);
+
+        // Only prune what the agent sees, earlier compactions already hid the rest
+        let visible_indices: Vec<usize> = (0..messages.len())
+            .filter(|&i| messages[i].is_agent_visible())
+            .collect();
+        let mut visible_messages: Vec<Message> = visible_indices
+            .iter()
+            .map(|&i| messages[i].clone())
+            .collect();
+        let mut visible_token_counts =
+            get_messages_token_counts_async(&token_counter, &visible_messages);
+        ToolOutputPruning::for_session(session_file)?.shrink_messages(
+            &mut visible_messages,
+            &mut visible_token_counts,
+            target_context_limit,
+        )?;
+
+        let mut visible: HashMap<usize, (Message, usize)> = visible_indices
+            .into_iter()
+            .zip(visible_messages.into_iter().zip(visible_token_counts))
+            .collect();
+
+        let mut final_messages = Vec::new();
+        let mut final_token_counts = Vec::new();
+        for (i, msg) in messages.iter().enumerate() {
+            match visible.remove(&i) {
+                Some((pruned_msg, tokens)) if pruned_msg != *msg => {
+                    // Keep the full output for the user, and give the agent the stub
+                    let user_metadata = msg.metadata.with_agent_invisible();
+                    let agent_metadata = msg.metadata.with_user_invisible();
+                    final_messages.push(msg.clone().with_metadata(user_metadata));
+                    final_token_counts.push(0);
+                    final_messages.push(pruned_msg.with_metadata(agent_metadata));
+                    final_token_counts.push(tokens);
+                }
+                Some((_, tokens)) => {
+                    final_messages.push(msg.clone());
+                    final_token_counts.push(tokens);
+                }
+                None => {
+                    // Token count doesn't matter for agent_visible=false messages
+                    final_messages.push(msg.clone());
+                    final_token_counts.push(0);
+                }
+            }
+        }
+
+        Ok((
+            Conversation::new_unvalidated(final_messages),
+            final_token_counts,
+        ))
+    }
+
+    /// Public API to fold all but the most recent `keep_turns` turns of the conversation into the rolling summary,
+    /// so that its token count is within the allowed context limit. Only the messages compacted since the summary
+    /// was last updated are sent to the model, and the most recent turns and pinned messages are kept verbatim.
+    /// Returns the messages, token counts, the ProviderUsage from summarization and the updated summary
+    pub async fn rolling_summarize_context(
+        &self,
+        messages: &[Message],
+        summary: &RollingSummary,
+        keep_turns: usize,
+    ) -> Result<
+        (
+            Conversation,
+            Vec<usize>,
+            Option<crate::providers::base::ProviderUsage>,
+            RollingSummary,
+        ),
+        anyhow::Error,
+    > {
+        let provider = self.provider().await?;
+        let token_counter = create_async_token_counter_for_model(&provider.get_model_config())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create token counter: {}", e))?;
+
+        // The summary and continuation messages of the last compaction are replaced, not summarized again
+        let previous_summary_text = summary.to_text();
+        let is_summary_message = |msg: &Message| {
+            !msg.is_user_visible() && {
+                let text = msg.as_concat_text();
+                text == previous_summary_text || text == ROLLING_SUMMARY_CONTINUATION
+            }
+        };
+        let pinned = pinned_indices(messages);
+        let visible_indices: Vec<usize> = (0..messages.len())
+            .filter(|&i| {
+                messages[i].is_agent_visible()
+                    && !is_summary_message(&messages[i])
+                    && !pinned.contains(&i)
+            })
+            .collect();
+
+        // Turns start with a user text message, as opposed to a tool response
+        let turn_starts: Vec<usize> = visible_indices
+            .iter()
+            .copied()
+            .filter(|&i| messages[i].role == Role::User && messages[i].has_only_text_content())
+            .collect();
+
+        // Keep the most recent turns verbatim, but fewer if that would leave nothing to summarize
+        let aged_before = (0..=keep_turns.min(turn_starts.len()))
+            .rev()
+            .map(|turns| {
+                if turns == 0 {
+                    messages.len()
+                } else {
+                    turn_starts[turn_starts.len() - turns]
+                }
+            })
+            .find(|&start| visible_indices.first().is_some_and(|&first| first < start))
+            .unwrap_or(messages.len());
+
+        let aged_messages: Vec<Message> = visible_indices
+            .iter()
+            .filter(|&&i| i < aged_before)
+            .map(|&i| messages[i].clone())
+            .collect();
+        if aged_messages.is_empty() {
+            tracing::warn!("No messages to add to the rolling summary.");
+            let token_counts = messages
+                .iter()
+                .map(|msg| {
+                    if msg.is_agent_visible() {
+                        token_counter.count_chat_tokens("", std::slice::from_ref(msg), &[])
+                    } else {
+                        0
+                    }
+                })
+                .collect();
+            return Ok((
+                Conversation::new_unvalidated(messages.to_vec()),
+                token_counts,
+                None,
+                summary.clone(),
+            ));
+        }
+
+        let (updated_summary, summarization_usage) =
+            update_rolling_summary(provider, summary, &aged_messages).await?;
+
+        // The compaction marker (user only), the summary and an assistant message to continue
+        // the conversation (agent only) go between the compacted messages and the recent turns
+        let summary_text = updated_summary.to_text();
+        let summary_tokens = token_counter.count_tokens(&summary_text);
+        let compaction_messages = [
+            (
+                Message::assistant()
+                    .with_summarization_requested("Conversation compacted and summarized")
+                    .with_metadata(MessageMetadata::user_only()),
+                0,
+            ),
+            (
+                Message::user()
+                    .with_text(summary_text)
+                    .with_metadata(MessageMetadata::agent_only()),
+                summary_tokens,
+            ),
+            (
+                Message::assistant()
+                    .with_text(ROLLING_SUMMARY_CONTINUATION)
+                    .with_metadata(MessageMetadata::agent_only()),
+                0,
+            ),
+        ];
+
+        let mut final_messages = Vec::new();
+        let mut final_token_counts = Vec::new();
+        for (i, msg) in messages.iter().enumerate() {
+            if i == aged_before {
+                for (message, tokens) in compaction_messages.iter().cloned() {
+                    final_messages.push(message);
+                    final_token_counts.push(tokens);
+                }
+            }
+
+            if i < aged_before && msg.is_agent_visible() && !pinned.contains(&i) {
+                // Compacted messages stay visible to the user only
+                let updated_metadata = msg.metadata.with_agent_invisible();
+                final_messages.push(msg.clone().with_metadata(updated_metadata));
+                final_token_counts.push(0);
+            } else {
+                let tokens = if msg.is_agent_visible() {
+                    token_counter.count_chat_tokens("", std::slice::from_ref(msg), &[])
+                } else {
+                    0
+                };
+                final_messages.push(msg.clone());
+                final_token_counts.push(tokens);
+            }
+        }
+        if aged_before == messages.len() {
+            for (message, tokens) in compaction_messages {
+                final_messages.push(message);
+                final_token_counts.push(tokens);
+            }
+        }
+
+        Ok((
+            Conversation::new_unvalidated(final_messages),
+            final_token_counts,
+            Some(summarization_usage),
+            updated_summary,
+        ))
+    }
+
     /// Public API to summarize the conversation so that its token count is within the allowed context limit.
+    /// Pinned messages are not summarized and stay in the context as they are.
     /// Returns the summarized messages, token counts, and the ProviderUsage from summarization
     pub async fn summarize_context(
         &self,
@@ -62,7 +295,14 @@ impl Agent {
         anyhow::Error,
     > {
         let provider = self.provider().await?;
-        let summary_result = summarize_messages(provider.clone(), messages).await?;
+        let pinned = pinned_indices(messages);
+        let messages_to_summarize: Vec<Message> = messages
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !pinned.contains(i))
+            .map(|(_, msg)| msg.clone())
+            .collect();
+        let summary_result = summarize_messages(provider.clone(), &messages_to_summarize).await?;
 
         let (summary_message, summarization_usage) = match summary_result {
             Some((summary_message, provider_usage)) => (summary_message, Some(provider_usage)),
@@ -74,15 +314,25 @@ impl Agent {
         };
 
         // Create the final message list with updated visibility metadata:
-        // 1. Original messages become user_visible but not agent_visible
+        // 1. Original messages become user_visible but not agent_visible, except pinned ones
         // 2. Summary message becomes agent_visible but not user_visible
         // 3. Assistant messages to continue the conversation remain both user_visible and agent_visible
 
         let mut final_messages = Vec::new();
         let mut final_token_counts = Vec::new();
 
+        let token_counter = create_async_token_counter_for_model(&provider.get_model_config())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create token counter: {}", e))?;
+
         // Add all original messages with updated visibility (preserve user_visible, set agent_visible=false)
-        for msg in messages.iter().cloned() {
+        for (i, msg) in messages.iter().cloned().enumerate() {
+            if pinned.contains(&i) {
+                let tokens = token_counter.count_chat_tokens("", std::slice::from_ref(&msg), &[]);
+                final_messages.push(msg);
+                final_token_counts.push(tokens);
+                continue;
+            }
             let updated_metadata = msg.metadata.with_agent_invisible();
             let updated_msg = msg.with_metadata(updated_metadata);
             final_messages.push(updated_msg);
@@ -125,4 +375,29 @@ impl Agent {
             summarization_usage,
         ))
     }
+
+    /// Public API to break down the tokens of the next request for `messages`: the system prompt,
+    /// each extension's instructions and hints, each tool schema, and the messages grouped by type
+    pub async fn context_usage(&self, messages: &[Message]) -> Result<ContextUsage, anyhow::Error> {
+        let (tools, _toolshim_tools, system_prompt) = self.prepare_tools_and_prompt().await?;
+        let extensions = self.extension_manager.get_extensions_info().await;
+
+        let model_config = self.provider().await?.get_model_config();
+        let token_counter = create_async_token_counter_for_model(&model_config)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create token counter: {}", e))?;
+
+        let entries = context_usage_entries(
+            &token_counter,
+            &system_prompt,
+            &extensions,
+            &tools,
+            messages,
+        );
+        Ok(ContextUsage::new(
+            model_config.model_name.clone(),
+            model_config.context_limit(),
+            entries,
+        ))
+    }
 }
@@ -1,4 +1,8 @@
 use crate::agents::tool_execution::ToolCallResult;
+use crate::conversation::message::Message;
+use crate::providers::base::{Provider, ProviderUsage};
+use crate::providers::errors::ProviderError;
+use crate::providers::structured_output::complete_structured;
 use crate::recipe::Response;
 use indoc::formatdoc;
 use mcp_core::ToolCall;
@@ -143,6 +147,34 @@ impl FinalOutputTool {
         }
     }
 
+    /// Collects the final output with the provider's native structured output, for when the
+    /// model finished without calling the tool. Returns the output along with the usage of
+    /// the calls it took, which the caller records for the session.
+    pub async fn collect_structured_output(
+        &mut self,
+        provider: &dyn Provider,
+        messages: &[Message],
+    ) -> Result<(String, ProviderUsage), ProviderError> {
+        let mut messages = messages.to_vec();
+        messages.push(Message::user().with_text(
+            "Provide the final output for the user now, as JSON matching the required schema.",
+        ));
+        let system = formatdoc! {r#"
+            You produce the final output for a user from the conversation so far.
+            Respond only with JSON that matches the required schema.
+        "#};
+        let (value, usage) = complete_structured(
+            provider,
+            &system,
+            &messages,
+            self.response.json_schema.as_ref().unwrap(),
+        )
+        .await?;
+        let final_output = Self::parsed_final_output_string(value);
+        self.final_output = Some(final_output.clone());
+        Ok((final_output, usage))
+    }
+
     // Formats the parsed JSON as a single line string so its easy to extract from the output
     fn parsed_final_output_string(parsed_json: Value) -> String {
         serde_json::to_string(&parsed_json).unwrap()
@@ -6,6 +6,8 @@ use futures::stream::StreamExt;
 use tracing::debug;
 
 use super::super::agents::Agent;
+use crate::agents::budget::{BudgetExceeded, BudgetTracker};
+use crate::context_mgmt::summarize::RollingSummary;
 use crate::conversation::message::{Message, MessageContent, ToolRequest};
 use crate::conversation::Conversation;
 use crate::providers::base::{stream_from_single_message, MessageStream, Provider, ProviderUsage};
@@ -16,6 +18,7 @@ use crate::providers::toolshim::{
 };
 
 use crate::session;
+use crate::session::usage::{UsageRole, RECORD_LOCK};
 use rmcp::model::Tool;
 
 async fn toolshim_postprocess(
@@ -31,6 +34,23 @@ async fn toolshim_postprocess(
         .map_err(|e| ProviderError::ExecutionError(format!("Failed to augment message: {}", e)))
 }
 
+/// Attribute an agent turn to the lead or worker model when the provider switches between them
+pub(crate) fn usage_role_for(provider: &dyn Provider, usage: &ProviderUsage) -> UsageRole {
+    match provider.as_lead_worker() {
+        Some(lead_worker) => {
+            let (lead, worker) = lead_worker.get_model_info();
+            if usage.model == lead {
+                UsageRole::Lead
+            } else if usage.model == worker {
+                UsageRole::Worker
+            } else {
+                UsageRole::Main
+            }
+        }
+        None => UsageRole::Main,
+    }
+}
+
 impl Agent {
     /// Prepares tools and system prompt for a provider request
     pub async fn prepare_tools_and_prompt(&self) -> anyhow::Result<(Vec<Tool>, Vec<Tool>, String)> {
@@ -273,9 +293,46 @@ impl Agent {
         (frontend_requests, other_requests, filtered_message)
     }
 
+    /// Check the run against its budget, persisting the reason on the session when exceeded
+    pub(crate) async fn check_budget(
+        tracker: &BudgetTracker,
+        session: &Option<crate::agents::types::SessionConfig>,
+    ) -> Option<BudgetExceeded> {
+        if tracker.budget().is_unlimited() {
+            return None;
+        }
+
+        let Some(session_config) = session else {
+            return tracker.check(None);
+        };
+        let session_file_path = match session::storage::get_path(session_config.id.clone()) {
+            Ok(path) => path,
+            Err(e) => {
+                tracing::warn!("Failed to get session file path for budget check: {}", e);
+                return tracker.check(None);
+            }
+        };
+        let _guard = RECORD_LOCK.lock().await;
+        let mut metadata = match session::storage::read_metadata(&session_file_path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                tracing::warn!("Failed to read session metadata for budget check: {}", e);
+                return tracker.check(None);
+            }
+        };
+
+        let exceeded = tracker.check(Some(&metadata))?;
+        metadata.budget_exceeded = Some(exceeded.clone());
+        if let Err(e) = session::storage::update_metadata(&session_file_path, &metadata).await {
+            tracing::error!("Failed to persist budget stop reason: {}", e);
+        }
+        Some(exceeded)
+    }
+
     pub(crate) async fn update_session_metrics(
         session_config: &crate::agents::types::SessionConfig,
         usage: &ProviderUsage,
+        role: UsageRole,
         messages_length: usize,
     ) -> Result<()> {
         let session_file_path = match session::storage::get_path(session_config.id.clone()) {
@@ -284,6 +341,7 @@ impl Agent {
                 return Err(anyhow::anyhow!("Failed to get session file path: {}", e));
             }
         };
+        let _guard = RECORD_LOCK.lock().await;
         let mut metadata = session::storage::read_metadata(&session_file_path)?;
 
         metadata.schedule_id = session_config.schedule_id.clone();
@@ -308,9 +366,22 @@ impl Agent {
             metadata.accumulated_output_tokens,
             usage.usage.output_tokens,
         );
+        session::usage::add_usage(&mut metadata, role, usage).await;
 
         session::storage::update_metadata(&session_file_path, &metadata).await?;
 
         Ok(())
     }
+
+    /// Stores the rolling summary of a compaction, so the next one only adds newly compacted messages
+    pub(crate) async fn save_rolling_summary(
+        session_config: &crate::agents::types::SessionConfig,
+        summary: RollingSummary,
+    ) -> Result<()> {
+        let session_file_path = session::storage::get_path(session_config.id.clone())?;
+        let _guard = RECORD_LOCK.lock().await;
+        let mut metadata = session::storage::read_metadata(&session_file_path)?;
+        metadata.rolling_summary = Some(summary);
+        session::storage::update_metadata(&session_file_path, &metadata).await
+    }
 }
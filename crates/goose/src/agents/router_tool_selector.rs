@@ -11,9 +11,17 @@ use std::collections::VecDeque;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::config::Config;
 use crate::conversation::message::Message;
 use crate::prompt_template::render_global_file;
 use crate::providers::base::Provider;
+use crate::providers::embedding::{embedding_backend, EmbeddingCapable};
+
+/// Value of `GOOSE_ROUTER_TOOL_SELECTION_STRATEGY` that selects tools by embedding similarity
+pub const VECTOR_TOOL_SELECTION_STRATEGY: &str = "vector";
+
+/// Number of tools returned by a vector search when the call doesn't give `k`
+const DEFAULT_VECTOR_SEARCH_RESULTS: usize = 5;
 
 #[derive(Serialize)]
 struct ToolSelectorContext {
@@ -30,6 +38,37 @@ pub trait RouterToolSelector: Send + Sync {
     async fn get_recent_tool_calls(&self, limit: usize) -> Result<Vec<String>, ErrorData>;
 }
 
+fn tool_string(tool: &Tool) -> String {
+    format!(
+        "Tool: {}\nDescription: {}\nSchema: {}",
+        tool.name,
+        tool.description
+            .as_ref()
+            .map(|d| d.as_ref())
+            .unwrap_or_default(),
+        serde_json::to_string_pretty(&tool.input_schema).unwrap_or_else(|_| "{}".to_string())
+    )
+}
+
+fn query_param(params: &Value) -> Result<&str, ErrorData> {
+    params
+        .get("query")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ErrorData {
+            code: ErrorCode::INVALID_PARAMS,
+            message: Cow::from("Missing 'query' parameter"),
+            data: None,
+        })
+}
+
+async fn push_recent_tool_call(recent_calls: &RwLock<VecDeque<String>>, tool_name: &str) {
+    let mut recent_calls = recent_calls.write().await;
+    if recent_calls.len() >= 100 {
+        recent_calls.pop_front();
+    }
+    recent_calls.push_back(tool_name.to_string());
+}
+
 pub struct LLMToolSelector {
     llm_provider: Arc<dyn Provider>,
     tool_strings: Arc<RwLock<HashMap<String, String>>>, // extension_name -> tool_string
@@ -49,14 +88,7 @@ impl LLMToolSelector {
 #[async_trait]
 impl RouterToolSelector for LLMToolSelector {
     async fn select_tools(&self, params: Value) -> Result<Vec<Content>, ErrorData> {
-        let query = params
-            .get("query")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| ErrorData {
-                code: ErrorCode::INVALID_PARAMS,
-                message: Cow::from("Missing 'query' parameter"),
-                data: None,
-            })?;
+        let query = query_param(&params)?;
 
         let extension_name = params
             .get("extension_name")
@@ -124,16 +156,7 @@ impl RouterToolSelector for LLMToolSelector {
         let mut tool_strings = self.tool_strings.write().await;
 
         for tool in tools {
-            let tool_string = format!(
-                "Tool: {}\nDescription: {}\nSchema: {}",
-                tool.name,
-                tool.description
-                    .as_ref()
-                    .map(|d| d.as_ref())
-                    .unwrap_or_default(),
-                serde_json::to_string_pretty(&tool.input_schema)
-                    .unwrap_or_else(|_| "{}".to_string())
-            );
+            let tool_string = tool_string(tool);
 
             // Use the provided extension_name instead of parsing from tool name
             let entry = tool_strings.entry(extension_name.to_string()).or_default();
@@ -158,11 +181,153 @@ impl RouterToolSelector for LLMToolSelector {
     }
 
     async fn record_tool_call(&self, tool_name: &str) -> Result<(), ErrorData> {
-        let mut recent_calls = self.recent_tool_calls.write().await;
-        if recent_calls.len() >= 100 {
-            recent_calls.pop_front();
+        push_recent_tool_call(&self.recent_tool_calls, tool_name).await;
+        Ok(())
+    }
+
+    async fn get_recent_tool_calls(&self, limit: usize) -> Result<Vec<String>, ErrorData> {
+        let recent_calls = self.recent_tool_calls.read().await;
+        Ok(recent_calls.iter().rev().take(limit).cloned().collect())
+    }
+}
+
+struct IndexedTool {
+    name: String,
+    text: String,
+    embedding: Vec<f32>,
+}
+
+/// Selects the tools whose descriptions are closest to the query in embedding space, so
+/// searching needs no model calls beyond the embeddings (which can be computed locally)
+pub struct VectorToolSelector {
+    embeddings: Arc<dyn EmbeddingCapable + Send + Sync>,
+    tools: Arc<RwLock<HashMap<String, Vec<IndexedTool>>>>, // extension_name -> tools
+    recent_tool_calls: Arc<RwLock<VecDeque<String>>>,
+}
+
+impl VectorToolSelector {
+    pub fn new(embeddings: Arc<dyn EmbeddingCapable + Send + Sync>) -> Self {
+        Self {
+            embeddings,
+            tools: Arc::new(RwLock::new(HashMap::new())),
+            recent_tool_calls: Arc::new(RwLock::new(VecDeque::with_capacity(100))),
+        }
+    }
+
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, ErrorData> {
+        let count = texts.len();
+        let embeddings = self
+            .embeddings
+            .create_embeddings(texts)
+            .await
+            .map_err(|e| ErrorData {
+                code: ErrorCode::INTERNAL_ERROR,
+                message: Cow::from(format!("Failed to create embeddings: {}", e)),
+                data: None,
+            })?;
+        if embeddings.len() != count {
+            return Err(ErrorData {
+                code: ErrorCode::INTERNAL_ERROR,
+                message: Cow::from(format!(
+                    "Expected {} embeddings, got {}",
+                    count,
+                    embeddings.len()
+                )),
+                data: None,
+            });
         }
-        recent_calls.push_back(tool_name.to_string());
+        Ok(embeddings)
+    }
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+#[async_trait]
+impl RouterToolSelector for VectorToolSelector {
+    async fn select_tools(&self, params: Value) -> Result<Vec<Content>, ErrorData> {
+        let query = query_param(&params)?;
+        let extension_name = params.get("extension_name").and_then(|v| v.as_str());
+        let k = params
+            .get("k")
+            .and_then(|v| v.as_u64())
+            .map(|k| k as usize)
+            .unwrap_or(DEFAULT_VECTOR_SEARCH_RESULTS);
+
+        let query_embedding = self
+            .embed(vec![query.to_string()])
+            .await?
+            .pop()
+            .unwrap_or_default();
+
+        let tools = self.tools.read().await;
+        let mut scored: Vec<(f32, &IndexedTool)> = tools
+            .iter()
+            .filter(|(ext, _)| extension_name.is_none_or(|name| name == ext.as_str()))
+            .flat_map(|(_, tools)| tools)
+            .map(|tool| (cosine_similarity(&query_embedding, &tool.embedding), tool))
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        Ok(scored
+            .into_iter()
+            .take(k)
+            .map(|(_, tool)| Content::text(tool.text.clone()))
+            .collect())
+    }
+
+    async fn index_tools(&self, tools: &[Tool], extension_name: &str) -> Result<(), ErrorData> {
+        let new_tools: Vec<&Tool> = {
+            let indexed = self.tools.read().await;
+            let existing = indexed.get(extension_name);
+            tools
+                .iter()
+                .filter(|tool| {
+                    existing.is_none_or(|existing| existing.iter().all(|t| t.name != tool.name))
+                })
+                .collect()
+        };
+        if new_tools.is_empty() {
+            return Ok(());
+        }
+
+        // Embed outside the lock, as a local model or an API call can take a while
+        let texts: Vec<String> = new_tools.iter().map(|tool| tool_string(tool)).collect();
+        let embeddings = self.embed(texts.clone()).await?;
+
+        let mut indexed = self.tools.write().await;
+        let entry = indexed.entry(extension_name.to_string()).or_default();
+        for ((tool, text), embedding) in new_tools.into_iter().zip(texts).zip(embeddings) {
+            if entry.iter().all(|t| t.name != tool.name) {
+                entry.push(IndexedTool {
+                    name: tool.name.to_string(),
+                    text,
+                    embedding,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove_tool(&self, tool_name: &str) -> Result<(), ErrorData> {
+        let mut indexed = self.tools.write().await;
+        for tools in indexed.values_mut() {
+            tools.retain(|tool| tool.name != tool_name);
+        }
+        indexed.retain(|_, tools| !tools.is_empty());
+        Ok(())
+    }
+
+    async fn record_tool_call(&self, tool_name: &str) -> Result<(), ErrorData> {
+        push_recent_tool_call(&self.recent_tool_calls, tool_name).await;
         Ok(())
     }
 
@@ -172,10 +337,140 @@ impl RouterToolSelector for LLMToolSelector {
     }
 }
 
-// Helper function to create a boxed tool selector
+// Helper function to create a boxed tool selector, using the strategy set by
+// GOOSE_ROUTER_TOOL_SELECTION_STRATEGY
 pub async fn create_tool_selector(
     provider: Arc<dyn Provider>,
 ) -> Result<Box<dyn RouterToolSelector>> {
+    let strategy: Option<String> = Config::global()
+        .get_param("GOOSE_ROUTER_TOOL_SELECTION_STRATEGY")
+        .ok();
+    if strategy.as_deref() == Some(VECTOR_TOOL_SELECTION_STRATEGY) {
+        let embeddings = embedding_backend(provider)?;
+        return Ok(Box::new(VectorToolSelector::new(embeddings)));
+    }
+
     let selector = LLMToolSelector::new(provider).await?;
     Ok(Box::new(selector))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ModelConfig;
+    use crate::providers::base::{ProviderMetadata, ProviderUsage};
+    use crate::providers::errors::ProviderError;
+    use rmcp::object;
+    use serial_test::serial;
+
+    const KEYWORDS: [&str; 3] = ["file", "weather", "email"];
+
+    /// Embeds text as counts of a few keywords, so similarity follows shared keywords
+    struct KeywordEmbeddingProvider;
+
+    #[async_trait]
+    impl Provider for KeywordEmbeddingProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            ModelConfig::new_or_fail("mock")
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            Err(ProviderError::ExecutionError(
+                "Vector search should not call the model".to_string(),
+            ))
+        }
+
+        fn supports_embeddings(&self) -> bool {
+            true
+        }
+
+        async fn create_embeddings(
+            &self,
+            texts: Vec<String>,
+        ) -> Result<Vec<Vec<f32>>, ProviderError> {
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    let text = text.to_lowercase();
+                    KEYWORDS
+                        .iter()
+                        .map(|keyword| text.matches(keyword).count() as f32)
+                        .collect()
+                })
+                .collect())
+        }
+    }
+
+    fn tool(name: &str, description: &str) -> Tool {
+        Tool::new(
+            name.to_string(),
+            description.to_string(),
+            object!({"type": "object"}),
+        )
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_vector_selector_uses_provider_embeddings() {
+        std::env::set_var(
+            "GOOSE_ROUTER_TOOL_SELECTION_STRATEGY",
+            VECTOR_TOOL_SELECTION_STRATEGY,
+        );
+        std::env::remove_var("GOOSE_EMBEDDING_PROVIDER");
+        let selector = create_tool_selector(Arc::new(KeywordEmbeddingProvider)).await;
+        std::env::remove_var("GOOSE_ROUTER_TOOL_SELECTION_STRATEGY");
+        let selector = selector.unwrap();
+
+        selector
+            .index_tools(
+                &[
+                    tool("developer__read_file", "Read a file from disk"),
+                    tool("developer__send_email", "Send an email"),
+                ],
+                "developer",
+            )
+            .await
+            .unwrap();
+        selector
+            .index_tools(&[tool("weather__forecast", "Get the weather")], "weather")
+            .await
+            .unwrap();
+
+        let results = selector
+            .select_tools(serde_json::json!({
+                "query": "open the file",
+                "extension_name": "developer",
+                "k": 1
+            }))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        let text = results[0].as_text().unwrap().text.clone();
+        assert!(text.starts_with("Tool: developer__read_file"), "{}", text);
+
+        // Other extensions' tools are only searched without an extension filter
+        let results = selector
+            .select_tools(serde_json::json!({"query": "what's the weather?"}))
+            .await
+            .unwrap();
+        let text = results[0].as_text().unwrap().text.clone();
+        assert!(text.starts_with("Tool: weather__forecast"), "{}", text);
+
+        selector.remove_tool("weather__forecast").await.unwrap();
+        let results = selector
+            .select_tools(serde_json::json!({"query": "weather", "extension_name": "weather"}))
+            .await
+            .unwrap();
+        assert!(results.is_empty());
+    }
+}
@@ -187,6 +187,7 @@ impl Agent {
             current_session_id: None,
             process_start_time: None,
             execution_mode: Some(execution_mode.to_string()),
+            budget: None,
         };
 
         match scheduler.add_scheduled_job(job).await {
@@ -477,7 +478,7 @@ impl Agent {
         };
 
         // Check if session file exists
-        if !session_path.exists() {
+        if !crate::session::storage::session_exists(&session_path) {
             return Err(ErrorData::new(
                 ErrorCode::INTERNAL_ERROR,
                 format!("Session '{}' not found", session_id),
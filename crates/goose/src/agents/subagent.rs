@@ -4,7 +4,9 @@ use crate::{
     agents::{extension_manager::ExtensionManager, Agent, TaskConfig},
     config::ExtensionConfigManager,
     prompt_template::render_global_file,
+    providers::base::ProviderUsage,
     providers::errors::ProviderError,
+    session,
 };
 use anyhow::anyhow;
 use chrono::{DateTime, Utc};
@@ -171,7 +173,11 @@ impl SubAgent {
             )
             .await
             {
-                Ok((response, _usage)) => {
+                Ok((response, usage)) => {
+                    if let Some(parent_session) = &self.config.parent_session {
+                        self.record_usage(parent_session, &usage).await;
+                    }
+
                     // Process any tool calls in the response
                     let tool_requests: Vec<ToolRequest> = response
                         .content
@@ -281,6 +287,17 @@ impl SubAgent {
         conversation.push(message);
     }
 
+    /// Record the usage of a provider call against the session that spawned this subagent
+    async fn record_usage(&self, parent_session: &session::Identifier, usage: &ProviderUsage) {
+        let result = match session::get_path(parent_session.clone()) {
+            Ok(path) => session::record_usage(&path, session::UsageRole::Subagent, usage).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            error!("Failed to record subagent usage for {}: {}", self.id, e);
+        }
+    }
+
     /// Build the system prompt for the subagent using the template
     async fn build_system_prompt(&self, available_tools: &[Tool]) -> Result<String, anyhow::Error> {
         let mut context = HashMap::new();
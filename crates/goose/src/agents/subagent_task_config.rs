@@ -1,4 +1,5 @@
 use crate::providers::base::Provider;
+use crate::session::Identifier;
 use std::env;
 use std::fmt;
 use std::sync::Arc;
@@ -17,6 +18,8 @@ pub struct TaskConfig {
     pub provider: Option<Arc<dyn Provider>>,
     pub max_turns: Option<usize>,
     pub extensions: Option<Vec<crate::agents::extension::ExtensionConfig>>,
+    /// Session that subagent provider usage is recorded against
+    pub parent_session: Option<Identifier>,
 }
 
 impl fmt::Debug for TaskConfig {
@@ -26,6 +29,7 @@ impl fmt::Debug for TaskConfig {
             .field("provider", &"<dyn Provider>")
             .field("max_turns", &self.max_turns)
             .field("extensions", &self.extensions)
+            .field("parent_session", &self.parent_session)
             .finish()
     }
 }
@@ -43,6 +47,7 @@ impl TaskConfig {
                     .unwrap_or(DEFAULT_SUBAGENT_MAX_TURNS),
             ),
             extensions: None,
+            parent_session: None,
         }
     }
 
@@ -1,3 +1,4 @@
+use crate::agents::budget::SessionBudget;
 use crate::session;
 use mcp_core::ToolResult;
 use rmcp::model::{Content, Tool};
@@ -94,4 +95,7 @@ pub struct SessionConfig {
     /// Retry configuration for automated validation and recovery
     #[serde(skip_serializing_if = "Option::is_none")]
     pub retry_config: Option<RetryConfig>,
+    /// Token, cost and time limits for the session, on top of any configured globally
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<SessionBudget>,
 }
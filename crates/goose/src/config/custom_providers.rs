@@ -3,7 +3,7 @@ use crate::model::ModelConfig;
 use crate::providers::anthropic::AnthropicProvider;
 use crate::providers::base::ModelInfo;
 use crate::providers::ollama::OllamaProvider;
-use crate::providers::openai::OpenAiProvider;
+use crate::providers::openai::{OpenAiApi, OpenAiProvider};
 use anyhow::Result;
 use etcetera::{choose_app_strategy, AppStrategy};
 use serde::{Deserialize, Serialize};
@@ -37,6 +37,13 @@ pub struct CustomProviderConfig {
     pub headers: Option<HashMap<String, String>>,
     pub timeout_seconds: Option<u64>,
     pub supports_streaming: Option<bool>,
+    /// For the OpenAI engine, which API to use; inferred from the base URL when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openai_api: Option<OpenAiApi>,
+    /// For the OpenAI Responses API, continue from the stored previous response
+    /// instead of sending the whole conversation each turn
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub responses_stateful: Option<bool>,
 }
 
 impl CustomProviderConfig {
@@ -95,6 +102,8 @@ impl CustomProviderConfig {
             headers: None,
             timeout_seconds: None,
             supports_streaming,
+            openai_api: None,
+            responses_stateful: None,
         };
 
         // save to JSON file
@@ -1,22 +1,74 @@
 use crate::conversation::message::Message;
 use crate::conversation::Conversation;
 use crate::{
-    agents::Agent, config::Config, context_mgmt::get_messages_token_counts_async,
-    token_counter::create_async_token_counter,
+    agents::Agent,
+    config::Config,
+    context_mgmt::{
+        estimate_target_context_limit, get_messages_token_counts_async, summarize::RollingSummary,
+    },
+    session::storage::SessionMetadata,
+    token_counter::create_async_token_counter_for_model,
 };
 use anyhow::Result;
+use std::path::Path;
 use tracing::{debug, info};
 
+/// Number of most recent turns kept verbatim when compacting, unless set with
+/// `GOOSE_AUTO_COMPACT_KEEP_TURNS`
+pub const DEFAULT_KEEP_TURNS: usize = 2;
+
+/// Fraction of the compaction threshold the local token estimate has to reach before the
+/// provider is asked for an exact count
+const EXACT_COUNT_MARGIN: f64 = 0.8;
+
+/// Thresholds of 0 or 1 and beyond turn auto-compaction off
+fn compaction_enabled(threshold: f64) -> bool {
+    threshold > 0.0 && threshold < 1.0
+}
+
+fn keep_turns_from_config() -> usize {
+    Config::global()
+        .get_param::<usize>("GOOSE_AUTO_COMPACT_KEEP_TURNS")
+        .unwrap_or(DEFAULT_KEEP_TURNS)
+}
+
+/// How compaction reduces the conversation, set with `GOOSE_AUTO_COMPACT_STRATEGY`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompactionStrategy {
+    /// Fold older messages into the rolling summary (`summarize`)
+    #[default]
+    Summarize,
+    /// Replace old tool outputs with short stubs first, and summarize only if that
+    /// is not enough (`prune_tool_output`)
+    PruneToolOutput,
+}
+
+impl CompactionStrategy {
+    pub fn from_config() -> Self {
+        let strategy = Config::global()
+            .get_param::<String>("GOOSE_AUTO_COMPACT_STRATEGY")
+            .unwrap_or_default();
+        match strategy.as_str() {
+            "prune_tool_output" => Self::PruneToolOutput,
+            _ => Self::Summarize,
+        }
+    }
+}
+
 /// Result of auto-compaction check
 #[derive(Debug)]
 pub struct AutoCompactResult {
     /// Whether compaction was performed
     pub compacted: bool,
+    /// Whether the conversation was summarized, rather than only having tool outputs pruned
+    pub summarized: bool,
     /// The messages after potential compaction
     pub messages: Conversation,
     /// Provider usage from summarization (if compaction occurred)
     /// This contains the actual token counts after compaction
     pub summarization_usage: Option<crate::providers::base::ProviderUsage>,
+    /// The updated rolling summary, to store in the session metadata (if summarized)
+    pub rolling_summary: Option<RollingSummary>,
 }
 
 /// Result of checking if compaction is needed
@@ -40,8 +92,9 @@ pub struct CompactionCheckResult {
 ///
 /// This function analyzes the current token usage and returns detailed information
 /// about whether compaction is needed and how close we are to the threshold.
-/// It prioritizes actual token counts from session metadata when available,
-/// falling back to estimated counts if needed.
+/// It prioritizes actual token counts from session metadata when available. Otherwise it
+/// estimates with the model's tokenizer, and asks the provider for an exact count only when
+/// the estimate is close to the threshold.
 ///
 /// # Arguments
 /// * `agent` - The agent to use for context management
@@ -67,23 +120,52 @@ pub async fn check_compaction_needed(
 
     let provider = agent.provider().await?;
     let context_limit = provider.get_model_config().context_limit();
+    let threshold_tokens = (context_limit as f64 * threshold) as usize;
 
     let (current_tokens, token_source) = match session_metadata.and_then(|m| m.total_tokens) {
         Some(tokens) => (tokens as usize, "session metadata"),
         None => {
-            let token_counter = create_async_token_counter()
+            let visible_messages: Vec<Message> = messages
+                .iter()
+                .filter(|m| m.is_agent_visible())
+                .cloned()
+                .collect();
+            // Count the request the agent would send next, including its prompt and tools
+            let (tools, _, system_prompt) = agent.prepare_tools_and_prompt().await?;
+            let token_counter = create_async_token_counter_for_model(&provider.get_model_config())
                 .await
                 .map_err(|e| anyhow::anyhow!("Failed to create token counter: {}", e))?;
-            let token_counts = get_messages_token_counts_async(&token_counter, messages);
-            (token_counts.iter().sum(), "estimated")
+            let token_counts = get_messages_token_counts_async(&token_counter, &visible_messages);
+            let estimate = token_counts.iter().sum::<usize>()
+                + token_counter.count_tokens(&system_prompt)
+                + token_counter.count_tokens_for_tools(&tools);
+
+            // The provider's exact count costs a request, so it is only worth asking for
+            // when the estimate is close enough to the threshold for the difference to matter
+            let near_threshold = compaction_enabled(threshold)
+                && estimate as f64 >= threshold_tokens as f64 * EXACT_COUNT_MARGIN;
+            if near_threshold {
+                match provider
+                    .count_tokens(&system_prompt, &visible_messages, &tools)
+                    .await
+                {
+                    Ok(Some(tokens)) => (tokens, "provider"),
+                    Ok(None) => (estimate, "estimated"),
+                    Err(e) => {
+                        debug!("Provider token count failed, estimating instead: {}", e);
+                        (estimate, "estimated")
+                    }
+                }
+            } else {
+                (estimate, "estimated")
+            }
         }
     };
 
     // Calculate usage ratio
     let usage_ratio = current_tokens as f64 / context_limit as f64;
 
-    // Calculate threshold token count and remaining tokens
-    let threshold_tokens = (context_limit as f64 * threshold) as usize;
+    // Calculate remaining tokens
     let remaining_tokens = threshold_tokens.saturating_sub(current_tokens);
 
     // Calculate percentage until compaction (how much more we can use before hitting threshold)
@@ -94,11 +176,7 @@ pub async fn check_compaction_needed(
     };
 
     // Check if compaction is needed (disabled if threshold is invalid)
-    let needs_compaction = if threshold <= 0.0 || threshold >= 1.0 {
-        false
-    } else {
-        usage_ratio > threshold
-    };
+    let needs_compaction = compaction_enabled(threshold) && usage_ratio > threshold;
 
     debug!(
         "Compaction check: {} / {} tokens ({:.1}%), threshold: {:.1}%, needs compaction: {}, source: {}",
@@ -122,19 +200,50 @@ pub async fn check_compaction_needed(
 
 /// Perform compaction on messages without checking thresholds
 ///
-/// This function directly performs compaction on the provided messages.
+/// This function directly performs compaction on the provided messages, using the
+/// strategy configured with GOOSE_AUTO_COMPACT_STRATEGY.
 /// If the most recent message is a user message, it will be preserved by removing it
 /// before compaction and adding it back afterwards.
 ///
 /// # Arguments
 /// * `agent` - The agent to use for context management
 /// * `messages` - The current message history
+/// * `session_metadata` - Optional session metadata containing the rolling summary
+/// * `session_file` - Optional session file, pruned tool outputs are kept with its data
 ///
 /// # Returns
 /// * `AutoCompactResult` containing the compacted messages and metadata
-pub async fn perform_compaction(agent: &Agent, messages: &[Message]) -> Result<AutoCompactResult> {
+pub async fn perform_compaction(
+    agent: &Agent,
+    messages: &[Message],
+    session_metadata: Option<&SessionMetadata>,
+    session_file: Option<&Path>,
+) -> Result<AutoCompactResult> {
     info!("Performing message compaction");
+    let summary = session_metadata
+        .and_then(|metadata| metadata.rolling_summary.clone())
+        .unwrap_or_default();
+    compact_messages(
+        agent,
+        messages,
+        CompactionStrategy::from_config(),
+        &summary,
+        keep_turns_from_config(),
+        session_file,
+    )
+    .await
+}
 
+/// Compact messages with the given strategy, preserving a trailing user message.
+/// Messages older than the most recent `keep_turns` turns are folded into `summary`.
+async fn compact_messages(
+    agent: &Agent,
+    messages: &[Message],
+    strategy: CompactionStrategy,
+    summary: &RollingSummary,
+    keep_turns: usize,
+    session_file: Option<&Path>,
+) -> Result<AutoCompactResult> {
     // Check if the most recent message is a user message
     let (messages_to_compact, preserved_user_message) = if let Some(last_message) = messages.last()
     {
@@ -148,25 +257,61 @@ pub async fn perform_compaction(agent: &Agent, messages: &[Message]) -> Result<A
         (messages, None)
     };
 
-    // Perform the compaction on messages excluding the preserved user message
-    let (mut compacted_messages, _, summarization_usage) =
-        agent.summarize_context(messages_to_compact).await?;
+    let (mut compacted_messages, summarization_usage, rolling_summary) = match strategy {
+        CompactionStrategy::Summarize => {
+            // The rolling_summarize_context method already handles the visibility properly
+            let (summary_messages, _, summarization_usage, rolling_summary) = agent
+                .rolling_summarize_context(messages_to_compact, summary, keep_turns)
+                .await?;
+            (summary_messages, summarization_usage, Some(rolling_summary))
+        }
+        CompactionStrategy::PruneToolOutput => {
+            let (pruned_messages, token_counts) = agent
+                .prune_context(messages_to_compact, session_file)
+                .await?;
+            let target_context_limit = estimate_target_context_limit(agent.provider().await?);
+            let pruned_tokens: usize = token_counts.iter().sum();
+
+            // Pruning adds a stubbed copy of each pruned message
+            let pruned_any = pruned_messages.len() > messages_to_compact.len();
+            if pruned_any && pruned_tokens <= target_context_limit {
+                info!(
+                    "Pruned old tool outputs to {} tokens, no summarization needed",
+                    pruned_tokens
+                );
+                (pruned_messages, None, None)
+            } else {
+                info!(
+                    "Pruning old tool outputs left {} tokens, summarizing",
+                    pruned_tokens
+                );
+                let (summary_messages, _, summarization_usage, rolling_summary) = agent
+                    .rolling_summarize_context(pruned_messages.messages(), summary, keep_turns)
+                    .await?;
+                (summary_messages, summarization_usage, Some(rolling_summary))
+            }
+        }
+    };
 
     // Add back the preserved user message if it exists
+    // (keeps default visibility: both true)
     if let Some(user_message) = preserved_user_message {
         compacted_messages.push(user_message);
     }
 
     Ok(AutoCompactResult {
         compacted: true,
+        summarized: rolling_summary.is_some(),
         messages: compacted_messages,
         summarization_usage,
+        rolling_summary,
     })
 }
 
 /// Check if messages need compaction and compact them if necessary
 ///
-/// This is a convenience wrapper function that combines checking and compaction.
+/// This is a convenience wrapper function that combines checking and compaction,
+/// using the strategy configured with GOOSE_AUTO_COMPACT_STRATEGY.
 /// If the most recent message is a user message, it will be preserved by removing it
 /// before compaction and adding it back afterwards.
 ///
@@ -174,7 +319,8 @@ pub async fn perform_compaction(agent: &Agent, messages: &[Message]) -> Result<A
 /// * `agent` - The agent to use for context management
 /// * `messages` - The current message history
 /// * `threshold_override` - Optional threshold override (defaults to GOOSE_AUTO_COMPACT_THRESHOLD config)
-/// * `session_metadata` - Optional session metadata containing actual token counts
+/// * `session_metadata` - Optional session metadata containing actual token counts and the rolling summary
+/// * `session_file` - Optional session file, pruned tool outputs are kept with its data
 ///
 /// # Returns
 /// * `AutoCompactResult` containing the potentially compacted messages and metadata
@@ -183,6 +329,7 @@ pub async fn check_and_compact_messages(
     messages: &[Message],
     threshold_override: Option<f64>,
     session_metadata: Option<&crate::session::storage::SessionMetadata>,
+    session_file: Option<&Path>,
 ) -> Result<AutoCompactResult> {
     // First check if compaction is needed
     let check_result =
@@ -197,8 +344,10 @@ pub async fn check_and_compact_messages(
         );
         return Ok(AutoCompactResult {
             compacted: false,
+            summarized: false,
             messages: Conversation::new_unvalidated(messages.to_vec()),
             summarization_usage: None,
+            rolling_summary: None,
         });
     }
 
@@ -207,35 +356,18 @@ pub async fn check_and_compact_messages(
         check_result.usage_ratio * 100.0
     );
 
-    // Check if the most recent message is a user message
-    let (messages_to_compact, preserved_user_message) = if let Some(last_message) = messages.last()
-    {
-        if matches!(last_message.role, rmcp::model::Role::User) {
-            // Remove the last user message before auto-compaction
-            (&messages[..messages.len() - 1], Some(last_message.clone()))
-        } else {
-            (messages, None)
-        }
-    } else {
-        (messages, None)
-    };
-
-    // Perform the compaction on messages excluding the preserved user message
-    // The summarize_context method already handles the visibility properly
-    let (mut summary_messages, _, summarization_usage) =
-        agent.summarize_context(messages_to_compact).await?;
-
-    // Add back the preserved user message if it exists
-    // (keeps default visibility: both true)
-    if let Some(user_message) = preserved_user_message {
-        summary_messages.push(user_message);
-    }
-
-    Ok(AutoCompactResult {
-        compacted: true,
-        messages: summary_messages,
-        summarization_usage,
-    })
+    let summary = session_metadata
+        .and_then(|metadata| metadata.rolling_summary.clone())
+        .unwrap_or_default();
+    compact_messages(
+        agent,
+        messages,
+        CompactionStrategy::from_config(),
+        &summary,
+        keep_turns_from_config(),
+        session_file,
+    )
+    .await
 }
 
 #[cfg(test)]
@@ -318,6 +450,12 @@ mod tests {
             accumulated_output_tokens: Some(50),
             extension_data: crate::session::ExtensionData::new(),
             recipe: None,
+            parent_session_id: None,
+            fork_point: None,
+            model_usage: Vec::new(),
+            budget_exceeded: None,
+            checkpoints: Vec::new(),
+            rolling_summary: None,
         }
     }
 
@@ -389,7 +527,7 @@ mod tests {
         let messages = vec![create_test_message("Hello"), create_test_message("World")];
 
         // Test with threshold 0 (disabled)
-        let result = check_and_compact_messages(&agent, &messages, Some(0.0), None)
+        let result = check_and_compact_messages(&agent, &messages, Some(0.0), None, None)
             .await
             .unwrap();
 
@@ -398,7 +536,7 @@ mod tests {
         assert!(result.summarization_usage.is_none());
 
         // Test with threshold 1.0 (disabled)
-        let result = check_and_compact_messages(&agent, &messages, Some(1.0), None)
+        let result = check_and_compact_messages(&agent, &messages, Some(1.0), None, None)
             .await
             .unwrap();
 
@@ -419,7 +557,7 @@ mod tests {
         // Create small messages that won't trigger compaction
         let messages = vec![create_test_message("Hello"), create_test_message("World")];
 
-        let result = check_and_compact_messages(&agent, &messages, Some(0.3), None)
+        let result = check_and_compact_messages(&agent, &messages, Some(0.3), None, None)
             .await
             .unwrap();
 
@@ -461,7 +599,7 @@ mod tests {
             )));
         }
 
-        let result = check_and_compact_messages(&agent, &messages, Some(0.3), None)
+        let result = check_and_compact_messages(&agent, &messages, Some(0.3), None, None)
             .await
             .unwrap();
 
@@ -520,7 +658,7 @@ mod tests {
             .unwrap();
 
         // Should use config value when no override provided
-        let result = check_and_compact_messages(&agent, &messages, None, None)
+        let result = check_and_compact_messages(&agent, &messages, None, None, None)
             .await
             .unwrap();
 
@@ -570,6 +708,7 @@ mod tests {
             &messages,
             Some(0.3), // 30% threshold
             Some(&session_metadata),
+            None,
         )
         .await
         .unwrap();
@@ -658,6 +797,7 @@ mod tests {
             &messages,
             Some(0.3), // 30% threshold
             Some(&session_metadata),
+            None,
         )
         .await
         .unwrap();
@@ -763,4 +903,341 @@ mod tests {
         // With estimation, likely won't trigger compaction
         assert!(!result_edge_case.needs_compaction);
     }
+
+    /// Replies with a new rolling summary each time, recording the prompts it was sent
+    struct RecordingProvider {
+        model_config: ModelConfig,
+        prompts: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for RecordingProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            self.model_config.clone()
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            let mut prompts = self.prompts.lock().unwrap();
+            prompts.push(system.to_string());
+            let summary = serde_json::json!({
+                "goals": [format!("goal {}", prompts.len())],
+                "decisions": [],
+                "files_touched": [],
+                "open_todos": [],
+                "notes": []
+            });
+            Ok((
+                Message::assistant().with_text(summary.to_string()),
+                ProviderUsage::new("mock".to_string(), Usage::default()),
+            ))
+        }
+    }
+
+    /// Counts tokens like a provider with a token counting endpoint, recording the requests
+    struct CountingProvider {
+        model_config: ModelConfig,
+        counted: std::sync::Mutex<Vec<(String, Vec<Tool>)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for CountingProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            self.model_config.clone()
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            Ok((
+                Message::assistant().with_text("Summary of conversation"),
+                ProviderUsage::new("mock".to_string(), Usage::default()),
+            ))
+        }
+
+        async fn count_tokens(
+            &self,
+            system: &str,
+            _messages: &[Message],
+            tools: &[Tool],
+        ) -> Result<Option<usize>, ProviderError> {
+            self.counted
+                .lock()
+                .unwrap()
+                .push((system.to_string(), tools.to_vec()));
+            Ok(Some(42))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_compaction_counts_system_prompt_and_tools() {
+        let counting_provider = |context_limit| {
+            Arc::new(CountingProvider {
+                model_config: ModelConfig::new("test-model")
+                    .unwrap()
+                    .with_context_limit(Some(context_limit)),
+                counted: std::sync::Mutex::new(Vec::new()),
+            })
+        };
+        let agent = Agent::new();
+        agent
+            .extend_system_prompt("Always answer in French.".to_string())
+            .await;
+        let messages = vec![create_test_message("Hello")];
+
+        // Far from the threshold, the local estimate is enough
+        let provider = counting_provider(10_000_000);
+        let _ = agent.update_provider(provider.clone()).await;
+        let result = check_compaction_needed(&agent, &messages, Some(0.3), None)
+            .await
+            .unwrap();
+        assert_ne!(result.current_tokens, 42);
+        assert!(result.current_tokens > 0);
+        assert!(provider.counted.lock().unwrap().is_empty());
+
+        // Close to it, the provider counts the request the agent would send, not just the
+        // messages
+        let provider = counting_provider(1_000);
+        let _ = agent.update_provider(provider.clone()).await;
+        let result = check_compaction_needed(&agent, &messages, Some(0.3), None)
+            .await
+            .unwrap();
+        assert_eq!(result.current_tokens, 42);
+
+        let (tools, _, system_prompt) = agent.prepare_tools_and_prompt().await.unwrap();
+        let counted = provider.counted.lock().unwrap();
+        assert_eq!(counted.len(), 1);
+        assert!(counted[0].0.contains("Always answer in French."));
+        assert_eq!(counted[0].0, system_prompt);
+        let tool_names = |tools: &[Tool]| -> Vec<String> {
+            tools.iter().map(|tool| tool.name.to_string()).collect()
+        };
+        assert_eq!(tool_names(&counted[0].1), tool_names(&tools));
+    }
+
+    fn create_turns(turns: std::ops::Range<usize>) -> Vec<Message> {
+        turns
+            .flat_map(|i| {
+                [
+                    create_test_message(&format!("turn {}", i)),
+                    Message::assistant().with_text(format!("answer {}", i)),
+                ]
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_rolling_summary_is_updated_incrementally() {
+        let provider = Arc::new(RecordingProvider {
+            model_config: ModelConfig::new("test-model")
+                .unwrap()
+                .with_context_limit(Some(30_000)),
+            prompts: std::sync::Mutex::new(Vec::new()),
+        });
+
+        let agent = Agent::new();
+        let _ = agent.update_provider(provider.clone()).await;
+
+        let mut messages = create_turns(0..4);
+        messages.push(create_test_message("turn 4"));
+
+        let first = compact_messages(
+            &agent,
+            &messages,
+            CompactionStrategy::Summarize,
+            &RollingSummary::default(),
+            1,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert!(first.summarized);
+        let first_summary = first.rolling_summary.unwrap();
+        assert_eq!(first_summary.goals, vec!["goal 1"]);
+        {
+            let prompts = provider.prompts.lock().unwrap();
+            assert!(prompts[0].contains("answer 2"));
+            assert!(!prompts[0].contains("turn 3"));
+        }
+
+        // Continue the session, and compact again with the stored summary
+        let mut messages = first.messages.messages().clone();
+        messages.push(Message::assistant().with_text("answer 4"));
+        messages.push(create_test_message("turn 5"));
+
+        let second = compact_messages(
+            &agent,
+            &messages,
+            CompactionStrategy::Summarize,
+            &first_summary,
+            1,
+            None,
+        )
+        .await
+        .unwrap();
+
+        // Only the newly compacted turn is sent, along with the previous summary
+        {
+            let prompts = provider.prompts.lock().unwrap();
+            assert!(prompts[1].contains("goal 1"));
+            assert!(prompts[1].contains("answer 3"));
+            assert!(!prompts[1].contains("answer 2"));
+            assert!(!prompts[1].contains("turn 4"));
+        }
+
+        // The agent sees the new summary followed by the most recent turns verbatim
+        let agent_visible: Vec<String> = second
+            .messages
+            .iter()
+            .filter(|m| m.is_agent_visible())
+            .map(|m| m.as_concat_text())
+            .collect();
+        assert_eq!(agent_visible.len(), 5);
+        assert!(agent_visible[0].contains("goal 2"));
+        assert_eq!(agent_visible[2..], ["turn 4", "answer 4", "turn 5"]);
+
+        // The user still sees every message
+        assert_eq!(
+            second
+                .messages
+                .iter()
+                .filter(|m| m.is_user_visible() && !m.as_concat_text().is_empty())
+                .count(),
+            11
+        );
+    }
+
+    fn create_tool_exchanges(count: usize) -> Vec<Message> {
+        let output: String = (0..400)
+            .map(|i| format!("line {} of the build log\n", i))
+            .collect();
+        let mut messages = vec![create_test_message("Build the project")];
+        for i in 0..count {
+            let id = format!("tool{}", i);
+            messages.push(Message::assistant().with_tool_request(
+                &id,
+                Ok(mcp_core::tool::ToolCall::new(
+                    "shell",
+                    serde_json::json!({"command": "cargo build"}),
+                )),
+            ));
+            messages.push(
+                Message::user()
+                    .with_tool_response(&id, Ok(vec![rmcp::model::Content::text(&output)])),
+            );
+        }
+        messages
+    }
+
+    #[tokio::test]
+    async fn test_prune_tool_output_without_summarizing() {
+        let mock_provider = Arc::new(MockProvider {
+            model_config: ModelConfig::new("test-model")
+                .unwrap()
+                .with_context_limit(Some(30_000)),
+        });
+
+        let agent = Agent::new();
+        let _ = agent.update_provider(mock_provider).await;
+
+        let mut messages = create_tool_exchanges(10);
+        messages.push(create_test_message("Now run the tests"));
+
+        let result = compact_messages(
+            &agent,
+            &messages,
+            CompactionStrategy::PruneToolOutput,
+            &RollingSummary::default(),
+            DEFAULT_KEEP_TURNS,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert!(result.compacted);
+        assert!(!result.summarized);
+        assert!(result.summarization_usage.is_none());
+
+        // The user still sees every message as it was
+        let user_visible: Vec<&Message> = result
+            .messages
+            .iter()
+            .filter(|m| m.is_user_visible())
+            .collect();
+        assert_eq!(user_visible.len(), messages.len());
+        assert!(user_visible
+            .iter()
+            .zip(&messages)
+            .all(|(a, b)| a.content == b.content));
+
+        // The agent sees every tool request, with stubs for the older outputs
+        let agent_visible: Vec<&Message> = result
+            .messages
+            .iter()
+            .filter(|m| m.is_agent_visible())
+            .collect();
+        assert_eq!(agent_visible.len(), messages.len());
+        assert_eq!(
+            agent_visible.iter().filter(|m| m.is_tool_call()).count(),
+            10
+        );
+        let pruned = agent_visible
+            .iter()
+            .zip(&messages)
+            .filter(|(a, b)| a.content != b.content)
+            .count();
+        assert!(pruned > 0 && pruned <= 7);
+        assert_eq!(
+            result.messages.last().unwrap().as_concat_text(),
+            "Now run the tests"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prune_tool_output_falls_back_to_summarizing() {
+        let mock_provider = Arc::new(MockProvider {
+            model_config: ModelConfig::new("test-model")
+                .unwrap()
+                .with_context_limit(Some(30_000)),
+        });
+
+        let agent = Agent::new();
+        let _ = agent.update_provider(mock_provider).await;
+
+        // Nothing to prune, so the conversation is summarized
+        let messages = vec![create_test_message("Hello"), create_test_message("World")];
+
+        let result = compact_messages(
+            &agent,
+            &messages,
+            CompactionStrategy::PruneToolOutput,
+            &RollingSummary::default(),
+            DEFAULT_KEEP_TURNS,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert!(result.compacted);
+        assert!(result.summarized);
+        assert!(result.summarization_usage.is_some());
+    }
 }
@@ -1,24 +1,201 @@
-use crate::conversation::message::Message;
+use crate::conversation::message::{Message, MessageContent};
 use crate::prompt_template::render_global_file;
 use crate::providers::base::Provider;
+use crate::providers::structured_output::{complete_structured, extract_json};
+use crate::utils::safe_truncate;
 
 use anyhow::Result;
 use rmcp::model::Role;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::sync::Arc;
+use utoipa::ToSchema;
 
 #[derive(Serialize)]
 struct SummarizeContext {
     messages: String,
 }
 
+#[derive(Serialize)]
+struct RollingSummaryContext {
+    summary: String,
+    messages: String,
+}
+
 use crate::providers::base::ProviderUsage;
 
+/// Maximum size of a tool call or tool result, in characters, when it is summarized
+const MAX_SUMMARIZED_TOOL_CONTENT_SIZE: usize = 2000;
+
+/// A running summary of the part of a session that no longer fits in the context,
+/// stored in the session metadata and updated each time more messages are compacted
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RollingSummary {
+    /// What the user wants to achieve
+    #[serde(default)]
+    pub goals: Vec<String>,
+    /// Key decisions and designs, and why they were made
+    #[serde(default)]
+    pub decisions: Vec<String>,
+    /// Files viewed, created or changed, with what was done to each
+    #[serde(default)]
+    pub files_touched: Vec<String>,
+    /// Work still to be done
+    #[serde(default)]
+    pub open_todos: Vec<String>,
+    /// Anything else needed to continue, such as errors and their fixes
+    #[serde(default)]
+    pub notes: Vec<String>,
+}
+
+impl RollingSummary {
+    fn schema() -> Value {
+        let list = json!({"type": "array", "items": {"type": "string"}});
+        json!({
+            "type": "object",
+            "properties": {
+                "goals": list,
+                "decisions": list,
+                "files_touched": list,
+                "open_todos": list,
+                "notes": list
+            },
+            "required": ["goals", "decisions", "files_touched", "open_todos", "notes"],
+            "additionalProperties": false
+        })
+    }
+
+    /// The summary as the text the agent continues the session from
+    pub fn to_text(&self) -> String {
+        let sections = [
+            ("Goals", &self.goals),
+            ("Decisions", &self.decisions),
+            ("Files Touched", &self.files_touched),
+            ("Open TODOs", &self.open_todos),
+            ("Notes", &self.notes),
+        ];
+        let mut text = "Summary of the earlier part of this session:".to_string();
+        for (title, items) in sections {
+            if items.is_empty() {
+                continue;
+            }
+            text.push_str(&format!("\n\n## {}", title));
+            for item in items {
+                text.push_str(&format!("\n- {}", item));
+            }
+        }
+        text
+    }
+}
+
+/// Formats messages as plain text for a summarization prompt, shortening tool calls and results
+fn format_messages_for_summary(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .map(|message| {
+            let role = match message.role {
+                Role::User => "user",
+                Role::Assistant => "assistant",
+            };
+            let parts: Vec<String> = message
+                .content
+                .iter()
+                .filter_map(|content| match content {
+                    MessageContent::Text(text) => Some(text.text.clone()),
+                    MessageContent::ToolRequest(request) => Some(match &request.tool_call {
+                        Ok(call) => format!(
+                            "[tool call: {}] {}",
+                            call.name,
+                            safe_truncate(
+                                &call.arguments.to_string(),
+                                MAX_SUMMARIZED_TOOL_CONTENT_SIZE
+                            )
+                        ),
+                        Err(e) => format!("[invalid tool call] {}", e),
+                    }),
+                    MessageContent::ToolResponse(response) => Some(match &response.tool_result {
+                        Ok(contents) => {
+                            let text = contents
+                                .iter()
+                                .filter_map(|content| content.as_text())
+                                .map(|text| text.text.as_str())
+                                .collect::<Vec<_>>()
+                                .join("\n");
+                            format!(
+                                "[tool result] {}",
+                                safe_truncate(&text, MAX_SUMMARIZED_TOOL_CONTENT_SIZE)
+                            )
+                        }
+                        Err(e) => format!("[tool error] {}", e),
+                    }),
+                    _ => None,
+                })
+                .collect();
+            format!("{}: {}", role, parts.join("\n"))
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Updates a rolling summary with messages that were just compacted, so that only those
+/// messages are sent to the model rather than the whole conversation
+pub async fn update_rolling_summary(
+    provider: Arc<dyn Provider>,
+    summary: &RollingSummary,
+    messages: &[Message],
+) -> Result<(RollingSummary, ProviderUsage), anyhow::Error> {
+    let context = RollingSummaryContext {
+        summary: serde_json::to_string_pretty(summary)?,
+        messages: format_messages_for_summary(messages),
+    };
+    let system_prompt = render_global_file("summarize_rolling.md", &context)?;
+    let request = vec![Message::user().with_text(
+        "Please update the summary with the new messages provided in the system prompt.",
+    )];
+
+    if provider.supports_structured_output() {
+        let (value, usage) = complete_structured(
+            provider.as_ref(),
+            &system_prompt,
+            &request,
+            &RollingSummary::schema(),
+        )
+        .await?;
+        return Ok((serde_json::from_value(value)?, usage));
+    }
+
+    let (response, mut provider_usage) = provider
+        .complete_fast(&system_prompt, &request, &[])
+        .await?;
+    provider_usage
+        .ensure_tokens(&system_prompt, &request, &response, &[])
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to ensure usage tokens: {}", e))?;
+
+    let updated = extract_json(&response)
+        .ok()
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_else(|| {
+            // Keep the reply as a note rather than losing what was summarized
+            tracing::warn!("Rolling summary update is not valid JSON, keeping it as a note");
+            let mut updated = summary.clone();
+            updated.notes.push(response.as_concat_text());
+            updated
+        });
+
+    Ok((updated, provider_usage))
+}
+
 /// Summarization function that uses the detailed prompt from the markdown template
 pub async fn summarize_messages(
     provider: Arc<dyn Provider>,
     messages: &[Message],
 ) -> Result<Option<(Message, ProviderUsage)>, anyhow::Error> {
+    // Messages the agent no longer sees were already compacted, so leave them out
+    let messages: Vec<&Message> = messages
+        .iter()
+        .filter(|msg| msg.is_agent_visible())
+        .collect();
     if messages.is_empty() {
         return Ok(None);
     }
@@ -75,6 +252,7 @@ mod tests {
     #[derive(Clone)]
     struct MockProvider {
         model_config: ModelConfig,
+        reply: String,
     }
 
     #[async_trait::async_trait]
@@ -100,7 +278,7 @@ mod tests {
                     Utc::now().timestamp(),
                     vec![MessageContent::Text(
                         RawTextContent {
-                            text: "Summarized content".to_string(),
+                            text: self.reply.clone(),
                             meta: None,
                         }
                         .no_annotation(),
@@ -108,11 +286,7 @@ mod tests {
                 ),
                 ProviderUsage::new(
                     "mock".to_string(),
-                    Usage {
-                        input_tokens: Some(100),
-                        output_tokens: Some(50),
-                        total_tokens: Some(150),
-                    },
+                    Usage::new(Some(100), Some(50), Some(150)),
                 ),
             ))
         }
@@ -123,6 +297,7 @@ mod tests {
 
         Ok(Arc::new(MockProvider {
             model_config: mock_model_config,
+            reply: "Summarized content".to_string(),
         }))
     }
 
@@ -184,4 +359,74 @@ mod tests {
             "The summary should be None for empty input."
         );
     }
+
+    #[tokio::test]
+    async fn test_update_rolling_summary() {
+        let reply = json!({
+            "goals": ["Add a --verbose flag to the CLI"],
+            "decisions": ["Use clap's ArgAction::Count"],
+            "files_touched": ["src/cli.rs: added the flag"],
+            "open_todos": ["Document the flag"],
+            "notes": []
+        });
+        let provider: Arc<dyn Provider> = Arc::new(MockProvider {
+            model_config: ModelConfig::new("test-model").unwrap(),
+            reply: format!("```json\n{}\n```", reply),
+        });
+
+        let (summary, usage) = update_rolling_summary(
+            provider,
+            &RollingSummary::default(),
+            &create_test_messages(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(summary.goals, vec!["Add a --verbose flag to the CLI"]);
+        assert_eq!(summary.open_todos, vec!["Document the flag"]);
+        assert!(usage.usage.output_tokens.unwrap_or(0) > 0);
+
+        let text = summary.to_text();
+        assert!(text.contains("## Files Touched\n- src/cli.rs: added the flag"));
+        assert!(!text.contains("## Notes"));
+    }
+
+    #[tokio::test]
+    async fn test_update_rolling_summary_keeps_unstructured_reply() {
+        let provider = create_mock_provider().expect("failed to create mock provider");
+        let previous = RollingSummary {
+            goals: vec!["Fix the flaky test".to_string()],
+            ..Default::default()
+        };
+
+        let (summary, _) = update_rolling_summary(provider, &previous, &create_test_messages())
+            .await
+            .unwrap();
+
+        assert_eq!(summary.goals, previous.goals);
+        assert_eq!(summary.notes, vec!["Summarized content"]);
+    }
+
+    #[test]
+    fn test_format_messages_for_summary_shortens_tool_output() {
+        let messages = vec![
+            Message::assistant().with_tool_request(
+                "tool1",
+                Ok(mcp_core::tool::ToolCall::new(
+                    "shell",
+                    json!({"command": "cat big.log"}),
+                )),
+            ),
+            Message::user().with_tool_response(
+                "tool1",
+                Ok(vec![rmcp::model::Content::text("x".repeat(10_000))]),
+            ),
+        ];
+
+        let text = format_messages_for_summary(&messages);
+
+        assert!(text.starts_with("assistant: [tool call: shell]"));
+        assert!(text.contains("user: [tool result] xxx"));
+        assert!(text.len() < 3000);
+    }
 }
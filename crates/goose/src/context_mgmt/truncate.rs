@@ -1,17 +1,32 @@
-use crate::conversation::message::{Message, MessageContent};
-use crate::conversation::Conversation;
+use crate::conversation::message::{Message, MessageContent, ToolResponse};
+use crate::conversation::{pinned_indices, Conversation};
 use crate::utils::safe_truncate;
 use anyhow::{anyhow, Result};
-use rmcp::model::{RawContent, ResourceContents, Role};
+use chrono::Utc;
+use rmcp::model::{Content, RawContent, ResourceContents, Role};
 use std::collections::HashSet;
+use std::io::Write;
 use std::ops::DerefMut;
+use std::path::{Path, PathBuf};
 use tracing::{debug, warn};
 
 /// Maximum size for truncated content in characters
 const MAX_TRUNCATED_CONTENT_SIZE: usize = 5000;
 
+/// Number of characters of a pruned tool output kept in its stub
+const PRUNED_OUTPUT_PREVIEW_SIZE: usize = 200;
+
+/// Tool outputs shorter than this are not pruned, as their stub would be about as long
+const MIN_PRUNABLE_OUTPUT_SIZE: usize = 1000;
+
+/// Rough approximation: 1 token per 4 characters for English text
+fn estimate_text_tokens(text: &str) -> usize {
+    (text.len() / 4).max(1)
+}
+
 /// Handles messages that are individually larger than the context limit
-/// by truncating their content rather than removing them entirely
+/// by truncating their content rather than removing them entirely.
+/// Pinned messages are left as they are.
 fn handle_oversized_messages(
     messages: &[Message],
     token_counts: &[usize],
@@ -21,16 +36,10 @@ fn handle_oversized_messages(
     let mut truncated_messages = Vec::new();
     let mut truncated_token_counts = Vec::new();
     let mut any_truncated = false;
-
-    // Create a basic token counter for re-estimating truncated content
-    // Note: This is a rough approximation since we don't have access to the actual tokenizer here
-    let estimate_tokens = |text: &str| -> usize {
-        // Rough approximation: 1 token per 4 characters for English text
-        (text.len() / 4).max(1)
-    };
+    let pinned = pinned_indices(messages);
 
     for (i, (message, &original_tokens)) in messages.iter().zip(token_counts.iter()).enumerate() {
-        if original_tokens > context_limit {
+        if original_tokens > context_limit && !pinned.contains(&i) {
             warn!(
                 "Message {} has {} tokens, exceeding context limit of {}",
                 i, original_tokens, context_limit
@@ -38,8 +47,9 @@ fn handle_oversized_messages(
 
             // Try to truncate the message content
             let truncated_message = truncate_message_content(message, MAX_TRUNCATED_CONTENT_SIZE)?;
+            // Note: This is a rough approximation since we don't have access to the actual tokenizer here
             let estimated_new_tokens =
-                estimate_message_tokens(&truncated_message, &estimate_tokens);
+                estimate_message_tokens(&truncated_message, &estimate_text_tokens);
 
             if estimated_new_tokens > context_limit {
                 // Even truncated message is too large, skip it entirely
@@ -175,10 +185,11 @@ fn estimate_message_tokens(message: &Message, estimate_fn: &dyn Fn(&str) -> usiz
 /// Truncates the messages to fit within the model's context window.
 /// Mutates the input messages and token counts in place.
 /// Returns an error if it's impossible to truncate the messages within the context limit.
+/// Pinned messages are never removed, whatever the strategy.
 /// - messages: The vector of messages in the conversation.
 /// - token_counts: A parallel vector containing the token count for each message.
 /// - context_limit: The maximum allowed context length in tokens.
-/// - strategy: The truncation strategy to use. Only option is OldestFirstTruncation.
+/// - strategy: The truncation strategy to use, OldestFirstTruncation or ToolOutputPruning.
 pub fn truncate_messages(
     messages: &[Message],
     token_counts: &[usize],
@@ -194,6 +205,9 @@ pub fn truncate_messages(
         ));
     }
 
+    // Let the strategy shrink messages before any are removed
+    strategy.shrink_messages(&mut messages, &mut token_counts, context_limit)?;
+
     // Step 1: Calculate total tokens
     let mut total_tokens: usize = token_counts.iter().sum();
     debug!("Total tokens before truncation: {}", total_tokens);
@@ -232,8 +246,10 @@ pub fn truncate_messages(
     }
 
     // Step 2: Determine indices to remove based on strategy
-    let indices_to_remove =
+    let mut indices_to_remove =
         strategy.determine_indices_to_remove(&messages, &token_counts, context_limit)?;
+    let pinned = pinned_indices(&messages);
+    indices_to_remove.retain(|i| !pinned.contains(i));
 
     // Circuit breaker: if we can't remove enough messages, fail gracefully
     let tokens_to_remove: usize = indices_to_remove
@@ -331,9 +347,20 @@ pub trait TruncationStrategy {
         token_counts: &[usize],
         context_limit: usize,
     ) -> Result<HashSet<usize>>;
+
+    /// Rewrites messages so they take fewer tokens, before any are removed, updating
+    /// their token counts. The default leaves messages unchanged.
+    fn shrink_messages(
+        &self,
+        _messages: &mut [Message],
+        _token_counts: &mut [usize],
+        _context_limit: usize,
+    ) -> Result<()> {
+        Ok(())
+    }
 }
 
-/// Strategy to truncate messages by removing the oldest first
+/// Strategy to truncate messages by removing the oldest first, skipping pinned ones
 pub struct OldestFirstTruncation;
 
 impl TruncationStrategy for OldestFirstTruncation {
@@ -346,11 +373,15 @@ impl TruncationStrategy for OldestFirstTruncation {
         let mut indices_to_remove = HashSet::new();
         let mut total_tokens: usize = token_counts.iter().sum();
         let mut tool_ids_to_remove = HashSet::new();
+        let pinned = pinned_indices(messages);
 
         for (i, message) in messages.iter().enumerate() {
             if total_tokens <= context_limit {
                 break;
             }
+            if pinned.contains(&i) {
+                continue;
+            }
 
             // Remove the message
             indices_to_remove.insert(i);
@@ -385,6 +416,187 @@ impl TruncationStrategy for OldestFirstTruncation {
     }
 }
 
+/// Strategy to first replace old tool outputs with a short stub, keeping the tool requests,
+/// and only then remove the oldest messages. The full output of a pruned tool response is
+/// written to a file the stub points to, so it can still be looked at when needed.
+pub struct ToolOutputPruning {
+    /// Number of most recent tool responses that are never pruned
+    pub keep_recent: usize,
+    /// Directory the full outputs of pruned tool responses are written to, readable by the
+    /// user only. Without one, pruned outputs are dropped.
+    pub output_dir: Option<PathBuf>,
+}
+
+impl ToolOutputPruning {
+    /// Prune tool outputs of a session, keeping the full outputs with its data
+    pub fn for_session(session_file: Option<&Path>) -> Result<Self> {
+        Ok(Self {
+            keep_recent: 3,
+            output_dir: session_file
+                .map(crate::session::storage::pruned_outputs_dir)
+                .transpose()?,
+        })
+    }
+
+    /// Replaces the output of a tool response with a stub, returning whether it did
+    fn prune_tool_response(&self, tool_response: &mut ToolResponse) -> bool {
+        let Ok(contents) = &tool_response.tool_result else {
+            return false;
+        };
+
+        let mut text = String::new();
+        let mut other_items = 0;
+        for content in contents {
+            match &content.raw {
+                RawContent::Text(text_content) => text.push_str(&text_content.text),
+                RawContent::Resource(resource) => match &resource.resource {
+                    ResourceContents::TextResourceContents {
+                        text: resource_text,
+                        ..
+                    } => text.push_str(resource_text),
+                    _ => other_items += 1,
+                },
+                _ => other_items += 1,
+            }
+        }
+
+        let size = text.chars().count();
+        if size < MIN_PRUNABLE_OUTPUT_SIZE && other_items == 0 {
+            return false;
+        }
+
+        let saved = match &self.output_dir {
+            Some(output_dir) if !text.is_empty() => {
+                match write_output(output_dir, &tool_response.id, &text) {
+                    Ok(path) => Some(path),
+                    Err(e) => {
+                        warn!("Failed to save pruned tool output: {}", e);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        let pointer = match saved {
+            Some(path) => format!("the full output is in {}", path.display()),
+            None => "call the tool again if you need it".to_string(),
+        };
+        let other_items_note = if other_items > 0 {
+            format!(" and {} non-text items", other_items)
+        } else {
+            String::new()
+        };
+        let stub = format!(
+            "{}\n\n[... tool output of {} characters{} pruned to save context; {} ...]",
+            safe_truncate(&text, PRUNED_OUTPUT_PREVIEW_SIZE),
+            size,
+            other_items_note,
+            pointer
+        );
+        tool_response.tool_result = Ok(vec![Content::text(stub)]);
+        true
+    }
+}
+
+fn write_output(output_dir: &Path, tool_id: &str, text: &str) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(output_dir)?;
+    restrict_to_owner(output_dir)?;
+    let tool_id: String = tool_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let path = output_dir.join(format!(
+        "{}_{}.txt",
+        Utc::now().format("%Y%m%d_%H%M%S"),
+        tool_id
+    ));
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(&path)?.write_all(text.as_bytes())?;
+    Ok(path)
+}
+
+/// Tool outputs can hold secrets, so keep them readable by the user only
+#[cfg(unix)]
+fn restrict_to_owner(dir: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+}
+
+#[cfg(not(unix))]
+fn restrict_to_owner(_dir: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+impl TruncationStrategy for ToolOutputPruning {
+    fn determine_indices_to_remove(
+        &self,
+        messages: &[Message],
+        token_counts: &[usize],
+        context_limit: usize,
+    ) -> Result<HashSet<usize>> {
+        OldestFirstTruncation.determine_indices_to_remove(messages, token_counts, context_limit)
+    }
+
+    fn shrink_messages(
+        &self,
+        messages: &mut [Message],
+        token_counts: &mut [usize],
+        context_limit: usize,
+    ) -> Result<()> {
+        let mut total_tokens: usize = token_counts.iter().sum();
+        let tool_responses: Vec<usize> = messages
+            .iter()
+            .enumerate()
+            .filter(|(_, message)| message.is_tool_response())
+            .map(|(i, _)| i)
+            .collect();
+        let prunable = tool_responses.len().saturating_sub(self.keep_recent);
+        let pinned = pinned_indices(messages);
+
+        for &i in &tool_responses[..prunable] {
+            if total_tokens <= context_limit {
+                break;
+            }
+            if pinned.contains(&i) {
+                continue;
+            }
+
+            let mut pruned = false;
+            for content in &mut messages[i].content {
+                if let MessageContent::ToolResponse(tool_response) = content {
+                    pruned |= self.prune_tool_response(tool_response);
+                }
+            }
+
+            if pruned {
+                let new_tokens = estimate_message_tokens(&messages[i], &estimate_text_tokens)
+                    .min(token_counts[i]);
+                debug!(
+                    "ToolOutputPruning: Pruned tool output at index {}. Tokens: {} -> {}",
+                    i, token_counts[i], new_tokens
+                );
+                total_tokens -= token_counts[i] - new_tokens;
+                token_counts[i] = new_tokens;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -725,4 +937,142 @@ mod tests {
 
         Ok(())
     }
+
+    fn tool_exchanges(count: usize, output: &str) -> (Vec<Message>, Vec<usize>) {
+        let mut messages = vec![user_text(0, 10).0];
+        let mut token_counts = vec![10];
+        for i in 0..count {
+            let id = format!("tool{}", i);
+            let tool_call = ToolCall::new("shell", json!({"command": format!("cat log{}", i)}));
+            messages.push(assistant_tool_request(&id, tool_call, 10).0);
+            messages.push(large_tool_response(&id, output.to_string(), 1000).0);
+            token_counts.extend([10, 1000]);
+        }
+        messages.push(user_text(1, 10).0);
+        token_counts.push(10);
+        (messages, token_counts)
+    }
+
+    fn tool_output_text(message: &Message) -> String {
+        match &message.content[0] {
+            MessageContent::ToolResponse(response) => response.tool_result.as_ref().unwrap()[0]
+                .as_text()
+                .unwrap()
+                .text
+                .clone(),
+            _ => panic!("Expected a tool response"),
+        }
+    }
+
+    #[test]
+    fn test_tool_output_pruning_stubs_old_outputs() -> Result<()> {
+        let output_dir = tempfile::tempdir()?;
+        let strategy = ToolOutputPruning {
+            keep_recent: 1,
+            output_dir: Some(output_dir.path().to_path_buf()),
+        };
+        let output = "build output line\n".repeat(300);
+        let (messages, token_counts) = tool_exchanges(3, &output);
+
+        // Pruning the oldest output is enough to get under the limit
+        let (pruned, pruned_counts) = truncate_messages(&messages, &token_counts, 2200, &strategy)?;
+
+        assert_eq!(pruned.len(), messages.len());
+        assert!(pruned_counts.iter().sum::<usize>() <= 2200);
+
+        // The tool request is kept and the output replaced by a stub pointing to the full output
+        assert_eq!(pruned.messages()[1], messages[1]);
+        let stub = tool_output_text(&pruned.messages()[2]);
+        assert!(stub.starts_with("build output line"));
+        assert!(stub.contains("pruned to save context"));
+        let saved = std::fs::read_dir(output_dir.path())?
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        assert_eq!(saved.len(), 1);
+        assert!(stub.contains(&saved[0].display().to_string()));
+        assert_eq!(std::fs::read_to_string(&saved[0])?, output);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&saved[0])?.permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // Without a session to keep them with, full outputs are not written anywhere
+        let strategy = ToolOutputPruning {
+            keep_recent: 1,
+            output_dir: None,
+        };
+        let (pruned, _) = truncate_messages(&messages, &token_counts, 2200, &strategy)?;
+        let stub = tool_output_text(&pruned.messages()[2]);
+        assert!(stub.contains("call the tool again"));
+
+        // Later outputs are left alone
+        assert_eq!(pruned.messages()[4], messages[4]);
+        assert_eq!(pruned.messages()[6], messages[6]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tool_output_pruning_keeps_recent_and_falls_back_to_removal() -> Result<()> {
+        let output_dir = tempfile::tempdir()?;
+        let strategy = ToolOutputPruning {
+            keep_recent: 1,
+            output_dir: Some(output_dir.path().to_path_buf()),
+        };
+        let (messages, token_counts) = tool_exchanges(3, &"x".repeat(4000));
+
+        // Pruning all but the most recent output is not enough, so the oldest messages go
+        let (truncated, truncated_counts) =
+            truncate_messages(&messages, &token_counts, 500, &strategy)?;
+
+        assert!(truncated_counts.iter().sum::<usize>() <= 500);
+        assert!(truncated.len() < messages.len());
+        assert_eq!(std::fs::read_dir(output_dir.path())?.count(), 2);
+
+        // Short outputs are never worth pruning
+        let (messages, token_counts) = tool_exchanges(3, "ok");
+        let mut shrunk = messages.clone();
+        let mut shrunk_counts = token_counts.clone();
+        strategy.shrink_messages(&mut shrunk, &mut shrunk_counts, 10)?;
+        assert_eq!(shrunk, messages);
+        assert_eq!(shrunk_counts, token_counts);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncation_keeps_pinned_messages() -> Result<()> {
+        let (mut messages, token_counts) = create_messages_with_counts(5, 10, true);
+        assert!(messages.set_pinned(2, true));
+        let pinned = messages.messages()[2].clone();
+
+        let (truncated, truncated_counts) = truncate_messages(
+            messages.messages(),
+            &token_counts,
+            40,
+            &OldestFirstTruncation,
+        )?;
+
+        assert!(truncated_counts.iter().sum::<usize>() <= 40);
+        assert_eq!(truncated.messages()[0], pinned);
+        assert_eq!(truncated.last(), messages.last());
+
+        // A pinned tool output is not pruned, so the next one is
+        let output_dir = tempfile::tempdir()?;
+        let strategy = ToolOutputPruning {
+            keep_recent: 1,
+            output_dir: Some(output_dir.path().to_path_buf()),
+        };
+        let (mut messages, token_counts) = tool_exchanges(3, &"log line\n".repeat(300));
+        messages[2].metadata.pinned = true;
+
+        let (pruned, _) = truncate_messages(&messages, &token_counts, 2200, &strategy)?;
+
+        assert_eq!(pruned.messages()[2], messages[2]);
+        assert!(tool_output_text(&pruned.messages()[4]).contains("pruned to save context"));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use rmcp::model::{Role, Tool};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::agents::extension::ExtensionInfo;
+use crate::conversation::message::{Message, MessageContent};
+use crate::token_counter::AsyncTokenCounter;
+
+/// Headings the developer extension puts before the hints files in its instructions
+const HINTS_HEADINGS: [&str; 2] = ["### Global Hints", "### Project Hints"];
+
+/// What a part of the next request is
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextUsageKind {
+    /// The system prompt, without extension instructions and hints
+    SystemPrompt,
+    ExtensionInstructions,
+    /// .goosehints and other hints files, loaded by the developer extension
+    Hints,
+    ToolSchema,
+    UserText,
+    AssistantText,
+    Thinking,
+    ToolCall,
+    ToolOutput,
+    Image,
+}
+
+impl fmt::Display for ContextUsageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::SystemPrompt => "system prompt",
+            Self::ExtensionInstructions => "extension instructions",
+            Self::Hints => "hints",
+            Self::ToolSchema => "tool schemas",
+            Self::UserText => "user text",
+            Self::AssistantText => "assistant text",
+            Self::Thinking => "thinking",
+            Self::ToolCall => "tool calls",
+            Self::ToolOutput => "tool outputs",
+            Self::Image => "images",
+        };
+        f.write_str(label)
+    }
+}
+
+/// The tokens taken by one part of the next request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ContextUsageEntry {
+    pub kind: ContextUsageKind,
+    /// The extension or tool this part belongs to, if any
+    pub name: Option<String>,
+    pub tokens: usize,
+    /// How many items were grouped into this entry, such as the number of tool outputs
+    pub count: usize,
+}
+
+/// Token breakdown of the next request to the model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ContextUsage {
+    pub model: String,
+    pub context_limit: usize,
+    /// Sum of the entries
+    pub total_tokens: usize,
+    pub entries: Vec<ContextUsageEntry>,
+}
+
+impl ContextUsage {
+    pub fn new(model: String, context_limit: usize, entries: Vec<ContextUsageEntry>) -> Self {
+        let total_tokens = entries.iter().map(|entry| entry.tokens).sum();
+        Self {
+            model,
+            context_limit,
+            total_tokens,
+            entries,
+        }
+    }
+
+    /// Tokens of all entries of `kind`
+    pub fn tokens_for(&self, kind: ContextUsageKind) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.kind == kind)
+            .map(|entry| entry.tokens)
+            .sum()
+    }
+}
+
+/// Splits extension instructions into the instructions and the hints appended to them
+fn split_hints(instructions: &str) -> (&str, &str) {
+    let start = HINTS_HEADINGS
+        .iter()
+        .filter_map(|heading| instructions.find(heading))
+        .min();
+    match start {
+        Some(start) => instructions.split_at(start),
+        None => (instructions, ""),
+    }
+}
+
+#[derive(Default)]
+struct Entries {
+    entries: Vec<ContextUsageEntry>,
+    index: HashMap<(ContextUsageKind, Option<String>), usize>,
+}
+
+impl Entries {
+    fn add(&mut self, kind: ContextUsageKind, name: Option<String>, tokens: usize) {
+        let key = (kind, name);
+        match self.index.get(&key) {
+            Some(&i) => {
+                self.entries[i].tokens += tokens;
+                self.entries[i].count += 1;
+            }
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push(ContextUsageEntry {
+                    kind: key.0,
+                    name: key.1,
+                    tokens,
+                    count: 1,
+                });
+            }
+        }
+    }
+}
+
+/// Breaks down the tokens of a request with `system_prompt`, built from `extensions`, and
+/// `tools` and `messages`. Messages are grouped by kind, and tool calls and outputs by tool.
+pub fn context_usage_entries(
+    token_counter: &AsyncTokenCounter,
+    system_prompt: &str,
+    extensions: &[ExtensionInfo],
+    tools: &[Tool],
+    messages: &[Message],
+) -> Vec<ContextUsageEntry> {
+    let mut entries = Entries::default();
+
+    let mut extension_parts = Vec::new();
+    for extension in extensions {
+        let (instructions, hints) = split_hints(&extension.instructions);
+        for (kind, text) in [
+            (ContextUsageKind::ExtensionInstructions, instructions),
+            (ContextUsageKind::Hints, hints),
+        ] {
+            if !text.trim().is_empty() {
+                let tokens = token_counter.count_tokens(text);
+                extension_parts.push((kind, extension.name.clone(), tokens));
+            }
+        }
+    }
+
+    // Extension instructions are part of the system prompt, and so is the preamble
+    // some providers add for tools
+    let extension_tokens: usize = extension_parts.iter().map(|(_, _, tokens)| tokens).sum();
+    let system_tokens = token_counter
+        .count_tokens(system_prompt)
+        .saturating_sub(extension_tokens)
+        + token_counter.count_tools_overhead(tools);
+    entries.add(ContextUsageKind::SystemPrompt, None, system_tokens);
+    for (kind, name, tokens) in extension_parts {
+        entries.add(kind, Some(name), tokens);
+    }
+
+    for tool in tools {
+        entries.add(
+            ContextUsageKind::ToolSchema,
+            Some(tool.name.to_string()),
+            token_counter.count_tool_schema_tokens(tool),
+        );
+    }
+
+    let mut tool_names: HashMap<&str, String> = HashMap::new();
+    for message in messages.iter().filter(|m| m.is_agent_visible()) {
+        for content in &message.content {
+            let (kind, name) = match content {
+                MessageContent::Text(_) if message.role == Role::User => {
+                    (ContextUsageKind::UserText, None)
+                }
+                MessageContent::Text(_) => (ContextUsageKind::AssistantText, None),
+                MessageContent::Image(_) => (ContextUsageKind::Image, None),
+                MessageContent::Thinking(_) | MessageContent::RedactedThinking(_) => {
+                    (ContextUsageKind::Thinking, None)
+                }
+                MessageContent::ToolRequest(request) => {
+                    let name = request
+                        .tool_call
+                        .as_ref()
+                        .map(|call| call.name.to_string())
+                        .ok();
+                    if let Some(name) = &name {
+                        tool_names.insert(&request.id, name.clone());
+                    }
+                    (ContextUsageKind::ToolCall, name)
+                }
+                MessageContent::ToolResponse(response) => (
+                    ContextUsageKind::ToolOutput,
+                    tool_names.get(response.id.as_str()).cloned(),
+                ),
+                _ => continue,
+            };
+            entries.add(kind, name, token_counter.count_content_tokens(content));
+        }
+    }
+
+    entries.entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token_counter::create_async_token_counter;
+    use mcp_core::tool::ToolCall;
+    use rmcp::model::Content;
+    use rmcp::object;
+    use serde_json::json;
+
+    #[test]
+    fn test_split_hints() {
+        let instructions =
+            "Use the shell tool.\n### Global Hints\nBe brief.\n### Project Hints\nUse cargo.";
+        let (instructions, hints) = split_hints(instructions);
+        assert_eq!(instructions, "Use the shell tool.\n");
+        assert!(hints.starts_with("### Global Hints"));
+        assert!(hints.ends_with("Use cargo."));
+
+        assert_eq!(split_hints("No hints here"), ("No hints here", ""));
+    }
+
+    #[tokio::test]
+    async fn test_context_usage_entries() {
+        let token_counter = create_async_token_counter().await.unwrap();
+        let extensions = vec![
+            ExtensionInfo::new(
+                "developer",
+                "Use the shell tool.\n### Project Hints\nAlways run the tests.",
+                false,
+            ),
+            ExtensionInfo::new("memory", "Remember things.", false),
+        ];
+        let system_prompt = format!(
+            "You are goose.\n{}\n{}",
+            extensions[0].instructions, extensions[1].instructions
+        );
+        let tools = vec![Tool::new(
+            "developer__shell",
+            "Run a command",
+            object!({"properties": {"command": {"type": "string"}}}),
+        )];
+        let messages = vec![
+            Message::user().with_text("List the files"),
+            Message::assistant()
+                .with_thinking("I should run ls", "sig")
+                .with_tool_request(
+                    "1",
+                    Ok(ToolCall::new("developer__shell", json!({"command": "ls"}))),
+                ),
+            Message::user().with_tool_response("1", Ok(vec![Content::text("a.txt\nb.txt")])),
+            Message::assistant().with_tool_request(
+                "2",
+                Ok(ToolCall::new(
+                    "developer__shell",
+                    json!({"command": "cat a.txt"}),
+                )),
+            ),
+            Message::user().with_tool_response("2", Ok(vec![Content::text("hello")])),
+            Message::assistant().with_text("There are two files"),
+            // Hidden from the agent, so not part of the request
+            Message::user().with_text("An old message").user_only(),
+        ];
+
+        let entries = context_usage_entries(
+            &token_counter,
+            &system_prompt,
+            &extensions,
+            &tools,
+            &messages,
+        );
+        let usage = ContextUsage::new("gpt-4o".to_string(), 128_000, entries);
+        let find = |kind, name: Option<&str>| {
+            usage
+                .entries
+                .iter()
+                .find(|e| e.kind == kind && e.name.as_deref() == name)
+                .cloned()
+        };
+
+        assert_eq!(usage.entries[0].kind, ContextUsageKind::SystemPrompt);
+        let hints = find(ContextUsageKind::Hints, Some("developer")).unwrap();
+        assert_eq!(
+            hints.tokens,
+            token_counter.count_tokens("### Project Hints\nAlways run the tests.")
+        );
+        assert!(find(ContextUsageKind::ExtensionInstructions, Some("memory")).is_some());
+        assert!(find(ContextUsageKind::Hints, Some("memory")).is_none());
+        assert!(find(ContextUsageKind::ToolSchema, Some("developer__shell")).is_some());
+
+        // Tool calls and outputs are grouped per tool
+        let outputs = find(ContextUsageKind::ToolOutput, Some("developer__shell")).unwrap();
+        assert_eq!(outputs.count, 2);
+        assert_eq!(
+            find(ContextUsageKind::ToolCall, Some("developer__shell"))
+                .unwrap()
+                .count,
+            2
+        );
+
+        assert_eq!(find(ContextUsageKind::UserText, None).unwrap().count, 1);
+        assert_eq!(
+            find(ContextUsageKind::AssistantText, None).unwrap().count,
+            1
+        );
+        assert_eq!(
+            find(ContextUsageKind::Thinking, None).unwrap().tokens,
+            token_counter.count_tokens("I should run ls")
+        );
+        assert_eq!(
+            usage.total_tokens,
+            usage.entries.iter().map(|e| e.tokens).sum::<usize>()
+        );
+    }
+}
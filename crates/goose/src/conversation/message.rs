@@ -383,6 +383,9 @@ pub struct MessageMetadata {
     /// Whether the message should be included in the agent's context window
     #[serde(default = "default_true")]
     pub agent_visible: bool,
+    /// Whether the message must be kept in the agent's context window when it is compacted
+    #[serde(default)]
+    pub pinned: bool,
 }
 
 impl Default for MessageMetadata {
@@ -390,6 +393,7 @@ impl Default for MessageMetadata {
         MessageMetadata {
             user_visible: true,
             agent_visible: true,
+            pinned: false,
         }
     }
 }
@@ -400,6 +404,7 @@ impl MessageMetadata {
         MessageMetadata {
             user_visible: false,
             agent_visible: true,
+            pinned: false,
         }
     }
 
@@ -408,6 +413,7 @@ impl MessageMetadata {
         MessageMetadata {
             user_visible: true,
             agent_visible: false,
+            pinned: false,
         }
     }
 
@@ -416,6 +422,7 @@ impl MessageMetadata {
         MessageMetadata {
             user_visible: false,
             agent_visible: false,
+            pinned: false,
         }
     }
 
@@ -722,6 +729,11 @@ impl Message {
     pub fn is_agent_visible(&self) -> bool {
         self.metadata.agent_visible
     }
+
+    /// Check if the message is pinned to the agent's context window
+    pub fn is_pinned(&self) -> bool {
+        self.metadata.pinned
+    }
 }
 
 #[cfg(test)]
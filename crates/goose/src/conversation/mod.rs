@@ -102,6 +102,57 @@ impl Conversation {
         self.0.clear();
     }
 
+    /// Pins or unpins the message at `index`, so that compaction keeps it in the agent's context.
+    /// Returns false if there is no such message, or it is not visible to the agent.
+    pub fn set_pinned(&mut self, index: usize, pinned: bool) -> bool {
+        match self.0.get_mut(index) {
+            Some(message) if message.is_agent_visible() || !pinned => {
+                message.metadata.pinned = pinned;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Takes whether each message is pinned or in the agent's context from `stored`, for the
+    /// messages the two conversations share. Clients send back the conversation as they show
+    /// it, which does not reflect messages pinned or dropped since, so the stored session
+    /// decides. Messages are matched by position, role, creation time and id; content is not
+    /// compared since stored content may be truncated on load.
+    pub fn keep_context_flags_from(&mut self, stored: &Conversation) {
+        for (message, stored) in self.0.iter_mut().zip(stored.messages()) {
+            let same = message.role == stored.role
+                && message.created == stored.created
+                && message.id == stored.id;
+            if !same {
+                break;
+            }
+            message.metadata.agent_visible = stored.metadata.agent_visible;
+            message.metadata.pinned = stored.metadata.pinned;
+        }
+    }
+
+    /// Removes the message at `index` from the agent's context, keeping it for the user. Messages
+    /// with the other half of its tool calls are dropped along with it. Returns the indices of the
+    /// dropped messages, which is empty if there is no such message visible to the agent.
+    pub fn drop_message(&mut self, index: usize) -> Vec<usize> {
+        if !self.0.get(index).is_some_and(|m| m.is_agent_visible()) {
+            return Vec::new();
+        }
+
+        let mut dropped: Vec<usize> = with_tool_pairs(&self.0, [index])
+            .into_iter()
+            .filter(|&i| self.0[i].is_agent_visible())
+            .collect();
+        dropped.sort_unstable();
+        for &i in &dropped {
+            let message = &mut self.0[i];
+            message.metadata = message.metadata.with_agent_invisible();
+            message.metadata.pinned = false;
+        }
+        dropped
+    }
+
     fn validate(self) -> Result<Self, InvalidConversation> {
         let (_messages, issues) = fix_messages(self.0.clone());
         if !issues.is_empty() {
@@ -122,6 +173,41 @@ impl Default for Conversation {
     }
 }
 
+/// Indices of the pinned messages visible to the agent, along with the messages holding the
+/// other half of their tool calls, which have to be kept with them
+pub fn pinned_indices(messages: &[Message]) -> HashSet<usize> {
+    let pinned = (0..messages.len()).filter(|&i| {
+        let message = &messages[i];
+        message.is_pinned() && message.is_agent_visible()
+    });
+    with_tool_pairs(messages, pinned)
+}
+
+/// Adds the messages sharing a tool call id with any of `indices` to them
+fn with_tool_pairs(
+    messages: &[Message],
+    indices: impl IntoIterator<Item = usize>,
+) -> HashSet<usize> {
+    let indices: HashSet<usize> = indices.into_iter().collect();
+    let tool_ids: HashSet<&str> = indices
+        .iter()
+        .flat_map(|&i| messages[i].get_tool_ids())
+        .collect();
+    if tool_ids.is_empty() {
+        return indices;
+    }
+
+    (0..messages.len())
+        .filter(|i| {
+            indices.contains(i)
+                || messages[*i]
+                    .get_tool_ids()
+                    .iter()
+                    .any(|id| tool_ids.contains(id))
+        })
+        .collect()
+}
+
 /// Fix a conversation that we're about to send to an LLM. So the last and first
 /// messages should always be from the user.
 pub fn fix_conversation(conversation: Conversation) -> (Conversation, Vec<String>) {
@@ -361,7 +447,9 @@ pub fn debug_conversation_fix(
 #[cfg(test)]
 mod tests {
     use crate::conversation::message::Message;
-    use crate::conversation::{debug_conversation_fix, fix_conversation, Conversation};
+    use crate::conversation::{
+        debug_conversation_fix, fix_conversation, pinned_indices, Conversation,
+    };
     use mcp_core::tool::ToolCall;
     use rmcp::model::Role;
     use serde_json::json;
@@ -531,4 +619,61 @@ mod tests {
         let (_fixed, issues) = run_verify(messages);
         assert_eq!(issues.len(), 0);
     }
+
+    #[test]
+    fn test_pin_and_drop_messages() {
+        let mut conversation = Conversation::new_unvalidated(vec![
+            Message::user().with_text("Here is the spec"),
+            Message::assistant()
+                .with_text("I'll read the log")
+                .with_tool_request("read_1", Ok(ToolCall::new("read", json!({})))),
+            Message::user().with_tool_response("read_1", Ok(vec![])),
+            Message::assistant().with_text("The log is long"),
+            Message::user().with_text("Thanks!"),
+        ]);
+
+        assert!(conversation.set_pinned(0, true));
+        assert!(conversation.set_pinned(2, true));
+        assert!(!conversation.set_pinned(9, true));
+        let mut pinned: Vec<usize> = pinned_indices(conversation.messages())
+            .into_iter()
+            .collect();
+        pinned.sort_unstable();
+        assert_eq!(pinned, vec![0, 1, 2]);
+
+        // Dropping a tool response drops its request too, and unpins them
+        assert_eq!(conversation.drop_message(2), vec![1, 2]);
+        assert!(conversation.drop_message(2).is_empty());
+        assert!(!conversation.messages()[2].is_agent_visible());
+        assert!(conversation.messages()[2].is_user_visible());
+        assert!(!conversation.messages()[2].is_pinned());
+        assert!(!conversation.set_pinned(1, true));
+        assert_eq!(
+            pinned_indices(conversation.messages())
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![0]
+        );
+        assert_eq!(conversation.len(), 5);
+
+        // A client that still has the old flags gets them from the stored conversation
+        let mut from_client = Conversation::new_unvalidated(
+            conversation
+                .messages()
+                .iter()
+                .cloned()
+                .map(|mut message| {
+                    message.metadata.agent_visible = true;
+                    message.metadata.pinned = false;
+                    message
+                })
+                .chain([Message::user().with_text("One more thing")]),
+        );
+        from_client.keep_context_flags_from(&conversation);
+        assert_eq!(
+            &from_client.messages()[..5],
+            conversation.messages().as_slice()
+        );
+        assert!(from_client.messages()[5].is_agent_visible());
+    }
 }
@@ -1,6 +1,9 @@
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 use thiserror::Error;
+use utoipa::ToSchema;
 
 const DEFAULT_CONTEXT_LIMIT: usize = 128_000;
 
@@ -65,6 +68,113 @@ static MODEL_SPECIFIC_LIMITS: Lazy<Vec<(&'static str, usize)>> = Lazy::new(|| {
     ]
 });
 
+/// Smallest thinking budget accepted by providers that take one
+pub const MIN_THINKING_BUDGET: u32 = 1024;
+
+/// How much a model should reason before answering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Low,
+    Medium,
+    High,
+}
+
+impl ReasoningEffort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReasoningEffort::Low => "low",
+            ReasoningEffort::Medium => "medium",
+            ReasoningEffort::High => "high",
+        }
+    }
+
+    /// Thinking budget for models that take a number of tokens rather than an effort level
+    pub fn budget_tokens(&self) -> u32 {
+        match self {
+            ReasoningEffort::Low => 4_096,
+            ReasoningEffort::Medium => 16_000,
+            ReasoningEffort::High => 32_000,
+        }
+    }
+
+    /// The effort closest to a thinking budget, for models that take an effort level
+    pub fn from_budget_tokens(budget_tokens: u32) -> Self {
+        match budget_tokens {
+            0..8_192 => ReasoningEffort::Low,
+            8_192..24_000 => ReasoningEffort::Medium,
+            _ => ReasoningEffort::High,
+        }
+    }
+}
+
+impl fmt::Display for ReasoningEffort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ReasoningEffort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "low" => Ok(ReasoningEffort::Low),
+            "medium" => Ok(ReasoningEffort::Medium),
+            "high" => Ok(ReasoningEffort::High),
+            _ => Err("must be one of: low, medium, high".to_string()),
+        }
+    }
+}
+
+/// Provider-neutral reasoning setting. Each format module translates it to the model's
+/// native parameter: a reasoning effort for OpenAI models, a thinking budget for Claude
+/// and Gemini models. Either field can be given and the other is derived from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+pub struct ReasoningConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effort: Option<ReasoningEffort>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_tokens: Option<u32>,
+}
+
+impl ReasoningConfig {
+    pub fn from_effort(effort: ReasoningEffort) -> Self {
+        Self {
+            effort: Some(effort),
+            budget_tokens: None,
+        }
+    }
+
+    pub fn from_budget_tokens(budget_tokens: u32) -> Self {
+        Self {
+            effort: None,
+            budget_tokens: Some(budget_tokens),
+        }
+    }
+
+    pub fn effort(&self) -> ReasoningEffort {
+        self.effort
+            .or_else(|| self.budget_tokens.map(ReasoningEffort::from_budget_tokens))
+            .unwrap_or(ReasoningEffort::Medium)
+    }
+
+    pub fn budget_tokens(&self) -> u32 {
+        self.budget_tokens
+            .unwrap_or_else(|| self.effort().budget_tokens())
+            .max(MIN_THINKING_BUDGET)
+    }
+}
+
+impl fmt::Display for ReasoningConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.budget_tokens {
+            Some(budget_tokens) => write!(f, "{} thinking tokens", budget_tokens),
+            None => write!(f, "{} effort", self.effort()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelConfig {
     pub model_name: String,
@@ -74,6 +184,9 @@ pub struct ModelConfig {
     pub toolshim: bool,
     pub toolshim_model: Option<String>,
     pub fast_model: Option<String>,
+    /// Extended thinking / reasoning to request, None to use the model's default
+    #[serde(default)]
+    pub reasoning: Option<ReasoningConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,6 +208,7 @@ impl ModelConfig {
         let temperature = Self::parse_temperature()?;
         let toolshim = Self::parse_toolshim()?;
         let toolshim_model = Self::parse_toolshim_model()?;
+        let reasoning = Self::parse_reasoning()?;
 
         Ok(Self {
             model_name,
@@ -104,6 +218,7 @@ impl ModelConfig {
             toolshim,
             toolshim_model,
             fast_model: None,
+            reasoning,
         })
     }
 
@@ -209,6 +324,71 @@ impl ModelConfig {
         }
     }
 
+    /// Reasoning from `GOOSE_REASONING_EFFORT` and `GOOSE_THINKING_BUDGET` (environment or
+    /// config file), falling back to the older `CLAUDE_THINKING_ENABLED`/`CLAUDE_THINKING_BUDGET`
+    fn parse_reasoning() -> Result<Option<ReasoningConfig>, ConfigError> {
+        // Values may have been parsed as numbers, so read them back as text
+        let param = |key: &str| {
+            crate::config::Config::global()
+                .get_param::<serde_json::Value>(key)
+                .ok()
+                .map(|value| match value {
+                    serde_json::Value::String(s) => s,
+                    other => other.to_string(),
+                })
+                .filter(|s| !s.trim().is_empty())
+        };
+
+        let effort = match param("GOOSE_REASONING_EFFORT") {
+            Some(val) => Some(val.parse::<ReasoningEffort>().map_err(|msg| {
+                ConfigError::InvalidValue("GOOSE_REASONING_EFFORT".to_string(), val, msg)
+            })?),
+            None => None,
+        };
+
+        let budget_tokens = match param("GOOSE_THINKING_BUDGET") {
+            Some(val) => Some(Self::validate_thinking_budget(
+                &val,
+                "GOOSE_THINKING_BUDGET",
+            )?),
+            None if std::env::var("CLAUDE_THINKING_ENABLED").is_ok() => {
+                match param("CLAUDE_THINKING_BUDGET") {
+                    Some(val) => Some(Self::validate_thinking_budget(
+                        &val,
+                        "CLAUDE_THINKING_BUDGET",
+                    )?),
+                    None => Some(ReasoningEffort::Medium.budget_tokens()),
+                }
+            }
+            None => None,
+        };
+
+        if effort.is_none() && budget_tokens.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(ReasoningConfig {
+            effort,
+            budget_tokens,
+        }))
+    }
+
+    fn validate_thinking_budget(val: &str, var: &str) -> Result<u32, ConfigError> {
+        let budget = val.trim().parse::<u32>().map_err(|_| {
+            ConfigError::InvalidValue(
+                var.to_string(),
+                val.to_string(),
+                "must be a positive integer".to_string(),
+            )
+        })?;
+        if budget < MIN_THINKING_BUDGET {
+            return Err(ConfigError::InvalidRange(
+                var.to_string(),
+                format!("must be at least {}", MIN_THINKING_BUDGET),
+            ));
+        }
+        Ok(budget)
+    }
+
     fn get_model_specific_limit(model_name: &str) -> Option<usize> {
         MODEL_SPECIFIC_LIMITS
             .iter()
@@ -253,6 +433,21 @@ impl ModelConfig {
         self
     }
 
+    pub fn with_reasoning(mut self, reasoning: Option<ReasoningConfig>) -> Self {
+        self.reasoning = reasoning;
+        self
+    }
+
+    /// The reasoning effort to request, if reasoning was configured
+    pub fn reasoning_effort(&self) -> Option<ReasoningEffort> {
+        self.reasoning.map(|reasoning| reasoning.effort())
+    }
+
+    /// The thinking budget to request, if reasoning was configured
+    pub fn thinking_budget(&self) -> Option<u32> {
+        self.reasoning.map(|reasoning| reasoning.budget_tokens())
+    }
+
     pub fn with_fast(mut self, fast_model: String) -> Self {
         self.fast_model = Some(fast_model);
         self
@@ -268,6 +463,13 @@ impl ModelConfig {
         }
     }
 
+    /// Whether the context limit was configured (e.g. with `GOOSE_CONTEXT_LIMIT`), rather
+    /// than looked up from the model name
+    pub fn has_explicit_context_limit(&self) -> bool {
+        self.context_limit.is_some()
+            && self.context_limit != Self::get_model_specific_limit(&self.model_name)
+    }
+
     pub fn context_limit(&self) -> usize {
         // If we have an explicit context limit set, use it
         if let Some(limit) = self.context_limit {
@@ -414,4 +616,43 @@ mod tests {
             });
         });
     }
+
+    #[test]
+    #[serial]
+    fn test_reasoning_config() {
+        with_var("GOOSE_REASONING_EFFORT", Some("high"), || {
+            let config = ModelConfig::new("test-model").unwrap();
+            assert_eq!(config.reasoning_effort(), Some(ReasoningEffort::High));
+            assert_eq!(config.thinking_budget(), Some(32_000));
+        });
+
+        with_var("GOOSE_THINKING_BUDGET", Some("6000"), || {
+            let config = ModelConfig::new("test-model").unwrap();
+            assert_eq!(config.reasoning_effort(), Some(ReasoningEffort::Low));
+            assert_eq!(config.thinking_budget(), Some(6000));
+        });
+
+        with_var("GOOSE_REASONING_EFFORT", Some("extreme"), || {
+            assert!(matches!(
+                ModelConfig::new("test-model"),
+                Err(ConfigError::InvalidValue(_, _, _))
+            ));
+        });
+
+        with_var("GOOSE_THINKING_BUDGET", Some("100"), || {
+            assert!(matches!(
+                ModelConfig::new("test-model"),
+                Err(ConfigError::InvalidRange(_, _))
+            ));
+        });
+
+        with_var("CLAUDE_THINKING_ENABLED", Some("1"), || {
+            let config = ModelConfig::new("test-model").unwrap();
+            assert_eq!(config.thinking_budget(), Some(16_000));
+        });
+
+        let config = ModelConfig::new("test-model").unwrap();
+        assert_eq!(config.reasoning, None);
+        assert_eq!(config.reasoning_effort(), None);
+    }
 }
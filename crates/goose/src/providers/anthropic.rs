@@ -12,7 +12,8 @@ use super::api_client::{ApiClient, ApiResponse, AuthMethod};
 use super::base::{ConfigKey, MessageStream, ModelInfo, Provider, ProviderMetadata, ProviderUsage};
 use super::errors::ProviderError;
 use super::formats::anthropic::{
-    create_request, get_usage, response_to_message, response_to_streaming_message,
+    count_tokens_request, create_request, get_usage, response_to_message,
+    response_to_streaming_message, thinking_budget, with_forced_tool_output,
 };
 use super::utils::{emit_debug_trace, get_model, map_http_error_to_provider_error};
 use crate::config::custom_providers::CustomProviderConfig;
@@ -62,8 +63,9 @@ impl AnthropicProvider {
             key: api_key,
         };
 
-        let api_client =
-            ApiClient::new(host, auth)?.with_header("anthropic-version", ANTHROPIC_API_VERSION)?;
+        let api_client = ApiClient::new(host, auth)?
+            .with_header("anthropic-version", ANTHROPIC_API_VERSION)?
+            .with_rate_limit("anthropic");
 
         Ok(Self {
             api_client,
@@ -84,7 +86,8 @@ impl AnthropicProvider {
         };
 
         let api_client = ApiClient::new(config.base_url, auth)?
-            .with_header("anthropic-version", ANTHROPIC_API_VERSION)?;
+            .with_header("anthropic-version", ANTHROPIC_API_VERSION)?
+            .with_rate_limit(&config.name);
 
         Ok(Self {
             api_client,
@@ -96,9 +99,8 @@ impl AnthropicProvider {
     fn get_conditional_headers(&self) -> Vec<(&str, &str)> {
         let mut headers = Vec::new();
 
-        let is_thinking_enabled = std::env::var("CLAUDE_THINKING_ENABLED").is_ok();
         if self.model.model_name.starts_with("claude-3-7-sonnet-") {
-            if is_thinking_enabled {
+            if thinking_budget(&self.model).is_some() {
                 headers.push(("anthropic-beta", "output-128k-2025-02-19"));
             }
             headers.push(("anthropic-beta", "token-efficient-tools-2025-02-19"));
@@ -213,6 +215,55 @@ impl Provider for AnthropicProvider {
         Ok((message, provider_usage))
     }
 
+    fn supports_structured_output(&self) -> bool {
+        true
+    }
+
+    async fn complete_structured(
+        &self,
+        system: &str,
+        messages: &[Message],
+        schema: &Value,
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let mut payload = create_request(&self.model, system, messages, &[])?;
+        with_forced_tool_output(&mut payload, schema);
+
+        let response = self
+            .with_retry(|| async { self.post(&payload).await })
+            .await?;
+        let json_response = Self::anthropic_api_call_result(response)?;
+
+        let message = response_to_message(&json_response)?;
+        let usage = get_usage(&json_response)?;
+        emit_debug_trace(&self.model, &payload, &json_response, &usage);
+        Ok((
+            message,
+            ProviderUsage::new(get_model(&json_response), usage),
+        ))
+    }
+
+    async fn count_tokens(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<Option<usize>, ProviderError> {
+        let payload = create_request(&self.model, system, messages, tools)?;
+        let mut request = self
+            .api_client
+            .request("v1/messages/count_tokens")
+            .without_rate_limit();
+        for (key, value) in self.get_conditional_headers() {
+            request = request.header(key, value)?;
+        }
+
+        let response = request.api_post(&count_tokens_request(&payload)).await?;
+        let json_response = Self::anthropic_api_call_result(response)?;
+        Ok(json_response["input_tokens"]
+            .as_u64()
+            .map(|tokens| tokens as usize))
+    }
+
     async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
         let response = self.api_client.api_get("v1/models").await?;
 
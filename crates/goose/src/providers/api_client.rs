@@ -8,8 +8,11 @@ use serde_json::Value;
 use std::fmt;
 use std::fs::read_to_string;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 
+use super::rate_limit::{self, RateLimiter};
+
 pub struct ApiClient {
     client: Client,
     host: String,
@@ -17,6 +20,7 @@ pub struct ApiClient {
     default_headers: HeaderMap,
     timeout: Duration,
     tls_config: Option<TlsConfig>,
+    rate_limit_provider: Option<String>,
 }
 
 pub enum AuthMethod {
@@ -195,6 +199,8 @@ pub struct ApiRequestBuilder<'a> {
     client: &'a ApiClient,
     path: &'a str,
     headers: HeaderMap,
+    timeout: Option<Duration>,
+    rate_limited: bool,
 }
 
 impl ApiClient {
@@ -220,6 +226,7 @@ impl ApiClient {
             default_headers: HeaderMap::new(),
             timeout,
             tls_config,
+            rate_limit_provider: None,
         })
     }
 
@@ -271,11 +278,19 @@ impl ApiClient {
         Ok(self)
     }
 
+    /// Shares a process-wide rate limit for `provider` across every client that sets it
+    pub fn with_rate_limit(mut self, provider: &str) -> Self {
+        self.rate_limit_provider = Some(provider.to_string());
+        self
+    }
+
     pub fn request<'a>(&'a self, path: &'a str) -> ApiRequestBuilder<'a> {
         ApiRequestBuilder {
             client: self,
             path,
             headers: HeaderMap::new(),
+            timeout: None,
+            rate_limited: true,
         }
     }
 
@@ -295,6 +310,10 @@ impl ApiClient {
         self.request(path).response_get().await
     }
 
+    pub async fn response_delete(&self, path: &str) -> Result<Response> {
+        self.request(path).response_delete().await
+    }
+
     fn build_url(&self, path: &str) -> Result<url::Url> {
         use url::Url;
         let mut base_url =
@@ -335,6 +354,19 @@ impl<'a> ApiRequestBuilder<'a> {
         self
     }
 
+    /// Overrides the client timeout for this request, e.g. for long downloads
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sends the request outside the rate limit, e.g. for token counts, which are limited
+    /// separately from completions and would otherwise take from their budget
+    pub fn without_rate_limit(mut self) -> Self {
+        self.rate_limited = false;
+        self
+    }
+
     pub async fn api_post(self, payload: &Value) -> Result<ApiResponse> {
         let response = self.response_post(payload).await?;
         ApiResponse::from_response(response).await
@@ -347,8 +379,17 @@ impl<'a> ApiRequestBuilder<'a> {
             serde_json::to_string(payload).unwrap_or_else(|_| "{}".to_string())
         );
 
+        let limiter = self.rate_limiter(payload);
+        if let Some(limiter) = &limiter {
+            limiter.acquire_for(payload).await;
+        }
+
         let request = self.send_request(|url, client| client.post(url)).await?;
-        Ok(request.json(payload).send().await?)
+        let response = request.json(payload).send().await?;
+        if let Some(limiter) = &limiter {
+            limiter.observe(response.status(), response.headers());
+        }
+        Ok(response)
     }
 
     pub async fn api_get(self) -> Result<ApiResponse> {
@@ -361,6 +402,21 @@ impl<'a> ApiRequestBuilder<'a> {
         Ok(request.send().await?)
     }
 
+    pub async fn response_delete(self) -> Result<Response> {
+        let request = self.send_request(|url, client| client.delete(url)).await?;
+        Ok(request.send().await?)
+    }
+
+    /// Models are usually named in the payload; otherwise (e.g. Google) the path identifies them
+    fn rate_limiter(&self, payload: &Value) -> Option<Arc<RateLimiter>> {
+        if !self.rate_limited {
+            return None;
+        }
+        let provider = self.client.rate_limit_provider.as_deref()?;
+        let model = rate_limit::model_key(self.path, payload);
+        Some(rate_limit::limiter_for(provider, model))
+    }
+
     async fn send_request<F>(&self, request_builder: F) -> Result<reqwest::RequestBuilder>
     where
         F: FnOnce(url::Url, &Client) -> reqwest::RequestBuilder,
@@ -368,6 +424,9 @@ impl<'a> ApiRequestBuilder<'a> {
         let url = self.client.build_url(self.path)?;
         let mut request = request_builder(url, &self.client.client);
         request = request.headers(self.headers.clone());
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
 
         request = match &self.client.auth {
             AuthMethod::BearerToken(token) => {
@@ -1,15 +1,20 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::Serialize;
-use serde_json::Value;
+use serde_json::{json, Value};
 
 use super::api_client::{ApiClient, AuthMethod, AuthProvider};
 use super::azureauth::{AuthError, AzureAuth};
-use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::errors::ProviderError;
-use super::formats::openai::{create_request, get_usage, response_to_message};
+use super::formats::openai::{
+    create_request, get_usage, response_to_message, response_to_streaming_message,
+};
 use super::retry::ProviderRetry;
-use super::utils::{emit_debug_trace, get_model, handle_response_openai_compat, ImageFormat};
+use super::utils::{
+    emit_debug_trace, get_model, handle_response_openai_compat, handle_status_openai_compat,
+    response_lines, stream_messages, ImageFormat,
+};
 use crate::conversation::message::Message;
 use crate::impl_provider_default;
 use crate::model::ModelConfig;
@@ -89,7 +94,8 @@ impl AzureProvider {
         })?;
 
         let auth_provider = AzureAuthProvider { auth };
-        let api_client = ApiClient::new(endpoint, AuthMethod::Custom(Box::new(auth_provider)))?;
+        let api_client = ApiClient::new(endpoint, AuthMethod::Custom(Box::new(auth_provider)))?
+            .with_rate_limit("azure_openai");
 
         Ok(Self {
             api_client,
@@ -99,14 +105,18 @@ impl AzureProvider {
         })
     }
 
-    async fn post(&self, payload: &Value) -> Result<Value, ProviderError> {
-        // Build the path for Azure OpenAI
-        let path = format!(
+    fn chat_completions_path(&self) -> String {
+        format!(
             "openai/deployments/{}/chat/completions?api-version={}",
             self.deployment_name, self.api_version
-        );
+        )
+    }
 
-        let response = self.api_client.response_post(&path, payload).await?;
+    async fn post(&self, payload: &Value) -> Result<Value, ProviderError> {
+        let response = self
+            .api_client
+            .response_post(&self.chat_completions_path(), payload)
+            .await?;
         handle_response_openai_compat(response).await
     }
 }
@@ -162,4 +172,38 @@ impl Provider for AzureProvider {
         emit_debug_trace(model_config, &payload, &response, &usage);
         Ok((message, ProviderUsage::new(response_model, usage)))
     }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let mut payload =
+            create_request(&self.model, system, messages, tools, &ImageFormat::OpenAi)?;
+        payload["stream"] = json!(true);
+        payload["stream_options"] = json!({
+            "include_usage": true,
+        });
+
+        let response = self
+            .with_retry(|| async {
+                let response = self
+                    .api_client
+                    .response_post(&self.chat_completions_path(), &payload)
+                    .await?;
+                handle_status_openai_compat(response).await
+            })
+            .await?;
+
+        Ok(stream_messages(
+            response_to_streaming_message(response_lines(response)),
+            self.model.clone(),
+            payload,
+        ))
+    }
 }
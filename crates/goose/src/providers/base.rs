@@ -3,6 +3,7 @@ use futures::Stream;
 use serde::{Deserialize, Serialize};
 
 use super::errors::ProviderError;
+use super::fallback::FallbackProvider;
 use super::retry::RetryConfig;
 use crate::conversation::message::Message;
 use crate::conversation::Conversation;
@@ -79,6 +80,73 @@ impl ModelInfo {
     }
 }
 
+/// A model a provider offers, as reported by the provider
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
+pub struct SupportedModel {
+    pub name: String,
+    /// The context length of the model, when the provider reports it
+    pub context_limit: Option<usize>,
+    /// What the model supports beyond chat, such as "tools", "vision" or "thinking"
+    pub capabilities: Vec<String>,
+}
+
+impl SupportedModel {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            context_limit: None,
+            capabilities: Vec::new(),
+        }
+    }
+}
+
+/// Model families known to take images, matched anywhere in the model name so that names
+/// with a provider prefix (e.g. `anthropic/claude-3-5-sonnet` or `us.anthropic.claude-...`)
+/// match too
+const IMAGE_MODEL_PATTERNS: &[&str] = &[
+    "claude-3",
+    "claude-4",
+    "claude-sonnet-4",
+    "claude-opus-4",
+    "claude-haiku-4",
+    "gpt-4o",
+    "gpt-4.1",
+    "gpt-4-turbo",
+    "gpt-5",
+    "gemini",
+    "gemma-3",
+    "gemma3",
+    "llama-4",
+    "llama4",
+    "llava",
+    "vision",
+    "-vl",
+    "pixtral",
+    "mistral-small-3.1",
+    "mistral-small-3.2",
+    "mistral-medium",
+    "grok-4",
+    "minicpm-v",
+    "moondream",
+];
+
+/// OpenAI reasoning models that take images, matched at the start of the model name
+const IMAGE_MODEL_PREFIXES: &[&str] = &["o1-20", "o3-20", "o3-pro", "o4-mini"];
+
+/// Whether a model is known to take images. Unknown models are assumed not to, so that
+/// images are described in text for them rather than failing the request.
+pub fn model_supports_images(model_name: &str) -> bool {
+    let name = model_name.to_lowercase();
+    let base_name = name.rsplit('/').next().unwrap_or(&name);
+    IMAGE_MODEL_PATTERNS
+        .iter()
+        .any(|pattern| name.contains(pattern))
+        || matches!(base_name, "o1" | "o3")
+        || IMAGE_MODEL_PREFIXES
+            .iter()
+            .any(|prefix| base_name.starts_with(prefix))
+}
+
 /// Metadata about a provider's configuration requirements and capabilities
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ProviderMetadata {
@@ -211,11 +279,27 @@ impl ConfigKey {
 pub struct ProviderUsage {
     pub model: String,
     pub usage: Usage,
+    /// Name of the provider that served the call, reported by providers that switch
+    /// between several (fallback chains, lead/worker)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
 }
 
 impl ProviderUsage {
     pub fn new(model: String, usage: Usage) -> Self {
-        Self { model, usage }
+        Self {
+            model,
+            usage,
+            provider: None,
+        }
+    }
+
+    /// Attribute this usage to `provider`, unless a nested provider already did
+    pub fn served_by(mut self, provider: Option<&str>) -> Self {
+        if self.provider.is_none() {
+            self.provider = provider.map(str::to_string);
+        }
+        self
     }
 
     /// Ensures this ProviderUsage has token counts, estimating them if necessary
@@ -243,6 +327,7 @@ impl ProviderUsage {
         ProviderUsage {
             model: self.model.clone(),
             usage: self.usage + other.usage,
+            provider: self.provider.clone().or_else(|| other.provider.clone()),
         }
     }
 }
@@ -252,6 +337,12 @@ pub struct Usage {
     pub input_tokens: Option<i32>,
     pub output_tokens: Option<i32>,
     pub total_tokens: Option<i32>,
+    /// Input tokens served from the provider's prompt cache, included in `input_tokens`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_input_tokens: Option<i32>,
+    /// Input tokens written to the provider's prompt cache, included in `input_tokens`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_input_tokens: Option<i32>,
 }
 
 fn sum_optionals<T>(a: Option<T>, b: Option<T>) -> Option<T>
@@ -274,6 +365,14 @@ impl Add for Usage {
             input_tokens: sum_optionals(self.input_tokens, other.input_tokens),
             output_tokens: sum_optionals(self.output_tokens, other.output_tokens),
             total_tokens: sum_optionals(self.total_tokens, other.total_tokens),
+            cache_read_input_tokens: sum_optionals(
+                self.cache_read_input_tokens,
+                other.cache_read_input_tokens,
+            ),
+            cache_write_input_tokens: sum_optionals(
+                self.cache_write_input_tokens,
+                other.cache_write_input_tokens,
+            ),
         }
     }
 }
@@ -294,8 +393,17 @@ impl Usage {
             input_tokens,
             output_tokens,
             total_tokens,
+            cache_read_input_tokens: None,
+            cache_write_input_tokens: None,
         }
     }
+
+    /// Record how many of the input tokens were read from or written to the prompt cache
+    pub fn with_cache_tokens(mut self, read: Option<i32>, write: Option<i32>) -> Self {
+        self.cache_read_input_tokens = read;
+        self.cache_write_input_tokens = write;
+        self
+    }
 }
 
 use async_trait::async_trait;
@@ -400,6 +508,17 @@ pub trait Provider: Send + Sync {
         Ok(None)
     }
 
+    /// Like `fetch_supported_models`, with the context length and capabilities of each
+    /// model for providers that report them
+    async fn fetch_supported_model_details(
+        &self,
+    ) -> Result<Option<Vec<SupportedModel>>, ProviderError> {
+        Ok(self
+            .fetch_supported_models()
+            .await?
+            .map(|models| models.into_iter().map(SupportedModel::new).collect()))
+    }
+
     /// Check if this provider supports embeddings
     fn supports_embeddings(&self) -> bool {
         false
@@ -410,6 +529,12 @@ pub trait Provider: Send + Sync {
         false
     }
 
+    /// Check if this provider can take images in messages. By default this is worked out
+    /// from the model name, see [`model_supports_images`].
+    fn supports_images(&self) -> bool {
+        model_supports_images(&self.get_model_config().model_name)
+    }
+
     /// Create embeddings if supported. Default implementation returns an error.
     async fn create_embeddings(&self, _texts: Vec<String>) -> Result<Vec<Vec<f32>>, ProviderError> {
         Err(ProviderError::ExecutionError(
@@ -417,12 +542,49 @@ pub trait Provider: Send + Sync {
         ))
     }
 
+    /// Check if this provider can constrain a completion to a JSON schema
+    fn supports_structured_output(&self) -> bool {
+        false
+    }
+
+    /// Complete with the output constrained to `schema`, using the provider's native
+    /// structured output. The result is not validated, see
+    /// [`super::structured_output::complete_structured`] for validation and repair.
+    async fn complete_structured(
+        &self,
+        _system: &str,
+        _messages: &[Message],
+        _schema: &serde_json::Value,
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        Err(ProviderError::NotImplemented(
+            "This provider does not support structured output".to_string(),
+        ))
+    }
+
+    /// Count the input tokens of a request with the provider's own tokenizer, for providers
+    /// with a token counting endpoint. None means the count has to be estimated locally,
+    /// see [`crate::token_counter`].
+    async fn count_tokens(
+        &self,
+        _system: &str,
+        _messages: &[Message],
+        _tools: &[Tool],
+    ) -> Result<Option<usize>, ProviderError> {
+        Ok(None)
+    }
+
     /// Check if this provider is a LeadWorkerProvider
     /// This is used for logging model information at startup
     fn as_lead_worker(&self) -> Option<&dyn LeadWorkerProviderTrait> {
         None
     }
 
+    /// Check if this provider is a FallbackProvider
+    /// This is used to report switches between providers
+    fn as_fallback(&self) -> Option<&FallbackProvider> {
+        None
+    }
+
     async fn stream(
         &self,
         _system: &str,
@@ -559,6 +721,34 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_model_supports_images() {
+        for model in [
+            "claude-sonnet-4-20250514",
+            "us.anthropic.claude-3-7-sonnet-20250219-v1:0",
+            "gpt-4o-mini",
+            "openai/gpt-4.1",
+            "o3",
+            "o4-mini",
+            "gemini-2.5-pro",
+            "llama3.2-vision:11b",
+            "qwen2.5-vl:7b",
+            "meta-llama/llama-4-scout-17b-16e-instruct",
+        ] {
+            assert!(model_supports_images(model), "{}", model);
+        }
+        for model in [
+            "o3-mini",
+            "llama-3.3-70b-versatile",
+            "qwen3:8b",
+            "deepseek-r1",
+            "mistral-large-latest",
+            "some-unknown-model",
+        ] {
+            assert!(!model_supports_images(model), "{}", model);
+        }
+    }
+
     #[test]
     fn test_set_and_get_current_model() {
         // Set the model
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage};
+use super::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::errors::ProviderError;
 use super::retry::{ProviderRetry, RetryConfig};
 use crate::conversation::message::Message;
@@ -8,17 +8,23 @@ use crate::impl_provider_default;
 use crate::model::ModelConfig;
 use crate::providers::utils::emit_debug_trace;
 use anyhow::Result;
+use async_stream::try_stream;
 use async_trait::async_trait;
 use aws_sdk_bedrockruntime::config::ProvideCredentials;
 use aws_sdk_bedrockruntime::operation::converse::ConverseError;
+use aws_sdk_bedrockruntime::operation::converse_stream::{
+    ConverseStreamError, ConverseStreamOutput,
+};
 use aws_sdk_bedrockruntime::{types as bedrock, Client};
 use rmcp::model::Tool;
 use serde_json::Value;
 
 // Import the migrated helper functions from providers/formats/bedrock.rs
 use super::formats::bedrock::{
-    from_bedrock_message, from_bedrock_usage, to_bedrock_message, to_bedrock_tool_config,
+    from_bedrock_message, from_bedrock_usage, supports_cache_points, to_bedrock_request,
+    to_bedrock_thinking, BedrockStreamState,
 };
+use super::prompt_cache::prompt_caching_enabled;
 
 pub const BEDROCK_DOC_LINK: &str =
     "https://docs.aws.amazon.com/bedrock/latest/userguide/models-supported.html";
@@ -108,6 +114,10 @@ impl BedrockProvider {
         }
     }
 
+    fn use_cache_points(&self) -> bool {
+        prompt_caching_enabled() && supports_cache_points(&self.model.model_name)
+    }
+
     async fn converse(
         &self,
         system: &str,
@@ -115,24 +125,19 @@ impl BedrockProvider {
         tools: &[Tool],
     ) -> Result<(bedrock::Message, Option<bedrock::TokenUsage>), ProviderError> {
         let model_name = &self.model.model_name;
+        let (system, messages, tool_config) =
+            to_bedrock_request(system, messages, tools, self.use_cache_points())?;
+        let (thinking_fields, inference_config) = to_bedrock_thinking(&self.model)?.unzip();
 
-        let mut request = self
+        let response = self
             .client
             .converse()
-            .system(bedrock::SystemContentBlock::Text(system.to_string()))
+            .set_system(Some(system))
             .model_id(model_name.to_string())
-            .set_messages(Some(
-                messages
-                    .iter()
-                    .map(to_bedrock_message)
-                    .collect::<Result<_>>()?,
-            ));
-
-        if !tools.is_empty() {
-            request = request.tool_config(to_bedrock_tool_config(tools)?);
-        }
-
-        let response = request
+            .set_messages(Some(messages))
+            .set_tool_config(tool_config)
+            .set_additional_model_request_fields(thinking_fields)
+            .set_inference_config(inference_config)
             .send()
             .await
             .map_err(|err| match err.into_service_error() {
@@ -169,6 +174,53 @@ impl BedrockProvider {
             )),
         }
     }
+    async fn converse_stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<ConverseStreamOutput, ProviderError> {
+        let (system, messages, tool_config) =
+            to_bedrock_request(system, messages, tools, self.use_cache_points())?;
+        let (thinking_fields, inference_config) = to_bedrock_thinking(&self.model)?.unzip();
+
+        self.client
+            .converse_stream()
+            .set_system(Some(system))
+            .model_id(self.model.model_name.to_string())
+            .set_messages(Some(messages))
+            .set_tool_config(tool_config)
+            .set_additional_model_request_fields(thinking_fields)
+            .set_inference_config(inference_config)
+            .send()
+            .await
+            .map_err(|err| match err.into_service_error() {
+                ConverseStreamError::ThrottlingException(throttle_err) => {
+                    ProviderError::RateLimitExceeded(format!(
+                        "Bedrock throttling error: {:?}",
+                        throttle_err
+                    ))
+                }
+                ConverseStreamError::AccessDeniedException(err) => {
+                    ProviderError::Authentication(format!("Failed to call Bedrock: {:?}", err))
+                }
+                ConverseStreamError::ValidationException(err)
+                    if err
+                        .message()
+                        .unwrap_or_default()
+                        .contains("Input is too long for requested model.") =>
+                {
+                    ProviderError::ContextLengthExceeded(format!(
+                        "Failed to call Bedrock: {:?}",
+                        err
+                    ))
+                }
+                ConverseStreamError::ModelErrorException(err) => {
+                    ProviderError::ExecutionError(format!("Failed to call Bedrock: {:?}", err))
+                }
+                err => ProviderError::ServerError(format!("Failed to call Bedrock: {:?}", err)),
+            })
+    }
 }
 
 impl_provider_default!(BedrockProvider);
@@ -235,4 +287,46 @@ impl Provider for BedrockProvider {
         let provider_usage = ProviderUsage::new(model_name.to_string(), usage);
         Ok((message, provider_usage))
     }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let mut output = self
+            .with_retry(|| self.converse_stream(system, messages, tools))
+            .await?;
+
+        let model_config = self.model.clone();
+        let debug_payload = serde_json::json!({
+            "system": system,
+            "messages": messages,
+            "tools": tools
+        });
+
+        Ok(Box::pin(try_stream! {
+            let mut state = BedrockStreamState::default();
+            while let Some(event) = output.stream.recv().await.map_err(|e| {
+                ProviderError::ServerError(format!("Failed to read Bedrock stream: {:?}", e))
+            })? {
+                let message = state
+                    .process_event(&event)
+                    .map_err(|e| ProviderError::RequestFailed(format!("Stream decode error: {}", e)))?;
+                if let Some(message) = message {
+                    emit_debug_trace(&model_config, &debug_payload, &message, &Usage::default());
+                    yield (Some(message), None);
+                }
+            }
+
+            if let Some(usage) = state.usage() {
+                emit_debug_trace(&model_config, &debug_payload, &Value::Null, &usage);
+                yield (None, Some(ProviderUsage::new(model_config.model_name.clone(), usage)));
+            }
+        }))
+    }
 }
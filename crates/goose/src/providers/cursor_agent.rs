@@ -403,6 +403,11 @@ impl Provider for CursorAgentProvider {
         self.model.clone()
     }
 
+    fn supports_images(&self) -> bool {
+        // Only text is passed to the CLI
+        false
+    }
+
     #[tracing::instrument(
         skip(self, model_config, system, messages, tools),
         fields(model_config, input, output, input_tokens, output_tokens, total_tokens)
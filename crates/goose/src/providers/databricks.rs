@@ -139,7 +139,8 @@ impl DatabricksProvider {
             AuthMethod::Custom(Box::new(DatabricksAuthProvider { auth: auth.clone() }));
 
         let api_client =
-            ApiClient::with_timeout(host, auth_method, Duration::from_secs(DEFAULT_TIMEOUT_SECS))?;
+            ApiClient::with_timeout(host, auth_method, Duration::from_secs(DEFAULT_TIMEOUT_SECS))?
+                .with_rate_limit("databricks");
 
         // Create the provider without the fast model first
         let mut provider = Self {
@@ -216,7 +217,8 @@ impl DatabricksProvider {
         let auth_method =
             AuthMethod::Custom(Box::new(DatabricksAuthProvider { auth: auth.clone() }));
 
-        let api_client = ApiClient::with_timeout(host, auth_method, Duration::from_secs(600))?;
+        let api_client = ApiClient::with_timeout(host, auth_method, Duration::from_secs(600))?
+            .with_rate_limit("databricks");
 
         Ok(Self {
             api_client,
@@ -1,7 +1,15 @@
+use std::sync::Arc;
+
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+use super::base::Provider;
+use crate::config::Config;
+
+/// Value of `GOOSE_EMBEDDING_PROVIDER` that selects local embeddings
+pub const LOCAL_EMBEDDING_PROVIDER: &str = "local";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmbeddingRequest {
     pub input: Vec<String>,
@@ -22,3 +30,42 @@ pub struct EmbeddingData {
 pub trait EmbeddingCapable {
     async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>>;
 }
+
+/// Embeddings from a provider's own embeddings API
+pub struct ProviderEmbeddings(pub Arc<dyn Provider>);
+
+#[async_trait]
+impl EmbeddingCapable for ProviderEmbeddings {
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        Ok(self.0.create_embeddings(texts).await?)
+    }
+}
+
+/// The embedding backend configured by `GOOSE_EMBEDDING_PROVIDER`: a model run locally
+/// when it is `local`, otherwise the embeddings API of `provider`
+pub fn embedding_backend(
+    provider: Arc<dyn Provider>,
+) -> Result<Arc<dyn EmbeddingCapable + Send + Sync>> {
+    let backend: Option<String> = Config::global().get_param("GOOSE_EMBEDDING_PROVIDER").ok();
+    match backend.as_deref() {
+        Some(LOCAL_EMBEDDING_PROVIDER) => local_embedding_backend(),
+        _ if provider.supports_embeddings() => Ok(Arc::new(ProviderEmbeddings(provider))),
+        _ => Err(anyhow::anyhow!(
+            "The current provider does not support embeddings. Set GOOSE_EMBEDDING_PROVIDER to '{}' to compute them locally",
+            LOCAL_EMBEDDING_PROVIDER
+        )),
+    }
+}
+
+#[cfg(feature = "local-embeddings")]
+fn local_embedding_backend() -> Result<Arc<dyn EmbeddingCapable + Send + Sync>> {
+    use super::local_embedding::LocalEmbeddingProvider;
+    Ok(Arc::new(LocalEmbeddingProvider::from_env()?))
+}
+
+#[cfg(not(feature = "local-embeddings"))]
+fn local_embedding_backend() -> Result<Arc<dyn EmbeddingCapable + Send + Sync>> {
+    Err(anyhow::anyhow!(
+        "Local embeddings are not available in this build of goose. Rebuild it with the 'local-embeddings' feature"
+    ))
+}
@@ -148,10 +148,14 @@ where
 impl OpenAIError {
     pub fn is_context_length_exceeded(&self) -> bool {
         if let Some(code) = &self.code {
-            code == "context_length_exceeded" || code == "string_above_max_length"
-        } else {
-            false
+            if code == "context_length_exceeded" || code == "string_above_max_length" {
+                return true;
+            }
         }
+        // LiteLLM reports the status as the code and names the error in the message
+        self.message
+            .as_deref()
+            .is_some_and(|message| message.contains("ContextWindowExceededError"))
     }
 }
 
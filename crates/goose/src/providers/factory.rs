@@ -8,6 +8,10 @@ use super::{
     claude_code::ClaudeCodeProvider,
     cursor_agent::CursorAgentProvider,
     databricks::DatabricksProvider,
+    fallback::{
+        FallbackModel, FallbackProvider, DEFAULT_CIRCUIT_COOLDOWN_SECS,
+        DEFAULT_CIRCUIT_FAILURE_THRESHOLD,
+    },
     gcpvertexai::GcpVertexAIProvider,
     gemini_cli::GeminiCliProvider,
     githubcopilot::GithubCopilotProvider,
@@ -27,8 +31,10 @@ use super::{
 };
 use crate::config::custom_providers::{custom_providers_dir, register_custom_providers};
 use crate::model::ModelConfig;
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::time::Duration;
 
 #[cfg(test)]
 use super::errors::ProviderError;
@@ -93,13 +99,99 @@ pub fn refresh_custom_providers() -> Result<()> {
 
 pub fn create(name: &str, model: ModelConfig) -> Result<Arc<dyn Provider>> {
     let config = crate::config::Config::global();
+    let fallback_models = fallback_models_from_config()?;
 
-    if let Ok(lead_model_name) = config.get_param::<String>("GOOSE_LEAD_MODEL") {
+    let provider = if let Ok(lead_model_name) = config.get_param::<String>("GOOSE_LEAD_MODEL") {
         tracing::info!("Creating lead/worker provider from environment variables");
-        return create_lead_worker_from_env(name, &model, &lead_model_name);
+        create_lead_worker_from_env(name, &model, &lead_model_name)?
+    } else {
+        REGISTRY.read().unwrap().create(name, model.clone())?
+    };
+
+    if fallback_models.is_empty() {
+        return Ok(provider);
+    }
+    Ok(create_fallback(name, provider, &model, &fallback_models))
+}
+
+/// Read the ordered fallback chain from GOOSE_FALLBACK_MODELS, either a list or a
+/// comma-separated string of provider/model pairs
+fn fallback_models_from_config() -> Result<Vec<FallbackModel>> {
+    let config = crate::config::Config::global();
+    let Ok(value) = config.get_param::<Value>("GOOSE_FALLBACK_MODELS") else {
+        return Ok(Vec::new());
+    };
+
+    let entries: Vec<String> = match value {
+        Value::String(s) => s
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect(),
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| {
+                item.as_str().map(String::from).ok_or_else(|| {
+                    anyhow!("GOOSE_FALLBACK_MODELS entries must be provider/model strings")
+                })
+            })
+            .collect::<Result<_>>()?,
+        _ => bail!("GOOSE_FALLBACK_MODELS must be a list of provider/model pairs"),
+    };
+    entries.iter().map(|entry| entry.parse()).collect()
+}
+
+fn create_fallback(
+    primary_name: &str,
+    primary: Arc<dyn Provider>,
+    primary_model: &ModelConfig,
+    fallback_models: &[FallbackModel],
+) -> Arc<dyn Provider> {
+    let config = crate::config::Config::global();
+
+    let failure_threshold = config
+        .get_param::<usize>("GOOSE_FALLBACK_FAILURE_THRESHOLD")
+        .unwrap_or(DEFAULT_CIRCUIT_FAILURE_THRESHOLD);
+    let cooldown_secs = config
+        .get_param::<u64>("GOOSE_FALLBACK_COOLDOWN_SECS")
+        .unwrap_or(DEFAULT_CIRCUIT_COOLDOWN_SECS);
+
+    let mut providers = vec![(primary_name.to_string(), primary)];
+    for fallback in fallback_models {
+        // A broken fallback should not keep the primary provider from working
+        let provider = ModelConfig::new(&fallback.model)
+            .map_err(anyhow::Error::from)
+            .map(|model| {
+                model
+                    .with_temperature(primary_model.temperature)
+                    .with_reasoning(primary_model.reasoning)
+            })
+            .and_then(|model| REGISTRY.read().unwrap().create(&fallback.provider, model));
+        match provider {
+            Ok(provider) => providers.push((fallback.provider.clone(), provider)),
+            Err(e) => tracing::warn!(
+                "Skipping fallback {}/{}: {}",
+                fallback.provider,
+                fallback.model,
+                e
+            ),
+        }
     }
 
-    REGISTRY.read().unwrap().create(name, model)
+    tracing::info!(
+        "Fallback chain: {}",
+        providers
+            .iter()
+            .map(|(name, provider)| format!("{}/{}", name, provider.get_model_config().model_name))
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    );
+    Arc::new(FallbackProvider::new(
+        providers,
+        failure_threshold,
+        Duration::from_secs(cooldown_secs),
+    ))
 }
 
 fn create_lead_worker_from_env(
@@ -126,7 +218,8 @@ fn create_lead_worker_from_env(
     let lead_model_config = ModelConfig::new_with_context_env(
         lead_model_name.to_string(),
         Some("GOOSE_LEAD_CONTEXT_LIMIT"),
-    )?;
+    )?
+    .with_reasoning(default_model.reasoning);
 
     let worker_model_config = create_worker_model_config(default_model)?;
 
@@ -139,13 +232,16 @@ fn create_lead_worker_from_env(
         .unwrap()
         .create(default_provider_name, worker_model_config)?;
 
-    Ok(Arc::new(LeadWorkerProvider::new_with_settings(
-        lead_provider,
-        worker_provider,
-        lead_turns,
-        failure_threshold,
-        fallback_turns,
-    )))
+    Ok(Arc::new(
+        LeadWorkerProvider::new_with_settings(
+            lead_provider,
+            worker_provider,
+            lead_turns,
+            failure_threshold,
+            fallback_turns,
+        )
+        .with_provider_names(lead_provider_name, default_provider_name.to_string()),
+    ))
 }
 
 fn create_worker_model_config(default_model: &ModelConfig) -> Result<ModelConfig> {
@@ -154,7 +250,8 @@ fn create_worker_model_config(default_model: &ModelConfig) -> Result<ModelConfig
         .with_temperature(default_model.temperature)
         .with_max_tokens(default_model.max_tokens)
         .with_toolshim(default_model.toolshim)
-        .with_toolshim_model(default_model.toolshim_model.clone());
+        .with_toolshim_model(default_model.toolshim_model.clone())
+        .with_reasoning(default_model.reasoning);
 
     let global_config = crate::config::Config::global();
 
@@ -321,6 +418,28 @@ mod tests {
         let _result = create("openai", ModelConfig::new_or_fail("gpt-4o-mini"));
     }
 
+    #[test]
+    fn test_fallback_models_from_config() {
+        let _guard = EnvVarGuard::new(&["GOOSE_FALLBACK_MODELS"]);
+        assert!(fallback_models_from_config().unwrap().is_empty());
+
+        _guard.set(
+            "GOOSE_FALLBACK_MODELS",
+            "openai/gpt-4o, openrouter/anthropic/claude-sonnet-4",
+        );
+        let models = fallback_models_from_config().unwrap();
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[1].provider, "openrouter");
+        assert_eq!(models[1].model, "anthropic/claude-sonnet-4");
+
+        _guard.set("GOOSE_FALLBACK_MODELS", r#"["google/gemini-2.5-pro"]"#);
+        let models = fallback_models_from_config().unwrap();
+        assert_eq!(models[0].provider, "google");
+
+        _guard.set("GOOSE_FALLBACK_MODELS", "gpt-4o");
+        assert!(fallback_models_from_config().is_err());
+    }
+
     #[test]
     fn test_create_regular_provider_without_lead_config() {
         let _guard = EnvVarGuard::new(&[
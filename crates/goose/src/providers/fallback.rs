@@ -0,0 +1,773 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+use super::base::{
+    stream_from_single_message, LeadWorkerProviderTrait, MessageStream, Provider, ProviderMetadata,
+    ProviderUsage, SupportedModel,
+};
+use super::errors::ProviderError;
+use crate::conversation::message::{Message, MessageContent};
+use crate::model::ModelConfig;
+use rmcp::model::Tool;
+
+pub const DEFAULT_CIRCUIT_FAILURE_THRESHOLD: usize = 2;
+pub const DEFAULT_CIRCUIT_COOLDOWN_SECS: u64 = 60;
+
+const IMAGE_PLACEHOLDER: &str = "[An image was attached here, but this model cannot view images]";
+
+/// A provider/model pair in a fallback chain, written as `provider/model` in config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FallbackModel {
+    pub provider: String,
+    pub model: String,
+}
+
+impl FromStr for FallbackModel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        // Model names may contain slashes (e.g. openrouter), provider names never do
+        match s.trim().split_once('/') {
+            Some((provider, model)) if !provider.is_empty() && !model.is_empty() => Ok(Self {
+                provider: provider.to_string(),
+                model: model.to_string(),
+            }),
+            _ => Err(anyhow::anyhow!(
+                "Invalid fallback model '{}', expected provider/model",
+                s
+            )),
+        }
+    }
+}
+
+/// Reported when a request is served by a different model than the previous request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ModelFallback {
+    pub from_provider: String,
+    pub from_model: String,
+    pub to_provider: String,
+    pub to_model: String,
+    /// Why the switch happened, e.g. the error returned by the previous model
+    pub reason: String,
+}
+
+/// Consecutive failures of one provider; once open, the provider is skipped until the
+/// cooldown has passed, after which a single request is let through to probe it
+#[derive(Debug, Default)]
+struct CircuitBreaker {
+    consecutive_failures: usize,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    fn is_available(&self, now: Instant) -> bool {
+        self.open_until.is_none_or(|until| now >= until)
+    }
+
+    fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.open_until = None;
+    }
+
+    fn record_failure(&mut self, threshold: usize, cooldown: Duration, now: Instant) {
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= threshold {
+            self.open_until = Some(now + cooldown);
+        }
+    }
+}
+
+struct FallbackEntry {
+    provider_name: String,
+    provider: Arc<dyn Provider>,
+    breaker: Mutex<CircuitBreaker>,
+}
+
+impl FallbackEntry {
+    fn model_name(&self) -> String {
+        self.provider.get_active_model_name()
+    }
+}
+
+#[derive(Default)]
+struct FallbackState {
+    /// Index of the entry that served the last request
+    active: usize,
+    /// Conversation length when `active` started serving; thinking blocks before this
+    /// point were produced by another provider
+    switched_at: usize,
+    /// Switches not yet picked up by the agent
+    events: Vec<ModelFallback>,
+}
+
+/// A provider that fails over along an ordered list of providers when one of them is
+/// down, rate limited or rejects its credentials
+pub struct FallbackProvider {
+    entries: Vec<FallbackEntry>,
+    failure_threshold: usize,
+    cooldown: Duration,
+    state: Mutex<FallbackState>,
+}
+
+/// Errors that say nothing about the request itself, so another provider may succeed
+fn should_fail_over(error: &ProviderError) -> bool {
+    matches!(
+        error,
+        ProviderError::ServerError(_)
+            | ProviderError::RateLimitExceeded(_)
+            | ProviderError::Authentication(_)
+    )
+}
+
+/// Prepare a conversation for a provider that did not produce all of it. Thinking blocks
+/// carry provider specific signatures, so those produced before `keep_thinking_from` are
+/// dropped; images are described in text for providers that cannot take them.
+fn translate_messages(
+    messages: &[Message],
+    keep_thinking_from: usize,
+    supports_images: bool,
+) -> Vec<Message> {
+    messages
+        .iter()
+        .enumerate()
+        .filter_map(|(i, message)| {
+            let strip_thinking = i < keep_thinking_from;
+            let mut message = message.clone();
+            message.content = message
+                .content
+                .into_iter()
+                .filter_map(|content| match content {
+                    MessageContent::Thinking(_) | MessageContent::RedactedThinking(_)
+                        if strip_thinking =>
+                    {
+                        None
+                    }
+                    MessageContent::Image(_) if !supports_images => {
+                        Some(MessageContent::text(IMAGE_PLACEHOLDER))
+                    }
+                    content => Some(content),
+                })
+                .collect();
+            (!message.content.is_empty()).then_some(message)
+        })
+        .collect()
+}
+
+/// Start a stream from `provider`, or a single message stream if it does not stream
+async fn stream_from(
+    provider: &dyn Provider,
+    system: &str,
+    messages: &[Message],
+    tools: &[Tool],
+) -> Result<MessageStream, ProviderError> {
+    if !provider.supports_streaming() {
+        let (message, usage) = provider.complete(system, messages, tools).await?;
+        return Ok(stream_from_single_message(message, usage));
+    }
+
+    let mut stream = provider.stream(system, messages, tools).await?;
+    // Providers report some failures, like overload, as the first event of the stream,
+    // so wait for it before committing to this provider
+    match stream.next().await {
+        Some(Err(e)) => Err(e),
+        first => Ok(Box::pin(futures::stream::iter(first).chain(stream))),
+    }
+}
+
+impl FallbackProvider {
+    /// Create a new FallbackProvider
+    ///
+    /// # Arguments
+    /// * `providers` - Provider names and providers in the order they should be tried
+    /// * `failure_threshold` - Consecutive failures after which a provider is skipped
+    /// * `cooldown` - How long a provider is skipped before it is tried again
+    pub fn new(
+        providers: Vec<(String, Arc<dyn Provider>)>,
+        failure_threshold: usize,
+        cooldown: Duration,
+    ) -> Self {
+        Self {
+            entries: providers
+                .into_iter()
+                .map(|(provider_name, provider)| FallbackEntry {
+                    provider_name,
+                    provider,
+                    breaker: Mutex::new(CircuitBreaker::default()),
+                })
+                .collect(),
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::new(FallbackState::default()),
+        }
+    }
+
+    fn primary(&self) -> &Arc<dyn Provider> {
+        &self.entries[0].provider
+    }
+
+    /// Take the switches that happened since the last call
+    pub fn take_events(&self) -> Vec<ModelFallback> {
+        std::mem::take(&mut self.state.lock().unwrap().events)
+    }
+
+    /// Name of the provider that served the last request
+    pub fn active_provider_name(&self) -> String {
+        let active = self.state.lock().unwrap().active;
+        self.entries[active].provider_name.clone()
+    }
+
+    /// Entries in the order to try them, skipping those with an open circuit unless all are
+    fn candidates(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let available: Vec<usize> = (0..self.entries.len())
+            .filter(|&i| self.entries[i].breaker.lock().unwrap().is_available(now))
+            .collect();
+        if available.is_empty() {
+            (0..self.entries.len()).collect()
+        } else {
+            available
+        }
+    }
+
+    fn messages_for(&self, index: usize, messages: &[Message]) -> Vec<Message> {
+        let state = self.state.lock().unwrap();
+        let keep_thinking_from = if index == state.active {
+            state.switched_at.min(messages.len())
+        } else {
+            messages.len()
+        };
+        let supports_images = self.entries[index].provider.supports_images();
+        translate_messages(messages, keep_thinking_from, supports_images)
+    }
+
+    fn record_failure(&self, index: usize, error: &ProviderError) {
+        let entry = &self.entries[index];
+        tracing::warn!(
+            "{} ({}) failed, trying the next fallback: {}",
+            entry.provider_name,
+            entry.model_name(),
+            error
+        );
+        entry.breaker.lock().unwrap().record_failure(
+            self.failure_threshold,
+            self.cooldown,
+            Instant::now(),
+        );
+    }
+
+    fn record_success(&self, index: usize, messages_len: usize, last_error: Option<&str>) {
+        self.entries[index].breaker.lock().unwrap().record_success();
+
+        let mut state = self.state.lock().unwrap();
+        if state.active == index {
+            return;
+        }
+        let from = &self.entries[state.active];
+        let to = &self.entries[index];
+        let reason = match last_error {
+            Some(error) => error.to_string(),
+            None if index < state.active => format!("{} is available again", to.provider_name),
+            None => format!(
+                "{} is unavailable after repeated failures",
+                from.provider_name
+            ),
+        };
+        tracing::warn!(
+            "Switching from {} ({}) to {} ({}): {}",
+            from.provider_name,
+            from.model_name(),
+            to.provider_name,
+            to.model_name(),
+            reason
+        );
+        let event = ModelFallback {
+            from_provider: from.provider_name.clone(),
+            from_model: from.model_name(),
+            to_provider: to.provider_name.clone(),
+            to_model: to.model_name(),
+            reason,
+        };
+        state.events.push(event);
+        state.active = index;
+        state.switched_at = messages_len;
+    }
+}
+
+impl LeadWorkerProviderTrait for FallbackProvider {
+    fn get_model_info(&self) -> (String, String) {
+        match self.primary().as_lead_worker() {
+            Some(lead_worker) => lead_worker.get_model_info(),
+            None => {
+                let model = self.primary().get_model_config().model_name;
+                (model.clone(), model)
+            }
+        }
+    }
+
+    fn get_active_model(&self) -> String {
+        let active = self.state.lock().unwrap().active;
+        self.entries[active].model_name()
+    }
+}
+
+#[async_trait]
+impl Provider for FallbackProvider {
+    fn metadata() -> ProviderMetadata {
+        // This is a wrapper provider, so we return minimal metadata
+        ProviderMetadata::new(
+            "fallback",
+            "Fallback Provider",
+            "A provider that fails over to other providers when one is unavailable",
+            "",     // No default model as this is determined by the wrapped providers
+            vec![], // No known models as this depends on wrapped providers
+            "",     // No doc link
+            vec![], // No config keys as configuration is done through wrapped providers
+        )
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.primary().get_model_config()
+    }
+
+    async fn complete_with_model(
+        &self,
+        model_config: &ModelConfig,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let candidates = self.candidates();
+        let mut last_error: Option<ProviderError> = None;
+
+        for (attempt, &index) in candidates.iter().enumerate() {
+            let provider = &self.entries[index].provider;
+            let translated = self.messages_for(index, messages);
+            // The requested model config (e.g. the fast model) only applies to the primary
+            let result = if index == 0 {
+                provider
+                    .complete_with_model(model_config, system, &translated, tools)
+                    .await
+            } else {
+                provider.complete(system, &translated, tools).await
+            };
+
+            match result {
+                Ok((message, usage)) => {
+                    let last_error = last_error.map(|e| e.to_string());
+                    self.record_success(index, messages.len(), last_error.as_deref());
+                    let usage = usage.served_by(Some(&self.entries[index].provider_name));
+                    return Ok((message, usage));
+                }
+                Err(e) if should_fail_over(&e) => {
+                    self.record_failure(index, &e);
+                    if attempt + 1 == candidates.len() {
+                        return Err(e);
+                    }
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            ProviderError::ExecutionError("No providers configured for fallback".to_string())
+        }))
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let candidates = self.candidates();
+        let mut last_error: Option<ProviderError> = None;
+
+        for (attempt, &index) in candidates.iter().enumerate() {
+            let provider = &self.entries[index].provider;
+            let translated = self.messages_for(index, messages);
+            let result = stream_from(provider.as_ref(), system, &translated, tools).await;
+
+            match result {
+                Ok(stream) => {
+                    let last_error = last_error.map(|e| e.to_string());
+                    self.record_success(index, messages.len(), last_error.as_deref());
+                    let provider_name = self.entries[index].provider_name.clone();
+                    return Ok(Box::pin(stream.map(move |item| {
+                        item.map(|(message, usage)| {
+                            (message, usage.map(|u| u.served_by(Some(&provider_name))))
+                        })
+                    })));
+                }
+                Err(e) if should_fail_over(&e) => {
+                    self.record_failure(index, &e);
+                    if attempt + 1 == candidates.len() {
+                        return Err(e);
+                    }
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            ProviderError::ExecutionError("No providers configured for fallback".to_string())
+        }))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.primary().supports_streaming()
+    }
+
+    fn supports_images(&self) -> bool {
+        // Images are translated per provider when failing over
+        true
+    }
+
+    fn supports_cache_control(&self) -> bool {
+        self.primary().supports_cache_control()
+    }
+
+    async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
+        self.primary().fetch_supported_models().await
+    }
+
+    async fn fetch_supported_model_details(
+        &self,
+    ) -> Result<Option<Vec<SupportedModel>>, ProviderError> {
+        self.primary().fetch_supported_model_details().await
+    }
+
+    fn supports_embeddings(&self) -> bool {
+        self.primary().supports_embeddings()
+    }
+
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, ProviderError> {
+        self.primary().create_embeddings(texts).await
+    }
+
+    fn supports_structured_output(&self) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.provider.supports_structured_output())
+    }
+
+    async fn complete_structured(
+        &self,
+        system: &str,
+        messages: &[Message],
+        schema: &serde_json::Value,
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        // Only providers with native structured output can take the request
+        let supports = |&index: &usize| self.entries[index].provider.supports_structured_output();
+        let mut candidates: Vec<usize> = self.candidates().into_iter().filter(supports).collect();
+        if candidates.is_empty() {
+            candidates = (0..self.entries.len()).filter(supports).collect();
+        }
+        let mut last_error: Option<ProviderError> = None;
+
+        for (attempt, &index) in candidates.iter().enumerate() {
+            let provider = &self.entries[index].provider;
+            let translated = self.messages_for(index, messages);
+            let result = provider
+                .complete_structured(system, &translated, schema)
+                .await;
+
+            match result {
+                Ok((message, usage)) => {
+                    let last_error = last_error.map(|e| e.to_string());
+                    self.record_success(index, messages.len(), last_error.as_deref());
+                    let usage = usage.served_by(Some(&self.entries[index].provider_name));
+                    return Ok((message, usage));
+                }
+                Err(e) if should_fail_over(&e) => {
+                    self.record_failure(index, &e);
+                    if attempt + 1 == candidates.len() {
+                        return Err(e);
+                    }
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            ProviderError::NotImplemented(
+                "No fallback provider supports structured output".to_string(),
+            )
+        }))
+    }
+
+    async fn count_tokens(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<Option<usize>, ProviderError> {
+        // Count with the tokenizer of the model that will get the request
+        let active = self.state.lock().unwrap().active;
+        let provider = Arc::clone(&self.entries[active].provider);
+        provider.count_tokens(system, messages, tools).await
+    }
+
+    fn as_lead_worker(&self) -> Option<&dyn LeadWorkerProviderTrait> {
+        self.primary().as_lead_worker()
+    }
+
+    fn as_fallback(&self) -> Option<&FallbackProvider> {
+        Some(self)
+    }
+
+    fn get_active_model_name(&self) -> String {
+        self.get_active_model()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::Usage;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct MockProvider {
+        name: String,
+        model_config: ModelConfig,
+        error: Option<fn() -> ProviderError>,
+        images: bool,
+        structured: bool,
+        calls: AtomicUsize,
+        last_messages: Mutex<Vec<Message>>,
+    }
+
+    impl MockProvider {
+        fn new(name: &str, error: Option<fn() -> ProviderError>) -> Self {
+            Self {
+                name: name.to_string(),
+                model_config: ModelConfig::new_or_fail(&format!("{}-model", name)),
+                error,
+                images: true,
+                structured: true,
+                calls: AtomicUsize::new(0),
+                last_messages: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Provider for MockProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            self.model_config.clone()
+        }
+
+        fn supports_images(&self) -> bool {
+            self.images
+        }
+
+        fn supports_structured_output(&self) -> bool {
+            self.structured
+        }
+
+        async fn complete_structured(
+            &self,
+            system: &str,
+            messages: &[Message],
+            _schema: &serde_json::Value,
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            let model_config = self.model_config.clone();
+            self.complete_with_model(&model_config, system, messages, &[])
+                .await
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            _system: &str,
+            messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            *self.last_messages.lock().unwrap() = messages.to_vec();
+            match self.error {
+                Some(error) => Err(error()),
+                None => Ok((
+                    Message::assistant().with_text(format!("Response from {}", self.name)),
+                    ProviderUsage::new(self.name.clone(), Usage::default()),
+                )),
+            }
+        }
+    }
+
+    fn server_error() -> ProviderError {
+        ProviderError::ServerError("overloaded".to_string())
+    }
+
+    fn context_error() -> ProviderError {
+        ProviderError::ContextLengthExceeded("too long".to_string())
+    }
+
+    fn fallback(providers: Vec<Arc<MockProvider>>, threshold: usize) -> FallbackProvider {
+        FallbackProvider::new(
+            providers
+                .into_iter()
+                .map(|p| (p.name.clone(), p as Arc<dyn Provider>))
+                .collect(),
+            threshold,
+            Duration::from_secs(60),
+        )
+    }
+
+    #[test]
+    fn test_parse_fallback_model() {
+        let model: FallbackModel = "openrouter/anthropic/claude-sonnet-4".parse().unwrap();
+        assert_eq!(model.provider, "openrouter");
+        assert_eq!(model.model, "anthropic/claude-sonnet-4");
+        assert!("gpt-4o".parse::<FallbackModel>().is_err());
+        assert!("openai/".parse::<FallbackModel>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_and_reports_event() {
+        let primary = Arc::new(MockProvider::new("anthropic", Some(server_error)));
+        let secondary = Arc::new(MockProvider::new("openai", None));
+        let provider = fallback(vec![primary.clone(), secondary.clone()], 3);
+
+        let (_message, usage) = provider.complete("system", &[], &[]).await.unwrap();
+        assert_eq!(usage.model, "openai");
+        assert_eq!(usage.provider.as_deref(), Some("openai"));
+        assert_eq!(provider.get_active_model_name(), "openai-model");
+        assert_eq!(provider.active_provider_name(), "openai");
+
+        let events = provider.take_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].from_provider, "anthropic");
+        assert_eq!(events[0].to_model, "openai-model");
+        assert!(events[0].reason.contains("overloaded"));
+        assert!(provider.take_events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_structured_output_fails_over() {
+        let primary = Arc::new(MockProvider::new("anthropic", Some(server_error)));
+        let mut unsupported = MockProvider::new("ollama", None);
+        unsupported.structured = false;
+        let unsupported = Arc::new(unsupported);
+        let secondary = Arc::new(MockProvider::new("openai", None));
+        let provider = fallback(vec![primary.clone(), unsupported.clone(), secondary], 3);
+
+        let (_message, usage) = provider
+            .complete_structured("system", &[], &serde_json::json!({"type": "object"}))
+            .await
+            .unwrap();
+        assert_eq!(usage.provider.as_deref(), Some("openai"));
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(unsupported.calls.load(Ordering::SeqCst), 0);
+        assert_eq!(provider.active_provider_name(), "openai");
+    }
+
+    #[tokio::test]
+    async fn test_stream_fails_over() {
+        let primary = Arc::new(MockProvider::new("anthropic", Some(server_error)));
+        let secondary = Arc::new(MockProvider::new("openai", None));
+        let provider = fallback(vec![primary, secondary], 3);
+
+        let items: Vec<_> = provider
+            .stream("system", &[], &[])
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(items.len(), 1);
+        let (_message, usage) = items[0].as_ref().unwrap();
+        let usage = usage.as_ref().unwrap();
+        assert_eq!(usage.model, "openai");
+        assert_eq!(usage.provider.as_deref(), Some("openai"));
+        assert_eq!(provider.take_events().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_opens_after_threshold() {
+        let primary = Arc::new(MockProvider::new("anthropic", Some(server_error)));
+        let secondary = Arc::new(MockProvider::new("openai", None));
+        let provider = fallback(vec![primary.clone(), secondary.clone()], 2);
+
+        for _ in 0..4 {
+            provider.complete("system", &[], &[]).await.unwrap();
+        }
+        // The primary is skipped once its circuit opened after two failures
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(secondary.calls.load(Ordering::SeqCst), 4);
+        assert_eq!(provider.take_events().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_fail_over_on_request_errors() {
+        let primary = Arc::new(MockProvider::new("anthropic", Some(context_error)));
+        let secondary = Arc::new(MockProvider::new("openai", None));
+        let provider = fallback(vec![primary, secondary.clone()], 3);
+
+        let result = provider.complete("system", &[], &[]).await;
+        assert!(matches!(
+            result,
+            Err(ProviderError::ContextLengthExceeded(_))
+        ));
+        assert_eq!(secondary.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_returns_last_error_when_all_fail() {
+        let primary = Arc::new(MockProvider::new("anthropic", Some(server_error)));
+        let secondary = Arc::new(MockProvider::new("openai", Some(server_error)));
+        let provider = fallback(vec![primary, secondary], 3);
+
+        let result = provider.complete("system", &[], &[]).await;
+        assert!(matches!(result, Err(ProviderError::ServerError(_))));
+        assert!(provider.take_events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_translates_conversation_on_switch() {
+        let primary = Arc::new(MockProvider::new("anthropic", Some(server_error)));
+        let secondary = Arc::new(MockProvider {
+            images: false,
+            ..MockProvider::new("ollama", None)
+        });
+        let provider = fallback(vec![primary, secondary.clone()], 3);
+
+        let messages = vec![
+            Message::user()
+                .with_text("what is this?")
+                .with_image("abc", "image/png"),
+            Message::assistant()
+                .with_thinking("looking", "sig")
+                .with_text("a cat"),
+            Message::assistant().with_redacted_thinking("secret"),
+            Message::user().with_text("thanks"),
+        ];
+        provider.complete("system", &messages, &[]).await.unwrap();
+
+        let sent = secondary.last_messages.lock().unwrap().clone();
+        assert_eq!(sent.len(), 3);
+        assert_eq!(sent[0].content[1], MessageContent::text(IMAGE_PLACEHOLDER));
+        assert_eq!(sent[1].content, vec![MessageContent::text("a cat")]);
+
+        // Thinking produced by the fallback itself is kept on later turns
+        let mut messages = messages;
+        messages.push(Message::assistant().with_thinking("mine", "sig2"));
+        provider.complete("system", &messages, &[]).await.unwrap();
+        let sent = secondary.last_messages.lock().unwrap().clone();
+        assert_eq!(
+            sent.last().unwrap().content,
+            vec![MessageContent::thinking("mine", "sig2")]
+        );
+    }
+}
@@ -2,6 +2,7 @@ use crate::conversation::message::{Message, MessageContent};
 use crate::model::ModelConfig;
 use crate::providers::base::Usage;
 use crate::providers::errors::ProviderError;
+use crate::providers::structured_output::STRUCTURED_OUTPUT_NAME;
 use anyhow::{anyhow, Result};
 use mcp_core::ToolCall;
 use rmcp::model::{ErrorCode, ErrorData, Role, Tool};
@@ -109,7 +110,16 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
                         DATA_FIELD: redacted.data
                     }));
                 }
-                MessageContent::Image(_) => continue, // Anthropic doesn't support image content yet
+                MessageContent::Image(image) => {
+                    content.push(json!({
+                        TYPE_FIELD: "image",
+                        "source": {
+                            TYPE_FIELD: "base64",
+                            "media_type": image.mime_type,
+                            DATA_FIELD: image.data,
+                        }
+                    }));
+                }
                 MessageContent::FrontendToolRequest(tool_request) => {
                     if let Ok(tool_call) = &tool_request.tool_call {
                         content.push(json!({
@@ -302,6 +312,10 @@ pub fn get_usage(data: &Value) -> Result<Usage> {
             Some(total_input_i32),
             Some(output_tokens_i32),
             Some(total_tokens_i32),
+        )
+        .with_cache_tokens(
+            Some(cache_read_tokens.min(i32::MAX as u64) as i32),
+            Some(cache_creation_tokens.min(i32::MAX as u64) as i32),
         ))
     } else if data.as_object().is_some() {
         // Check if the data itself is the usage object (for message_delta events that might have usage at top level)
@@ -345,6 +359,10 @@ pub fn get_usage(data: &Value) -> Result<Usage> {
                 Some(total_input_i32),
                 Some(output_tokens_i32),
                 Some(total_tokens_i32),
+            )
+            .with_cache_tokens(
+                Some(cache_read_tokens.min(i32::MAX as u64) as i32),
+                Some(cache_creation_tokens.min(i32::MAX as u64) as i32),
             ))
         } else {
             tracing::debug!("🔍 Anthropic no token data found in object");
@@ -401,10 +419,11 @@ pub fn create_request(
             .insert("tools".to_string(), json!(tool_specs));
     }
 
-    // Add temperature if specified and not using extended thinking model
+    let thinking_budget = thinking_budget(model_config);
+
+    // Temperature can't be changed with extended thinking
     if let Some(temp) = model_config.temperature {
-        // Claude 3.7 models with thinking enabled don't support temperature
-        if !model_config.model_name.starts_with("claude-3-7-sonnet-") {
+        if thinking_budget.is_none() {
             payload
                 .as_object_mut()
                 .unwrap()
@@ -412,19 +431,12 @@ pub fn create_request(
         }
     }
 
-    // Add thinking parameters for claude-3-7-sonnet model
-    let is_thinking_enabled = std::env::var("CLAUDE_THINKING_ENABLED").is_ok();
-    if model_config.model_name.starts_with("claude-3-7-sonnet-") && is_thinking_enabled {
-        // Minimum budget_tokens is 1024
-        let budget_tokens = std::env::var("CLAUDE_THINKING_BUDGET")
-            .unwrap_or_else(|_| "16000".to_string())
-            .parse()
-            .unwrap_or(16000);
-
-        payload
-            .as_object_mut()
-            .unwrap()
-            .insert("max_tokens".to_string(), json!(max_tokens + budget_tokens));
+    if let Some(budget_tokens) = thinking_budget {
+        // The thinking budget counts towards max_tokens
+        payload.as_object_mut().unwrap().insert(
+            "max_tokens".to_string(),
+            json!(max_tokens + budget_tokens as i32),
+        );
 
         payload.as_object_mut().unwrap().insert(
             "thinking".to_string(),
@@ -438,6 +450,64 @@ pub fn create_request(
     Ok(payload)
 }
 
+/// Fields of a messages request that the count_tokens endpoint accepts
+const COUNT_TOKENS_FIELDS: [&str; 6] = [
+    "model",
+    "messages",
+    "system",
+    "tools",
+    "tool_choice",
+    "thinking",
+];
+
+/// The body for the count_tokens endpoint, from a messages request `payload`
+pub fn count_tokens_request(payload: &Value) -> Value {
+    let mut request = payload.clone();
+    if let Some(request) = request.as_object_mut() {
+        request.retain(|key, _| COUNT_TOKENS_FIELDS.contains(&key.as_str()));
+    }
+    request
+}
+
+/// Makes the model answer with a single call to a tool whose input is `schema`, which is how
+/// Anthropic models produce structured output. Extended thinking can't be combined with a
+/// forced tool choice, so it is turned off.
+pub fn with_forced_tool_output(payload: &mut Value, schema: &Value) {
+    let payload = payload.as_object_mut().unwrap();
+    payload.remove("thinking");
+    payload.insert(
+        "tools".to_string(),
+        json!([{
+            "name": STRUCTURED_OUTPUT_NAME,
+            "description": "Respond with the final output, matching the input schema",
+            "input_schema": schema,
+        }]),
+    );
+    payload.insert(
+        "tool_choice".to_string(),
+        json!({"type": "tool", "name": STRUCTURED_OUTPUT_NAME}),
+    );
+}
+
+/// Whether the model supports extended thinking
+pub fn supports_extended_thinking(model_name: &str) -> bool {
+    const THINKING_MODELS: [&str; 4] = [
+        "claude-3-7-sonnet",
+        "claude-sonnet-4",
+        "claude-opus-4",
+        "claude-4",
+    ];
+    THINKING_MODELS.iter().any(|m| model_name.contains(m))
+}
+
+/// The thinking budget to request, if reasoning is configured and the model supports it
+pub fn thinking_budget(model_config: &ModelConfig) -> Option<u32> {
+    if !supports_extended_thinking(&model_config.model_name) {
+        return None;
+    }
+    model_config.thinking_budget()
+}
+
 /// Process streaming response from Anthropic's API
 pub fn response_to_streaming_message<S>(
     mut stream: S,
@@ -466,6 +536,8 @@ where
         let mut accumulated_text = String::new();
         let mut accumulated_tool_calls: std::collections::HashMap<String, (String, String)> = std::collections::HashMap::new();
         let mut current_tool_id: Option<String> = None;
+        // Thinking and signature of the thinking block being streamed
+        let mut current_thinking: Option<(String, String)> = None;
         let mut final_usage: Option<crate::providers::base::ProviderUsage> = None;
         let mut message_id: Option<String> = None;
 
@@ -520,7 +592,19 @@ where
                 "content_block_start" => {
                     // A new content block started
                     if let Some(content_block) = event.data.get("content_block") {
-                        if content_block.get("type") == Some(&json!("tool_use")) {
+                        if content_block.get("type") == Some(&json!(THINKING_TYPE)) {
+                            current_thinking = Some((String::new(), String::new()));
+                        } else if content_block.get("type") == Some(&json!(REDACTED_THINKING_TYPE)) {
+                            if let Some(data) = content_block.get(DATA_FIELD).and_then(|v| v.as_str()) {
+                                let mut message = Message::new(
+                                    Role::Assistant,
+                                    chrono::Utc::now().timestamp(),
+                                    vec![MessageContent::redacted_thinking(data)],
+                                );
+                                message.id = message_id.clone();
+                                yield (Some(message), None);
+                            }
+                        } else if content_block.get("type") == Some(&json!("tool_use")) {
                             if let Some(id) = content_block.get("id").and_then(|v| v.as_str()) {
                                 current_tool_id = Some(id.to_string());
                                 if let Some(name) = content_block.get("name").and_then(|v| v.as_str()) {
@@ -547,6 +631,14 @@ where
                                 message.id = message_id.clone();
                                 yield (Some(message), None);
                             }
+                        } else if delta.get("type") == Some(&json!("thinking_delta")) {
+                            if let (Some((thinking, _)), Some(text)) = (current_thinking.as_mut(), delta.get(THINKING_TYPE).and_then(|v| v.as_str())) {
+                                thinking.push_str(text);
+                            }
+                        } else if delta.get("type") == Some(&json!("signature_delta")) {
+                            if let (Some((_, signature)), Some(text)) = (current_thinking.as_mut(), delta.get(SIGNATURE_FIELD).and_then(|v| v.as_str())) {
+                                signature.push_str(text);
+                            }
                         } else if delta.get("type") == Some(&json!("input_json_delta")) {
                             // Tool input delta
                             if let Some(tool_id) = &current_tool_id {
@@ -562,6 +654,17 @@ where
                 }
                 "content_block_stop" => {
                     // Content block finished
+                    if let Some((thinking, signature)) = current_thinking.take() {
+                        // Thinking is yielded whole, as it is sent back with its signature
+                        let mut message = Message::new(
+                            Role::Assistant,
+                            chrono::Utc::now().timestamp(),
+                            vec![MessageContent::thinking(thinking, signature)],
+                        );
+                        message.id = message_id.clone();
+                        yield (Some(message), None);
+                        continue;
+                    }
                     if let Some(tool_id) = current_tool_id.take() {
                         // Tool call finished, yield complete tool call
                         if let Some((name, args)) = accumulated_tool_calls.remove(&tool_id) {
@@ -622,7 +725,11 @@ where
                                 (None, None) => None,
                             };
 
-                            let merged_usage = crate::providers::base::Usage::new(merged_input, merged_output, merged_total);
+                            let merged_usage = crate::providers::base::Usage::new(merged_input, merged_output, merged_total)
+                                .with_cache_tokens(
+                                    existing_usage.usage.cache_read_input_tokens.or(delta_usage.cache_read_input_tokens),
+                                    existing_usage.usage.cache_write_input_tokens.or(delta_usage.cache_write_input_tokens),
+                                );
                             final_usage = Some(crate::providers::base::ProviderUsage::new(existing_usage.model.clone(), merged_usage));
                             tracing::debug!("🔍 Anthropic MERGED usage: input_tokens={:?}, output_tokens={:?}, total_tokens={:?}",
                                     merged_input, merged_output, merged_total);
@@ -679,6 +786,7 @@ where
 mod tests {
     use super::*;
     use crate::conversation::message::Message;
+    use crate::model::{ReasoningConfig, ReasoningEffort};
     use rmcp::object;
     use serde_json::json;
 
@@ -856,6 +964,21 @@ mod tests {
         assert_eq!(spec[2]["content"][0]["text"], "How are you?");
     }
 
+    #[test]
+    fn test_image_to_anthropic_spec() {
+        let messages = vec![Message::user()
+            .with_text("What is in this picture?")
+            .with_image("aGVsbG8=", "image/png")];
+
+        let spec = format_messages(&messages);
+
+        assert_eq!(spec[0]["content"][0]["type"], "text");
+        assert_eq!(spec[0]["content"][1]["type"], "image");
+        assert_eq!(spec[0]["content"][1]["source"]["type"], "base64");
+        assert_eq!(spec[0]["content"][1]["source"]["media_type"], "image/png");
+        assert_eq!(spec[0]["content"][1]["source"]["data"], "aGVsbG8=");
+    }
+
     #[test]
     fn test_tools_to_anthropic_spec() {
         let tools = vec![
@@ -914,41 +1037,93 @@ mod tests {
 
     #[test]
     fn test_create_request_with_thinking() -> Result<()> {
-        let original_value = std::env::var("CLAUDE_THINKING_ENABLED").ok();
-        std::env::set_var("CLAUDE_THINKING_ENABLED", "true");
+        let model_config = ModelConfig::new_or_fail("claude-sonnet-4-20250514")
+            .with_temperature(Some(0.5))
+            .with_reasoning(Some(ReasoningConfig::from_effort(ReasoningEffort::Low)));
+        let system = "You are a helpful assistant.";
+        let messages = vec![Message::user().with_text("Hello")];
 
-        let result = (|| {
-            let model_config = ModelConfig::new_or_fail("claude-3-7-sonnet-20250219");
-            let system = "You are a helpful assistant.";
-            let messages = vec![Message::user().with_text("Hello")];
-            let tools = vec![];
+        let payload = create_request(&model_config, system, &messages, &[])?;
 
-            let payload = create_request(&model_config, system, &messages, &tools)?;
+        // Verify basic structure
+        assert_eq!(payload["model"], "claude-sonnet-4-20250514");
+        assert_eq!(payload["messages"][0]["role"], "user");
+        assert_eq!(payload["messages"][0]["content"][0]["text"], "Hello");
 
-            // Verify basic structure
-            assert_eq!(payload["model"], "claude-3-7-sonnet-20250219");
-            assert_eq!(payload["messages"][0]["role"], "user");
-            assert_eq!(payload["messages"][0]["content"][0]["text"], "Hello");
+        // Verify thinking parameters, with the budget on top of the output tokens
+        assert_eq!(payload["thinking"]["type"], "enabled");
+        assert_eq!(payload["thinking"]["budget_tokens"], 4096);
+        assert_eq!(payload["max_tokens"], 8192 + 4096);
 
-            // Verify thinking parameters
-            assert!(payload.get("thinking").is_some());
-            assert_eq!(payload["thinking"]["type"], "enabled");
-            assert!(payload["thinking"]["budget_tokens"].as_i64().unwrap() >= 1024);
+        // Temperature can't be set together with thinking
+        assert!(payload.get("temperature").is_none());
 
-            // Temperature should not be present for 3.7 models with thinking
-            assert!(payload.get("temperature").is_none());
+        // Models without extended thinking ignore the setting
+        let model_config = ModelConfig::new_or_fail("claude-3-5-haiku-latest")
+            .with_reasoning(Some(ReasoningConfig::from_effort(ReasoningEffort::Low)));
+        let payload = create_request(&model_config, system, &messages, &[])?;
+        assert!(payload.get("thinking").is_none());
+
+        Ok(())
+    }
 
-            Ok(())
-        })();
+    #[test]
+    fn test_count_tokens_request() -> Result<()> {
+        let model_config = ModelConfig::new_or_fail("claude-sonnet-4-20250514")
+            .with_temperature(Some(0.5))
+            .with_max_tokens(Some(1024));
+        let messages = vec![Message::user().with_text("Hello")];
+        let payload = create_request(&model_config, "Be brief.", &messages, &[])?;
+
+        let request = count_tokens_request(&payload);
+
+        assert_eq!(request["model"], "claude-sonnet-4-20250514");
+        assert_eq!(request["messages"], payload["messages"]);
+        assert_eq!(request["system"], payload["system"]);
+        // The endpoint rejects generation settings
+        assert!(request.get("max_tokens").is_none());
+        assert!(request.get("temperature").is_none());
+        Ok(())
+    }
 
-        // Restore the original env var state
-        match original_value {
-            Some(val) => std::env::set_var("CLAUDE_THINKING_ENABLED", val),
-            None => std::env::remove_var("CLAUDE_THINKING_ENABLED"),
+    #[tokio::test]
+    async fn test_streaming_thinking() -> Result<()> {
+        use futures::StreamExt;
+
+        let events = [
+            json!({"type": "message_start", "message": {"id": "msg_1", "model": "claude-sonnet-4", "usage": {"input_tokens": 10, "output_tokens": 1}}}),
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "Let me "}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "think."}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "sig"}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "content_block_start", "index": 1, "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "Hi"}}),
+            json!({"type": "content_block_stop", "index": 1}),
+            json!({"type": "message_stop"}),
+        ];
+        let lines: Vec<Result<String>> = events
+            .iter()
+            .map(|event| Ok(format!("data: {}", event)))
+            .collect();
+
+        let messages: Vec<Message> = response_to_streaming_message(futures::stream::iter(lines))
+            .filter_map(|item| async move { item.ok().and_then(|(message, _)| message) })
+            .collect()
+            .await;
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].id.as_deref(), Some("msg_1"));
+        match &messages[0].content[0] {
+            MessageContent::Thinking(thinking) => {
+                assert_eq!(thinking.thinking, "Let me think.");
+                assert_eq!(thinking.signature, "sig");
+            }
+            other => panic!("Expected thinking, got {:?}", other),
         }
+        assert_eq!(messages[1].as_concat_text(), "Hi");
 
-        // Return the test result
-        result
+        Ok(())
     }
 
     #[test]
@@ -980,6 +1155,8 @@ mod tests {
         assert_eq!(usage.input_tokens, Some(15007));
         assert_eq!(usage.output_tokens, Some(50));
         assert_eq!(usage.total_tokens, Some(15057)); // 15007 + 50
+        assert_eq!(usage.cache_read_input_tokens, Some(5000));
+        assert_eq!(usage.cache_write_input_tokens, Some(10000));
 
         Ok(())
     }
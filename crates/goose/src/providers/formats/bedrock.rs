@@ -1,5 +1,5 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
 use anyhow::{anyhow, bail, Result};
@@ -12,7 +12,9 @@ use rmcp::model::{Content, ErrorCode, ErrorData, RawContent, ResourceContents, R
 use serde_json::Value;
 
 use super::super::base::Usage;
+use super::anthropic::thinking_budget;
 use crate::conversation::message::{Message, MessageContent};
+use crate::model::ModelConfig;
 
 pub fn to_bedrock_message(message: &Message) -> Result<bedrock::Message> {
     bedrock::Message::builder()
@@ -37,14 +39,25 @@ pub fn to_bedrock_message_content(content: &MessageContent) -> Result<bedrock::C
         MessageContent::Image(image) => {
             bedrock::ContentBlock::Image(to_bedrock_image(&image.data, &image.mime_type)?)
         }
-        MessageContent::Thinking(_) => {
-            // Thinking blocks are not supported in Bedrock - skip
-            bedrock::ContentBlock::Text("".to_string())
+        MessageContent::Thinking(thinking) if !thinking.signature.is_empty() => {
+            bedrock::ContentBlock::ReasoningContent(bedrock::ReasoningContentBlock::ReasoningText(
+                bedrock::ReasoningTextBlock::builder()
+                    .text(thinking.thinking.clone())
+                    .signature(thinking.signature.clone())
+                    .build()?,
+            ))
         }
-        MessageContent::RedactedThinking(_) => {
-            // Redacted thinking blocks are not supported in Bedrock - skip
+        MessageContent::Thinking(_) => {
+            // Thinking from other providers can't be verified by Bedrock - skip
             bedrock::ContentBlock::Text("".to_string())
         }
+        MessageContent::RedactedThinking(redacted) => bedrock::ContentBlock::ReasoningContent(
+            bedrock::ReasoningContentBlock::RedactedContent(aws_smithy_types::Blob::new(
+                base64::prelude::BASE64_STANDARD
+                    .decode(&redacted.data)
+                    .map_err(|e| anyhow!("Failed to decode redacted thinking: {}", e))?,
+            )),
+        ),
         MessageContent::ContextLengthExceeded(_) => {
             bail!("ContextLengthExceeded should not get passed to the provider")
         }
@@ -184,6 +197,87 @@ pub fn to_bedrock_tool_config(tools: &[Tool]) -> Result<bedrock::ToolConfigurati
         .build()?)
 }
 
+/// Whether the model accepts cache points in Converse requests
+pub fn supports_cache_points(model_id: &str) -> bool {
+    const WITHOUT_CACHE_POINTS: [&str; 6] = [
+        "claude-instant",
+        "claude-v2",
+        "claude-3-haiku",
+        "claude-3-sonnet",
+        "claude-3-opus",
+        "claude-3-5-sonnet",
+    ];
+    (model_id.contains("anthropic.claude")
+        && !WITHOUT_CACHE_POINTS.iter().any(|m| model_id.contains(m)))
+        || model_id.contains("amazon.nova")
+}
+
+fn cache_point() -> Result<bedrock::CachePointBlock> {
+    Ok(bedrock::CachePointBlock::builder()
+        .r#type(bedrock::CachePointType::Default)
+        .build()?)
+}
+
+/// The system prompt, messages and tool configuration of a Converse request.
+///
+/// With `cache_points`, a cache point follows the system prompt, the tools and the last
+/// two user messages (Bedrock allows four per request), so that each turn reads the
+/// prefix cached by the previous one and caches its own.
+pub fn to_bedrock_request(
+    system: &str,
+    messages: &[Message],
+    tools: &[Tool],
+    cache_points: bool,
+) -> Result<(
+    Vec<bedrock::SystemContentBlock>,
+    Vec<bedrock::Message>,
+    Option<bedrock::ToolConfiguration>,
+)> {
+    let mut system_blocks = vec![bedrock::SystemContentBlock::Text(system.to_string())];
+    let mut bedrock_messages = messages
+        .iter()
+        .map(to_bedrock_message)
+        .collect::<Result<Vec<_>>>()?;
+    let tool_config = if tools.is_empty() {
+        None
+    } else {
+        Some(to_bedrock_tool_config(tools)?)
+    };
+
+    if !cache_points {
+        return Ok((system_blocks, bedrock_messages, tool_config));
+    }
+
+    system_blocks.push(bedrock::SystemContentBlock::CachePoint(cache_point()?));
+
+    let tool_config = match tool_config {
+        Some(config) => {
+            let mut tools = config.tools;
+            tools.push(bedrock::Tool::CachePoint(cache_point()?));
+            Some(
+                bedrock::ToolConfiguration::builder()
+                    .set_tools(Some(tools))
+                    .set_tool_choice(config.tool_choice)
+                    .build()?,
+            )
+        }
+        None => None,
+    };
+
+    for message in bedrock_messages
+        .iter_mut()
+        .rev()
+        .filter(|m| m.role == bedrock::ConversationRole::User)
+        .take(2)
+    {
+        message
+            .content
+            .push(bedrock::ContentBlock::CachePoint(cache_point()?));
+    }
+
+    Ok((system_blocks, bedrock_messages, tool_config))
+}
+
 pub fn to_bedrock_tool(tool: &Tool) -> Result<bedrock::Tool> {
     Ok(bedrock::Tool::ToolSpec(
         bedrock::ToolSpecification::builder()
@@ -303,6 +397,14 @@ pub fn from_bedrock_content_block(block: &bedrock::ContentBlock) -> Result<Messa
                     .collect::<ToolResult<Vec<_>>>()
             },
         ),
+        bedrock::ContentBlock::ReasoningContent(bedrock::ReasoningContentBlock::ReasoningText(
+            reasoning,
+        )) => MessageContent::thinking(reasoning.text(), reasoning.signature().unwrap_or_default()),
+        bedrock::ContentBlock::ReasoningContent(
+            bedrock::ReasoningContentBlock::RedactedContent(data),
+        ) => MessageContent::redacted_thinking(
+            base64::prelude::BASE64_STANDARD.encode(data.as_ref()),
+        ),
         _ => bail!("Unsupported content block type from Bedrock"),
     })
 }
@@ -331,10 +433,173 @@ pub fn from_bedrock_role(role: &bedrock::ConversationRole) -> Result<Role> {
 }
 
 pub fn from_bedrock_usage(usage: &bedrock::TokenUsage) -> Usage {
-    Usage {
-        input_tokens: Some(usage.input_tokens),
-        output_tokens: Some(usage.output_tokens),
-        total_tokens: Some(usage.total_tokens),
+    let cache_read = usage.cache_read_input_tokens;
+    let cache_write = usage.cache_write_input_tokens;
+    // Bedrock leaves cached tokens out of the input count; include them like other providers
+    let input_tokens =
+        usage.input_tokens + cache_read.unwrap_or_default() + cache_write.unwrap_or_default();
+    Usage::new(
+        Some(input_tokens),
+        Some(usage.output_tokens),
+        Some(usage.total_tokens),
+    )
+    .with_cache_tokens(cache_read, cache_write)
+}
+
+/// Additional request fields and inference configuration enabling extended thinking, for
+/// Claude models when reasoning is configured
+pub fn to_bedrock_thinking(
+    model_config: &ModelConfig,
+) -> Result<Option<(Document, bedrock::InferenceConfiguration)>> {
+    if !model_config.model_name.contains("anthropic.claude") {
+        return Ok(None);
+    }
+    let Some(budget_tokens) = thinking_budget(model_config) else {
+        return Ok(None);
+    };
+
+    let fields = to_bedrock_json(&serde_json::json!({
+        "thinking": {"type": "enabled", "budget_tokens": budget_tokens}
+    }));
+    // The thinking budget counts towards the maximum output tokens
+    let inference_config = bedrock::InferenceConfiguration::builder()
+        .max_tokens(model_config.max_tokens.unwrap_or(8192) + budget_tokens as i32)
+        .build();
+    Ok(Some((fields, inference_config)))
+}
+
+/// Accumulates the events of a `ConverseStream` response into messages.
+///
+/// Text deltas become messages right away. Tool use input arrives as deltas of its JSON
+/// encoding, so the tool requests are only returned once the message stops. Reasoning is
+/// returned whole when its content block stops, as it is sent back with its signature.
+#[derive(Debug)]
+pub struct BedrockStreamState {
+    message_id: String,
+    tool_uses: BTreeMap<i32, (String, String, String)>,
+    reasoning: BTreeMap<i32, (String, String)>,
+    usage: Option<Usage>,
+}
+
+impl Default for BedrockStreamState {
+    fn default() -> Self {
+        Self {
+            message_id: format!("msg_{}", uuid::Uuid::new_v4()),
+            tool_uses: BTreeMap::new(),
+            reasoning: BTreeMap::new(),
+            usage: None,
+        }
+    }
+}
+
+impl BedrockStreamState {
+    /// Process the next event, returning a message if the event completes one
+    pub fn process_event(
+        &mut self,
+        event: &bedrock::ConverseStreamOutput,
+    ) -> Result<Option<Message>> {
+        let content = match event {
+            bedrock::ConverseStreamOutput::ContentBlockStart(start) => {
+                if let Some(bedrock::ContentBlockStart::ToolUse(tool_use)) = &start.start {
+                    self.tool_uses.insert(
+                        start.content_block_index,
+                        (
+                            tool_use.tool_use_id.clone(),
+                            tool_use.name.clone(),
+                            String::new(),
+                        ),
+                    );
+                }
+                Vec::new()
+            }
+            bedrock::ConverseStreamOutput::ContentBlockDelta(delta) => match &delta.delta {
+                Some(bedrock::ContentBlockDelta::Text(text)) if !text.is_empty() => {
+                    vec![MessageContent::text(text)]
+                }
+                Some(bedrock::ContentBlockDelta::ToolUse(tool_use)) => {
+                    let (_, _, input) = self
+                        .tool_uses
+                        .get_mut(&delta.content_block_index)
+                        .ok_or_else(|| {
+                            anyhow!("Tool use input for a content block that was not started")
+                        })?;
+                    input.push_str(&tool_use.input);
+                    Vec::new()
+                }
+                Some(bedrock::ContentBlockDelta::ReasoningContent(
+                    bedrock::ReasoningContentBlockDelta::RedactedContent(data),
+                )) => vec![MessageContent::redacted_thinking(
+                    base64::prelude::BASE64_STANDARD.encode(data.as_ref()),
+                )],
+                Some(bedrock::ContentBlockDelta::ReasoningContent(reasoning)) => {
+                    let (text, signature) =
+                        self.reasoning.entry(delta.content_block_index).or_default();
+                    match reasoning {
+                        bedrock::ReasoningContentBlockDelta::Text(delta) => text.push_str(delta),
+                        bedrock::ReasoningContentBlockDelta::Signature(delta) => {
+                            signature.push_str(delta)
+                        }
+                        _ => {}
+                    }
+                    Vec::new()
+                }
+                _ => Vec::new(),
+            },
+            bedrock::ConverseStreamOutput::ContentBlockStop(stop) => self
+                .reasoning
+                .remove(&stop.content_block_index)
+                .map(|(text, signature)| MessageContent::thinking(text, signature))
+                .into_iter()
+                .collect(),
+            bedrock::ConverseStreamOutput::MessageStop(_) => std::mem::take(&mut self.tool_uses)
+                .into_values()
+                .map(|(id, name, input)| {
+                    let arguments = if input.is_empty() {
+                        Ok(Value::Object(Default::default()))
+                    } else {
+                        serde_json::from_str::<Value>(&input)
+                    };
+                    match arguments {
+                        Ok(arguments) => {
+                            MessageContent::tool_request(id, Ok(ToolCall::new(name, arguments)))
+                        }
+                        Err(e) => MessageContent::tool_request(
+                            id.clone(),
+                            Err(ErrorData::new(
+                                ErrorCode::INVALID_PARAMS,
+                                format!(
+                                    "Could not interpret tool use parameters for id {}: {}",
+                                    id, e
+                                ),
+                                None,
+                            )),
+                        ),
+                    }
+                })
+                .collect(),
+            bedrock::ConverseStreamOutput::Metadata(metadata) => {
+                if let Some(usage) = &metadata.usage {
+                    self.usage = Some(from_bedrock_usage(usage));
+                }
+                Vec::new()
+            }
+            _ => Vec::new(),
+        };
+
+        if content.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(self.message(content)))
+    }
+
+    fn message(&self, content: Vec<MessageContent>) -> Message {
+        Message::new(Role::Assistant, Utc::now().timestamp(), content)
+            .with_id(self.message_id.clone())
+    }
+
+    /// The usage reported at the end of the stream
+    pub fn usage(&self) -> Option<Usage> {
+        self.usage
     }
 }
 
@@ -364,8 +629,10 @@ pub fn from_bedrock_json(document: &Document) -> Result<Value> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::ReasoningConfig;
     use anyhow::Result;
     use rmcp::model::{AnnotateAble, RawImageContent};
+    use serde_json::json;
 
     // Base64 encoded 1x1 PNG image for testing
     const TEST_IMAGE_BASE64: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mP8/5+hHgAHggJ/PchI7wAAAABJRU5ErkJggg==";
@@ -454,4 +721,260 @@ mod tests {
 
         Ok(())
     }
+
+    /// Build an SDK event from its JSON encoding in the `application/vnd.amazon.eventstream` body
+    fn stream_event(event: &Value) -> Result<bedrock::ConverseStreamOutput> {
+        let (kind, body) = event.as_object().unwrap().iter().next().unwrap();
+        let index = body["contentBlockIndex"].as_i64().unwrap_or_default() as i32;
+        Ok(match kind.as_str() {
+            "messageStart" => bedrock::ConverseStreamOutput::MessageStart(
+                bedrock::MessageStartEvent::builder()
+                    .role(bedrock::ConversationRole::from(
+                        body["role"].as_str().unwrap(),
+                    ))
+                    .build()?,
+            ),
+            "contentBlockStart" => bedrock::ConverseStreamOutput::ContentBlockStart(
+                bedrock::ContentBlockStartEvent::builder()
+                    .start(bedrock::ContentBlockStart::ToolUse(
+                        bedrock::ToolUseBlockStart::builder()
+                            .tool_use_id(body["start"]["toolUse"]["toolUseId"].as_str().unwrap())
+                            .name(body["start"]["toolUse"]["name"].as_str().unwrap())
+                            .build()?,
+                    ))
+                    .content_block_index(index)
+                    .build()?,
+            ),
+            "contentBlockDelta" => {
+                let reasoning = &body["delta"]["reasoningContent"];
+                let delta = match body["delta"].get("text") {
+                    Some(text) => bedrock::ContentBlockDelta::Text(text.as_str().unwrap().into()),
+                    None if reasoning.get("text").is_some() => {
+                        bedrock::ContentBlockDelta::ReasoningContent(
+                            bedrock::ReasoningContentBlockDelta::Text(
+                                reasoning["text"].as_str().unwrap().into(),
+                            ),
+                        )
+                    }
+                    None if reasoning.get("signature").is_some() => {
+                        bedrock::ContentBlockDelta::ReasoningContent(
+                            bedrock::ReasoningContentBlockDelta::Signature(
+                                reasoning["signature"].as_str().unwrap().into(),
+                            ),
+                        )
+                    }
+                    None => bedrock::ContentBlockDelta::ToolUse(
+                        bedrock::ToolUseBlockDelta::builder()
+                            .input(body["delta"]["toolUse"]["input"].as_str().unwrap())
+                            .build()?,
+                    ),
+                };
+                bedrock::ConverseStreamOutput::ContentBlockDelta(
+                    bedrock::ContentBlockDeltaEvent::builder()
+                        .delta(delta)
+                        .content_block_index(index)
+                        .build()?,
+                )
+            }
+            "contentBlockStop" => bedrock::ConverseStreamOutput::ContentBlockStop(
+                bedrock::ContentBlockStopEvent::builder()
+                    .content_block_index(index)
+                    .build()?,
+            ),
+            "messageStop" => bedrock::ConverseStreamOutput::MessageStop(
+                bedrock::MessageStopEvent::builder()
+                    .stop_reason(bedrock::StopReason::from(
+                        body["stopReason"].as_str().unwrap(),
+                    ))
+                    .build()?,
+            ),
+            "metadata" => bedrock::ConverseStreamOutput::Metadata(
+                bedrock::ConverseStreamMetadataEvent::builder()
+                    .usage(
+                        bedrock::TokenUsage::builder()
+                            .input_tokens(body["usage"]["inputTokens"].as_i64().unwrap() as i32)
+                            .output_tokens(body["usage"]["outputTokens"].as_i64().unwrap() as i32)
+                            .total_tokens(body["usage"]["totalTokens"].as_i64().unwrap() as i32)
+                            .build()?,
+                    )
+                    .build(),
+            ),
+            other => bail!("unexpected event {}", other),
+        })
+    }
+
+    #[test]
+    fn test_stream_state_accumulates_tool_use_deltas() -> Result<()> {
+        let recorded = r#"{"messageStart":{"role":"assistant"}}
+{"contentBlockDelta":{"contentBlockIndex":0,"delta":{"text":"I'll look"}}}
+{"contentBlockDelta":{"contentBlockIndex":0,"delta":{"text":" at the directory."}}}
+{"contentBlockStop":{"contentBlockIndex":0}}
+{"contentBlockStart":{"contentBlockIndex":1,"start":{"toolUse":{"toolUseId":"tooluse_kZJMlvQmRJ6eAyJE5GIl7Q","name":"developer__shell"}}}}
+{"contentBlockDelta":{"contentBlockIndex":1,"delta":{"toolUse":{"input":""}}}}
+{"contentBlockDelta":{"contentBlockIndex":1,"delta":{"toolUse":{"input":"{\"command\": \"l"}}}}
+{"contentBlockDelta":{"contentBlockIndex":1,"delta":{"toolUse":{"input":"s -la\"}"}}}}
+{"contentBlockStop":{"contentBlockIndex":1}}
+{"contentBlockStart":{"contentBlockIndex":2,"start":{"toolUse":{"toolUseId":"tooluse_3Jt0bVt5QbWbhl3lDD2o9A","name":"developer__list_windows"}}}}
+{"contentBlockStop":{"contentBlockIndex":2}}
+{"messageStop":{"stopReason":"tool_use"}}
+{"metadata":{"usage":{"inputTokens":1561,"outputTokens":87,"totalTokens":1648},"metrics":{"latencyMs":2114}}}"#;
+
+        let mut state = BedrockStreamState::default();
+        let mut messages = Vec::new();
+        for line in recorded.lines() {
+            let event = stream_event(&serde_json::from_str(line)?)?;
+            messages.extend(state.process_event(&event)?);
+        }
+
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].as_concat_text(), "I'll look");
+        assert_eq!(messages[1].as_concat_text(), " at the directory.");
+        assert_eq!(messages[0].id, messages[2].id);
+
+        let requests: Vec<_> = messages[2]
+            .content
+            .iter()
+            .filter_map(|c| c.as_tool_request())
+            .collect();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].id, "tooluse_kZJMlvQmRJ6eAyJE5GIl7Q");
+        let tool_call = requests[0].tool_call.as_ref().unwrap();
+        assert_eq!(tool_call.name, "developer__shell");
+        assert_eq!(
+            tool_call.arguments,
+            serde_json::json!({"command": "ls -la"})
+        );
+        let tool_call = requests[1].tool_call.as_ref().unwrap();
+        assert_eq!(tool_call.name, "developer__list_windows");
+        assert_eq!(tool_call.arguments, serde_json::json!({}));
+
+        let usage = state.usage().unwrap();
+        assert_eq!(usage.input_tokens, Some(1561));
+        assert_eq!(usage.output_tokens, Some(87));
+        assert_eq!(usage.total_tokens, Some(1648));
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_bedrock_request_cache_points() -> Result<()> {
+        let messages = vec![
+            Message::user().with_text("first"),
+            Message::assistant().with_text("reply"),
+            Message::user().with_text("second"),
+            Message::assistant().with_text("reply"),
+            Message::user().with_text("third"),
+        ];
+        let tools = vec![Tool::new(
+            "shell",
+            "Run a command",
+            rmcp::object!({"type": "object", "properties": {}}),
+        )];
+
+        let (system, bedrock_messages, tool_config) =
+            to_bedrock_request("system", &messages, &tools, false)?;
+        assert_eq!(system.len(), 1);
+        assert!(bedrock_messages.iter().all(|m| m.content.len() == 1));
+        assert_eq!(tool_config.unwrap().tools.len(), 1);
+
+        let (system, bedrock_messages, tool_config) =
+            to_bedrock_request("system", &messages, &tools, true)?;
+        assert!(system[1].is_cache_point());
+        assert!(tool_config.unwrap().tools[1].is_cache_point());
+        let cached: Vec<bool> = bedrock_messages
+            .iter()
+            .map(|m| m.content.last().unwrap().is_cache_point())
+            .collect();
+        assert_eq!(cached, vec![false, false, true, false, true]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_supports_cache_points() {
+        assert!(supports_cache_points(
+            "us.anthropic.claude-sonnet-4-20250514-v1:0"
+        ));
+        assert!(supports_cache_points(
+            "anthropic.claude-3-7-sonnet-20250219-v1:0"
+        ));
+        assert!(supports_cache_points("amazon.nova-pro-v1:0"));
+        assert!(!supports_cache_points(
+            "anthropic.claude-3-5-sonnet-20241022-v2:0"
+        ));
+        assert!(!supports_cache_points("meta.llama3-70b-instruct-v1:0"));
+    }
+
+    #[test]
+    fn test_from_bedrock_usage_includes_cached_tokens() -> Result<()> {
+        let usage = bedrock::TokenUsage::builder()
+            .input_tokens(10)
+            .output_tokens(20)
+            .total_tokens(1530)
+            .cache_read_input_tokens(1000)
+            .cache_write_input_tokens(500)
+            .build()?;
+
+        let usage = from_bedrock_usage(&usage);
+        assert_eq!(usage.input_tokens, Some(1510));
+        assert_eq!(usage.total_tokens, Some(1530));
+        assert_eq!(usage.cache_read_input_tokens, Some(1000));
+        assert_eq!(usage.cache_write_input_tokens, Some(500));
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_state_returns_reasoning_with_signature() -> Result<()> {
+        let mut state = BedrockStreamState::default();
+        let events = [
+            json!({"contentBlockDelta": {"contentBlockIndex": 0, "delta": {"reasoningContent": {"text": "Let me "}}}}),
+            json!({"contentBlockDelta": {"contentBlockIndex": 0, "delta": {"reasoningContent": {"text": "think."}}}}),
+            json!({"contentBlockDelta": {"contentBlockIndex": 0, "delta": {"reasoningContent": {"signature": "sig"}}}}),
+        ];
+        for event in &events {
+            assert!(state.process_event(&stream_event(event)?)?.is_none());
+        }
+
+        let message = state
+            .process_event(&stream_event(
+                &json!({"contentBlockStop": {"contentBlockIndex": 0}}),
+            )?)?
+            .unwrap();
+        let MessageContent::Thinking(thinking) = &message.content[0] else {
+            panic!("Expected thinking");
+        };
+        assert_eq!(thinking.thinking, "Let me think.");
+        assert_eq!(thinking.signature, "sig");
+
+        // Thinking is sent back as reasoning content with its signature
+        let bedrock::ContentBlock::ReasoningContent(bedrock::ReasoningContentBlock::ReasoningText(
+            reasoning,
+        )) = to_bedrock_message_content(&message.content[0])?
+        else {
+            panic!("Expected reasoning content");
+        };
+        assert_eq!(reasoning.text(), "Let me think.");
+        assert_eq!(reasoning.signature(), Some("sig"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_bedrock_thinking() -> Result<()> {
+        let reasoning = Some(ReasoningConfig::from_budget_tokens(2048));
+
+        let model_config = ModelConfig::new_or_fail("us.anthropic.claude-sonnet-4-20250514-v1:0")
+            .with_reasoning(reasoning);
+        let (fields, inference_config) = to_bedrock_thinking(&model_config)?.unwrap();
+        assert_eq!(
+            from_bedrock_json(&fields)?,
+            json!({"thinking": {"type": "enabled", "budget_tokens": 2048}})
+        );
+        assert_eq!(inference_config.max_tokens(), Some(8192 + 2048));
+
+        let model_config =
+            ModelConfig::new_or_fail("amazon.nova-pro-v1:0").with_reasoning(reasoning);
+        assert!(to_bedrock_thinking(&model_config)?.is_none());
+
+        let model_config = ModelConfig::new_or_fail("us.anthropic.claude-sonnet-4-20250514-v1:0");
+        assert!(to_bedrock_thinking(&model_config)?.is_none());
+        Ok(())
+    }
 }
@@ -1,5 +1,6 @@
 use crate::conversation::message::{Message, MessageContent};
-use crate::model::ModelConfig;
+use crate::model::{ModelConfig, ReasoningEffort};
+use crate::providers::formats::anthropic::thinking_budget;
 use crate::providers::utils::{
     convert_image, detect_image_path, is_valid_function_name, load_image_file, safely_parse_json,
     sanitize_function_name, ImageFormat,
@@ -500,27 +501,24 @@ pub fn create_request(
     let model_name = model_config.model_name.to_string();
     let is_o1 = model_name.starts_with("o1") || model_name.starts_with("goose-o1");
     let is_o3 = model_name.starts_with("o3") || model_name.starts_with("goose-o3");
-    let is_claude_sonnet =
-        model_name.contains("claude-3-7-sonnet") || model_name.contains("claude-4-sonnet"); // can be goose- or databricks-
 
     // Only extract reasoning effort for O1/O3 models
     let (model_name, reasoning_effort) = if is_o1 || is_o3 {
-        let parts: Vec<&str> = model_config.model_name.split('-').collect();
-        let last_part = parts.last().unwrap();
-
-        match *last_part {
-            "low" | "medium" | "high" => {
-                let base_name = parts[..parts.len() - 1].join("-");
-                (base_name, Some(last_part.to_string()))
-            }
-            _ => (
-                model_config.model_name.to_string(),
-                Some("medium".to_string()),
-            ),
-        }
+        let (base_name, suffix_effort) = match model_name.rsplit_once('-') {
+            Some((base_name, suffix)) => match suffix.parse::<ReasoningEffort>() {
+                Ok(effort) => (base_name.to_string(), Some(effort)),
+                Err(_) => (model_name.clone(), None),
+            },
+            None => (model_name.clone(), None),
+        };
+        let effort = model_config
+            .reasoning_effort()
+            .or(suffix_effort)
+            .unwrap_or(ReasoningEffort::Medium);
+        (base_name, Some(effort.to_string()))
     } else {
         // For non-O family models, use the model name as is and no reasoning effort
-        (model_config.model_name.to_string(), None)
+        (model_name, None)
     };
 
     let system_message = DatabricksMessage {
@@ -567,14 +565,9 @@ pub fn create_request(
             .insert("tools".to_string(), json!(tools_spec));
     }
 
-    // Add thinking parameters for Claude 3.7 Sonnet model when requested
-    let is_thinking_enabled = std::env::var("CLAUDE_THINKING_ENABLED").is_ok();
-    if is_claude_sonnet && is_thinking_enabled {
-        // Minimum budget_tokens is 1024
-        let budget_tokens = std::env::var("CLAUDE_THINKING_BUDGET")
-            .unwrap_or_else(|_| "16000".to_string())
-            .parse()
-            .unwrap_or(16000);
+    // Add thinking parameters for Claude models when requested
+    if let Some(budget_tokens) = thinking_budget(model_config) {
+        let budget_tokens = budget_tokens as i32;
 
         // For Claude models with thinking enabled, we need to add max_tokens + budget_tokens
         // Default to 8192 (Claude max output) + budget if not specified
@@ -1046,6 +1039,7 @@ mod tests {
             toolshim: false,
             toolshim_model: None,
             fast_model: None,
+            reasoning: None,
         };
         let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
         let obj = request.as_object().unwrap();
@@ -1078,6 +1072,7 @@ mod tests {
             toolshim: false,
             toolshim_model: None,
             fast_model: None,
+            reasoning: None,
         };
         let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
         let obj = request.as_object().unwrap();
@@ -1111,6 +1106,7 @@ mod tests {
             toolshim: false,
             toolshim_model: None,
             fast_model: None,
+            reasoning: None,
         };
         let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
         let obj = request.as_object().unwrap();
@@ -1,12 +1,14 @@
 use super::{anthropic, google};
 use crate::conversation::message::Message;
 use crate::model::ModelConfig;
-use crate::providers::base::Usage;
+use crate::providers::base::{ProviderUsage, Usage};
 use anyhow::{Context, Result};
+use futures::Stream;
 use rmcp::model::Tool;
 use serde_json::Value;
 
 use std::fmt;
+use std::pin::Pin;
 
 /// Sensible default values of Google Cloud Platform (GCP) locations for model deployment.
 ///
@@ -325,6 +327,31 @@ pub fn response_to_message(response: Value, request_context: RequestContext) ->
     }
 }
 
+/// Messages decoded from a streamed response, followed by the usage
+pub type DecodedMessageStream =
+    Pin<Box<dyn Stream<Item = Result<(Option<Message>, Option<ProviderUsage>)>> + Send>>;
+
+/// Converts the lines of a streamed provider response to messages.
+///
+/// # Arguments
+/// * `stream` - The lines of the response body
+/// * `request_context` - Context information about the request
+///
+/// # Returns
+/// * A stream of messages and, at its end, usage statistics
+pub fn response_to_streaming_message<S>(
+    stream: S,
+    request_context: &RequestContext,
+) -> DecodedMessageStream
+where
+    S: Stream<Item = Result<String>> + Unpin + Send + 'static,
+{
+    match request_context.provider() {
+        ModelProvider::Anthropic => Box::pin(anthropic::response_to_streaming_message(stream)),
+        ModelProvider::Google => Box::pin(google::response_to_streaming_message(stream)),
+    }
+}
+
 /// Extracts token usage information from the response data.
 ///
 /// # Arguments
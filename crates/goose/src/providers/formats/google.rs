@@ -1,8 +1,12 @@
 use crate::model::ModelConfig;
-use crate::providers::base::Usage;
+use crate::providers::base::{ProviderUsage, Usage};
 use crate::providers::errors::ProviderError;
-use crate::providers::utils::{is_valid_function_name, sanitize_function_name};
-use anyhow::Result;
+use crate::providers::utils::{
+    is_valid_function_name, sanitize_function_name, unescape_json_values,
+};
+use anyhow::{anyhow, Result};
+use async_stream::try_stream;
+use futures::Stream;
 use mcp_core::ToolCall;
 use rand::{distributions::Alphanumeric, Rng};
 use rmcp::model::{AnnotateAble, ErrorCode, ErrorData, RawContent, Role, Tool};
@@ -12,6 +16,11 @@ use crate::conversation::message::{Message, MessageContent};
 use serde_json::{json, Map, Value};
 use std::ops::Deref;
 
+/// Model families that think before answering, and take a thinking budget
+const THINKING_MODELS: [&str; 2] = ["gemini-2.5", "gemini-3"];
+
+const THOUGHT_SIGNATURE_FIELD: &str = "thoughtSignature";
+
 /// Convert internal Message format to Google's API message specification
 pub fn format_messages(messages: &[Message]) -> Vec<Value> {
     messages
@@ -29,13 +38,40 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
                 "model"
             };
             let mut parts = Vec::new();
+            // Thought signature to send back on the part it came with
+            let mut pending_signature: Option<&str> = None;
             for message_content in message.content.iter() {
                 match message_content {
                     MessageContent::Text(text) => {
                         if !text.text.is_empty() {
-                            parts.push(json!({"text": text.text}));
+                            let mut part = json!({"text": text.text});
+                            if let Some(signature) = pending_signature.take() {
+                                part[THOUGHT_SIGNATURE_FIELD] = json!(signature);
+                            }
+                            parts.push(part);
+                        }
+                    }
+                    MessageContent::Thinking(thinking) => {
+                        if thinking.thinking.is_empty() {
+                            if !thinking.signature.is_empty() {
+                                pending_signature = Some(&thinking.signature);
+                            }
+                        } else {
+                            let mut part = json!({"text": thinking.thinking, "thought": true});
+                            if !thinking.signature.is_empty() {
+                                part[THOUGHT_SIGNATURE_FIELD] = json!(thinking.signature);
+                            }
+                            parts.push(part);
                         }
                     }
+                    MessageContent::Image(image) => {
+                        parts.push(json!({
+                            "inline_data": {
+                                "mime_type": image.mime_type,
+                                "data": image.data,
+                            }
+                        }));
+                    }
                     MessageContent::ToolRequest(request) => match &request.tool_call {
                         Ok(tool_call) => {
                             let mut function_call_part = Map::new();
@@ -49,9 +85,13 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
                                 function_call_part
                                     .insert("args".to_string(), tool_call.arguments.clone());
                             }
-                            parts.push(json!({
+                            let mut part = json!({
                                 "functionCall": function_call_part
-                            }));
+                            });
+                            if let Some(signature) = pending_signature.take() {
+                                part[THOUGHT_SIGNATURE_FIELD] = json!(signature);
+                            }
+                            parts.push(part);
                         }
                         Err(e) => {
                             parts.push(json!({"text":format!("Error: {}", e)}));
@@ -242,6 +282,22 @@ pub fn response_to_message(response: Value) -> Result<Message> {
         .unwrap_or(&binding);
 
     for part in parts {
+        let signature = part.get(THOUGHT_SIGNATURE_FIELD).and_then(|v| v.as_str());
+        let is_thought = part.get("thought").and_then(|v| v.as_bool()) == Some(true);
+        if is_thought {
+            if let Some(text) = part.get("text").and_then(|v| v.as_str()) {
+                content.push(MessageContent::thinking(
+                    text,
+                    signature.unwrap_or_default(),
+                ));
+            }
+            continue;
+        }
+        if let Some(signature) = signature {
+            // Kept as thinking without text, so the signature can be sent back on this part
+            content.push(MessageContent::thinking("", signature));
+        }
+
         if let Some(text) = part.get("text").and_then(|v| v.as_str()) {
             content.push(MessageContent::text(text.to_string()));
         } else if let Some(function_call) = part.get("functionCall") {
@@ -293,7 +349,13 @@ pub fn get_usage(data: &Value) -> Result<Usage> {
             .get("totalTokenCount")
             .and_then(|v| v.as_u64())
             .map(|v| v as i32);
-        Ok(Usage::new(input_tokens, output_tokens, total_tokens))
+        // Covers both implicit caching and explicit context caches
+        let cached_tokens = usage_meta_data
+            .get("cachedContentTokenCount")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as i32);
+        Ok(Usage::new(input_tokens, output_tokens, total_tokens)
+            .with_cache_tokens(cached_tokens, None))
     } else {
         tracing::debug!(
             "Failed to get usage data: {}",
@@ -304,6 +366,105 @@ pub fn get_usage(data: &Value) -> Result<Usage> {
     }
 }
 
+/// Convert a stream of server-sent events from `streamGenerateContent?alt=sse` into messages.
+///
+/// Every event is a complete `GenerateContentResponse` holding the next parts of the
+/// candidate; Gemini sends each function call whole, so no argument deltas need joining.
+/// Usage metadata is cumulative, so only the last value is yielded, at the end of the stream.
+pub fn response_to_streaming_message<S>(
+    mut stream: S,
+) -> impl Stream<Item = Result<(Option<Message>, Option<ProviderUsage>)>> + 'static
+where
+    S: Stream<Item = Result<String>> + Unpin + Send + 'static,
+{
+    try_stream! {
+        use futures::StreamExt;
+
+        let mut usage: Option<ProviderUsage> = None;
+        // Thought summaries arrive in pieces, and are yielded whole before the next content
+        let mut thought = String::new();
+        let mut message_id: Option<String> = None;
+
+        while let Some(line) = stream.next().await {
+            let line = line?;
+            let Some(data) = line.strip_prefix("data:").map(|d| d.trim()) else {
+                continue;
+            };
+            if data.is_empty() {
+                continue;
+            }
+
+            let chunk: Value = serde_json::from_str(data)
+                .map_err(|e| anyhow!("Failed to parse streaming chunk: {}: {:?}", e, data))?;
+
+            if let Some(error) = chunk.get("error") {
+                let message = error.get("message").and_then(|m| m.as_str()).unwrap_or("unknown error");
+                Err(anyhow!("Server reported an error in the stream: {}", message))?;
+            }
+
+            if chunk.get("usageMetadata").is_some() {
+                let model = chunk
+                    .get("modelVersion")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("Unknown")
+                    .to_string();
+                usage = Some(ProviderUsage::new(model, get_usage(&chunk)?));
+            }
+
+            if let Some(id) = chunk.get("responseId").and_then(|id| id.as_str()) {
+                message_id = Some(id.to_string());
+            }
+            let mut message = response_to_message(unescape_json_values(&chunk))?;
+            message.content.retain(|c| c.as_text().is_none_or(|t| !t.is_empty()));
+            message.content.retain(|c| match c {
+                MessageContent::Thinking(t) if t.signature.is_empty() => {
+                    thought.push_str(&t.thinking);
+                    false
+                }
+                _ => true,
+            });
+            if message.content.is_empty() {
+                continue;
+            }
+            if !thought.is_empty() {
+                message
+                    .content
+                    .insert(0, MessageContent::thinking(std::mem::take(&mut thought), ""));
+            }
+            if let Some(id) = &message_id {
+                message = message.with_id(id.clone());
+            }
+            yield (Some(message), None);
+        }
+
+        if !thought.is_empty() {
+            let mut message = Message::assistant().with_thinking(thought, "");
+            message.id = message_id;
+            yield (Some(message), None);
+        }
+        if usage.is_some() {
+            yield (None, usage);
+        }
+    }
+}
+
+/// The thinking budget to request, if reasoning is configured and the model thinks.
+/// Gemini caps the budget per model, at 24k tokens for Flash and 32k for Pro.
+pub fn thinking_budget(model_config: &ModelConfig) -> Option<u32> {
+    let model_name = &model_config.model_name;
+    if !THINKING_MODELS.iter().any(|m| model_name.contains(m)) {
+        return None;
+    }
+    let max_budget = if model_name.contains("flash") {
+        24_576
+    } else {
+        32_768
+    };
+    model_config
+        .thinking_budget()
+        .map(|budget| budget.min(max_budget))
+}
+
 /// Create a complete request payload for Google's API
 pub fn create_request(
     model_config: &ModelConfig,
@@ -330,6 +491,12 @@ pub fn create_request(
     if let Some(tokens) = model_config.max_tokens {
         generation_config.insert("maxOutputTokens".to_string(), json!(tokens));
     }
+    if let Some(budget_tokens) = thinking_budget(model_config) {
+        generation_config.insert(
+            "thinkingConfig".to_string(),
+            json!({"thinkingBudget": budget_tokens, "includeThoughts": true}),
+        );
+    }
     if !generation_config.is_empty() {
         payload.insert("generationConfig".to_string(), json!(generation_config));
     }
@@ -337,10 +504,74 @@ pub fn create_request(
     Ok(json!(payload))
 }
 
+/// Constrains the response to JSON matching `schema`, keeping only the parts of the schema
+/// that Gemini's `responseSchema` understands
+pub fn with_response_schema(payload: &mut Value, schema: &Value) {
+    let response_schema = match schema.as_object() {
+        Some(map) => process_map(map, Some("properties")),
+        None => schema.clone(),
+    };
+    let generation_config = payload
+        .as_object_mut()
+        .unwrap()
+        .entry("generationConfig")
+        .or_insert_with(|| json!({}));
+    generation_config["responseMimeType"] = json!("application/json");
+    generation_config["responseSchema"] = response_schema;
+}
+
+/// The body for the countTokens endpoint, counting everything `payload` sends to `model`
+/// (the model's resource name)
+pub fn count_tokens_request(payload: &Value, model: &str) -> Value {
+    let mut request = payload.clone();
+    if let Some(request) = request.as_object_mut() {
+        request.remove("generationConfig");
+        request.insert("model".to_string(), json!(model));
+    }
+    json!({ "generateContentRequest": request })
+}
+
+/// Request fields that stay the same from one turn to the next, and can be stored in an
+/// explicit context cache
+const CONTEXT_CACHE_FIELDS: [&str; 3] = ["system_instruction", "tools", "tool_config"];
+
+/// Context caches must hold a minimum number of tokens (1024 for the smallest models), so
+/// do not try to create one for less text than that
+const MIN_CONTEXT_CACHE_CHARS: usize = 4 * 1024;
+
+/// The body of a `cachedContents` resource holding the system instruction and tools of
+/// `payload`, for `model` (the model's resource name), or None when there is too little to cache
+pub fn context_cache_content(payload: &Value, model: &str) -> Option<Value> {
+    let mut content = Map::new();
+    for field in CONTEXT_CACHE_FIELDS {
+        if let Some(value) = payload.get(field) {
+            content.insert(field.to_string(), value.clone());
+        }
+    }
+    if Value::Object(content.clone()).to_string().len() < MIN_CONTEXT_CACHE_CHARS {
+        return None;
+    }
+    content.insert("model".to_string(), json!(model));
+    Some(Value::Object(content))
+}
+
+/// `payload` with the fields stored in the context cache `cache_name` replaced by a reference to it
+pub fn with_context_cache(payload: &Value, cache_name: &str) -> Value {
+    let mut payload = payload.clone();
+    if let Some(object) = payload.as_object_mut() {
+        for field in CONTEXT_CACHE_FIELDS {
+            object.remove(field);
+        }
+        object.insert("cachedContent".to_string(), json!(cache_name));
+    }
+    payload
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::conversation::message::Message;
+    use crate::model::{ReasoningConfig, ReasoningEffort};
     use rmcp::{model::Content, object};
     use serde_json::json;
 
@@ -393,6 +624,46 @@ mod tests {
         assert_eq!(usage.input_tokens, Some(1));
         assert_eq!(usage.output_tokens, Some(2));
         assert_eq!(usage.total_tokens, Some(3));
+        assert_eq!(usage.cache_read_input_tokens, None);
+
+        let data = json!({
+            "usageMetadata": {
+                "promptTokenCount": 5000,
+                "candidatesTokenCount": 20,
+                "totalTokenCount": 5020,
+                "cachedContentTokenCount": 4096
+            }
+        });
+        let usage = get_usage(&data).unwrap();
+        assert_eq!(usage.input_tokens, Some(5000));
+        assert_eq!(usage.cache_read_input_tokens, Some(4096));
+    }
+
+    #[test]
+    fn test_context_cache_payload() {
+        let tool = Tool::new(
+            "tool",
+            "x".repeat(MIN_CONTEXT_CACHE_CHARS),
+            object!({"type": "object", "properties": {}}),
+        );
+        let messages = vec![set_up_text_message("Hello", Role::User)];
+        let model_config = ModelConfig::new_or_fail("gemini-2.5-flash");
+
+        let small = create_request(&model_config, "system", &messages, &[]).unwrap();
+        assert!(context_cache_content(&small, "models/gemini-2.5-flash").is_none());
+
+        let payload = create_request(&model_config, "system", &messages, &[tool]).unwrap();
+        let content = context_cache_content(&payload, "models/gemini-2.5-flash").unwrap();
+        assert_eq!(content["model"], "models/gemini-2.5-flash");
+        assert_eq!(content["system_instruction"], payload["system_instruction"]);
+        assert_eq!(content["tools"], payload["tools"]);
+        assert!(content.get("contents").is_none());
+
+        let cached = with_context_cache(&payload, "cachedContents/abc");
+        assert_eq!(cached["cachedContent"], "cachedContents/abc");
+        assert_eq!(cached["contents"], payload["contents"]);
+        assert!(cached.get("system_instruction").is_none());
+        assert!(cached.get("tools").is_none());
     }
 
     #[test]
@@ -409,6 +680,20 @@ mod tests {
         assert_eq!(payload[1]["parts"][0]["text"], "World");
     }
 
+    #[test]
+    fn test_message_to_google_spec_image_message() {
+        let messages = vec![Message::user()
+            .with_text("What is in this picture?")
+            .with_image("aGVsbG8=", "image/png")];
+        let payload = format_messages(&messages);
+        assert_eq!(payload[0]["parts"][0]["text"], "What is in this picture?");
+        assert_eq!(
+            payload[0]["parts"][1]["inline_data"]["mime_type"],
+            "image/png"
+        );
+        assert_eq!(payload[0]["parts"][1]["inline_data"]["data"], "aGVsbG8=");
+    }
+
     #[test]
     fn test_message_to_google_spec_tool_request_message() {
         let arguments = json!({
@@ -811,4 +1096,127 @@ mod tests {
 
         assert_eq!(payload, expected_payload);
     }
+
+    #[tokio::test]
+    async fn test_response_to_streaming_message() -> Result<()> {
+        use futures::StreamExt;
+
+        let response_lines = r#"data: {"candidates": [{"content": {"parts": [{"text": "I'll list"}],"role": "model"},"index": 0}],"usageMetadata": {"promptTokenCount": 1204,"candidatesTokenCount": 3,"totalTokenCount": 1207},"modelVersion": "gemini-2.5-flash","responseId": "u4-TaMbXJqGz1MkP3e3QkQs"}
+
+data: {"candidates": [{"content": {"parts": [{"text": " the files for you."}],"role": "model"},"index": 0}],"usageMetadata": {"promptTokenCount": 1204,"candidatesTokenCount": 8,"totalTokenCount": 1212},"modelVersion": "gemini-2.5-flash","responseId": "u4-TaMbXJqGz1MkP3e3QkQs"}
+
+data: {"candidates": [{"content": {"parts": [{"functionCall": {"name": "developer__shell","args": {"command": "ls"}}}],"role": "model"},"finishReason": "STOP","index": 0}],"usageMetadata": {"promptTokenCount": 1204,"candidatesTokenCount": 23,"totalTokenCount": 1227},"modelVersion": "gemini-2.5-flash","responseId": "u4-TaMbXJqGz1MkP3e3QkQs"}
+
+"#;
+        let lines = futures::stream::iter(response_lines.lines().map(|l| Ok(l.to_string())));
+        let items: Vec<_> = response_to_streaming_message(lines).collect().await;
+        let items = items.into_iter().collect::<Result<Vec<_>>>()?;
+        assert_eq!(items.len(), 4);
+
+        let messages: Vec<&Message> = items.iter().filter_map(|(m, _)| m.as_ref()).collect();
+        assert_eq!(messages[0].as_concat_text(), "I'll list");
+        assert_eq!(messages[1].as_concat_text(), " the files for you.");
+        assert_eq!(messages[0].id.as_deref(), Some("u4-TaMbXJqGz1MkP3e3QkQs"));
+        let MessageContent::ToolRequest(request) = &messages[2].content[0] else {
+            panic!("Expected a tool request");
+        };
+        let tool_call = request.tool_call.as_ref().unwrap();
+        assert_eq!(tool_call.name, "developer__shell");
+        assert_eq!(tool_call.arguments, json!({"command": "ls"}));
+
+        // Usage is cumulative, so only the final value is reported
+        assert!(items[..3].iter().all(|(_, usage)| usage.is_none()));
+        let usage = items[3].1.as_ref().unwrap();
+        assert_eq!(usage.model, "gemini-2.5-flash");
+        assert_eq!(usage.usage.input_tokens, Some(1204));
+        assert_eq!(usage.usage.output_tokens, Some(23));
+        assert_eq!(usage.usage.total_tokens, Some(1227));
+        Ok(())
+    }
+
+    #[test]
+    fn test_thinking_round_trip() -> Result<()> {
+        let response = json!({
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        {"text": "Listing the files first.", "thought": true},
+                        {"functionCall": {"name": "developer__shell", "args": {"command": "ls"}}, "thoughtSignature": "c2ln"}
+                    ]
+                }
+            }]
+        });
+        let message = response_to_message(response)?;
+        assert_eq!(message.content.len(), 3);
+        assert!(matches!(
+            &message.content[0],
+            MessageContent::Thinking(t) if t.thinking == "Listing the files first." && t.signature.is_empty()
+        ));
+        assert!(matches!(
+            &message.content[1],
+            MessageContent::Thinking(t) if t.thinking.is_empty() && t.signature == "c2ln"
+        ));
+
+        // The signature goes back on the function call it came with
+        let parts = &format_messages(&[message])[0]["parts"];
+        assert_eq!(
+            parts[0],
+            json!({"text": "Listing the files first.", "thought": true})
+        );
+        assert_eq!(parts[1]["functionCall"]["name"], "developer__shell");
+        assert_eq!(parts[1]["thoughtSignature"], "c2ln");
+        assert_eq!(parts.as_array().unwrap().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_request_thinking_config() -> Result<()> {
+        let reasoning = Some(ReasoningConfig::from_effort(ReasoningEffort::High));
+
+        let model_config = ModelConfig::new_or_fail("gemini-2.5-flash").with_reasoning(reasoning);
+        let payload = create_request(&model_config, "system", &[], &[])?;
+        assert_eq!(
+            payload["generationConfig"]["thinkingConfig"],
+            json!({"thinkingBudget": 24_576, "includeThoughts": true})
+        );
+
+        let model_config = ModelConfig::new_or_fail("gemini-2.5-pro").with_reasoning(reasoning);
+        let payload = create_request(&model_config, "system", &[], &[])?;
+        assert_eq!(
+            payload["generationConfig"]["thinkingConfig"]["thinkingBudget"],
+            32_000
+        );
+
+        let model_config = ModelConfig::new_or_fail("gemini-2.0-flash").with_reasoning(reasoning);
+        let payload = create_request(&model_config, "system", &[], &[])?;
+        assert!(payload.get("generationConfig").is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_streaming_thought_summary() -> Result<()> {
+        use futures::StreamExt;
+
+        let chunks = [
+            json!({"candidates": [{"content": {"role": "model", "parts": [{"text": "Thinking ", "thought": true}]}}], "responseId": "r1"}),
+            json!({"candidates": [{"content": {"role": "model", "parts": [{"text": "hard.", "thought": true}]}}], "responseId": "r1"}),
+            json!({"candidates": [{"content": {"role": "model", "parts": [{"text": "Done."}]}}], "responseId": "r1"}),
+        ];
+        let lines = futures::stream::iter(chunks.map(|c| Ok(format!("data: {}", c))));
+        let items = response_to_streaming_message(lines)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(items.len(), 1);
+        let message = items[0].0.as_ref().unwrap();
+        assert!(matches!(
+            &message.content[0],
+            MessageContent::Thinking(t) if t.thinking == "Thinking hard."
+        ));
+        assert_eq!(message.as_concat_text(), "Done.");
+        Ok(())
+    }
 }
@@ -3,5 +3,7 @@ pub mod bedrock;
 pub mod databricks;
 pub mod gcpvertexai;
 pub mod google;
+pub mod ollama;
 pub mod openai;
+pub mod openai_responses;
 pub mod snowflake;
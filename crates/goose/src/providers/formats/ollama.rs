@@ -0,0 +1,335 @@
+//! Ollama's native chat API (`/api/chat`). Unlike Ollama's OpenAI-compatible endpoint, it
+//! takes the context window (`num_ctx`) with each request. Requests are built in the OpenAI
+//! format and converted, and responses are converted to it, so that both endpoints share
+//! the OpenAI message handling.
+
+use crate::conversation::message::Message;
+use crate::model::ModelConfig;
+use crate::providers::base::ProviderUsage;
+use crate::providers::formats::openai;
+use crate::providers::utils::ImageFormat;
+use anyhow::{anyhow, Result};
+use async_stream::try_stream;
+use futures::Stream;
+use rmcp::model::Tool;
+use serde_json::{json, Map, Value};
+
+/// Create a request for `/api/chat`, asking for a context window of `num_ctx` if given
+pub fn create_request(
+    model_config: &ModelConfig,
+    system: &str,
+    messages: &[Message],
+    tools: &[Tool],
+    num_ctx: Option<usize>,
+) -> Result<Value> {
+    let payload =
+        openai::create_request(model_config, system, messages, tools, &ImageFormat::OpenAi)?;
+
+    let mut options = Map::new();
+    if let Some(num_ctx) = num_ctx {
+        options.insert("num_ctx".to_string(), json!(num_ctx));
+    }
+    if let Some(temperature) = payload.get("temperature") {
+        options.insert("temperature".to_string(), temperature.clone());
+    }
+    if let Some(max_tokens) = payload
+        .get("max_tokens")
+        .or_else(|| payload.get("max_completion_tokens"))
+    {
+        options.insert("num_predict".to_string(), max_tokens.clone());
+    }
+
+    let messages: Vec<Value> = payload["messages"]
+        .as_array()
+        .map(|messages| messages.iter().map(to_native_message).collect())
+        .unwrap_or_default();
+    let mut request = json!({
+        "model": payload["model"],
+        "messages": messages,
+        "stream": false,
+    });
+    if let Some(tools) = payload.get("tools") {
+        request["tools"] = tools.clone();
+    }
+    if !options.is_empty() {
+        request["options"] = Value::Object(options);
+    }
+    Ok(request)
+}
+
+/// Ollama takes message content as text with a separate list of base64 images, and tool
+/// call arguments as objects rather than encoded JSON
+fn to_native_message(message: &Value) -> Value {
+    let mut native = message.clone();
+    if native["role"] == "developer" {
+        native["role"] = json!("system");
+    }
+
+    if let Some(parts) = message.get("content").and_then(|c| c.as_array()) {
+        let mut texts = Vec::new();
+        let mut images = Vec::new();
+        for part in parts {
+            match part.get("type").and_then(|t| t.as_str()) {
+                Some("text") => texts.extend(part.get("text").and_then(|t| t.as_str())),
+                Some("image_url") => images.extend(
+                    part.pointer("/image_url/url")
+                        .and_then(|url| url.as_str())
+                        .and_then(|url| url.split_once(";base64,"))
+                        .map(|(_, data)| data.to_string()),
+                ),
+                _ => {}
+            }
+        }
+        native["content"] = json!(texts.join("\n"));
+        if !images.is_empty() {
+            native["images"] = json!(images);
+        }
+    }
+
+    if let Some(tool_calls) = native.get_mut("tool_calls").and_then(|c| c.as_array_mut()) {
+        for tool_call in tool_calls {
+            if let Some(arguments) = tool_call.pointer_mut("/function/arguments") {
+                if let Some(parsed) = arguments
+                    .as_str()
+                    .and_then(|arguments| serde_json::from_str::<Value>(arguments).ok())
+                {
+                    *arguments = parsed;
+                }
+            }
+        }
+    }
+    native
+}
+
+/// Convert an `/api/chat` response to an OpenAI chat completion
+pub fn response_to_openai(response: &Value) -> Value {
+    let message = &response["message"];
+    let tool_calls: Vec<Value> = message
+        .get("tool_calls")
+        .and_then(|c| c.as_array())
+        .map(|calls| calls.iter().map(to_openai_tool_call).collect())
+        .unwrap_or_default();
+
+    let mut openai_message = json!({
+        "role": "assistant",
+        "content": message.get("content").cloned().unwrap_or(json!("")),
+    });
+    if !tool_calls.is_empty() {
+        openai_message["tool_calls"] = json!(tool_calls);
+    }
+
+    json!({
+        "model": response["model"],
+        "choices": [{
+            "index": 0,
+            "message": openai_message,
+            "finish_reason": finish_reason(response),
+        }],
+        "usage": usage(response),
+    })
+}
+
+/// Ollama doesn't always give tool calls an id, but goose needs one to match the result
+fn to_openai_tool_call(tool_call: &Value) -> Value {
+    let id = tool_call
+        .get("id")
+        .and_then(|id| id.as_str())
+        .map(String::from)
+        .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple()));
+    let arguments = match tool_call.pointer("/function/arguments") {
+        Some(Value::String(arguments)) => arguments.clone(),
+        Some(arguments) => arguments.to_string(),
+        None => "{}".to_string(),
+    };
+    json!({
+        "id": id,
+        "type": "function",
+        "function": {
+            "name": tool_call.pointer("/function/name"),
+            "arguments": arguments,
+        },
+    })
+}
+
+fn finish_reason(response: &Value) -> Value {
+    response
+        .get("done_reason")
+        .cloned()
+        .unwrap_or(json!("stop"))
+}
+
+fn usage(response: &Value) -> Value {
+    let input_tokens = response.get("prompt_eval_count").and_then(|c| c.as_u64());
+    let output_tokens = response.get("eval_count").and_then(|c| c.as_u64());
+    json!({
+        "prompt_tokens": input_tokens,
+        "completion_tokens": output_tokens,
+        "total_tokens": input_tokens.zip(output_tokens).map(|(input, output)| input + output),
+    })
+}
+
+/// Convert a stream of `/api/chat` responses, one JSON object per line, into messages
+pub fn response_to_streaming_message<S>(
+    stream: S,
+) -> impl Stream<Item = Result<(Option<Message>, Option<ProviderUsage>)>> + 'static
+where
+    S: Stream<Item = Result<String>> + Unpin + Send + 'static,
+{
+    openai::response_to_streaming_message(Box::pin(to_openai_chunks(stream)))
+}
+
+fn to_openai_chunks<S>(mut stream: S) -> impl Stream<Item = Result<String>> + Send + 'static
+where
+    S: Stream<Item = Result<String>> + Unpin + Send + 'static,
+{
+    try_stream! {
+        use futures::StreamExt;
+
+        let mut tool_call_count = 0;
+        while let Some(line) = stream.next().await {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let chunk: Value = serde_json::from_str(&line)
+                .map_err(|e| anyhow!("Failed to parse streaming chunk: {}: {:?}", e, line))?;
+
+            if let Some(error) = chunk.get("error") {
+                yield format!("data: {}", json!({"error": {"message": error}}));
+                continue;
+            }
+
+            let message = &chunk["message"];
+            let mut delta = json!({});
+            if let Some(content) = message.get("content").and_then(|c| c.as_str()) {
+                delta["content"] = json!(content);
+            }
+            if let Some(tool_calls) = message.get("tool_calls").and_then(|c| c.as_array()) {
+                let tool_calls: Vec<Value> = tool_calls
+                    .iter()
+                    .map(|tool_call| {
+                        let mut tool_call = to_openai_tool_call(tool_call);
+                        tool_call["index"] = json!(tool_call_count);
+                        tool_call_count += 1;
+                        tool_call
+                    })
+                    .collect();
+                delta["tool_calls"] = json!(tool_calls);
+            }
+
+            let done = chunk.get("done").and_then(|d| d.as_bool()).unwrap_or(false);
+            let finish_reason = if done { finish_reason(&chunk) } else { Value::Null };
+            let mut openai_chunk = json!({
+                "model": chunk["model"],
+                "choices": [{
+                    "index": 0,
+                    "delta": delta,
+                    "finish_reason": finish_reason,
+                }],
+            });
+            if done {
+                openai_chunk["usage"] = usage(&chunk);
+            }
+            yield format!("data: {}", openai_chunk);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::message::MessageContent;
+    use futures::StreamExt;
+    use mcp_core::ToolCall;
+    use rmcp::object;
+
+    #[test]
+    fn test_create_request() -> Result<()> {
+        let messages = vec![
+            Message::user()
+                .with_text("What is in this image?")
+                .with_image("aGVsbG8=", "image/png"),
+            Message::assistant().with_tool_request(
+                "call_1",
+                Ok(ToolCall::new("get_weather", json!({"location": "Paris"}))),
+            ),
+        ];
+        let tools = vec![Tool::new(
+            "get_weather",
+            "Get the weather for a location",
+            object!({"type": "object"}),
+        )];
+        let model_config = ModelConfig::new_or_fail("qwen3").with_temperature(Some(0.2));
+
+        let request = create_request(&model_config, "system", &messages, &tools, Some(32_768))?;
+
+        assert_eq!(request["model"], "qwen3");
+        assert_eq!(request["stream"], false);
+        assert_eq!(request["options"]["num_ctx"], 32_768);
+        assert_eq!(request["messages"][1]["content"], "What is in this image?");
+        assert_eq!(request["messages"][1]["images"], json!(["aGVsbG8="]));
+        assert_eq!(
+            request["messages"][2]["tool_calls"][0]["function"]["arguments"],
+            json!({"location": "Paris"})
+        );
+        assert_eq!(request["tools"][0]["function"]["name"], "get_weather");
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_to_openai() -> Result<()> {
+        let response = json!({
+            "model": "qwen3",
+            "created_at": "2025-01-01T00:00:00Z",
+            "message": {
+                "role": "assistant",
+                "content": "",
+                "tool_calls": [
+                    {"function": {"name": "get_weather", "arguments": {"location": "Paris"}}}
+                ]
+            },
+            "done": true,
+            "done_reason": "stop",
+            "prompt_eval_count": 60,
+            "eval_count": 40
+        });
+
+        let response = response_to_openai(&response);
+        let message = openai::response_to_message(&response)?;
+        let usage = openai::get_usage(&response["usage"]);
+
+        let MessageContent::ToolRequest(request) = &message.content[0] else {
+            panic!("Expected a tool request, got {:?}", message.content);
+        };
+        let tool_call = request.tool_call.as_ref().unwrap();
+        assert_eq!(tool_call.name, "get_weather");
+        assert_eq!(tool_call.arguments, json!({"location": "Paris"}));
+        assert_eq!(usage.total_tokens, Some(100));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_streaming_response() -> Result<()> {
+        let lines = [
+            r#"{"model":"qwen3","message":{"role":"assistant","content":"Hel"},"done":false}"#,
+            r#"{"model":"qwen3","message":{"role":"assistant","content":"lo!"},"done":false}"#,
+            r#"{"model":"qwen3","message":{"role":"assistant","content":""},"done":true,"done_reason":"stop","prompt_eval_count":20,"eval_count":3}"#,
+        ];
+        let stream = futures::stream::iter(lines.map(|line| Ok(line.to_string())));
+
+        let items: Vec<_> = response_to_streaming_message(stream).collect().await;
+        let mut text = String::new();
+        let mut usage = None;
+        for item in items {
+            let (message, item_usage) = item?;
+            if let Some(message) = message {
+                text.push_str(&message.as_concat_text());
+            }
+            usage = item_usage.or(usage);
+        }
+
+        assert_eq!(text, "Hello!");
+        assert_eq!(usage.unwrap().usage.total_tokens, Some(23));
+        Ok(())
+    }
+}
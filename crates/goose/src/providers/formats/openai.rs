@@ -1,5 +1,5 @@
 use crate::conversation::message::{Message, MessageContent};
-use crate::model::ModelConfig;
+use crate::model::{ModelConfig, ReasoningEffort};
 use crate::providers::base::{ProviderUsage, Usage};
 use crate::providers::utils::{
     convert_image, detect_image_path, is_valid_function_name, load_image_file, safely_parse_json,
@@ -15,23 +15,25 @@ use rmcp::model::{
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::ops::Deref;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 struct DeltaToolCallFunction {
     name: Option<String>,
-    arguments: String, // chunk of encoded JSON,
+    arguments: Option<String>, // chunk of encoded JSON,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 struct DeltaToolCall {
     id: Option<String>,
+    #[serde(default)]
     function: DeltaToolCallFunction,
     index: Option<i32>,
     r#type: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 struct Delta {
     content: Option<String>,
     role: Option<String>,
@@ -40,6 +42,7 @@ struct Delta {
 
 #[derive(Serialize, Deserialize, Debug)]
 struct StreamingChoice {
+    #[serde(default)]
     delta: Delta,
     index: Option<i32>,
     finish_reason: Option<String>,
@@ -47,11 +50,14 @@ struct StreamingChoice {
 
 #[derive(Serialize, Deserialize, Debug)]
 struct StreamingChunk {
+    #[serde(default)]
     choices: Vec<StreamingChoice>,
     created: Option<i64>,
     id: Option<String>,
     usage: Option<Value>,
     model: Option<String>,
+    x_groq: Option<Value>,
+    error: Option<Value>,
 }
 
 /// Convert internal Message format to OpenAI's API message specification
@@ -203,8 +209,16 @@ pub fn format_messages(messages: &[Message], image_format: &ImageFormat) -> Vec<
                     // Skip tool confirmation requests
                 }
                 MessageContent::Image(image) => {
-                    // Handle direct image content
-                    converted["content"] = json!([convert_image(image, image_format)]);
+                    // Handle direct image content, keeping any text that came before it
+                    let image = convert_image(image, image_format);
+                    converted["content"] = match converted["content"].take() {
+                        Value::String(text) => json!([{"type": "text", "text": text}, image]),
+                        Value::Array(mut parts) => {
+                            parts.push(image);
+                            Value::Array(parts)
+                        }
+                        _ => json!([image]),
+                    };
                 }
                 MessageContent::FrontendToolRequest(request) => match &request.tool_call {
                     Ok(tool_call) => {
@@ -363,7 +377,15 @@ pub fn get_usage(usage: &Value) -> Usage {
             _ => None,
         });
 
-    Usage::new(input_tokens, output_tokens, total_tokens)
+    // OpenAI reports cache hits in the prompt token details, DeepSeek at the top level
+    let cached_tokens = usage
+        .get("prompt_tokens_details")
+        .and_then(|d| d.get("cached_tokens"))
+        .or_else(|| usage.get("prompt_cache_hit_tokens"))
+        .and_then(|v| v.as_i64())
+        .map(|v| v as i32);
+
+    Usage::new(input_tokens, output_tokens, total_tokens).with_cache_tokens(cached_tokens, None)
 }
 
 /// Validates and fixes tool schemas to ensure they have proper parameter structure.
@@ -381,7 +403,7 @@ pub fn validate_tool_schemas(tools: &mut [Value]) {
 }
 
 /// Ensures that the given JSON value follows the expected JSON Schema structure.
-fn ensure_valid_json_schema(schema: &mut Value) {
+pub fn ensure_valid_json_schema(schema: &mut Value) {
     if let Some(params_obj) = schema.as_object_mut() {
         // Check if this is meant to be an object type schema
         let is_object_type = params_obj
@@ -413,9 +435,66 @@ fn ensure_valid_json_schema(schema: &mut Value) {
 }
 
 fn strip_data_prefix(line: &str) -> Option<&str> {
-    line.strip_prefix("data: ").map(|s| s.trim())
+    line.strip_prefix("data:").map(|s| s.trim())
+}
+
+fn streaming_chunk_usage(chunk: &StreamingChunk) -> Option<ProviderUsage> {
+    // Groq reports usage under `x_groq` rather than at the top level
+    let usage = chunk.usage.as_ref().filter(|u| !u.is_null()).or_else(|| {
+        chunk
+            .x_groq
+            .as_ref()
+            .and_then(|x| x.get("usage"))
+            .filter(|u| !u.is_null())
+    })?;
+    Some(ProviderUsage::new(chunk.model.clone()?, get_usage(usage)))
 }
 
+fn streamed_tool_calls_to_message(
+    tool_calls: &BTreeMap<i32, (String, String, String)>,
+    id: Option<&String>,
+) -> Message {
+    let contents = tool_calls
+        .values()
+        .map(|(id, function_name, arguments)| {
+            let parsed = if arguments.is_empty() {
+                Ok(json!({}))
+            } else {
+                safely_parse_json(arguments)
+            };
+            match parsed {
+                Ok(params) => MessageContent::tool_request(
+                    id.clone(),
+                    Ok(ToolCall::new(function_name.clone(), params)),
+                ),
+                Err(e) => {
+                    let error = ErrorData {
+                        code: ErrorCode::INVALID_PARAMS,
+                        message: Cow::from(format!(
+                            "Could not interpret tool use parameters for id {}: {}",
+                            id, e
+                        )),
+                        data: None,
+                    };
+                    MessageContent::tool_request(id.clone(), Err(error))
+                }
+            }
+        })
+        .collect();
+
+    let msg = Message::new(Role::Assistant, chrono::Utc::now().timestamp(), contents);
+    match id {
+        Some(id) => msg.with_id(id.clone()),
+        None => msg,
+    }
+}
+
+/// Convert a stream of OpenAI-compatible server-sent event lines into messages.
+///
+/// Text deltas are yielded as they arrive. Tool call argument deltas are accumulated
+/// and yielded as one message once the choice finishes, since a tool request is only
+/// usable with its complete arguments. Usage is yielded once, at the end of the stream,
+/// with the last value reported by the server.
 pub fn response_to_streaming_message<S>(
     mut stream: S,
 ) -> impl Stream<Item = anyhow::Result<(Option<Message>, Option<ProviderUsage>)>> + 'static
@@ -425,157 +504,111 @@ where
     try_stream! {
         use futures::StreamExt;
 
-        'outer: while let Some(response) = stream.next().await {
-            if response.as_ref().is_ok_and(|s| s == "data: [DONE]") {
-                break 'outer;
-            }
-            let response_str = response?;
-            let line = strip_data_prefix(&response_str);
+        let mut tool_calls: BTreeMap<i32, (String, String, String)> = BTreeMap::new();
+        let mut message_id: Option<String> = None;
+        let mut usage: Option<ProviderUsage> = None;
 
-            if line.is_none() || line.is_some_and(|l| l.is_empty()) {
-                continue
+        while let Some(response) = stream.next().await {
+            let response_str = response?;
+            let Some(line) = strip_data_prefix(&response_str) else {
+                continue;
+            };
+            if line.is_empty() {
+                continue;
+            }
+            if line == "[DONE]" {
+                break;
             }
 
-            let chunk: StreamingChunk = serde_json::from_str(line
-                .ok_or_else(|| anyhow!("unexpected stream format"))?)
+            let chunk: StreamingChunk = serde_json::from_str(line)
                 .map_err(|e| anyhow!("Failed to parse streaming chunk: {}: {:?}", e, &line))?;
 
-            let usage = chunk.usage.as_ref().and_then(|u| {
-                chunk.model.as_ref().map(|model| {
-                    ProviderUsage {
-                        usage: get_usage(u),
-                        model: model.clone(),
-                    }
-                })
-            });
-
-            if chunk.choices.is_empty() {
-                yield (None, usage)
-            } else if let Some(tool_calls) = &chunk.choices[0].delta.tool_calls {
-                let mut tool_call_data: std::collections::HashMap<i32, (String, String, String)> = std::collections::HashMap::new();
-
-                for tool_call in tool_calls {
-                    if let (Some(index), Some(id), Some(name)) = (tool_call.index, &tool_call.id, &tool_call.function.name) {
-                        tool_call_data.insert(index, (id.clone(), name.clone(), tool_call.function.arguments.clone()));
-                    }
-                }
-
-                // Check if this chunk already has finish_reason "tool_calls"
-                let is_complete = chunk.choices[0].finish_reason == Some("tool_calls".to_string());
-
-                if !is_complete {
-                    let mut done = false;
-                    while !done {
-                        if let Some(response_chunk) = stream.next().await {
-                            if response_chunk.as_ref().is_ok_and(|s| s == "data: [DONE]") {
-                                break 'outer;
-                            }
-                            let response_str = response_chunk?;
-                            if let Some(line) = strip_data_prefix(&response_str) {
-                                let tool_chunk: StreamingChunk = serde_json::from_str(line)
-                                    .map_err(|e| anyhow!("Failed to parse streaming chunk: {}: {:?}", e, &line))?;
-
-                                if let Some(delta_tool_calls) = &tool_chunk.choices[0].delta.tool_calls {
-                                    for delta_call in delta_tool_calls {
-                                        if let Some(index) = delta_call.index {
-                                            if let Some((_, _, ref mut args)) = tool_call_data.get_mut(&index) {
-                                                args.push_str(&delta_call.function.arguments);
-                                            } else if let (Some(id), Some(name)) = (&delta_call.id, &delta_call.function.name) {
-                                                tool_call_data.insert(index, (id.clone(), name.clone(), delta_call.function.arguments.clone()));
-                                            }
-                                        }
-                                    }
-                                } else {
-                                    done = true;
-                                }
-
-                                if tool_chunk.choices[0].finish_reason == Some("tool_calls".to_string()) {
-                                    done = true;
-                                }
-                            }
-                        } else {
-                            break;
-                        }
-                    }
-                }
+            // Some servers, e.g. OpenRouter, report errors that happen mid-stream as a chunk
+            if let Some(error) = &chunk.error {
+                let message = error.get("message").and_then(|m| m.as_str()).unwrap_or("unknown error");
+                Err(anyhow!("Server reported an error in the stream: {}", message))?;
+            }
 
-                let mut contents = Vec::new();
-                let mut sorted_indices: Vec<_> = tool_call_data.keys().cloned().collect();
-                sorted_indices.sort();
+            if let Some(chunk_usage) = streaming_chunk_usage(&chunk) {
+                usage = Some(chunk_usage);
+            }
+            if chunk.id.as_ref().is_some_and(|id| !id.is_empty()) {
+                message_id = chunk.id.clone();
+            }
 
-                for index in sorted_indices {
-                    if let Some((id, function_name, arguments)) = tool_call_data.get(&index) {
-                        let parsed = if arguments.is_empty() {
-                            Ok(json!({}))
-                        } else {
-                            serde_json::from_str::<Value>(arguments)
-                        };
-
-                        let content = match parsed {
-                            Ok(params) => {
-                                MessageContent::tool_request(
-                                    id.clone(),
-                                    Ok(ToolCall::new(function_name.clone(), params)),
-                                )
-                            },
-                            Err(e) => {
-                                let error = ErrorData {
-                                    code: ErrorCode::INVALID_PARAMS,
-                                    message: Cow::from(format!(
-                                        "Could not interpret tool use parameters for id {}: {}",
-                                        id, e
-                                    )),
-                                    data: None,
-                                };
-                                MessageContent::tool_request(id.clone(), Err(error))
-                            }
-                        };
-                        contents.push(content);
+            let Some(choice) = chunk.choices.first() else {
+                continue;
+            };
+
+            if let Some(delta_tool_calls) = &choice.delta.tool_calls {
+                for (position, delta_call) in delta_tool_calls.iter().enumerate() {
+                    let index = delta_call.index.unwrap_or(position as i32);
+                    let arguments = delta_call.function.arguments.as_deref().unwrap_or_default();
+                    if let Some((_, _, args)) = tool_calls.get_mut(&index) {
+                        args.push_str(arguments);
+                    } else if let (Some(id), Some(name)) = (&delta_call.id, &delta_call.function.name) {
+                        tool_calls.insert(index, (id.clone(), name.clone(), arguments.to_string()));
                     }
                 }
-
-                let mut msg = Message::new(
-                    Role::Assistant,
-                    chrono::Utc::now().timestamp(),
-                    contents,
-                );
-
-                // Add ID if present
-                if let Some(id) = chunk.id {
-                    msg = msg.with_id(id);
-                }
-
-                yield (
-                    Some(msg),
-                    usage,
-                )
-            } else if let Some(text) = &chunk.choices[0].delta.content {
+            } else if let Some(text) = choice.delta.content.as_ref().filter(|t| !t.is_empty()) {
                 let mut msg = Message::new(
                     Role::Assistant,
                     chrono::Utc::now().timestamp(),
                     vec![MessageContent::text(text)],
                 );
-
-                // Add ID if present
-                if let Some(id) = chunk.id {
-                    msg = msg.with_id(id);
+                if let Some(id) = &message_id {
+                    msg = msg.with_id(id.clone());
                 }
+                yield (Some(msg), None);
+            }
 
-                yield (
-                    Some(msg),
-                    if chunk.choices[0].finish_reason.is_some() {
-                        usage
-                    } else {
-                        None
-                    },
-                )
-            } else if usage.is_some() {
-                yield (None, usage)
+            if choice.finish_reason.is_some() && !tool_calls.is_empty() {
+                yield (Some(streamed_tool_calls_to_message(&tool_calls, message_id.as_ref())), None);
+                tool_calls.clear();
             }
         }
+
+        if !tool_calls.is_empty() {
+            yield (Some(streamed_tool_calls_to_message(&tool_calls, message_id.as_ref())), None);
+        }
+        if usage.is_some() {
+            yield (None, usage);
+        }
     }
 }
 
+/// Whether the model belongs to the O family (or gpt-5), which take a reasoning effort
+/// and do not accept a temperature. A vendor prefix, as in `openai/o3`, is ignored.
+pub fn is_reasoning_model(model_name: &str) -> bool {
+    let model_name = model_name.rsplit('/').next().unwrap_or(model_name);
+    model_name.starts_with("o") || model_name.starts_with("gpt-5")
+}
+
+/// Split the model name to send and the reasoning effort to request.
+///
+/// Reasoning models take the effort configured in `ModelConfig::reasoning`, or an effort
+/// suffix such as `o3-high`, defaulting to medium. Other models are sent as is, without
+/// a reasoning effort.
+pub fn reasoning_model_and_effort(model_config: &ModelConfig) -> (String, Option<String>) {
+    let model_name = &model_config.model_name;
+    if !is_reasoning_model(model_name) {
+        return (model_name.to_string(), None);
+    }
+
+    let (base_name, suffix_effort) = match model_name.rsplit_once('-') {
+        Some((base_name, suffix)) => match suffix.parse::<ReasoningEffort>() {
+            Ok(effort) => (base_name.to_string(), Some(effort)),
+            Err(_) => (model_name.to_string(), None),
+        },
+        None => (model_name.to_string(), None),
+    };
+    let effort = model_config
+        .reasoning_effort()
+        .or(suffix_effort)
+        .unwrap_or(ReasoningEffort::Medium);
+    (base_name, Some(effort.to_string()))
+}
+
 pub fn create_request(
     model_config: &ModelConfig,
     system: &str,
@@ -589,28 +622,8 @@ pub fn create_request(
         ));
     }
 
-    let is_ox_model =
-        model_config.model_name.starts_with("o") || model_config.model_name.starts_with("gpt-5");
-
-    // Only extract reasoning effort for O1/O3 models
-    let (model_name, reasoning_effort) = if is_ox_model {
-        let parts: Vec<&str> = model_config.model_name.split('-').collect();
-        let last_part = parts.last().unwrap();
-
-        match *last_part {
-            "low" | "medium" | "high" => {
-                let base_name = parts[..parts.len() - 1].join("-");
-                (base_name, Some(last_part.to_string()))
-            }
-            _ => (
-                model_config.model_name.to_string(),
-                Some("medium".to_string()),
-            ),
-        }
-    } else {
-        // For non-O family models, use the model name as is and no reasoning effort
-        (model_config.model_name.to_string(), None)
-    };
+    let is_ox_model = is_reasoning_model(&model_config.model_name);
+    let (model_name, reasoning_effort) = reasoning_model_and_effort(model_config);
 
     let system_message = json!({
         "role": if is_ox_model { "developer" } else { "system" },
@@ -677,6 +690,7 @@ pub fn create_request(
 mod tests {
     use super::*;
     use crate::conversation::message::Message;
+    use crate::model::ReasoningConfig;
     use rmcp::object;
     use serde_json::json;
     use tokio::pin;
@@ -963,6 +977,20 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_format_messages_keeps_text_before_image() -> anyhow::Result<()> {
+        let message = Message::user()
+            .with_text("What is in this picture?")
+            .with_image("aGVsbG8=", "image/png");
+        let spec = format_messages(&[message], &ImageFormat::OpenAi);
+
+        let content = spec[0]["content"].as_array().unwrap();
+        assert_eq!(content.len(), 2);
+        assert_eq!(content[0]["text"], "What is in this picture?");
+        assert_eq!(content[1]["type"], "image_url");
+        Ok(())
+    }
+
     #[test]
     fn test_response_to_message_text() -> anyhow::Result<()> {
         let response = json!({
@@ -1090,6 +1118,7 @@ mod tests {
             toolshim: false,
             toolshim_model: None,
             fast_model: None,
+            reasoning: None,
         };
         let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
         let obj = request.as_object().unwrap();
@@ -1122,6 +1151,7 @@ mod tests {
             toolshim: false,
             toolshim_model: None,
             fast_model: None,
+            reasoning: None,
         };
         let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
         let obj = request.as_object().unwrap();
@@ -1144,6 +1174,31 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_create_request_configured_reasoning() -> anyhow::Result<()> {
+        // Configured reasoning takes precedence over the model name suffix
+        let model_config = ModelConfig::new_or_fail("o3-low")
+            .with_reasoning(Some(ReasoningConfig::from_effort(ReasoningEffort::High)));
+        let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
+        assert_eq!(request["model"], "o3");
+        assert_eq!(request["reasoning_effort"], "high");
+
+        // A thinking budget is mapped to the closest effort
+        let model_config = ModelConfig::new_or_fail("gpt-5")
+            .with_reasoning(Some(ReasoningConfig::from_budget_tokens(2048)));
+        let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
+        assert_eq!(request["model"], "gpt-5");
+        assert_eq!(request["reasoning_effort"], "low");
+
+        // Models without reasoning ignore the setting
+        let model_config = ModelConfig::new_or_fail("gpt-4o")
+            .with_reasoning(Some(ReasoningConfig::from_effort(ReasoningEffort::High)));
+        let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
+        assert!(request.get("reasoning_effort").is_none());
+
+        Ok(())
+    }
+
     #[test]
     fn test_create_request_o3_custom_reasoning_effort() -> anyhow::Result<()> {
         // Test custom reasoning effort for O3 model
@@ -1155,6 +1210,7 @@ mod tests {
             toolshim: false,
             toolshim_model: None,
             fast_model: None,
+            reasoning: None,
         };
         let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
         let obj = request.as_object().unwrap();
@@ -1177,6 +1233,15 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_is_reasoning_model_with_vendor_prefix() {
+        assert!(is_reasoning_model("o3"));
+        assert!(is_reasoning_model("openai/o3-mini"));
+        assert!(is_reasoning_model("openai/gpt-5"));
+        assert!(!is_reasoning_model("openai/gpt-4o"));
+        assert!(!is_reasoning_model("anthropic/claude-sonnet-4"));
+    }
+
     #[tokio::test]
     async fn test_streamed_multi_tool_response_to_messages() -> anyhow::Result<()> {
         let response_lines = r#"
@@ -1226,4 +1291,120 @@ data: [DONE]
 
         panic!("Expected tool call message with two calls, but did not see it");
     }
+
+    async fn collect_stream(
+        response_lines: &str,
+    ) -> anyhow::Result<Vec<(Option<Message>, Option<ProviderUsage>)>> {
+        let lines: Vec<anyhow::Result<String>> = response_lines
+            .lines()
+            .map(|line| Ok(line.to_string()))
+            .collect();
+        let messages = response_to_streaming_message(tokio_stream::iter(lines));
+        pin!(messages);
+        let mut items = Vec::new();
+        while let Some(item) = messages.next().await {
+            items.push(item?);
+        }
+        Ok(items)
+    }
+
+    #[test]
+    fn test_get_usage_cached_tokens() {
+        let usage = get_usage(&json!({
+            "prompt_tokens": 2006,
+            "completion_tokens": 300,
+            "total_tokens": 2306,
+            "prompt_tokens_details": {"cached_tokens": 1920}
+        }));
+        assert_eq!(usage.input_tokens, Some(2006));
+        assert_eq!(usage.cache_read_input_tokens, Some(1920));
+        assert_eq!(usage.cache_write_input_tokens, None);
+
+        let usage = get_usage(&json!({"prompt_tokens": 10, "completion_tokens": 2}));
+        assert_eq!(usage.total_tokens, Some(12));
+        assert_eq!(usage.cache_read_input_tokens, None);
+    }
+
+    #[tokio::test]
+    async fn test_streamed_text_yields_usage_once_at_end() -> anyhow::Result<()> {
+        let response_lines = r#"
+data: {"id":"chatcmpl-C1kX","object":"chat.completion.chunk","created":1754500000,"model":"gpt-4o-2024-08-06","choices":[{"index":0,"delta":{"role":"assistant","content":"","refusal":null},"logprobs":null,"finish_reason":null}],"usage":null}
+
+data: {"id":"chatcmpl-C1kX","object":"chat.completion.chunk","created":1754500000,"model":"gpt-4o-2024-08-06","choices":[{"index":0,"delta":{"content":"Hello"},"logprobs":null,"finish_reason":null}],"usage":null}
+
+data: {"id":"chatcmpl-C1kX","object":"chat.completion.chunk","created":1754500000,"model":"gpt-4o-2024-08-06","choices":[{"index":0,"delta":{"content":" there!"},"logprobs":null,"finish_reason":null}],"usage":null}
+
+data: {"id":"chatcmpl-C1kX","object":"chat.completion.chunk","created":1754500000,"model":"gpt-4o-2024-08-06","choices":[{"index":0,"delta":{},"logprobs":null,"finish_reason":"stop"}],"usage":null}
+
+data: {"id":"chatcmpl-C1kX","object":"chat.completion.chunk","created":1754500000,"model":"gpt-4o-2024-08-06","choices":[],"usage":{"prompt_tokens":12,"completion_tokens":3,"total_tokens":15}}
+
+data: [DONE]
+"#;
+
+        let items = collect_stream(response_lines).await?;
+        let texts: Vec<String> = items
+            .iter()
+            .filter_map(|(message, _)| message.as_ref().map(|m| m.as_concat_text()))
+            .collect();
+        assert_eq!(texts, vec!["Hello", " there!"]);
+
+        let usages: Vec<&ProviderUsage> = items.iter().filter_map(|(_, u)| u.as_ref()).collect();
+        assert_eq!(usages.len(), 1);
+        assert_eq!(usages[0].model, "gpt-4o-2024-08-06");
+        assert_eq!(usages[0].usage.input_tokens, Some(12));
+        assert_eq!(usages[0].usage.output_tokens, Some(3));
+        assert!(items.last().unwrap().0.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_streamed_tool_call_argument_deltas_with_groq_usage() -> anyhow::Result<()> {
+        // Groq sends `data:` without a space in some proxies and reports usage under `x_groq`
+        let response_lines = r#"
+data: {"id":"chatcmpl-8f2e","object":"chat.completion.chunk","created":1754500100,"model":"llama-3.3-70b-versatile","choices":[{"index":0,"delta":{"role":"assistant","content":null},"logprobs":null,"finish_reason":null}],"x_groq":{"id":"req_01k1"}}
+
+data: {"id":"chatcmpl-8f2e","object":"chat.completion.chunk","created":1754500100,"model":"llama-3.3-70b-versatile","choices":[{"index":0,"delta":{"tool_calls":[{"id":"call_ab12","type":"function","function":{"name":"developer__shell","arguments":""},"index":0}]},"logprobs":null,"finish_reason":null}]}
+
+data:{"id":"chatcmpl-8f2e","object":"chat.completion.chunk","created":1754500100,"model":"llama-3.3-70b-versatile","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"command\":"}}]},"logprobs":null,"finish_reason":null}]}
+
+data: {"id":"chatcmpl-8f2e","object":"chat.completion.chunk","created":1754500100,"model":"llama-3.3-70b-versatile","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":" \"ls -la\"}"}}]},"logprobs":null,"finish_reason":null}]}
+
+data: {"id":"chatcmpl-8f2e","object":"chat.completion.chunk","created":1754500100,"model":"llama-3.3-70b-versatile","choices":[{"index":0,"delta":{},"logprobs":null,"finish_reason":"tool_calls"}],"x_groq":{"id":"req_01k1","usage":{"queue_time":0.05,"prompt_tokens":812,"prompt_time":0.04,"completion_tokens":21,"completion_time":0.07,"total_tokens":833,"total_time":0.11}}}
+
+data: [DONE]
+"#;
+
+        let items = collect_stream(response_lines).await?;
+        let messages: Vec<&Message> = items.iter().filter_map(|(m, _)| m.as_ref()).collect();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].id.as_deref(), Some("chatcmpl-8f2e"));
+        let MessageContent::ToolRequest(request) = &messages[0].content[0] else {
+            panic!("Expected a tool request");
+        };
+        assert_eq!(request.id, "call_ab12");
+        let tool_call = request.tool_call.as_ref().unwrap();
+        assert_eq!(tool_call.name, "developer__shell");
+        assert_eq!(tool_call.arguments, json!({"command": "ls -la"}));
+
+        let (_, usage) = items.last().unwrap();
+        let usage = usage.as_ref().expect("usage at the end of the stream");
+        assert_eq!(usage.usage.input_tokens, Some(812));
+        assert_eq!(usage.usage.output_tokens, Some(21));
+        assert_eq!(usage.usage.total_tokens, Some(833));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_streamed_error_chunk_fails_the_stream() {
+        let response_lines = r#"
+: OPENROUTER PROCESSING
+
+data: {"id":"gen-1754500200","object":"chat.completion.chunk","created":1754500200,"model":"anthropic/claude-sonnet-4","choices":[{"index":0,"delta":{"role":"assistant","content":"Sure"},"finish_reason":null}]}
+
+data: {"id":"gen-1754500200","object":"chat.completion.chunk","created":1754500200,"model":"anthropic/claude-sonnet-4","error":{"code":502,"message":"Provider returned error"},"choices":[{"index":0,"delta":{"content":""},"finish_reason":"error"}]}
+"#;
+
+        let err = collect_stream(response_lines).await.unwrap_err();
+        assert!(err.to_string().contains("Provider returned error"));
+    }
 }
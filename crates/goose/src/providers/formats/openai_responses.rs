@@ -0,0 +1,760 @@
+//! Request and response conversion for the OpenAI Responses API (`/v1/responses`).
+//!
+//! Unlike Chat Completions, the Responses API exposes the model's reasoning as output
+//! items. These are mapped to `MessageContent::Thinking` (when a summary is available)
+//! or `MessageContent::RedactedThinking`, with the item id and encrypted content kept
+//! in the signature so they can be sent back on the next turn.
+
+use crate::conversation::message::{Message, MessageContent};
+use crate::model::ModelConfig;
+use crate::providers::base::{ProviderUsage, Usage};
+use crate::providers::formats::openai::{
+    ensure_valid_json_schema, is_reasoning_model, reasoning_model_and_effort,
+};
+use crate::providers::utils::{is_valid_function_name, safely_parse_json, sanitize_function_name};
+use anyhow::{anyhow, Error};
+use async_stream::try_stream;
+use futures::Stream;
+use mcp_core::ToolCall;
+use rmcp::model::{ErrorCode, ErrorData, RawContent, ResourceContents, Role, Tool};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::{HashSet, VecDeque};
+use std::ops::Deref;
+use std::sync::Mutex;
+
+/// Prefix of the ids the Responses API assigns to responses
+const RESPONSE_ID_PREFIX: &str = "resp_";
+
+/// Number of stored requests whose input is remembered for continuations
+const MAX_REMEMBERED_INPUTS: usize = 64;
+
+/// What we keep of a reasoning item in a thinking signature, to replay it later
+#[derive(Serialize, Deserialize)]
+struct ReasoningSignature {
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encrypted_content: Option<String>,
+}
+
+fn reasoning_item(signature: &str, summary: Option<&str>) -> Option<Value> {
+    // Thinking from other providers carries signatures we cannot replay here
+    let signature: ReasoningSignature = serde_json::from_str(signature).ok()?;
+    let summary: Vec<Value> = summary
+        .filter(|s| !s.is_empty())
+        .map(|s| json!({"type": "summary_text", "text": s}))
+        .into_iter()
+        .collect();
+
+    let mut item = json!({
+        "type": "reasoning",
+        "id": signature.id,
+        "summary": summary,
+    });
+    if let Some(encrypted_content) = signature.encrypted_content {
+        item["encrypted_content"] = json!(encrypted_content);
+    }
+    Some(item)
+}
+
+fn image_url(data: &str, mime_type: &str) -> String {
+    format!("data:{};base64,{}", mime_type, data)
+}
+
+fn tool_result_output(contents: &[rmcp::model::Content]) -> (String, Vec<Value>) {
+    let mut texts = Vec::new();
+    let mut images = Vec::new();
+
+    // Send only contents with no audience or with Assistant in the audience
+    for content in contents.iter().filter(|content| {
+        content
+            .audience()
+            .is_none_or(|audience| audience.contains(&Role::Assistant))
+    }) {
+        match content.deref() {
+            RawContent::Text(text) => texts.push(text.text.clone()),
+            RawContent::Image(image) => {
+                texts.push(
+                    "This tool result included an image that is uploaded in the next message."
+                        .to_string(),
+                );
+                images.push(json!({
+                    "type": "input_image",
+                    "image_url": image_url(&image.data, &image.mime_type),
+                }));
+            }
+            RawContent::Resource(resource) => {
+                if let ResourceContents::TextResourceContents { text, .. } = &resource.resource {
+                    texts.push(text.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (texts.join(" "), images)
+}
+
+fn flush_message(items: &mut Vec<Value>, parts: &mut Vec<Value>, role: &str) {
+    if !parts.is_empty() {
+        let content = std::mem::take(parts);
+        items.push(json!({"type": "message", "role": role, "content": content}));
+    }
+}
+
+/// Push an item after any pending message parts, keeping the conversation order
+fn push_item(items: &mut Vec<Value>, parts: &mut Vec<Value>, role: &str, item: Value) {
+    flush_message(items, parts, role);
+    items.push(item);
+}
+
+/// Convert internal messages to Responses API input items
+pub fn format_input(messages: &[Message]) -> Vec<Value> {
+    let mut items = Vec::new();
+    // Tool requests we could not send, whose responses have no call to attach to
+    let mut unsent_calls = HashSet::new();
+
+    for message in messages {
+        let is_assistant = message.role == Role::Assistant;
+        let role = if is_assistant { "assistant" } else { "user" };
+        let mut parts = Vec::new();
+        let mut trailing = Vec::new();
+
+        for content in &message.content {
+            match content {
+                MessageContent::Text(text) => {
+                    if !text.text.is_empty() {
+                        let kind = if is_assistant {
+                            "output_text"
+                        } else {
+                            "input_text"
+                        };
+                        parts.push(json!({"type": kind, "text": text.text}));
+                    }
+                }
+                MessageContent::Image(image) => {
+                    parts.push(json!({
+                        "type": "input_image",
+                        "image_url": image_url(&image.data, &image.mime_type),
+                    }));
+                }
+                MessageContent::Thinking(thinking) => {
+                    if let Some(item) =
+                        reasoning_item(&thinking.signature, Some(&thinking.thinking))
+                    {
+                        push_item(&mut items, &mut parts, role, item);
+                    }
+                }
+                MessageContent::RedactedThinking(redacted) => {
+                    if let Some(item) = reasoning_item(&redacted.data, None) {
+                        push_item(&mut items, &mut parts, role, item);
+                    }
+                }
+                MessageContent::ToolRequest(request) => match &request.tool_call {
+                    Ok(tool_call) => push_item(
+                        &mut items,
+                        &mut parts,
+                        role,
+                        json!({
+                            "type": "function_call",
+                            "call_id": request.id,
+                            "name": sanitize_function_name(&tool_call.name),
+                            "arguments": tool_call.arguments.to_string(),
+                        }),
+                    ),
+                    Err(_) => {
+                        unsent_calls.insert(request.id.clone());
+                    }
+                },
+                MessageContent::FrontendToolRequest(request) => match &request.tool_call {
+                    Ok(tool_call) => push_item(
+                        &mut items,
+                        &mut parts,
+                        role,
+                        json!({
+                            "type": "function_call",
+                            "call_id": request.id,
+                            "name": sanitize_function_name(&tool_call.name),
+                            "arguments": tool_call.arguments.to_string(),
+                        }),
+                    ),
+                    Err(_) => {
+                        unsent_calls.insert(request.id.clone());
+                    }
+                },
+                MessageContent::ToolResponse(response) => {
+                    let (output, images) = match &response.tool_result {
+                        Ok(contents) => tool_result_output(contents),
+                        // A tool result error is shown as output so the model can interpret the error message
+                        Err(e) => (
+                            format!("The tool call returned the following error:\n{}", e),
+                            Vec::new(),
+                        ),
+                    };
+
+                    if unsent_calls.contains(&response.id) {
+                        parts.push(json!({"type": "input_text", "text": output}));
+                    } else {
+                        push_item(
+                            &mut items,
+                            &mut parts,
+                            role,
+                            json!({
+                                "type": "function_call_output",
+                                "call_id": response.id,
+                                "output": output,
+                            }),
+                        );
+                    }
+                    trailing.extend(images);
+                }
+                MessageContent::ToolConfirmationRequest(_)
+                | MessageContent::ContextLengthExceeded(_)
+                | MessageContent::SummarizationRequested(_) => {}
+            }
+        }
+
+        flush_message(&mut items, &mut parts, role);
+        if !trailing.is_empty() {
+            items.push(json!({"type": "message", "role": "user", "content": trailing}));
+        }
+    }
+
+    items
+}
+
+/// Convert internal Tool format to the Responses API function tool specification
+pub fn format_tools(tools: &[Tool]) -> anyhow::Result<Vec<Value>> {
+    let mut tool_names = HashSet::new();
+    let mut result = Vec::new();
+
+    for tool in tools {
+        if !tool_names.insert(&tool.name) {
+            return Err(anyhow!("Duplicate tool name: {}", tool.name));
+        }
+
+        let mut parameters = Value::Object(tool.input_schema.as_ref().clone());
+        ensure_valid_json_schema(&mut parameters);
+
+        result.push(json!({
+            "type": "function",
+            "name": tool.name,
+            "description": tool.description,
+            "parameters": parameters,
+        }));
+    }
+
+    Ok(result)
+}
+
+/// The inputs of the stored requests sent by a provider, to tell whether the messages
+/// before a response are still exactly what the server holds for it
+#[derive(Debug, Default)]
+pub struct StoredInputs {
+    hashes: Mutex<VecDeque<String>>,
+}
+
+impl StoredInputs {
+    fn hash(messages: &[Message]) -> String {
+        let input = Value::Array(format_input(messages)).to_string();
+        format!("{:x}", Sha256::digest(input.as_bytes()))
+    }
+
+    /// Remember `messages` as the full input of a stored request
+    pub fn remember(&self, messages: &[Message]) {
+        let hash = Self::hash(messages);
+        let mut hashes = self.hashes.lock().unwrap();
+        if hashes.contains(&hash) {
+            return;
+        }
+        if hashes.len() == MAX_REMEMBERED_INPUTS {
+            hashes.pop_front();
+        }
+        hashes.push_back(hash);
+    }
+
+    /// Whether `messages` is the full input of a remembered stored request
+    pub fn contains(&self, messages: &[Message]) -> bool {
+        let hash = Self::hash(messages);
+        self.hashes.lock().unwrap().contains(&hash)
+    }
+}
+
+/// Find where a stateful conversation can continue from.
+///
+/// Returns the id of the last response the server produced for this conversation and the
+/// messages that came after it, which are all that has to be sent. The response is only
+/// continued from while the messages before it are unchanged since it was requested;
+/// once they were truncated, pruned, summarized or dropped, the server's copy is stale
+/// and the whole conversation is sent instead.
+pub fn split_at_previous_response<'a>(
+    messages: &'a [Message],
+    stored: &StoredInputs,
+) -> (Option<&'a str>, &'a [Message]) {
+    let last_response = messages
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(index, message)| {
+            let id = message.id.as_deref()?;
+            (message.role == Role::Assistant && id.starts_with(RESPONSE_ID_PREFIX))
+                .then_some((index, id))
+        });
+    match last_response {
+        Some((index, id)) if stored.contains(&messages[..index]) => {
+            (Some(id), &messages[index + 1..])
+        }
+        _ => (None, messages),
+    }
+}
+
+/// Build a Responses API request.
+///
+/// When `stored` is given the response is stored by the server and, if an earlier
+/// response of this conversation can be continued, only the newer messages are sent along
+/// with its `previous_response_id`. Otherwise nothing is stored and reasoning items are
+/// carried over in their encrypted form.
+pub fn create_request(
+    model_config: &ModelConfig,
+    system: &str,
+    messages: &[Message],
+    tools: &[Tool],
+    stored: Option<&StoredInputs>,
+) -> anyhow::Result<Value, Error> {
+    let is_ox_model = is_reasoning_model(&model_config.model_name);
+    let (model_name, reasoning_effort) = reasoning_model_and_effort(model_config);
+    let stateful = stored.is_some();
+
+    let (previous_response_id, messages) = match stored {
+        Some(stored) => {
+            let split = split_at_previous_response(messages, stored);
+            stored.remember(messages);
+            split
+        }
+        None => (None, messages),
+    };
+
+    let mut payload = json!({
+        "model": model_name,
+        "instructions": system,
+        "input": format_input(messages),
+        "store": stateful,
+    });
+    let object = payload.as_object_mut().unwrap();
+
+    if let Some(previous_response_id) = previous_response_id {
+        object.insert(
+            "previous_response_id".to_string(),
+            json!(previous_response_id),
+        );
+    }
+
+    if let Some(effort) = reasoning_effort {
+        object.insert(
+            "reasoning".to_string(),
+            json!({"effort": effort, "summary": "auto"}),
+        );
+        if !stateful {
+            object.insert(
+                "include".to_string(),
+                json!(["reasoning.encrypted_content"]),
+            );
+        }
+    }
+
+    if !tools.is_empty() {
+        object.insert("tools".to_string(), json!(format_tools(tools)?));
+    }
+
+    // o1, o3 models currently don't support temperature
+    if !is_ox_model {
+        if let Some(temp) = model_config.temperature {
+            object.insert("temperature".to_string(), json!(temp));
+        }
+    }
+
+    if let Some(tokens) = model_config.max_tokens {
+        object.insert("max_output_tokens".to_string(), json!(tokens));
+    }
+
+    Ok(payload)
+}
+
+fn function_call_to_content(item: &Value) -> MessageContent {
+    let id = item["call_id"].as_str().unwrap_or_default().to_string();
+    let function_name = item["name"].as_str().unwrap_or_default().to_string();
+    let arguments_str = item["arguments"]
+        .as_str()
+        .filter(|a| !a.is_empty())
+        .unwrap_or("{}");
+
+    if !is_valid_function_name(&function_name) {
+        let error = ErrorData {
+            code: ErrorCode::INVALID_REQUEST,
+            message: Cow::from(format!(
+                "The provided function name '{}' had invalid characters, it must match this regex [a-zA-Z0-9_-]+",
+                function_name
+            )),
+            data: None,
+        };
+        return MessageContent::tool_request(id, Err(error));
+    }
+
+    match safely_parse_json(arguments_str) {
+        Ok(params) => MessageContent::tool_request(id, Ok(ToolCall::new(&function_name, params))),
+        Err(e) => {
+            let error = ErrorData {
+                code: ErrorCode::INVALID_PARAMS,
+                message: Cow::from(format!(
+                    "Could not interpret tool use parameters for id {}: {}. Raw arguments: '{}'",
+                    id, e, arguments_str
+                )),
+                data: None,
+            };
+            MessageContent::tool_request(id, Err(error))
+        }
+    }
+}
+
+fn reasoning_to_content(item: &Value) -> Option<MessageContent> {
+    let signature = serde_json::to_string(&ReasoningSignature {
+        id: item["id"].as_str()?.to_string(),
+        encrypted_content: item["encrypted_content"].as_str().map(str::to_string),
+    })
+    .ok()?;
+
+    let summary = item["summary"]
+        .as_array()
+        .map(|parts| {
+            parts
+                .iter()
+                .filter_map(|part| part["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        })
+        .unwrap_or_default();
+
+    Some(if summary.is_empty() {
+        MessageContent::redacted_thinking(signature)
+    } else {
+        MessageContent::thinking(summary, signature)
+    })
+}
+
+/// Convert one output item of a response to message content
+pub fn output_item_to_content(item: &Value) -> Vec<MessageContent> {
+    match item["type"].as_str().unwrap_or_default() {
+        "message" => item["content"]
+            .as_array()
+            .map(|parts| {
+                parts
+                    .iter()
+                    .filter_map(|part| match part["type"].as_str() {
+                        Some("output_text") => part["text"].as_str(),
+                        Some("refusal") => part["refusal"].as_str(),
+                        _ => None,
+                    })
+                    .filter(|text| !text.is_empty())
+                    .map(MessageContent::text)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        "reasoning" => reasoning_to_content(item).into_iter().collect(),
+        "function_call" => vec![function_call_to_content(item)],
+        other => {
+            // Built-in tools (web search, file search, ...) already report their
+            // findings in the message text that follows them
+            tracing::debug!("Ignoring Responses API output item of type {}", other);
+            Vec::new()
+        }
+    }
+}
+
+/// Convert a Responses API response to internal Message format
+pub fn response_to_message(response: &Value) -> anyhow::Result<Message> {
+    if let Some(error) = response.get("error").filter(|e| !e.is_null()) {
+        let message = error["message"].as_str().unwrap_or("unknown error");
+        return Err(anyhow!("Response failed: {}", message));
+    }
+
+    let content = response["output"]
+        .as_array()
+        .map(|items| items.iter().flat_map(output_item_to_content).collect())
+        .unwrap_or_default();
+
+    let message = Message::new(Role::Assistant, chrono::Utc::now().timestamp(), content);
+    Ok(match response["id"].as_str() {
+        Some(id) => message.with_id(id),
+        None => message,
+    })
+}
+
+pub fn get_usage(usage: &Value) -> Usage {
+    let input_tokens = usage
+        .get("input_tokens")
+        .and_then(|v| v.as_i64())
+        .map(|v| v as i32);
+
+    let output_tokens = usage
+        .get("output_tokens")
+        .and_then(|v| v.as_i64())
+        .map(|v| v as i32);
+
+    let total_tokens = usage
+        .get("total_tokens")
+        .and_then(|v| v.as_i64())
+        .map(|v| v as i32)
+        .or_else(|| match (input_tokens, output_tokens) {
+            (Some(input), Some(output)) => Some(input + output),
+            _ => None,
+        });
+
+    let cached_tokens = usage
+        .get("input_tokens_details")
+        .and_then(|d| d.get("cached_tokens"))
+        .and_then(|v| v.as_i64())
+        .map(|v| v as i32);
+
+    Usage::new(input_tokens, output_tokens, total_tokens).with_cache_tokens(cached_tokens, None)
+}
+
+/// Convert a stream of Responses API server-sent event lines into messages.
+///
+/// Text deltas are yielded as they arrive. Reasoning and function call items are
+/// yielded once complete, from their `response.output_item.done` event. Usage is
+/// yielded once, at the end of the stream, from the final response.
+pub fn response_to_streaming_message<S>(
+    mut stream: S,
+) -> impl Stream<Item = anyhow::Result<(Option<Message>, Option<ProviderUsage>)>> + 'static
+where
+    S: Stream<Item = anyhow::Result<String>> + Unpin + Send + 'static,
+{
+    try_stream! {
+        use futures::StreamExt;
+
+        let mut response_id: Option<String> = None;
+        let mut usage: Option<ProviderUsage> = None;
+
+        let message = |id: &Option<String>, content: Vec<MessageContent>| {
+            let msg = Message::new(Role::Assistant, chrono::Utc::now().timestamp(), content);
+            match id {
+                Some(id) => msg.with_id(id.clone()),
+                None => msg,
+            }
+        };
+
+        while let Some(line) = stream.next().await {
+            let line = line?;
+            let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                continue;
+            };
+            if data.is_empty() || data == "[DONE]" {
+                continue;
+            }
+
+            let event: Value = serde_json::from_str(data)
+                .map_err(|e| anyhow!("Failed to parse streaming event: {}: {:?}", e, data))?;
+
+            match event["type"].as_str().unwrap_or_default() {
+                "response.created" | "response.in_progress" => {
+                    if let Some(id) = event["response"]["id"].as_str() {
+                        response_id = Some(id.to_string());
+                    }
+                }
+                "response.output_text.delta" | "response.refusal.delta" => {
+                    if let Some(text) = event["delta"].as_str().filter(|t| !t.is_empty()) {
+                        yield (Some(message(&response_id, vec![MessageContent::text(text)])), None);
+                    }
+                }
+                "response.output_item.done" => {
+                    // Message text was already streamed through its deltas
+                    if event["item"]["type"].as_str() != Some("message") {
+                        let content = output_item_to_content(&event["item"]);
+                        if !content.is_empty() {
+                            yield (Some(message(&response_id, content)), None);
+                        }
+                    }
+                }
+                "response.completed" | "response.incomplete" => {
+                    let response = &event["response"];
+                    if let Some(reason) = response["incomplete_details"]["reason"].as_str() {
+                        tracing::warn!("Response incomplete: {}", reason);
+                    }
+                    if let Some(response_usage) = response.get("usage").filter(|u| !u.is_null()) {
+                        usage = Some(ProviderUsage::new(
+                            response["model"].as_str().unwrap_or_default().to_string(),
+                            get_usage(response_usage),
+                        ));
+                    }
+                }
+                "response.failed" => {
+                    let message = event["response"]["error"]["message"].as_str().unwrap_or("unknown error");
+                    Err(anyhow!("Server reported an error in the stream: {}", message))?;
+                }
+                "error" => {
+                    let message = event["message"].as_str().unwrap_or("unknown error");
+                    Err(anyhow!("Server reported an error in the stream: {}", message))?;
+                }
+                _ => {}
+            }
+        }
+
+        if usage.is_some() {
+            yield (None, usage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::Content;
+    use rmcp::object;
+
+    #[test]
+    fn test_reasoning_round_trip() -> anyhow::Result<()> {
+        let response = json!({
+            "id": "resp_1",
+            "output": [
+                {
+                    "type": "reasoning",
+                    "id": "rs_1",
+                    "summary": [{"type": "summary_text", "text": "Check the files"}],
+                    "encrypted_content": "gAAAA"
+                },
+                {"type": "reasoning", "id": "rs_2", "summary": []},
+                {"type": "web_search_call", "id": "ws_1", "status": "completed"},
+                {
+                    "type": "function_call",
+                    "call_id": "call_1",
+                    "name": "developer__shell",
+                    "arguments": "{\"command\": \"ls\"}"
+                }
+            ]
+        });
+
+        let message = response_to_message(&response)?;
+        assert_eq!(message.id.as_deref(), Some("resp_1"));
+        assert_eq!(message.content.len(), 3);
+        let thinking = message.content[0].as_thinking().unwrap();
+        assert_eq!(thinking.thinking, "Check the files");
+        assert!(message.content[1].as_redacted_thinking().is_some());
+
+        let input = format_input(&[message]);
+        assert_eq!(
+            input,
+            vec![
+                json!({
+                    "type": "reasoning",
+                    "id": "rs_1",
+                    "summary": [{"type": "summary_text", "text": "Check the files"}],
+                    "encrypted_content": "gAAAA"
+                }),
+                json!({"type": "reasoning", "id": "rs_2", "summary": []}),
+                json!({
+                    "type": "function_call",
+                    "call_id": "call_1",
+                    "name": "developer__shell",
+                    "arguments": "{\"command\":\"ls\"}"
+                }),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_input_tool_response_and_foreign_thinking() {
+        let messages = vec![
+            Message::user().with_text("list files"),
+            Message::assistant()
+                .with_thinking("anthropic thinking", "not-a-responses-signature")
+                .with_tool_request("call_1", Ok(ToolCall::new("shell", json!({"cmd": "ls"})))),
+            Message::user().with_tool_response("call_1", Ok(vec![Content::text("a.txt")])),
+        ];
+
+        let input = format_input(&messages);
+        assert_eq!(input.len(), 3);
+        assert_eq!(
+            input[0],
+            json!({"type": "message", "role": "user", "content": [{"type": "input_text", "text": "list files"}]})
+        );
+        assert_eq!(input[1]["type"], "function_call");
+        assert_eq!(
+            input[2],
+            json!({"type": "function_call_output", "call_id": "call_1", "output": "a.txt"})
+        );
+    }
+
+    #[test]
+    fn test_create_request_stateful_continuation() -> anyhow::Result<()> {
+        let messages = vec![
+            Message::user().with_text("hi"),
+            Message::assistant().with_text("hello").with_id("resp_1"),
+            Message::user().with_text("how are you?"),
+        ];
+        let tool = Tool::new(
+            "get_weather",
+            "Get the weather",
+            object!({"type": "object", "properties": {"city": {"type": "string"}}}),
+        );
+        let model_config = ModelConfig::new_or_fail("o3-high");
+        let stored = StoredInputs::default();
+        stored.remember(&messages[..1]);
+
+        let stateful = create_request(
+            &model_config,
+            "system",
+            &messages,
+            &[tool.clone()],
+            Some(&stored),
+        )?;
+        assert_eq!(stateful["model"], "o3");
+        assert_eq!(stateful["previous_response_id"], "resp_1");
+        assert_eq!(stateful["store"], true);
+        assert_eq!(stateful["input"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            stateful["reasoning"],
+            json!({"effort": "high", "summary": "auto"})
+        );
+        assert!(stateful.get("include").is_none());
+        assert_eq!(stateful["tools"][0]["name"], "get_weather");
+        assert_eq!(stateful["tools"][0]["parameters"]["required"], json!([]));
+
+        let stateless = create_request(&model_config, "system", &messages, &[tool], None)?;
+        assert!(stateless.get("previous_response_id").is_none());
+        assert_eq!(stateless["store"], false);
+        assert_eq!(stateless["input"].as_array().unwrap().len(), 3);
+        assert_eq!(stateless["include"], json!(["reasoning.encrypted_content"]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_continuation_is_dropped_when_prefix_changed() {
+        let stored = StoredInputs::default();
+        let first = vec![
+            Message::user().with_text("one"),
+            Message::assistant().with_text("two"),
+            Message::user().with_text("three"),
+        ];
+        stored.remember(&first);
+
+        let mut messages = first.clone();
+        messages.push(Message::assistant().with_text("four").with_id("resp_1"));
+        messages.push(Message::user().with_text("five"));
+        let (id, rest) = split_at_previous_response(&messages, &stored);
+        assert_eq!(id, Some("resp_1"));
+        assert_eq!(rest.len(), 1);
+
+        // Compaction replaced the start of the conversation the server holds
+        messages[0] = Message::user().with_text("summary of one");
+        let (id, rest) = split_at_previous_response(&messages, &stored);
+        assert_eq!(id, None);
+        assert_eq!(rest.len(), messages.len());
+
+        // Responses from another process can't be checked, so they are not continued
+        let (id, _) = split_at_previous_response(&messages, &StoredInputs::default());
+        assert_eq!(id, None);
+    }
+}
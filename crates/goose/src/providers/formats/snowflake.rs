@@ -1,10 +1,12 @@
 use crate::conversation::message::{Message, MessageContent};
 use crate::model::ModelConfig;
-use crate::providers::base::Usage;
+use crate::providers::base::{ProviderUsage, Usage};
 use crate::providers::errors::ProviderError;
 use anyhow::{anyhow, Result};
+use async_stream::try_stream;
+use futures::Stream;
 use mcp_core::tool::ToolCall;
-use rmcp::model::{Role, Tool};
+use rmcp::model::{ErrorCode, ErrorData, Role, Tool};
 use serde_json::{json, Value};
 use std::collections::HashSet;
 
@@ -197,6 +199,132 @@ pub fn parse_streaming_response(sse_data: &str) -> Result<Message> {
     Ok(message)
 }
 
+fn streamed_tool_request(id: &str, name: &str, input: &str) -> MessageContent {
+    let parsed = if input.is_empty() {
+        Ok(json!({}))
+    } else {
+        serde_json::from_str::<Value>(input)
+    };
+    match parsed {
+        Ok(arguments) => MessageContent::tool_request(id, Ok(ToolCall::new(name, arguments))),
+        Err(e) => MessageContent::tool_request(
+            id,
+            Err(ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!(
+                    "Could not interpret tool use parameters for id {}: {}",
+                    id, e
+                ),
+                None,
+            )),
+        ),
+    }
+}
+
+/// Convert a stream of Snowflake's server-sent events into messages.
+///
+/// Text deltas are yielded as they arrive. A tool use starts with a delta carrying its
+/// id and name, followed by deltas with chunks of its input; the tool requests are
+/// yielded together once the stream ends, followed by the usage from the last event.
+pub fn response_to_streaming_message<S>(
+    mut stream: S,
+) -> impl Stream<Item = Result<(Option<Message>, Option<ProviderUsage>)>> + 'static
+where
+    S: Stream<Item = Result<String>> + Unpin + Send + 'static,
+{
+    try_stream! {
+        use futures::StreamExt;
+
+        let mut message_id: Option<String> = None;
+        let mut tool_uses: Vec<(String, String, String)> = Vec::new();
+        let mut usage: Option<ProviderUsage> = None;
+
+        while let Some(line) = stream.next().await {
+            let line = line?;
+            // Errors are returned as a plain JSON body rather than as an event
+            let data = match line.strip_prefix("data:") {
+                Some(data) => data.trim(),
+                None if line.trim_start().starts_with('{') => line.trim(),
+                None => continue,
+            };
+            if data.is_empty() || data == "[DONE]" {
+                continue;
+            }
+
+            let event: Value = serde_json::from_str(data)
+                .map_err(|e| anyhow!("Failed to parse streaming chunk: {}: {:?}", e, data))?;
+
+            if event.get("choices").is_none() {
+                if let Some(code) = event.get("code").and_then(|c| c.as_str()) {
+                    let message = event.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown message");
+                    Err(anyhow!("{} - {}", code, message))?;
+                }
+            }
+
+            if let Some(id) = event.get("id").and_then(|i| i.as_str()) {
+                message_id = Some(id.to_string());
+            }
+            if let (Some(model), Some(event_usage)) = (
+                event.get("model").and_then(|m| m.as_str()),
+                event.get("usage").filter(|u| u.as_object().is_some_and(|u| !u.is_empty())),
+            ) {
+                usage = Some(ProviderUsage::new(model.to_string(), get_streaming_usage(event_usage)));
+            }
+
+            let Some(delta) = event
+                .get("choices")
+                .and_then(|c| c.as_array())
+                .and_then(|c| c.first())
+                .and_then(|c| c.get("delta"))
+            else {
+                continue;
+            };
+
+            match delta.get("type").and_then(|t| t.as_str()) {
+                Some("text") => {
+                    let text = delta.get("content").and_then(|c| c.as_str()).unwrap_or_default();
+                    if !text.is_empty() {
+                        let mut message = Message::assistant().with_text(text);
+                        if let Some(id) = &message_id {
+                            message = message.with_id(id.clone());
+                        }
+                        yield (Some(message), None);
+                    }
+                }
+                Some("tool_use") => {
+                    let input = delta.get("input").and_then(|i| i.as_str()).unwrap_or_default();
+                    match delta.get("tool_use_id").and_then(|i| i.as_str()) {
+                        Some(id) => {
+                            let name = delta.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+                            tool_uses.push((id.to_string(), name.to_string(), input.to_string()));
+                        }
+                        None => {
+                            if let Some((_, _, tool_input)) = tool_uses.last_mut() {
+                                tool_input.push_str(input);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !tool_uses.is_empty() {
+            let mut message = Message::assistant();
+            for (id, name, input) in &tool_uses {
+                message = message.with_content(streamed_tool_request(id, name, input));
+            }
+            if let Some(id) = &message_id {
+                message = message.with_id(id.clone());
+            }
+            yield (Some(message), None);
+        }
+        if usage.is_some() {
+            yield (None, usage);
+        }
+    }
+}
+
 /// Convert Snowflake's API response to internal Message format
 pub fn response_to_message(response: &Value) -> Result<Message> {
     let mut message = Message::assistant();
@@ -305,6 +433,18 @@ pub fn get_usage(data: &Value) -> Result<Usage> {
     }
 }
 
+/// Extract usage from a streamed event, which uses OpenAI-style keys
+fn get_streaming_usage(usage: &Value) -> Usage {
+    let tokens = |key: &str| usage.get(key).and_then(|v| v.as_u64()).map(|v| v as i32);
+    let input_tokens = tokens("prompt_tokens");
+    let output_tokens = tokens("completion_tokens");
+    let total_tokens = tokens("total_tokens").or(match (input_tokens, output_tokens) {
+        (Some(input), Some(output)) => Some(input + output),
+        _ => None,
+    });
+    Usage::new(input_tokens, output_tokens, total_tokens)
+}
+
 /// Create a complete request payload for Snowflake's API
 pub fn create_request(
     model_config: &ModelConfig,
@@ -545,6 +685,61 @@ data: {"id":"a9537c2c-2017-4906-9817-2456168d89fa","model":"claude-sonnet-4-2025
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_response_to_streaming_message() -> Result<()> {
+        use futures::StreamExt;
+
+        let sse_data = r#"data: {"id":"a9537c2c-2017-4906-9817-2456168d89fa","model":"claude-sonnet-4-20250514","choices":[{"delta":{"type":"text","content":"I","content_list":[{"type":"text","text":"I"}],"text":"I"}}],"usage":{}}
+
+data: {"id":"a9537c2c-2017-4906-9817-2456168d89fa","model":"claude-sonnet-4-20250514","choices":[{"delta":{"type":"text","content":"'ll check both","content_list":[{"type":"text","text":"'ll check both"}],"text":"'ll check both"}}],"usage":{}}
+
+data: {"id":"a9537c2c-2017-4906-9817-2456168d89fa","model":"claude-sonnet-4-20250514","choices":[{"delta":{"type":"tool_use","tool_use_id":"tooluse_FB_nOElDTAOKa-YnVWI5Uw","name":"get_stock_price","content_list":[{"tool_use_id":"tooluse_FB_nOElDTAOKa-YnVWI5Uw","name":"get_stock_price"}],"text":""}}],"usage":{}}
+
+data: {"id":"a9537c2c-2017-4906-9817-2456168d89fa","model":"claude-sonnet-4-20250514","choices":[{"delta":{"type":"tool_use","input":"{\"symbol\":","content_list":[{"input":"{\"symbol\":"}],"text":""}}],"usage":{}}
+
+data: {"id":"a9537c2c-2017-4906-9817-2456168d89fa","model":"claude-sonnet-4-20250514","choices":[{"delta":{"type":"tool_use","input":"\"NVDA\"}","content_list":[{"input":"\"NVDA\"}"}],"text":""}}],"usage":{}}
+
+data: {"id":"a9537c2c-2017-4906-9817-2456168d89fa","model":"claude-sonnet-4-20250514","choices":[{"delta":{"type":"tool_use","tool_use_id":"tooluse_2xq0-3RwSbyc2kL8yqFh0A","name":"get_stock_price","content_list":[{"tool_use_id":"tooluse_2xq0-3RwSbyc2kL8yqFh0A","name":"get_stock_price"}],"text":""}}],"usage":{}}
+
+data: {"id":"a9537c2c-2017-4906-9817-2456168d89fa","model":"claude-sonnet-4-20250514","choices":[{"delta":{"type":"tool_use","input":"{\"symbol\":\"AMD\"}","content_list":[{"input":"{\"symbol\":\"AMD\"}"}],"text":""}}],"usage":{"prompt_tokens":397,"completion_tokens":94,"total_tokens":491}}
+"#;
+        let lines = futures::stream::iter(sse_data.lines().map(|l| Ok(l.to_string())));
+        let items: Vec<_> = response_to_streaming_message(lines).collect().await;
+        let items = items.into_iter().collect::<Result<Vec<_>>>()?;
+        assert_eq!(items.len(), 4);
+
+        assert_eq!(items[0].0.as_ref().unwrap().as_concat_text(), "I");
+        assert_eq!(
+            items[1].0.as_ref().unwrap().as_concat_text(),
+            "'ll check both"
+        );
+
+        let tool_message = items[2].0.as_ref().unwrap();
+        let requests: Vec<_> = tool_message
+            .content
+            .iter()
+            .filter_map(|c| c.as_tool_request())
+            .collect();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].id, "tooluse_FB_nOElDTAOKa-YnVWI5Uw");
+        assert_eq!(
+            requests[0].tool_call.as_ref().unwrap().arguments,
+            json!({"symbol": "NVDA"})
+        );
+        assert_eq!(requests[1].id, "tooluse_2xq0-3RwSbyc2kL8yqFh0A");
+        assert_eq!(
+            requests[1].tool_call.as_ref().unwrap().arguments,
+            json!({"symbol": "AMD"})
+        );
+
+        let usage = items[3].1.as_ref().unwrap();
+        assert_eq!(usage.model, "claude-sonnet-4-20250514");
+        assert_eq!(usage.usage.input_tokens, Some(397));
+        assert_eq!(usage.usage.output_tokens, Some(94));
+        assert_eq!(usage.usage.total_tokens, Some(491));
+        Ok(())
+    }
+
     #[test]
     fn test_create_request_format() -> Result<()> {
         use crate::conversation::message::Message;
@@ -3,26 +3,28 @@ use std::time::Duration;
 use anyhow::Result;
 use async_trait::async_trait;
 use once_cell::sync::Lazy;
-use reqwest::{Client, StatusCode};
-use serde_json::Value;
+use reqwest::{Client, Response, StatusCode};
+use serde_json::{json, Value};
 use tokio::time::sleep;
 use url::Url;
 
 use crate::conversation::message::Message;
 use crate::model::ModelConfig;
-use crate::providers::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage};
+use crate::providers::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage};
 
 use crate::providers::errors::ProviderError;
 use crate::providers::formats::gcpvertexai::{
-    create_request, get_usage, response_to_message, ClaudeVersion, GcpVertexAIModel, GeminiVersion,
-    ModelProvider, RequestContext,
+    create_request, get_usage, response_to_message, response_to_streaming_message, ClaudeVersion,
+    GcpVertexAIModel, GeminiVersion, ModelProvider, RequestContext,
 };
 
 use crate::impl_provider_default;
 use crate::providers::formats::gcpvertexai::GcpLocation::Iowa;
+use crate::providers::formats::google::{context_cache_content, with_context_cache};
 use crate::providers::gcpauth::GcpAuth;
-use crate::providers::retry::RetryConfig;
-use crate::providers::utils::emit_debug_trace;
+use crate::providers::prompt_cache::{cache_key, context_caching_enabled, ContextCaches};
+use crate::providers::retry::{effective_retry_config, RetryConfig};
+use crate::providers::utils::{emit_debug_trace, response_lines, stream_messages};
 use rmcp::model::Tool;
 
 /// Base URL for GCP Vertex AI documentation
@@ -77,6 +79,9 @@ pub struct GcpVertexAIProvider {
     /// Retry configuration for handling rate limit errors
     #[serde(skip)]
     retry_config: RetryConfig,
+    /// Explicit context caches created for Gemini models
+    #[serde(skip)]
+    context_caches: ContextCaches,
 }
 
 impl GcpVertexAIProvider {
@@ -107,7 +112,9 @@ impl GcpVertexAIProvider {
         let config = crate::config::Config::global();
         let project_id = config.get_param("GCP_PROJECT_ID")?;
         let location = Self::determine_location(config)?;
-        let host = format!("https://{}-aiplatform.googleapis.com", location);
+        let host = config
+            .get_param("GCP_VERTEX_HOST")
+            .unwrap_or_else(|_| format!("https://{}-aiplatform.googleapis.com", location));
 
         let client = Client::builder()
             .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
@@ -126,6 +133,7 @@ impl GcpVertexAIProvider {
             location,
             model,
             retry_config,
+            context_caches: ContextCaches::new(),
         })
     }
 
@@ -191,10 +199,12 @@ impl GcpVertexAIProvider {
     /// # Arguments
     /// * `provider` - The model provider (Anthropic or Google)
     /// * `location` - The GCP location for model deployment
+    /// * `stream` - Whether the response should be streamed as server-sent events
     fn build_request_url(
         &self,
         provider: ModelProvider,
         location: &str,
+        stream: bool,
     ) -> Result<Url, GcpVertexAIError> {
         // Create host URL for the specified location
         let host_url = if self.location == location {
@@ -208,9 +218,10 @@ impl GcpVertexAIProvider {
             Url::parse(host_url).map_err(|e| GcpVertexAIError::InvalidUrl(e.to_string()))?;
 
         // Determine endpoint based on provider type
-        let endpoint = match provider {
-            ModelProvider::Anthropic => "streamRawPredict",
-            ModelProvider::Google => "generateContent",
+        let endpoint = match (provider, stream) {
+            (ModelProvider::Anthropic, _) => "streamRawPredict",
+            (ModelProvider::Google, false) => "generateContent",
+            (ModelProvider::Google, true) => "streamGenerateContent?alt=sse",
         };
 
         // Construct path for URL
@@ -235,16 +246,20 @@ impl GcpVertexAIProvider {
     /// * `payload` - The request payload to send
     /// * `context` - Request context containing model information
     /// * `location` - The GCP location for the request
-    async fn post_with_location(
+    /// * `stream` - Whether the response should be streamed as server-sent events
+    async fn send_with_location(
         &self,
         payload: &Value,
         context: &RequestContext,
         location: &str,
-    ) -> Result<Value, ProviderError> {
+        stream: bool,
+    ) -> Result<Response, ProviderError> {
         let url = self
-            .build_request_url(context.provider(), location)
+            .build_request_url(context.provider(), location, stream)
             .map_err(|e| ProviderError::RequestFailed(e.to_string()))?;
 
+        let retry_config = effective_retry_config(retry_config.clone());
+
         // Initialize separate counters for different error types
         let mut rate_limit_attempts = 0;
         let mut overloaded_attempts = 0;
@@ -252,12 +267,12 @@ impl GcpVertexAIProvider {
 
         loop {
             // Check if we've exceeded max retries
-            if rate_limit_attempts > self.retry_config.max_retries
-                && overloaded_attempts > self.retry_config.max_retries
+            if rate_limit_attempts > retry_config.max_retries
+                && overloaded_attempts > retry_config.max_retries
             {
                 let error_msg = format!(
                     "Exceeded maximum retry attempts ({}) for rate limiting errors",
-                    self.retry_config.max_retries
+                    retry_config.max_retries
                 );
                 tracing::error!("{}", error_msg);
                 return Err(last_error.unwrap_or(ProviderError::RateLimitExceeded(error_msg)));
@@ -286,10 +301,10 @@ impl GcpVertexAIProvider {
                 status if status == StatusCode::TOO_MANY_REQUESTS => {
                     rate_limit_attempts += 1;
 
-                    if rate_limit_attempts > self.retry_config.max_retries {
+                    if rate_limit_attempts > retry_config.max_retries {
                         let error_msg = format!(
                             "Exceeded maximum retry attempts ({}) for rate limiting (429) errors",
-                            self.retry_config.max_retries
+                            retry_config.max_retries
                         );
                         tracing::error!("{}", error_msg);
                         return Err(
@@ -314,7 +329,7 @@ impl GcpVertexAIProvider {
                     tracing::warn!(
                         "Rate limit exceeded error (429) (attempt {}/{}): {}. Retrying after backoff...",
                         rate_limit_attempts,
-                        self.retry_config.max_retries,
+                        retry_config.max_retries,
                         error_message
                     );
 
@@ -322,17 +337,17 @@ impl GcpVertexAIProvider {
                     last_error = Some(ProviderError::RateLimitExceeded(error_message));
 
                     // Calculate and apply the backoff delay
-                    let delay = self.retry_config.delay_for_attempt(rate_limit_attempts);
+                    let delay = retry_config.delay_for_attempt(rate_limit_attempts);
                     tracing::info!("Backing off for {:?} before retry (rate limit 429)", delay);
                     sleep(delay).await;
                 }
                 status if status == *STATUS_API_OVERLOADED => {
                     overloaded_attempts += 1;
 
-                    if overloaded_attempts > self.retry_config.max_retries {
+                    if overloaded_attempts > retry_config.max_retries {
                         let error_msg = format!(
                             "Exceeded maximum retry attempts ({}) for API overloaded (529) errors",
-                            self.retry_config.max_retries
+                            retry_config.max_retries
                         );
                         tracing::error!("{}", error_msg);
                         return Err(
@@ -349,7 +364,7 @@ impl GcpVertexAIProvider {
                     tracing::warn!(
                         "API overloaded error (529) (attempt {}/{}): {}. Retrying after backoff...",
                         overloaded_attempts,
-                        self.retry_config.max_retries,
+                        retry_config.max_retries,
                         error_message
                     );
 
@@ -357,13 +372,14 @@ impl GcpVertexAIProvider {
                     last_error = Some(ProviderError::RateLimitExceeded(error_message));
 
                     // Calculate and apply the backoff delay
-                    let delay = self.retry_config.delay_for_attempt(overloaded_attempts);
+                    let delay = retry_config.delay_for_attempt(overloaded_attempts);
                     tracing::info!(
                         "Backing off for {:?} before retry (API overloaded 529)",
                         delay
                     );
                     sleep(delay).await;
                 }
+                StatusCode::OK => return Ok(response),
                 // For any other status codes, process normally
                 _ => {
                     let response_json = response.json::<Value>().await.map_err(|e| {
@@ -371,7 +387,6 @@ impl GcpVertexAIProvider {
                     })?;
 
                     return match status {
-                        StatusCode::OK => Ok(response_json),
                         StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
                             tracing::debug!(
                                 "Authentication failed. Status: {status}, Payload: {payload:?}"
@@ -380,6 +395,9 @@ impl GcpVertexAIProvider {
                                 "Authentication failed: {response_json:?}"
                             )))
                         }
+                        StatusCode::BAD_REQUEST if is_context_length_error(&response_json) => Err(
+                            ProviderError::ContextLengthExceeded(format!("{response_json:?}")),
+                        ),
                         _ => {
                             tracing::debug!(
                                 "Request failed. Status: {status}, Response: {response_json:?}"
@@ -399,18 +417,24 @@ impl GcpVertexAIProvider {
     /// # Arguments
     /// * `payload` - The request payload to send
     /// * `context` - Request context containing model information
-    async fn post(
+    /// * `stream` - Whether the response should be streamed as server-sent events
+    async fn send(
         &self,
         payload: &Value,
         context: &RequestContext,
-    ) -> Result<Value, ProviderError> {
+        stream: bool,
+    ) -> Result<Response, ProviderError> {
         // Try with user-specified location first
         let result = self
-            .post_with_location(payload, context, &self.location)
+            .send_with_location(payload, context, &self.location, stream)
             .await;
 
-        // If location is already the known location for the model or request succeeded, return result
-        if self.location == context.model.known_location().to_string() || result.is_ok() {
+        // If location is already the known location for the model or request succeeded, return result.
+        // Requests using a context cache must stay in the location the cache was created in.
+        if self.location == context.model.known_location().to_string()
+            || result.is_ok()
+            || payload.get("cachedContent").is_some()
+        {
             return result;
         }
 
@@ -425,13 +449,152 @@ impl GcpVertexAIProvider {
                     "Trying known location {known_location} for {model_name} instead of {configured_location}: {msg}"
                 );
 
-                self.post_with_location(payload, context, &known_location)
+                self.send_with_location(payload, context, &known_location, stream)
                     .await
             }
             // For any other error, return the original result
             _ => result,
         }
     }
+
+    /// Creates a context cache in the configured location and returns its resource name.
+    ///
+    /// # Arguments
+    /// * `content` - The body of the `cachedContents` resource
+    async fn create_context_cache(&self, content: &Value) -> Result<String, ProviderError> {
+        let path = format!(
+            "v1/projects/{}/locations/{}/cachedContents",
+            self.project_id, self.location
+        );
+        let url = Url::parse(&self.host)
+            .and_then(|url| url.join(&path))
+            .map_err(|e| ProviderError::RequestFailed(e.to_string()))?;
+        let auth_header = self
+            .get_auth_header()
+            .await
+            .map_err(|e| ProviderError::Authentication(e.to_string()))?;
+
+        let response = self
+            .client
+            .post(url)
+            .json(content)
+            .header("Authorization", auth_header)
+            .send()
+            .await
+            .map_err(|e| ProviderError::RequestFailed(e.to_string()))?;
+        let status = response.status();
+        let cache = response
+            .json::<Value>()
+            .await
+            .map_err(|e| ProviderError::RequestFailed(format!("Failed to parse response: {e}")))?;
+
+        if !status.is_success() {
+            return Err(ProviderError::RequestFailed(format!(
+                "Request failed with status {status}: {cache:?}"
+            )));
+        }
+        cache["name"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| ProviderError::RequestFailed("Context cache has no name".to_string()))
+    }
+
+    /// Deletes a context cache created by `create_context_cache`.
+    ///
+    /// # Arguments
+    /// * `name` - The resource name of the cache
+    async fn delete_context_cache(&self, name: &str) -> Result<(), ProviderError> {
+        let url = Url::parse(&self.host)
+            .and_then(|url| url.join(&format!("v1/{name}")))
+            .map_err(|e| ProviderError::RequestFailed(e.to_string()))?;
+        let auth_header = self
+            .get_auth_header()
+            .await
+            .map_err(|e| ProviderError::Authentication(e.to_string()))?;
+
+        let response = self
+            .client
+            .delete(url)
+            .header("Authorization", auth_header)
+            .send()
+            .await
+            .map_err(|e| ProviderError::RequestFailed(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ProviderError::RequestFailed(format!(
+                "Request failed with status {status}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Moves the system instruction and tools of a Gemini request to an explicit context cache.
+    ///
+    /// Returns the cache key and the request referring to the cache, or None for Claude models
+    /// (which use cache breakpoints instead), when context caching is off, or when no cache could
+    /// be created.
+    ///
+    /// # Arguments
+    /// * `payload` - The request payload to send
+    /// * `context` - Request context containing model information
+    async fn context_cached_payload(
+        &self,
+        payload: &Value,
+        context: &RequestContext,
+    ) -> Option<(String, Value)> {
+        if context.provider() != ModelProvider::Google || !context_caching_enabled() {
+            return None;
+        }
+        let model = format!(
+            "projects/{}/locations/{}/publishers/google/models/{}",
+            self.project_id, self.location, self.model.model_name
+        );
+        let content = context_cache_content(payload, &model)?;
+        let key = cache_key(&content);
+
+        let name = self
+            .context_caches
+            .get_or_create(
+                &key,
+                |ttl| async move {
+                    let mut content = content;
+                    content["ttl"] = json!(format!("{}s", ttl.as_secs()));
+                    self.create_context_cache(&content).await
+                },
+                |name| async move { self.delete_context_cache(&name).await },
+            )
+            .await?;
+
+        Some((key, with_context_cache(payload, &name)))
+    }
+
+    /// Makes a POST request to the Vertex AI API and parses the JSON response.
+    ///
+    /// # Arguments
+    /// * `payload` - The request payload to send
+    /// * `context` - Request context containing model information
+    async fn post(
+        &self,
+        payload: &Value,
+        context: &RequestContext,
+    ) -> Result<Value, ProviderError> {
+        let response = self.send(payload, context, false).await?;
+        response
+            .json::<Value>()
+            .await
+            .map_err(|e| ProviderError::RequestFailed(format!("Failed to parse response: {e}")))
+    }
+}
+
+/// Whether a 400 response says the request did not fit the model's context window, in the
+/// words of Gemini ("exceeds the maximum number of tokens") or Claude ("prompt is too long")
+fn is_context_length_error(response: &Value) -> bool {
+    let message = response
+        .pointer("/error/message")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    message.contains("exceeds the maximum number of tokens")
+        || message.contains("prompt is too long")
 }
 
 impl_provider_default!(GcpVertexAIProvider);
@@ -523,7 +686,14 @@ impl Provider for GcpVertexAIProvider {
         let (request, context) = create_request(model_config, system, messages, tools)?;
 
         // Send request and process response
-        let response = self.post(&request, &context).await?;
+        let cached = self.context_cached_payload(&request, &context).await;
+        let response = self
+            .context_caches
+            .send_with_fallback(cached, &request, |payload| {
+                let context = &context;
+                async move { self.post(&payload, context).await }
+            })
+            .await?;
         let usage = get_usage(&response, &context)?;
 
         emit_debug_trace(model_config, &request, &response, &usage);
@@ -539,6 +709,39 @@ impl Provider for GcpVertexAIProvider {
     fn get_model_config(&self) -> ModelConfig {
         self.model.clone()
     }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    /// Streams a model interaction, as server-sent events from `streamRawPredict` for Claude
+    /// and `streamGenerateContent` for Gemini models.
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let (mut request, context) = create_request(&self.model, system, messages, tools)?;
+        if context.provider() == ModelProvider::Anthropic {
+            request["stream"] = json!(true);
+        }
+
+        let cached = self.context_cached_payload(&request, &context).await;
+        let response = self
+            .context_caches
+            .send_with_fallback(cached, &request, |payload| {
+                let context = &context;
+                async move { self.send(&payload, context, true).await }
+            })
+            .await?;
+
+        Ok(stream_messages(
+            response_to_streaming_message(response_lines(response), &context),
+            self.model.clone(),
+            request,
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -584,6 +787,24 @@ mod tests {
         assert_ne!(*STATUS_API_OVERLOADED, StatusCode::SERVICE_UNAVAILABLE);
     }
 
+    #[test]
+    fn test_context_length_errors() {
+        let gemini = json!({"error": {
+            "code": 400,
+            "message": "The input token count (1048577) exceeds the maximum number of tokens allowed (1048576).",
+            "status": "INVALID_ARGUMENT"
+        }});
+        let claude = json!({"type": "error", "error": {
+            "type": "invalid_request_error",
+            "message": "prompt is too long: 210000 tokens > 200000 maximum"
+        }});
+        let other = json!({"error": {"code": 400, "message": "Invalid JSON payload"}});
+
+        assert!(is_context_length_error(&gemini));
+        assert!(is_context_length_error(&claude));
+        assert!(!is_context_length_error(&other));
+    }
+
     #[test]
     fn test_model_provider_conversion() {
         assert_eq!(ModelProvider::Anthropic.as_str(), "anthropic");
@@ -1,16 +1,23 @@
 use super::api_client::{ApiClient, AuthMethod};
 use super::errors::ProviderError;
 use super::retry::ProviderRetry;
-use super::utils::{emit_debug_trace, handle_response_google_compat, unescape_json_values};
+use super::utils::{
+    emit_debug_trace, handle_response_google_compat, handle_status_google_compat, response_lines,
+    stream_messages, unescape_json_values,
+};
 use crate::conversation::message::Message;
 use crate::impl_provider_default;
 use crate::model::ModelConfig;
-use crate::providers::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage};
-use crate::providers::formats::google::{create_request, get_usage, response_to_message};
+use crate::providers::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage};
+use crate::providers::formats::google::{
+    context_cache_content, count_tokens_request, create_request, get_usage, response_to_message,
+    response_to_streaming_message, with_context_cache, with_response_schema,
+};
+use crate::providers::prompt_cache::{cache_key, context_caching_enabled, ContextCaches};
 use anyhow::Result;
 use async_trait::async_trait;
 use rmcp::model::Tool;
-use serde_json::Value;
+use serde_json::{json, Value};
 
 pub const GOOGLE_API_HOST: &str = "https://generativelanguage.googleapis.com";
 pub const GOOGLE_DEFAULT_MODEL: &str = "gemini-2.5-flash";
@@ -50,6 +57,8 @@ pub struct GoogleProvider {
     #[serde(skip)]
     api_client: ApiClient,
     model: ModelConfig,
+    #[serde(skip)]
+    context_caches: ContextCaches,
 }
 
 impl_provider_default!(GoogleProvider);
@@ -69,10 +78,15 @@ impl GoogleProvider {
             key: api_key,
         };
 
-        let api_client =
-            ApiClient::new(host, auth)?.with_header("Content-Type", "application/json")?;
+        let api_client = ApiClient::new(host, auth)?
+            .with_header("Content-Type", "application/json")?
+            .with_rate_limit("google");
 
-        Ok(Self { api_client, model })
+        Ok(Self {
+            api_client,
+            model,
+            context_caches: ContextCaches::new(),
+        })
     }
 
     async fn post(&self, model_name: &str, payload: &Value) -> Result<Value, ProviderError> {
@@ -80,6 +94,48 @@ impl GoogleProvider {
         let response = self.api_client.response_post(&path, payload).await?;
         handle_response_google_compat(response).await
     }
+
+    /// `payload` with its system instruction and tools moved to an explicit context cache,
+    /// along with the cache key, or None when context caching is off or no cache could be created
+    async fn context_cached_payload(
+        &self,
+        model_name: &str,
+        payload: &Value,
+    ) -> Option<(String, Value)> {
+        if !context_caching_enabled() {
+            return None;
+        }
+        let content = context_cache_content(payload, &format!("models/{}", model_name))?;
+        let key = cache_key(&content);
+
+        let name = self
+            .context_caches
+            .get_or_create(
+                &key,
+                |ttl| async move {
+                    let mut content = content;
+                    content["ttl"] = json!(format!("{}s", ttl.as_secs()));
+                    let response = self
+                        .api_client
+                        .response_post("v1beta/cachedContents", &content)
+                        .await?;
+                    let cache = handle_response_google_compat(response).await?;
+                    cache["name"].as_str().map(str::to_string).ok_or_else(|| {
+                        ProviderError::RequestFailed("Context cache has no name".to_string())
+                    })
+                },
+                |name| async move {
+                    let response = self
+                        .api_client
+                        .response_delete(&format!("v1beta/{}", name))
+                        .await?;
+                    handle_response_google_compat(response).await.map(|_| ())
+                },
+            )
+            .await?;
+
+        Some((key, with_context_cache(payload, &name)))
+    }
 }
 
 #[async_trait]
@@ -117,10 +173,17 @@ impl Provider for GoogleProvider {
         let payload = create_request(model_config, system, messages, tools)?;
 
         // Make request
+        let cached = self
+            .context_cached_payload(&model_config.model_name, &payload)
+            .await;
         let response = self
-            .with_retry(|| async {
-                let payload_clone = payload.clone();
-                self.post(&model_config.model_name, &payload_clone).await
+            .context_caches
+            .send_with_fallback(cached, &payload, |payload| async move {
+                self.with_retry(|| async {
+                    let payload_clone = payload.clone();
+                    self.post(&model_config.model_name, &payload_clone).await
+                })
+                .await
             })
             .await?;
 
@@ -136,6 +199,97 @@ impl Provider for GoogleProvider {
         Ok((message, provider_usage))
     }
 
+    fn supports_structured_output(&self) -> bool {
+        true
+    }
+
+    async fn complete_structured(
+        &self,
+        system: &str,
+        messages: &[Message],
+        schema: &Value,
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let mut payload = create_request(&self.model, system, messages, &[])?;
+        with_response_schema(&mut payload, schema);
+
+        let response = self
+            .with_retry(|| async { self.post(&self.model.model_name, &payload).await })
+            .await?;
+
+        // The JSON is in the text, so leave its escapes alone
+        let message = response_to_message(response.clone())?;
+        let usage = get_usage(&response)?;
+        let response_model = match response.get("modelVersion") {
+            Some(model_version) => model_version.as_str().unwrap_or_default().to_string(),
+            None => self.model.model_name.clone(),
+        };
+        emit_debug_trace(&self.model, &payload, &response, &usage);
+        Ok((message, ProviderUsage::new(response_model, usage)))
+    }
+
+    async fn count_tokens(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<Option<usize>, ProviderError> {
+        let payload = create_request(&self.model, system, messages, tools)?;
+        let model_name = &self.model.model_name;
+        let request = count_tokens_request(&payload, &format!("models/{}", model_name));
+
+        let path = format!("v1beta/models/{}:countTokens", model_name);
+        let response = self
+            .api_client
+            .request(&path)
+            .without_rate_limit()
+            .response_post(&request)
+            .await?;
+        let response = handle_response_google_compat(response).await?;
+        Ok(response["totalTokens"]
+            .as_u64()
+            .map(|tokens| tokens as usize))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let payload = create_request(&self.model, system, messages, tools)?;
+        let path = format!(
+            "v1beta/models/{}:streamGenerateContent?alt=sse",
+            self.model.model_name
+        );
+
+        let cached = self
+            .context_cached_payload(&self.model.model_name, &payload)
+            .await;
+        let response = self
+            .context_caches
+            .send_with_fallback(cached, &payload, |payload| {
+                let path = &path;
+                async move {
+                    self.with_retry(|| async {
+                        let response = self.api_client.response_post(path, &payload).await?;
+                        handle_status_google_compat(response).await
+                    })
+                    .await
+                }
+            })
+            .await?;
+
+        Ok(stream_messages(
+            response_to_streaming_message(response_lines(response)),
+            self.model.clone(),
+            payload,
+        ))
+    }
+
     /// Fetch supported models from Google Generative Language API; returns Err on failure, Ok(None) if not present
     async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
         let response = self.api_client.response_get("v1beta/models").await?;
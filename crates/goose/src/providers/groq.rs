@@ -1,16 +1,23 @@
 use super::api_client::{ApiClient, AuthMethod};
 use super::errors::ProviderError;
 use super::retry::ProviderRetry;
-use super::utils::{get_model, handle_response_openai_compat};
+use super::utils::{
+    get_model, handle_response_openai_compat, handle_status_openai_compat, response_lines,
+    stream_messages, ImageFormat,
+};
 use crate::conversation::message::Message;
 use crate::impl_provider_default;
 use crate::model::ModelConfig;
-use crate::providers::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
-use crate::providers::formats::openai::{create_request, get_usage, response_to_message};
+use crate::providers::base::{
+    ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage,
+};
+use crate::providers::formats::openai::{
+    create_request, get_usage, response_to_message, response_to_streaming_message,
+};
 use anyhow::Result;
 use async_trait::async_trait;
 use rmcp::model::Tool;
-use serde_json::Value;
+use serde_json::{json, Value};
 
 pub const GROQ_API_HOST: &str = "https://api.groq.com";
 pub const GROQ_DEFAULT_MODEL: &str = "moonshotai/kimi-k2-instruct";
@@ -41,7 +48,7 @@ impl GroqProvider {
             .unwrap_or_else(|_| GROQ_API_HOST.to_string());
 
         let auth = AuthMethod::BearerToken(api_key);
-        let api_client = ApiClient::new(host, auth)?;
+        let api_client = ApiClient::new(host, auth)?.with_rate_limit("groq");
 
         Ok(Self { api_client, model })
     }
@@ -87,13 +94,7 @@ impl Provider for GroqProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<(Message, ProviderUsage), ProviderError> {
-        let payload = create_request(
-            model_config,
-            system,
-            messages,
-            tools,
-            &super::utils::ImageFormat::OpenAi,
-        )?;
+        let payload = create_request(model_config, system, messages, tools, &ImageFormat::OpenAi)?;
 
         let response = self.with_retry(|| self.post(payload.clone())).await?;
 
@@ -107,6 +108,40 @@ impl Provider for GroqProvider {
         Ok((message, ProviderUsage::new(response_model, usage)))
     }
 
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let mut payload =
+            create_request(&self.model, system, messages, tools, &ImageFormat::OpenAi)?;
+        payload["stream"] = json!(true);
+        payload["stream_options"] = json!({
+            "include_usage": true,
+        });
+
+        let response = self
+            .with_retry(|| async {
+                let response = self
+                    .api_client
+                    .response_post("openai/v1/chat/completions", &payload)
+                    .await?;
+                handle_status_openai_compat(response).await
+            })
+            .await?;
+
+        Ok(stream_messages(
+            response_to_streaming_message(response_lines(response)),
+            self.model.clone(),
+            payload,
+        ))
+    }
+
     /// Fetch supported models from Groq; returns Err on failure, Ok(None) if no models found
     async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
         let response = self
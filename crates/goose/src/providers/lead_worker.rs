@@ -23,6 +23,8 @@ pub struct LeadWorkerProvider {
     fallback_turns: usize,
     in_fallback_mode: Arc<Mutex<bool>>,
     fallback_remaining: Arc<Mutex<usize>>,
+    lead_provider_name: Option<String>,
+    worker_provider_name: Option<String>,
 }
 
 impl LeadWorkerProvider {
@@ -47,6 +49,8 @@ impl LeadWorkerProvider {
             fallback_turns: 2,               // Use lead model for 2 turns when in fallback mode
             in_fallback_mode: Arc::new(Mutex::new(false)),
             fallback_remaining: Arc::new(Mutex::new(0)),
+            lead_provider_name: None,
+            worker_provider_name: None,
         }
     }
 
@@ -75,6 +79,8 @@ impl LeadWorkerProvider {
             fallback_turns,
             in_fallback_mode: Arc::new(Mutex::new(false)),
             fallback_remaining: Arc::new(Mutex::new(0)),
+            lead_provider_name: None,
+            worker_provider_name: None,
         }
     }
 
@@ -105,6 +111,13 @@ impl LeadWorkerProvider {
         *self.in_fallback_mode.lock().await
     }
 
+    /// Names of the lead and worker providers, reported in the usage of the calls they serve
+    pub fn with_provider_names(mut self, lead: String, worker: String) -> Self {
+        self.lead_provider_name = Some(lead);
+        self.worker_provider_name = Some(worker);
+        self
+    }
+
     /// Get the currently active provider based on turn count and fallback state
     async fn get_active_provider(&self) -> Arc<dyn Provider> {
         let count = *self.turn_count.lock().await;
@@ -379,6 +392,8 @@ impl Provider for LeadWorkerProvider {
 
         // Make the completion request
         let result = provider.complete(system, messages, tools).await;
+        // Failures are retried with the lead provider below
+        let served_by_lead = result.is_err() || Arc::ptr_eq(&provider, &self.lead_provider);
 
         // For technical failures, try with default model (lead provider) instead
         let final_result = match &result {
@@ -407,7 +422,12 @@ impl Provider for LeadWorkerProvider {
         // Handle the result and update tracking (only for successful completions)
         self.handle_completion_result(&final_result).await;
 
-        final_result
+        let provider_name = if served_by_lead {
+            self.lead_provider_name.as_deref()
+        } else {
+            self.worker_provider_name.as_deref()
+        };
+        final_result.map(|(message, usage)| (message, usage.served_by(provider_name)))
     }
 
     async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
@@ -446,6 +466,49 @@ impl Provider for LeadWorkerProvider {
         }
     }
 
+    fn supports_structured_output(&self) -> bool {
+        self.lead_provider.supports_structured_output()
+            || self.worker_provider.supports_structured_output()
+    }
+
+    async fn complete_structured(
+        &self,
+        system: &str,
+        messages: &[Message],
+        schema: &serde_json::Value,
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        // Prefer the lead model for structured output, as it is usually the more capable one
+        if self.lead_provider.supports_structured_output() {
+            let (message, usage) = self
+                .lead_provider
+                .complete_structured(system, messages, schema)
+                .await?;
+            Ok((message, usage.served_by(self.lead_provider_name.as_deref())))
+        } else {
+            let (message, usage) = self
+                .worker_provider
+                .complete_structured(system, messages, schema)
+                .await?;
+            Ok((
+                message,
+                usage.served_by(self.worker_provider_name.as_deref()),
+            ))
+        }
+    }
+
+    async fn count_tokens(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<Option<usize>, ProviderError> {
+        // Count with the tokenizer of the model that will get the next turn
+        self.get_active_provider()
+            .await
+            .count_tokens(system, messages, tools)
+            .await
+    }
+
     /// Check if this provider is a LeadWorkerProvider
     fn as_lead_worker(&self) -> Option<&dyn LeadWorkerProviderTrait> {
         Some(self)
@@ -512,12 +575,14 @@ mod tests {
             model_config: ModelConfig::new_or_fail("worker-model"),
         });
 
-        let provider = LeadWorkerProvider::new(lead_provider, worker_provider, Some(3));
+        let provider = LeadWorkerProvider::new(lead_provider, worker_provider, Some(3))
+            .with_provider_names("anthropic".to_string(), "openai".to_string());
 
         // First three turns should use lead provider
         for i in 0..3 {
             let (_message, usage) = provider.complete("system", &[], &[]).await.unwrap();
             assert_eq!(usage.model, "lead");
+            assert_eq!(usage.provider.as_deref(), Some("anthropic"));
             assert_eq!(provider.get_turn_count().await, i + 1);
             assert!(!provider.is_in_fallback_mode().await);
         }
@@ -526,6 +591,7 @@ mod tests {
         for i in 3..6 {
             let (_message, usage) = provider.complete("system", &[], &[]).await.unwrap();
             assert_eq!(usage.model, "worker");
+            assert_eq!(usage.provider.as_deref(), Some("openai"));
             assert_eq!(provider.get_turn_count().await, i + 1);
             assert!(!provider.is_in_fallback_mode().await);
         }
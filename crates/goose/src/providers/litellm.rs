@@ -4,11 +4,14 @@ use serde_json::{json, Value};
 use std::collections::HashMap;
 
 use super::api_client::{ApiClient, AuthMethod};
-use super::base::{ConfigKey, ModelInfo, Provider, ProviderMetadata, ProviderUsage};
+use super::base::{ConfigKey, MessageStream, ModelInfo, Provider, ProviderMetadata, ProviderUsage};
 use super::embedding::EmbeddingCapable;
 use super::errors::ProviderError;
 use super::retry::ProviderRetry;
-use super::utils::{emit_debug_trace, get_model, handle_response_openai_compat, ImageFormat};
+use super::utils::{
+    emit_debug_trace, get_model, handle_response_openai_compat, handle_status_openai_compat,
+    response_lines, stream_messages, ImageFormat,
+};
 use crate::conversation::message::Message;
 use crate::impl_provider_default;
 use crate::model::ModelConfig;
@@ -53,7 +56,8 @@ impl LiteLLMProvider {
         };
 
         let mut api_client =
-            ApiClient::with_timeout(host, auth, std::time::Duration::from_secs(timeout_secs))?;
+            ApiClient::with_timeout(host, auth, std::time::Duration::from_secs(timeout_secs))?
+                .with_rate_limit("litellm");
 
         if let Some(headers) = custom_headers {
             let mut header_map = reqwest::header::HeaderMap::new();
@@ -194,6 +198,49 @@ impl Provider for LiteLLMProvider {
         Ok((message, ProviderUsage::new(response_model, usage)))
     }
 
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let mut payload = super::formats::openai::create_request(
+            &self.model,
+            system,
+            messages,
+            tools,
+            &ImageFormat::OpenAi,
+        )?;
+
+        if self.supports_cache_control() {
+            payload = update_request_for_cache_control(&payload);
+        }
+        payload["stream"] = json!(true);
+        payload["stream_options"] = json!({
+            "include_usage": true,
+        });
+
+        let response = self
+            .with_retry(|| async {
+                let response = self
+                    .api_client
+                    .response_post(&self.base_path, &payload)
+                    .await?;
+                handle_status_openai_compat(response).await
+            })
+            .await?;
+
+        Ok(stream_messages(
+            super::formats::openai::response_to_streaming_message(response_lines(response)),
+            self.model.clone(),
+            payload,
+        ))
+    }
+
     fn supports_embeddings(&self) -> bool {
         true
     }
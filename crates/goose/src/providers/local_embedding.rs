@@ -0,0 +1,317 @@
+//! Sentence embeddings computed locally on the CPU with a small BERT model, so that
+//! embedding-based features keep working offline and with providers that have no
+//! embeddings API.
+//!
+//! The model is a Hugging Face repository with `config.json`, `tokenizer.json` and
+//! `model.safetensors`, downloaded on first use and cached under the goose cache directory.
+//! Only built with the `local-embeddings` feature.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config as BertConfig, DTYPE};
+use etcetera::{choose_app_strategy, AppStrategy};
+use futures::StreamExt;
+use tokenizers::{PaddingParams, Tokenizer, TruncationParams};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::OnceCell;
+
+use super::embedding::EmbeddingCapable;
+use crate::config::{Config, APP_STRATEGY};
+
+pub const DEFAULT_LOCAL_EMBEDDING_MODEL: &str = "sentence-transformers/all-MiniLM-L6-v2";
+pub const DEFAULT_LOCAL_EMBEDDING_HOST: &str = "https://huggingface.co";
+
+const MODEL_FILES: [&str; 3] = ["config.json", "tokenizer.json", "model.safetensors"];
+
+pub struct LocalEmbeddingProvider {
+    model_id: String,
+    host: String,
+    cache_dir: PathBuf,
+    model: OnceCell<Arc<LocalEmbeddingModel>>,
+}
+
+impl LocalEmbeddingProvider {
+    pub fn new(model_id: impl Into<String>, host: impl Into<String>, cache_dir: PathBuf) -> Self {
+        Self {
+            model_id: model_id.into(),
+            host: host.into(),
+            cache_dir,
+            model: OnceCell::new(),
+        }
+    }
+
+    /// Uses the model in `GOOSE_EMBEDDING_MODEL`, downloaded from
+    /// `GOOSE_LOCAL_EMBEDDING_HOST` (a Hugging Face mirror)
+    pub fn from_env() -> Result<Self> {
+        let config = Config::global();
+        let model_id: String = config
+            .get_param("GOOSE_EMBEDDING_MODEL")
+            .unwrap_or_else(|_| DEFAULT_LOCAL_EMBEDDING_MODEL.to_string());
+        let host: String = config
+            .get_param("GOOSE_LOCAL_EMBEDDING_HOST")
+            .unwrap_or_else(|_| DEFAULT_LOCAL_EMBEDDING_HOST.to_string());
+        let cache_dir = choose_app_strategy(APP_STRATEGY.clone())
+            .context("goose requires a home dir")?
+            .in_cache_dir("models");
+        Ok(Self::new(model_id, host, cache_dir))
+    }
+
+    /// Where the model files are cached
+    pub fn model_dir(&self) -> PathBuf {
+        self.cache_dir.join(self.model_id.replace('/', "--"))
+    }
+
+    /// Downloads the model files that are not cached yet
+    pub async fn download(&self) -> Result<PathBuf> {
+        let dir = self.model_dir();
+        tokio::fs::create_dir_all(&dir).await?;
+
+        let client = reqwest::Client::new();
+        for file in MODEL_FILES {
+            let path = dir.join(file);
+            if path.exists() {
+                continue;
+            }
+
+            let url = format!(
+                "{}/{}/resolve/main/{}",
+                self.host.trim_end_matches('/'),
+                self.model_id,
+                file
+            );
+            tracing::info!("Downloading {}", url);
+            let response = client.get(&url).send().await?;
+            if !response.status().is_success() {
+                return Err(anyhow!("Failed to download {}: {}", url, response.status()));
+            }
+
+            // Write to a temporary file first, so an interrupted download is not cached
+            let partial = dir.join(format!("{}.part", file));
+            let mut out = tokio::fs::File::create(&partial).await?;
+            let mut body = response.bytes_stream();
+            while let Some(chunk) = body.next().await {
+                out.write_all(&chunk?).await?;
+            }
+            out.flush().await?;
+            tokio::fs::rename(&partial, &path).await?;
+        }
+        Ok(dir)
+    }
+
+    async fn model(&self) -> Result<Arc<LocalEmbeddingModel>> {
+        self.model
+            .get_or_try_init(|| async {
+                let dir = self.download().await?;
+                let model =
+                    tokio::task::spawn_blocking(move || LocalEmbeddingModel::load(&dir)).await??;
+                Ok(Arc::new(model))
+            })
+            .await
+            .cloned()
+    }
+}
+
+#[async_trait]
+impl EmbeddingCapable for LocalEmbeddingProvider {
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(vec![]);
+        }
+        let model = self.model().await?;
+        tokio::task::spawn_blocking(move || model.embed(&texts)).await?
+    }
+}
+
+struct LocalEmbeddingModel {
+    bert: BertModel,
+    tokenizer: Tokenizer,
+}
+
+impl LocalEmbeddingModel {
+    fn load(dir: &Path) -> Result<Self> {
+        let config: BertConfig =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("config.json"))?)?;
+
+        let mut tokenizer = Tokenizer::from_file(dir.join("tokenizer.json"))
+            .map_err(|e| anyhow!("Failed to load tokenizer: {}", e))?;
+        let max_length = tokenizer
+            .get_truncation()
+            .map_or(config.max_position_embeddings, |truncation| {
+                truncation.max_length.min(config.max_position_embeddings)
+            });
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length,
+                ..Default::default()
+            }))
+            .map_err(|e| anyhow!("Failed to configure tokenizer: {}", e))?;
+        tokenizer.with_padding(Some(PaddingParams::default()));
+
+        let weights = dir.join("model.safetensors");
+        // SAFETY: the weights file is only written by `download`, before it is memory mapped
+        let vb = unsafe { VarBuilder::from_mmaped_safetensors(&[weights], DTYPE, &Device::Cpu)? };
+        let bert = BertModel::load(vb, &config)?;
+
+        Ok(Self { bert, tokenizer })
+    }
+
+    /// Mean pooled and L2 normalized sentence embeddings
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts.to_vec(), true)
+            .map_err(|e| anyhow!("Failed to tokenize: {}", e))?;
+
+        let device = &self.bert.device;
+        let stack = |field: fn(&tokenizers::Encoding) -> &[u32]| -> Result<Tensor> {
+            let rows = encodings
+                .iter()
+                .map(|encoding| Tensor::new(field(encoding), device))
+                .collect::<candle_core::Result<Vec<_>>>()?;
+            Ok(Tensor::stack(&rows, 0)?)
+        };
+        let input_ids = stack(|encoding| encoding.get_ids())?;
+        let token_type_ids = stack(|encoding| encoding.get_type_ids())?;
+        let attention_mask = stack(|encoding| encoding.get_attention_mask())?;
+
+        let hidden = self
+            .bert
+            .forward(&input_ids, &token_type_ids, Some(&attention_mask))?;
+
+        // Average the token embeddings, leaving out padding
+        let mask = attention_mask.to_dtype(DType::F32)?.unsqueeze(2)?;
+        let summed = hidden.broadcast_mul(&mask)?.sum(1)?;
+        let pooled = summed.broadcast_div(&mask.sum(1)?)?;
+        let norms = pooled.sqr()?.sum_keepdim(1)?.sqrt()?;
+        Ok(pooled.broadcast_div(&norms)?.to_vec2::<f32>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candle_nn::VarMap;
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const MODEL_ID: &str = "test/tiny-bert";
+
+    /// Writes a tiny randomly initialized BERT model, with a vocabulary of a few words
+    fn write_tiny_model(dir: &Path) {
+        let config = json!({
+            "vocab_size": 8,
+            "hidden_size": 8,
+            "num_hidden_layers": 1,
+            "num_attention_heads": 2,
+            "intermediate_size": 16,
+            "hidden_act": "gelu",
+            "hidden_dropout_prob": 0.0,
+            "max_position_embeddings": 32,
+            "type_vocab_size": 2,
+            "initializer_range": 0.02,
+            "layer_norm_eps": 1e-12,
+            "pad_token_id": 0
+        });
+        std::fs::write(dir.join("config.json"), config.to_string()).unwrap();
+
+        let tokenizer = json!({
+            "version": "1.0",
+            "truncation": null,
+            "padding": null,
+            "added_tokens": [],
+            "normalizer": {
+                "type": "BertNormalizer",
+                "clean_text": true,
+                "handle_chinese_chars": true,
+                "strip_accents": null,
+                "lowercase": true
+            },
+            "pre_tokenizer": {"type": "BertPreTokenizer"},
+            "post_processor": {"type": "BertProcessing", "sep": ["[SEP]", 3], "cls": ["[CLS]", 2]},
+            "decoder": null,
+            "model": {
+                "type": "WordPiece",
+                "unk_token": "[UNK]",
+                "continuing_subword_prefix": "##",
+                "max_input_chars_per_word": 100,
+                "vocab": {
+                    "[PAD]": 0, "[UNK]": 1, "[CLS]": 2, "[SEP]": 3,
+                    "read": 4, "write": 5, "a": 6, "file": 7
+                }
+            }
+        });
+        std::fs::write(dir.join("tokenizer.json"), tokenizer.to_string()).unwrap();
+
+        let bert_config: BertConfig = serde_json::from_value(config).unwrap();
+        let varmap = VarMap::new();
+        let vb = VarBuilder::from_varmap(&varmap, DTYPE, &Device::Cpu);
+        BertModel::load(vb, &bert_config).unwrap();
+        varmap.save(dir.join("model.safetensors")).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_downloads_once_and_embeds() {
+        let source = tempfile::tempdir().unwrap();
+        write_tiny_model(source.path());
+
+        let server = MockServer::start().await;
+        for file in MODEL_FILES {
+            let body = std::fs::read(source.path().join(file)).unwrap();
+            Mock::given(method("GET"))
+                .and(path(format!("/{}/resolve/main/{}", MODEL_ID, file)))
+                .respond_with(ResponseTemplate::new(200).set_body_bytes(body))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let cache = tempfile::tempdir().unwrap();
+        let provider = LocalEmbeddingProvider::new(MODEL_ID, server.uri(), cache.path().into());
+        let embeddings = provider
+            .create_embeddings(vec!["read a file".to_string(), "write".to_string()])
+            .await
+            .unwrap();
+
+        assert_eq!(embeddings.len(), 2);
+        for embedding in &embeddings {
+            assert_eq!(embedding.len(), 8);
+            let norm: f32 = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+            assert!((norm - 1.0).abs() < 1e-4);
+        }
+        assert_ne!(embeddings[0], embeddings[1]);
+        assert!(provider.model_dir().join("model.safetensors").exists());
+
+        // A second provider on the same cache loads from disk without downloading
+        let cached = LocalEmbeddingProvider::new(MODEL_ID, server.uri(), cache.path().into());
+        let again = cached
+            .create_embeddings(vec!["read a file".to_string()])
+            .await
+            .unwrap();
+        assert!(again[0]
+            .iter()
+            .zip(&embeddings[0])
+            .all(|(a, b)| (a - b).abs() < 1e-5));
+    }
+
+    #[tokio::test]
+    async fn test_failed_download_is_not_cached() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let cache = tempfile::tempdir().unwrap();
+        let provider = LocalEmbeddingProvider::new(MODEL_ID, server.uri(), cache.path().into());
+        let result = provider.create_embeddings(vec!["hello".to_string()]).await;
+
+        assert!(result.is_err());
+        assert!(!provider.model_dir().join("config.json").exists());
+    }
+}
@@ -10,6 +10,7 @@ pub mod databricks;
 pub mod embedding;
 pub mod errors;
 mod factory;
+pub mod fallback;
 pub mod formats;
 mod gcpauth;
 pub mod gcpvertexai;
@@ -19,15 +20,20 @@ pub mod google;
 pub mod groq;
 pub mod lead_worker;
 pub mod litellm;
+#[cfg(feature = "local-embeddings")]
+pub mod local_embedding;
 pub mod oauth;
 pub mod ollama;
 pub mod openai;
 pub mod openrouter;
 pub mod pricing;
+pub mod prompt_cache;
 pub mod provider_registry;
-mod retry;
+pub mod rate_limit;
+pub mod retry;
 pub mod sagemaker_tgi;
 pub mod snowflake;
+pub mod structured_output;
 pub mod testprovider;
 pub mod tetrate;
 pub mod toolshim;
@@ -1,30 +1,33 @@
 use super::api_client::{ApiClient, AuthMethod};
-use super::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::base::{
+    model_supports_images, ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage,
+    SupportedModel, Usage,
+};
 use super::errors::ProviderError;
 use super::retry::ProviderRetry;
-use super::utils::{get_model, handle_response_openai_compat, handle_status_openai_compat};
+use super::utils::{
+    get_model, handle_response_openai_compat, handle_status_openai_compat, response_lines,
+    stream_messages,
+};
 use crate::config::custom_providers::CustomProviderConfig;
 use crate::conversation::message::Message;
 use crate::conversation::Conversation;
 use crate::impl_provider_default;
 use crate::model::ModelConfig;
-use crate::providers::formats::openai::{
-    create_request, get_usage, response_to_message, response_to_streaming_message,
+use crate::providers::formats::ollama::{
+    create_request, response_to_openai, response_to_streaming_message,
 };
+use crate::providers::formats::openai::{get_usage, response_to_message};
 use crate::utils::safe_truncate;
 use anyhow::Result;
-use async_stream::try_stream;
 use async_trait::async_trait;
-use futures::TryStreamExt;
+use futures::StreamExt;
 use regex::Regex;
 use rmcp::model::Tool;
+use serde::Deserialize;
 use serde_json::{json, Value};
-use std::io;
 use std::time::Duration;
-use tokio::pin;
-use tokio_stream::StreamExt;
-use tokio_util::codec::{FramedRead, LinesCodec};
-use tokio_util::io::StreamReader;
+use tokio::sync::OnceCell;
 use url::Url;
 
 pub const OLLAMA_HOST: &str = "localhost";
@@ -34,6 +37,21 @@ pub const OLLAMA_DEFAULT_MODEL: &str = "qwen2.5";
 // Ollama can run many models, we only provide the default
 pub const OLLAMA_KNOWN_MODELS: &[&str] = &[OLLAMA_DEFAULT_MODEL];
 pub const OLLAMA_DOC_URL: &str = "https://ollama.com/library";
+// Pulling a model can take much longer than a completion
+pub const OLLAMA_PULL_TIMEOUT: u64 = 6 * 60 * 60; // seconds
+                                                  // Largest context window asked for when using the one the model reports, as the memory
+                                                  // Ollama reserves grows with it; a configured context limit is used as is
+pub const OLLAMA_MAX_NUM_CTX: usize = 32_768;
+
+/// A progress update from Ollama while it pulls a model
+#[derive(Debug, Clone, Deserialize)]
+pub struct OllamaPullProgress {
+    pub status: String,
+    /// The layer being downloaded, if any
+    pub digest: Option<String>,
+    pub total: Option<u64>,
+    pub completed: Option<u64>,
+}
 
 #[derive(serde::Serialize)]
 pub struct OllamaProvider {
@@ -41,6 +59,9 @@ pub struct OllamaProvider {
     api_client: ApiClient,
     model: ModelConfig,
     supports_streaming: bool,
+    /// What `/api/show` reports about the model, kept once a lookup succeeds
+    #[serde(skip)]
+    model_details: OnceCell<SupportedModel>,
 }
 
 impl_provider_default!(OllamaProvider);
@@ -88,6 +109,7 @@ impl OllamaProvider {
             api_client,
             model,
             supports_streaming: true,
+            model_details: OnceCell::new(),
         })
     }
 
@@ -124,16 +146,126 @@ impl OllamaProvider {
             api_client,
             model,
             supports_streaming: config.supports_streaming.unwrap_or(true),
+            model_details: OnceCell::new(),
         })
     }
 
     async fn post(&self, payload: &Value) -> Result<Value, ProviderError> {
+        let response = self.api_client.response_post("api/chat", payload).await?;
+        let response = handle_response_openai_compat(response).await?;
+        Ok(response_to_openai(&response))
+    }
+
+    /// The context length and capabilities Ollama reports for a local model
+    pub async fn show_model(&self, name: &str) -> Result<SupportedModel, ProviderError> {
         let response = self
             .api_client
-            .response_post("v1/chat/completions", payload)
+            .response_post("api/show", &json!({ "model": name }))
             .await?;
-        handle_response_openai_compat(response).await
+        let response = handle_response_openai_compat(response).await?;
+        Ok(parse_model_details(name, &response))
     }
+
+    /// Pulls a model from the Ollama library, reporting progress as it downloads
+    pub async fn pull_model(
+        &self,
+        name: &str,
+        mut on_progress: impl FnMut(&OllamaPullProgress),
+    ) -> Result<(), ProviderError> {
+        let response = self
+            .api_client
+            .request("api/pull")
+            .timeout(Duration::from_secs(OLLAMA_PULL_TIMEOUT))
+            .response_post(&json!({ "model": name, "stream": true }))
+            .await?;
+        let response = handle_status_openai_compat(response).await?;
+
+        let mut lines = response_lines(response);
+        while let Some(line) = lines.next().await {
+            let line = line.map_err(|e| {
+                ProviderError::RequestFailed(format!("Failed to read pull progress: {}", e))
+            })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let update: Value = serde_json::from_str(&line).map_err(|e| {
+                ProviderError::RequestFailed(format!("Invalid pull progress: {}", e))
+            })?;
+            if let Some(error) = update.get("error").and_then(|e| e.as_str()) {
+                return Err(ProviderError::RequestFailed(format!(
+                    "Failed to pull {}: {}",
+                    name, error
+                )));
+            }
+            if let Ok(progress) = serde_json::from_value::<OllamaPullProgress>(update) {
+                on_progress(&progress);
+            }
+        }
+        Ok(())
+    }
+
+    /// The model config, with `/api/show` looked up to fill in the context length. Only a
+    /// successful lookup is cached, so a failed one is tried again on the next request.
+    async fn resolved_model(&self) -> (ModelConfig, Option<usize>) {
+        let details = self
+            .model_details
+            .get_or_try_init(|| self.show_model(&self.model.model_name))
+            .await
+            .inspect_err(|e| tracing::debug!("Failed to look up {}: {}", self.model.model_name, e))
+            .ok();
+        let mut model = self.model.clone();
+        let num_ctx = apply_reported_context(&mut model, details);
+        (model, num_ctx)
+    }
+}
+
+/// Reads the context length and capabilities out of an `/api/show` response
+fn parse_model_details(name: &str, response: &Value) -> SupportedModel {
+    // The context length is stored under the model architecture, e.g. `llama.context_length`
+    let context_limit = response
+        .get("model_info")
+        .and_then(|info| info.as_object())
+        .and_then(|info| {
+            info.iter()
+                .find(|(key, _)| key.ends_with(".context_length"))
+                .and_then(|(_, value)| value.as_u64())
+        })
+        .map(|limit| limit as usize);
+    let capabilities = response
+        .get("capabilities")
+        .and_then(|capabilities| capabilities.as_array())
+        .map(|capabilities| {
+            capabilities
+                .iter()
+                .filter_map(|capability| capability.as_str())
+                .filter(|capability| *capability != "completion")
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    SupportedModel {
+        name: name.to_string(),
+        context_limit,
+        capabilities,
+    }
+}
+
+/// Returns the context window to ask Ollama for (`num_ctx`) when it is known: the
+/// configured context limit, or else the one Ollama reports for the model, capped at
+/// [`OLLAMA_MAX_NUM_CTX`], which also becomes the model's context limit. Ollama otherwise
+/// loads models with a small default window and silently truncates the prompt.
+fn apply_reported_context(
+    model: &mut ModelConfig,
+    details: Option<&SupportedModel>,
+) -> Option<usize> {
+    if model.has_explicit_context_limit() {
+        return model.context_limit;
+    }
+    let reported = details.and_then(|details| details.context_limit)?;
+    let num_ctx = reported.min(OLLAMA_MAX_NUM_CTX);
+    model.context_limit = Some(num_ctx);
+    Some(num_ctx)
 }
 
 // No authentication provider for Ollama
@@ -170,7 +302,17 @@ impl Provider for OllamaProvider {
     }
 
     fn get_model_config(&self) -> ModelConfig {
-        self.model.clone()
+        let mut model = self.model.clone();
+        apply_reported_context(&mut model, self.model_details.get());
+        model
+    }
+
+    fn supports_images(&self) -> bool {
+        // Once `/api/show` has been looked up, trust the capabilities it reports
+        match self.model_details.get() {
+            Some(details) => details.capabilities.iter().any(|c| c == "vision"),
+            None => model_supports_images(&self.model.model_name),
+        }
     }
 
     #[tracing::instrument(
@@ -188,13 +330,8 @@ impl Provider for OllamaProvider {
         let goose_mode = config.get_param("GOOSE_MODE").unwrap_or("auto".to_string());
         let filtered_tools = if goose_mode == "chat" { &[] } else { tools };
 
-        let payload = create_request(
-            &self.model,
-            system,
-            messages,
-            filtered_tools,
-            &super::utils::ImageFormat::OpenAi,
-        )?;
+        let (model, num_ctx) = self.resolved_model().await;
+        let payload = create_request(&model, system, messages, filtered_tools, num_ctx)?;
         let response = self
             .with_retry(|| async {
                 let payload_clone = payload.clone();
@@ -244,37 +381,53 @@ impl Provider for OllamaProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<MessageStream, ProviderError> {
-        let mut payload = create_request(
-            &self.model,
-            system,
-            messages,
-            tools,
-            &super::utils::ImageFormat::OpenAi,
-        )?;
+        let (model, num_ctx) = self.resolved_model().await;
+        let mut payload = create_request(&model, system, messages, tools, num_ctx)?;
         payload["stream"] = json!(true);
-        payload["stream_options"] = json!({
-            "include_usage": true,
-        });
 
-        let response = self
-            .api_client
-            .response_post("v1/chat/completions", &payload)
-            .await?;
+        let response = self.api_client.response_post("api/chat", &payload).await?;
         let response = handle_status_openai_compat(response).await?;
-        let stream = response.bytes_stream().map_err(io::Error::other);
-        let model_config = self.model.clone();
-
-        Ok(Box::pin(try_stream! {
-            let stream_reader = StreamReader::new(stream);
-            let framed = FramedRead::new(stream_reader, LinesCodec::new()).map_err(anyhow::Error::from);
-            let message_stream = response_to_streaming_message(framed);
-            pin!(message_stream);
-            while let Some(message) = message_stream.next().await {
-                let (message, usage) = message.map_err(|e| ProviderError::RequestFailed(format!("Stream decode error: {}", e)))?;
-                super::utils::emit_debug_trace(&model_config, &payload, &message, &usage.as_ref().map(|f| f.usage).unwrap_or_default());
-                yield (message, usage);
-            }
+
+        Ok(stream_messages(
+            response_to_streaming_message(response_lines(response)),
+            model,
+            payload,
+        ))
+    }
+
+    /// The models pulled into the local Ollama
+    async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
+        let response = self.api_client.response_get("api/tags").await?;
+        let response = handle_response_openai_compat(response).await?;
+
+        let models = response
+            .get("models")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| {
+                ProviderError::UsageError("Missing or invalid `models` field in response".into())
+            })?;
+
+        let mut model_names: Vec<String> = models
+            .iter()
+            .filter_map(|m| m.get("name").and_then(|v| v.as_str()).map(String::from))
+            .collect();
+        model_names.sort();
+        Ok(Some(model_names))
+    }
+
+    async fn fetch_supported_model_details(
+        &self,
+    ) -> Result<Option<Vec<SupportedModel>>, ProviderError> {
+        let Some(model_names) = self.fetch_supported_models().await? else {
+            return Ok(None);
+        };
+        let details = futures::future::join_all(model_names.iter().map(|name| async move {
+            self.show_model(name)
+                .await
+                .unwrap_or_else(|_| SupportedModel::new(name.as_str()))
         }))
+        .await;
+        Ok(Some(details))
     }
 }
 
@@ -321,3 +474,218 @@ impl OllamaProvider {
         filtered
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn provider(server: &MockServer, model: &str) -> OllamaProvider {
+        OllamaProvider {
+            api_client: ApiClient::new(server.uri(), AuthMethod::Custom(Box::new(NoAuth))).unwrap(),
+            model: ModelConfig::new_or_fail(model),
+            supports_streaming: false,
+            model_details: OnceCell::new(),
+        }
+    }
+
+    fn show_response(context_length: u64) -> Value {
+        json!({
+            "details": {"family": "qwen2"},
+            "model_info": {
+                "general.architecture": "qwen2",
+                "qwen2.context_length": context_length,
+                "qwen2.embedding_length": 3584
+            },
+            "capabilities": ["completion", "tools", "vision"]
+        })
+    }
+
+    fn chat_response() -> Value {
+        json!({
+            "model": "mistral",
+            "created_at": "2025-01-01T00:00:00Z",
+            "message": {"role": "assistant", "content": "Hello!"},
+            "done": true,
+            "done_reason": "stop",
+            "prompt_eval_count": 10,
+            "eval_count": 2
+        })
+    }
+
+    #[test]
+    fn test_parse_model_details() {
+        let details = parse_model_details("qwen2.5vl:7b", &show_response(128_000));
+        assert_eq!(details.context_limit, Some(128_000));
+        assert_eq!(details.capabilities, vec!["tools", "vision"]);
+
+        let details = parse_model_details("old", &json!({"details": {}}));
+        assert_eq!(details, SupportedModel::new("old"));
+    }
+
+    #[tokio::test]
+    async fn test_lists_local_models_with_details() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/tags"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "models": [{"name": "qwen2.5:latest"}, {"name": "llama3.2:3b"}]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/show"))
+            .and(body_partial_json(json!({"model": "qwen2.5:latest"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(show_response(32_768)))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/show"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let models = provider(&server, "qwen2.5")
+            .fetch_supported_model_details()
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0], SupportedModel::new("llama3.2:3b"));
+        assert_eq!(models[1].name, "qwen2.5:latest");
+        assert_eq!(models[1].context_limit, Some(32_768));
+    }
+
+    #[tokio::test]
+    async fn test_requests_reported_context_length() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/show"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(show_response(32_768)))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_partial_json(json!({"options": {"num_ctx": 32_768}})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(chat_response()))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let provider = provider(&server, "mistral");
+        let messages = [Message::user().with_text("Hi")];
+        for _ in 0..2 {
+            provider.complete("system", &messages, &[]).await.unwrap();
+        }
+        assert_eq!(provider.get_model_config().context_limit(), 32_768);
+    }
+
+    #[tokio::test]
+    async fn test_retries_failed_model_lookup() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/show"))
+            .respond_with(ResponseTemplate::new(404))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/show"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(show_response(32_768)))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(chat_response()))
+            .mount(&server)
+            .await;
+
+        let provider = provider(&server, "mistral");
+        let messages = [Message::user().with_text("Hi")];
+        provider.complete("system", &messages, &[]).await.unwrap();
+        assert!(provider.model_details.get().is_none());
+
+        for _ in 0..2 {
+            provider.complete("system", &messages, &[]).await.unwrap();
+        }
+        assert_eq!(provider.get_model_config().context_limit(), 32_768);
+    }
+
+    #[tokio::test]
+    async fn test_caps_reported_context_length() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/show"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(show_response(1_048_576)))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_partial_json(
+                json!({"options": {"num_ctx": OLLAMA_MAX_NUM_CTX}}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(chat_response()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = provider(&server, "mistral");
+        let messages = [Message::user().with_text("Hi")];
+        provider.complete("system", &messages, &[]).await.unwrap();
+        // The context limit is the window Ollama was asked for, not what the model allows
+        assert_eq!(
+            provider.get_model_config().context_limit(),
+            OLLAMA_MAX_NUM_CTX
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pull_model_reports_progress() {
+        let server = MockServer::start().await;
+        let lines = [
+            json!({"status": "pulling manifest"}),
+            json!({"status": "pulling abc", "digest": "sha256:abc", "total": 100, "completed": 40}),
+            json!({"status": "pulling abc", "digest": "sha256:abc", "total": 100, "completed": 100}),
+            json!({"status": "success"}),
+        ];
+        let body: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+        Mock::given(method("POST"))
+            .and(path("/api/pull"))
+            .and(body_partial_json(json!({"model": "qwen3", "stream": true})))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "application/x-ndjson"))
+            .mount(&server)
+            .await;
+
+        let mut updates = Vec::new();
+        provider(&server, "qwen3")
+            .pull_model("qwen3", |progress| updates.push(progress.clone()))
+            .await
+            .unwrap();
+
+        assert_eq!(updates.len(), 4);
+        assert_eq!(updates[2].completed, Some(100));
+        assert_eq!(updates[3].status, "success");
+    }
+
+    #[tokio::test]
+    async fn test_pull_model_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/pull"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "{\"status\":\"pulling manifest\"}\n{\"error\":\"pull model manifest: file does not exist\"}\n",
+                "application/x-ndjson",
+            ))
+            .mount(&server)
+            .await;
+
+        let result = provider(&server, "nope").pull_model("nope", |_| {}).await;
+        assert!(
+            matches!(result, Err(ProviderError::RequestFailed(msg)) if msg.contains("does not exist"))
+        );
+    }
+}
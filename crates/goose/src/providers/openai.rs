@@ -1,24 +1,20 @@
 use anyhow::Result;
-use async_stream::try_stream;
 use async_trait::async_trait;
-use futures::TryStreamExt;
 use reqwest::StatusCode;
 use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::io;
-use tokio::pin;
-use tokio_stream::StreamExt;
-use tokio_util::codec::{FramedRead, LinesCodec};
-use tokio_util::io::StreamReader;
 
 use super::api_client::{ApiClient, AuthMethod};
 use super::base::{ConfigKey, ModelInfo, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::embedding::{EmbeddingCapable, EmbeddingRequest, EmbeddingResponse};
 use super::errors::ProviderError;
 use super::formats::openai::{create_request, get_usage, response_to_message};
+use super::formats::openai_responses::{self, StoredInputs};
+use super::prompt_cache::{cache_key, prompt_caching_enabled};
+use super::structured_output::STRUCTURED_OUTPUT_NAME;
 use super::utils::{
     emit_debug_trace, get_model, handle_response_openai_compat, handle_status_openai_compat,
-    ImageFormat,
+    response_lines, stream_messages, ImageFormat,
 };
 use crate::config::custom_providers::CustomProviderConfig;
 use crate::conversation::message::Message;
@@ -27,6 +23,7 @@ use crate::model::ModelConfig;
 use crate::providers::base::MessageStream;
 use crate::providers::formats::openai::response_to_streaming_message;
 use rmcp::model::Tool;
+use serde::{Deserialize, Serialize};
 
 pub const OPEN_AI_DEFAULT_MODEL: &str = "gpt-4o";
 pub const OPEN_AI_DEFAULT_FAST_MODEL: &str = "gpt-4o-mini";
@@ -44,6 +41,35 @@ pub const OPEN_AI_KNOWN_MODELS: &[(&str, usize)] = &[
 
 pub const OPEN_AI_DOC_URL: &str = "https://platform.openai.com/docs/models";
 
+/// Which OpenAI API a provider talks to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenAiApi {
+    /// `/v1/chat/completions`, also spoken by most OpenAI compatible servers
+    #[default]
+    ChatCompletions,
+    /// `/v1/responses`, which exposes reasoning items and stateful continuation
+    Responses,
+}
+
+impl OpenAiApi {
+    /// Pick the API from a base path, for configurations that only set the path
+    pub fn from_base_path(base_path: &str) -> Self {
+        if base_path.trim_end_matches('/').ends_with("responses") {
+            OpenAiApi::Responses
+        } else {
+            OpenAiApi::ChatCompletions
+        }
+    }
+
+    pub fn default_base_path(&self) -> &'static str {
+        match self {
+            OpenAiApi::ChatCompletions => "v1/chat/completions",
+            OpenAiApi::Responses => "v1/responses",
+        }
+    }
+}
+
 #[derive(Debug, serde::Serialize)]
 pub struct OpenAiProvider {
     #[serde(skip)]
@@ -54,6 +80,11 @@ pub struct OpenAiProvider {
     model: ModelConfig,
     custom_headers: Option<HashMap<String, String>>,
     supports_streaming: bool,
+    api: OpenAiApi,
+    responses_stateful: bool,
+    #[serde(skip)]
+    stored_inputs: StoredInputs,
+    supports_prompt_cache_key: bool,
 }
 
 impl_provider_default!(OpenAiProvider);
@@ -67,9 +98,17 @@ impl OpenAiProvider {
         let host: String = config
             .get_param("OPENAI_HOST")
             .unwrap_or_else(|_| "https://api.openai.com".to_string());
-        let base_path: String = config
-            .get_param("OPENAI_BASE_PATH")
-            .unwrap_or_else(|_| "v1/chat/completions".to_string());
+        let base_path: Option<String> = config.get_param("OPENAI_BASE_PATH").ok();
+        let api: OpenAiApi = config.get_param("OPENAI_API").unwrap_or_else(|_| {
+            base_path
+                .as_deref()
+                .map(OpenAiApi::from_base_path)
+                .unwrap_or_default()
+        });
+        let base_path = base_path.unwrap_or_else(|| api.default_base_path().to_string());
+        let responses_stateful: bool = config
+            .get_param("OPENAI_RESPONSES_STATEFUL")
+            .unwrap_or(false);
         let organization: Option<String> = config.get_param("OPENAI_ORGANIZATION").ok();
         let project: Option<String> = config.get_param("OPENAI_PROJECT").ok();
         let custom_headers: Option<HashMap<String, String>> = config
@@ -79,9 +118,12 @@ impl OpenAiProvider {
             .map(parse_custom_headers);
         let timeout_secs: u64 = config.get_param("OPENAI_TIMEOUT").unwrap_or(600);
 
+        let supports_prompt_cache_key = host.contains("api.openai.com");
+
         let auth = AuthMethod::BearerToken(api_key);
         let mut api_client =
-            ApiClient::with_timeout(host, auth, std::time::Duration::from_secs(timeout_secs))?;
+            ApiClient::with_timeout(host, auth, std::time::Duration::from_secs(timeout_secs))?
+                .with_rate_limit("openai");
 
         if let Some(org) = &organization {
             api_client = api_client.with_header("OpenAI-Organization", org)?;
@@ -109,6 +151,10 @@ impl OpenAiProvider {
             model,
             custom_headers,
             supports_streaming: true,
+            api,
+            responses_stateful,
+            stored_inputs: StoredInputs::default(),
+            supports_prompt_cache_key,
         })
     }
 
@@ -132,8 +178,11 @@ impl OpenAiProvider {
             format!("{}://{}", url.scheme(), url.host_str().unwrap_or(""))
         };
         let base_path = url.path().trim_start_matches('/').to_string();
+        let api = config
+            .openai_api
+            .unwrap_or_else(|| OpenAiApi::from_base_path(&base_path));
         let base_path = if base_path.is_empty() {
-            "v1/chat/completions".to_string()
+            api.default_base_path().to_string()
         } else {
             base_path
         };
@@ -141,7 +190,8 @@ impl OpenAiProvider {
         let timeout_secs = config.timeout_seconds.unwrap_or(600);
         let auth = AuthMethod::BearerToken(api_key);
         let mut api_client =
-            ApiClient::with_timeout(host, auth, std::time::Duration::from_secs(timeout_secs))?;
+            ApiClient::with_timeout(host, auth, std::time::Duration::from_secs(timeout_secs))?
+                .with_rate_limit(&config.name);
 
         // Add custom headers if present
         if let Some(headers) = &config.headers {
@@ -162,6 +212,10 @@ impl OpenAiProvider {
             model,
             custom_headers: config.headers,
             supports_streaming: config.supports_streaming.unwrap_or(true),
+            api,
+            responses_stateful: config.responses_stateful.unwrap_or(false),
+            stored_inputs: StoredInputs::default(),
+            supports_prompt_cache_key: false,
         })
     }
 
@@ -172,6 +226,85 @@ impl OpenAiProvider {
             .await?;
         handle_response_openai_compat(response).await
     }
+
+    /// Route requests with the same system prompt and tools to the same prompt cache.
+    /// Only sent to OpenAI itself, as compatible servers may reject the unknown field.
+    fn add_prompt_cache_key(&self, payload: &mut Value, system: &str, tools: &[Tool]) {
+        if !self.supports_prompt_cache_key || !prompt_caching_enabled() {
+            return;
+        }
+        let tool_names: Vec<&str> = tools.iter().map(|tool| tool.name.as_ref()).collect();
+        payload["prompt_cache_key"] = json!(cache_key(&json!({
+            "model": payload["model"],
+            "system": system,
+            "tools": tool_names,
+        })));
+    }
+
+    /// Inputs of earlier stored requests, when responses are stored for continuation
+    fn stored_inputs(&self) -> Option<&StoredInputs> {
+        self.responses_stateful.then_some(&self.stored_inputs)
+    }
+
+    async fn complete_with_responses_api(
+        &self,
+        model_config: &ModelConfig,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let mut payload = openai_responses::create_request(
+            model_config,
+            system,
+            messages,
+            tools,
+            self.stored_inputs(),
+        )?;
+        self.add_prompt_cache_key(&mut payload, system, tools);
+
+        let json_response = self.post(&payload).await?;
+
+        let message = openai_responses::response_to_message(&json_response)?;
+        let usage = json_response
+            .get("usage")
+            .map(openai_responses::get_usage)
+            .unwrap_or_else(|| {
+                tracing::debug!("Failed to get usage data");
+                Usage::default()
+            });
+        let model = get_model(&json_response);
+        emit_debug_trace(&self.model, &payload, &json_response, &usage);
+        Ok((message, ProviderUsage::new(model, usage)))
+    }
+
+    async fn stream_with_responses_api(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let mut payload = openai_responses::create_request(
+            &self.model,
+            system,
+            messages,
+            tools,
+            self.stored_inputs(),
+        )?;
+        self.add_prompt_cache_key(&mut payload, system, tools);
+        payload["stream"] = Value::Bool(true);
+
+        let response = self
+            .api_client
+            .response_post(&self.base_path, &payload)
+            .await?;
+        let response = handle_status_openai_compat(response).await?;
+
+        Ok(stream_messages(
+            openai_responses::response_to_streaming_message(response_lines(response)),
+            self.model.clone(),
+            payload,
+        ))
+    }
 }
 
 #[async_trait]
@@ -196,6 +329,8 @@ impl Provider for OpenAiProvider {
                 ConfigKey::new("OPENAI_PROJECT", false, false, None),
                 ConfigKey::new("OPENAI_CUSTOM_HEADERS", false, true, None),
                 ConfigKey::new("OPENAI_TIMEOUT", false, false, Some("600")),
+                ConfigKey::new("OPENAI_API", false, false, Some("chat_completions")),
+                ConfigKey::new("OPENAI_RESPONSES_STATEFUL", false, false, Some("false")),
             ],
         )
     }
@@ -215,7 +350,15 @@ impl Provider for OpenAiProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<(Message, ProviderUsage), ProviderError> {
-        let payload = create_request(model_config, system, messages, tools, &ImageFormat::OpenAi)?;
+        if self.api == OpenAiApi::Responses {
+            return self
+                .complete_with_responses_api(model_config, system, messages, tools)
+                .await;
+        }
+
+        let mut payload =
+            create_request(model_config, system, messages, tools, &ImageFormat::OpenAi)?;
+        self.add_prompt_cache_key(&mut payload, system, tools);
 
         let json_response = self.post(&payload).await?;
 
@@ -232,8 +375,61 @@ impl Provider for OpenAiProvider {
         Ok((message, ProviderUsage::new(model, usage)))
     }
 
+    fn supports_structured_output(&self) -> bool {
+        true
+    }
+
+    async fn complete_structured(
+        &self,
+        system: &str,
+        messages: &[Message],
+        schema: &Value,
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let (payload, json_response, message, usage) = if self.api == OpenAiApi::Responses {
+            let mut payload =
+                openai_responses::create_request(&self.model, system, messages, &[], None)?;
+            payload["text"] = json!({
+                "format": {
+                    "type": "json_schema",
+                    "name": STRUCTURED_OUTPUT_NAME,
+                    "schema": schema,
+                }
+            });
+            let json_response = self.post(&payload).await?;
+            let message = openai_responses::response_to_message(&json_response)?;
+            let usage = json_response
+                .get("usage")
+                .map(openai_responses::get_usage)
+                .unwrap_or_default();
+            (payload, json_response, message, usage)
+        } else {
+            let mut payload =
+                create_request(&self.model, system, messages, &[], &ImageFormat::OpenAi)?;
+            payload["response_format"] = json!({
+                "type": "json_schema",
+                "json_schema": {
+                    "name": STRUCTURED_OUTPUT_NAME,
+                    "schema": schema,
+                }
+            });
+            let json_response = self.post(&payload).await?;
+            let message = response_to_message(&json_response)?;
+            let usage = json_response
+                .get("usage")
+                .map(get_usage)
+                .unwrap_or_default();
+            (payload, json_response, message, usage)
+        };
+        let model = get_model(&json_response);
+        emit_debug_trace(&self.model, &payload, &json_response, &usage);
+        Ok((message, ProviderUsage::new(model, usage)))
+    }
+
     async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
-        let models_path = self.base_path.replace("v1/chat/completions", "v1/models");
+        let models_path = self
+            .base_path
+            .replace(OpenAiApi::ChatCompletions.default_base_path(), "v1/models")
+            .replace(OpenAiApi::Responses.default_base_path(), "v1/models");
         let response = self.api_client.response_get(&models_path).await?;
         let json = handle_response_openai_compat(response).await?;
         if let Some(err_obj) = json.get("error") {
@@ -275,8 +471,15 @@ impl Provider for OpenAiProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<MessageStream, ProviderError> {
+        if self.api == OpenAiApi::Responses {
+            return self
+                .stream_with_responses_api(system, messages, tools)
+                .await;
+        }
+
         let mut payload =
             create_request(&self.model, system, messages, tools, &ImageFormat::OpenAi)?;
+        self.add_prompt_cache_key(&mut payload, system, tools);
         payload["stream"] = serde_json::Value::Bool(true);
         payload["stream_options"] = json!({
             "include_usage": true,
@@ -288,22 +491,11 @@ impl Provider for OpenAiProvider {
             .await?;
         let response = handle_status_openai_compat(response).await?;
 
-        let stream = response.bytes_stream().map_err(io::Error::other);
-
-        let model_config = self.model.clone();
-
-        Ok(Box::pin(try_stream! {
-            let stream_reader = StreamReader::new(stream);
-            let framed = FramedRead::new(stream_reader, LinesCodec::new()).map_err(anyhow::Error::from);
-
-            let message_stream = response_to_streaming_message(framed);
-            pin!(message_stream);
-            while let Some(message) = message_stream.next().await {
-                let (message, usage) = message.map_err(|e| ProviderError::RequestFailed(format!("Stream decode error: {}", e)))?;
-                emit_debug_trace(&model_config, &payload, &message, &usage.as_ref().map(|f| f.usage).unwrap_or_default());
-                yield (message, usage);
-            }
-        }))
+        Ok(stream_messages(
+            response_to_streaming_message(response_lines(response)),
+            self.model.clone(),
+            payload,
+        ))
     }
 }
 
@@ -360,3 +552,214 @@ impl EmbeddingCapable for OpenAiProvider {
             .collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use mcp_core::ToolCall;
+    use rmcp::model::Content;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn responses_provider(server: &MockServer, stateful: bool) -> OpenAiProvider {
+        OpenAiProvider {
+            api_client: ApiClient::new(server.uri(), AuthMethod::BearerToken("test".into()))
+                .unwrap(),
+            base_path: OpenAiApi::Responses.default_base_path().to_string(),
+            organization: None,
+            project: None,
+            model: ModelConfig::new_or_fail("o4-mini"),
+            custom_headers: None,
+            supports_streaming: true,
+            api: OpenAiApi::Responses,
+            responses_stateful: stateful,
+            stored_inputs: StoredInputs::default(),
+            supports_prompt_cache_key: false,
+        }
+    }
+
+    #[test]
+    fn test_api_from_base_path() {
+        assert_eq!(
+            OpenAiApi::from_base_path("v1/responses"),
+            OpenAiApi::Responses
+        );
+        assert_eq!(
+            OpenAiApi::from_base_path("openai/v1/chat/completions"),
+            OpenAiApi::ChatCompletions
+        );
+        assert_eq!(OpenAiApi::from_base_path(""), OpenAiApi::ChatCompletions);
+    }
+
+    #[tokio::test]
+    async fn test_responses_api_complete() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/responses"))
+            .and(body_partial_json(json!({
+                "model": "o4-mini",
+                "instructions": "system",
+                "store": false,
+                "include": ["reasoning.encrypted_content"],
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "resp_1",
+                "model": "o4-mini-2025-04-16",
+                "output": [
+                    {
+                        "type": "reasoning",
+                        "id": "rs_1",
+                        "summary": [{"type": "summary_text", "text": "Look around first"}],
+                        "encrypted_content": "gAAAA"
+                    },
+                    {
+                        "type": "function_call",
+                        "call_id": "call_1",
+                        "name": "developer__shell",
+                        "arguments": "{\"command\": \"ls\"}"
+                    }
+                ],
+                "usage": {
+                    "input_tokens": 12,
+                    "input_tokens_details": {"cached_tokens": 8},
+                    "output_tokens": 30,
+                    "total_tokens": 42
+                }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = responses_provider(&server, false);
+        let (message, usage) = provider
+            .complete("system", &[Message::user().with_text("hi")], &[])
+            .await
+            .unwrap();
+
+        assert_eq!(message.id.as_deref(), Some("resp_1"));
+        assert_eq!(
+            message.content[0].as_thinking().unwrap().thinking,
+            "Look around first"
+        );
+        let request = message.content[1].as_tool_request().unwrap();
+        assert_eq!(request.id, "call_1");
+        assert_eq!(request.tool_call.as_ref().unwrap().name, "developer__shell");
+        assert_eq!(usage.model, "o4-mini-2025-04-16");
+        assert_eq!(usage.usage.total_tokens, Some(42));
+        assert_eq!(usage.usage.cache_read_input_tokens, Some(8));
+    }
+
+    #[tokio::test]
+    async fn test_responses_api_stateful_stream() {
+        let server = MockServer::start().await;
+        let events = [
+            json!({"type": "response.created", "response": {"id": "resp_2"}}),
+            json!({"type": "response.output_item.done", "item": {"type": "reasoning", "id": "rs_2", "summary": []}}),
+            json!({"type": "response.output_text.delta", "item_id": "msg_1", "delta": "Found "}),
+            json!({"type": "response.output_text.delta", "item_id": "msg_1", "delta": "a.txt"}),
+            json!({"type": "response.output_item.done", "item": {"type": "message", "content": [{"type": "output_text", "text": "Found a.txt"}]}}),
+            json!({"type": "response.completed", "response": {
+                "id": "resp_2",
+                "model": "o4-mini-2025-04-16",
+                "usage": {"input_tokens": 5, "output_tokens": 7, "total_tokens": 12}
+            }}),
+        ];
+        let body: String = events
+            .iter()
+            .map(|event| format!("event: {}\ndata: {}\n\n", event["type"], event))
+            .collect();
+        Mock::given(method("POST"))
+            .and(path("/v1/responses"))
+            .and(body_partial_json(json!({
+                "previous_response_id": "resp_1",
+                "store": true,
+                "stream": true,
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let messages = vec![
+            Message::user().with_text("list files"),
+            Message::assistant()
+                .with_tool_request("call_1", Ok(ToolCall::new("shell", json!({"cmd": "ls"}))))
+                .with_id("resp_1"),
+            Message::user().with_tool_response("call_1", Ok(vec![Content::text("a.txt")])),
+        ];
+        let provider = responses_provider(&server, true);
+        provider.stored_inputs.remember(&messages[..1]);
+        let mut stream = provider.stream("system", &messages, &[]).await.unwrap();
+
+        let mut text = String::new();
+        let mut redacted = 0;
+        let mut usages = Vec::new();
+        while let Some(item) = stream.next().await {
+            let (message, usage) = item.unwrap();
+            if let Some(message) = message {
+                assert_eq!(message.id.as_deref(), Some("resp_2"));
+                text.push_str(&message.as_concat_text());
+                redacted += message
+                    .content
+                    .iter()
+                    .filter(|c| c.as_redacted_thinking().is_some())
+                    .count();
+            }
+            usages.extend(usage);
+        }
+        assert_eq!(text, "Found a.txt");
+        assert_eq!(redacted, 1);
+        assert_eq!(usages.len(), 1);
+        assert_eq!(usages[0].usage.total_tokens, Some(12));
+
+        let requests = server.received_requests().await.unwrap();
+        let payload: Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(
+            payload["input"],
+            json!([{"type": "function_call_output", "call_id": "call_1", "output": "a.txt"}])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_structured_output() {
+        let server = MockServer::start().await;
+        let schema = json!({
+            "type": "object",
+            "properties": {"count": {"type": "integer"}},
+            "required": ["count"]
+        });
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_partial_json(json!({
+                "response_format": {
+                    "type": "json_schema",
+                    "json_schema": {"name": STRUCTURED_OUTPUT_NAME, "schema": schema},
+                }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "model": "gpt-4o",
+                "choices": [{
+                    "message": {"role": "assistant", "content": "{\"count\": 2}"},
+                    "finish_reason": "stop"
+                }],
+                "usage": {"prompt_tokens": 10, "completion_tokens": 4, "total_tokens": 14}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut provider = responses_provider(&server, false);
+        provider.api = OpenAiApi::ChatCompletions;
+        provider.base_path = OpenAiApi::ChatCompletions.default_base_path().to_string();
+        let messages = vec![Message::user().with_text("how many files?")];
+
+        let (value, usage) = crate::providers::structured_output::complete_structured(
+            &provider, "system", &messages, &schema,
+        )
+        .await
+        .unwrap();
+        assert_eq!(value, json!({"count": 2}));
+        assert_eq!(usage.usage.total_tokens, Some(14));
+    }
+}
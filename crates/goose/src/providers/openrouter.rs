@@ -3,17 +3,20 @@ use async_trait::async_trait;
 use serde_json::{json, Value};
 
 use super::api_client::{ApiClient, AuthMethod};
-use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::errors::ProviderError;
 use super::retry::ProviderRetry;
 use super::utils::{
     emit_debug_trace, get_model, handle_response_google_compat, handle_response_openai_compat,
-    is_google_model,
+    handle_status_google_compat, handle_status_openai_compat, is_google_model, response_lines,
+    stream_messages,
 };
 use crate::conversation::message::Message;
 use crate::impl_provider_default;
 use crate::model::ModelConfig;
-use crate::providers::formats::openai::{create_request, get_usage, response_to_message};
+use crate::providers::formats::openai::{
+    create_request, get_usage, response_to_message, response_to_streaming_message,
+};
 use rmcp::model::Tool;
 
 pub const OPENROUTER_DEFAULT_MODEL: &str = "anthropic/claude-sonnet-4";
@@ -52,7 +55,8 @@ impl OpenRouterProvider {
         let auth = AuthMethod::BearerToken(api_key);
         let api_client = ApiClient::new(host, auth)?
             .with_header("HTTP-Referer", "https://block.github.io/goose")?
-            .with_header("X-Title", "Goose")?;
+            .with_header("X-Title", "Goose")?
+            .with_rate_limit("openrouter");
 
         Ok(Self { api_client, model })
     }
@@ -69,9 +73,7 @@ impl OpenRouterProvider {
         }
 
         // For OpenAI-compatible models, parse the response body to JSON
-        let response_body = handle_response_openai_compat(response)
-            .await
-            .map_err(|e| ProviderError::RequestFailed(format!("Failed to parse response: {e}")))?;
+        let response_body = handle_response_openai_compat(response).await?;
 
         let _debug = format!(
             "OpenRouter request with payload: {} and response: {}",
@@ -271,6 +273,44 @@ impl Provider for OpenRouterProvider {
         Ok((message, ProviderUsage::new(response_model, usage)))
     }
 
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let mut payload = create_request_based_on_model(self, system, messages, tools)?;
+        payload["stream"] = json!(true);
+        // https://openrouter.ai/docs/use-cases/usage-accounting
+        payload["usage"] = json!({
+            "include": true,
+        });
+
+        let response = self
+            .with_retry(|| async {
+                let response = self
+                    .api_client
+                    .response_post("api/v1/chat/completions", &payload)
+                    .await?;
+                if is_google_model(&payload) {
+                    handle_status_google_compat(response).await
+                } else {
+                    handle_status_openai_compat(response).await
+                }
+            })
+            .await?;
+
+        Ok(stream_messages(
+            response_to_streaming_message(response_lines(response)),
+            self.model.clone(),
+            payload,
+        ))
+    }
+
     /// Fetch supported models from OpenRouter API (only models with tool support)
     async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
         // Handle request failures gracefully
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use regex::Regex;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -38,6 +39,30 @@ pub struct PricingInfo {
     pub input_cost: f64,  // Cost per token
     pub output_cost: f64, // Cost per token
     pub context_length: Option<u32>,
+    /// Cost per token read from the prompt cache, when the model has a separate rate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_cost: Option<f64>,
+    /// Cost per token written to the prompt cache, when the model has a separate rate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_cost: Option<f64>,
+}
+
+impl PricingInfo {
+    /// Cost of a call whose `input_tokens` include the tokens read from and written to the
+    /// prompt cache. Those are charged at their own rates, or at the input rate if unknown.
+    pub fn cost(
+        &self,
+        input_tokens: usize,
+        cache_read_tokens: usize,
+        cache_write_tokens: usize,
+        output_tokens: usize,
+    ) -> f64 {
+        let uncached = input_tokens.saturating_sub(cache_read_tokens + cache_write_tokens);
+        self.input_cost * uncached as f64
+            + self.cache_read_cost.unwrap_or(self.input_cost) * cache_read_tokens as f64
+            + self.cache_write_cost.unwrap_or(self.input_cost) * cache_write_tokens as f64
+            + self.output_cost * output_tokens as f64
+    }
 }
 
 /// Cache for OpenRouter pricing data with disk persistence
@@ -160,6 +185,16 @@ impl PricingCache {
                             input_cost,
                             output_cost,
                             context_length: model.context_length,
+                            cache_read_cost: model
+                                .pricing
+                                .input_cache_read
+                                .as_deref()
+                                .and_then(convert_pricing),
+                            cache_write_cost: model
+                                .pricing
+                                .input_cache_write
+                                .as_deref()
+                                .and_then(convert_pricing),
                         },
                     );
                 }
@@ -257,6 +292,10 @@ pub struct OpenRouterModel {
 pub struct OpenRouterPricing {
     pub prompt: String,     // Cost per token for input (in USD)
     pub completion: String, // Cost per token for output (in USD)
+    #[serde(default)]
+    pub input_cache_read: Option<String>, // Cost per token read from the prompt cache
+    #[serde(default)]
+    pub input_cache_write: Option<String>, // Cost per token written to the prompt cache
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -303,6 +342,14 @@ pub async fn initialize_pricing_cache() -> Result<()> {
     PRICING_CACHE.initialize().await
 }
 
+/// Initialize the pricing cache unless it is already loaded
+pub async fn ensure_pricing_cache() -> Result<()> {
+    if PRICING_CACHE.memory_cache.read().await.is_some() {
+        return Ok(());
+    }
+    PRICING_CACHE.initialize().await
+}
+
 /// Get pricing for a specific model
 pub async fn get_model_pricing(provider: &str, model: &str) -> Option<PricingInfo> {
     PRICING_CACHE.get_model_pricing(provider, model).await
@@ -360,6 +407,73 @@ pub fn parse_model_id(model_id: &str) -> Option<(String, String)> {
     }
 }
 
+/// Strip suffixes that don't affect pricing, e.g. "-latest" and "-YYYYMMDD" dates,
+/// and convert version numbers like "-3-7-" to "-3.7-" to match OpenRouter model ids
+pub fn normalize_model_name(model: &str) -> String {
+    let mut result = model.to_string();
+
+    // Remove "-latest" suffix
+    if let Some(stripped) = result.strip_suffix("-latest") {
+        result = stripped.to_string();
+    }
+
+    // Remove date-like suffixes: -YYYYMMDD
+    let re_date = Regex::new(r"-\d{8}$").unwrap();
+    if re_date.is_match(&result) {
+        result = re_date.replace(&result, "").to_string();
+    }
+
+    // Convert version numbers like -3-7- to -3.7- (e.g., claude-3-7-sonnet -> claude-3.7-sonnet)
+    let re_version = Regex::new(r"-(\d+)-(\d+)-").unwrap();
+    if re_version.is_match(&result) {
+        result = re_version.replace(&result, "-$1.$2-").to_string();
+    }
+
+    result
+}
+
+/// Estimate the cost in USD of a call from cached pricing, if the model is known
+pub async fn estimate_cost_usd(
+    provider: &str,
+    model: &str,
+    input_tokens: usize,
+    output_tokens: usize,
+) -> Option<f64> {
+    estimate_cached_cost_usd(provider, model, input_tokens, 0, 0, output_tokens).await
+}
+
+/// Estimate the cost in USD of a call whose `input_tokens` include prompt cache reads and
+/// writes, pricing those at the model's cache rates
+pub async fn estimate_cached_cost_usd(
+    provider: &str,
+    model: &str,
+    input_tokens: usize,
+    cache_read_tokens: usize,
+    cache_write_tokens: usize,
+    output_tokens: usize,
+) -> Option<f64> {
+    // For OpenRouter, parse the model name to extract real provider/model
+    let openrouter_data = if provider == "openrouter" {
+        parse_model_id(model)
+    } else {
+        None
+    };
+
+    let (provider_to_use, model_to_use) = match &openrouter_data {
+        Some((real_provider, real_model)) => (real_provider.as_str(), real_model.as_str()),
+        None => (provider, model),
+    };
+
+    let cleaned_model = normalize_model_name(model_to_use);
+    let pricing = get_model_pricing(provider_to_use, &cleaned_model).await?;
+    Some(pricing.cost(
+        input_tokens,
+        cache_read_tokens,
+        cache_write_tokens,
+        output_tokens,
+    ))
+}
+
 /// Convert OpenRouter pricing to cost per token (already in that format)
 pub fn convert_pricing(price_str: &str) -> Option<f64> {
     // OpenRouter prices are already in USD per token
@@ -395,6 +509,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_normalize_model_name() {
+        assert_eq!(
+            normalize_model_name("claude-3-7-sonnet-latest"),
+            "claude-3.7-sonnet"
+        );
+        assert_eq!(
+            normalize_model_name("claude-sonnet-4-20250514"),
+            "claude-sonnet-4"
+        );
+        assert_eq!(normalize_model_name("gpt-4o"), "gpt-4o");
+    }
+
     #[test]
     fn test_convert_pricing() {
         assert_eq!(convert_pricing("0.000003"), Some(0.000003));
@@ -402,6 +529,28 @@ mod tests {
         assert_eq!(convert_pricing("invalid"), None);
     }
 
+    #[test]
+    fn test_cost_prices_cache_tokens_separately() {
+        let pricing = PricingInfo {
+            input_cost: 3e-6,
+            output_cost: 15e-6,
+            context_length: None,
+            cache_read_cost: Some(0.3e-6),
+            cache_write_cost: Some(3.75e-6),
+        };
+        let cost = pricing.cost(10_000, 8_000, 1_000, 100);
+        let expected = 1_000.0 * 3e-6 + 8_000.0 * 0.3e-6 + 1_000.0 * 3.75e-6 + 100.0 * 15e-6;
+        assert!((cost - expected).abs() < 1e-12);
+
+        let without_cache_rates = PricingInfo {
+            cache_read_cost: None,
+            cache_write_cost: None,
+            ..pricing
+        };
+        let cost = without_cache_rates.cost(10_000, 8_000, 1_000, 100);
+        assert!((cost - (10_000.0 * 3e-6 + 100.0 * 15e-6)).abs() < 1e-12);
+    }
+
     #[tokio::test]
     async fn test_claude_sonnet_4_pricing_lookup() {
         // Initialize the cache to load from disk
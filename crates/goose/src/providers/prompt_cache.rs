@@ -0,0 +1,386 @@
+//! Provider-agnostic pieces of prompt caching.
+//!
+//! Each provider family caches prompts its own way:
+//! - Anthropic-style `cache_control` breakpoints (`formats::anthropic`, OpenRouter, LiteLLM)
+//! - Bedrock cache points in Converse requests
+//! - Gemini explicit context caches, created ahead of the request and referred to by name
+//! - OpenAI automatic caching, routed by a prompt cache key
+//!
+//! Whichever is used, the cached input tokens are reported in `Usage` and accumulated per
+//! model in the session metadata.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use super::errors::ProviderError;
+
+/// How long explicit context caches live on the provider before they expire
+pub const CONTEXT_CACHE_TTL: Duration = Duration::from_secs(600);
+
+/// Stop using a context cache this long before it expires, so requests in flight do not
+/// refer to an expired cache
+const CONTEXT_CACHE_EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+/// Most context caches kept per provider; beyond this the ones closest to expiring are deleted
+const MAX_CONTEXT_CACHES: usize = 8;
+
+/// How long other requests go without a cache while one is being created, after which a
+/// creation that never finished (e.g. its request was cancelled) is given up on
+const CONTEXT_CACHE_CREATE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Whether goose should set up prompt caching beyond what a provider does automatically,
+/// such as cache points and cache keys. Controlled by `GOOSE_PROMPT_CACHING`.
+pub fn prompt_caching_enabled() -> bool {
+    crate::config::Config::global()
+        .get_param::<bool>("GOOSE_PROMPT_CACHING")
+        .unwrap_or(true)
+}
+
+/// Whether goose may create explicit context caches (Gemini on Google AI and Vertex AI).
+///
+/// These are billed for storage while they live, so they are opt-in through
+/// `GOOSE_CONTEXT_CACHING`, on top of `GOOSE_PROMPT_CACHING`.
+pub fn context_caching_enabled() -> bool {
+    prompt_caching_enabled()
+        && crate::config::Config::global()
+            .get_param::<bool>("GOOSE_CONTEXT_CACHING")
+            .unwrap_or(false)
+}
+
+/// A short, stable key for cacheable request content
+pub fn cache_key(content: &Value) -> String {
+    let digest = Sha256::digest(content.to_string().as_bytes());
+    format!(
+        "goose-{}",
+        digest[..8]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    )
+}
+
+#[derive(Debug)]
+enum ContextCacheEntry {
+    /// Another request is creating the cache; requests meanwhile go without one
+    Creating {
+        started_at: Instant,
+    },
+    Ready {
+        name: String,
+        expires_at: Instant,
+    },
+    /// Creating the cache failed, e.g. because the content is below the provider's minimum
+    /// size; do not try again for this content until `retry_at`
+    Unavailable {
+        retry_at: Instant,
+    },
+}
+
+impl ContextCacheEntry {
+    fn is_live(&self, now: Instant) -> bool {
+        match self {
+            ContextCacheEntry::Creating { started_at } => {
+                *started_at + CONTEXT_CACHE_CREATE_TIMEOUT > now
+            }
+            ContextCacheEntry::Ready { expires_at, .. } => *expires_at > now,
+            ContextCacheEntry::Unavailable { retry_at } => *retry_at > now,
+        }
+    }
+}
+
+/// Explicit context caches created by a provider, keyed by a hash of their content.
+///
+/// Caches are created with `CONTEXT_CACHE_TTL`, so the provider expires any that are not
+/// deleted, e.g. when goose exits.
+#[derive(Debug, Default)]
+pub struct ContextCaches {
+    entries: Mutex<HashMap<String, ContextCacheEntry>>,
+}
+
+impl ContextCaches {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The name of a live cache for `key`, creating one with `create` if needed.
+    ///
+    /// Caches evicted to make room are deleted with `delete`. Returns None when no cache
+    /// could be created, or another request is still creating it, in which case the request
+    /// should be sent without one.
+    pub async fn get_or_create<F, Fut, D, DFut>(
+        &self,
+        key: &str,
+        create: F,
+        delete: D,
+    ) -> Option<String>
+    where
+        F: FnOnce(Duration) -> Fut,
+        Fut: Future<Output = Result<String, ProviderError>>,
+        D: Fn(String) -> DFut,
+        DFut: Future<Output = Result<(), ProviderError>>,
+    {
+        {
+            // Not held while creating, so requests are not serialized behind network calls
+            let mut entries = self.entries.lock().await;
+            let now = Instant::now();
+            match entries.get(key) {
+                Some(ContextCacheEntry::Ready { name, expires_at }) if *expires_at > now => {
+                    return Some(name.clone());
+                }
+                Some(entry) if entry.is_live(now) => return None,
+                _ => {}
+            }
+            entries.insert(
+                key.to_string(),
+                ContextCacheEntry::Creating { started_at: now },
+            );
+        }
+
+        let created_at = Instant::now();
+        let (entry, name) = match create(CONTEXT_CACHE_TTL).await {
+            Ok(name) => {
+                tracing::debug!("Created context cache {}", name);
+                let entry = ContextCacheEntry::Ready {
+                    name: name.clone(),
+                    expires_at: created_at + CONTEXT_CACHE_TTL - CONTEXT_CACHE_EXPIRY_MARGIN,
+                };
+                (entry, Some(name))
+            }
+            Err(e) => {
+                tracing::debug!("Could not create context cache, continuing without: {}", e);
+                let entry = ContextCacheEntry::Unavailable {
+                    retry_at: created_at + CONTEXT_CACHE_TTL,
+                };
+                (entry, None)
+            }
+        };
+
+        let evicted = {
+            let mut entries = self.entries.lock().await;
+            entries.insert(key.to_string(), entry);
+            evict(&mut entries, Instant::now())
+        };
+        for evicted_name in evicted {
+            match delete(evicted_name.clone()).await {
+                Ok(()) => tracing::debug!("Deleted context cache {}", evicted_name),
+                Err(e) => {
+                    tracing::debug!("Could not delete context cache {}: {}", evicted_name, e)
+                }
+            }
+        }
+        name
+    }
+
+    /// Forget the cache for `key`, e.g. after the provider no longer recognized it
+    pub async fn invalidate(&self, key: &str) {
+        self.entries.lock().await.remove(key);
+    }
+
+    /// Send `cached`, a request referring to the context cache under its key, and fall back
+    /// to sending `payload` without the cache if the provider rejects it, e.g. because the
+    /// cache was deleted. Without a cached request, `payload` is sent as is.
+    pub async fn send_with_fallback<T, F, Fut>(
+        &self,
+        cached: Option<(String, Value)>,
+        payload: &Value,
+        send: F,
+    ) -> Result<T, ProviderError>
+    where
+        F: Fn(Value) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        if let Some((key, cached_payload)) = cached {
+            match send(cached_payload).await {
+                Err(ProviderError::RequestFailed(msg)) => {
+                    tracing::debug!(
+                        "Request using context cache failed, retrying without it: {}",
+                        msg
+                    );
+                    self.invalidate(&key).await;
+                }
+                result => return result,
+            }
+        }
+        send(payload.clone()).await
+    }
+}
+
+/// Drop entries that are no longer live and, beyond `MAX_CONTEXT_CACHES`, the caches closest
+/// to expiring. Returns the names of the dropped caches that still exist on the provider.
+fn evict(entries: &mut HashMap<String, ContextCacheEntry>, now: Instant) -> Vec<String> {
+    entries.retain(|_, entry| entry.is_live(now));
+
+    let mut ready: Vec<(Instant, String)> = entries
+        .iter()
+        .filter_map(|(key, entry)| match entry {
+            ContextCacheEntry::Ready { expires_at, .. } => Some((*expires_at, key.clone())),
+            _ => None,
+        })
+        .collect();
+    if ready.len() <= MAX_CONTEXT_CACHES {
+        return Vec::new();
+    }
+    ready.sort();
+    ready[..ready.len() - MAX_CONTEXT_CACHES]
+        .iter()
+        .filter_map(|(_, key)| match entries.remove(key) {
+            Some(ContextCacheEntry::Ready { name, .. }) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex as StdMutex;
+
+    async fn keep(_name: String) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_key_is_stable() {
+        let key = cache_key(&json!({"system": "You are goose"}));
+        assert_eq!(key, cache_key(&json!({"system": "You are goose"})));
+        assert_ne!(key, cache_key(&json!({"system": "You are not goose"})));
+        assert!(key.starts_with("goose-"));
+        assert_eq!(key.len(), "goose-".len() + 16);
+    }
+
+    #[tokio::test]
+    async fn test_context_caches_reuse_and_invalidate() {
+        let caches = ContextCaches::new();
+        let created = AtomicUsize::new(0);
+        let create = |_ttl: Duration| async {
+            let n = created.fetch_add(1, Ordering::SeqCst);
+            Ok(format!("cachedContents/{}", n))
+        };
+
+        assert_eq!(
+            caches.get_or_create("a", create, keep).await.as_deref(),
+            Some("cachedContents/0")
+        );
+        assert_eq!(
+            caches.get_or_create("a", create, keep).await.as_deref(),
+            Some("cachedContents/0")
+        );
+
+        caches.invalidate("a").await;
+        assert_eq!(
+            caches.get_or_create("a", create, keep).await.as_deref(),
+            Some("cachedContents/1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_with_fallback_drops_rejected_cache() {
+        let caches = ContextCaches::new();
+        let create = |_ttl: Duration| async { Ok("cachedContents/gone".to_string()) };
+        let name = caches.get_or_create("a", create, keep).await.unwrap();
+        let cached = Some(("a".to_string(), json!({"cachedContent": name})));
+
+        let sent = caches
+            .send_with_fallback(
+                cached,
+                &json!({"system_instruction": "s"}),
+                |p| async move {
+                    if p.get("cachedContent").is_some() {
+                        Err(ProviderError::RequestFailed("cache not found".to_string()))
+                    } else {
+                        Ok(p)
+                    }
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(sent, json!({"system_instruction": "s"}));
+
+        let recreated = |_ttl: Duration| async { Ok("cachedContents/new".to_string()) };
+        assert_eq!(
+            caches.get_or_create("a", recreated, keep).await.as_deref(),
+            Some("cachedContents/new")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_context_caches_remember_failures() {
+        let caches = ContextCaches::new();
+        let attempts = AtomicUsize::new(0);
+        let create = |_ttl: Duration| async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(ProviderError::RequestFailed(
+                "content too small".to_string(),
+            ))
+        };
+
+        assert!(caches.get_or_create("a", create, keep).await.is_none());
+        assert!(caches.get_or_create("a", create, keep).await.is_none());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_context_caches_do_not_block_while_creating() {
+        let caches = ContextCaches::new();
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        let slow = move |_ttl: Duration| async move {
+            released.await.unwrap();
+            Ok("cachedContents/slow".to_string())
+        };
+        let fast = |_ttl: Duration| async { Ok("cachedContents/other".to_string()) };
+        let duplicates = AtomicUsize::new(0);
+        let duplicate = |_ttl: Duration| async {
+            duplicates.fetch_add(1, Ordering::SeqCst);
+            Ok("cachedContents/duplicate".to_string())
+        };
+
+        let creating = caches.get_or_create("a", slow, keep);
+        let others = async {
+            // Neither the same content nor other content waits for the slow creation
+            let same = caches.get_or_create("a", duplicate, keep).await;
+            let other = caches.get_or_create("b", fast, keep).await;
+            release.send(()).unwrap();
+            (same, other)
+        };
+        let (created, (same, other)) = tokio::join!(creating, others);
+
+        assert_eq!(created.as_deref(), Some("cachedContents/slow"));
+        assert!(same.is_none());
+        assert_eq!(duplicates.load(Ordering::SeqCst), 0);
+        assert_eq!(other.as_deref(), Some("cachedContents/other"));
+    }
+
+    #[tokio::test]
+    async fn test_context_caches_delete_evicted_caches() {
+        let caches = ContextCaches::new();
+        let deleted = StdMutex::new(Vec::new());
+        let delete = |name: String| {
+            deleted.lock().unwrap().push(name);
+            async { Ok(()) }
+        };
+
+        for i in 0..MAX_CONTEXT_CACHES + 2 {
+            let create = move |_ttl: Duration| async move { Ok(format!("cachedContents/{}", i)) };
+            caches
+                .get_or_create(&format!("key-{}", i), create, delete)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            *deleted.lock().unwrap(),
+            vec![
+                "cachedContents/0".to_string(),
+                "cachedContents/1".to_string()
+            ]
+        );
+        assert_eq!(caches.entries.lock().await.len(), MAX_CONTEXT_CACHES);
+    }
+}
@@ -0,0 +1,499 @@
+//! Client-side rate limiting for provider requests.
+//!
+//! Limiters are keyed by provider and model and live for the whole process, so every
+//! `ApiClient` talking to the same model (including subagents) draws from the same budget.
+
+use once_cell::sync::Lazy;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+
+/// Header pairs (remaining, reset) that describe the request budget
+const REQUEST_LIMIT_HEADERS: &[(&str, &str)] = &[
+    (
+        "x-ratelimit-remaining-requests",
+        "x-ratelimit-reset-requests",
+    ),
+    (
+        "anthropic-ratelimit-requests-remaining",
+        "anthropic-ratelimit-requests-reset",
+    ),
+];
+
+/// Header pairs (remaining, reset) that describe the token budget
+const TOKEN_LIMIT_HEADERS: &[(&str, &str)] = &[
+    ("x-ratelimit-remaining-tokens", "x-ratelimit-reset-tokens"),
+    (
+        "anthropic-ratelimit-tokens-remaining",
+        "anthropic-ratelimit-tokens-reset",
+    ),
+];
+
+static LIMITERS: Lazy<Mutex<HashMap<String, Arc<RateLimiter>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RateLimits {
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_minute: Option<u32>,
+}
+
+impl RateLimits {
+    /// Reads `<PROVIDER>_RATE_LIMIT_RPM`/`_TPM`, falling back to `GOOSE_RATE_LIMIT_RPM`/`_TPM`
+    pub fn from_config(provider: &str) -> Self {
+        let config = Config::global();
+        let prefix = provider.to_uppercase().replace(['-', '.', ' '], "_");
+        let read = |suffix: &str| -> Option<u32> {
+            config
+                .get_param::<u32>(&format!("{}_RATE_LIMIT_{}", prefix, suffix))
+                .or_else(|_| config.get_param::<u32>(&format!("GOOSE_RATE_LIMIT_{}", suffix)))
+                .ok()
+                .filter(|limit| *limit > 0)
+        };
+        Self {
+            requests_per_minute: read("RPM"),
+            tokens_per_minute: read("TPM"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    available: f64,
+    refill_per_sec: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn per_minute(limit: u32, now: Instant) -> Self {
+        let capacity = limit as f64;
+        Self {
+            capacity,
+            available: capacity,
+            refill_per_sec: capacity / 60.0,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.available = (self.available + elapsed * self.refill_per_sec).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// Time until `amount` is available. Requests larger than the bucket only wait for a full
+    /// bucket so they can still go through.
+    fn wait_for(&self, amount: f64) -> Duration {
+        let missing = amount.min(self.capacity) - self.available;
+        if missing <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(missing / self.refill_per_sec)
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    requests: Option<TokenBucket>,
+    tokens: Option<TokenBucket>,
+    blocked_until: Option<Instant>,
+}
+
+impl LimiterState {
+    /// Takes capacity for one request of `tokens` tokens, or returns how long to wait first
+    fn try_take(&mut self, tokens: u32, now: Instant) -> Option<Duration> {
+        if let Some(until) = self.blocked_until {
+            if until > now {
+                return Some(until - now);
+            }
+            self.blocked_until = None;
+        }
+
+        let mut wait = Duration::ZERO;
+        if let Some(bucket) = self.requests.as_mut() {
+            bucket.refill(now);
+            wait = wait.max(bucket.wait_for(1.0));
+        }
+        if let Some(bucket) = self.tokens.as_mut() {
+            bucket.refill(now);
+            wait = wait.max(bucket.wait_for(tokens as f64));
+        }
+        if !wait.is_zero() {
+            return Some(wait);
+        }
+
+        if let Some(bucket) = self.requests.as_mut() {
+            bucket.available -= 1.0;
+        }
+        if let Some(bucket) = self.tokens.as_mut() {
+            bucket.available -= (tokens as f64).min(bucket.capacity);
+        }
+        None
+    }
+
+    fn block_for(&mut self, duration: Duration, now: Instant) {
+        let until = now + duration;
+        if !matches!(self.blocked_until, Some(current) if current >= until) {
+            self.blocked_until = Some(until);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    key: String,
+    limits: RateLimits,
+    state: Mutex<LimiterState>,
+    /// Serializes waiters so requests are released in arrival order
+    queue: tokio::sync::Mutex<()>,
+    waiting: AtomicUsize,
+}
+
+impl RateLimiter {
+    pub fn new(key: impl Into<String>, limits: RateLimits) -> Self {
+        let now = Instant::now();
+        Self {
+            key: key.into(),
+            limits,
+            state: Mutex::new(LimiterState {
+                requests: limits
+                    .requests_per_minute
+                    .map(|limit| TokenBucket::per_minute(limit, now)),
+                tokens: limits
+                    .tokens_per_minute
+                    .map(|limit| TokenBucket::per_minute(limit, now)),
+                blocked_until: None,
+            }),
+            queue: tokio::sync::Mutex::new(()),
+            waiting: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Number of requests currently waiting for capacity
+    pub fn queue_depth(&self) -> usize {
+        self.waiting.load(Ordering::SeqCst)
+    }
+
+    /// Waits until the request described by `payload` fits in the budget
+    pub async fn acquire_for(&self, payload: &Value) {
+        let tokens = if self.limits.tokens_per_minute.is_some() {
+            estimate_tokens(payload)
+        } else {
+            0
+        };
+        self.acquire(tokens).await
+    }
+
+    pub async fn acquire(&self, tokens: u32) {
+        let _waiting = WaitingGuard::new(&self.waiting);
+        let _turn = self.queue.lock().await;
+        loop {
+            let wait = self.state.lock().unwrap().try_take(tokens, Instant::now());
+            let Some(wait) = wait else { break };
+            tracing::info!(
+                limiter = %self.key,
+                queue_depth = self.queue_depth(),
+                wait_ms = wait.as_millis() as u64,
+                "Waiting for rate limit capacity"
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Updates the budget from what the server reported about it
+    pub fn observe(&self, status: StatusCode, headers: &HeaderMap) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+
+        if status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE {
+            if let Some(delay) = retry_after(headers) {
+                tracing::warn!(
+                    limiter = %self.key,
+                    retry_after_ms = delay.as_millis() as u64,
+                    "Provider asked us to back off"
+                );
+                state.block_for(delay, now);
+            }
+        }
+
+        for (remaining, reset) in REQUEST_LIMIT_HEADERS {
+            if let Some(remaining) = header_number(headers, remaining) {
+                if let Some(bucket) = state.requests.as_mut() {
+                    bucket.refill(now);
+                    bucket.available = bucket.available.min(remaining);
+                }
+                if remaining < 1.0 {
+                    if let Some(delay) = header_str(headers, reset).and_then(parse_reset) {
+                        state.block_for(delay, now);
+                    }
+                }
+            }
+        }
+
+        for (remaining, reset) in TOKEN_LIMIT_HEADERS {
+            if let Some(remaining) = header_number(headers, remaining) {
+                if let Some(bucket) = state.tokens.as_mut() {
+                    bucket.refill(now);
+                    bucket.available = bucket.available.min(remaining);
+                }
+                if remaining < 1.0 {
+                    if let Some(delay) = header_str(headers, reset).and_then(parse_reset) {
+                        state.block_for(delay, now);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Keeps the queue depth accurate even if a waiting request is cancelled
+struct WaitingGuard<'a>(&'a AtomicUsize);
+
+impl<'a> WaitingGuard<'a> {
+    fn new(counter: &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Self(counter)
+    }
+}
+
+impl Drop for WaitingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Returns the process-wide limiter for a provider and model
+pub fn limiter_for(provider: &str, model: &str) -> Arc<RateLimiter> {
+    let key = format!("{}/{}", provider, model);
+    let mut limiters = LIMITERS.lock().unwrap();
+    limiters
+        .entry(key.clone())
+        .or_insert_with(|| Arc::new(RateLimiter::new(key, RateLimits::from_config(provider))))
+        .clone()
+}
+
+/// The model a request is for, so that every endpoint for a model shares one limiter: the
+/// payload's `model`, else the `models/<name>` path segment (as in Google's
+/// `v1beta/models/<name>:generateContent`), else the path itself
+pub fn model_key<'a>(path: &'a str, payload: &'a Value) -> &'a str {
+    if let Some(model) = payload.get("model").and_then(Value::as_str) {
+        return model;
+    }
+    let path = path.split('?').next().unwrap_or(path);
+    match path.split_once("models/") {
+        Some((_, rest)) => {
+            let segment = rest.split('/').next().unwrap_or(rest);
+            segment.split(':').next().unwrap_or(segment)
+        }
+        None => path,
+    }
+}
+
+/// Rough token cost of a request: the prompt at ~4 bytes per token plus the requested output
+pub fn estimate_tokens(payload: &Value) -> u32 {
+    let prompt = serde_json::to_string(payload)
+        .map(|body| body.len() / 4)
+        .unwrap_or(0);
+    let output = ["max_tokens", "max_completion_tokens", "max_output_tokens"]
+        .iter()
+        .find_map(|key| payload.get(key).and_then(Value::as_u64))
+        .or_else(|| {
+            payload
+                .pointer("/generationConfig/maxOutputTokens")
+                .and_then(Value::as_u64)
+        })
+        .unwrap_or(0);
+    (prompt as u64 + output).min(u32::MAX as u64) as u32
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn header_number(headers: &HeaderMap, name: &str) -> Option<f64> {
+    header_str(headers, name).and_then(|value| value.trim().parse().ok())
+}
+
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    if let Some(ms) = header_number(headers, "retry-after-ms") {
+        return Some(Duration::from_secs_f64(ms.max(0.0) / 1000.0));
+    }
+    let value = header_str(headers, "retry-after")?.trim();
+    if let Ok(secs) = value.parse::<f64>() {
+        return Some(Duration::from_secs_f64(secs.max(0.0)));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
+
+/// Parses a reset hint: a duration like `1s`, `6m0s` or `20ms`, plain seconds, or an
+/// RFC 3339 timestamp.
+fn parse_reset(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<f64>() {
+        return Some(Duration::from_secs_f64(secs.max(0.0)));
+    }
+    if let Ok(date) = chrono::DateTime::parse_from_rfc3339(value) {
+        return (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .ok();
+    }
+
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let units = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let scale = match &rest[..units] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        total += number * scale;
+        rest = &rest[units..];
+    }
+    Some(Duration::from_secs_f64(total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_parse_reset() {
+        assert_eq!(parse_reset("1s"), Some(Duration::from_secs(1)));
+        assert_eq!(parse_reset("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(parse_reset("20ms"), Some(Duration::from_millis(20)));
+        assert_eq!(parse_reset("1h2m3s"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_reset("2.5"), Some(Duration::from_millis(2500)));
+        assert_eq!(parse_reset("soon"), None);
+    }
+
+    #[test]
+    fn test_request_bucket_waits_when_empty() {
+        let now = Instant::now();
+        let mut state = LimiterState {
+            requests: Some(TokenBucket::per_minute(2, now)),
+            ..Default::default()
+        };
+        assert_eq!(state.try_take(0, now), None);
+        assert_eq!(state.try_take(0, now), None);
+        let wait = state.try_take(0, now).expect("bucket should be empty");
+        assert_eq!(wait, Duration::from_secs(30));
+        assert_eq!(state.try_take(0, now + Duration::from_secs(30)), None);
+    }
+
+    #[test]
+    fn test_oversized_request_waits_for_full_bucket() {
+        let now = Instant::now();
+        let mut state = LimiterState {
+            tokens: Some(TokenBucket::per_minute(600, now)),
+            ..Default::default()
+        };
+        assert_eq!(state.try_take(100, now), None);
+        let wait = state.try_take(10_000, now).expect("needs a full bucket");
+        assert_eq!(wait, Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_blocks_limiter() {
+        let limiter = RateLimiter::new("test/model", RateLimits::default());
+        limiter.observe(
+            StatusCode::TOO_MANY_REQUESTS,
+            &headers(&[("retry-after", "2")]),
+        );
+        let wait = limiter
+            .state
+            .lock()
+            .unwrap()
+            .try_take(0, Instant::now())
+            .expect("should be blocked");
+        assert!(wait > Duration::from_secs(1) && wait <= Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_remaining_headers_shrink_budget() {
+        let limiter = RateLimiter::new(
+            "test/model",
+            RateLimits {
+                requests_per_minute: Some(60),
+                tokens_per_minute: None,
+            },
+        );
+        limiter.observe(
+            StatusCode::OK,
+            &headers(&[
+                ("x-ratelimit-remaining-requests", "0"),
+                ("x-ratelimit-reset-requests", "3s"),
+            ]),
+        );
+        let mut state = limiter.state.lock().unwrap();
+        let wait = state.try_take(0, Instant::now()).expect("should wait");
+        assert!(wait > Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_estimate_tokens_includes_output_budget() {
+        let payload = json!({"model": "m", "max_tokens": 1000});
+        let prompt = serde_json::to_string(&payload).unwrap().len() as u32 / 4;
+        assert_eq!(estimate_tokens(&payload), prompt + 1000);
+    }
+
+    #[test]
+    fn test_limiters_are_shared_per_key() {
+        let a = limiter_for("shared-test", "model-a");
+        let b = limiter_for("shared-test", "model-a");
+        let c = limiter_for("shared-test", "model-b");
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+    }
+
+    #[test]
+    fn test_model_key() {
+        let empty = json!({});
+        for path in [
+            "v1beta/models/gemini-2.5-pro:generateContent",
+            "v1beta/models/gemini-2.5-pro:streamGenerateContent?alt=sse",
+            "v1beta/models/gemini-2.5-pro:countTokens",
+        ] {
+            assert_eq!(model_key(path, &empty), "gemini-2.5-pro");
+        }
+        assert_eq!(
+            model_key("v1/messages", &json!({"model": "claude-sonnet-4"})),
+            "claude-sonnet-4"
+        );
+        assert_eq!(model_key("v1/messages?beta=true", &empty), "v1/messages");
+    }
+}
@@ -2,6 +2,7 @@ use super::errors::ProviderError;
 use crate::providers::base::Provider;
 use async_trait::async_trait;
 use std::future::Future;
+use std::sync::OnceLock;
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -10,6 +11,20 @@ pub const DEFAULT_INITIAL_RETRY_INTERVAL_MS: u64 = 1000;
 pub const DEFAULT_BACKOFF_MULTIPLIER: f64 = 2.0;
 pub const DEFAULT_MAX_RETRY_INTERVAL_MS: u64 = 30_000;
 
+static RETRY_CONFIG_OVERRIDE: OnceLock<RetryConfig> = OnceLock::new();
+
+/// Use `config` in place of every provider's own retry config for the rest of the process,
+/// e.g. so tests of error handling do not wait out real backoff. Returns false if an
+/// override was already set.
+pub fn override_retry_config(config: RetryConfig) -> bool {
+    RETRY_CONFIG_OVERRIDE.set(config).is_ok()
+}
+
+/// `config`, unless the process replaced it with [`override_retry_config`]
+pub(crate) fn effective_retry_config(config: RetryConfig) -> RetryConfig {
+    RETRY_CONFIG_OVERRIDE.get().cloned().unwrap_or(config)
+}
+
 #[derive(Debug, Clone)]
 pub struct RetryConfig {
     /// Maximum number of retry attempts
@@ -81,7 +96,7 @@ pub trait ProviderRetry {
         T: Send,
     {
         let mut attempts = 0;
-        let config = self.retry_config();
+        let config = effective_retry_config(self.retry_config());
 
         loop {
             return match operation().await {
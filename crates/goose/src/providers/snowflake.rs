@@ -4,11 +4,15 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use super::api_client::{ApiClient, AuthMethod};
-use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage};
+use super::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage};
 use super::errors::ProviderError;
-use super::formats::snowflake::{create_request, get_usage, response_to_message};
+use super::formats::snowflake::{
+    create_request, get_usage, response_to_message, response_to_streaming_message,
+};
 use super::retry::ProviderRetry;
-use super::utils::{get_model, map_http_error_to_provider_error, ImageFormat};
+use super::utils::{
+    get_model, map_http_error_to_provider_error, response_lines, stream_messages, ImageFormat,
+};
 use crate::config::ConfigError;
 use crate::conversation::message::Message;
 use crate::impl_provider_default;
@@ -61,8 +65,12 @@ impl SnowflakeProvider {
         // Convert host to lowercase
         host = host.to_lowercase();
 
-        // Ensure host ends with snowflakecomputing.com
-        if !host.ends_with("snowflakecomputing.com") {
+        // Ensure host ends with snowflakecomputing.com, unless it names a port (e.g. a proxy)
+        let has_port = host
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .contains(':');
+        if !host.ends_with("snowflakecomputing.com") && !has_port {
             host = format!("{}.snowflakecomputing.com", host);
         }
 
@@ -87,7 +95,9 @@ impl SnowflakeProvider {
         };
 
         let auth = AuthMethod::BearerToken(token?);
-        let api_client = ApiClient::new(base_url, auth)?.with_header("User-Agent", "Goose")?;
+        let api_client = ApiClient::new(base_url, auth)?
+            .with_header("User-Agent", "Goose")?
+            .with_rate_limit("snowflake");
 
         Ok(Self {
             api_client,
@@ -326,4 +336,38 @@ impl Provider for SnowflakeProvider {
 
         Ok((message, ProviderUsage::new(response_model, usage)))
     }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let payload = create_request(&self.model, system, messages, tools)?;
+
+        let response = self
+            .with_retry(|| async {
+                let response = self
+                    .api_client
+                    .response_post("api/v2/cortex/inference:complete", &payload)
+                    .await?;
+                let status = response.status();
+                if !status.is_success() {
+                    let error_json = response.json::<Value>().await.ok();
+                    return Err(map_http_error_to_provider_error(status, error_json));
+                }
+                Ok(response)
+            })
+            .await?;
+
+        Ok(stream_messages(
+            response_to_streaming_message(response_lines(response)),
+            self.model.clone(),
+            payload,
+        ))
+    }
 }
@@ -0,0 +1,217 @@
+//! Native structured output: completions constrained to a JSON schema by the provider,
+//! validated against that schema and repaired by re-asking the model when they don't match.
+
+use serde_json::Value;
+
+use super::base::{Provider, ProviderUsage};
+use super::errors::ProviderError;
+use crate::conversation::message::Message;
+
+/// Name given to the schema (or the forced tool) in structured output requests
+pub const STRUCTURED_OUTPUT_NAME: &str = "structured_output";
+
+/// How many times the model is asked to fix output that does not match the schema
+pub const MAX_REPAIR_ATTEMPTS: usize = 2;
+
+/// Returns the schema violations of `value`, empty when it is valid
+pub fn validation_errors(schema: &Value, value: &Value) -> Result<Vec<String>, ProviderError> {
+    let validator = jsonschema::validator_for(schema)
+        .map_err(|e| ProviderError::UsageError(format!("Invalid JSON schema: {}", e)))?;
+    Ok(validator
+        .iter_errors(value)
+        .map(|error| format!("- {}: {}", error.instance_path, error))
+        .collect())
+}
+
+/// The JSON produced by a structured completion: the arguments of a forced tool call, or
+/// otherwise the text of the message, which may be wrapped in a markdown code fence
+pub fn extract_json(message: &Message) -> Result<Value, String> {
+    if let Some(call) = message
+        .content
+        .iter()
+        .filter_map(|content| content.as_tool_request())
+        .find_map(|request| request.tool_call.as_ref().ok())
+    {
+        return Ok(call.arguments.clone());
+    }
+
+    let text = message.as_concat_text();
+    let trimmed = text.trim();
+    let unfenced = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.strip_suffix("```"))
+        .unwrap_or(trimmed);
+    serde_json::from_str(unfenced.trim()).map_err(|e| format!("- output is not valid JSON: {}", e))
+}
+
+/// Asks `provider` for JSON matching `schema`, validating the result and asking the model
+/// to repair it up to [`MAX_REPAIR_ATTEMPTS`] times
+pub async fn complete_structured(
+    provider: &dyn Provider,
+    system: &str,
+    messages: &[Message],
+    schema: &Value,
+) -> Result<(Value, ProviderUsage), ProviderError> {
+    let mut conversation = messages.to_vec();
+    let mut total_usage = None::<ProviderUsage>;
+
+    for attempt in 0..=MAX_REPAIR_ATTEMPTS {
+        let (message, usage) = provider
+            .complete_structured(system, &conversation, schema)
+            .await?;
+        let total_usage = match total_usage.as_mut() {
+            Some(total) => {
+                total.usage += usage.usage;
+                total
+            }
+            None => total_usage.insert(usage),
+        };
+
+        let (output, errors) = match extract_json(&message) {
+            Ok(value) => {
+                let errors = validation_errors(schema, &value)?;
+                if errors.is_empty() {
+                    return Ok((value, total_usage.clone()));
+                }
+                (value.to_string(), errors.join("\n"))
+            }
+            Err(error) => (message.as_concat_text(), error),
+        };
+
+        tracing::warn!(
+            "Structured output failed validation (attempt {}):\n{}",
+            attempt + 1,
+            errors
+        );
+        conversation.push(Message::assistant().with_text(output));
+        conversation.push(Message::user().with_text(format!(
+            "Your output does not match the required JSON schema:\n{}\n\nRespond again with only JSON that matches the schema.",
+            errors
+        )));
+    }
+
+    Err(ProviderError::ExecutionError(format!(
+        "Structured output did not match the schema after {} attempts",
+        MAX_REPAIR_ATTEMPTS + 1
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ModelConfig;
+    use crate::providers::base::{ProviderMetadata, Usage};
+    use async_trait::async_trait;
+    use mcp_core::ToolCall;
+    use rmcp::model::Tool;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    struct ScriptedProvider {
+        replies: Mutex<Vec<Message>>,
+        seen: Mutex<Vec<usize>>,
+    }
+
+    impl ScriptedProvider {
+        fn new(replies: Vec<Message>) -> Self {
+            Self {
+                replies: Mutex::new(replies),
+                seen: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            ModelConfig::new_or_fail("test")
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            unimplemented!()
+        }
+
+        fn supports_structured_output(&self) -> bool {
+            true
+        }
+
+        async fn complete_structured(
+            &self,
+            _system: &str,
+            messages: &[Message],
+            _schema: &Value,
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            self.seen.lock().unwrap().push(messages.len());
+            let reply = self.replies.lock().unwrap().remove(0);
+            Ok((
+                reply,
+                ProviderUsage::new("test".to_string(), Usage::new(Some(10), Some(5), Some(15))),
+            ))
+        }
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {"answer": {"type": "integer"}},
+            "required": ["answer"]
+        })
+    }
+
+    #[test]
+    fn test_extract_json() {
+        let fenced = Message::assistant().with_text("```json\n{\"answer\": 1}\n```");
+        assert_eq!(extract_json(&fenced).unwrap(), json!({"answer": 1}));
+
+        let tool = Message::assistant().with_tool_request(
+            "1",
+            Ok(ToolCall::new(STRUCTURED_OUTPUT_NAME, json!({"answer": 2}))),
+        );
+        assert_eq!(extract_json(&tool).unwrap(), json!({"answer": 2}));
+
+        let prose = Message::assistant().with_text("the answer is 3");
+        assert!(extract_json(&prose).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_complete_structured_repairs_invalid_output() {
+        let provider = ScriptedProvider::new(vec![
+            Message::assistant().with_text("not json"),
+            Message::assistant().with_text("{\"answer\": \"three\"}"),
+            Message::assistant().with_text("{\"answer\": 3}"),
+        ]);
+        let messages = vec![Message::user().with_text("what is 1 + 2?")];
+
+        let (value, usage) = complete_structured(&provider, "system", &messages, &schema())
+            .await
+            .unwrap();
+
+        assert_eq!(value, json!({"answer": 3}));
+        assert_eq!(usage.usage.total_tokens, Some(45));
+        assert_eq!(*provider.seen.lock().unwrap(), vec![1, 3, 5]);
+    }
+
+    #[tokio::test]
+    async fn test_complete_structured_gives_up() {
+        let provider = ScriptedProvider::new(vec![
+            Message::assistant().with_text("{}"),
+            Message::assistant().with_text("{}"),
+            Message::assistant().with_text("{}"),
+        ]);
+        let messages = vec![Message::user().with_text("answer")];
+
+        let result = complete_structured(&provider, "system", &messages, &schema()).await;
+        assert!(matches!(result, Err(ProviderError::ExecutionError(_))));
+    }
+}
@@ -1,13 +1,6 @@
 use anyhow::Result;
-use async_stream::try_stream;
 use async_trait::async_trait;
-use futures::TryStreamExt;
 use serde_json::{json, Value};
-use std::io;
-use tokio::pin;
-use tokio_stream::StreamExt;
-use tokio_util::codec::{FramedRead, LinesCodec};
-use tokio_util::io::StreamReader;
 
 use super::api_client::{ApiClient, AuthMethod};
 use super::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage};
@@ -16,7 +9,7 @@ use super::formats::openai::response_to_streaming_message;
 use super::retry::ProviderRetry;
 use super::utils::{
     emit_debug_trace, get_model, handle_response_google_compat, handle_response_openai_compat,
-    handle_status_openai_compat, is_google_model,
+    handle_status_openai_compat, is_google_model, response_lines, stream_messages,
 };
 use crate::config::signup_tetrate::TETRATE_DEFAULT_MODEL;
 use crate::conversation::message::Message;
@@ -62,7 +55,8 @@ impl TetrateProvider {
         let auth = AuthMethod::BearerToken(api_key);
         let api_client = ApiClient::new(host, auth)?
             .with_header("HTTP-Referer", "https://block.github.io/goose")?
-            .with_header("X-Title", "Goose")?;
+            .with_header("X-Title", "Goose")?
+            .with_rate_limit("tetrate");
 
         Ok(Self {
             api_client,
@@ -83,9 +77,7 @@ impl TetrateProvider {
         }
 
         // For OpenAI-compatible models, parse the response body to JSON
-        let response_body = handle_response_openai_compat(response)
-            .await
-            .map_err(|e| ProviderError::RequestFailed(format!("Failed to parse response: {e}")))?;
+        let response_body = handle_response_openai_compat(response).await?;
 
         let _debug = format!(
             "Tetrate Agent Router Service request with payload: {} and response: {}",
@@ -216,21 +208,12 @@ impl Provider for TetrateProvider {
             .await?;
 
         let response = handle_status_openai_compat(response).await?;
-        let stream = response.bytes_stream().map_err(io::Error::other);
-        let model_config = self.model.clone();
-
-        Ok(Box::pin(try_stream! {
-            let stream_reader = StreamReader::new(stream);
-            let framed = FramedRead::new(stream_reader, LinesCodec::new()).map_err(anyhow::Error::from);
-
-            let message_stream = response_to_streaming_message(framed);
-            pin!(message_stream);
-            while let Some(message) = message_stream.next().await {
-                let (message, usage) = message.map_err(|e| ProviderError::RequestFailed(format!("Stream decode error: {}", e)))?;
-                emit_debug_trace(&model_config, &payload, &message, &usage.as_ref().map(|f| f.usage).unwrap_or_default());
-                yield (message, usage);
-            }
-        }))
+
+        Ok(stream_messages(
+            response_to_streaming_message(response_lines(response)),
+            self.model.clone(),
+            payload,
+        ))
     }
 
     /// Fetch supported models from Tetrate Agent Router Service API (only models with tool support)
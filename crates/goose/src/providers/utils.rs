@@ -1,8 +1,11 @@
-use super::base::Usage;
+use super::base::{MessageStream, ProviderUsage, Usage};
 use super::errors::GoogleErrorCode;
+use crate::conversation::message::Message;
 use crate::model::ModelConfig;
 use anyhow::Result;
+use async_stream::try_stream;
 use base64::Engine;
+use futures::{Stream, StreamExt, TryStreamExt};
 use regex::Regex;
 use reqwest::{Response, StatusCode};
 use rmcp::model::{AnnotateAble, ImageContent, RawImageContent};
@@ -10,6 +13,9 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
 use std::io::Read;
 use std::path::Path;
+use std::pin::Pin;
+use tokio_util::codec::{FramedRead, LinesCodec};
+use tokio_util::io::StreamReader;
 
 use crate::providers::errors::{OpenAIError, ProviderError};
 
@@ -253,6 +259,9 @@ pub async fn handle_response_google_compat(response: Response) -> Result<Value,
             );
             Err(ProviderError::RequestFailed(format!("Request failed with status: {}. Message: {}", final_status, error_msg)))
         }
+        StatusCode::TOO_MANY_REQUESTS => {
+            Err(ProviderError::RateLimitExceeded(format!("{:?}", payload)))
+        }
         _ if final_status.is_server_error() => {
             Err(ProviderError::ServerError(format!("{:?}", payload)))
         }
@@ -268,6 +277,47 @@ pub async fn handle_response_google_compat(response: Response) -> Result<Value,
     }
 }
 
+/// Like `handle_status_openai_compat`, for Google Gemini API-compatible endpoints.
+///
+/// Returns the response untouched when it succeeded, so its body can still be streamed.
+pub async fn handle_status_google_compat(response: Response) -> Result<Response, ProviderError> {
+    if response.status() == StatusCode::OK {
+        return Ok(response);
+    }
+    let status = response.status();
+    match handle_response_google_compat(response).await {
+        Ok(payload) => Err(map_http_error_to_provider_error(status, Some(payload))),
+        Err(e) => Err(e),
+    }
+}
+
+/// The lines of a streamed response body, e.g. server-sent events
+pub type LineStream = Pin<Box<dyn Stream<Item = Result<String>> + Send>>;
+
+/// Split the body of a streaming response into lines
+pub fn response_lines(response: Response) -> LineStream {
+    let bytes = response.bytes_stream().map_err(std::io::Error::other);
+    let lines =
+        FramedRead::new(StreamReader::new(bytes), LinesCodec::new()).map_err(anyhow::Error::from);
+    Box::pin(lines)
+}
+
+/// Turn a decoded stream of messages into a provider `MessageStream`, emitting a debug
+/// trace for every item like the non-streaming completions do
+pub fn stream_messages<S>(messages: S, model_config: ModelConfig, payload: Value) -> MessageStream
+where
+    S: Stream<Item = Result<(Option<Message>, Option<ProviderUsage>)>> + Send + 'static,
+{
+    Box::pin(try_stream! {
+        let mut messages = Box::pin(messages);
+        while let Some(message) = messages.next().await {
+            let (message, usage) = message.map_err(|e| ProviderError::RequestFailed(format!("Stream decode error: {}", e)))?;
+            emit_debug_trace(&model_config, &payload, &message, &usage.as_ref().map(|f| f.usage).unwrap_or_default());
+            yield (message, usage);
+        }
+    })
+}
+
 pub fn sanitize_function_name(name: &str) -> String {
     let re = Regex::new(r"[^a-zA-Z0-9_-]").unwrap();
     re.replace_all(name, "_").to_string()
@@ -922,6 +972,30 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_handle_response_google_compat_rate_limit() {
+        let mock_server = MockServer::start().await;
+        Mock::given(matchers::method("POST"))
+            .respond_with(ResponseTemplate::new(429).set_body_json(json!({
+                "error": {
+                    "code": 429,
+                    "message": "Resource has been exhausted (e.g. check quota).",
+                    "status": "RESOURCE_EXHAUSTED"
+                }
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let response = reqwest::Client::new()
+            .post(mock_server.uri())
+            .send()
+            .await
+            .unwrap();
+        let result = handle_response_google_compat(response).await;
+
+        assert!(matches!(result, Err(ProviderError::RateLimitExceeded(_))));
+    }
+
     #[test]
     fn test_map_http_error_to_provider_error() {
         let test_cases = vec![
@@ -1,14 +1,16 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::Utc;
+use reqwest::Response;
 use serde::Serialize;
 use serde_json::{json, Value};
 
 use super::api_client::{ApiClient, AuthMethod};
-use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::errors::ProviderError;
+use super::formats::openai::response_to_streaming_message;
 use super::retry::ProviderRetry;
-use super::utils::map_http_error_to_provider_error;
+use super::utils::{map_http_error_to_provider_error, response_lines, stream_messages};
 use crate::conversation::message::{Message, MessageContent};
 use crate::impl_provider_default;
 use crate::model::ModelConfig;
@@ -100,7 +102,7 @@ impl VeniceProvider {
         model.model_name = strip_flags(&model.model_name).to_string();
 
         let auth = AuthMethod::BearerToken(api_key);
-        let api_client = ApiClient::new(host, auth)?;
+        let api_client = ApiClient::new(host, auth)?.with_rate_limit("venice");
 
         let instance = Self {
             api_client,
@@ -112,9 +114,7 @@ impl VeniceProvider {
         Ok(instance)
     }
 
-    async fn post(&self, path: &str, payload: &Value) -> Result<Value, ProviderError> {
-        let response = self.api_client.response_post(path, payload).await?;
-
+    async fn handle_status(&self, response: Response) -> Result<Response, ProviderError> {
         let status = response.status();
         tracing::debug!("Venice response status: {}", status);
 
@@ -173,6 +173,13 @@ impl VeniceProvider {
             return Err(map_http_error_to_provider_error(status, error_json));
         }
 
+        Ok(response)
+    }
+
+    async fn post(&self, path: &str, payload: &Value) -> Result<Value, ProviderError> {
+        let response = self.api_client.response_post(path, payload).await?;
+        let response = self.handle_status(response).await?;
+
         let response_text = response.text().await?;
         serde_json::from_str(&response_text).map_err(|e| {
             ProviderError::RequestFailed(format!(
@@ -183,6 +190,179 @@ impl VeniceProvider {
     }
 }
 
+/// Build a Venice chat completions request
+fn create_request(
+    model_config: &ModelConfig,
+    system: &str,
+    messages: &[Message],
+    tools: &[Tool],
+) -> Value {
+    // Create properly formatted messages for Venice API
+    let mut formatted_messages = Vec::new();
+
+    // Add the system message if present
+    if !system.is_empty() {
+        formatted_messages.push(json!({
+            "role": "system",
+            "content": system
+        }));
+    }
+
+    // Format regular messages according to Venice API requirements
+    for msg in messages {
+        // Venice API expects 'content' to be a string, not an array of MessageContent
+        let content = match msg.role {
+            Role::User => {
+                // For user messages, concatenate all text content
+                let text_content: String = msg
+                    .content
+                    .iter()
+                    .filter_map(|c| c.as_text())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                // If we have text content, use it directly
+                if !text_content.is_empty() {
+                    text_content
+                } else {
+                    // Otherwise, try to get a reasonable string representation
+                    msg.as_concat_text()
+                }
+            }
+            _ => {
+                // For assistant messages, handle possible tool calls
+                let has_tool_calls = msg
+                    .content
+                    .iter()
+                    .any(|c| matches!(c, MessageContent::ToolRequest(_)));
+
+                if has_tool_calls {
+                    // If there are tool calls, we'll handle them separately
+                    // Just use an empty string for content
+                    "".to_string()
+                } else {
+                    // Otherwise use text content
+                    msg.as_concat_text()
+                }
+            }
+        };
+
+        // Create basic message with content as string
+        let mut venice_msg = json!({
+            "role": match msg.role {
+                Role::User => "user",
+                Role::Assistant => "assistant",
+            },
+            "content": content
+        });
+
+        // Add debug information to tracing
+        tracing::debug!(
+            "Venice message format: role={:?}, content_len={}, has_tool_calls={}",
+            msg.role,
+            content.len(),
+            msg.content
+                .iter()
+                .any(|c| matches!(c, MessageContent::ToolRequest(_)))
+        );
+
+        // For assistant messages with tool calls, add them in Venice format
+        if msg.role == Role::Assistant {
+            let tool_calls: Vec<_> = msg
+                .content
+                .iter()
+                .filter_map(|c| c.as_tool_request())
+                .collect();
+
+            if !tool_calls.is_empty() {
+                // Transform our tool calls to Venice format
+                let venice_tool_calls: Vec<Value> = tool_calls
+                    .iter()
+                    .filter_map(|tr| {
+                        if let ToolResult::Ok(tool_call) = &tr.tool_call {
+                            // Log tool call details for debugging
+                            tracing::debug!(
+                                "Tool call conversion: id={}, name={}, args_len={}",
+                                tr.id,
+                                tool_call.name,
+                                tool_call.arguments.to_string().len()
+                            );
+
+                            // Convert to Venice format
+                            Some(json!({
+                                "id": tr.id,
+                                "type": "function",
+                                "function": {
+                                    "name": tool_call.name,
+                                    "arguments": tool_call.arguments.to_string()
+                                }
+                            }))
+                        } else {
+                            tracing::warn!("Skipping tool call with error: id={}", tr.id);
+                            None
+                        }
+                    })
+                    .collect();
+
+                if !venice_tool_calls.is_empty() {
+                    tracing::debug!("Adding {} tool calls to message", venice_tool_calls.len());
+                    venice_msg["tool_calls"] = json!(venice_tool_calls);
+                }
+            }
+        }
+
+        // For tool messages with tool responses, add required tool_call_id
+        // Check for tool responses regardless of role - they should have an ID
+        // that corresponds to the tool call they're responding to
+        {
+            let tool_responses: Vec<_> = msg
+                .content
+                .iter()
+                .filter_map(|c| c.as_tool_response())
+                .collect();
+
+            if !tool_responses.is_empty() && !tool_responses[0].id.is_empty() {
+                venice_msg["tool_call_id"] = json!(tool_responses[0].id);
+                // Venice expects tool messages to have 'role' = 'tool'
+                venice_msg["role"] = json!("tool");
+            }
+        }
+
+        formatted_messages.push(venice_msg);
+    }
+
+    // Build Venice-specific payload
+    let mut payload = json!({
+        "model": strip_flags(&model_config.model_name),
+        "messages": formatted_messages,
+        "stream": false,
+        "temperature": 0.7,
+        "max_tokens": 2048,
+    });
+
+    if !tools.is_empty() {
+        // Format tools specifically for Venice API
+        let formatted_tools: Vec<serde_json::Value> = tools
+            .iter()
+            .map(|tool| {
+                // Format each tool in the expected Venice format
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": tool.input_schema
+                    }
+                })
+            })
+            .collect();
+
+        payload["tools"] = json!(formatted_tools);
+    }
+
+    payload
+}
+
 #[async_trait]
 impl Provider for VeniceProvider {
     fn metadata() -> ProviderMetadata {
@@ -256,168 +436,7 @@ impl Provider for VeniceProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<(Message, ProviderUsage), ProviderError> {
-        // Create properly formatted messages for Venice API
-        let mut formatted_messages = Vec::new();
-
-        // Add the system message if present
-        if !system.is_empty() {
-            formatted_messages.push(json!({
-                "role": "system",
-                "content": system
-            }));
-        }
-
-        // Format regular messages according to Venice API requirements
-        for msg in messages {
-            // Venice API expects 'content' to be a string, not an array of MessageContent
-            let content = match msg.role {
-                Role::User => {
-                    // For user messages, concatenate all text content
-                    let text_content: String = msg
-                        .content
-                        .iter()
-                        .filter_map(|c| c.as_text())
-                        .collect::<Vec<_>>()
-                        .join("\n");
-
-                    // If we have text content, use it directly
-                    if !text_content.is_empty() {
-                        text_content
-                    } else {
-                        // Otherwise, try to get a reasonable string representation
-                        msg.as_concat_text()
-                    }
-                }
-                _ => {
-                    // For assistant messages, handle possible tool calls
-                    let has_tool_calls = msg
-                        .content
-                        .iter()
-                        .any(|c| matches!(c, MessageContent::ToolRequest(_)));
-
-                    if has_tool_calls {
-                        // If there are tool calls, we'll handle them separately
-                        // Just use an empty string for content
-                        "".to_string()
-                    } else {
-                        // Otherwise use text content
-                        msg.as_concat_text()
-                    }
-                }
-            };
-
-            // Create basic message with content as string
-            let mut venice_msg = json!({
-                "role": match msg.role {
-                    Role::User => "user",
-                    Role::Assistant => "assistant",
-                },
-                "content": content
-            });
-
-            // Add debug information to tracing
-            tracing::debug!(
-                "Venice message format: role={:?}, content_len={}, has_tool_calls={}",
-                msg.role,
-                content.len(),
-                msg.content
-                    .iter()
-                    .any(|c| matches!(c, MessageContent::ToolRequest(_)))
-            );
-
-            // For assistant messages with tool calls, add them in Venice format
-            if msg.role == Role::Assistant {
-                let tool_calls: Vec<_> = msg
-                    .content
-                    .iter()
-                    .filter_map(|c| c.as_tool_request())
-                    .collect();
-
-                if !tool_calls.is_empty() {
-                    // Transform our tool calls to Venice format
-                    let venice_tool_calls: Vec<Value> = tool_calls
-                        .iter()
-                        .filter_map(|tr| {
-                            if let ToolResult::Ok(tool_call) = &tr.tool_call {
-                                // Log tool call details for debugging
-                                tracing::debug!(
-                                    "Tool call conversion: id={}, name={}, args_len={}",
-                                    tr.id,
-                                    tool_call.name,
-                                    tool_call.arguments.to_string().len()
-                                );
-
-                                // Convert to Venice format
-                                Some(json!({
-                                    "id": tr.id,
-                                    "type": "function",
-                                    "function": {
-                                        "name": tool_call.name,
-                                        "arguments": tool_call.arguments.to_string()
-                                    }
-                                }))
-                            } else {
-                                tracing::warn!("Skipping tool call with error: id={}", tr.id);
-                                None
-                            }
-                        })
-                        .collect();
-
-                    if !venice_tool_calls.is_empty() {
-                        tracing::debug!("Adding {} tool calls to message", venice_tool_calls.len());
-                        venice_msg["tool_calls"] = json!(venice_tool_calls);
-                    }
-                }
-            }
-
-            // For tool messages with tool responses, add required tool_call_id
-            // Check for tool responses regardless of role - they should have an ID
-            // that corresponds to the tool call they're responding to
-            {
-                let tool_responses: Vec<_> = msg
-                    .content
-                    .iter()
-                    .filter_map(|c| c.as_tool_response())
-                    .collect();
-
-                if !tool_responses.is_empty() && !tool_responses[0].id.is_empty() {
-                    venice_msg["tool_call_id"] = json!(tool_responses[0].id);
-                    // Venice expects tool messages to have 'role' = 'tool'
-                    venice_msg["role"] = json!("tool");
-                }
-            }
-
-            formatted_messages.push(venice_msg);
-        }
-
-        // Build Venice-specific payload
-        let mut payload = json!({
-            "model": strip_flags(&model_config.model_name),
-            "messages": formatted_messages,
-            "stream": false,
-            "temperature": 0.7,
-            "max_tokens": 2048,
-        });
-
-        if !tools.is_empty() {
-            // Format tools specifically for Venice API
-            let formatted_tools: Vec<serde_json::Value> = tools
-                .iter()
-                .map(|tool| {
-                    // Format each tool in the expected Venice format
-                    json!({
-                        "type": "function",
-                        "function": {
-                            "name": tool.name,
-                            "description": tool.description,
-                            "parameters": tool.input_schema
-                        }
-                    })
-                })
-                .collect();
-
-            payload["tools"] = json!(formatted_tools);
-        }
+        let payload = create_request(model_config, system, messages, tools);
 
         tracing::debug!("Sending request to Venice API");
         tracing::debug!("Venice request payload: {}", payload.to_string());
@@ -494,17 +513,50 @@ impl Provider for VeniceProvider {
 
         // Extract usage
         let usage_data = &response_json["usage"];
-        let usage = Usage {
-            input_tokens: usage_data["prompt_tokens"].as_i64().map(|v| v as i32),
-            output_tokens: usage_data["completion_tokens"].as_i64().map(|v| v as i32),
-            total_tokens: usage_data["total_tokens"].as_i64().map(|v| v as i32),
-        };
+        let usage = Usage::new(
+            usage_data["prompt_tokens"].as_i64().map(|v| v as i32),
+            usage_data["completion_tokens"].as_i64().map(|v| v as i32),
+            usage_data["total_tokens"].as_i64().map(|v| v as i32),
+        );
 
         Ok((
             Message::new(Role::Assistant, Utc::now().timestamp(), content),
             ProviderUsage::new(strip_flags(&self.model.model_name).to_string(), usage),
         ))
     }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let mut payload = create_request(&self.model, system, messages, tools);
+        payload["stream"] = json!(true);
+        payload["stream_options"] = json!({
+            "include_usage": true,
+        });
+
+        let response = self
+            .with_retry(|| async {
+                let response = self
+                    .api_client
+                    .response_post(&self.base_path, &payload)
+                    .await?;
+                self.handle_status(response).await
+            })
+            .await?;
+
+        Ok(stream_messages(
+            response_to_streaming_message(response_lines(response)),
+            self.model.clone(),
+            payload,
+        ))
+    }
 }
 
 #[cfg(test)]
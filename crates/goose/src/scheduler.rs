@@ -13,7 +13,7 @@ use tokio::sync::Mutex;
 use tokio_cron_scheduler::{job::JobId, Job, JobScheduler as TokioJobScheduler};
 
 use crate::agents::AgentEvent;
-use crate::agents::{Agent, SessionConfig};
+use crate::agents::{Agent, SessionBudget, SessionConfig};
 use crate::config::{self, Config};
 use crate::conversation::message::Message;
 use crate::conversation::Conversation;
@@ -160,6 +160,9 @@ pub struct ScheduledJob {
     pub process_start_time: Option<DateTime<Utc>>,
     #[serde(default)]
     pub execution_mode: Option<String>, // "foreground" or "background"
+    /// Token, cost and time limits applied to each run of the job
+    #[serde(default)]
+    pub budget: Option<SessionBudget>,
 }
 
 async fn persist_jobs_from_arc(
@@ -1222,6 +1225,7 @@ async fn run_scheduled_job_internal(
             execution_mode: job.execution_mode.clone(),
             max_turns: None,
             retry_config: None,
+            budget: job.budget.clone(),
         };
 
         match agent
@@ -1255,6 +1259,18 @@ async fn run_scheduled_job_internal(
                         Ok(AgentEvent::HistoryReplaced(_)) => {
                             // Handle history replacement events if needed
                         }
+                        Ok(AgentEvent::BudgetExceeded(exceeded)) => {
+                            tracing::warn!("[Job {}] Stopped: {}", job.id, exceeded);
+                        }
+                        Ok(AgentEvent::ModelFallback(fallback)) => {
+                            tracing::warn!(
+                                "[Job {}] Switched to {}/{}: {}",
+                                job.id,
+                                fallback.to_provider,
+                                fallback.to_model,
+                                fallback.reason
+                            );
+                        }
                         Err(e) => {
                             tracing::error!(
                                 "[Job {}] Error receiving message from agent: {}",
@@ -1300,6 +1316,12 @@ async fn run_scheduled_job_internal(
                             accumulated_output_tokens: None,
                             extension_data: crate::session::ExtensionData::new(),
                             recipe: None,
+                            parent_session_id: None,
+                            fork_point: None,
+                            model_usage: Vec::new(),
+                            budget_exceeded: None,
+                            checkpoints: Vec::new(),
+                            rolling_summary: None,
                         };
                         if let Err(e_fb) = crate::session::storage::save_messages_with_metadata(
                             &session_file_path,
@@ -1474,6 +1496,7 @@ mod tests {
             current_session_id: None,
             process_start_time: None,
             execution_mode: Some("background".to_string()), // Default for test
+            budget: None,
         };
 
         let mock_model_config = ModelConfig::new_or_fail("test_model");
@@ -0,0 +1,738 @@
+//! Workspace checkpoints, taken before each agent turn so that a session can be rewound.
+//!
+//! Files are stored in a content-addressed store next to the session files rather than in
+//! the user's git history: every file is saved once under the hash of its content, and each
+//! checkpoint writes a manifest mapping the paths in the working directory to those hashes.
+//! The checkpoints themselves are recorded in the session metadata.
+//!
+//! Checkpoints are opt-in through `GOOSE_CHECKPOINTS`. Hidden files and directories (such
+//! as `.env` or `.ssh`) are never copied, and the store of a session is capped at
+//! [`MAX_STORE_BYTES`] by dropping its oldest checkpoints.
+
+use crate::config::Config;
+use crate::conversation::message::Message;
+use crate::conversation::Conversation;
+use crate::session::storage::{self, SessionMetadata};
+use crate::session::usage::RECORD_LOCK;
+use crate::utils::safe_truncate;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use ignore::WalkBuilder;
+use rmcp::model::Role;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use utoipa::ToSchema;
+
+/// Config key to turn workspace checkpoints on
+pub const GOOSE_CHECKPOINTS: &str = "GOOSE_CHECKPOINTS";
+
+/// The oldest checkpoints of a session are dropped once its store grows beyond this
+pub const MAX_STORE_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Working directories with more files than this are not checkpointed
+const MAX_FILES: usize = 10_000;
+/// Files larger than this are left out of checkpoints, and are never touched by a rewind
+const MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+const PROMPT_PREVIEW_CHARS: usize = 80;
+
+/// A snapshot of the working directory taken before an agent turn
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Checkpoint {
+    /// Turn number, counting the checkpointed turns of the session from 1
+    pub turn: usize,
+    /// Number of messages in the conversation before the user message that started the turn
+    pub message_count: usize,
+    /// Start of the user message that started the turn
+    pub prompt: String,
+    /// The directory that was snapshotted
+    #[schema(value_type = String)]
+    pub working_dir: PathBuf,
+    /// Number of files in the snapshot
+    pub file_count: usize,
+    /// Unix timestamp (in seconds) of when the snapshot was taken
+    pub created_at: i64,
+}
+
+/// What a rewind changed
+#[derive(Debug, Clone)]
+pub struct RewindOutcome {
+    /// The checkpoint the session was rewound to
+    pub checkpoint: Checkpoint,
+    /// Files written back with their content from the checkpoint
+    pub restored: Vec<PathBuf>,
+    /// Files removed because they did not exist at the checkpoint
+    pub removed: Vec<PathBuf>,
+    /// The conversation as it was before the turn started
+    pub conversation: Conversation,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct FileEntry {
+    hash: String,
+    size: u64,
+    /// Modification time in nanoseconds, used to skip hashing unchanged files
+    modified: u64,
+    executable: bool,
+}
+
+/// The files of one checkpoint, keyed by their path relative to the working directory
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    files: BTreeMap<String, FileEntry>,
+    /// Files that existed at the checkpoint but were not saved, because they were too
+    /// large or could not be read. Rewinding leaves them alone rather than removing them.
+    #[serde(default)]
+    skipped: BTreeSet<String>,
+}
+
+/// Whether the working directory is snapshotted before each agent turn
+pub fn checkpoints_enabled() -> bool {
+    Config::global()
+        .get_param::<bool>(GOOSE_CHECKPOINTS)
+        .unwrap_or(false)
+}
+
+/// Directory holding the checkpoint store of a session
+fn store_dir(session_file: &Path) -> Result<PathBuf> {
+    let session_dir = storage::ensure_session_dir()?;
+    let session_id = storage::session_id_from_path(session_file)?;
+    Ok(session_dir.join("checkpoints").join(session_id))
+}
+
+fn manifest_path(store: &Path, turn: usize) -> PathBuf {
+    store.join("manifests").join(format!("{}.json", turn))
+}
+
+fn object_path(store: &Path, hash: &str) -> PathBuf {
+    store.join("objects").join(hash)
+}
+
+fn prompt_preview(message: &Message) -> String {
+    safe_truncate(message.as_concat_text().trim(), PROMPT_PREVIEW_CHARS)
+}
+
+/// The message that started a turn: a user message with text, not a tool result
+fn is_turn_start(message: &Message) -> bool {
+    message.role == Role::User
+        && !message.is_tool_response()
+        && !message.as_concat_text().trim().is_empty()
+}
+
+/// Files under `root` that checkpoints track, keyed by their relative path, or None
+/// as soon as there are more than `limit`.
+///
+/// Honors .gitignore and .gooseignore-style ignore files, skips hidden files and
+/// directories (including .git) and symlinks, and never descends into `exclude` (the
+/// session store itself). Files over `MAX_FILE_SIZE` are included, for the caller to skip.
+fn tracked_files(
+    root: &Path,
+    exclude: &Path,
+    limit: Option<usize>,
+) -> Option<Vec<(String, PathBuf, fs::Metadata)>> {
+    let exclude = exclude.to_path_buf();
+    let walker = WalkBuilder::new(root)
+        .hidden(true)
+        .require_git(false)
+        .add_custom_ignore_filename(".gooseignore")
+        .filter_entry(move |entry| entry.path() != exclude)
+        .build();
+
+    let mut files = Vec::new();
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let key = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        files.push((key, entry.path().to_path_buf(), metadata));
+        if limit.is_some_and(|limit| files.len() > limit) {
+            return None;
+        }
+    }
+    Some(files)
+}
+
+fn modified_nanos(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path, executable: bool) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = fs::metadata(path)?.permissions();
+    let mode = permissions.mode();
+    let mode = if executable {
+        mode | ((mode & 0o444) >> 2)
+    } else {
+        mode & !0o111
+    };
+    permissions.set_mode(mode);
+    fs::set_permissions(path, permissions)
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path, _executable: bool) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Keep the copies of workspace files readable by the user only
+#[cfg(unix)]
+fn restrict_to_owner(dir: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(dir, fs::Permissions::from_mode(0o700))
+}
+
+#[cfg(not(unix))]
+fn restrict_to_owner(_dir: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Save every tracked file under `root` to the object store and return the manifest,
+/// or None if the directory has too many files to checkpoint.
+///
+/// Files whose size and modification time match `previous` are not read again.
+fn snapshot(
+    root: &Path,
+    store: &Path,
+    exclude: &Path,
+    previous: Option<&Manifest>,
+) -> Result<Option<Manifest>> {
+    let Some(files) = tracked_files(root, exclude, Some(MAX_FILES)) else {
+        return Ok(None);
+    };
+
+    fs::create_dir_all(store.join("objects"))?;
+    restrict_to_owner(store)?;
+    let mut manifest = Manifest::default();
+    for (key, path, metadata) in files {
+        let size = metadata.len();
+        if size > MAX_FILE_SIZE {
+            manifest.skipped.insert(key);
+            continue;
+        }
+        let modified = modified_nanos(&metadata);
+        let executable = is_executable(&metadata);
+
+        let unchanged = previous
+            .and_then(|m| m.files.get(&key))
+            .filter(|e| e.size == size && e.modified == modified)
+            .filter(|e| object_path(store, &e.hash).exists());
+        let hash = match unchanged {
+            Some(entry) => entry.hash.clone(),
+            None => {
+                let Ok(content) = fs::read(&path) else {
+                    manifest.skipped.insert(key);
+                    continue;
+                };
+                let hash = blake3::hash(&content).to_hex().to_string();
+                let object = object_path(store, &hash);
+                if !object.exists() {
+                    let tmp = object.with_extension("tmp");
+                    fs::write(&tmp, &content)?;
+                    fs::rename(&tmp, &object)?;
+                }
+                hash
+            }
+        };
+
+        manifest.files.insert(
+            key,
+            FileEntry {
+                hash,
+                size,
+                modified,
+                executable,
+            },
+        );
+    }
+    Ok(Some(manifest))
+}
+
+/// Make the tracked files under `root` match `manifest`, returning the files written and
+/// the files removed
+fn restore(
+    root: &Path,
+    store: &Path,
+    exclude: &Path,
+    manifest: &Manifest,
+) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    // Read every object up front so a missing one fails the rewind before anything changes
+    let mut contents = BTreeMap::new();
+    for (key, entry) in &manifest.files {
+        let content = fs::read(object_path(store, &entry.hash))
+            .map_err(|e| anyhow!("Checkpoint content for {} is missing: {}", key, e))?;
+        contents.insert(key.as_str(), content);
+    }
+
+    let mut removed = Vec::new();
+    let mut current = HashSet::new();
+    for (key, path, metadata) in tracked_files(root, exclude, None).unwrap_or_default() {
+        // Files too large to save, now or at the checkpoint, are never touched
+        if metadata.len() > MAX_FILE_SIZE || manifest.skipped.contains(&key) {
+            continue;
+        }
+        if manifest.files.contains_key(&key) {
+            current.insert(key);
+        } else {
+            fs::remove_file(&path)?;
+            removed.push(path);
+        }
+    }
+
+    let mut restored = Vec::new();
+    for (key, entry) in &manifest.files {
+        let path = key.split('/').fold(root.to_path_buf(), |p, c| p.join(c));
+        let content = &contents[key.as_str()];
+        if current.contains(key) && fs::read(&path).is_ok_and(|c| &c == content) {
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, content)?;
+        set_executable(&path, entry.executable)?;
+        restored.push(path);
+    }
+    Ok((restored, removed))
+}
+
+fn read_manifest(store: &Path, turn: usize) -> Result<Manifest> {
+    let content = fs::read_to_string(manifest_path(store, turn))?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+fn write_manifest(store: &Path, turn: usize, manifest: &Manifest) -> Result<()> {
+    let path = manifest_path(store, turn);
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(path, serde_json::to_string(manifest)?)?;
+    Ok(())
+}
+
+/// Remove the objects no remaining manifest refers to
+fn collect_garbage(store: &Path, checkpoints: &[Checkpoint]) -> Result<()> {
+    let mut referenced = HashSet::new();
+    for checkpoint in checkpoints {
+        if let Ok(manifest) = read_manifest(store, checkpoint.turn) {
+            referenced.extend(manifest.files.into_values().map(|e| e.hash));
+        }
+    }
+    for entry in fs::read_dir(store.join("objects"))?.flatten() {
+        if !referenced.contains(entry.file_name().to_string_lossy().as_ref()) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+    Ok(())
+}
+
+fn objects_size(store: &Path) -> Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(store.join("objects"))?.flatten() {
+        size += entry.metadata().map(|m| m.len()).unwrap_or_default();
+    }
+    Ok(size)
+}
+
+/// Drop the oldest checkpoints, and the objects only they used, until the store fits
+/// in `max_bytes`
+fn enforce_store_limit(
+    store: &Path,
+    checkpoints: &mut Vec<Checkpoint>,
+    max_bytes: u64,
+) -> Result<()> {
+    while !checkpoints.is_empty() && objects_size(store)? > max_bytes {
+        let dropped = checkpoints.remove(0);
+        tracing::warn!(
+            "Dropping the checkpoint of turn {} to keep the checkpoint store under {} MB",
+            dropped.turn,
+            max_bytes / (1024 * 1024)
+        );
+        let _ = fs::remove_file(manifest_path(store, dropped.turn));
+        collect_garbage(store, checkpoints)?;
+    }
+    Ok(())
+}
+
+/// Turns that have a manifest in the store
+fn stored_turns(store: &Path) -> Vec<usize> {
+    let Ok(entries) = fs::read_dir(store.join("manifests")) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            path.extension().filter(|ext| *ext == "json")?;
+            path.file_stem()?.to_str()?.parse().ok()
+        })
+        .collect()
+}
+
+/// Remove the manifests that the saved `metadata` no longer records, together with the
+/// objects only they used.
+///
+/// Manifests newer than the last recorded checkpoint are left alone, as they may belong to
+/// a checkpoint that is being recorded right now.
+pub fn prune_checkpoints(session_file: &Path, metadata: &SessionMetadata) -> Result<()> {
+    let store = store_dir(session_file)?;
+    if !store.exists() {
+        return Ok(());
+    }
+    let Some(last) = metadata.checkpoints.last().map(|c| c.turn) else {
+        return Ok(());
+    };
+
+    let recorded: HashSet<usize> = metadata.checkpoints.iter().map(|c| c.turn).collect();
+    let mut pruned = false;
+    for turn in stored_turns(&store) {
+        if turn < last && !recorded.contains(&turn) {
+            let _ = fs::remove_file(manifest_path(&store, turn));
+            pruned = true;
+        }
+    }
+    if pruned {
+        collect_garbage(&store, &metadata.checkpoints)?;
+    }
+    Ok(())
+}
+
+/// Snapshot `working_dir` before the agent replies to the last message of `conversation`
+/// and record the checkpoint in the session metadata.
+///
+/// Nothing is recorded if the last message does not start a new turn (for example a
+/// tool result) or if the directory is too large to checkpoint.
+pub async fn create_checkpoint(
+    session_file: &Path,
+    working_dir: &Path,
+    conversation: &Conversation,
+) -> Result<Option<Checkpoint>> {
+    let Some(last) = conversation.last().filter(|m| is_turn_start(m)) else {
+        return Ok(None);
+    };
+    let prompt = prompt_preview(last);
+    let message_count = conversation.len() - 1;
+
+    let _guard = RECORD_LOCK.lock().await;
+    let mut metadata = storage::read_metadata(session_file)?;
+    let turn = metadata.checkpoints.last().map_or(1, |c| c.turn + 1);
+
+    let store = store_dir(session_file)?;
+    let exclude = storage::ensure_session_dir()?;
+    let root = working_dir.to_path_buf();
+    let previous = metadata
+        .checkpoints
+        .last()
+        .filter(|c| c.working_dir == root)
+        .map(|c| c.turn);
+    let manifest = {
+        let store = store.clone();
+        let root = root.clone();
+        tokio::task::spawn_blocking(move || {
+            let previous = previous.and_then(|turn| read_manifest(&store, turn).ok());
+            let manifest = snapshot(&root, &store, &exclude, previous.as_ref())?;
+            if let Some(manifest) = &manifest {
+                write_manifest(&store, turn, manifest)?;
+            }
+            Ok::<_, anyhow::Error>(manifest)
+        })
+        .await??
+    };
+    let Some(manifest) = manifest else {
+        tracing::warn!(
+            "Skipping checkpoint: {} has more than {} files",
+            root.display(),
+            MAX_FILES
+        );
+        return Ok(None);
+    };
+
+    let checkpoint = Checkpoint {
+        turn,
+        message_count,
+        prompt,
+        working_dir: root,
+        file_count: manifest.files.len(),
+        created_at: Utc::now().timestamp(),
+    };
+    metadata.checkpoints.push(checkpoint);
+    metadata.checkpoints = {
+        let mut checkpoints = std::mem::take(&mut metadata.checkpoints);
+        tokio::task::spawn_blocking(move || {
+            enforce_store_limit(&store, &mut checkpoints, MAX_STORE_BYTES)?;
+            Ok::<_, anyhow::Error>(checkpoints)
+        })
+        .await??
+    };
+    storage::update_metadata(session_file, &metadata).await?;
+    Ok(metadata
+        .checkpoints
+        .last()
+        .filter(|c| c.turn == turn)
+        .cloned())
+}
+
+/// Rewind a session to the start of `turn`: restore the working directory to its
+/// checkpoint and drop that turn and everything after it from the conversation.
+///
+/// Fails without changing anything if the conversation no longer lines up with the
+/// checkpoint, for example because it was summarized since.
+pub async fn rewind_to_turn(session_file: &Path, turn: usize) -> Result<RewindOutcome> {
+    let _guard = RECORD_LOCK.lock().await;
+    let mut metadata = storage::read_metadata(session_file)?;
+    let checkpoint = find_checkpoint(&metadata, turn)?.clone();
+
+    let messages = storage::read_full_messages(session_file)?;
+    let turn_start = messages.messages().get(checkpoint.message_count);
+    if !turn_start.is_some_and(|m| is_turn_start(m) && prompt_preview(m) == checkpoint.prompt) {
+        return Err(anyhow!(
+            "The conversation has changed since turn {} (for example it was summarized), so it can no longer be rewound",
+            turn
+        ));
+    }
+
+    let store = store_dir(session_file)?;
+    let exclude = storage::ensure_session_dir()?;
+    let (restored, removed) = {
+        let store = store.clone();
+        let root = checkpoint.working_dir.clone();
+        tokio::task::spawn_blocking(move || {
+            let manifest = read_manifest(&store, turn)?;
+            restore(&root, &store, &exclude, &manifest)
+        })
+        .await??
+    };
+
+    let conversation = Conversation::new_unvalidated(
+        messages.messages()[..checkpoint.message_count]
+            .iter()
+            .cloned(),
+    );
+    for dropped in metadata.checkpoints.iter().filter(|c| c.turn >= turn) {
+        let _ = fs::remove_file(manifest_path(&store, dropped.turn));
+    }
+    metadata.checkpoints.retain(|c| c.turn < turn);
+    metadata.message_count = conversation.len();
+    storage::save_messages_with_metadata(session_file, &metadata, &conversation)?;
+    if let Err(e) = collect_garbage(&store, &metadata.checkpoints) {
+        tracing::warn!("Failed to clean up checkpoint store: {}", e);
+    }
+
+    Ok(RewindOutcome {
+        checkpoint,
+        restored,
+        removed,
+        conversation,
+    })
+}
+
+fn find_checkpoint(metadata: &SessionMetadata, turn: usize) -> Result<&Checkpoint> {
+    metadata
+        .checkpoints
+        .iter()
+        .find(|c| c.turn == turn)
+        .ok_or_else(|| match metadata.checkpoints.as_slice() {
+            [] => anyhow!("This session has no checkpoints"),
+            [first, .., last] => anyhow!(
+                "No checkpoint for turn {}, available turns are {} to {}",
+                turn,
+                first.turn,
+                last.turn
+            ),
+            [only] => anyhow!(
+                "No checkpoint for turn {}, the only turn is {}",
+                turn,
+                only.turn
+            ),
+        })
+}
+
+/// Remove the checkpoint store of a session
+pub fn delete_checkpoints(session_file: &Path) -> Result<()> {
+    let store = store_dir(session_file)?;
+    if store.exists() {
+        fs::remove_dir_all(store)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let workspace = tempdir().unwrap();
+        let store = tempdir().unwrap();
+        let root = workspace.path();
+        write(root, ".gitignore", "build/\n");
+        write(root, "src/main.rs", "fn main() {}\n");
+        write(root, "notes.txt", "keep\n");
+        write(root, "build/out.bin", "ignored\n");
+        write(root, ".git/HEAD", "ref: refs/heads/main\n");
+        write(root, ".env", "API_KEY=secret\n");
+        write(root, ".aws/credentials", "[default]\n");
+
+        let manifest = snapshot(root, store.path(), store.path(), None)
+            .unwrap()
+            .unwrap();
+        let keys: Vec<&String> = manifest.files.keys().collect();
+        assert_eq!(keys, vec!["notes.txt", "src/main.rs"]);
+
+        write(root, "src/main.rs", "fn main() { broken }\n");
+        write(root, "src/new.rs", "// created later\n");
+        fs::remove_file(root.join("notes.txt")).unwrap();
+        write(root, "build/out.bin", "rebuilt\n");
+
+        let (mut restored, removed) = restore(root, store.path(), store.path(), &manifest).unwrap();
+        restored.sort();
+        assert_eq!(
+            restored,
+            vec![root.join("notes.txt"), root.join("src/main.rs")]
+        );
+        assert_eq!(removed, vec![root.join("src/new.rs")]);
+        assert_eq!(
+            fs::read_to_string(root.join("src/main.rs")).unwrap(),
+            "fn main() {}\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("notes.txt")).unwrap(),
+            "keep\n"
+        );
+        // Ignored and hidden files are left alone
+        assert_eq!(
+            fs::read_to_string(root.join("build/out.bin")).unwrap(),
+            "rebuilt\n"
+        );
+        assert!(root.join(".env").exists());
+    }
+
+    #[test]
+    fn test_restore_keeps_files_skipped_at_snapshot() {
+        let workspace = tempdir().unwrap();
+        let store = tempdir().unwrap();
+        let root = workspace.path();
+        write(root, "src/main.rs", "fn main() {}\n");
+        let big = vec![b'x'; MAX_FILE_SIZE as usize + 1];
+        fs::write(root.join("data.bin"), &big).unwrap();
+
+        let manifest = snapshot(root, store.path(), store.path(), None)
+            .unwrap()
+            .unwrap();
+        assert!(!manifest.files.contains_key("data.bin"));
+        assert!(manifest.skipped.contains("data.bin"));
+
+        // It is small enough to be tracked now, but existed at the checkpoint
+        write(root, "data.bin", "trimmed\n");
+        let (_, removed) = restore(root, store.path(), store.path(), &manifest).unwrap();
+        assert!(removed.is_empty());
+        assert_eq!(
+            fs::read_to_string(root.join("data.bin")).unwrap(),
+            "trimmed\n"
+        );
+    }
+
+    fn checkpoint(turn: usize) -> Checkpoint {
+        Checkpoint {
+            turn,
+            message_count: 0,
+            prompt: String::new(),
+            working_dir: PathBuf::new(),
+            file_count: 1,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_store_limit_drops_oldest_checkpoints() {
+        let workspace = tempdir().unwrap();
+        let store = tempdir().unwrap();
+        let root = workspace.path();
+
+        let mut checkpoints = Vec::new();
+        for turn in 1..=3 {
+            write(root, "data.txt", &format!("{}\n", "x".repeat(100 * turn)));
+            let manifest = snapshot(root, store.path(), store.path(), None)
+                .unwrap()
+                .unwrap();
+            write_manifest(store.path(), turn, &manifest).unwrap();
+            checkpoints.push(checkpoint(turn));
+        }
+        assert_eq!(objects_size(store.path()).unwrap(), 101 + 201 + 301);
+
+        enforce_store_limit(store.path(), &mut checkpoints, 600).unwrap();
+        let turns: Vec<usize> = checkpoints.iter().map(|c| c.turn).collect();
+        assert_eq!(turns, vec![2, 3]);
+        assert_eq!(objects_size(store.path()).unwrap(), 201 + 301);
+        assert_eq!(stored_turns(store.path()).len(), 2);
+
+        enforce_store_limit(store.path(), &mut checkpoints, 0).unwrap();
+        assert!(checkpoints.is_empty());
+        assert_eq!(objects_size(store.path()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_snapshot_stores_identical_content_once() {
+        let workspace = tempdir().unwrap();
+        let store = tempdir().unwrap();
+        let root = workspace.path();
+        write(root, "a.txt", "same\n");
+        write(root, "b.txt", "same\n");
+
+        let first = snapshot(root, store.path(), store.path(), None)
+            .unwrap()
+            .unwrap();
+        let second = snapshot(root, store.path(), store.path(), Some(&first))
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.files, second.files);
+        assert_eq!(
+            fs::read_dir(store.path().join("objects")).unwrap().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_is_turn_start() {
+        assert!(is_turn_start(&Message::user().with_text("fix the tests")));
+        assert!(!is_turn_start(&Message::user().with_text("  ")));
+        assert!(!is_turn_start(&Message::assistant().with_text("done")));
+        assert!(!is_turn_start(&Message::user().with_tool_response(
+            "1",
+            Ok(vec![rmcp::model::Content::text("output")])
+        )));
+    }
+}
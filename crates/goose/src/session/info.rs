@@ -1,7 +1,6 @@
 use crate::session::{self, SessionMetadata};
 use anyhow::Result;
 use serde::Serialize;
-use std::cmp::Ordering;
 use utoipa::ToSchema;
 
 #[derive(Clone, Serialize, ToSchema)]
@@ -19,80 +18,45 @@ pub enum SortOrder {
 }
 
 pub fn get_valid_sorted_sessions(sort_order: SortOrder) -> Result<Vec<SessionInfo>> {
-    let sessions = match session::list_sessions() {
-        Ok(sessions) => sessions,
-        Err(e) => {
+    let (store, session_dir) = match (session::session_store(), session::ensure_session_dir()) {
+        (Ok(store), Ok(dir)) => (store, dir),
+        (Err(e), _) | (_, Err(e)) => {
             tracing::error!("Failed to list sessions: {:?}", e);
             return Err(anyhow::anyhow!("Failed to list sessions"));
         }
     };
 
-    let mut session_infos: Vec<SessionInfo> = Vec::new();
-    let mut corrupted_count = 0;
-
-    for (id, path) in sessions {
-        // Get file modification time with fallback
-        let modified = path
-            .metadata()
-            .and_then(|m| m.modified())
-            .map(|time| {
-                chrono::DateTime::<chrono::Utc>::from(time)
-                    .format("%Y-%m-%d %H:%M:%S UTC")
-                    .to_string()
-            })
-            .unwrap_or_else(|_| {
-                tracing::warn!("Failed to get modification time for session: {}", id);
-                "Unknown".to_string()
-            });
-
-        // Try to read metadata with error handling
-        match session::read_metadata(&path) {
-            Ok(metadata) => {
-                session_infos.push(SessionInfo {
-                    id,
-                    path: path.to_string_lossy().to_string(),
-                    modified,
-                    metadata,
-                });
-            }
-            Err(e) => {
-                corrupted_count += 1;
-                tracing::warn!(
-                    "Failed to read metadata for session '{}': {}. Skipping corrupted session.",
-                    id,
-                    e
-                );
-
-                // Optionally, we could create a placeholder entry for corrupted sessions
-                // to show them in the UI with an error indicator, but for now we skip them
-                continue;
-            }
+    // Unreadable sessions are skipped (and logged) by the store
+    let sessions = match store.list() {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            tracing::error!("Failed to list sessions: {:?}", e);
+            return Err(anyhow::anyhow!("Failed to list sessions"));
         }
-    }
+    };
 
-    if corrupted_count > 0 {
-        tracing::warn!(
-            "Skipped {} corrupted sessions during listing",
-            corrupted_count
-        );
-    }
+    let mut session_infos: Vec<SessionInfo> = sessions
+        .into_iter()
+        .map(|session| SessionInfo {
+            path: session_dir
+                .join(format!("{}.jsonl", session.id))
+                .to_string_lossy()
+                .to_string(),
+            modified: session
+                .updated_at
+                .format("%Y-%m-%d %H:%M:%S UTC")
+                .to_string(),
+            id: session.id,
+            metadata: session.metadata,
+        })
+        .collect();
 
     // Sort sessions by modified date
     // Since all dates are in ISO format (YYYY-MM-DD HH:MM:SS UTC), we can just use string comparison
     // This works because the ISO format ensures lexicographical ordering matches chronological ordering
-    session_infos.sort_by(|a, b| {
-        if a.modified == "Unknown" && b.modified == "Unknown" {
-            return Ordering::Equal;
-        } else if a.modified == "Unknown" {
-            return Ordering::Greater; // Unknown dates go last
-        } else if b.modified == "Unknown" {
-            return Ordering::Less;
-        }
-
-        match sort_order {
-            SortOrder::Ascending => a.modified.cmp(&b.modified),
-            SortOrder::Descending => b.modified.cmp(&a.modified),
-        }
+    session_infos.sort_by(|a, b| match sort_order {
+        SortOrder::Ascending => a.modified.cmp(&b.modified),
+        SortOrder::Descending => b.modified.cmp(&a.modified),
     });
 
     Ok(session_infos)
@@ -1,14 +1,26 @@
+pub mod checkpoint;
 pub mod extension_data;
 pub mod info;
+pub mod search;
+pub mod sqlite_store;
 pub mod storage;
+pub mod store;
+pub mod usage;
 
 // Re-export common session types and functions
 pub use storage::{
-    ensure_session_dir, generate_description, generate_description_with_schedule_id,
-    generate_session_id, get_most_recent_session, get_path, list_sessions, persist_messages,
-    persist_messages_with_schedule_id, read_messages, read_metadata, update_metadata, Identifier,
-    SessionMetadata,
+    delete_session, drop_message, ensure_session_dir, fork_session, generate_description,
+    generate_description_with_schedule_id, generate_session_id, get_most_recent_session, get_path,
+    list_sessions, persist_messages, persist_messages_with_schedule_id, pin_message, read_messages,
+    read_metadata, session_exists, update_metadata, Identifier, SessionMetadata,
+};
+pub use store::{
+    migrate_jsonl_sessions, session_store, MigrationReport, SessionQuery, SessionStore,
+    SessionStoreKind, StoredSession,
 };
 
+pub use checkpoint::{create_checkpoint, rewind_to_turn, Checkpoint, RewindOutcome};
 pub use extension_data::{ExtensionData, ExtensionState, TodoState};
 pub use info::{get_valid_sorted_sessions, SessionInfo};
+pub use search::{search_sessions, SearchMatchKind, SessionSearchHit};
+pub use usage::{record_usage, session_cost_usd, ModelUsage, UsageRole};
@@ -0,0 +1,498 @@
+use crate::conversation::message::{Message, MessageContent};
+use crate::session::store::{session_store, SessionStore};
+use anyhow::Result;
+use rmcp::model::{RawContent, ResourceContents, Role};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Characters of context kept on each side of the first match in a snippet
+const SNIPPET_CONTEXT_CHARS: usize = 80;
+
+/// Score bonus when the whole query appears verbatim in a field
+const PHRASE_BONUS: f64 = 2.0;
+
+/// Which part of a message a search hit matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchMatchKind {
+    Text,
+    ToolRequest,
+    ToolResponse,
+}
+
+impl SearchMatchKind {
+    fn weight(self) -> f64 {
+        match self {
+            SearchMatchKind::Text => 1.0,
+            SearchMatchKind::ToolRequest => 0.8,
+            SearchMatchKind::ToolResponse => 0.6,
+        }
+    }
+}
+
+/// A single message matching a session search
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSearchHit {
+    pub session_id: String,
+    /// Description of the session the hit belongs to
+    pub description: String,
+    /// Index of the matching message within the session conversation
+    pub message_index: usize,
+    #[schema(value_type = String, example = "user")]
+    pub role: Role,
+    pub matched_in: SearchMatchKind,
+    /// Relevance score, higher is better
+    pub score: f64,
+    /// Excerpt of the matching text around the first match
+    pub snippet: String,
+}
+
+struct SearchField {
+    kind: SearchMatchKind,
+    text: String,
+    lowercase: String,
+}
+
+/// The lowercased searchable text of a message grouped by where it came from, as kept
+/// in a full-text index
+pub(crate) struct MessageSearchText {
+    pub text: String,
+    pub tool_request: String,
+    pub tool_response: String,
+}
+
+impl MessageSearchText {
+    pub(crate) fn new(message: &Message) -> Self {
+        let mut search_text = Self {
+            text: String::new(),
+            tool_request: String::new(),
+            tool_response: String::new(),
+        };
+        for field in searchable_fields(message) {
+            let target = match field.kind {
+                SearchMatchKind::Text => &mut search_text.text,
+                SearchMatchKind::ToolRequest => &mut search_text.tool_request,
+                SearchMatchKind::ToolResponse => &mut search_text.tool_response,
+            };
+            if !target.is_empty() {
+                target.push('\n');
+            }
+            target.push_str(&field.lowercase);
+        }
+        search_text
+    }
+}
+
+/// A message containing every query term
+pub struct MatchedMessage {
+    pub session_id: String,
+    pub description: String,
+    pub message_index: usize,
+    pub message: Message,
+}
+
+/// Messages containing every query term, with the statistics used to rank them
+pub struct SearchMatches {
+    /// Number of messages that were searched
+    pub total_messages: usize,
+    /// Number of messages containing each term, in query term order
+    pub document_frequency: Vec<usize>,
+    pub messages: Vec<MatchedMessage>,
+}
+
+/// Split a query into its distinct lowercase terms
+fn query_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for term in query.split_whitespace().map(|t| t.to_lowercase()) {
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+fn searchable_fields(message: &Message) -> Vec<SearchField> {
+    let mut fields = Vec::new();
+    let mut push = |kind, text: String| {
+        if !text.trim().is_empty() {
+            let lowercase = text.to_lowercase();
+            fields.push(SearchField {
+                kind,
+                text,
+                lowercase,
+            });
+        }
+    };
+
+    for content in &message.content {
+        match content {
+            MessageContent::Text(text) => push(SearchMatchKind::Text, text.text.clone()),
+            MessageContent::ToolRequest(request) => {
+                if let Ok(call) = &request.tool_call {
+                    push(
+                        SearchMatchKind::ToolRequest,
+                        format!("{} {}", call.name, call.arguments),
+                    );
+                }
+            }
+            MessageContent::ToolResponse(response) => {
+                if let Ok(contents) = &response.tool_result {
+                    for item in contents {
+                        match &item.raw {
+                            RawContent::Text(text) => {
+                                push(SearchMatchKind::ToolResponse, text.text.clone())
+                            }
+                            RawContent::Resource(resource) => {
+                                if let ResourceContents::TextResourceContents { text, .. } =
+                                    &resource.resource
+                                {
+                                    push(SearchMatchKind::ToolResponse, text.clone())
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    fields
+}
+
+/// The index of the character in `text` at byte `offset` of its lowercase form.
+/// Lowercasing can change a character's length (e.g. 'K' U+212A or 'İ'), so offsets are
+/// mapped back one character at a time.
+fn char_index_at_lowercase_offset(text: &str, offset: usize) -> usize {
+    let mut lowercase_len = 0;
+    for (index, c) in text.chars().enumerate() {
+        if lowercase_len >= offset {
+            return index;
+        }
+        lowercase_len += c.to_lowercase().map(char::len_utf8).sum::<usize>();
+    }
+    text.chars().count()
+}
+
+/// Build an excerpt of `field` around the first occurrence of the phrase or any term
+fn snippet(field: &SearchField, phrase: &str, terms: &[String]) -> String {
+    let byte_offset = field
+        .lowercase
+        .find(phrase)
+        .or_else(|| terms.iter().filter_map(|t| field.lowercase.find(t)).min())
+        .unwrap_or(0);
+
+    let match_char = char_index_at_lowercase_offset(&field.text, byte_offset);
+    let start = match_char.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let total_chars = field.text.chars().count();
+    let end = (match_char + phrase.chars().count() + SNIPPET_CONTEXT_CHARS).min(total_chars);
+
+    let excerpt: String = field
+        .text
+        .chars()
+        .skip(start)
+        .take(end - start)
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .collect();
+
+    format!(
+        "{}{}{}",
+        if start > 0 { "…" } else { "" },
+        excerpt.trim(),
+        if end < total_chars { "…" } else { "" }
+    )
+}
+
+/// Search the messages of every session in `store`.
+///
+/// A message matches when all whitespace-separated query terms occur (case-insensitively)
+/// in its text, tool request names/arguments or tool outputs. Hits are ranked with a
+/// tf-idf style score that favours verbatim phrase matches and plain text over tool output.
+/// Stores with a full-text index are queried through it; others are scanned message by message.
+pub fn search_store(
+    store: &dyn SessionStore,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<SessionSearchHit>> {
+    let terms = query_terms(query);
+    if terms.is_empty() || limit == 0 {
+        return Ok(Vec::new());
+    }
+    let matches = match store.search_index(&terms)? {
+        Some(matches) => matches,
+        None => scan_store(store, &terms)?,
+    };
+
+    let mut hits = rank(matches, &terms);
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+/// Find the matching messages by reading every message of every session, for stores
+/// without a full-text index
+fn scan_store(store: &dyn SessionStore, terms: &[String]) -> Result<SearchMatches> {
+    let mut matches = SearchMatches {
+        total_messages: 0,
+        document_frequency: vec![0; terms.len()],
+        messages: Vec::new(),
+    };
+
+    for session in store.list()? {
+        let messages = match store.read_messages(&session.id) {
+            Ok(messages) => messages,
+            Err(e) => {
+                tracing::warn!("Skipping session '{}' during search: {}", session.id, e);
+                continue;
+            }
+        };
+
+        for (message_index, message) in messages.iter().enumerate() {
+            matches.total_messages += 1;
+            let counts = term_counts(&searchable_fields(message), terms);
+            for (df, count) in matches.document_frequency.iter_mut().zip(&counts) {
+                if *count > 0 {
+                    *df += 1;
+                }
+            }
+
+            if counts.iter().all(|count| *count > 0) {
+                matches.messages.push(MatchedMessage {
+                    session_id: session.id.clone(),
+                    description: session.metadata.description.clone(),
+                    message_index,
+                    message: message.clone(),
+                });
+            }
+        }
+    }
+    Ok(matches)
+}
+
+fn term_counts(fields: &[SearchField], terms: &[String]) -> Vec<usize> {
+    terms
+        .iter()
+        .map(|term| {
+            fields
+                .iter()
+                .map(|f| f.lowercase.matches(term.as_str()).count())
+                .sum()
+        })
+        .collect()
+}
+
+fn rank(matches: SearchMatches, terms: &[String]) -> Vec<SessionSearchHit> {
+    let phrase = terms.join(" ");
+    let total_messages = matches.total_messages;
+    let document_frequency = matches.document_frequency;
+
+    matches
+        .messages
+        .into_iter()
+        .filter_map(|matched| {
+            let fields = searchable_fields(&matched.message);
+            let counts = term_counts(&fields, terms);
+            // An index may match more loosely than the substring check used here
+            if !counts.iter().all(|count| *count > 0) {
+                return None;
+            }
+
+            let term_score: f64 = counts
+                .iter()
+                .zip(&document_frequency)
+                .map(|(count, df)| {
+                    let idf = (1.0 + total_messages as f64 / (*df).max(1) as f64).ln();
+                    (1.0 + (*count as f64).ln()) * idf
+                })
+                .sum();
+
+            // The best field decides where the hit is reported and how it is weighted
+            let best = fields.iter().max_by(|a, b| {
+                field_rank(a, &phrase, terms).total_cmp(&field_rank(b, &phrase, terms))
+            })?;
+
+            let phrase_bonus = if terms.len() > 1 && best.lowercase.contains(&phrase) {
+                PHRASE_BONUS
+            } else {
+                0.0
+            };
+
+            Some(SessionSearchHit {
+                session_id: matched.session_id,
+                description: matched.description,
+                message_index: matched.message_index,
+                role: matched.message.role.clone(),
+                matched_in: best.kind,
+                score: (term_score + phrase_bonus) * best.kind.weight(),
+                snippet: snippet(best, &phrase, terms),
+            })
+        })
+        .collect()
+}
+
+fn field_rank(field: &SearchField, phrase: &str, terms: &[String]) -> f64 {
+    let matched = terms
+        .iter()
+        .filter(|t| field.lowercase.contains(t.as_str()))
+        .count() as f64;
+    let phrase = if field.lowercase.contains(phrase) {
+        PHRASE_BONUS
+    } else {
+        0.0
+    };
+    (matched + phrase) * field.kind.weight()
+}
+
+/// Search all sessions in the configured session store
+pub fn search_sessions(query: &str, limit: usize) -> Result<Vec<SessionSearchHit>> {
+    search_store(session_store()?.as_ref(), query, limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::Conversation;
+    use crate::session::sqlite_store::SqliteSessionStore;
+    use crate::session::storage::SessionMetadata;
+    use crate::session::store::JsonlSessionStore;
+    use mcp_core::ToolCall;
+    use rmcp::model::Content;
+    use serde_json::json;
+
+    fn seed(store: &dyn SessionStore, id: &str, description: &str, messages: Vec<Message>) {
+        let metadata = SessionMetadata {
+            description: description.to_string(),
+            ..Default::default()
+        };
+        store
+            .save(id, &metadata, &Conversation::new_unvalidated(messages))
+            .unwrap();
+    }
+
+    fn seed_fixtures(store: &dyn SessionStore) {
+        seed(
+            store,
+            "kafka",
+            "Fix flaky test",
+            vec![
+                Message::user().with_text("Please fix the flaky Kafka test in the consumer module"),
+                Message::assistant().with_tool_request(
+                    "1",
+                    Ok(ToolCall::new(
+                        "developer__shell",
+                        json!({"command": "cargo test kafka_consumer"}),
+                    )),
+                ),
+                Message::user().with_tool_response(
+                    "1",
+                    Ok(vec![Content::text("test kafka_consumer ... FAILED")]),
+                ),
+            ],
+        );
+        seed(
+            store,
+            "docs",
+            "Write docs",
+            vec![Message::user().with_text("Document the flaky behaviour of the cache")],
+        );
+    }
+
+    fn assert_fixture_hits(store: &dyn SessionStore) -> Result<()> {
+        let hits = search_store(store, "flaky kafka", 10)?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "kafka");
+        assert_eq!(hits[0].message_index, 0);
+        assert_eq!(hits[0].matched_in, SearchMatchKind::Text);
+        assert!(hits[0].snippet.contains("flaky Kafka test"));
+
+        let hits = search_store(store, "kafka_consumer", 10)?;
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].matched_in, SearchMatchKind::ToolRequest);
+        assert_eq!(hits[1].matched_in, SearchMatchKind::ToolResponse);
+
+        let hits = search_store(store, "flaky", 1)?;
+        assert_eq!(hits.len(), 1);
+
+        assert!(search_store(store, "   ", 10)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_ranks_and_snippets() -> Result<()> {
+        let store = SqliteSessionStore::in_memory()?;
+        seed_fixtures(&store);
+        assert!(store.search_index(&["kafka".to_string()])?.is_some());
+        assert_fixture_hits(&store)
+    }
+
+    #[test]
+    fn test_search_scans_jsonl_store() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store = JsonlSessionStore::new(dir.path().to_path_buf());
+        seed_fixtures(&store);
+        assert!(store.search_index(&["kafka".to_string()])?.is_none());
+        assert_fixture_hits(&store)
+    }
+
+    #[test]
+    fn test_search_index_follows_rewrites_and_deletes() -> Result<()> {
+        let store = SqliteSessionStore::in_memory()?;
+        seed(
+            &store,
+            "s1",
+            "",
+            vec![
+                Message::user().with_text("Migrate the Postgres schema"),
+                Message::assistant().with_text("Done, see db/migrations"),
+            ],
+        );
+        assert_eq!(search_store(&store, "postgres", 10)?.len(), 1);
+        // Terms shorter than a trigram are matched without the index
+        assert_eq!(search_store(&store, "db", 10)?.len(), 1);
+
+        seed(
+            &store,
+            "s1",
+            "",
+            vec![Message::user().with_text("Summary: migrated the MySQL schema")],
+        );
+        assert!(search_store(&store, "postgres", 10)?.is_empty());
+        assert!(search_store(&store, "db", 10)?.is_empty());
+        assert_eq!(search_store(&store, "mysql schema", 10)?.len(), 1);
+
+        store.delete("s1")?;
+        assert!(search_store(&store, "mysql", 10)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_snippet_is_trimmed_around_match() {
+        let text = format!("{} needle {}", "a".repeat(200), "b".repeat(200));
+        let field = SearchField {
+            kind: SearchMatchKind::Text,
+            lowercase: text.to_lowercase(),
+            text,
+        };
+        let snippet = snippet(&field, "needle", &["needle".to_string()]);
+        assert!(snippet.starts_with('…'));
+        assert!(snippet.ends_with('…'));
+        assert!(snippet.contains("needle"));
+        assert!(snippet.chars().count() < 200);
+    }
+
+    #[test]
+    fn test_snippet_handles_lowercase_length_changes() {
+        for prefix in ["\u{212A}", "İ", "İİ Kelvin \u{212A}"] {
+            let text = format!("{} {} needle {}", prefix, "a".repeat(100), "b".repeat(100));
+            let field = SearchField {
+                kind: SearchMatchKind::Text,
+                lowercase: text.to_lowercase(),
+                text,
+            };
+            let snippet = snippet(&field, "needle", &["needle".to_string()]);
+            assert!(snippet.starts_with('…'), "{}", snippet);
+            assert!(snippet.contains("needle"), "{}", snippet);
+        }
+    }
+}
@@ -0,0 +1,630 @@
+use crate::conversation::message::Message;
+use crate::conversation::Conversation;
+use crate::session::search::{MatchedMessage, MessageSearchText, SearchMatches};
+use crate::session::storage::SessionMetadata;
+use crate::session::store::{SessionQuery, SessionStore, StoredSession};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT PRIMARY KEY,
+    working_dir TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    schedule_id TEXT,
+    message_count INTEGER NOT NULL DEFAULT 0,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    metadata TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_sessions_working_dir ON sessions(working_dir);
+CREATE INDEX IF NOT EXISTS idx_sessions_schedule_id ON sessions(schedule_id);
+CREATE INDEX IF NOT EXISTS idx_sessions_updated_at ON sessions(updated_at);
+
+CREATE TABLE IF NOT EXISTS messages (
+    session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    idx INTEGER NOT NULL,
+    role TEXT NOT NULL,
+    created INTEGER NOT NULL,
+    message TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    PRIMARY KEY (session_id, idx)
+);
+
+CREATE TABLE IF NOT EXISTS token_usage (
+    session_id TEXT PRIMARY KEY REFERENCES sessions(id) ON DELETE CASCADE,
+    total_tokens INTEGER,
+    input_tokens INTEGER,
+    output_tokens INTEGER,
+    accumulated_total_tokens INTEGER,
+    accumulated_input_tokens INTEGER,
+    accumulated_output_tokens INTEGER
+);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+    text,
+    tool_request,
+    tool_response,
+    tokenize = 'trigram'
+);
+CREATE TRIGGER IF NOT EXISTS messages_fts_delete AFTER DELETE ON messages BEGIN
+    DELETE FROM messages_fts WHERE rowid = old.rowid;
+END;
+"#;
+
+/// Schema version stored in `PRAGMA user_version`. Version 1 added the full-text index.
+const SCHEMA_VERSION: i64 = 1;
+
+/// Session store backed by a single SQLite database.
+///
+/// Messages are appended incrementally: a save only inserts the messages that were
+/// added since the previous save. Each row keeps a hash of its content, so messages
+/// that changed in place (e.g. during compaction) are found and rewritten from the
+/// first one that differs. The searchable text of every message is kept in an FTS5
+/// index that shares the message's rowid.
+pub struct SqliteSessionStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteSessionStore {
+    /// Open (and create if needed) the database at `path`
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open session database {:?}", path))?;
+        Self::init(conn)
+    }
+
+    /// Open a throwaway in-memory database
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        conn.execute_batch(SCHEMA)?;
+        Self::migrate(&mut conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn migrate(conn: &mut Connection) -> Result<()> {
+        let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version >= SCHEMA_VERSION {
+            return Ok(());
+        }
+
+        // Databases created before the full-text index need it filled from their messages
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM messages_fts", [])?;
+        {
+            let mut select = tx.prepare("SELECT rowid, message FROM messages")?;
+            let rows = select.query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?;
+            for row in rows {
+                let (rowid, json) = row?;
+                match serde_json::from_str::<Message>(&json) {
+                    Ok(message) => Self::index_message(&tx, rowid, &message)?,
+                    Err(e) => tracing::warn!("Not indexing unreadable message: {}", e),
+                }
+            }
+        }
+        tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn with_conn<T>(&self, f: impl FnOnce(&mut Connection) -> Result<T>) -> Result<T> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Session database lock poisoned"))?;
+        f(&mut conn)
+    }
+
+    fn upsert_session(tx: &Transaction, id: &str, metadata: &SessionMetadata) -> Result<()> {
+        let now = Utc::now().timestamp_millis();
+        tx.execute(
+            "INSERT INTO sessions
+                (id, working_dir, description, schedule_id, message_count, created_at, updated_at, metadata)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6, ?7)
+             ON CONFLICT(id) DO UPDATE SET
+                working_dir = excluded.working_dir,
+                description = excluded.description,
+                schedule_id = excluded.schedule_id,
+                message_count = excluded.message_count,
+                updated_at = excluded.updated_at,
+                metadata = excluded.metadata",
+            params![
+                id,
+                metadata.working_dir.to_string_lossy(),
+                metadata.description,
+                metadata.schedule_id,
+                metadata.message_count as i64,
+                now,
+                serde_json::to_string(metadata)?,
+            ],
+        )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO token_usage
+                (session_id, total_tokens, input_tokens, output_tokens,
+                 accumulated_total_tokens, accumulated_input_tokens, accumulated_output_tokens)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                id,
+                metadata.total_tokens,
+                metadata.input_tokens,
+                metadata.output_tokens,
+                metadata.accumulated_total_tokens,
+                metadata.accumulated_input_tokens,
+                metadata.accumulated_output_tokens,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn insert_messages(
+        tx: &Transaction,
+        id: &str,
+        start: usize,
+        rows: &[MessageRow],
+    ) -> Result<()> {
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO messages (session_id, idx, role, created, message, hash)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for (offset, row) in rows.iter().enumerate() {
+            let role = serde_json::to_value(&row.message.role)?;
+            stmt.execute(params![
+                id,
+                (start + offset) as i64,
+                role.as_str().unwrap_or_default(),
+                row.message.created,
+                row.json,
+                row.hash,
+            ])?;
+            Self::index_message(tx, tx.last_insert_rowid(), row.message)?;
+        }
+        Ok(())
+    }
+
+    fn index_message(tx: &Transaction, rowid: i64, message: &Message) -> Result<()> {
+        let search_text = MessageSearchText::new(message);
+        tx.prepare_cached(
+            "INSERT INTO messages_fts (rowid, text, tool_request, tool_response)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?
+        .execute(params![
+            rowid,
+            search_text.text,
+            search_text.tool_request,
+            search_text.tool_response,
+        ])?;
+        Ok(())
+    }
+
+    /// SQL condition on `messages_fts` that holds when every term occurs, with its
+    /// parameters. The trigram tokenizer only indexes terms of three or more characters,
+    /// shorter ones are matched with LIKE.
+    fn fts_condition(terms: &[String]) -> (String, Vec<String>) {
+        let (indexed, short): (Vec<&String>, Vec<&String>) =
+            terms.iter().partition(|term| term.chars().count() >= 3);
+
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+        if !indexed.is_empty() {
+            let expression = indexed
+                .iter()
+                .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+                .collect::<Vec<_>>()
+                .join(" AND ");
+            values.push(expression);
+            conditions.push(format!("messages_fts MATCH ?{}", values.len()));
+        }
+        for term in short {
+            let pattern = term
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            values.push(format!("%{}%", pattern));
+            let n = values.len();
+            conditions.push(format!(
+                "(text LIKE ?{n} ESCAPE '\\' OR tool_request LIKE ?{n} ESCAPE '\\' \
+                 OR tool_response LIKE ?{n} ESCAPE '\\')"
+            ));
+        }
+        (conditions.join(" AND "), values)
+    }
+
+    /// Number of stored messages that can be kept as-is, compared by content hash up to
+    /// the first message that changed
+    fn reusable_prefix(tx: &Transaction, id: &str, rows: &[MessageRow]) -> Result<usize> {
+        let mut stmt =
+            tx.prepare_cached("SELECT hash FROM messages WHERE session_id = ?1 ORDER BY idx")?;
+        let stored = stmt.query_map(params![id], |row| row.get::<_, String>(0))?;
+
+        let mut keep = 0;
+        for (stored, row) in stored.zip(rows) {
+            if stored? != row.hash {
+                break;
+            }
+            keep += 1;
+        }
+        Ok(keep)
+    }
+
+    fn row_to_session(id: String, updated_at: i64, metadata: String) -> Result<StoredSession> {
+        Ok(StoredSession {
+            id,
+            metadata: serde_json::from_str(&metadata)?,
+            updated_at: DateTime::<Utc>::from_timestamp_millis(updated_at).unwrap_or_default(),
+        })
+    }
+}
+
+/// A message serialized for storage, with the hash used to tell whether it changed
+struct MessageRow<'a> {
+    message: &'a Message,
+    json: String,
+    hash: String,
+}
+
+impl<'a> MessageRow<'a> {
+    fn new(message: &'a Message) -> Result<Self> {
+        let json = serde_json::to_string(message)?;
+        let hash = format!("{:x}", Sha256::digest(json.as_bytes()));
+        Ok(Self {
+            message,
+            json,
+            hash,
+        })
+    }
+}
+
+impl SessionStore for SqliteSessionStore {
+    fn exists(&self, id: &str) -> Result<bool> {
+        self.with_conn(|conn| {
+            let found = conn
+                .query_row("SELECT 1 FROM sessions WHERE id = ?1", params![id], |_| {
+                    Ok(())
+                })
+                .optional()?;
+            Ok(found.is_some())
+        })
+    }
+
+    fn read_metadata(&self, id: &str) -> Result<SessionMetadata> {
+        self.with_conn(|conn| {
+            let metadata: Option<String> = conn
+                .query_row(
+                    "SELECT metadata FROM sessions WHERE id = ?1",
+                    params![id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            match metadata {
+                Some(json) => Ok(serde_json::from_str(&json)?),
+                None => Ok(SessionMetadata::default()),
+            }
+        })
+    }
+
+    fn read_messages(&self, id: &str) -> Result<Conversation> {
+        self.with_conn(|conn| {
+            let mut stmt =
+                conn.prepare("SELECT message FROM messages WHERE session_id = ?1 ORDER BY idx")?;
+            let rows = stmt.query_map(params![id], |row| row.get::<_, String>(0))?;
+
+            let mut messages = Vec::new();
+            for row in rows {
+                let json = row?;
+                match serde_json::from_str::<Message>(&json) {
+                    Ok(message) => messages.push(message),
+                    Err(e) => tracing::warn!("Skipping unreadable message in '{}': {}", id, e),
+                }
+            }
+            Ok(Conversation::new_unvalidated(messages))
+        })
+    }
+
+    fn save(
+        &self,
+        id: &str,
+        metadata: &SessionMetadata,
+        conversation: &Conversation,
+    ) -> Result<()> {
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            Self::upsert_session(&tx, id, metadata)?;
+
+            let rows = conversation
+                .messages()
+                .iter()
+                .map(MessageRow::new)
+                .collect::<Result<Vec<_>>>()?;
+            let keep = Self::reusable_prefix(&tx, id, &rows)?;
+            tx.execute(
+                "DELETE FROM messages WHERE session_id = ?1 AND idx >= ?2",
+                params![id, keep as i64],
+            )?;
+            Self::insert_messages(&tx, id, keep, &rows[keep..])?;
+
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    fn update_metadata(&self, id: &str, metadata: &SessionMetadata) -> Result<()> {
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            Self::upsert_session(&tx, id, metadata)?;
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    fn delete(&self, id: &str) -> Result<()> {
+        self.with_conn(|conn| {
+            conn.execute("DELETE FROM sessions WHERE id = ?1", params![id])?;
+            Ok(())
+        })
+    }
+
+    fn query(&self, query: &SessionQuery) -> Result<Vec<StoredSession>> {
+        let mut sql = String::from("SELECT id, updated_at, metadata FROM sessions WHERE 1 = 1");
+        let mut values: Vec<rusqlite::types::Value> = Vec::new();
+
+        if let Some(dir) = &query.working_dir {
+            values.push(dir.to_string_lossy().to_string().into());
+            sql.push_str(&format!(" AND working_dir = ?{}", values.len()));
+        }
+        if let Some(schedule_id) = &query.schedule_id {
+            values.push(schedule_id.clone().into());
+            sql.push_str(&format!(" AND schedule_id = ?{}", values.len()));
+        }
+        if let Some(after) = query.updated_after {
+            values.push(after.timestamp_millis().into());
+            sql.push_str(&format!(" AND updated_at >= ?{}", values.len()));
+        }
+        if let Some(before) = query.updated_before {
+            values.push(before.timestamp_millis().into());
+            sql.push_str(&format!(" AND updated_at < ?{}", values.len()));
+        }
+        sql.push_str(" ORDER BY updated_at DESC");
+        if let Some(limit) = query.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(values), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?;
+
+            let mut sessions = Vec::new();
+            for row in rows {
+                let (id, updated_at, metadata) = row?;
+                match Self::row_to_session(id.clone(), updated_at, metadata) {
+                    Ok(session) => sessions.push(session),
+                    Err(e) => tracing::warn!("Skipping unreadable session '{}': {}", id, e),
+                }
+            }
+            Ok(sessions)
+        })
+    }
+
+    fn search_index(&self, terms: &[String]) -> Result<Option<SearchMatches>> {
+        if terms.is_empty() {
+            return Ok(None);
+        }
+
+        self.with_conn(|conn| {
+            let total_messages: i64 =
+                conn.query_row("SELECT COUNT(*) FROM messages", [], |row| row.get(0))?;
+
+            let mut document_frequency = Vec::with_capacity(terms.len());
+            for term in terms {
+                let (condition, values) = Self::fts_condition(std::slice::from_ref(term));
+                let count: i64 = conn.query_row(
+                    &format!("SELECT COUNT(*) FROM messages_fts WHERE {}", condition),
+                    rusqlite::params_from_iter(values),
+                    |row| row.get(0),
+                )?;
+                document_frequency.push(count as usize);
+            }
+
+            let (condition, values) = Self::fts_condition(terms);
+            let mut stmt = conn.prepare(&format!(
+                "SELECT m.session_id, s.description, m.idx, m.message
+                 FROM messages_fts
+                 JOIN messages m ON m.rowid = messages_fts.rowid
+                 JOIN sessions s ON s.id = m.session_id
+                 WHERE {}",
+                condition
+            ))?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(values), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })?;
+
+            let mut messages = Vec::new();
+            for row in rows {
+                let (session_id, description, idx, json) = row?;
+                match serde_json::from_str::<Message>(&json) {
+                    Ok(message) => messages.push(MatchedMessage {
+                        session_id,
+                        description,
+                        message_index: idx as usize,
+                        message,
+                    }),
+                    Err(e) => {
+                        tracing::warn!("Skipping unreadable message in '{}': {}", session_id, e)
+                    }
+                }
+            }
+
+            Ok(Some(SearchMatches {
+                total_messages: total_messages as usize,
+                document_frequency,
+                messages,
+            }))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::message::Message;
+
+    fn conversation(texts: &[&str]) -> Conversation {
+        Conversation::new_unvalidated(texts.iter().enumerate().map(|(i, text)| {
+            if i % 2 == 0 {
+                Message::user().with_text(*text)
+            } else {
+                Message::assistant().with_text(*text)
+            }
+        }))
+    }
+
+    #[test]
+    fn test_save_appends_incrementally() -> Result<()> {
+        let store = SqliteSessionStore::in_memory()?;
+        let metadata = SessionMetadata::default();
+
+        store.save("s1", &metadata, &conversation(&["one", "two"]))?;
+        store.save("s1", &metadata, &conversation(&["one", "two", "three"]))?;
+
+        let messages = store.read_messages("s1")?;
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages.messages()[2].as_concat_text(), "three");
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_rewrites_changed_history() -> Result<()> {
+        let store = SqliteSessionStore::in_memory()?;
+        let metadata = SessionMetadata::default();
+
+        store.save("s1", &metadata, &conversation(&["one", "two", "three"]))?;
+        store.save("s1", &metadata, &conversation(&["summary"]))?;
+
+        let messages = store.read_messages("s1")?;
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages.messages()[0].as_concat_text(), "summary");
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_rewrites_edited_messages() -> Result<()> {
+        let store = SqliteSessionStore::in_memory()?;
+        let metadata = SessionMetadata::default();
+
+        store.save("s1", &metadata, &conversation(&["one", "two", "three"]))?;
+        store.save("s1", &metadata, &conversation(&["one", "edited", "three"]))?;
+
+        let messages = store.read_messages("s1")?;
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages.messages()[1].as_concat_text(), "edited");
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_rewrites_pinned_messages() -> Result<()> {
+        let store = SqliteSessionStore::in_memory()?;
+        let metadata = SessionMetadata::default();
+
+        let mut messages = conversation(&["one", "two", "three"]);
+        store.save("s1", &metadata, &messages)?;
+        assert!(messages.set_pinned(0, true));
+        store.save("s1", &metadata, &messages)?;
+
+        let stored = store.read_messages("s1")?;
+        assert_eq!(stored.len(), 3);
+        assert!(stored.messages()[0].is_pinned());
+        Ok(())
+    }
+
+    #[test]
+    fn test_metadata_and_token_usage_round_trip() -> Result<()> {
+        let store = SqliteSessionStore::in_memory()?;
+        let metadata = SessionMetadata {
+            description: "refactor".to_string(),
+            accumulated_total_tokens: Some(1234),
+            ..Default::default()
+        };
+
+        store.save("s1", &metadata, &Conversation::empty())?;
+        let read = store.read_metadata("s1")?;
+        assert_eq!(read.description, "refactor");
+        assert_eq!(read.accumulated_total_tokens, Some(1234));
+
+        let total: Option<i32> = store.with_conn(|conn| {
+            Ok(conn.query_row(
+                "SELECT accumulated_total_tokens FROM token_usage WHERE session_id = 's1'",
+                [],
+                |row| row.get(0),
+            )?)
+        })?;
+        assert_eq!(total, Some(1234));
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_and_delete() -> Result<()> {
+        let store = SqliteSessionStore::in_memory()?;
+        let dir = std::env::temp_dir();
+        let mut scheduled = SessionMetadata::new(dir.clone());
+        scheduled.schedule_id = Some("daily".to_string());
+
+        store.save(
+            "a",
+            &SessionMetadata::new(dir.clone()),
+            &Conversation::empty(),
+        )?;
+        store.save("b", &scheduled, &conversation(&["hi"]))?;
+
+        let by_dir = store.query(&SessionQuery {
+            working_dir: Some(dir),
+            ..Default::default()
+        })?;
+        assert_eq!(by_dir.len(), 2);
+
+        let by_schedule = store.query(&SessionQuery {
+            schedule_id: Some("daily".to_string()),
+            ..Default::default()
+        })?;
+        assert_eq!(by_schedule.len(), 1);
+        assert_eq!(by_schedule[0].id, "b");
+
+        let future = store.query(&SessionQuery {
+            updated_after: Some(Utc::now() + chrono::Duration::days(1)),
+            ..Default::default()
+        })?;
+        assert!(future.is_empty());
+
+        store.delete("b")?;
+        assert!(!store.exists("b")?);
+        assert!(store.read_messages("b")?.is_empty());
+        Ok(())
+    }
+}
@@ -5,11 +5,16 @@
 // - Backup creation
 // Additional debug logging can be added if needed for troubleshooting.
 
+use crate::agents::budget::BudgetExceeded;
+use crate::context_mgmt::summarize::RollingSummary;
 use crate::conversation::message::Message;
 use crate::conversation::Conversation;
 use crate::providers::base::Provider;
 use crate::recipe::Recipe;
+use crate::session::checkpoint::{self, Checkpoint};
 use crate::session::extension_data::ExtensionData;
+use crate::session::store::{active_sqlite_store, SessionStore};
+use crate::session::usage::{ModelUsage, RECORD_LOCK};
 use crate::utils::safe_truncate;
 use anyhow::Result;
 use chrono::Local;
@@ -23,7 +28,7 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use utoipa::ToSchema;
 
-// Security limits
+// Security limits (JSONL backend only)
 const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024; // 10MB
 const MAX_MESSAGE_COUNT: usize = 5000;
 const MAX_LINE_LENGTH: usize = 1024 * 1024; // 1MB per line
@@ -72,6 +77,27 @@ pub struct SessionMetadata {
     pub extension_data: ExtensionData,
 
     pub recipe: Option<Recipe>,
+
+    /// ID of the session this session was forked from, if any
+    pub parent_session_id: Option<String>,
+    /// Number of messages copied from the parent session when this session was forked
+    pub fork_point: Option<usize>,
+
+    /// Token usage and estimated cost per model and role, accumulated across all provider calls
+    #[serde(default)]
+    pub model_usage: Vec<ModelUsage>,
+
+    /// Set when the agent was last stopped because the session ran out of budget
+    #[serde(default)]
+    pub budget_exceeded: Option<BudgetExceeded>,
+
+    /// Snapshots of the working directory taken before each agent turn, oldest first
+    #[serde(default)]
+    pub checkpoints: Vec<Checkpoint>,
+
+    /// Running summary of the messages compacted out of the agent's context
+    #[serde(default)]
+    pub rolling_summary: Option<RollingSummary>,
 }
 
 // Custom deserializer to handle old sessions without working_dir
@@ -95,6 +121,18 @@ impl<'de> Deserialize<'de> for SessionMetadata {
             #[serde(default)]
             extension_data: ExtensionData,
             recipe: Option<Recipe>,
+            #[serde(default)]
+            parent_session_id: Option<String>,
+            #[serde(default)]
+            fork_point: Option<usize>,
+            #[serde(default)]
+            model_usage: Vec<ModelUsage>,
+            #[serde(default)]
+            budget_exceeded: Option<BudgetExceeded>,
+            #[serde(default)]
+            checkpoints: Vec<Checkpoint>,
+            #[serde(default)]
+            rolling_summary: Option<RollingSummary>,
         }
 
         let helper = Helper::deserialize(deserializer)?;
@@ -118,6 +156,12 @@ impl<'de> Deserialize<'de> for SessionMetadata {
             working_dir,
             extension_data: helper.extension_data,
             recipe: helper.recipe,
+            parent_session_id: helper.parent_session_id,
+            fork_point: helper.fork_point,
+            model_usage: helper.model_usage,
+            budget_exceeded: helper.budget_exceeded,
+            checkpoints: helper.checkpoints,
+            rolling_summary: helper.rolling_summary,
         })
     }
 }
@@ -144,6 +188,12 @@ impl SessionMetadata {
             accumulated_output_tokens: None,
             extension_data: ExtensionData::new(),
             recipe: None,
+            parent_session_id: None,
+            fork_point: None,
+            model_usage: Vec::new(),
+            budget_exceeded: None,
+            checkpoints: Vec::new(),
+            rolling_summary: None,
         }
     }
 }
@@ -352,6 +402,15 @@ pub fn ensure_session_dir() -> Result<PathBuf> {
 /// Get the path to the most recently modified session file
 pub fn get_most_recent_session() -> Result<PathBuf> {
     let session_dir = ensure_session_dir()?;
+    if let Some(store) = active_sqlite_store() {
+        return store
+            .list()?
+            .into_iter()
+            .next()
+            .map(|session| session_dir.join(format!("{}.jsonl", session.id)))
+            .ok_or_else(|| anyhow::anyhow!("No sessions found"));
+    }
+
     let mut entries = fs::read_dir(&session_dir)?
         .filter_map(|entry| entry.ok())
         .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "jsonl"))
@@ -379,6 +438,17 @@ pub fn get_most_recent_session() -> Result<PathBuf> {
 /// List all available session files
 pub fn list_sessions() -> Result<Vec<(String, PathBuf)>> {
     let session_dir = ensure_session_dir()?;
+    if let Some(store) = active_sqlite_store() {
+        return Ok(store
+            .list()?
+            .into_iter()
+            .map(|session| {
+                let path = session_dir.join(format!("{}.jsonl", session.id));
+                (session.id, path)
+            })
+            .collect());
+    }
+
     let entries = fs::read_dir(&session_dir)?
         .filter_map(|entry| {
             let entry = entry.ok()?;
@@ -396,12 +466,75 @@ pub fn list_sessions() -> Result<Vec<(String, PathBuf)>> {
     Ok(entries)
 }
 
+/// Session id for a session path, i.e. the file stem
+pub(crate) fn session_id_from_path(path: &Path) -> Result<String> {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .ok_or_else(|| anyhow::anyhow!("Invalid session path"))
+}
+
+/// The JSONL backend caps message counts; the SQLite backend does not
+fn exceeds_message_limit(count: usize) -> bool {
+    count > MAX_MESSAGE_COUNT && active_sqlite_store().is_none()
+}
+
+/// Check whether a session has been saved in the configured storage backend
+pub fn session_exists(session_file: &Path) -> bool {
+    let Ok(secure_path) = get_path(Identifier::Path(session_file.to_path_buf())) else {
+        return false;
+    };
+    match active_sqlite_store() {
+        Some(store) => session_id_from_path(&secure_path)
+            .and_then(|id| store.exists(&id))
+            .unwrap_or(false),
+        None => secure_path.exists(),
+    }
+}
+
+/// Directory holding the full outputs of the tool responses pruned from a session
+pub fn pruned_outputs_dir(session_file: &Path) -> Result<PathBuf> {
+    let session_dir = ensure_session_dir()?;
+    let session_id = session_id_from_path(session_file)?;
+    Ok(session_dir.join("pruned_tool_outputs").join(session_id))
+}
+
+/// Delete a session from the configured storage backend
+pub fn delete_session(session_file: &Path) -> Result<()> {
+    let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
+    if let Err(e) = checkpoint::delete_checkpoints(&secure_path) {
+        tracing::warn!("Failed to delete session checkpoints: {}", e);
+    }
+    let pruned_outputs = pruned_outputs_dir(&secure_path)?;
+    if pruned_outputs.exists() {
+        if let Err(e) = fs::remove_dir_all(&pruned_outputs) {
+            tracing::warn!("Failed to delete pruned tool outputs: {}", e);
+        }
+    }
+    if let Some(store) = active_sqlite_store() {
+        return store.delete(&session_id_from_path(&secure_path)?);
+    }
+    fs::remove_file(&secure_path)?;
+    Ok(())
+}
+
 /// Generate a session ID using timestamp format (yyyymmdd_hhmmss)
 /// TODO(Douwe): make this actually be unique
 pub fn generate_session_id() -> String {
     Local::now().format("%Y%m%d_%H%M%S").to_string()
 }
 
+/// Generate a session ID that doesn't collide with an existing session
+fn generate_unused_session_id() -> Result<String> {
+    let base = generate_session_id();
+    let mut id = base.clone();
+    let mut suffix = 1;
+    while session_exists(&get_path(Identifier::Name(id.clone()))?) {
+        id = format!("{}_{}", base, suffix);
+        suffix += 1;
+    }
+    Ok(id)
+}
+
 /// Read messages from a session file with corruption recovery
 ///
 /// Creates the file if it doesn't exist, reads and deserializes all messages if it does.
@@ -416,6 +549,10 @@ pub fn read_messages(session_file: &Path) -> Result<Conversation> {
     // Validate the path for security
     let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
 
+    if let Some(store) = active_sqlite_store() {
+        return store.read_messages(&session_id_from_path(&secure_path)?);
+    }
+
     let result = read_messages_with_truncation(&secure_path, Some(50000)); // 50KB limit per message content
     match &result {
         Ok(_messages) => {}
@@ -1014,18 +1151,27 @@ pub fn read_metadata(session_file: &Path) -> Result<SessionMetadata> {
     // Validate the path for security
     let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
 
+    if let Some(store) = active_sqlite_store() {
+        return store.read_metadata(&session_id_from_path(&secure_path)?);
+    }
+
+    read_jsonl_metadata(&secure_path)
+}
+
+/// Read session metadata from the first line of a JSONL session file
+pub(crate) fn read_jsonl_metadata(secure_path: &Path) -> Result<SessionMetadata> {
     if !secure_path.exists() {
         return Ok(SessionMetadata::default());
     }
 
     // Security check: file size
-    let file_metadata = fs::metadata(&secure_path)?;
+    let file_metadata = fs::metadata(secure_path)?;
     if file_metadata.len() > MAX_FILE_SIZE {
         tracing::warn!("Session file exceeds size limit during metadata read");
         return Err(anyhow::anyhow!("Session file too large"));
     }
 
-    let file = fs::File::open(&secure_path).map_err(|e| {
+    let file = fs::File::open(secure_path).map_err(|e| {
         tracing::error!("Failed to open session file for metadata read: {}", e);
         anyhow::anyhow!("Failed to access session file")
     })?;
@@ -1091,7 +1237,7 @@ pub async fn persist_messages_with_schedule_id(
     let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
 
     // Security check: message count limit
-    if messages.len() > MAX_MESSAGE_COUNT {
+    if exceeds_message_limit(messages.len()) {
         tracing::warn!("Message count exceeds limit: {}", messages.len());
         return Err(anyhow::anyhow!("Too many messages"));
     }
@@ -1117,7 +1263,7 @@ pub async fn persist_messages_with_schedule_id(
         }
         _ => {
             // Read existing metadata or create new with proper working_dir
-            let mut metadata = if secure_path.exists() {
+            let mut metadata = if session_exists(&secure_path) {
                 read_metadata(&secure_path)?
             } else {
                 // Create new metadata with the provided working_dir or fall back to home
@@ -1143,7 +1289,10 @@ pub async fn persist_messages_with_schedule_id(
 
 /// Write messages to a session file with the provided metadata using secure atomic operations
 ///
-/// This function uses atomic file operations to prevent corruption:
+/// When the SQLite session store is configured the session is written to the database
+/// instead, appending only the messages that are new since the last save.
+///
+/// For JSONL files this function uses atomic file operations to prevent corruption:
 /// 1. Writes to a temporary file first with secure permissions
 /// 2. Uses fs2 file locking to prevent concurrent writes
 /// 3. Atomically moves the temp file to the final location
@@ -1159,11 +1308,28 @@ pub fn save_messages_with_metadata(
     metadata: &SessionMetadata,
     conversation: &Conversation,
 ) -> Result<()> {
-    use fs2::FileExt;
-
     // Validate the path for security
     let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
 
+    match active_sqlite_store() {
+        Some(store) => store.save(&session_id_from_path(&secure_path)?, metadata, conversation)?,
+        None => save_jsonl(&secure_path, metadata, conversation)?,
+    }
+
+    if let Err(e) = checkpoint::prune_checkpoints(&secure_path, metadata) {
+        tracing::warn!("Failed to clean up session checkpoints: {}", e);
+    }
+    Ok(())
+}
+
+/// Atomically write a JSONL session file: metadata on the first line, then one message per line
+pub(crate) fn save_jsonl(
+    secure_path: &Path,
+    metadata: &SessionMetadata,
+    conversation: &Conversation,
+) -> Result<()> {
+    use fs2::FileExt;
+
     // Security check: message count limit
     if conversation.len() > MAX_MESSAGE_COUNT {
         tracing::warn!(
@@ -1253,7 +1419,7 @@ pub fn save_messages_with_metadata(
     })?;
 
     // Atomically move the temporary file to the final location
-    fs::rename(&temp_file, &secure_path).map_err(|e| {
+    fs::rename(&temp_file, secure_path).map_err(|e| {
         // Clean up temp file on failure
         tracing::error!("Failed to move temporary file: {}", e);
         let _ = fs::remove_file(&temp_file);
@@ -1297,7 +1463,7 @@ pub async fn generate_description_with_schedule_id(
     let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
 
     // Security check: message count limit
-    if messages.len() > MAX_MESSAGE_COUNT {
+    if exceeds_message_limit(messages.len()) {
         tracing::warn!(
             "Message count exceeds limit during description generation: {}",
             messages.len()
@@ -1317,7 +1483,7 @@ pub async fn generate_description_with_schedule_id(
         })?;
 
     // Create metadata with proper working_dir or read existing and update
-    let mut metadata = if secure_path.exists() {
+    let mut metadata = if session_exists(&secure_path) {
         read_metadata(&secure_path)?
     } else {
         // Create new metadata with the provided working_dir or fall back to home
@@ -1349,6 +1515,10 @@ pub async fn update_metadata(session_file: &Path, metadata: &SessionMetadata) ->
     // Validate the path for security
     let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
 
+    if let Some(store) = active_sqlite_store() {
+        return store.update_metadata(&session_id_from_path(&secure_path)?, metadata);
+    }
+
     // Read all messages from the file
     let messages = read_messages(&secure_path)?;
 
@@ -1356,6 +1526,94 @@ pub async fn update_metadata(session_file: &Path, metadata: &SessionMetadata) ->
     save_messages_with_metadata(&secure_path, metadata, &messages)
 }
 
+/// Read all messages of a session without truncating large content
+pub(crate) fn read_full_messages(session_file: &Path) -> Result<Conversation> {
+    let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
+    match active_sqlite_store() {
+        Some(store) => store.read_messages(&session_id_from_path(&secure_path)?),
+        None => read_messages_with_truncation(&secure_path, None),
+    }
+}
+
+/// Pin or unpin message `index` of a session, see [`Conversation::set_pinned`].
+/// Returns false, without changing the session, if it has no such message in the agent's context.
+pub async fn pin_message(session_file: &Path, index: usize, pinned: bool) -> Result<bool> {
+    let _guard = RECORD_LOCK.lock().await;
+    let mut messages = read_full_messages(session_file)?;
+    if !messages.set_pinned(index, pinned) {
+        return Ok(false);
+    }
+    save_messages_with_metadata(session_file, &read_metadata(session_file)?, &messages)?;
+    Ok(true)
+}
+
+/// Drop message `index` of a session from the agent's context, see [`Conversation::drop_message`].
+/// Returns the indices of the dropped messages, which is empty, without changing the session,
+/// if it has no such message in the agent's context.
+pub async fn drop_message(session_file: &Path, index: usize) -> Result<Vec<usize>> {
+    let _guard = RECORD_LOCK.lock().await;
+    let mut messages = read_full_messages(session_file)?;
+    let dropped = messages.drop_message(index);
+    if !dropped.is_empty() {
+        save_messages_with_metadata(session_file, &read_metadata(session_file)?, &messages)?;
+    }
+    Ok(dropped)
+}
+
+/// Fork a session into a new session containing its first `at` messages
+///
+/// Message `at` and everything after it are left out, so the branch continues from the
+/// point just before that message. The new session records the source session id and
+/// the fork point in its metadata and can be resumed like any other session. If no
+/// target is given a fresh session id is generated. Returns the path of the new session.
+pub fn fork_session(source_file: &Path, at: usize, target: Option<Identifier>) -> Result<PathBuf> {
+    let source_path = get_path(Identifier::Path(source_file.to_path_buf()))?;
+    if !session_exists(&source_path) {
+        return Err(anyhow::anyhow!("Session not found"));
+    }
+
+    // Read without truncation so the branch is an exact copy of the prefix
+    let messages = read_full_messages(&source_path)?;
+    if at > messages.len() {
+        return Err(anyhow::anyhow!(
+            "Cannot fork at message {}: the session only has {} messages",
+            at,
+            messages.len()
+        ));
+    }
+
+    let target_path = match target {
+        Some(identifier) => get_path(identifier)?,
+        None => get_path(Identifier::Name(generate_unused_session_id()?))?,
+    };
+    if session_exists(&target_path) {
+        return Err(anyhow::anyhow!("Target session already exists"));
+    }
+
+    let parent = read_metadata(&source_path)?;
+    let metadata = SessionMetadata {
+        message_count: at,
+        total_tokens: None,
+        input_tokens: None,
+        output_tokens: None,
+        accumulated_total_tokens: None,
+        accumulated_input_tokens: None,
+        accumulated_output_tokens: None,
+        parent_session_id: Some(session_id_from_path(&source_path)?),
+        fork_point: Some(at),
+        model_usage: Vec::new(),
+        budget_exceeded: None,
+        // The checkpoint store belongs to the source session
+        checkpoints: Vec::new(),
+        rolling_summary: None,
+        ..parent
+    };
+    let prefix = Conversation::new_unvalidated(messages.messages()[..at].iter().cloned());
+
+    save_messages_with_metadata(&target_path, &metadata, &prefix)?;
+    Ok(target_path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1983,4 +2241,84 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_fork_session() -> Result<()> {
+        let dir = tempdir()?;
+        let source = dir.path().join("source.jsonl");
+        let fork = dir.path().join("fork.jsonl");
+
+        let messages = Conversation::new_unvalidated(vec![
+            Message::user().with_text("first"),
+            Message::assistant().with_text("second"),
+            Message::user().with_text("third"),
+            Message::assistant().with_text("fourth"),
+        ]);
+        let mut metadata = SessionMetadata::new(dir.path().to_path_buf());
+        metadata.description = "original".to_string();
+        metadata.accumulated_total_tokens = Some(500);
+        save_messages_with_metadata(&source, &metadata, &messages)?;
+
+        let forked = fork_session(&source, 2, Some(Identifier::Path(fork.clone())))?;
+        assert_eq!(forked, fork);
+
+        let forked_messages = read_messages(&fork)?;
+        assert_eq!(forked_messages.len(), 2);
+        assert_eq!(forked_messages.messages()[1].as_concat_text(), "second");
+
+        let forked_metadata = read_metadata(&fork)?;
+        assert_eq!(
+            forked_metadata.parent_session_id,
+            Some("source".to_string())
+        );
+        assert_eq!(forked_metadata.fork_point, Some(2));
+        assert_eq!(forked_metadata.description, "original");
+        assert_eq!(forked_metadata.accumulated_total_tokens, None);
+
+        // The source is untouched
+        assert_eq!(read_messages(&source)?.len(), 4);
+
+        // Forking past the end or onto an existing session fails
+        assert!(fork_session(
+            &source,
+            5,
+            Some(Identifier::Path(dir.path().join("x.jsonl")))
+        )
+        .is_err());
+        assert!(fork_session(&source, 1, Some(Identifier::Path(fork))).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pin_and_drop_message() -> Result<()> {
+        let dir = tempdir()?;
+        let file = dir.path().join("pins.jsonl");
+
+        let messages = Conversation::new_unvalidated(vec![
+            Message::user().with_text("spec"),
+            Message::assistant().with_text("noted"),
+            Message::user().with_text("huge log"),
+        ]);
+        let mut metadata = SessionMetadata::new(dir.path().to_path_buf());
+        metadata.description = "pins".to_string();
+        save_messages_with_metadata(&file, &metadata, &messages)?;
+
+        assert!(pin_message(&file, 0, true).await?);
+        assert_eq!(drop_message(&file, 2).await?, vec![2]);
+
+        let stored = read_messages(&file)?;
+        assert_eq!(stored.len(), 3);
+        assert!(stored.messages()[0].is_pinned());
+        assert!(!stored.messages()[2].is_agent_visible());
+        assert!(stored.messages()[2].is_user_visible());
+        assert_eq!(read_metadata(&file)?.description, "pins");
+
+        // Dropped messages can't be pinned or dropped again
+        assert!(!pin_message(&file, 2, true).await?);
+        assert!(drop_message(&file, 2).await?.is_empty());
+        assert!(drop_message(&file, 3).await?.is_empty());
+
+        Ok(())
+    }
 }
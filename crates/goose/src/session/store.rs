@@ -0,0 +1,343 @@
+use crate::config::Config;
+use crate::conversation::Conversation;
+use crate::session::search::SearchMatches;
+use crate::session::sqlite_store::SqliteSessionStore;
+use crate::session::storage::{self, SessionMetadata};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Config key selecting the session storage backend ("jsonl" or "sqlite")
+pub const SESSION_STORE_CONFIG_KEY: &str = "GOOSE_SESSION_STORE";
+
+/// File name of the SQLite database inside the session directory
+pub const SQLITE_DB_FILE_NAME: &str = "sessions.db";
+
+/// The available session storage backends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStoreKind {
+    #[default]
+    Jsonl,
+    Sqlite,
+}
+
+impl SessionStoreKind {
+    /// Read the configured backend, falling back to JSONL when unset or unrecognised
+    pub fn from_config() -> Self {
+        let value: String = match Config::global().get_param(SESSION_STORE_CONFIG_KEY) {
+            Ok(value) => value,
+            Err(_) => return Self::default(),
+        };
+        match value.trim().to_lowercase().as_str() {
+            "sqlite" => Self::Sqlite,
+            "jsonl" => Self::Jsonl,
+            other => {
+                tracing::warn!("Unknown session store '{}', falling back to jsonl", other);
+                Self::Jsonl
+            }
+        }
+    }
+}
+
+/// A session as returned by listing and querying a store
+#[derive(Debug, Clone)]
+pub struct StoredSession {
+    pub id: String,
+    pub metadata: SessionMetadata,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Filters for querying sessions. All set filters must match.
+#[derive(Debug, Clone, Default)]
+pub struct SessionQuery {
+    pub working_dir: Option<PathBuf>,
+    pub schedule_id: Option<String>,
+    /// Only sessions updated at or after this instant
+    pub updated_after: Option<DateTime<Utc>>,
+    /// Only sessions updated strictly before this instant
+    pub updated_before: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+}
+
+impl SessionQuery {
+    fn matches(&self, session: &StoredSession) -> bool {
+        if let Some(dir) = &self.working_dir {
+            if &session.metadata.working_dir != dir {
+                return false;
+            }
+        }
+        if let Some(schedule_id) = &self.schedule_id {
+            if session.metadata.schedule_id.as_ref() != Some(schedule_id) {
+                return false;
+            }
+        }
+        if let Some(after) = self.updated_after {
+            if session.updated_at < after {
+                return false;
+            }
+        }
+        if let Some(before) = self.updated_before {
+            if session.updated_at >= before {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Common trait for session storage backends. Sessions are keyed by their id,
+/// which is the file stem of the session path used throughout goose.
+pub trait SessionStore: Send + Sync {
+    /// Whether a session with this id has been stored
+    fn exists(&self, id: &str) -> Result<bool>;
+
+    /// Read the metadata of a session, or default metadata if it doesn't exist
+    fn read_metadata(&self, id: &str) -> Result<SessionMetadata>;
+
+    /// Read all messages of a session, or an empty conversation if it doesn't exist
+    fn read_messages(&self, id: &str) -> Result<Conversation>;
+
+    /// Persist the metadata and full conversation of a session. Backends may
+    /// only write the messages that are new since the last save.
+    fn save(&self, id: &str, metadata: &SessionMetadata, conversation: &Conversation)
+        -> Result<()>;
+
+    /// Replace only the metadata of a session
+    fn update_metadata(&self, id: &str, metadata: &SessionMetadata) -> Result<()>;
+
+    /// Remove a session and all of its messages
+    fn delete(&self, id: &str) -> Result<()>;
+
+    /// List sessions matching the query, most recently updated first
+    fn query(&self, query: &SessionQuery) -> Result<Vec<StoredSession>>;
+
+    /// List every stored session, most recently updated first
+    fn list(&self) -> Result<Vec<StoredSession>> {
+        self.query(&SessionQuery::default())
+    }
+
+    /// Look up the messages containing all of the lowercase `terms` in the store's
+    /// full-text index. Stores without an index return `None` and are scanned instead.
+    fn search_index(&self, _terms: &[String]) -> Result<Option<SearchMatches>> {
+        Ok(None)
+    }
+}
+
+/// Session store backed by one JSONL file per session
+pub struct JsonlSessionStore {
+    dir: PathBuf,
+}
+
+impl JsonlSessionStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path_for(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.jsonl", id))
+    }
+}
+
+impl SessionStore for JsonlSessionStore {
+    fn exists(&self, id: &str) -> Result<bool> {
+        Ok(self.path_for(id).exists())
+    }
+
+    fn read_metadata(&self, id: &str) -> Result<SessionMetadata> {
+        storage::read_jsonl_metadata(&self.path_for(id))
+    }
+
+    fn read_messages(&self, id: &str) -> Result<Conversation> {
+        storage::read_messages_with_truncation(&self.path_for(id), Some(50000))
+    }
+
+    fn save(
+        &self,
+        id: &str,
+        metadata: &SessionMetadata,
+        conversation: &Conversation,
+    ) -> Result<()> {
+        storage::save_jsonl(&self.path_for(id), metadata, conversation)
+    }
+
+    fn update_metadata(&self, id: &str, metadata: &SessionMetadata) -> Result<()> {
+        let messages = self.read_messages(id)?;
+        self.save(id, metadata, &messages)
+    }
+
+    fn delete(&self, id: &str) -> Result<()> {
+        let path = self.path_for(id);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    fn query(&self, query: &SessionQuery) -> Result<Vec<StoredSession>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut sessions = Vec::new();
+        for entry in fs::read_dir(&self.dir)?.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "jsonl") {
+                continue;
+            }
+            let Some(id) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                continue;
+            };
+            let metadata = match storage::read_jsonl_metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable session '{}': {}", id, e);
+                    continue;
+                }
+            };
+            let updated_at = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .map(DateTime::<Utc>::from)
+                .unwrap_or_default();
+
+            let session = StoredSession {
+                id,
+                metadata,
+                updated_at,
+            };
+            if query.matches(&session) {
+                sessions.push(session);
+            }
+        }
+
+        sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        if let Some(limit) = query.limit {
+            sessions.truncate(limit);
+        }
+        Ok(sessions)
+    }
+}
+
+static SQLITE_STORE: OnceCell<Arc<SqliteSessionStore>> = OnceCell::new();
+
+/// Returns the SQLite store when it is the configured backend, opening it on first use
+pub(crate) fn active_sqlite_store() -> Option<Arc<SqliteSessionStore>> {
+    if SessionStoreKind::from_config() != SessionStoreKind::Sqlite {
+        return None;
+    }
+
+    SQLITE_STORE
+        .get_or_try_init(|| {
+            let path = storage::ensure_session_dir()?.join(SQLITE_DB_FILE_NAME);
+            SqliteSessionStore::open(&path).map(Arc::new)
+        })
+        .map_err(|e| tracing::error!("Failed to open session database: {}", e))
+        .ok()
+        .cloned()
+}
+
+/// Returns the configured session store
+pub fn session_store() -> Result<Arc<dyn SessionStore>> {
+    if let Some(store) = active_sqlite_store() {
+        return Ok(store);
+    }
+    Ok(Arc::new(JsonlSessionStore::new(
+        storage::ensure_session_dir()?,
+    )))
+}
+
+/// Outcome of migrating JSONL session files into another store
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct MigrationReport {
+    pub migrated: Vec<String>,
+    pub skipped: Vec<String>,
+    pub failed: Vec<(String, String)>,
+}
+
+/// Copy every JSONL session in `source_dir` into `target`.
+///
+/// Sessions that already exist in the target are skipped unless `overwrite` is set,
+/// which makes the migration safe to run more than once. Source files are left untouched.
+pub fn migrate_jsonl_sessions(
+    source_dir: &Path,
+    target: &dyn SessionStore,
+    overwrite: bool,
+) -> Result<MigrationReport> {
+    let source = JsonlSessionStore::new(source_dir.to_path_buf());
+    let mut report = MigrationReport::default();
+
+    for session in source.list()? {
+        if !overwrite && target.exists(&session.id)? {
+            report.skipped.push(session.id);
+            continue;
+        }
+
+        // Read without truncation so nothing is lost in the move
+        let messages =
+            match storage::read_messages_with_truncation(&source.path_for(&session.id), None) {
+                Ok(messages) => messages,
+                Err(e) => {
+                    report.failed.push((session.id, e.to_string()));
+                    continue;
+                }
+            };
+
+        if overwrite {
+            target.delete(&session.id)?;
+        }
+        match target.save(&session.id, &session.metadata, &messages) {
+            Ok(()) => report.migrated.push(session.id),
+            Err(e) => report.failed.push((session.id, e.to_string())),
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::message::Message;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_migrate_jsonl_sessions() -> Result<()> {
+        let dir = tempdir()?;
+        let jsonl = JsonlSessionStore::new(dir.path().to_path_buf());
+
+        let mut metadata = SessionMetadata::new(dir.path().to_path_buf());
+        metadata.description = "kafka fix".to_string();
+        metadata.schedule_id = Some("nightly".to_string());
+        let conversation = Conversation::new_unvalidated(vec![
+            Message::user().with_text("fix the flaky kafka test"),
+            Message::assistant().with_text("done"),
+        ]);
+        jsonl.save("20250101_120000", &metadata, &conversation)?;
+
+        let sqlite = SqliteSessionStore::open(&dir.path().join(SQLITE_DB_FILE_NAME))?;
+        let report = migrate_jsonl_sessions(dir.path(), &sqlite, false)?;
+        assert_eq!(report.migrated, vec!["20250101_120000".to_string()]);
+        assert!(report.failed.is_empty());
+
+        let migrated = sqlite.read_metadata("20250101_120000")?;
+        assert_eq!(migrated.description, "kafka fix");
+        assert_eq!(sqlite.read_messages("20250101_120000")?.len(), 2);
+
+        // Running again skips what is already there
+        let report = migrate_jsonl_sessions(dir.path(), &sqlite, false)?;
+        assert!(report.migrated.is_empty());
+        assert_eq!(report.skipped.len(), 1);
+
+        let by_schedule = sqlite.query(&SessionQuery {
+            schedule_id: Some("nightly".to_string()),
+            ..Default::default()
+        })?;
+        assert_eq!(by_schedule.len(), 1);
+        Ok(())
+    }
+}
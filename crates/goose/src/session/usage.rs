@@ -0,0 +1,309 @@
+use crate::config::Config;
+use crate::providers::base::ProviderUsage;
+use crate::providers::pricing::estimate_cached_cost_usd;
+use crate::session::storage::{self, SessionMetadata};
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+/// What a provider call was made for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageRole {
+    /// A regular agent turn
+    #[default]
+    Main,
+    /// An agent turn served by the lead model of a lead/worker setup
+    Lead,
+    /// An agent turn served by the worker model of a lead/worker setup
+    Worker,
+    /// A call made by a subagent on behalf of the session
+    Subagent,
+    /// Summarizing the conversation, usually with the fast model
+    Summarization,
+}
+
+impl std::fmt::Display for UsageRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            UsageRole::Main => "main",
+            UsageRole::Lead => "lead",
+            UsageRole::Worker => "worker",
+            UsageRole::Subagent => "subagent",
+            UsageRole::Summarization => "summarization",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Token usage and cost of one model in one role, accumulated over a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, Default)]
+pub struct ModelUsage {
+    pub provider: String,
+    pub model: String,
+    pub role: UsageRole,
+    /// Number of provider calls recorded
+    pub calls: u32,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    /// Input tokens read from the provider's prompt cache, included in `input_tokens`
+    #[serde(default)]
+    pub cache_read_tokens: i64,
+    /// Input tokens written to the provider's prompt cache, included in `input_tokens`
+    #[serde(default)]
+    pub cache_write_tokens: i64,
+    /// Estimated cost in USD, None when no pricing is known for the model
+    pub cost_usd: Option<f64>,
+}
+
+impl ModelUsage {
+    pub fn total_tokens(&self) -> i64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    /// Share of input tokens served from the prompt cache, None before any input was recorded
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        if self.input_tokens <= 0 {
+            return None;
+        }
+        Some(self.cache_read_tokens as f64 / self.input_tokens as f64)
+    }
+
+    /// The recorded cost, or an estimate from the pricing cache if none was recorded
+    /// (for example because the cache was not populated when the calls were made)
+    pub async fn cost_or_estimate(&self) -> Option<f64> {
+        if self.cost_usd.is_some() {
+            return self.cost_usd;
+        }
+        estimate_cached_cost_usd(
+            &self.provider,
+            &self.model,
+            self.input_tokens.max(0) as usize,
+            self.cache_read_tokens.max(0) as usize,
+            self.cache_write_tokens.max(0) as usize,
+            self.output_tokens.max(0) as usize,
+        )
+        .await
+    }
+}
+
+/// Name of the provider that served `usage`, used to look up pricing. Providers that
+/// switch between several report it; for the others it is the configured provider.
+fn provider_name_for(role: UsageRole, usage: &ProviderUsage) -> String {
+    if let Some(provider) = &usage.provider {
+        return provider.clone();
+    }
+    let config = Config::global();
+    let provider = if role == UsageRole::Lead {
+        config
+            .get_param::<String>("GOOSE_LEAD_PROVIDER")
+            .or_else(|_| config.get_param::<String>("GOOSE_PROVIDER"))
+    } else {
+        config.get_param::<String>("GOOSE_PROVIDER")
+    };
+    provider.unwrap_or_else(|_| "unknown".to_string())
+}
+
+impl SessionMetadata {
+    /// Merge one provider call into the per-model usage of this session
+    pub fn add_model_usage(
+        &mut self,
+        provider: &str,
+        role: UsageRole,
+        usage: &ProviderUsage,
+        cost_usd: Option<f64>,
+    ) {
+        let input = usage.usage.input_tokens.unwrap_or(0).max(0) as i64;
+        let output = usage.usage.output_tokens.unwrap_or(0).max(0) as i64;
+        let cache_read = usage.usage.cache_read_input_tokens.unwrap_or(0).max(0) as i64;
+        let cache_write = usage.usage.cache_write_input_tokens.unwrap_or(0).max(0) as i64;
+
+        let index = match self
+            .model_usage
+            .iter()
+            .position(|m| m.provider == provider && m.model == usage.model && m.role == role)
+        {
+            Some(index) => index,
+            None => {
+                self.model_usage.push(ModelUsage {
+                    provider: provider.to_string(),
+                    model: usage.model.clone(),
+                    role,
+                    ..Default::default()
+                });
+                self.model_usage.len() - 1
+            }
+        };
+
+        let entry = &mut self.model_usage[index];
+        entry.calls += 1;
+        entry.input_tokens += input;
+        entry.output_tokens += output;
+        entry.cache_read_tokens += cache_read;
+        entry.cache_write_tokens += cache_write;
+        if let Some(cost) = cost_usd {
+            entry.cost_usd = Some(entry.cost_usd.unwrap_or(0.0) + cost);
+        }
+    }
+
+    /// Total tokens across all recorded provider calls, falling back to the accumulated
+    /// token count for sessions recorded before per-model usage was tracked
+    pub fn total_model_tokens(&self) -> i64 {
+        if self.model_usage.is_empty() {
+            return self.accumulated_total_tokens.unwrap_or(0).max(0) as i64;
+        }
+        self.model_usage.iter().map(ModelUsage::total_tokens).sum()
+    }
+
+    /// Share of input tokens across all models served from the prompt cache
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let input: i64 = self.model_usage.iter().map(|m| m.input_tokens).sum();
+        if input <= 0 {
+            return None;
+        }
+        let cache_read: i64 = self.model_usage.iter().map(|m| m.cache_read_tokens).sum();
+        Some(cache_read as f64 / input as f64)
+    }
+
+    /// Total recorded cost in USD, or None if no recorded call could be priced
+    pub fn total_cost_usd(&self) -> Option<f64> {
+        self.model_usage
+            .iter()
+            .filter_map(|m| m.cost_usd)
+            .fold(None, |total, cost| Some(total.unwrap_or(0.0) + cost))
+    }
+}
+
+async fn cost_for(provider: &str, usage: &ProviderUsage) -> Option<f64> {
+    let tokens = |count: Option<i32>| count.unwrap_or(0).max(0) as usize;
+    estimate_cached_cost_usd(
+        provider,
+        &usage.model,
+        tokens(usage.usage.input_tokens),
+        tokens(usage.usage.cache_read_input_tokens),
+        tokens(usage.usage.cache_write_input_tokens),
+        tokens(usage.usage.output_tokens),
+    )
+    .await
+}
+
+/// Estimate the cost in USD of a provider call made in `role`
+pub async fn estimate_usage_cost(role: UsageRole, usage: &ProviderUsage) -> Option<f64> {
+    cost_for(&provider_name_for(role, usage), usage).await
+}
+
+/// Price a provider call and add it to `metadata`
+pub async fn add_usage(metadata: &mut SessionMetadata, role: UsageRole, usage: &ProviderUsage) {
+    let provider = provider_name_for(role, usage);
+    let cost = cost_for(&provider, usage).await;
+    metadata.add_model_usage(&provider, role, usage, cost);
+}
+
+/// Serializes read-modify-write cycles of the session metadata, e.g. `record_usage` calls
+/// from parallel subagents racing the agent's own metrics update. Everything that reads,
+/// changes and writes back the metadata of a running session should hold it.
+pub(crate) static RECORD_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// Record a provider call against the session stored at `session_file`
+pub async fn record_usage(
+    session_file: &Path,
+    role: UsageRole,
+    usage: &ProviderUsage,
+) -> Result<()> {
+    let _guard = RECORD_LOCK.lock().await;
+    let mut metadata = storage::read_metadata(session_file)?;
+    add_usage(&mut metadata, role, usage).await;
+    storage::update_metadata(session_file, &metadata).await
+}
+
+/// Cost of a session in USD, including usage that could only be priced after it was recorded
+pub async fn session_cost_usd(metadata: &SessionMetadata) -> Option<f64> {
+    let mut total = None;
+    for usage in &metadata.model_usage {
+        if let Some(cost) = usage.cost_or_estimate().await {
+            total = Some(total.unwrap_or(0.0) + cost);
+        }
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::Usage;
+
+    #[test]
+    fn test_add_model_usage_groups_by_model_and_role() {
+        let mut metadata = SessionMetadata::default();
+        let lead = ProviderUsage::new(
+            "big-model".to_string(),
+            Usage::new(Some(100), Some(20), None),
+        );
+        let worker = ProviderUsage::new(
+            "small-model".to_string(),
+            Usage::new(Some(50), Some(10), None),
+        );
+
+        metadata.add_model_usage("anthropic", UsageRole::Lead, &lead, Some(0.5));
+        metadata.add_model_usage("anthropic", UsageRole::Lead, &lead, Some(0.25));
+        metadata.add_model_usage("openai", UsageRole::Worker, &worker, None);
+        metadata.add_model_usage("openai", UsageRole::Summarization, &worker, Some(0.01));
+
+        assert_eq!(metadata.model_usage.len(), 3);
+        let lead_usage = &metadata.model_usage[0];
+        assert_eq!(lead_usage.calls, 2);
+        assert_eq!(lead_usage.input_tokens, 200);
+        assert_eq!(lead_usage.output_tokens, 40);
+        assert_eq!(lead_usage.cost_usd, Some(0.75));
+        assert_eq!(metadata.model_usage[1].cost_usd, None);
+        let total = metadata.total_cost_usd().unwrap();
+        assert!((total - 0.76).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_add_usage_attributes_to_serving_provider() {
+        let mut metadata = SessionMetadata::default();
+        let usage = ProviderUsage::new("gpt-4o".to_string(), Usage::new(Some(10), Some(5), None))
+            .served_by(Some("openai"));
+
+        add_usage(&mut metadata, UsageRole::Main, &usage).await;
+        assert_eq!(metadata.model_usage[0].provider, "openai");
+    }
+
+    #[test]
+    fn test_total_cost_without_pricing() {
+        let mut metadata = SessionMetadata::default();
+        assert_eq!(metadata.total_cost_usd(), None);
+
+        let usage = ProviderUsage::new("local".to_string(), Usage::new(Some(10), Some(5), None));
+        metadata.add_model_usage("ollama", UsageRole::Main, &usage, None);
+        assert_eq!(metadata.total_cost_usd(), None);
+        assert_eq!(metadata.model_usage[0].total_tokens(), 15);
+    }
+
+    #[test]
+    fn test_cache_tokens_and_hit_rate() {
+        let mut metadata = SessionMetadata::default();
+        assert_eq!(metadata.cache_hit_rate(), None);
+
+        let cold = ProviderUsage::new(
+            "claude".to_string(),
+            Usage::new(Some(1000), Some(10), None).with_cache_tokens(Some(0), Some(800)),
+        );
+        let warm = ProviderUsage::new(
+            "claude".to_string(),
+            Usage::new(Some(1000), Some(10), None).with_cache_tokens(Some(800), Some(0)),
+        );
+        metadata.add_model_usage("anthropic", UsageRole::Main, &cold, None);
+        metadata.add_model_usage("anthropic", UsageRole::Main, &warm, None);
+
+        let usage = &metadata.model_usage[0];
+        assert_eq!(usage.cache_read_tokens, 800);
+        assert_eq!(usage.cache_write_tokens, 800);
+        assert_eq!(usage.cache_hit_rate(), Some(0.4));
+        assert_eq!(metadata.cache_hit_rate(), Some(0.4));
+    }
+}
@@ -1,40 +1,458 @@
 use ahash::AHasher;
+use base64::Engine;
 use dashmap::DashMap;
-use rmcp::model::Tool;
+use etcetera::{choose_app_strategy, AppStrategy};
+use once_cell::sync::Lazy;
+use rmcp::model::{RawContent, Tool};
 use std::hash::{Hash, Hasher};
+use std::io::Cursor;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tiktoken_rs::CoreBPE;
-use tokio::sync::OnceCell;
 
-use crate::conversation::message::Message;
+use crate::config::APP_STRATEGY;
+use crate::conversation::message::{Message, MessageContent};
+use crate::model::ModelConfig;
 
-// Global tokenizer instance to avoid repeated initialization
-static TOKENIZER: OnceCell<Arc<CoreBPE>> = OnceCell::const_new();
+// Tokenizers are shared by every counter for the same model family
+static TOKENIZERS: Lazy<DashMap<TokenizerSource, Arc<Tokenizer>>> = Lazy::new(DashMap::new);
 
 // Cache size limits to prevent unbounded growth
 const MAX_TOKEN_CACHE_SIZE: usize = 10_000;
 
+/// Used for images whose dimensions cannot be read, about a 1024x1024 image for most models
+const DEFAULT_IMAGE_TOKENS: usize = 1_000;
+
+const CLAUDE_MAX_IMAGE_TOKENS: usize = 1_600;
+
+/// Tokens Anthropic adds to the system prompt when tools are provided
+const CLAUDE_TOOL_USE_OVERHEAD: usize = 346;
+
+/// The family of a model, which decides its tokenizer and how images and tool
+/// schemas are counted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModelFamily {
+    /// GPT-4o and later OpenAI models, using the o200k_base encoding
+    OpenAi,
+    /// GPT-4 and GPT-3.5, using the cl100k_base encoding
+    OpenAiLegacy,
+    Claude,
+    Gemini,
+    Llama,
+    Qwen,
+    Mistral,
+    DeepSeek,
+    /// Models we know nothing about, counted like GPT-4o
+    Other,
+}
+
+impl ModelFamily {
+    /// Detects the family from a model name, including provider prefixes such as
+    /// `openrouter/anthropic/claude-sonnet-4` or `databricks-meta-llama-3-3-70b-instruct`
+    pub fn from_model_name(model_name: &str) -> Self {
+        let name = model_name.to_lowercase();
+        let base = name.rsplit('/').next().unwrap_or(&name);
+
+        if name.contains("claude") {
+            Self::Claude
+        } else if name.contains("gemini") || name.contains("gemma") {
+            Self::Gemini
+        } else if name.contains("llama") {
+            Self::Llama
+        } else if name.contains("qwen") || name.contains("qwq") {
+            Self::Qwen
+        } else if ["mistral", "mixtral", "codestral", "devstral", "magistral"]
+            .iter()
+            .any(|m| name.contains(m))
+        {
+            Self::Mistral
+        } else if name.contains("deepseek") {
+            Self::DeepSeek
+        } else if (base.starts_with("gpt-4")
+            && !base.starts_with("gpt-4o")
+            && !base.starts_with("gpt-4."))
+            || base.starts_with("gpt-3.5")
+        {
+            Self::OpenAiLegacy
+        } else if base.starts_with("gpt-")
+            || base.starts_with("o1")
+            || base.starts_with("o3")
+            || base.starts_with("o4")
+        {
+            Self::OpenAi
+        } else {
+            Self::Other
+        }
+    }
+
+    pub fn from_model_config(model_config: &ModelConfig) -> Self {
+        Self::from_model_name(&model_config.model_name)
+    }
+
+    fn tokenizer_source(&self) -> TokenizerSource {
+        match self {
+            Self::OpenAiLegacy => TokenizerSource::Tiktoken(Encoding::Cl100kBase),
+            Self::Gemini => TokenizerSource::HuggingFace("google/gemma-3-27b-it"),
+            Self::Llama => TokenizerSource::HuggingFace("meta-llama/Llama-3.3-70B-Instruct"),
+            Self::Qwen => TokenizerSource::HuggingFace("Qwen/Qwen3-32B"),
+            Self::Mistral => {
+                TokenizerSource::HuggingFace("mistralai/Mistral-Small-24B-Instruct-2501")
+            }
+            Self::DeepSeek => TokenizerSource::HuggingFace("deepseek-ai/DeepSeek-V3"),
+            // Anthropic does not publish its tokenizer, the count_tokens endpoint is exact
+            Self::OpenAi | Self::Claude | Self::Other => {
+                TokenizerSource::Tiktoken(Encoding::O200kBase)
+            }
+        }
+    }
+
+    /// Tokens for an image of `width` x `height` pixels, after the provider's resizing
+    pub fn image_tokens(&self, width: u32, height: u32) -> usize {
+        let (width, height) = (width.max(1) as f64, height.max(1) as f64);
+        match self {
+            Self::Claude => {
+                // (width * height) / 750, after resizing to fit 1568px on the long edge
+                // and about 1.15 megapixels, which caps an image at about 1600 tokens
+                let scale = (1568.0 / width.max(height)).min(1.0);
+                let tokens = ((width * scale) * (height * scale) / 750.0).ceil() as usize;
+                tokens.min(CLAUDE_MAX_IMAGE_TOKENS)
+            }
+            Self::Gemini => {
+                // 258 tokens when both sides are at most 384px, otherwise per 768px tile
+                if width <= 384.0 && height <= 384.0 {
+                    258
+                } else {
+                    ((width / 768.0).ceil() * (height / 768.0).ceil()) as usize * 258
+                }
+            }
+            _ => {
+                // OpenAI high detail: fit in 2048px, shortest side down to 768px,
+                // then 170 tokens per 512px tile plus 85
+                let scale = (2048.0 / width.max(height)).min(1.0);
+                let (width, height) = (width * scale, height * scale);
+                let scale = (768.0 / width.min(height)).min(1.0);
+                let (width, height) = (width * scale, height * scale);
+                let tiles = (width / 512.0).ceil() * (height / 512.0).ceil();
+                85 + tiles as usize * 170
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Encoding {
+    O200kBase,
+    Cl100kBase,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum TokenizerSource {
+    Tiktoken(Encoding),
+    /// A tokenizer.json from a Hugging Face repo, found in the local tokenizer cache
+    HuggingFace(&'static str),
+}
+
+/// A tokenizer for one family of models
+pub enum Tokenizer {
+    Tiktoken(CoreBPE),
+    HuggingFace(Box<tokenizers::Tokenizer>),
+}
+
+impl Tokenizer {
+    pub fn count_tokens(&self, text: &str) -> usize {
+        match self {
+            Self::Tiktoken(bpe) => bpe.encode_with_special_tokens(text).len(),
+            Self::HuggingFace(tokenizer) => tokenizer
+                .encode(text, false)
+                .map(|encoding| encoding.len())
+                .unwrap_or_else(|_| text.len() / 4),
+        }
+    }
+}
+
+/// Where Hugging Face tokenizers are looked up, as `<repo with / replaced by -->/tokenizer.json`.
+/// They are not downloaded, since many of them are gated; families without one are counted
+/// with o200k_base.
+pub fn tokenizer_cache_dir() -> Option<PathBuf> {
+    choose_app_strategy(APP_STRATEGY.clone())
+        .ok()
+        .map(|strategy| strategy.in_cache_dir("tokenizers"))
+}
+
+fn load_tokenizer(source: TokenizerSource) -> Result<Tokenizer, String> {
+    match source {
+        TokenizerSource::Tiktoken(Encoding::O200kBase) => tiktoken_rs::o200k_base()
+            .map(Tokenizer::Tiktoken)
+            .map_err(|e| format!("Failed to initialize o200k_base tokenizer: {}", e)),
+        TokenizerSource::Tiktoken(Encoding::Cl100kBase) => tiktoken_rs::cl100k_base()
+            .map(Tokenizer::Tiktoken)
+            .map_err(|e| format!("Failed to initialize cl100k_base tokenizer: {}", e)),
+        TokenizerSource::HuggingFace(repo) => {
+            let path = tokenizer_cache_dir()
+                .ok_or_else(|| "No cache directory for tokenizers".to_string())?
+                .join(repo.replace('/', "--"))
+                .join("tokenizer.json");
+            tokenizers::Tokenizer::from_file(&path)
+                .map(|tokenizer| Tokenizer::HuggingFace(Box::new(tokenizer)))
+                .map_err(|e| format!("Failed to load {}: {}", path.display(), e))
+        }
+    }
+}
+
+/// Get the shared tokenizer for a model family, falling back to o200k_base when its
+/// Hugging Face tokenizer is not cached
+fn get_tokenizer(family: ModelFamily) -> Result<Arc<Tokenizer>, String> {
+    let source = family.tokenizer_source();
+    if let Some(tokenizer) = TOKENIZERS.get(&source) {
+        return Ok(tokenizer.clone());
+    }
+
+    let tokenizer = match load_tokenizer(source) {
+        Ok(tokenizer) => Arc::new(tokenizer),
+        Err(e) if source != ModelFamily::Other.tokenizer_source() => {
+            tracing::debug!("Counting {:?} tokens with o200k_base: {}", family, e);
+            get_tokenizer(ModelFamily::Other)?
+        }
+        Err(e) => return Err(e),
+    };
+    // Another thread may have loaded it meanwhile, keep the first one
+    Ok(TOKENIZERS.entry(source).or_insert(tokenizer).clone())
+}
+
+/// Tokens for a base64 encoded image
+pub fn count_image_tokens(family: ModelFamily, data: &str) -> usize {
+    image_dimensions(data)
+        .map(|(width, height)| family.image_tokens(width, height))
+        .unwrap_or(DEFAULT_IMAGE_TOKENS)
+}
+
+/// Reads the dimensions from the image header, without decoding the image
+fn image_dimensions(data: &str) -> Option<(u32, u32)> {
+    let bytes = base64::prelude::BASE64_STANDARD.decode(data).ok()?;
+    image::io::Reader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+fn counts_like_openai(family: ModelFamily) -> bool {
+    matches!(
+        family,
+        ModelFamily::OpenAi | ModelFamily::OpenAiLegacy | ModelFamily::Other
+    )
+}
+
+/// Tokens added once when any tools are sent
+fn count_tools_overhead(family: ModelFamily, tools: &[Tool]) -> usize {
+    if tools.is_empty() {
+        return 0;
+    }
+
+    match family {
+        // Tokens for the end of the function definitions
+        _ if counts_like_openai(family) => 12,
+        ModelFamily::Claude => CLAUDE_TOOL_USE_OVERHEAD,
+        _ => 0,
+    }
+}
+
+fn count_tool_tokens(
+    family: ModelFamily,
+    tool: &Tool,
+    count_tokens: impl Fn(&str) -> usize,
+) -> usize {
+    if counts_like_openai(family) {
+        return count_openai_tool_tokens(tool, count_tokens);
+    }
+
+    // Other providers send the schemas to the model as JSON
+    let schema = serde_json::json!({
+        "name": tool.name,
+        "description": tool.description,
+        "input_schema": tool.input_schema,
+    });
+    count_tokens(&schema.to_string())
+}
+
+fn count_tools_tokens(
+    family: ModelFamily,
+    tools: &[Tool],
+    count_tokens: impl Fn(&str) -> usize,
+) -> usize {
+    count_tools_overhead(family, tools)
+        + tools
+            .iter()
+            .map(|tool| count_tool_tokens(family, tool, &count_tokens))
+            .sum::<usize>()
+}
+
+/// OpenAI's function definition accounting, following how the schemas are rendered
+/// into the prompt
+fn count_openai_tool_tokens(tool: &Tool, count_tokens: impl Fn(&str) -> usize) -> usize {
+    // Token counts for different function components
+    let func_init = 7; // Tokens for function initialization
+    let prop_init = 3; // Tokens for properties initialization
+    let prop_key = 3; // Tokens for each property key
+    let enum_init: isize = -3; // Tokens adjustment for enum list start
+    let enum_item = 3; // Tokens for each enum item
+
+    let mut func_token_count = func_init;
+    let name = &tool.name;
+    let description = &tool
+        .description
+        .as_ref()
+        .map(|d| d.as_ref())
+        .unwrap_or_default()
+        .trim_end_matches('.');
+
+    // Note: the separator (:) is likely tokenized with adjacent tokens, so we use original approach for accuracy
+    let line = format!("{}:{}", name, description);
+    func_token_count += count_tokens(&line);
+
+    if let serde_json::Value::Object(properties) = &tool.input_schema["properties"] {
+        if !properties.is_empty() {
+            func_token_count += prop_init;
+            for (key, value) in properties {
+                func_token_count += prop_key;
+                let p_name = key;
+                let p_type = value["type"].as_str().unwrap_or("");
+                let p_desc = value["description"]
+                    .as_str()
+                    .unwrap_or("")
+                    .trim_end_matches('.');
+
+                // Note: separators are tokenized with adjacent tokens, keep original for accuracy
+                let line = format!("{}:{}:{}", p_name, p_type, p_desc);
+                func_token_count += count_tokens(&line);
+
+                if let Some(enum_values) = value["enum"].as_array() {
+                    func_token_count = func_token_count.saturating_add_signed(enum_init);
+                    for item in enum_values {
+                        if let Some(item_str) = item.as_str() {
+                            func_token_count += enum_item;
+                            func_token_count += count_tokens(item_str);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    func_token_count
+}
+
+fn count_content_tokens(
+    family: ModelFamily,
+    content: &MessageContent,
+    count_tokens: impl Fn(&str) -> usize,
+) -> usize {
+    match content {
+        MessageContent::Text(text) => count_tokens(&text.text),
+        MessageContent::Image(image) => count_image_tokens(family, &image.data),
+        MessageContent::ToolRequest(tool_request) => match &tool_request.tool_call {
+            Ok(tool_call) => {
+                // Note: separators are tokenized with adjacent tokens, keep original for accuracy
+                let text = format!(
+                    "{}:{}:{}",
+                    tool_request.id, tool_call.name, tool_call.arguments
+                );
+                count_tokens(&text)
+            }
+            Err(_) => 0,
+        },
+        MessageContent::ToolResponse(tool_response) => {
+            let text_tokens = content
+                .as_tool_response_text()
+                .map(|text| count_tokens(&text))
+                .unwrap_or(0);
+            let image_tokens: usize = match &tool_response.tool_result {
+                Ok(contents) => contents
+                    .iter()
+                    .filter_map(|item| match &item.raw {
+                        RawContent::Image(image) => Some(count_image_tokens(family, &image.data)),
+                        _ => None,
+                    })
+                    .sum(),
+                Err(_) => 0,
+            };
+            text_tokens + image_tokens
+        }
+        // Thinking is sent back to the models that produce it within a turn
+        MessageContent::Thinking(thinking) => count_tokens(&thinking.thinking),
+        _ => 0,
+    }
+}
+
+fn count_chat_tokens(
+    family: ModelFamily,
+    system_prompt: &str,
+    messages: &[Message],
+    tools: &[Tool],
+    count_tokens: impl Fn(&str) -> usize,
+) -> usize {
+    // <|im_start|>ROLE<|im_sep|>MESSAGE<|im_end|>
+    let tokens_per_message = 4;
+
+    let mut num_tokens = 0;
+    if !system_prompt.is_empty() {
+        num_tokens += count_tokens(system_prompt) + tokens_per_message;
+    }
+
+    for message in messages {
+        num_tokens += tokens_per_message;
+        for content in &message.content {
+            num_tokens += count_content_tokens(family, content, &count_tokens);
+        }
+    }
+
+    num_tokens += count_tools_tokens(family, tools, &count_tokens);
+
+    // Every reply is primed with <|start|>assistant<|message|>
+    num_tokens += 3;
+
+    num_tokens
+}
+
 /// Async token counter with caching capabilities
 pub struct AsyncTokenCounter {
-    tokenizer: Arc<CoreBPE>,
+    family: ModelFamily,
+    tokenizer: Arc<Tokenizer>,
     token_cache: Arc<DashMap<u64, usize>>, // content hash -> token count
 }
 
 /// Legacy synchronous token counter for backward compatibility
 pub struct TokenCounter {
-    tokenizer: Arc<CoreBPE>,
+    family: ModelFamily,
+    tokenizer: Arc<Tokenizer>,
 }
 
 impl AsyncTokenCounter {
-    /// Creates a new async token counter with caching
+    /// Creates a new async token counter with caching, using the o200k_base encoding
     pub async fn new() -> Result<Self, String> {
-        let tokenizer = get_tokenizer().await?;
+        Self::for_family(ModelFamily::Other).await
+    }
+
+    /// Creates a token counter for the model of `model_config`
+    pub async fn for_model(model_config: &ModelConfig) -> Result<Self, String> {
+        Self::for_family(ModelFamily::from_model_config(model_config)).await
+    }
+
+    pub async fn for_family(family: ModelFamily) -> Result<Self, String> {
+        let tokenizer = tokio::task::spawn_blocking(move || get_tokenizer(family))
+            .await
+            .map_err(|e| format!("Failed to load tokenizer: {}", e))??;
         Ok(Self {
+            family,
             tokenizer,
             token_cache: Arc::new(DashMap::new()),
         })
     }
 
+    pub fn family(&self) -> ModelFamily {
+        self.family
+    }
+
     /// Count tokens with optimized caching
     pub fn count_tokens(&self, text: &str) -> usize {
         // Use faster AHash for better performance
@@ -48,8 +466,7 @@ impl AsyncTokenCounter {
         }
 
         // Compute and cache result with size management
-        let tokens = self.tokenizer.encode_with_special_tokens(text);
-        let count = tokens.len();
+        let count = self.tokenizer.count_tokens(text);
 
         // Manage cache size to prevent unbounded growth
         if self.token_cache.len() >= MAX_TOKEN_CACHE_SIZE {
@@ -66,64 +483,22 @@ impl AsyncTokenCounter {
 
     /// Count tokens for tools with optimized string handling
     pub fn count_tokens_for_tools(&self, tools: &[Tool]) -> usize {
-        // Token counts for different function components
-        let func_init = 7; // Tokens for function initialization
-        let prop_init = 3; // Tokens for properties initialization
-        let prop_key = 3; // Tokens for each property key
-        let enum_init: isize = -3; // Tokens adjustment for enum list start
-        let enum_item = 3; // Tokens for each enum item
-        let func_end = 12; // Tokens for function ending
-
-        let mut func_token_count = 0;
-        if !tools.is_empty() {
-            for tool in tools {
-                func_token_count += func_init;
-                let name = &tool.name;
-                let description = &tool
-                    .description
-                    .as_ref()
-                    .map(|d| d.as_ref())
-                    .unwrap_or_default()
-                    .trim_end_matches('.');
+        count_tools_tokens(self.family, tools, |text| self.count_tokens(text))
+    }
 
-                // Note: the separator (:) is likely tokenized with adjacent tokens, so we use original approach for accuracy
-                let line = format!("{}:{}", name, description);
-                func_token_count += self.count_tokens(&line);
-
-                if let serde_json::Value::Object(properties) = &tool.input_schema["properties"] {
-                    if !properties.is_empty() {
-                        func_token_count += prop_init;
-                        for (key, value) in properties {
-                            func_token_count += prop_key;
-                            let p_name = key;
-                            let p_type = value["type"].as_str().unwrap_or("");
-                            let p_desc = value["description"]
-                                .as_str()
-                                .unwrap_or("")
-                                .trim_end_matches('.');
-
-                            // Note: separators are tokenized with adjacent tokens, keep original for accuracy
-                            let line = format!("{}:{}:{}", p_name, p_type, p_desc);
-                            func_token_count += self.count_tokens(&line);
-
-                            if let Some(enum_values) = value["enum"].as_array() {
-                                func_token_count =
-                                    func_token_count.saturating_add_signed(enum_init);
-                                for item in enum_values {
-                                    if let Some(item_str) = item.as_str() {
-                                        func_token_count += enum_item;
-                                        func_token_count += self.count_tokens(item_str);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            func_token_count += func_end;
-        }
+    /// Count the schema of a single tool, without the overhead of sending tools at all
+    pub fn count_tool_schema_tokens(&self, tool: &Tool) -> usize {
+        count_tool_tokens(self.family, tool, |text| self.count_tokens(text))
+    }
+
+    /// Count the tokens added once when `tools` are sent, on top of their schemas
+    pub fn count_tools_overhead(&self, tools: &[Tool]) -> usize {
+        count_tools_overhead(self.family, tools)
+    }
 
-        func_token_count
+    /// Count a single piece of message content, including images
+    pub fn count_content_tokens(&self, content: &MessageContent) -> usize {
+        count_content_tokens(self.family, content, |text| self.count_tokens(text))
     }
 
     /// Count chat tokens (using cached count_tokens)
@@ -133,39 +508,9 @@ impl AsyncTokenCounter {
         messages: &[Message],
         tools: &[Tool],
     ) -> usize {
-        let tokens_per_message = 4;
-        let mut num_tokens = 0;
-
-        if !system_prompt.is_empty() {
-            num_tokens += self.count_tokens(system_prompt) + tokens_per_message;
-        }
-
-        for message in messages {
-            num_tokens += tokens_per_message;
-            for content in &message.content {
-                if let Some(content_text) = content.as_text() {
-                    num_tokens += self.count_tokens(content_text);
-                } else if let Some(tool_request) = content.as_tool_request() {
-                    let tool_call = tool_request.tool_call.as_ref().unwrap();
-                    // Note: separators are tokenized with adjacent tokens, keep original for accuracy
-                    let text = format!(
-                        "{}:{}:{}",
-                        tool_request.id, tool_call.name, tool_call.arguments
-                    );
-                    num_tokens += self.count_tokens(&text);
-                } else if let Some(tool_response_text) = content.as_tool_response_text() {
-                    num_tokens += self.count_tokens(&tool_response_text);
-                }
-            }
-        }
-
-        if !tools.is_empty() {
-            num_tokens += self.count_tokens_for_tools(tools);
-        }
-
-        num_tokens += 3; // Reply primer
-
-        num_tokens
+        count_chat_tokens(self.family, system_prompt, messages, tools, |text| {
+            self.count_tokens(text)
+        })
     }
 
     /// Count everything including resources (using cached count_tokens)
@@ -203,73 +548,32 @@ impl Default for TokenCounter {
 }
 
 impl TokenCounter {
-    /// Creates a new `TokenCounter` using the fixed o200k_base encoding.
+    /// Creates a new `TokenCounter` using the o200k_base encoding.
     pub fn new() -> Self {
-        // Use blocking version of get_tokenizer
-        let tokenizer = get_tokenizer_blocking().expect("Failed to initialize tokenizer");
-        Self { tokenizer }
+        Self::for_family(ModelFamily::Other)
+    }
+
+    /// Creates a token counter for the model of `model_config`
+    pub fn for_model(model_config: &ModelConfig) -> Self {
+        Self::for_family(ModelFamily::from_model_config(model_config))
     }
 
-    /// Count tokens for a piece of text using our single tokenizer.
+    pub fn for_family(family: ModelFamily) -> Self {
+        let tokenizer = get_tokenizer(family).expect("Failed to initialize tokenizer");
+        Self { family, tokenizer }
+    }
+
+    pub fn family(&self) -> ModelFamily {
+        self.family
+    }
+
+    /// Count tokens for a piece of text using the tokenizer of the model family.
     pub fn count_tokens(&self, text: &str) -> usize {
-        let tokens = self.tokenizer.encode_with_special_tokens(text);
-        tokens.len()
+        self.tokenizer.count_tokens(text)
     }
 
     pub fn count_tokens_for_tools(&self, tools: &[Tool]) -> usize {
-        // Token counts for different function components
-        let func_init = 7; // Tokens for function initialization
-        let prop_init = 3; // Tokens for properties initialization
-        let prop_key = 3; // Tokens for each property key
-        let enum_init: isize = -3; // Tokens adjustment for enum list start
-        let enum_item = 3; // Tokens for each enum item
-        let func_end = 12; // Tokens for function ending
-
-        let mut func_token_count = 0;
-        if !tools.is_empty() {
-            for tool in tools {
-                func_token_count += func_init; // Add tokens for start of each function
-                let name = &tool.name;
-                let description = &tool
-                    .description
-                    .as_ref()
-                    .map(|d| d.as_ref())
-                    .unwrap_or_default()
-                    .trim_end_matches('.');
-                let line = format!("{}:{}", name, description);
-                func_token_count += self.count_tokens(&line); // Add tokens for name and description
-
-                if let serde_json::Value::Object(properties) = &tool.input_schema["properties"] {
-                    if !properties.is_empty() {
-                        func_token_count += prop_init; // Add tokens for start of properties
-                        for (key, value) in properties {
-                            func_token_count += prop_key; // Add tokens for each property
-                            let p_name = key;
-                            let p_type = value["type"].as_str().unwrap_or("");
-                            let p_desc = value["description"]
-                                .as_str()
-                                .unwrap_or("")
-                                .trim_end_matches('.');
-                            let line = format!("{}:{}:{}", p_name, p_type, p_desc);
-                            func_token_count += self.count_tokens(&line);
-                            if let Some(enum_values) = value["enum"].as_array() {
-                                func_token_count =
-                                    func_token_count.saturating_add_signed(enum_init); // Add tokens if property has enum list
-                                for item in enum_values {
-                                    if let Some(item_str) = item.as_str() {
-                                        func_token_count += enum_item;
-                                        func_token_count += self.count_tokens(item_str);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            func_token_count += func_end;
-        }
-
-        func_token_count
+        count_tools_tokens(self.family, tools, |text| self.count_tokens(text))
     }
 
     pub fn count_chat_tokens(
@@ -278,47 +582,9 @@ impl TokenCounter {
         messages: &[Message],
         tools: &[Tool],
     ) -> usize {
-        // <|im_start|>ROLE<|im_sep|>MESSAGE<|im_end|>
-        let tokens_per_message = 4;
-
-        // Count tokens in the system prompt
-        let mut num_tokens = 0;
-        if !system_prompt.is_empty() {
-            num_tokens += self.count_tokens(system_prompt) + tokens_per_message;
-        }
-
-        for message in messages {
-            num_tokens += tokens_per_message;
-            // Count tokens in the content
-            for content in &message.content {
-                // content can either be text response or tool request
-                if let Some(content_text) = content.as_text() {
-                    num_tokens += self.count_tokens(content_text);
-                } else if let Some(tool_request) = content.as_tool_request() {
-                    let tool_call = tool_request.tool_call.as_ref().unwrap();
-                    let text = format!(
-                        "{}:{}:{}",
-                        tool_request.id, tool_call.name, tool_call.arguments
-                    );
-                    num_tokens += self.count_tokens(&text);
-                } else if let Some(tool_response_text) = content.as_tool_response_text() {
-                    num_tokens += self.count_tokens(&tool_response_text);
-                } else {
-                    // unsupported content type such as image - pass
-                    continue;
-                }
-            }
-        }
-
-        // Count tokens for tools if provided
-        if !tools.is_empty() {
-            num_tokens += self.count_tokens_for_tools(tools);
-        }
-
-        // Every reply is primed with <|start|>assistant<|message|>
-        num_tokens += 3;
-
-        num_tokens
+        count_chat_tokens(self.family, system_prompt, messages, tools, |text| {
+            self.count_tokens(text)
+        })
     }
 
     pub fn count_everything(
@@ -339,44 +605,18 @@ impl TokenCounter {
     }
 }
 
-/// Get the global tokenizer instance (async version)
-/// Fixed encoding for all tokenization - using o200k_base for GPT-4o and o1 models
-async fn get_tokenizer() -> Result<Arc<CoreBPE>, String> {
-    let tokenizer = TOKENIZER
-        .get_or_init(|| async {
-            match tiktoken_rs::o200k_base() {
-                Ok(bpe) => Arc::new(bpe),
-                Err(e) => panic!("Failed to initialize o200k_base tokenizer: {}", e),
-            }
-        })
-        .await;
-    Ok(tokenizer.clone())
-}
-
-/// Get the global tokenizer instance (blocking version for backward compatibility)
-fn get_tokenizer_blocking() -> Result<Arc<CoreBPE>, String> {
-    // For the blocking version, we need to handle the case where the tokenizer hasn't been initialized yet
-    if let Some(tokenizer) = TOKENIZER.get() {
-        return Ok(tokenizer.clone());
-    }
-
-    // Initialize the tokenizer synchronously
-    match tiktoken_rs::o200k_base() {
-        Ok(bpe) => {
-            let tokenizer = Arc::new(bpe);
-            // Try to set it in the OnceCell, but it's okay if another thread beat us to it
-            let _ = TOKENIZER.set(tokenizer.clone());
-            Ok(tokenizer)
-        }
-        Err(e) => Err(format!("Failed to initialize o200k_base tokenizer: {}", e)),
-    }
-}
-
 /// Factory function for creating async token counters with proper error handling
 pub async fn create_async_token_counter() -> Result<AsyncTokenCounter, String> {
     AsyncTokenCounter::new().await
 }
 
+/// Factory function for creating an async token counter for the model of `model_config`
+pub async fn create_async_token_counter_for_model(
+    model_config: &ModelConfig,
+) -> Result<AsyncTokenCounter, String> {
+    AsyncTokenCounter::for_model(model_config).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -693,4 +933,120 @@ mod tests {
             "Longer text should have more tokens"
         );
     }
+
+    #[test]
+    fn test_model_family_from_model_name() {
+        let cases = [
+            ("gpt-4o", ModelFamily::OpenAi),
+            ("gpt-4.1-mini", ModelFamily::OpenAi),
+            ("o3-mini", ModelFamily::OpenAi),
+            ("gpt-4-turbo", ModelFamily::OpenAiLegacy),
+            ("gpt-3.5-turbo", ModelFamily::OpenAiLegacy),
+            ("claude-sonnet-4-20250514", ModelFamily::Claude),
+            (
+                "us.anthropic.claude-3-7-sonnet-20250219-v1:0",
+                ModelFamily::Claude,
+            ),
+            ("gemini-2.5-pro", ModelFamily::Gemini),
+            ("databricks-meta-llama-3-3-70b-instruct", ModelFamily::Llama),
+            ("qwen2.5-coder:32b", ModelFamily::Qwen),
+            ("mistralai/devstral-small", ModelFamily::Mistral),
+            ("deepseek-chat", ModelFamily::DeepSeek),
+            ("openrouter/openai/gpt-4o", ModelFamily::OpenAi),
+            ("grok-3", ModelFamily::Other),
+        ];
+        for (model_name, family) in cases {
+            assert_eq!(
+                ModelFamily::from_model_name(model_name),
+                family,
+                "{}",
+                model_name
+            );
+        }
+    }
+
+    #[test]
+    fn test_image_tokens_per_family() {
+        // 1024x1024 is scaled to 768x768, which is 4 tiles
+        assert_eq!(ModelFamily::OpenAi.image_tokens(1024, 1024), 85 + 4 * 170);
+        assert_eq!(ModelFamily::OpenAi.image_tokens(512, 512), 85 + 170);
+        assert_eq!(ModelFamily::Claude.image_tokens(1000, 1000), 1334);
+        // Large images are scaled down first
+        assert_eq!(ModelFamily::Claude.image_tokens(3136, 3136), 1600);
+        assert_eq!(ModelFamily::Gemini.image_tokens(300, 300), 258);
+        assert_eq!(ModelFamily::Gemini.image_tokens(1000, 1000), 4 * 258);
+    }
+
+    #[test]
+    fn test_count_chat_tokens_with_images() {
+        let mut png = Vec::new();
+        image::DynamicImage::new_rgb8(100, 60)
+            .write_to(&mut Cursor::new(&mut png), image::ImageOutputFormat::Png)
+            .unwrap();
+        let data = base64::prelude::BASE64_STANDARD.encode(&png);
+
+        let counter = TokenCounter::for_family(ModelFamily::Claude);
+        let text_only = vec![Message::user().with_text("What is in this image?")];
+        let with_image = vec![Message::user()
+            .with_text("What is in this image?")
+            .with_image(data.clone(), "image/png")];
+        let difference = counter.count_chat_tokens("", &with_image, &[])
+            - counter.count_chat_tokens("", &text_only, &[]);
+        assert_eq!(difference, ModelFamily::Claude.image_tokens(100, 60));
+
+        // Images returned by tools count too
+        let tool_response = vec![Message::user().with_tool_response(
+            "tool_1",
+            Ok(vec![rmcp::model::Content::image(data, "image/png")]),
+        )];
+        assert!(counter.count_chat_tokens("", &tool_response, &[]) >= difference);
+
+        // Unreadable images get a fixed estimate
+        assert_eq!(
+            count_image_tokens(ModelFamily::Claude, "not an image"),
+            DEFAULT_IMAGE_TOKENS
+        );
+    }
+
+    #[test]
+    fn test_tool_schema_accounting_per_family() {
+        let tools = vec![Tool::new(
+            "read_file",
+            "Read a file",
+            object!({
+                "properties": {
+                    "path": {"type": "string", "description": "The file to read"}
+                },
+                "required": ["path"]
+            }),
+        )];
+
+        let claude = TokenCounter::for_family(ModelFamily::Claude);
+        assert!(claude.count_tokens_for_tools(&tools) > CLAUDE_TOOL_USE_OVERHEAD);
+        assert_eq!(claude.count_tokens_for_tools(&[]), 0);
+
+        // OpenAI models keep their function definition accounting
+        let openai = TokenCounter::for_family(ModelFamily::OpenAi);
+        assert_eq!(
+            openai.count_tokens_for_tools(&tools),
+            TokenCounter::new().count_tokens_for_tools(&tools)
+        );
+        assert!(openai.count_tokens_for_tools(&tools) < claude.count_tokens_for_tools(&tools));
+    }
+
+    #[tokio::test]
+    async fn test_counter_for_model() {
+        let model_config = ModelConfig::new_or_fail("gpt-3.5-turbo");
+        let counter = create_async_token_counter_for_model(&model_config)
+            .await
+            .unwrap();
+        assert_eq!(counter.family(), ModelFamily::OpenAiLegacy);
+        assert!(counter.count_tokens("Hello, how are you?") > 0);
+
+        // Families without a cached Hugging Face tokenizer still count tokens
+        let counter = AsyncTokenCounter::for_family(ModelFamily::Llama)
+            .await
+            .unwrap();
+        assert!(counter.count_tokens("Hello, how are you?") > 0);
+    }
 }
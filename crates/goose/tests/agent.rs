@@ -148,6 +148,12 @@ async fn run_truncate_test(
             Ok(AgentEvent::HistoryReplaced(_)) => {
                 // Handle history replacement events if needed
             }
+            Ok(AgentEvent::BudgetExceeded(exceeded)) => {
+                println!("Budget exceeded: {exceeded}");
+            }
+            Ok(AgentEvent::ModelFallback(fallback)) => {
+                println!("Switched to {}/{}", fallback.to_provider, fallback.to_model);
+            }
             Err(e) => {
                 println!("Error: {:?}", e);
                 return Err(e);
@@ -885,6 +891,7 @@ mod retry_tests {
             execution_mode: None,
             max_turns: None,
             retry_config: Some(retry_config),
+            budget: None,
         };
 
         let conversation =
@@ -1064,6 +1071,7 @@ mod max_turns_tests {
             execution_mode: None,
             max_turns: Some(1),
             retry_config: None,
+            budget: None,
         };
         let conversation = Conversation::new(vec![Message::user().with_text("Hello")]).unwrap();
 
@@ -1092,6 +1100,8 @@ mod max_turns_tests {
                 Ok(AgentEvent::McpNotification(_)) => {}
                 Ok(AgentEvent::ModelChange { .. }) => {}
                 Ok(AgentEvent::HistoryReplaced(_)) => {}
+                Ok(AgentEvent::BudgetExceeded(_)) => {}
+                Ok(AgentEvent::ModelFallback(_)) => {}
                 Err(e) => {
                     return Err(e);
                 }
@@ -1116,4 +1126,135 @@ mod max_turns_tests {
         }
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_token_budget_stops_agent() -> Result<()> {
+        let agent = Agent::new();
+        agent
+            .update_provider(Arc::new(MockToolProvider::new()))
+            .await?;
+
+        let session_name = format!("test_budget_{}", uuid::Uuid::new_v4().simple());
+        let session_config = goose::agents::SessionConfig {
+            id: Identifier::Name(session_name.clone()),
+            working_dir: PathBuf::from("/tmp"),
+            schedule_id: None,
+            execution_mode: None,
+            max_turns: Some(10),
+            retry_config: None,
+            // Each mock turn uses 15 tokens, so the second turn is never started
+            budget: Some(goose::agents::SessionBudget {
+                max_tokens: Some(15),
+                ..Default::default()
+            }),
+        };
+        let conversation = Conversation::new(vec![Message::user().with_text("Hello")]).unwrap();
+
+        let reply_stream = agent
+            .reply(conversation, Some(session_config), None)
+            .await?;
+        tokio::pin!(reply_stream);
+
+        let mut exceeded = None;
+        while let Some(response_result) = reply_stream.next().await {
+            match response_result {
+                Ok(AgentEvent::Message(response)) => {
+                    if let Some(MessageContent::ToolConfirmationRequest(ref req)) =
+                        response.content.first()
+                    {
+                        agent.handle_confirmation(
+                            req.id.clone(),
+                            goose::permission::PermissionConfirmation {
+                                principal_type: goose::permission::permission_confirmation::PrincipalType::Tool,
+                                permission: goose::permission::Permission::AllowOnce,
+                            }
+                        ).await;
+                    }
+                }
+                Ok(AgentEvent::BudgetExceeded(event)) => exceeded = Some(event),
+                Ok(_) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let session_path = goose::session::get_path(Identifier::Name(session_name))?;
+        let metadata = goose::session::read_metadata(&session_path)?;
+        goose::session::delete_session(&session_path)?;
+
+        let exceeded = exceeded.expect("expected the token budget to stop the agent");
+        assert_eq!(exceeded.limit, goose::agents::BudgetLimit::Tokens);
+        assert_eq!(exceeded.spent, 15.0);
+        assert_eq!(metadata.budget_exceeded, Some(exceeded));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rewind_restores_files_and_conversation() -> Result<()> {
+        // Checkpoints are opt-in
+        std::env::set_var("GOOSE_CHECKPOINTS", "true");
+        let agent = Agent::new();
+        agent
+            .update_provider(Arc::new(MockToolProvider::new()))
+            .await?;
+
+        let workspace = tempfile::tempdir()?;
+        let file = workspace.path().join("notes.txt");
+        std::fs::write(&file, "before\n")?;
+
+        let session_name = format!("test_rewind_{}", uuid::Uuid::new_v4().simple());
+        let session_path = goose::session::get_path(Identifier::Name(session_name.clone()))?;
+        let conversation = Conversation::new(vec![Message::user().with_text("Hello")]).unwrap();
+        goose::session::persist_messages(
+            &session_path,
+            &conversation,
+            None,
+            Some(workspace.path().to_path_buf()),
+        )
+        .await?;
+
+        let session_config = goose::agents::SessionConfig {
+            id: Identifier::Name(session_name),
+            working_dir: workspace.path().to_path_buf(),
+            schedule_id: None,
+            execution_mode: None,
+            max_turns: Some(1),
+            retry_config: None,
+            budget: None,
+        };
+        let reply_stream = agent
+            .reply(conversation.clone(), Some(session_config), None)
+            .await?;
+        tokio::pin!(reply_stream);
+        while let Some(response_result) = reply_stream.next().await {
+            response_result?;
+        }
+
+        // The agent's turn changes the workspace and the conversation
+        std::fs::write(&file, "after\n")?;
+        std::fs::write(workspace.path().join("new.txt"), "created\n")?;
+        let mut messages = conversation.messages().clone();
+        messages.push(Message::assistant().with_text("Done"));
+        goose::session::persist_messages(
+            &session_path,
+            &Conversation::new_unvalidated(messages),
+            None,
+            None,
+        )
+        .await?;
+
+        let checkpoints = goose::session::read_metadata(&session_path)?.checkpoints;
+        let outcome = goose::session::rewind_to_turn(&session_path, 1).await;
+        let metadata = goose::session::read_metadata(&session_path)?;
+        goose::session::delete_session(&session_path)?;
+
+        assert_eq!(checkpoints.len(), 1);
+        assert_eq!(checkpoints[0].prompt, "Hello");
+        let outcome = outcome?;
+        assert_eq!(outcome.restored, vec![file.clone()]);
+        assert_eq!(outcome.removed, vec![workspace.path().join("new.txt")]);
+        assert!(outcome.conversation.is_empty());
+        assert_eq!(std::fs::read_to_string(&file)?, "before\n");
+        assert!(metadata.checkpoints.is_empty());
+        Ok(())
+    }
 }
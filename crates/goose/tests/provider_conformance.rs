@@ -0,0 +1,682 @@
+//! Conformance suite that every HTTP provider runs against a local mock server.
+//!
+//! Each provider has a directory under `tests/provider_fixtures` with one JSON fixture per
+//! scenario (`text.json`, `tool_calls.json`, ...). A fixture holds:
+//!
+//! - `request`: the path the provider should call, and optionally a `body` that the request
+//!   must include (a partial match, so only the fields that matter need to be listed)
+//! - `response`: what the server answers, as a `status` with either a JSON `body`, SSE
+//!   `events` or a `raw_body` of the given `content_type`, plus optional `headers`
+//! - `expect`: what the provider should make of it: `text`, `tool_calls`, `thinking`,
+//!   `usage` or an `error` (the name of a `ProviderError` variant)
+//!
+//! Responses are in each provider's own wire format, including its error bodies, so the
+//! suite catches providers that parse or classify them wrongly. The conversation sent in
+//! each scenario is fixed here, so fixtures from different providers are comparable.
+//!
+//! Every provider lists the scenarios it supports; a missing fixture for one of them, or a
+//! fixture for a scenario it does not list, fails its test. Retries run without backoff.
+
+use anyhow::{anyhow, bail, Result};
+use futures::StreamExt;
+use goose::conversation::message::{Message, MessageContent};
+use goose::model::{ModelConfig, ReasoningConfig};
+use goose::providers::base::{Provider, ProviderUsage};
+use goose::providers::errors::ProviderError;
+use goose::providers::retry::{override_retry_config, RetryConfig};
+use goose::providers::{
+    anthropic, azure, bedrock, databricks, gcpvertexai, google, groq, litellm, ollama, openai,
+    openrouter, snowflake, tetrate, venice, xai,
+};
+use rmcp::model::Tool;
+use rmcp::object;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// A 1x1 transparent PNG
+const TEST_IMAGE: &str =
+    "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+
+const SYSTEM_PROMPT: &str = "You are a helpful assistant.";
+
+/// Where providers with GCP credentials get their access token from the mock server
+const GCP_TOKEN_PATH: &str = "/token";
+
+/// Provider construction reads the environment, which is shared by all tests
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scenario {
+    Text,
+    ToolCalls,
+    Image,
+    Thinking,
+    Streaming,
+    ContextLength,
+    RateLimit,
+}
+
+impl Scenario {
+    const ALL: [Scenario; 7] = [
+        Scenario::Text,
+        Scenario::ToolCalls,
+        Scenario::Image,
+        Scenario::Thinking,
+        Scenario::Streaming,
+        Scenario::ContextLength,
+        Scenario::RateLimit,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Scenario::Text => "text",
+            Scenario::ToolCalls => "tool_calls",
+            Scenario::Image => "image",
+            Scenario::Thinking => "thinking",
+            Scenario::Streaming => "streaming",
+            Scenario::ContextLength => "context_length",
+            Scenario::RateLimit => "rate_limit",
+        }
+    }
+
+    fn messages(self) -> Vec<Message> {
+        match self {
+            Scenario::Text | Scenario::Streaming | Scenario::RateLimit => {
+                vec![Message::user().with_text("Just say hello!")]
+            }
+            Scenario::ToolCalls => {
+                vec![Message::user().with_text("What's the weather like in Paris and in London?")]
+            }
+            Scenario::Image => vec![Message::user()
+                .with_text("What is in this image?")
+                .with_image(TEST_IMAGE, "image/png")],
+            Scenario::Thinking => vec![Message::user().with_text("What is 17 * 23?")],
+            Scenario::ContextLength => vec![Message::user().with_text("hello ".repeat(1_000))],
+        }
+    }
+
+    fn tools(self) -> Vec<Tool> {
+        match self {
+            Scenario::ToolCalls => vec![Tool::new(
+                "get_weather",
+                "Get the weather for a location",
+                object!({
+                    "type": "object",
+                    "required": ["location"],
+                    "properties": {
+                        "location": {"type": "string", "description": "The city"}
+                    }
+                }),
+            )],
+            _ => vec![],
+        }
+    }
+
+    fn model_config(self, model: &str) -> ModelConfig {
+        let config = ModelConfig::new_or_fail(model);
+        match self {
+            Scenario::Thinking => {
+                config.with_reasoning(Some(ReasoningConfig::from_budget_tokens(2048)))
+            }
+            _ => config,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Fixture {
+    request: FixtureRequest,
+    response: FixtureResponse,
+    #[serde(default)]
+    expect: Expectation,
+}
+
+#[derive(Debug, Deserialize)]
+struct FixtureRequest {
+    path: String,
+    #[serde(default)]
+    body: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FixtureResponse {
+    #[serde(default = "default_status")]
+    status: u16,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    body: Option<Value>,
+    #[serde(default)]
+    events: Vec<SseEvent>,
+    #[serde(default)]
+    raw_body: Option<String>,
+    #[serde(default)]
+    content_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SseEvent {
+    #[serde(default)]
+    event: Option<String>,
+    data: Value,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Expectation {
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ExpectedToolCall>,
+    #[serde(default)]
+    thinking: Option<String>,
+    #[serde(default)]
+    usage: Option<ExpectedUsage>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExpectedToolCall {
+    name: String,
+    arguments: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExpectedUsage {
+    input_tokens: Option<i32>,
+    output_tokens: Option<i32>,
+    total_tokens: Option<i32>,
+}
+
+fn default_status() -> u16 {
+    200
+}
+
+impl FixtureResponse {
+    fn template(&self) -> ResponseTemplate {
+        let mut template = ResponseTemplate::new(self.status);
+        for (name, value) in &self.headers {
+            template = template.insert_header(name.as_str(), value.as_str());
+        }
+        if !self.events.is_empty() {
+            let body: String = self
+                .events
+                .iter()
+                .map(|event| {
+                    let data = match &event.data {
+                        Value::String(text) => text.clone(),
+                        data => data.to_string(),
+                    };
+                    match &event.event {
+                        Some(name) => format!("event: {}\ndata: {}\n\n", name, data),
+                        None => format!("data: {}\n\n", data),
+                    }
+                })
+                .collect();
+            template.set_body_raw(body, "text/event-stream")
+        } else if let Some(body) = &self.body {
+            template.set_body_json(body)
+        } else if let Some(body) = &self.raw_body {
+            let content_type = self.content_type.as_deref().unwrap_or("text/plain");
+            template.set_body_raw(body.clone(), content_type)
+        } else {
+            template
+        }
+    }
+}
+
+/// An HTTP provider under test, and how to point it at the mock server
+struct Target {
+    /// Directory name under `tests/provider_fixtures`
+    name: &'static str,
+    model: &'static str,
+    host_var: &'static str,
+    api_key_var: Option<&'static str>,
+    /// Other settings the provider needs, e.g. a deployment name or region
+    vars: &'static [(&'static str, &'static str)],
+    /// The scenarios the provider must have fixtures for
+    scenarios: &'static [Scenario],
+    /// Give the provider GCP credentials whose token comes from the mock server
+    gcp_auth: bool,
+    build: fn(ModelConfig) -> Result<Arc<dyn Provider>>,
+}
+
+impl Target {
+    fn fixture(&self, scenario: Scenario) -> Result<Option<Fixture>> {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/provider_fixtures")
+            .join(self.name)
+            .join(format!("{}.json", scenario.name()));
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| anyhow!("Invalid fixture {}: {}", path.display(), e))
+    }
+
+    fn provider(&self, scenario: Scenario, host: &str) -> Result<Arc<dyn Provider>> {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut vars: Vec<(&str, String)> = vec![(self.host_var, host.to_string())];
+        if let Some(key_var) = self.api_key_var {
+            vars.push((key_var, "test-key".to_string()));
+        }
+        vars.extend(
+            self.vars
+                .iter()
+                .map(|(name, value)| (*name, value.to_string())),
+        );
+        // Read when the provider is built, so the file only has to outlive that
+        let credentials = if self.gcp_auth {
+            let mut file = tempfile::NamedTempFile::new()?;
+            serde_json::to_writer(
+                &mut file,
+                &serde_json::json!({
+                    "type": "authorized_user",
+                    "client_id": "test-client",
+                    "client_secret": "test-secret",
+                    "refresh_token": "test-refresh-token",
+                    "token_uri": format!("{}{}", host, GCP_TOKEN_PATH),
+                }),
+            )?;
+            vars.push((
+                "GOOGLE_APPLICATION_CREDENTIALS",
+                file.path().to_string_lossy().into_owned(),
+            ));
+            Some(file)
+        } else {
+            None
+        };
+
+        let saved: Vec<_> = vars
+            .iter()
+            .map(|(name, _)| (*name, std::env::var(name).ok()))
+            .collect();
+        for (name, value) in &vars {
+            std::env::set_var(name, value);
+        }
+        let provider = (self.build)(scenario.model_config(self.model));
+        for (name, value) in saved {
+            match value {
+                Some(value) => std::env::set_var(name, value),
+                None => std::env::remove_var(name),
+            }
+        }
+        drop(credentials);
+        provider
+    }
+
+    async fn run(&self) {
+        // Error scenarios are retried by most providers; don't wait out real backoff
+        override_retry_config(RetryConfig::new(1, 0, 1.0, 0));
+
+        for scenario in Scenario::ALL {
+            let fixture = self
+                .fixture(scenario)
+                .unwrap_or_else(|e| panic!("{}: {}", self.name, e));
+            let required = self.scenarios.contains(&scenario);
+            let fixture = match (fixture, required) {
+                (Some(fixture), true) => fixture,
+                (None, false) => continue,
+                (None, true) => panic!(
+                    "{} has no fixture for the {} scenario",
+                    self.name,
+                    scenario.name()
+                ),
+                (Some(_), false) => panic!(
+                    "{} has a fixture for the {} scenario, which it does not list",
+                    self.name,
+                    scenario.name()
+                ),
+            };
+            if let Err(e) = self.run_scenario(scenario, &fixture).await {
+                panic!(
+                    "{} failed the {} scenario: {}",
+                    self.name,
+                    scenario.name(),
+                    e
+                );
+            }
+        }
+    }
+
+    async fn run_scenario(&self, scenario: Scenario, fixture: &Fixture) -> Result<()> {
+        let server = MockServer::start().await;
+        let mut mock = Mock::given(method("POST")).and(path(fixture.request.path.as_str()));
+        if let Some(body) = &fixture.request.body {
+            mock = mock.and(body_partial_json(body));
+        }
+        mock.respond_with(fixture.response.template())
+            .mount(&server)
+            .await;
+        if self.gcp_auth {
+            Mock::given(method("POST"))
+                .and(path(GCP_TOKEN_PATH))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "access_token": "test-token",
+                    "expires_in": 3599,
+                    "token_type": "Bearer",
+                })))
+                .mount(&server)
+                .await;
+        }
+
+        let provider = self.provider(scenario, &server.uri())?;
+        let messages = scenario.messages();
+        let tools = scenario.tools();
+        let result = if scenario == Scenario::Streaming {
+            collect_stream(provider.as_ref(), &messages, &tools).await
+        } else {
+            provider.complete(SYSTEM_PROMPT, &messages, &tools).await
+        };
+
+        if let Err(e) = check(&fixture.expect, result) {
+            // Show what was sent, as a request that doesn't match the fixture gets a 404
+            let requests: Vec<String> = server
+                .received_requests()
+                .await
+                .unwrap_or_default()
+                .iter()
+                .map(|request| {
+                    format!(
+                        "{} {}\n{}",
+                        request.method,
+                        request.url,
+                        String::from_utf8_lossy(&request.body)
+                    )
+                })
+                .collect();
+            bail!("{}\nRequests received: {:#?}", e, requests);
+        }
+        Ok(())
+    }
+}
+
+async fn collect_stream(
+    provider: &dyn Provider,
+    messages: &[Message],
+    tools: &[Tool],
+) -> Result<(Message, ProviderUsage), ProviderError> {
+    let mut stream = provider.stream(SYSTEM_PROMPT, messages, tools).await?;
+    let mut content = Vec::new();
+    let mut usage = None;
+    while let Some(item) = stream.next().await {
+        let (message, item_usage) = item?;
+        if let Some(message) = message {
+            content.extend(message.content);
+        }
+        if item_usage.is_some() {
+            usage = item_usage;
+        }
+    }
+
+    let mut message = Message::assistant();
+    for item in content {
+        message = message.with_content(item);
+    }
+    let usage = usage.ok_or_else(|| ProviderError::UsageError("Stream had no usage".into()))?;
+    Ok((message, usage))
+}
+
+fn check(
+    expect: &Expectation,
+    result: Result<(Message, ProviderUsage), ProviderError>,
+) -> Result<()> {
+    let (message, usage) = match (result, &expect.error) {
+        (Err(error), Some(expected)) => {
+            let kind = format!("{:?}", error);
+            let kind = kind.split('(').next().unwrap_or_default();
+            if kind != expected {
+                bail!("Expected a {} error, got {:?}", expected, error);
+            }
+            return Ok(());
+        }
+        (Ok(_), Some(expected)) => bail!("Expected a {} error, but the call succeeded", expected),
+        (Err(error), None) => bail!("Call failed: {:?}", error),
+        (Ok(result), None) => result,
+    };
+
+    if let Some(expected) = &expect.text {
+        let text: String = message.content.iter().filter_map(|c| c.as_text()).collect();
+        if &text != expected {
+            bail!("Expected text {:?}, got {:?}", expected, text);
+        }
+    }
+
+    let tool_calls: Vec<_> = message
+        .content
+        .iter()
+        .filter_map(|c| c.as_tool_request())
+        .map(|request| request.tool_call.clone())
+        .collect::<Result<_, _>>()
+        .map_err(|e| anyhow!("Invalid tool call: {:?}", e))?;
+    if tool_calls.len() != expect.tool_calls.len() {
+        bail!(
+            "Expected {} tool calls, got {:?}",
+            expect.tool_calls.len(),
+            tool_calls
+        );
+    }
+    for (call, expected) in tool_calls.iter().zip(&expect.tool_calls) {
+        if call.name != expected.name || call.arguments != expected.arguments {
+            bail!("Expected tool call {:?}, got {:?}", expected, call);
+        }
+    }
+
+    if let Some(expected) = &expect.thinking {
+        let thinking: String = message
+            .content
+            .iter()
+            .filter_map(|c| match c {
+                MessageContent::Thinking(thinking) => Some(thinking.thinking.as_str()),
+                _ => None,
+            })
+            .collect();
+        if &thinking != expected {
+            bail!("Expected thinking {:?}, got {:?}", expected, thinking);
+        }
+    }
+
+    if let Some(expected) = &expect.usage {
+        let actual = usage.usage;
+        if (
+            expected.input_tokens,
+            expected.output_tokens,
+            expected.total_tokens,
+        ) != (
+            actual.input_tokens,
+            actual.output_tokens,
+            actual.total_tokens,
+        ) {
+            bail!("Expected usage {:?}, got {:?}", expected, actual);
+        }
+    }
+
+    Ok(())
+}
+
+macro_rules! conformance_test {
+    (
+        $name:ident,
+        $provider:ty,
+        $model:expr,
+        $host_var:expr,
+        $api_key_var:expr,
+        [$($scenario:ident),* $(,)?]
+        $(, vars: [$(($var:expr, $value:expr)),* $(,)?])?
+        $(, gcp_auth: $gcp_auth:expr)?
+        $(,)?
+    ) => {
+        // Some providers block on the runtime while they are built
+        #[tokio::test(flavor = "multi_thread")]
+        async fn $name() {
+            Target {
+                name: stringify!($name),
+                model: $model,
+                host_var: $host_var,
+                api_key_var: $api_key_var,
+                vars: &[$($(($var, $value)),*)?],
+                scenarios: &[$(Scenario::$scenario),*],
+                gcp_auth: false $(|| $gcp_auth)?,
+                build: |model| Ok(Arc::new(<$provider>::from_env(model)?)),
+            }
+            .run()
+            .await;
+        }
+    };
+}
+
+conformance_test!(
+    openai,
+    openai::OpenAiProvider,
+    "gpt-4o",
+    "OPENAI_HOST",
+    Some("OPENAI_API_KEY"),
+    [Text, ToolCalls, Image, Streaming, ContextLength, RateLimit]
+);
+conformance_test!(
+    anthropic,
+    anthropic::AnthropicProvider,
+    "claude-sonnet-4-20250514",
+    "ANTHROPIC_HOST",
+    Some("ANTHROPIC_API_KEY"),
+    [
+        Text,
+        ToolCalls,
+        Image,
+        Thinking,
+        Streaming,
+        ContextLength,
+        RateLimit
+    ]
+);
+conformance_test!(
+    google,
+    google::GoogleProvider,
+    "gemini-2.5-flash",
+    "GOOGLE_HOST",
+    Some("GOOGLE_API_KEY"),
+    [
+        Text,
+        ToolCalls,
+        Image,
+        Thinking,
+        Streaming,
+        ContextLength,
+        RateLimit
+    ]
+);
+conformance_test!(
+    groq,
+    groq::GroqProvider,
+    "llama-3.3-70b-versatile",
+    "GROQ_HOST",
+    Some("GROQ_API_KEY"),
+    [Text, ToolCalls, Streaming, ContextLength, RateLimit]
+);
+conformance_test!(
+    xai,
+    xai::XaiProvider,
+    "grok-3",
+    "XAI_HOST",
+    Some("XAI_API_KEY"),
+    [Text, ToolCalls, Streaming, ContextLength, RateLimit]
+);
+conformance_test!(
+    ollama,
+    ollama::OllamaProvider,
+    "qwen3",
+    "OLLAMA_HOST",
+    None,
+    // Ollama truncates prompts that are too long rather than failing, and has no rate limits
+    [Text, ToolCalls, Image, Streaming]
+);
+conformance_test!(
+    openrouter,
+    openrouter::OpenRouterProvider,
+    "openai/gpt-4o",
+    "OPENROUTER_HOST",
+    Some("OPENROUTER_API_KEY"),
+    [Text, ToolCalls, Image, ContextLength, RateLimit]
+);
+conformance_test!(
+    azure,
+    azure::AzureProvider,
+    "gpt-4o",
+    "AZURE_OPENAI_ENDPOINT",
+    Some("AZURE_OPENAI_API_KEY"),
+    [Text, ToolCalls, Image, Streaming, ContextLength, RateLimit],
+    vars: [("AZURE_OPENAI_DEPLOYMENT_NAME", "gpt-4o")]
+);
+conformance_test!(
+    databricks,
+    databricks::DatabricksProvider,
+    "databricks-claude-sonnet-4",
+    "DATABRICKS_HOST",
+    Some("DATABRICKS_TOKEN"),
+    [Text, ToolCalls, Streaming, ContextLength, RateLimit]
+);
+conformance_test!(
+    litellm,
+    litellm::LiteLLMProvider,
+    "gpt-4o-mini",
+    "LITELLM_HOST",
+    Some("LITELLM_API_KEY"),
+    [Text, ToolCalls, Streaming, ContextLength, RateLimit]
+);
+conformance_test!(
+    venice,
+    venice::VeniceProvider,
+    "llama-3.3-70b",
+    "VENICE_HOST",
+    Some("VENICE_API_KEY"),
+    [Text, ToolCalls, Streaming, RateLimit]
+);
+conformance_test!(
+    tetrate,
+    tetrate::TetrateProvider,
+    "gpt-4.1",
+    "TETRATE_HOST",
+    Some("TETRATE_API_KEY"),
+    [Text, ToolCalls, Streaming, ContextLength, RateLimit]
+);
+conformance_test!(
+    snowflake,
+    snowflake::SnowflakeProvider,
+    "claude-4-sonnet",
+    "SNOWFLAKE_HOST",
+    Some("SNOWFLAKE_TOKEN"),
+    [Text, ToolCalls, Streaming, RateLimit]
+);
+// Converse streams use the binary AWS event stream encoding, which fixtures can't express
+conformance_test!(
+    bedrock,
+    bedrock::BedrockProvider,
+    "us.anthropic.claude-sonnet-4-20250514-v1:0",
+    "AWS_ENDPOINT_URL",
+    None,
+    [Text, ToolCalls, ContextLength, RateLimit],
+    vars: [
+        ("AWS_ACCESS_KEY_ID", "test-access-key"),
+        ("AWS_SECRET_ACCESS_KEY", "test-secret-key"),
+        ("AWS_REGION", "us-east-1"),
+        ("AWS_MAX_ATTEMPTS", "1"),
+        ("AWS_EC2_METADATA_DISABLED", "true"),
+    ]
+);
+conformance_test!(
+    gcpvertexai,
+    gcpvertexai::GcpVertexAIProvider,
+    "gemini-2.5-flash",
+    "GCP_VERTEX_HOST",
+    None,
+    [Text, ToolCalls, Streaming, ContextLength, RateLimit],
+    vars: [("GCP_PROJECT_ID", "test-project"), ("GCP_LOCATION", "us-central1")],
+    gcp_auth: true
+);
@@ -363,6 +363,7 @@ impl ScheduleToolTestBuilder {
             current_session_id: None,
             process_start_time: None,
             execution_mode: Some("background".to_string()),
+            budget: None,
         };
         {
             let mut jobs = self.scheduler.jobs.lock().await;
@@ -413,5 +414,11 @@ pub fn create_test_session_metadata(message_count: usize, working_dir: &str) ->
         accumulated_output_tokens: Some(50),
         extension_data: Default::default(),
         recipe: None,
+        parent_session_id: None,
+        fork_point: None,
+        model_usage: Vec::new(),
+        budget_exceeded: None,
+        checkpoints: Vec::new(),
+        rolling_summary: None,
     }
 }
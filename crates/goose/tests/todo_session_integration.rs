@@ -139,6 +139,7 @@ async fn test_todo_add_persists_to_session() {
         max_turns: Some(10),
         execution_mode: Some("auto".to_string()),
         retry_config: None,
+        budget: None,
     };
 
     // Process the conversation
@@ -195,6 +196,7 @@ async fn test_todo_list_reads_from_session() {
         max_turns: Some(10),
         execution_mode: Some("auto".to_string()),
         retry_config: None,
+        budget: None,
     };
 
     // Process the conversation
@@ -289,6 +291,7 @@ async fn test_todo_clear_removes_from_session() {
         max_turns: Some(10),
         execution_mode: Some("auto".to_string()),
         retry_config: None,
+        budget: None,
     };
 
     // Process the conversation
@@ -30,6 +30,50 @@ export type AuthorRequest = {
 /**
  * Configuration key metadata for provider setup
  */
+/**
+ * Why the agent was stopped: which limit was hit, the limit itself and what had been spent
+ */
+export type BudgetExceeded = {
+    limit: BudgetLimit;
+    max: number;
+    spent: number;
+};
+
+/**
+ * The kind of budget that was exceeded
+ */
+export type BudgetLimit = 'tokens' | 'cost_usd' | 'duration';
+
+/**
+ * A snapshot of the working directory taken before an agent turn
+ */
+export type Checkpoint = {
+    /**
+     * Unix timestamp (in seconds) of when the snapshot was taken
+     */
+    created_at: number;
+    /**
+     * Number of files in the snapshot
+     */
+    file_count: number;
+    /**
+     * Number of messages in the conversation before the user message that started the turn
+     */
+    message_count: number;
+    /**
+     * Start of the user message that started the turn
+     */
+    prompt: string;
+    /**
+     * Turn number, counting the checkpointed turns of the session from 1
+     */
+    turn: number;
+    /**
+     * The directory that was snapshotted
+     */
+    working_dir: string;
+};
+
 export type ConfigKey = {
     /**
      * Optional default value for the key
@@ -99,6 +143,50 @@ export type ContextManageResponse = {
     tokenCounts: Array<number>;
 };
 
+/**
+ * Token breakdown of the next request to the model
+ */
+export type ContextUsage = {
+    context_limit: number;
+    entries: Array<ContextUsageEntry>;
+    model: string;
+    /**
+     * Sum of the entries
+     */
+    total_tokens: number;
+};
+
+/**
+ * The tokens taken by one part of the next request
+ */
+export type ContextUsageEntry = {
+    /**
+     * How many items were grouped into this entry, such as the number of tool outputs
+     */
+    count: number;
+    kind: ContextUsageKind;
+    /**
+     * The extension or tool this part belongs to, if any
+     */
+    name?: string | null;
+    tokens: number;
+};
+
+/**
+ * What a part of the next request is
+ */
+export type ContextUsageKind = 'system_prompt' | 'extension_instructions' | 'hints' | 'tool_schema' | 'user_text' | 'assistant_text' | 'thinking' | 'tool_call' | 'tool_output' | 'image';
+
+/**
+ * Request payload for a breakdown of the context usage
+ */
+export type ContextUsageRequest = {
+    /**
+     * Messages of the conversation, as they would be sent with the next request
+     */
+    messages: Array<Message>;
+};
+
 export type CreateCustomProviderRequest = {
     api_key: string;
     api_url: string;
@@ -122,6 +210,7 @@ export type CreateRecipeResponse = {
 };
 
 export type CreateScheduleRequest = {
+    budget?: SessionBudget | null;
     cron: string;
     execution_mode?: string | null;
     id: string;
@@ -140,6 +229,20 @@ export type DeleteRecipeRequest = {
     id: string;
 };
 
+export type DropMessageRequest = {
+    /**
+     * Index of the message in the session
+     */
+    index: number;
+};
+
+export type DropMessageResponse = {
+    /**
+     * Indices of the dropped messages, including the other half of any tool calls
+     */
+    dropped: Array<number>;
+};
+
 export type EmbeddedResource = {
     _meta?: {
         [key: string]: unknown;
@@ -312,6 +415,20 @@ export type ExtensionResponse = {
     extensions: Array<ExtensionEntry>;
 };
 
+export type ForkSessionRequest = {
+    /**
+     * Index of the first message to leave out; the fork keeps messages before it
+     */
+    at: number;
+};
+
+export type ForkSessionResponse = {
+    /**
+     * Identifier of the newly created session
+     */
+    sessionId: string;
+};
+
 export type FrontendToolRequest = {
     id: string;
     toolCall: {
@@ -397,12 +514,30 @@ export type MessageMetadata = {
      * Whether the message should be included in the agent's context window
      */
     agentVisible?: boolean;
+    /**
+     * Whether the message must be kept in the agent's context window when it is compacted
+     */
+    pinned?: boolean;
     /**
      * Whether the message should be visible to the user in the UI
      */
     userVisible?: boolean;
 };
 
+/**
+ * Reported when a request is served by a different model than the previous request
+ */
+export type ModelFallback = {
+    from_model: string;
+    from_provider: string;
+    /**
+     * Why the switch happened, e.g. the error returned by the previous model
+     */
+    reason: string;
+    to_model: string;
+    to_provider: string;
+};
+
 /**
  * Information about a model's capabilities
  */
@@ -433,6 +568,33 @@ export type ModelInfo = {
     supports_cache_control?: boolean | null;
 };
 
+/**
+ * Token usage and cost of one model in one role, accumulated over a session
+ */
+export type ModelUsage = {
+    /**
+     * Input tokens read from the provider's prompt cache, included in `input_tokens`
+     */
+    cache_read_tokens?: number;
+    /**
+     * Input tokens written to the provider's prompt cache, included in `input_tokens`
+     */
+    cache_write_tokens?: number;
+    /**
+     * Number of provider calls recorded
+     */
+    calls: number;
+    /**
+     * Estimated cost in USD, None when no pricing is known for the model
+     */
+    cost_usd?: number | null;
+    input_tokens: number;
+    model: string;
+    output_tokens: number;
+    provider: string;
+    role: UsageRole;
+};
+
 export type PermissionConfirmationRequest = {
     action: string;
     id: string;
@@ -445,6 +607,17 @@ export type PermissionConfirmationRequest = {
  */
 export type PermissionLevel = 'always_allow' | 'ask_before' | 'never_allow';
 
+export type PinMessageRequest = {
+    /**
+     * Index of the message in the session
+     */
+    index: number;
+    /**
+     * Whether to pin or unpin the message
+     */
+    pinned: boolean;
+};
+
 export type PrincipalType = 'Extension' | 'Tool';
 
 export type ProviderDetails = {
@@ -522,6 +695,11 @@ export type RawTextContent = {
     text: string;
 };
 
+/**
+ * How much a model should reason before answering
+ */
+export type ReasoningEffort = 'low' | 'medium' | 'high';
+
 /**
  * A Recipe represents a personalized, user-generated agent configuration that defines
  * specific behaviors and capabilities within the Goose system.
@@ -669,6 +847,33 @@ export type RetryConfig = {
 
 export type Role = string;
 
+/**
+ * A running summary of the part of a session that no longer fits in the context,
+ * stored in the session metadata and updated each time more messages are compacted
+ */
+export type RollingSummary = {
+    /**
+     * Key decisions and designs, and why they were made
+     */
+    decisions?: Array<string>;
+    /**
+     * Files viewed, created or changed, with what was done to each
+     */
+    files_touched?: Array<string>;
+    /**
+     * What the user wants to achieve
+     */
+    goals?: Array<string>;
+    /**
+     * Anything else needed to continue, such as errors and their fixes
+     */
+    notes?: Array<string>;
+    /**
+     * Work still to be done
+     */
+    open_todos?: Array<string>;
+};
+
 export type RunNowResponse = {
     session_id: string;
 };
@@ -682,6 +887,7 @@ export type ScanRecipeResponse = {
 };
 
 export type ScheduledJob = {
+    budget?: SessionBudget | null;
     cron: string;
     current_session_id?: string | null;
     currently_running?: boolean;
@@ -693,6 +899,29 @@ export type ScheduledJob = {
     source: string;
 };
 
+/**
+ * Which part of a message a search hit matched
+ */
+export type SearchMatchKind = 'text' | 'toolRequest' | 'toolResponse';
+
+/**
+ * Spend limits for a session. Limits that are not set are not enforced.
+ */
+export type SessionBudget = {
+    /**
+     * Maximum estimated cost in USD across all provider calls of the session
+     */
+    max_cost_usd?: number | null;
+    /**
+     * Maximum wall-clock time in seconds for a single run of the agent
+     */
+    max_duration_secs?: number | null;
+    /**
+     * Maximum total tokens across all provider calls of the session
+     */
+    max_tokens?: number | null;
+};
+
 export type SessionConfigRequest = {
     response?: Response | null;
     session_id: string;
@@ -755,11 +984,20 @@ export type SessionMetadata = {
      * The total number of tokens used in the session. Accumulated across all messages (useful for tracking cost over an entire session).
      */
     accumulated_total_tokens?: number | null;
+    budget_exceeded?: BudgetExceeded | null;
+    /**
+     * Snapshots of the working directory taken before each agent turn, oldest first
+     */
+    checkpoints?: Array<Checkpoint>;
     /**
      * A short description of the session, typically 3 words or less
      */
     description: string;
     extension_data?: ExtensionData;
+    /**
+     * Number of messages copied from the parent session when this session was forked
+     */
+    fork_point?: number | null;
     /**
      * The number of input tokens used in the session. Retrieved from the provider's last usage.
      */
@@ -768,11 +1006,20 @@ export type SessionMetadata = {
      * Number of messages in the session
      */
     message_count: number;
+    /**
+     * Token usage and estimated cost per model and role, accumulated across all provider calls
+     */
+    model_usage?: Array<ModelUsage>;
     /**
      * The number of output tokens used in the session. Retrieved from the provider's last usage.
      */
     output_tokens?: number | null;
+    /**
+     * ID of the session this session was forked from, if any
+     */
+    parent_session_id?: string | null;
     recipe?: Recipe | null;
+    rolling_summary?: RollingSummary | null;
     /**
      * ID of the schedule that triggered this session, if any
      */
@@ -787,6 +1034,38 @@ export type SessionMetadata = {
     working_dir: string;
 };
 
+/**
+ * A single message matching a session search
+ */
+export type SessionSearchHit = {
+    /**
+     * Description of the session the hit belongs to
+     */
+    description: string;
+    matchedIn: SearchMatchKind;
+    /**
+     * Index of the matching message within the session conversation
+     */
+    messageIndex: number;
+    role: string;
+    /**
+     * Relevance score, higher is better
+     */
+    score: number;
+    sessionId: string;
+    /**
+     * Excerpt of the matching text around the first match
+     */
+    snippet: string;
+};
+
+export type SessionSearchResponse = {
+    /**
+     * Matching messages, best match first
+     */
+    results: Array<SessionSearchHit>;
+};
+
 export type SessionsQuery = {
     limit?: number;
 };
@@ -794,7 +1073,9 @@ export type SessionsQuery = {
 export type Settings = {
     goose_model?: string | null;
     goose_provider?: string | null;
+    reasoning_effort?: ReasoningEffort | null;
     temperature?: number | null;
+    thinking_budget?: number | null;
 };
 
 export type SetupResponse = {
@@ -838,6 +1119,21 @@ export type SummarizationRequested = {
     msg: string;
 };
 
+/**
+ * A model a provider offers, as reported by the provider
+ */
+export type SupportedModel = {
+    /**
+     * What the model supports beyond chat, such as "tools", "vision" or "thinking"
+     */
+    capabilities: Array<string>;
+    /**
+     * The context length of the model, when the provider reports it
+     */
+    context_limit?: number | null;
+    name: string;
+};
+
 export type TextContent = {
     _meta?: {
         [key: string]: unknown;
@@ -935,6 +1231,11 @@ export type UpsertPermissionsQuery = {
     tool_permissions: Array<ToolPermission>;
 };
 
+/**
+ * What a provider call was made for
+ */
+export type UsageRole = 'main' | 'lead' | 'worker' | 'subagent' | 'summarization';
+
 export type AddSubRecipesData = {
     body: AddSubRecipesRequest;
     path?: never;
@@ -1455,9 +1756,9 @@ export type GetProviderModelsErrors = {
 
 export type GetProviderModelsResponses = {
     /**
-     * Models fetched successfully
+     * Models fetched successfully, with context length and capabilities where the provider reports them
      */
-    200: Array<string>;
+    200: Array<SupportedModel>;
 };
 
 export type GetProviderModelsResponse = GetProviderModelsResponses[keyof GetProviderModelsResponses];
@@ -1635,6 +1936,37 @@ export type ManageContextResponses = {
 
 export type ManageContextResponse = ManageContextResponses[keyof ManageContextResponses];
 
+export type ContextUsageData = {
+    body: ContextUsageRequest;
+    path?: never;
+    query?: never;
+    url: '/context/usage';
+};
+
+export type ContextUsageErrors = {
+    /**
+     * Unauthorized - Invalid or missing API key
+     */
+    401: unknown;
+    /**
+     * Precondition failed - Agent not available
+     */
+    412: unknown;
+    /**
+     * Internal server error
+     */
+    500: unknown;
+};
+
+export type ContextUsageResponses = {
+    /**
+     * Token breakdown of the next request
+     */
+    200: ContextUsage;
+};
+
+export type ContextUsageResponse = ContextUsageResponses[keyof ContextUsageResponses];
+
 export type StartOpenrouterSetupData = {
     body?: never;
     path?: never;
@@ -2139,6 +2471,42 @@ export type ListSessionsResponses = {
 
 export type ListSessionsResponse = ListSessionsResponses[keyof ListSessionsResponses];
 
+export type SearchSessionsData = {
+    body?: never;
+    path?: never;
+    query: {
+        /**
+         * Text to search for; every word must match
+         */
+        query: string;
+        /**
+         * Maximum number of results to return
+         */
+        limit?: number;
+    };
+    url: '/sessions/search';
+};
+
+export type SearchSessionsErrors = {
+    /**
+     * Unauthorized - Invalid or missing API key
+     */
+    401: unknown;
+    /**
+     * Internal server error
+     */
+    500: unknown;
+};
+
+export type SearchSessionsResponses = {
+    /**
+     * Search results retrieved successfully
+     */
+    200: SessionSearchResponse;
+};
+
+export type SearchSessionsResponse = SearchSessionsResponses[keyof SearchSessionsResponses];
+
 export type GetSessionHistoryData = {
     body?: never;
     path: {
@@ -2175,6 +2543,124 @@ export type GetSessionHistoryResponses = {
 
 export type GetSessionHistoryResponse = GetSessionHistoryResponses[keyof GetSessionHistoryResponses];
 
+export type DropMessageData = {
+    body: DropMessageRequest;
+    path: {
+        /**
+         * Unique identifier for the session
+         */
+        session_id: string;
+    };
+    query?: never;
+    url: '/sessions/{session_id}/drop';
+};
+
+export type DropMessageErrors = {
+    /**
+     * Bad request - No such message in the agent's context
+     */
+    400: unknown;
+    /**
+     * Unauthorized - Invalid or missing API key
+     */
+    401: unknown;
+    /**
+     * Session not found
+     */
+    404: unknown;
+    /**
+     * Internal server error
+     */
+    500: unknown;
+};
+
+export type DropMessageResponses = {
+    /**
+     * Message dropped from the agent's context successfully
+     */
+    200: DropMessageResponse;
+};
+
+export type DropMessageResponse2 = DropMessageResponses[keyof DropMessageResponses];
+
+export type ForkSessionData = {
+    body: ForkSessionRequest;
+    path: {
+        /**
+         * Unique identifier for the session to fork
+         */
+        session_id: string;
+    };
+    query?: never;
+    url: '/sessions/{session_id}/fork';
+};
+
+export type ForkSessionErrors = {
+    /**
+     * Bad request - Fork point beyond the end of the session
+     */
+    400: unknown;
+    /**
+     * Unauthorized - Invalid or missing API key
+     */
+    401: unknown;
+    /**
+     * Session not found
+     */
+    404: unknown;
+    /**
+     * Internal server error
+     */
+    500: unknown;
+};
+
+export type ForkSessionResponses = {
+    /**
+     * Session forked successfully
+     */
+    200: ForkSessionResponse;
+};
+
+export type ForkSessionResponse2 = ForkSessionResponses[keyof ForkSessionResponses];
+
+export type PinMessageData = {
+    body: PinMessageRequest;
+    path: {
+        /**
+         * Unique identifier for the session
+         */
+        session_id: string;
+    };
+    query?: never;
+    url: '/sessions/{session_id}/pin';
+};
+
+export type PinMessageErrors = {
+    /**
+     * Bad request - No such message in the agent's context
+     */
+    400: unknown;
+    /**
+     * Unauthorized - Invalid or missing API key
+     */
+    401: unknown;
+    /**
+     * Session not found
+     */
+    404: unknown;
+    /**
+     * Internal server error
+     */
+    500: unknown;
+};
+
+export type PinMessageResponses = {
+    /**
+     * Message pinned or unpinned successfully
+     */
+    200: unknown;
+};
+
 export type StatusData = {
     body?: never;
     path?: never;
@@ -195,7 +195,7 @@ export const ConfigProvider: React.FC<ConfigProviderProps> = ({ children }) => {
           'X-Secret-Key': await window.electron.getSecretKey(),
         },
       });
-      return response.data || [];
+      return response.data?.map((model) => model.name) || [];
     } catch (error) {
       console.error(`Failed to fetch models for provider ${providerName}:`, error);
       return [];
@@ -1,7 +1,8 @@
 import { useCallback, useEffect, useId, useReducer, useRef, useState } from 'react';
 import useSWR from 'swr';
 import { createUserMessage, hasCompletedToolCalls, Message, Role } from '../types/message';
-import { getSessionHistory, SessionMetadata } from '../api';
+import { BudgetExceeded, getSessionHistory, ModelFallback, SessionMetadata } from '../api';
+import { toastInfo } from '../toasts';
 import { ChatState } from '../types/chatState';
 
 let messageIdCounter = 0;
@@ -32,6 +33,8 @@ type MessageEvent =
   | { type: 'Error'; error: string }
   | { type: 'Finish'; reason: string }
   | { type: 'ModelChange'; model: string; mode: string }
+  | { type: 'BudgetExceeded'; exceeded: BudgetExceeded }
+  | { type: 'ModelFallback'; fallback: ModelFallback }
   | NotificationEvent;
 
 export interface UseMessageStreamOptions {
@@ -326,6 +329,26 @@ export function useMessageStream({
                     break;
                   }
 
+                  case 'ModelFallback': {
+                    // The provider switched models mid-session, so let the user know why
+                    const { to_provider, to_model, reason } = parsedEvent.fallback;
+                    toastInfo({
+                      title: `Switched to ${to_provider}/${to_model}`,
+                      msg: reason,
+                    });
+                    setCurrentModelInfo({ model: to_model, mode: 'fallback' });
+                    break;
+                  }
+
+                  case 'BudgetExceeded': {
+                    // The agent stopped cleanly, so report why without failing the stream
+                    const { limit, max, spent } = parsedEvent.exceeded;
+                    setError(
+                      new Error(`Session budget exceeded (${limit}): ${spent} of ${max} used`)
+                    );
+                    break;
+                  }
+
                   case 'Error': {
                     // Always throw the error so it gets caught and sets the error state
                     // This ensures the retry UI appears for ALL errors